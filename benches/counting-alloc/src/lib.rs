@@ -0,0 +1,70 @@
+//! Allocation-counting global allocator for the knishio-client benchmarks
+//!
+//! ```text
+//! #[global_allocator]
+//! static ALLOCATOR: counting_alloc::CountingAllocator = counting_alloc::CountingAllocator;
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// The system allocator, counting allocations and allocated bytes
+pub struct CountingAllocator;
+
+/// Allocations made so far in this process
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllocationCount {
+    /// Calls to `alloc`, `alloc_zeroed` and `realloc`
+    pub allocations: u64,
+    /// Bytes requested by those calls
+    pub bytes: u64,
+}
+
+impl AllocationCount {
+    /// Current totals
+    pub fn now() -> Self {
+        AllocationCount {
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Allocations made since `earlier`
+    pub fn since(earlier: AllocationCount) -> Self {
+        let now = Self::now();
+        AllocationCount {
+            allocations: now.allocations - earlier.allocations,
+            bytes: now.bytes - earlier.bytes,
+        }
+    }
+}
+
+fn record(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED_BYTES.fetch_add(size as u64, Ordering::Relaxed);
+}
+
+// SAFETY: every call is forwarded unchanged to `System`
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
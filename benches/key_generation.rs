@@ -0,0 +1,81 @@
+//! Wallet key generation benchmark
+//!
+//! Times key generation, address derivation and OTS signing with every SHAKE256
+//! backend compiled into this build and reports the speedup over the portable one.
+//!
+//! ```text
+//! cargo bench --bench key_generation
+//! RUSTFLAGS="-C target-cpu=native" cargo bench --features multi-buffer-keccak --bench key_generation
+//! ```
+
+use knishio_client::crypto::{
+    derive_public_digest, generate_key, generate_keys, generate_ots_signature, generate_secret,
+    hash_backend, set_shake_backend, ShakeBackend,
+};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const WARM_UP: Duration = Duration::from_millis(200);
+const MEASURE: Duration = Duration::from_secs(1);
+
+/// Mean time per call of `routine`
+fn measure(mut routine: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    while start.elapsed() < WARM_UP {
+        routine();
+    }
+
+    let mut iterations = 0u32;
+    let start = Instant::now();
+    while start.elapsed() < MEASURE {
+        routine();
+        iterations += 1;
+    }
+    start.elapsed() / iterations
+}
+
+fn main() {
+    let secret = generate_secret("key-generation-bench");
+    let position = "1a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7f809";
+    let key = generate_key(&secret, "USER", position);
+    let molecular_hash = "0123456789abcdefg0123456789abcdefg0123456789abcdefg0123456789abc";
+    let positions: Vec<String> = (0..16).map(|i| format!("{:064x}", i + 1)).collect();
+    let batch: Vec<(&str, &str, &str)> = positions.iter().map(|p| (secret.as_str(), "USER", p.as_str())).collect();
+
+    let backends: Vec<ShakeBackend> = [ShakeBackend::Portable, ShakeBackend::Avx2, ShakeBackend::Neon]
+        .into_iter()
+        .filter(|backend| backend.is_available())
+        .collect();
+
+    let cases: [(&str, &dyn Fn()); 4] = [
+        ("generate_key", &|| {
+            black_box(generate_key(black_box(&secret), "USER", position));
+        }),
+        ("generate_keys x16", &|| {
+            black_box(generate_keys(black_box(&batch)));
+        }),
+        ("derive_public_digest", &|| {
+            black_box(derive_public_digest(black_box(&key)).unwrap());
+        }),
+        ("generate_ots_signature", &|| {
+            black_box(generate_ots_signature(black_box(&key), molecular_hash).unwrap());
+        }),
+    ];
+
+    println!("single-input SHAKE256 backend: {}", hash_backend().name());
+    for (name, routine) in cases {
+        let mut baseline = None;
+        for backend in &backends {
+            set_shake_backend(*backend).unwrap();
+            let mean = measure(routine);
+            let baseline = *baseline.get_or_insert(mean);
+            println!(
+                "{:<24} {:<10} {:>10.1} us  {:>5.2}x",
+                name,
+                backend.to_string(),
+                mean.as_secs_f64() * 1e6,
+                baseline.as_secs_f64() / mean.as_secs_f64()
+            );
+        }
+    }
+}
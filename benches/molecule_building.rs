@@ -0,0 +1,113 @@
+//! Molecule building benchmark
+//!
+//! Times transfer molecule construction and reports the heap allocations each one makes,
+//! counted by the `counting-alloc` global allocator. The "clone" case reproduces the
+//! client's former habit of cloning the source and remainder wallets into the molecule,
+//! as a before figure.
+//!
+//! ```text
+//! cargo bench --bench molecule_building
+//! ```
+
+use counting_alloc::{AllocationCount, CountingAllocator};
+use knishio_client::molecule::{StackableTransferParams, TypeSafeMoleculeBuilder};
+use knishio_client::{Molecule, TokenUnit, Wallet};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const SECRET: &str = "molecule-building-bench";
+const MEASURE: Duration = Duration::from_secs(1);
+
+/// Mean time, allocations and allocated bytes per call of `routine`
+fn measure(mut routine: impl FnMut()) -> (Duration, f64, f64) {
+    routine();
+
+    let before = AllocationCount::now();
+    let mut iterations = 0u32;
+    let start = Instant::now();
+    while start.elapsed() < MEASURE {
+        routine();
+        iterations += 1;
+    }
+    let elapsed = start.elapsed();
+    let allocated = AllocationCount::since(before);
+    let per_call = |total: u64| total as f64 / f64::from(iterations);
+    (elapsed / iterations, per_call(allocated.allocations), per_call(allocated.bytes))
+}
+
+/// Funded source, remainder and recipient wallets for one transfer
+fn wallets(units: usize) -> (Wallet, Wallet, Wallet) {
+    let mut source = Wallet::create(Some(SECRET), None, "TEST", None, None).unwrap();
+    source.set_balance_i128(1000);
+    source.token_units = (0..units)
+        .map(|i| TokenUnit::new(format!("unit-{}", i), format!("Unit {}", i), Default::default()))
+        .collect();
+    let remainder = source.create_remainder(SECRET).unwrap();
+    let recipient = Wallet::create(None, Some("recipient-bundle"), "TEST", None, None).unwrap();
+    (source, remainder, recipient)
+}
+
+fn transfer(source: Wallet, remainder: Wallet, recipient: &Wallet, clone_wallets: bool) -> Molecule {
+    let mut molecule = Molecule::new();
+    if clone_wallets {
+        molecule.source_wallet = Some(source.clone());
+        molecule.remainder_wallet = Some(remainder.clone());
+    } else {
+        molecule.source_wallet = Some(source);
+        molecule.remainder_wallet = Some(remainder);
+    }
+    molecule.init_value(recipient, 100.0).unwrap();
+    molecule
+}
+
+fn builder_transfer(source: Wallet, remainder: Wallet, recipient: &Wallet, units: Vec<String>) {
+    let builder = TypeSafeMoleculeBuilder::new(SECRET)
+        .with_source_wallet(source)
+        .with_remainder_wallet(remainder)
+        .add_stackable_transfer(StackableTransferParams {
+            token: "TEST".to_string(),
+            amount: 100.0,
+            recipient_address: recipient.address.clone().unwrap_or_default(),
+            recipient_position: recipient.position.clone().unwrap_or_default(),
+            recipient_bundle: recipient.bundle.clone(),
+            batch_id: None,
+            units,
+        })
+        .unwrap();
+    black_box(builder.molecule());
+}
+
+fn main() {
+    let cases: [(&str, &dyn Fn()); 4] = [
+        ("init_value (clone)", &|| {
+            let (source, remainder, recipient) = wallets(0);
+            black_box(transfer(source, remainder, &recipient, true));
+        }),
+        ("init_value (move)", &|| {
+            let (source, remainder, recipient) = wallets(0);
+            black_box(transfer(source, remainder, &recipient, false));
+        }),
+        ("builder fungible", &|| {
+            let (source, remainder, recipient) = wallets(0);
+            builder_transfer(source, remainder, &recipient, Vec::new());
+        }),
+        ("builder stackable", &|| {
+            let (source, remainder, recipient) = wallets(4);
+            builder_transfer(source, remainder, &recipient, vec!["unit-0".to_string()]);
+        }),
+    ];
+
+    for (name, routine) in cases {
+        let (mean, allocations, bytes) = measure(routine);
+        println!(
+            "{:<24} {:>10.1} us  {:>8.0} allocations  {:>8.1} KiB",
+            name,
+            mean.as_secs_f64() * 1e6,
+            allocations,
+            bytes / 1024.0
+        );
+    }
+}
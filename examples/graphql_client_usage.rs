@@ -32,6 +32,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         keep_alive_timeout: Duration::from_secs(60),
         tcp_keepalive: Some(Duration::from_secs(30)),
         insecure_tls: false,
+        ..ClientConfig::default()
     };
 
     let retry_config = RetryConfig {
@@ -167,6 +168,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         keep_alive_interval: Some(Duration::from_secs(30)),
         max_reconnect_attempts: Some(5),
         reconnect_delay: Some(Duration::from_secs(2)),
+        ..SocketConfig::default()
     };
 
     let mut ws_client = GraphQLClient::with_socket(
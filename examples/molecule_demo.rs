@@ -1,4 +1,4 @@
-use knishio_client::{Wallet, Molecule, types::MetaItem};
+use knishio_client::{Wallet, Molecule, MoleculeParams, types::MetaItem};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("=== KnishIO Rust SDK - Molecule Demo ===\n");
@@ -17,13 +17,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Create molecule
     println!("\n2. Creating molecule for value transfer...");
-    let mut molecule = Molecule::with_params(
-        Some(secret.to_string()),
-        None,
-        Some(sender_wallet.clone()),
-        None,
-        None,
-        None,
+    let mut molecule = Molecule::from_params(
+        MoleculeParams::new()
+            .secret(secret)
+            .source_wallet(sender_wallet.clone()),
     );
     
     // Initialize value transfer
@@ -82,13 +79,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Create a metadata molecule
     println!("\n8. Creating metadata molecule...");
-    let mut meta_molecule = Molecule::with_params(
-        Some(secret.to_string()),
-        None,
-        Some(sender_wallet),
-        None,
-        None,
-        None,
+    let mut meta_molecule = Molecule::from_params(
+        MoleculeParams::new()
+            .secret(secret)
+            .source_wallet(sender_wallet),
     );
     
     let metadata = vec![
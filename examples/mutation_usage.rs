@@ -4,7 +4,7 @@
 //! Each example shows the complete workflow from setup to execution.
 
 use knishio_client::{
-    KnishIOClient, GraphQLClient, Wallet, Molecule,
+    KnishIOClient, GraphQLClient, Wallet, Molecule, MoleculeParams,
     mutation::{
         Mutation,
         MutationProposeMolecule, MutationCreateWallet, MutationCreateToken,
@@ -85,14 +85,7 @@ async fn example_create_wallet(
     )?;
 
     // Create a molecule for the wallet creation
-    let molecule = Molecule::with_params(
-        Some("test-secret-12345".to_string()),
-        None,
-        None,
-        None,
-        None,
-        None
-    );
+    let molecule = Molecule::from_params(MoleculeParams::new().secret("test-secret-12345"));
 
     // Create the mutation
     let mut mutation = MutationCreateWallet::new(
@@ -148,14 +141,7 @@ async fn example_create_token(
     };
 
     // Create a molecule for token creation
-    let molecule = Molecule::with_params(
-        Some("creator-secret-12345".to_string()),
-        None,
-        None,
-        None,
-        None,
-        None
-    );
+    let molecule = Molecule::from_params(MoleculeParams::new().secret("creator-secret-12345"));
 
     // Create the mutation
     let mut mutation = MutationCreateToken::new(
@@ -211,13 +197,10 @@ async fn example_transfer_tokens(
     };
 
     // Create a molecule for the transfer
-    let molecule = Molecule::with_params(
-        Some("source-secret-12345".to_string()),
-        None,
-        Some(source_wallet.clone()),
-        None,
-        None,
-        None
+    let molecule = Molecule::from_params(
+        MoleculeParams::new()
+            .secret("source-secret-12345")
+            .source_wallet(source_wallet.clone()),
     );
 
     // Create the mutation
@@ -265,14 +248,7 @@ async fn example_request_tokens(
     };
 
     // Create a molecule for the request
-    let molecule = Molecule::with_params(
-        Some("requester-secret-12345".to_string()),
-        None,
-        None,
-        None,
-        None,
-        None
-    );
+    let molecule = Molecule::from_params(MoleculeParams::new().secret("requester-secret-12345"));
 
     // Create the mutation
     let mut mutation = MutationRequestTokens::new(
@@ -310,14 +286,7 @@ async fn example_claim_shadow_wallet(
     };
 
     // Create a molecule for the claim
-    let molecule = Molecule::with_params(
-        Some("claimer-secret-12345".to_string()),
-        None,
-        None,
-        None,
-        None,
-        None
-    );
+    let molecule = Molecule::from_params(MoleculeParams::new().secret("claimer-secret-12345"));
 
     // Create the mutation
     let mut mutation = MutationClaimShadowWallet::new(
@@ -369,14 +338,7 @@ async fn example_request_authorization(
     };
 
     // Create a molecule for the authorization request
-    let molecule = Molecule::with_params(
-        Some("user-secret-12345".to_string()),
-        None,
-        None,
-        None,
-        None,
-        None
-    );
+    let molecule = Molecule::from_params(MoleculeParams::new().secret("user-secret-12345"));
 
     // Create the mutation
     let mut mutation = MutationRequestAuthorization::new(
@@ -8,7 +8,8 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use crate::crypto::{shake256, shake256_incremental, hex_to_base17};
 use crate::error::KnishIOError;
-use crate::types::{Isotope, MetaItem};
+use crate::types::{atom_value, Isotope, MetaItem, ValueString};
+use crate::utils::canonical_json;
 
 /// Represents a single atomic operation within a molecular transaction
 ///
@@ -32,7 +33,10 @@ pub struct Atom {
     pub token: String,
     
     /// Value for the operation (optional, stored as string)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    ///
+    /// Values this SDK creates are canonical (`ValueString`); received strings are kept
+    /// as signed, and JSON numbers are canonicalized.
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "crate::types::deserialize_atom_value")]
     pub value: Option<String>,
     
     /// Batch ID for grouping related operations (optional)
@@ -62,8 +66,10 @@ pub struct Atom {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub index: Option<u32>,
     
-    /// Creation timestamp (automatically set)
-    #[serde(rename = "createdAt")]
+    /// Creation timestamp (automatically set), milliseconds since the Unix epoch
+    ///
+    /// Hashed as this string; read it with `created_at_millis` or `created_at_datetime`.
+    #[serde(rename = "createdAt", deserialize_with = "crate::types::deserialize_created_at")]
     pub created_at: String,
     
     /// Version identifier (optional)
@@ -122,6 +128,20 @@ impl Atom {
         }
     }
     
+    /// Creation time in milliseconds since the Unix epoch
+    ///
+    /// # Errors
+    ///
+    /// `InvalidTimestamp` when `created_at` is not a timestamp `parse_created_at` reads
+    pub fn created_at_millis(&self) -> crate::error::Result<i64> {
+        crate::types::created_at_millis(&self.created_at)
+    }
+
+    /// Creation time as a date, see `created_at_millis`
+    pub fn created_at_datetime(&self) -> crate::error::Result<chrono::DateTime<chrono::Utc>> {
+        crate::types::parse_created_at(&self.created_at)
+    }
+
     /// Create an Atom using the builder pattern (matches JS Atom.create)
     ///
     /// # Arguments
@@ -145,7 +165,7 @@ impl Atom {
             wallet_address: params.wallet_address.unwrap_or_default(),
             isotope: params.isotope,
             token: params.token.unwrap_or_default(),
-            value: params.value.map(|v| v.to_string()),
+            value: params.value.map(ValueString::canonical_f64),
             batch_id: params.batch_id,
             meta_type: params.meta_type,
             meta_id: params.meta_id,
@@ -245,10 +265,7 @@ impl Atom {
             // 2. Sort keys alphabetically
             // 3. Wrap each key-value as a single-key object in an array
             // 4. Recursively structure nested objects (meta items)
-            let atom_views: Vec<serde_json::Value> = sorted_atoms.iter()
-                .map(Self::structure_atom_v4)
-                .collect();
-            shake256(&serde_json::to_string(&atom_views)?, 256)
+            shake256(&Self::structured_json(&sorted_atoms), 256)
         } else {
             // JavaScript legacy hashing: exact match pattern
             let num_atoms = atoms.len().to_string();  // Use original length, not sorted
@@ -287,6 +304,23 @@ impl Atom {
         }
     }
     
+    /// Canonical JSON of the Version4 structured view of `atoms`
+    ///
+    /// This is the exact byte sequence `hash_atoms` hashes when every atom carries a
+    /// version (atoms are sorted by index first). Unversioned atoms are hashed from their
+    /// concatenated property values instead, so for them this is informational only.
+    pub fn canonical_json(atoms: &[Atom]) -> String {
+        Self::structured_json(&Self::sort_atoms(atoms))
+    }
+
+    /// Canonical JSON of the structured views of already sorted atoms
+    fn structured_json(sorted_atoms: &[Atom]) -> String {
+        let atom_views: Vec<serde_json::Value> = sorted_atoms.iter()
+            .map(Self::structure_atom_v4)
+            .collect();
+        canonical_json(&serde_json::Value::Array(atom_views))
+    }
+
     /// Produce the Version4 structured view for a single atom.
     ///
     /// Replicates the SDK's HashAtom.structure() algorithm:
@@ -351,16 +385,9 @@ impl Atom {
     /// Vector of atoms sorted by index
     pub fn sort_atoms(atoms: &[Atom]) -> Vec<Atom> {
         let mut sorted = atoms.to_vec();
-        // JavaScript: first.index < second.index ? -1 : 1
-        sorted.sort_by(|first, second| {
-            let first_index = first.index.unwrap_or(0);
-            let second_index = second.index.unwrap_or(0);
-            if first_index < second_index {
-                std::cmp::Ordering::Less
-            } else {
-                std::cmp::Ordering::Greater
-            }
-        });
+        // JavaScript: first.index < second.index ? -1 : 1. That comparator is not a total
+        // order on ties, so sort stably by key instead: equal indices keep their input order
+        sorted.sort_by_key(|atom| atom.index.unwrap_or(0));
         sorted
     }
     
@@ -529,8 +556,8 @@ impl Atom {
         let mut atom = Atom::new(position, wallet_address, isotope, token);
 
         // Set optional properties
-        if let Some(value) = json.get("value").and_then(|v| v.as_str()) {
-            atom.value = Some(value.to_string());
+        if let Some(value) = json.get("value") {
+            atom.value = atom_value(value)?;
         }
 
         if let Some(batch_id) = json.get("batchId").and_then(|v| v.as_str()) {
@@ -577,8 +604,8 @@ impl Atom {
             atom.ots_fragment = Some(ots_fragment.to_string());
         }
 
-        if let Some(created_at) = json.get("createdAt").and_then(|v| v.as_str()) {
-            atom.created_at = created_at.to_string();
+        if let Some(created_at) = json.get("createdAt").filter(|v| !v.is_null()) {
+            atom.created_at = crate::types::created_at_from_json(created_at);
         }
 
         Ok(atom)
@@ -608,7 +635,7 @@ impl Atom {
         meta_id: Option<&str>,
         meta: Option<Vec<MetaItem>>,
     ) -> Self {
-        self.value = value.map(|v| v.to_string());
+        self.value = value.map(ValueString::canonical_f64);
         self.batch_id = batch_id.map(|b| b.to_string());
         self.meta_type = meta_type.map(|mt| mt.to_string());
         self.meta_id = meta_id.map(|mi| mi.to_string());
@@ -942,6 +969,17 @@ mod tests {
         assert_eq!(sorted[0].index, Some(1));
         assert_eq!(sorted[1].index, Some(2));
         assert_eq!(sorted[2].index, Some(3));
+
+        // Equal indices keep their input order
+        let tied = vec![atom_at("first", 1), atom_at("zero", 0), atom_at("second", 1)];
+        let positions: Vec<_> = Atom::sort_atoms(&tied).into_iter().map(|atom| atom.position).collect();
+        assert_eq!(positions, ["zero", "first", "second"]);
+    }
+
+    fn atom_at(position: &str, index: u32) -> Atom {
+        let mut atom = Atom::new(position, "addr", Isotope::V, "TEST");
+        atom.index = Some(index);
+        atom
     }
     
     #[test]
@@ -1004,6 +1042,37 @@ mod tests {
         assert_eq!(atom.isotope, Isotope::V);
         assert_eq!(atom.token, "TEST");
         assert_eq!(atom.value, Some("100".to_string()));
+        assert_eq!(atom.created_at_millis().unwrap(), 1_640_995_200_000);
+        assert_eq!(atom.created_at_datetime().unwrap().to_rfc3339(), "2022-01-01T00:00:00+00:00");
+
+        // The timestamp is hashed as received and checked only when read
+        let undated = Atom::json_to_object(&json.replace("1640995200000", "2022-01-01")).unwrap();
+        assert_eq!(undated.created_at, "2022-01-01");
+        assert!(undated.created_at_millis().is_err());
+        let atom = Atom::json_to_object(&json.replace(r#""1640995200000""#, "1640995200000")).unwrap();
+        assert_eq!(atom.created_at, "1640995200000");
+    }
+
+    #[test]
+    fn test_value_canonical_form() {
+        // Created values are canonical
+        let atom = Atom::create(AtomCreateParams { isotope: Isotope::V, value: Some(-0.0), ..Default::default() });
+        assert_eq!(atom.value.as_deref(), Some("0"));
+        let atom = Atom::new("pos", "addr", Isotope::V, "TEST").with_optional_fields(Some(50.0), None, None, None, None);
+        assert_eq!(atom.value.as_deref(), Some("50"));
+
+        // Received strings are kept as signed; JSON numbers are canonicalized
+        let json = |value: &str| format!(r#"{{"position":"p","walletAddress":"a","isotope":"V","token":"T","value":{},"meta":[],"createdAt":"1"}}"#, value);
+        assert_eq!(Atom::json_to_object(&json(r#""50.0""#)).unwrap().value.as_deref(), Some("50.0"));
+        assert_eq!(Atom::json_to_object(&json("50.0")).unwrap().value.as_deref(), Some("50"));
+        assert_eq!(Atom::json_to_object(&json("null")).unwrap().value, None);
+        assert!(Atom::json_to_object(&json("true")).is_err());
+
+        let value: serde_json::Value = serde_json::from_str(&json("-2.50")).unwrap();
+        let atom = Atom::from_json(&value, crate::types::AtomFromJsonOptions::default()).unwrap();
+        assert_eq!(atom.value.as_deref(), Some("-2.5"));
+        let reparsed = Atom::json_to_object(&serde_json::to_string(&atom).unwrap()).unwrap();
+        assert_eq!(reparsed.value, atom.value);
     }
     
     #[test]
@@ -1036,6 +1105,30 @@ mod tests {
         assert_eq!(hash_base17.len(), 64);
     }
     
+    #[test]
+    fn test_canonical_json_is_versioned_hash_input() {
+        let mut atom1 = Atom::new("pos1", "addr1", Isotope::M, "USER");
+        atom1.index = Some(1);
+        atom1.version = Some("4".to_string());
+        atom1.meta_type = Some("profile".to_string());
+        atom1.meta = vec![MetaItem::new("name", "Zoë \"Z\"")];
+
+        let mut atom0 = Atom::new("pos0", "addr0", Isotope::V, "TEST");
+        atom0.index = Some(0);
+        atom0.version = Some("4".to_string());
+        atom0.value = Some("-5".to_string());
+
+        let atoms = vec![atom1, atom0];
+        let canonical = Atom::canonical_json(&atoms);
+
+        // Sorted by index, one single-key object per property, no whitespace
+        assert!(canonical.starts_with(r#"[[{"batchId":null},{"createdAt":"#));
+        assert!(canonical.contains(r#"{"isotope":"V"}"#));
+        assert!(canonical.find(r#""addr0""#) < canonical.find(r#""addr1""#));
+        assert!(canonical.contains(r#"[{"key":"name"},{"value":"Zoë \"Z\""}]"#));
+        assert_eq!(Atom::hash_atoms(&atoms, "hex").unwrap(), shake256(&canonical, 256));
+    }
+
     #[test]
     fn test_hex_to_base17() {
         let hex = "0123456789abcdef";
@@ -0,0 +1,132 @@
+//! Guest session fingerprints
+//!
+//! Guest auth tokens are signed by an AUTH wallet derived from a device fingerprint
+//! (JS: `generateSecret(await this.getFingerprint())`, where the browser SDK uses a
+//! browser fingerprint). A `Fingerprint` supplies that identifier; each device or
+//! installation should produce its own, stable value so guests do not share a wallet.
+
+use crate::crypto::shake256;
+use crate::error::{KnishIOError, Result};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Source of a stable identifier for the current device or installation
+///
+/// Closures returning `Result<String>` implement this trait, so callers can plug in
+/// their own scheme without a new type.
+pub trait Fingerprint: Send + Sync {
+    /// Identifier of this device or installation
+    fn fingerprint(&self) -> Result<String>;
+}
+
+impl<F> Fingerprint for F
+where
+    F: Fn() -> Result<String> + Send + Sync,
+{
+    fn fingerprint(&self) -> Result<String> {
+        self()
+    }
+}
+
+/// Locations of the OS machine ID on Linux and the BSDs
+const MACHINE_ID_PATHS: [&str; 3] = ["/etc/machine-id", "/var/lib/dbus/machine-id", "/etc/hostid"];
+
+/// Fingerprint derived from the operating system's machine ID
+///
+/// The raw machine ID is never returned: it is hashed together with an SDK-specific
+/// prefix, as the machine-id documentation asks applications to do.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MachineIdFingerprint;
+
+impl Fingerprint for MachineIdFingerprint {
+    fn fingerprint(&self) -> Result<String> {
+        let machine_id = MACHINE_ID_PATHS.iter()
+            .filter_map(|path| std::fs::read_to_string(path).ok())
+            .map(|id| id.trim().to_string())
+            .find(|id| !id.is_empty())
+            .ok_or_else(|| KnishIOError::ConfigurationError("No machine ID available on this system".to_string()))?;
+
+        Ok(shake256(&format!("knishio-guest:{}", machine_id), 256))
+    }
+}
+
+/// Random identifier generated on first use and kept in a file
+///
+/// Survives restarts as long as the file does, which makes it suitable for containers
+/// and platforms without a machine ID.
+#[derive(Debug, Clone)]
+pub struct PersistentIdFingerprint {
+    path: PathBuf,
+}
+
+impl PersistentIdFingerprint {
+    /// Keep the identifier at `path` (parent directories are created as needed)
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        PersistentIdFingerprint { path: path.as_ref().to_path_buf() }
+    }
+
+    /// File the identifier is kept in
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Fingerprint for PersistentIdFingerprint {
+    fn fingerprint(&self) -> Result<String> {
+        if let Ok(existing) = std::fs::read_to_string(&self.path) {
+            let existing = existing.trim();
+            if !existing.is_empty() {
+                return Ok(existing.to_string());
+            }
+        }
+
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, &id)?;
+        Ok(id)
+    }
+}
+
+/// Default fingerprint: the machine ID, or a random identifier kept for the life of
+/// the process where no machine ID exists
+#[derive(Debug, Default)]
+pub struct DefaultFingerprint {
+    fallback: OnceLock<String>,
+}
+
+impl Fingerprint for DefaultFingerprint {
+    fn fingerprint(&self) -> Result<String> {
+        MachineIdFingerprint.fingerprint().or_else(|_| {
+            Ok(self.fallback.get_or_init(|| uuid::Uuid::new_v4().simple().to_string()).clone())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_persistent_id_survives_reload() {
+        let dir = std::env::temp_dir().join(format!("knishio-fingerprint-{}", uuid::Uuid::new_v4().simple()));
+        let path = dir.join("nested").join("guest-id");
+
+        let first = PersistentIdFingerprint::new(&path).fingerprint().unwrap();
+        let second = PersistentIdFingerprint::new(&path).fingerprint().unwrap();
+        assert_eq!(first, second);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), first);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_default_and_custom_fingerprints() {
+        let default = DefaultFingerprint::default();
+        assert_eq!(default.fingerprint().unwrap(), default.fingerprint().unwrap());
+
+        let custom: Box<dyn Fingerprint> = Box::new(|| Ok("device-42".to_string()));
+        assert_eq!(custom.fingerprint().unwrap(), "device-42");
+    }
+}
@@ -3,9 +3,60 @@
 //! This module handles authentication tokens for API access.
 //! Maintains exact compatibility with JavaScript AuthToken.js implementation.
 
+pub mod fingerprint;
+pub mod sealed;
+pub mod secret_source;
+pub mod store;
+
+pub use fingerprint::{DefaultFingerprint, Fingerprint, MachineIdFingerprint, PersistentIdFingerprint};
+pub use sealed::{
+    SealedSnapshot, SnapshotKey, MAX_SNAPSHOT_KDF_ITERATIONS, MIN_SNAPSHOT_KDF_ITERATIONS, SNAPSHOT_FORMAT_VERSION,
+    SNAPSHOT_KDF_ITERATIONS,
+};
+pub use secret_source::{SecretCallback, SecretSource};
+pub use store::{AuthKey, AuthTokenStore, DEFAULT_AUTH_STORE_CAPACITY};
+
 use serde::{Deserialize, Serialize};
-use crate::wallet::Wallet;
+use std::sync::Arc;
+use crate::utils::clock::{system_clock, Clock};
+use crate::wallet::{Wallet, WalletParams};
 use crate::error::Result;
+use crate::types::SystemTokens;
+
+/// What an auth token permits
+///
+/// Guest tokens come from an anonymous AUTH wallet and only cover reads; profile tokens
+/// are issued to a user's bundle and also cover molecules signed with their secret.
+/// Scopes are ordered: a profile token satisfies anything a guest token does.
+///
+/// Only the guest handshake marks a token guest. Tokens set by hand or restored from
+/// snapshots that predate scopes default to profile, so local checks refuse only what the
+/// node issued as a guest session and leave the rest to the node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthScope {
+    /// Anonymous session
+    Guest,
+    /// Session authenticated as a user's bundle
+    #[default]
+    Profile,
+}
+
+impl AuthScope {
+    /// True if a token of this scope may perform operations requiring `required`
+    pub fn satisfies(self, required: AuthScope) -> bool {
+        self >= required
+    }
+}
+
+impl std::fmt::Display for AuthScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            AuthScope::Guest => "guest",
+            AuthScope::Profile => "profile",
+        })
+    }
+}
 
 /// Snapshot structure for token restoration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +66,9 @@ pub struct AuthTokenSnapshot {
     pub pubkey: Option<String>,
     pub encrypt: Option<bool>,
     pub wallet: WalletSnapshot,
+    /// Snapshots saved before scopes existed restore as profile tokens
+    #[serde(default)]
+    pub scope: AuthScope,
 }
 
 /// Wallet snapshot for auth token
@@ -40,6 +94,9 @@ pub struct AuthToken {
     pubkey: Option<String>,
     encrypt: Option<bool>,
     wallet: Option<Wallet>,
+    scope: AuthScope,
+    /// Time source of expiry checks
+    clock: Arc<dyn Clock>,
 }
 
 impl AuthToken {
@@ -48,7 +105,7 @@ impl AuthToken {
     /// # Arguments
     ///
     /// * `token` - Authentication token string
-    /// * `expires_at` - Expiration timestamp (optional)
+    /// * `expires_at` - Expiration as a Unix timestamp in seconds (optional)
     /// * `encrypt` - Whether encryption is enabled (optional)
     /// * `pubkey` - Public key for encryption (optional)
     pub fn new(
@@ -63,6 +120,8 @@ impl AuthToken {
             pubkey,
             encrypt,
             wallet: None,
+            scope: AuthScope::default(),
+            clock: system_clock(),
         }
     }
     
@@ -71,7 +130,7 @@ impl AuthToken {
     /// # Arguments
     ///
     /// * `token` - Authentication token string
-    /// * `expires_at` - Expiration timestamp (optional)
+    /// * `expires_at` - Expiration as a Unix timestamp in seconds (optional)
     /// * `encrypt` - Whether encryption is enabled (optional)
     /// * `pubkey` - Public key for encryption (optional)
     /// * `wallet` - Associated wallet
@@ -102,15 +161,16 @@ impl AuthToken {
     ///
     /// Result containing restored AuthToken
     pub fn restore(snapshot: AuthTokenSnapshot, secret: &str) -> Result<Self> {
-        let wallet = Wallet::new(
-            Some(secret),
-            None,
-            Some("AUTH"),
-            None,
-            snapshot.wallet.position.as_deref(),
-            None,
-            snapshot.wallet.characters.as_deref(),
-        )?;
+        Self::restore_with_tokens(snapshot, secret, &SystemTokens::default())
+    }
+
+    /// Restore AuthToken from snapshot and secret, with the wallet on `tokens.auth`
+    pub fn restore_with_tokens(snapshot: AuthTokenSnapshot, secret: &str, tokens: &SystemTokens) -> Result<Self> {
+        let wallet = Wallet::from_params(WalletParams {
+            position: snapshot.wallet.position.clone(),
+            characters: snapshot.wallet.characters.clone(),
+            ..WalletParams::new().secret(secret).token(tokens.auth.as_str())
+        })?;
         
         Ok(Self::create(
             snapshot.token,
@@ -118,9 +178,31 @@ impl AuthToken {
             snapshot.encrypt,
             snapshot.pubkey,
             wallet,
-        ))
+        ).with_scope(snapshot.scope))
     }
     
+    /// Mark the token as carrying `scope`
+    pub fn with_scope(mut self, scope: AuthScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// What this token permits (tokens are profile-scoped unless marked guest)
+    pub fn scope(&self) -> AuthScope {
+        self.scope
+    }
+
+    /// Judge expiry against `clock` instead of the wall clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Replace the clock expiry is judged against
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
     /// Set associated wallet (matches JS setWallet)
     ///
     /// # Arguments
@@ -172,6 +254,7 @@ impl AuthToken {
             pubkey: self.pubkey.clone(),
             encrypt: self.encrypt,
             wallet: wallet_snapshot,
+            scope: self.scope,
         }
     }
     
@@ -200,7 +283,7 @@ impl AuthToken {
     /// Milliseconds until expiration (negative if expired)
     pub fn get_expire_interval(&self) -> i64 {
         if let Some(expires_at) = self.expires_at {
-            (expires_at * 1000) - chrono::Utc::now().timestamp_millis()
+            (expires_at * 1000) - self.clock.now_millis()
         } else {
             0
         }
@@ -278,6 +361,8 @@ impl<'de> Deserialize<'de> for AuthToken {
             pubkey: snapshot.pubkey,
             encrypt: snapshot.encrypt,
             wallet: None, // Wallet must be restored separately with secret
+            scope: snapshot.scope,
+            clock: system_clock(),
         })
     }
 }
@@ -300,6 +385,25 @@ mod tests {
         assert!(auth_token.is_expired()); // Should be expired by now
     }
     
+    #[test]
+    fn test_auth_scope() {
+        assert!(AuthScope::Profile.satisfies(AuthScope::Guest));
+        assert!(!AuthScope::Guest.satisfies(AuthScope::Profile));
+
+        let token = AuthToken::new("t".to_string(), None, None, None);
+        assert_eq!(token.scope(), AuthScope::Profile);
+
+        let token = token.with_scope(AuthScope::Guest);
+        let json = serde_json::to_value(&token).unwrap();
+        assert_eq!(json["scope"], "guest");
+        assert_eq!(serde_json::from_value::<AuthToken>(json).unwrap().scope(), AuthScope::Guest);
+
+        // Snapshots from before scopes existed restore as profile
+        let legacy = serde_json::json!({"token": "t", "expires_at": null, "pubkey": null, "encrypt": null,
+            "wallet": {"position": null, "characters": null}});
+        assert_eq!(serde_json::from_value::<AuthToken>(legacy).unwrap().scope(), AuthScope::Profile);
+    }
+
     #[test]
     fn test_auth_token_with_wallet() {
         let wallet = Wallet::create(
@@ -399,4 +503,20 @@ mod tests {
         assert!(auth_token_expired.is_expired());
         assert!(auth_token_expired.get_expire_interval() < 0);
     }
+
+    #[test]
+    fn test_expiration_follows_clock() {
+        let clock = crate::utils::ManualClock::new(1_700_000_000_000);
+        let auth_token = AuthToken::new("test-token".to_string(), Some(1_700_000_060), None, None)
+            .with_clock(Arc::new(clock.clone()));
+
+        assert_eq!(auth_token.get_expire_interval(), 60_000);
+        assert!(!auth_token.is_expired());
+
+        clock.advance(std::time::Duration::from_secs(59));
+        assert!(!auth_token.is_expired());
+        clock.advance(std::time::Duration::from_secs(2));
+        assert!(auth_token.is_expired());
+        assert_eq!(auth_token.get_expire_interval(), -1_000);
+    }
 }
\ No newline at end of file
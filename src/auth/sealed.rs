@@ -0,0 +1,275 @@
+//! Encrypted auth token snapshots
+//!
+//! A saved snapshot holds the AUTH wallet's position, so anyone reading it could rebuild
+//! the session key together with the secret, and the token itself grants the session.
+//! `save_auth_token` therefore seals the snapshot with AES-256-GCM before returning it,
+//! and `load_auth_token` refuses anything it cannot open.
+//!
+//! The key is either derived from the user secret (PBKDF2-HMAC-SHA256 with a random
+//! salt) or supplied directly by the caller. The envelope records its format version,
+//! so later formats can be read alongside this one:
+//!
+//! - version 1: the plain `AuthTokenSnapshot` JSON written by earlier releases (no longer
+//!   accepted; log in again and re-save)
+//! - version 2: `SealedSnapshot`
+
+use super::AuthTokenSnapshot;
+use crate::error::{KnishIOError, Result};
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use base64::Engine as _;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+/// Format version written by `AuthTokenSnapshot::seal`
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 2;
+
+/// PBKDF2 iterations used when sealing with the user secret
+pub const SNAPSHOT_KDF_ITERATIONS: u32 = 100_000;
+
+/// Fewest PBKDF2 iterations an envelope may ask for
+pub const MIN_SNAPSHOT_KDF_ITERATIONS: u32 = 10_000;
+
+/// Most PBKDF2 iterations an envelope may ask for, so a crafted one cannot stall `open`
+pub const MAX_SNAPSHOT_KDF_ITERATIONS: u32 = 1_000_000;
+
+const KDF_PBKDF2: &str = "pbkdf2-sha256";
+const KDF_RAW: &str = "raw";
+
+/// Key a snapshot is sealed with
+#[derive(Clone)]
+pub enum SnapshotKey {
+    /// Derive the key from the user secret
+    Secret(String),
+    /// Use a caller-managed 256-bit key, e.g. from a platform keychain
+    Key([u8; 32]),
+}
+
+impl std::fmt::Debug for SnapshotKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SnapshotKey::Secret(_) => "SnapshotKey::Secret(..)",
+            SnapshotKey::Key(_) => "SnapshotKey::Key(..)",
+        })
+    }
+}
+
+/// Encrypted `AuthTokenSnapshot` as stored
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SealedSnapshot {
+    /// Format version (`SNAPSHOT_FORMAT_VERSION`)
+    pub version: u32,
+    /// `pbkdf2-sha256` for secret-derived keys, `raw` for provided keys
+    pub kdf: String,
+    /// PBKDF2 iterations (0 for raw keys)
+    pub iterations: u32,
+    /// Hex PBKDF2 salt (empty for raw keys)
+    pub salt: String,
+    /// Hex AES-GCM nonce
+    pub nonce: String,
+    /// Base64 ciphertext of the snapshot JSON, including the GCM tag
+    pub ciphertext: String,
+}
+
+impl AuthTokenSnapshot {
+    /// Encrypt this snapshot under `key`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use knishio_client::auth::{AuthToken, SnapshotKey};
+    ///
+    /// let token = AuthToken::new("token".to_string(), Some(1_900_000_000), None, None);
+    /// let key = SnapshotKey::Key([7u8; 32]);
+    ///
+    /// let sealed = token.get_snapshot().seal(&key).unwrap();
+    /// assert_eq!(sealed.open(&key).unwrap().token, "token");
+    /// assert!(sealed.open(&SnapshotKey::Key([8u8; 32])).is_err());
+    /// ```
+    pub fn seal(&self, key: &SnapshotKey) -> Result<SealedSnapshot> {
+        SealedSnapshot::seal_bytes(&serde_json::to_vec(self)?, key, &aad(SNAPSHOT_FORMAT_VERSION))
+    }
+}
+
+impl SealedSnapshot {
+    /// Parse stored snapshot data, checking its format version
+    ///
+    /// # Errors
+    ///
+    /// Plain version 1 snapshots and unknown versions are refused with a message saying so
+    pub fn parse(data: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(data)?;
+        match value.get("version").and_then(serde_json::Value::as_u64) {
+            Some(version) if version == u64::from(SNAPSHOT_FORMAT_VERSION) => Ok(serde_json::from_value(value)?),
+            Some(version) => Err(KnishIOError::custom(format!("Unsupported auth token snapshot version {}", version))),
+            None if value.get("token").is_some() => Err(KnishIOError::custom(
+                "Unencrypted auth token snapshot (version 1) is no longer accepted; log in and save the token again",
+            )),
+            None => Err(KnishIOError::custom("Auth token snapshot has no format version")),
+        }
+    }
+
+    /// Decrypt the snapshot with `key`
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecryptionKey` if `key` is not the one the snapshot was sealed with or the
+    /// envelope was altered
+    pub fn open(&self, key: &SnapshotKey) -> Result<AuthTokenSnapshot> {
+        let plaintext = self.open_bytes(key, &aad(self.version))?;
+        serde_json::from_slice(&plaintext).map_err(|_| KnishIOError::DecryptionKey)
+    }
+
+    /// Encrypt `plaintext` under `key`, authenticating `aad` along with it
+    ///
+    /// `aad` names what the envelope holds, so one kind of sealed data cannot be opened
+    /// as another.
+    pub(crate) fn seal_bytes(plaintext: &[u8], key: &SnapshotKey, aad: &[u8]) -> Result<SealedSnapshot> {
+        let (kdf, iterations, salt) = match key {
+            SnapshotKey::Secret(_) => {
+                let mut salt = [0u8; 16];
+                rand::rng().fill_bytes(&mut salt);
+                (KDF_PBKDF2, SNAPSHOT_KDF_ITERATIONS, salt.to_vec())
+            }
+            SnapshotKey::Key(_) => (KDF_RAW, 0, Vec::new()),
+        };
+        let cipher = cipher_for(key, kdf, iterations, &salt)?;
+
+        let mut nonce = [0u8; 12];
+        rand::rng().fill_bytes(&mut nonce);
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+            .map_err(|_| KnishIOError::EncryptionError)?;
+
+        Ok(SealedSnapshot {
+            version: SNAPSHOT_FORMAT_VERSION,
+            kdf: kdf.to_string(),
+            iterations,
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: base64::engine::general_purpose::STANDARD.encode(ciphertext),
+        })
+    }
+
+    /// Decrypt the envelope with `key`, checking the `aad` it was sealed with
+    pub(crate) fn open_bytes(&self, key: &SnapshotKey, aad: &[u8]) -> Result<Vec<u8>> {
+        if self.version != SNAPSHOT_FORMAT_VERSION {
+            return Err(KnishIOError::custom(format!("Unsupported auth token snapshot version {}", self.version)));
+        }
+        let salt = hex::decode(&self.salt).map_err(|_| KnishIOError::DecryptionKey)?;
+        let nonce = hex::decode(&self.nonce).map_err(|_| KnishIOError::DecryptionKey)?;
+        if nonce.len() != 12 {
+            return Err(KnishIOError::DecryptionKey);
+        }
+        let ciphertext = base64::engine::general_purpose::STANDARD
+            .decode(&self.ciphertext)
+            .map_err(|_| KnishIOError::DecryptionKey)?;
+
+        let cipher = cipher_for(key, &self.kdf, self.iterations, &salt)?;
+        cipher
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad })
+            .map_err(|_| KnishIOError::DecryptionKey)
+    }
+}
+
+/// Cipher for `key`, checking it matches the envelope's key derivation
+///
+/// The iteration count comes from the envelope, so it is bounded before any key is derived.
+fn cipher_for(key: &SnapshotKey, kdf: &str, iterations: u32, salt: &[u8]) -> Result<Aes256Gcm> {
+    let mut bytes = [0u8; 32];
+    match (key, kdf) {
+        (SnapshotKey::Secret(_), KDF_PBKDF2)
+            if !(MIN_SNAPSHOT_KDF_ITERATIONS..=MAX_SNAPSHOT_KDF_ITERATIONS).contains(&iterations) =>
+        {
+            return Err(KnishIOError::custom(format!(
+                "Auth token snapshot asks for {} PBKDF2 iterations, outside {}..={}",
+                iterations, MIN_SNAPSHOT_KDF_ITERATIONS, MAX_SNAPSHOT_KDF_ITERATIONS
+            )));
+        }
+        (SnapshotKey::Secret(secret), KDF_PBKDF2) if !salt.is_empty() => {
+            pbkdf2::pbkdf2_hmac::<sha2::Sha256>(secret.as_bytes(), salt, iterations, &mut bytes);
+        }
+        (SnapshotKey::Key(raw), KDF_RAW) => bytes = *raw,
+        _ => return Err(KnishIOError::DecryptionKey),
+    }
+    Aes256Gcm::new_from_slice(&bytes).map_err(|_| KnishIOError::InvalidKey)
+}
+
+/// Associated data binding the ciphertext to its format version
+fn aad(version: u32) -> Vec<u8> {
+    format!("KnishIO auth token snapshot v{}", version).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{AuthScope, AuthToken};
+    use crate::wallet::Wallet;
+
+    #[test]
+    fn test_sealed_snapshot() {
+        let secret = "a".repeat(2048);
+        let wallet = Wallet::create(Some(&secret), None, "AUTH", None, None).unwrap();
+        let token = AuthToken::create("session".to_string(), Some(1_900_000_000), None, None, wallet)
+            .with_scope(AuthScope::Profile);
+        let snapshot = token.get_snapshot();
+
+        let key = SnapshotKey::Secret(secret.clone());
+        let stored = serde_json::to_string(&snapshot.seal(&key).unwrap()).unwrap();
+        assert!(!stored.contains("session"));
+        assert!(!stored.contains(snapshot.wallet.position.as_deref().unwrap()));
+
+        let sealed = SealedSnapshot::parse(&stored).unwrap();
+        let opened = sealed.open(&key).unwrap();
+        assert_eq!(opened.token, "session");
+        assert_eq!(opened.wallet.position, snapshot.wallet.position);
+        assert_eq!(opened.scope, AuthScope::Profile);
+
+        // Wrong secret, wrong kind of key, tampering
+        assert!(matches!(sealed.open(&SnapshotKey::Secret("b".repeat(2048))), Err(KnishIOError::DecryptionKey)));
+        assert!(matches!(sealed.open(&SnapshotKey::Key([0u8; 32])), Err(KnishIOError::DecryptionKey)));
+        let mut tampered = sealed.clone();
+        tampered.iterations -= 1;
+        assert!(tampered.open(&key).is_err());
+
+        // Iteration counts outside the bounds are refused before deriving anything
+        for iterations in [0, MIN_SNAPSHOT_KDF_ITERATIONS - 1, MAX_SNAPSHOT_KDF_ITERATIONS + 1, u32::MAX] {
+            let mut crafted = sealed.clone();
+            crafted.iterations = iterations;
+            let error = crafted.open(&key).unwrap_err();
+            assert!(error.to_string().contains("PBKDF2 iterations"), "{}: {}", iterations, error);
+        }
+
+        // Version 1 plaintext and unknown versions
+        let plain = serde_json::to_string(&snapshot).unwrap();
+        assert!(SealedSnapshot::parse(&plain).unwrap_err().to_string().contains("version 1"));
+        let future = stored.replace("\"version\":2", "\"version\":3");
+        assert!(SealedSnapshot::parse(&future).unwrap_err().to_string().contains("version 3"));
+    }
+
+    #[test]
+    fn test_client_save_and_load() {
+        use crate::client::KnishIOClient;
+
+        let secret = "c".repeat(2048);
+        let wallet = Wallet::create(Some(&secret), None, "AUTH", None, None).unwrap();
+        let mut client = KnishIOClient::new("http://localhost:8000/graphql", None, None, None, None, Some(false));
+        assert!(client.save_auth_token("session").is_err());
+        client.set_secret(secret.clone());
+        client.set_auth_token(AuthToken::create("session".to_string(), Some(1_900_000_000), None, None, wallet));
+
+        let saved = client.save_auth_token("session").unwrap();
+        assert_eq!(client.load_auth_token("session", &saved).unwrap().get_token(), "session");
+
+        let provided = SnapshotKey::Key([9u8; 32]);
+        let saved = client.save_auth_token_with_key("session", &provided).unwrap();
+        assert!(client.load_auth_token("session", &saved).is_err());
+        assert!(client.load_auth_token_with_key("session", &saved, &provided).is_ok());
+
+        client.set_secret("d".repeat(2048));
+        let saved = client.save_auth_token("session").unwrap();
+        client.set_secret(secret);
+        assert!(matches!(client.load_auth_token("session", &saved), Err(KnishIOError::DecryptionKey)));
+    }
+}
@@ -0,0 +1,185 @@
+//! Where a client's secret comes from
+//!
+//! Passing the secret around as a string literal spreads it through application code,
+//! config structs and logs. A `SecretSource` names where to fetch it instead, and
+//! `ClientBuilder::secret_source` resolves it once, when the client is built.
+//!
+//! Secret files must not be readable by group or others on Unix; a file readable by
+//! anyone but its owner is refused rather than silently trusted.
+
+use crate::error::{KnishIOError, Result};
+use std::fmt;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Function producing a secret on demand
+pub type SecretCallback = Arc<dyn Fn() -> Result<String> + Send + Sync>;
+
+/// A place a secret can be read from
+#[derive(Clone)]
+pub enum SecretSource {
+    /// Environment variable
+    Env(String),
+    /// File holding the secret; surrounding whitespace is ignored
+    File(PathBuf),
+    /// Ask on the terminal, without echo, showing this prompt
+    Prompt(String),
+    /// Application-provided lookup, e.g. a keychain or vault client
+    Callback(SecretCallback),
+}
+
+impl fmt::Debug for SecretSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretSource::Env(var) => f.debug_tuple("Env").field(var).finish(),
+            SecretSource::File(path) => f.debug_tuple("File").field(path).finish(),
+            SecretSource::Prompt(prompt) => f.debug_tuple("Prompt").field(prompt).finish(),
+            SecretSource::Callback(_) => f.write_str("Callback"),
+        }
+    }
+}
+
+impl SecretSource {
+    /// Read the secret from environment variable `var`
+    pub fn env(var: impl Into<String>) -> Self {
+        SecretSource::Env(var.into())
+    }
+
+    /// Read the secret from the file at `path`
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        SecretSource::File(path.into())
+    }
+
+    /// Ask for the secret on the terminal
+    pub fn prompt(prompt: impl Into<String>) -> Self {
+        SecretSource::Prompt(prompt.into())
+    }
+
+    /// Get the secret from `callback`
+    pub fn callback<F>(callback: F) -> Self
+    where
+        F: Fn() -> Result<String> + Send + Sync + 'static,
+    {
+        SecretSource::Callback(Arc::new(callback))
+    }
+
+    /// Fetch the secret
+    ///
+    /// # Errors
+    ///
+    /// Returns `SecretUnavailable` if the variable is unset, the file is missing or too
+    /// widely readable, the prompt gets no answer, or the secret is empty; callback
+    /// errors are passed through.
+    pub fn resolve(&self) -> Result<String> {
+        let secret = match self {
+            SecretSource::Env(var) => std::env::var(var)
+                .map_err(|e| KnishIOError::SecretUnavailable(format!("{}: {}", var, e)))?,
+            SecretSource::File(path) => read_secret_file(path)?,
+            SecretSource::Prompt(prompt) => read_from_terminal(prompt)?,
+            SecretSource::Callback(callback) => callback()?,
+        };
+
+        let secret = secret.trim();
+        if secret.is_empty() {
+            return Err(KnishIOError::SecretUnavailable(format!("{:?} produced an empty secret", self)));
+        }
+        Ok(secret.to_string())
+    }
+}
+
+/// Read a secret file after checking that only its owner can read it
+fn read_secret_file(path: &Path) -> Result<String> {
+    let unavailable = |e: std::io::Error| KnishIOError::SecretUnavailable(format!("{}: {}", path.display(), e));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = std::fs::metadata(path).map_err(unavailable)?.permissions().mode();
+        if mode & 0o077 != 0 {
+            return Err(KnishIOError::SecretUnavailable(format!(
+                "{} is accessible by group or others (mode {:o}); restrict it to 0600",
+                path.display(),
+                mode & 0o777
+            )));
+        }
+    }
+
+    std::fs::read_to_string(path).map_err(unavailable)
+}
+
+/// Prompt on stderr and read one line, with echo turned off when stdin is a terminal
+fn read_from_terminal(prompt: &str) -> Result<String> {
+    let unavailable = |e: std::io::Error| KnishIOError::SecretUnavailable(format!("prompt: {}", e));
+
+    let mut stderr = std::io::stderr();
+    write!(stderr, "{}", prompt).and_then(|_| stderr.flush()).map_err(unavailable)?;
+
+    let stdin = std::io::stdin();
+    let hide = stdin.is_terminal() && set_echo(false);
+    let mut line = String::new();
+    let read = stdin.lock().read_line(&mut line);
+    if hide {
+        set_echo(true);
+        let _ = writeln!(stderr);
+    }
+
+    match read.map_err(unavailable)? {
+        0 => Err(KnishIOError::SecretUnavailable("prompt: no input".to_string())),
+        _ => Ok(line),
+    }
+}
+
+/// Switch terminal echo with `stty`; false if that was not possible
+fn set_echo(on: bool) -> bool {
+    if !cfg!(unix) {
+        return false;
+    }
+
+    std::process::Command::new("stty")
+        .arg(if on { "echo" } else { "-echo" })
+        .stdin(std::process::Stdio::inherit())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_and_callback_sources() {
+        let var = format!("KNISHIO_TEST_SECRET_{}", uuid::Uuid::new_v4().simple());
+        assert!(matches!(SecretSource::env(&var).resolve(), Err(KnishIOError::SecretUnavailable(_))));
+
+        std::env::set_var(&var, "env-secret\n");
+        assert_eq!(SecretSource::env(&var).resolve().unwrap(), "env-secret");
+        std::env::remove_var(&var);
+
+        let source = SecretSource::callback(|| Ok("vault-secret".to_string()));
+        assert_eq!(source.resolve().unwrap(), "vault-secret");
+        assert_eq!(format!("{:?}", source), "Callback");
+
+        let empty = SecretSource::callback(|| Ok("  ".to_string()));
+        assert!(matches!(empty.resolve(), Err(KnishIOError::SecretUnavailable(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_source_checks_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("knishio-secret-{}", uuid::Uuid::new_v4().simple()));
+        std::fs::write(&path, "file-secret\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let error = SecretSource::file(&path).resolve().unwrap_err();
+        assert!(error.to_string().contains("mode 644"), "{}", error);
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+        assert_eq!(SecretSource::file(&path).resolve().unwrap(), "file-secret");
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(SecretSource::file(&path).resolve().is_err());
+    }
+}
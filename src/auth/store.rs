@@ -0,0 +1,235 @@
+//! Auth token store
+//!
+//! Auth tokens are issued per node and per bundle. A process serving many users against
+//! one node holds one token per user, so tokens are kept under `(URI, bundle)`. The store
+//! is bounded: once `capacity` tokens are held, inserting another evicts the least
+//! recently used one.
+
+use super::AuthToken;
+use crate::utils::clock::Clock;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Default number of tokens kept before the least recently used one is evicted
+pub const DEFAULT_AUTH_STORE_CAPACITY: usize = 1024;
+
+/// Key of a stored token: node URI plus the bundle it authenticates (None for guests)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AuthKey {
+    /// Node URI
+    pub uri: String,
+    /// Authenticated bundle hash
+    pub bundle: Option<String>,
+}
+
+impl AuthKey {
+    /// Key for `bundle` on `uri`
+    pub fn new(uri: impl Into<String>, bundle: Option<&str>) -> Self {
+        AuthKey {
+            uri: uri.into(),
+            bundle: bundle.map(str::to_string),
+        }
+    }
+}
+
+/// Bounded LRU store of auth tokens keyed by `(URI, bundle)`
+#[derive(Debug, Clone)]
+pub struct AuthTokenStore {
+    capacity: usize,
+    entries: HashMap<AuthKey, (AuthToken, u64)>,
+    clock: u64,
+}
+
+impl Default for AuthTokenStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_AUTH_STORE_CAPACITY)
+    }
+}
+
+impl AuthTokenStore {
+    /// Create a store holding at most `capacity` tokens (0 is treated as 1)
+    pub fn new(capacity: usize) -> Self {
+        AuthTokenStore {
+            capacity: capacity.max(1),
+            entries: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    /// Maximum number of tokens held
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Change the capacity, evicting least recently used tokens if the store is over it
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.entries.len() > self.capacity {
+            self.evict_oldest();
+        }
+    }
+
+    /// Number of tokens held
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// True if no tokens are held
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Store `token` for `bundle` on `uri`, replacing any previous token for that pair
+    ///
+    /// # Returns
+    ///
+    /// The token evicted to make room, if any
+    pub fn insert(&mut self, uri: &str, bundle: Option<&str>, token: AuthToken) -> Option<(AuthKey, AuthToken)> {
+        let key = AuthKey::new(uri, bundle);
+        let evicted = if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            self.evict_oldest()
+        } else {
+            None
+        };
+
+        let tick = self.tick();
+        self.entries.insert(key, (token, tick));
+        evicted
+    }
+
+    /// Token for `bundle` on `uri`, marking it as recently used
+    pub fn get(&mut self, uri: &str, bundle: Option<&str>) -> Option<&AuthToken> {
+        let tick = self.tick();
+        let (token, used) = self.entries.get_mut(&AuthKey::new(uri, bundle))?;
+        *used = tick;
+        Some(token)
+    }
+
+    /// Token for `bundle` on `uri` without affecting eviction order
+    pub fn peek(&self, uri: &str, bundle: Option<&str>) -> Option<&AuthToken> {
+        self.entries.get(&AuthKey::new(uri, bundle)).map(|(token, _)| token)
+    }
+
+    /// Any token held for `uri`, preferring the most recently used one
+    pub fn peek_uri(&self, uri: &str) -> Option<&AuthToken> {
+        self.entries.iter()
+            .filter(|(key, _)| key.uri == uri)
+            .max_by_key(|(_, (_, used))| *used)
+            .map(|(_, (token, _))| token)
+    }
+
+    /// Remove the token for `bundle` on `uri`
+    pub fn remove(&mut self, uri: &str, bundle: Option<&str>) -> Option<AuthToken> {
+        self.entries.remove(&AuthKey::new(uri, bundle)).map(|(token, _)| token)
+    }
+
+    /// Remove every token held for `bundle`, on any node
+    ///
+    /// # Returns
+    ///
+    /// Number of tokens removed
+    pub fn remove_bundle(&mut self, bundle: &str) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|key, _| key.bundle.as_deref() != Some(bundle));
+        before - self.entries.len()
+    }
+
+    /// Remove expired tokens
+    ///
+    /// # Returns
+    ///
+    /// Number of tokens removed
+    pub fn purge_expired(&mut self) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|_, (token, _)| !token.is_expired());
+        before - self.entries.len()
+    }
+
+    /// Judge the expiry of every stored token against `clock`
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        for (token, _) in self.entries.values_mut() {
+            token.set_clock(clock.clone());
+        }
+    }
+
+    /// Remove every token
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Keys of all stored tokens
+    pub fn keys(&self) -> impl Iterator<Item = &AuthKey> {
+        self.entries.keys()
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn evict_oldest(&mut self) -> Option<(AuthKey, AuthToken)> {
+        let oldest = self.entries.iter()
+            .min_by_key(|(_, (_, used))| *used)
+            .map(|(key, _)| key.clone())?;
+        self.entries.remove(&oldest).map(|(token, _)| (oldest, token))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(name: &str, expires_in: i64) -> AuthToken {
+        AuthToken::new(name.to_string(), Some(chrono::Utc::now().timestamp() + expires_in), None, None)
+    }
+
+    #[test]
+    fn test_purge_follows_clock() {
+        let clock = crate::utils::ManualClock::starting_now();
+        let mut store = AuthTokenStore::default();
+        store.insert("https://a", Some("alice"), token("short", 60));
+        store.insert("https://a", Some("bob"), token("long", 600));
+        store.set_clock(Arc::new(clock.clone()));
+        assert_eq!(store.purge_expired(), 0);
+
+        clock.advance(std::time::Duration::from_secs(120));
+        assert_eq!(store.purge_expired(), 1);
+        assert!(store.peek("https://a", Some("bob")).is_some());
+    }
+
+    #[test]
+    fn test_tokens_are_keyed_by_uri_and_bundle() {
+        let mut store = AuthTokenStore::default();
+        store.insert("https://a", Some("alice"), token("a-alice", 60));
+        store.insert("https://a", Some("bob"), token("a-bob", 60));
+        store.insert("https://b", Some("alice"), token("b-alice", 60));
+
+        assert_eq!(store.peek("https://a", Some("alice")).unwrap().token(), "a-alice");
+        assert_eq!(store.peek("https://a", Some("bob")).unwrap().token(), "a-bob");
+        assert!(store.peek("https://a", None).is_none());
+
+        assert_eq!(store.remove_bundle("alice"), 2);
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.peek_uri("https://a").unwrap().token(), "a-bob");
+    }
+
+    #[test]
+    fn test_least_recently_used_is_evicted() {
+        let mut store = AuthTokenStore::new(2);
+        store.insert("https://a", Some("alice"), token("alice", 60));
+        store.insert("https://a", Some("bob"), token("bob", 60));
+
+        // Touch alice so bob becomes the eviction candidate
+        store.get("https://a", Some("alice"));
+        let (evicted, _) = store.insert("https://a", Some("carol"), token("carol", 60)).unwrap();
+        assert_eq!(evicted.bundle.as_deref(), Some("bob"));
+
+        // Replacing an existing key never evicts
+        assert!(store.insert("https://a", Some("carol"), token("carol-2", 60)).is_none());
+
+        store.insert("https://a", Some("dave"), token("dave", -60));
+        assert_eq!(store.purge_expired(), 1);
+        store.set_capacity(1);
+        assert_eq!(store.len(), 1);
+    }
+}
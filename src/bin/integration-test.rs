@@ -16,7 +16,7 @@ use anyhow::{Result, Context};
 use serde_json::{json, Value};
 
 use knishio_client::{
-    molecule::Molecule,
+    molecule::{Molecule, MoleculeParams},
     wallet::Wallet,
     crypto::{generate_secret, generate_bundle_hash},
     types::MetaItem,
@@ -259,13 +259,12 @@ async fn test_rust_molecular_metadata_creation(client: &reqwest::Client, url: &s
     log_test("Rust source wallet creation", true, None, None);
     
     // Create Rust molecule
-    let mut molecule = Molecule::with_params(
-        Some(test_secret.to_string()),
-        Some(test_bundle.to_string()),
-        Some(source_wallet),
-        None,
-        Some(cell_slug.to_string()),
-        None
+    let mut molecule = Molecule::from_params(
+        MoleculeParams::new()
+            .secret(test_secret)
+            .bundle(test_bundle.to_string())
+            .source_wallet(source_wallet)
+            .cell_slug(cell_slug.to_string()),
     );
     
     // Add metadata using Rust SDK
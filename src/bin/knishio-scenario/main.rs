@@ -0,0 +1,293 @@
+/**
+ * Knish.IO Scenario Runner
+ *
+ * Executes YAML-defined end-to-end flows (authenticate → create token → transfer →
+ * verify balance, ...) against a validator node and reports pass/fail per step.
+ * Example scenarios live in `examples/scenarios/`; they double as living
+ * documentation of the SDK calls each step makes.
+ *
+ * Usage:
+ *   cargo run --bin knishio-scenario -- --node https://testnet.knish.io/graphql examples/scenarios/token_flow.yaml
+ *   KNISHIO_API_URL=http://localhost:8000/graphql cargo run --bin knishio-scenario -- examples/scenarios/auth_smoke.yaml
+ */
+
+mod scenario;
+mod yaml;
+
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Result};
+use knishio_client::{
+    crypto::{generate_bundle_hash, generate_secret},
+    KnishIOClient, KnishIOError, Response,
+};
+
+use scenario::{Action, Scenario};
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+/// Command line options
+struct Options {
+    node: Option<String>,
+    cell_slug: Option<String>,
+    keep_going: bool,
+    dry_run: bool,
+    files: Vec<PathBuf>,
+}
+
+/// Outcome of a single step
+struct StepOutcome {
+    label: String,
+    result: Result<String>,
+    elapsed: Duration,
+}
+
+/// An identity's secret, bundle and (once used) its client
+struct Identity {
+    secret: String,
+    bundle: String,
+    client: Option<KnishIOClient>,
+}
+
+fn print_usage() {
+    println!("Knish.IO Scenario Runner");
+    println!();
+    println!("Usage:");
+    println!("  cargo run --bin knishio-scenario -- [options] <scenario.yaml>...");
+    println!();
+    println!("Options:");
+    println!("  --node <url>     GraphQL endpoint (default: scenario `node`, then KNISHIO_API_URL)");
+    println!("  --cell <slug>    Cell slug (default: scenario `cell_slug`, then KNISHIO_CELL_SLUG)");
+    println!("  --keep-going     Run remaining steps after a failure");
+    println!("  --dry-run        Parse and validate scenarios without contacting a node");
+    println!("  -h, --help       Show this help");
+}
+
+fn parse_args() -> Result<Option<Options>> {
+    let mut options = Options {
+        node: None,
+        cell_slug: None,
+        keep_going: false,
+        dry_run: false,
+        files: Vec::new(),
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--node" | "--url" => options.node = Some(args.next().ok_or_else(|| anyhow!("--node needs a value"))?),
+            "--cell" => options.cell_slug = Some(args.next().ok_or_else(|| anyhow!("--cell needs a value"))?),
+            "--keep-going" => options.keep_going = true,
+            "--dry-run" => options.dry_run = true,
+            "-h" | "--help" => {
+                print_usage();
+                return Ok(None);
+            }
+            flag if flag.starts_with("--") => bail!("unknown option {}", flag),
+            file => options.files.push(PathBuf::from(file)),
+        }
+    }
+
+    if options.files.is_empty() {
+        print_usage();
+        bail!("no scenario files given");
+    }
+
+    Ok(Some(options))
+}
+
+/// Short random tag exposed to scenarios as `${RUN}`
+fn run_tag() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..6].to_uppercase()
+}
+
+fn resolve_identities(scenario: &Scenario) -> HashMap<String, Identity> {
+    scenario.identities.iter()
+        .map(|(name, spec)| {
+            let secret = match (&spec.secret, &spec.seed) {
+                (Some(secret), _) => secret.clone(),
+                (None, Some(seed)) => generate_secret(seed),
+                (None, None) => generate_secret(&uuid::Uuid::new_v4().to_string()),
+            };
+            let bundle = generate_bundle_hash(&secret);
+            (name.clone(), Identity { secret, bundle, client: None })
+        })
+        .collect()
+}
+
+/// Client for `name`, created on first use with the identity's secret
+fn client_for<'a>(
+    identities: &'a mut HashMap<String, Identity>,
+    name: &str,
+    node: &str,
+    cell_slug: Option<&str>,
+) -> Result<&'a mut KnishIOClient> {
+    let identity = identities.get_mut(name).ok_or_else(|| anyhow!("unknown identity {}", name))?;
+    let secret = identity.secret.clone();
+
+    Ok(identity.client.get_or_insert_with(|| {
+        let mut client = KnishIOClient::new(node, cell_slug.map(str::to_string), None, None, None, Some(false));
+        client.set_secret(secret);
+        client
+    }))
+}
+
+fn check_response(response: &dyn Response) -> Result<()> {
+    if response.success() {
+        Ok(())
+    } else {
+        bail!(
+            "rejected: {}",
+            response.reason().or_else(|| response.error()).unwrap_or_else(|| "unknown reason".to_string())
+        )
+    }
+}
+
+async fn run_action(
+    action: &Action,
+    identities: &mut HashMap<String, Identity>,
+    node: &str,
+    cell_slug: Option<&str>,
+) -> Result<String> {
+    match action {
+        Action::Authenticate { identity } => {
+            let client = client_for(identities, identity, node, cell_slug)?;
+            client.authenticate(HashMap::new()).await?;
+            Ok(format!("bundle {}", client.get_bundle().unwrap_or_default()))
+        }
+        Action::CreateToken { identity, token, amount, meta } => {
+            let client = client_for(identities, identity, node, cell_slug)?;
+            let meta = (!meta.is_empty()).then(|| meta.clone());
+            let response = client.create_token(token, Some(*amount), meta, None, Vec::new()).await?;
+            check_response(response.as_ref())?;
+            Ok(format!("issued {} {}", amount, token))
+        }
+        Action::Transfer { from, to, token, amount } => {
+            let recipient = match identities.get(to) {
+                Some(identity) => identity.bundle.clone(),
+                None => to.clone(),
+            };
+            let client = client_for(identities, from, node, cell_slug)?;
+            let response = client.transfer_token(&recipient, token, Some(*amount), Vec::new(), None, None).await?;
+            check_response(response.as_ref())?;
+            Ok(format!("sent {} {} to {}", amount, token, recipient))
+        }
+        Action::VerifyBalance { identity, token, expect } => {
+            let client = client_for(identities, identity, node, cell_slug)?;
+            // A bundle that never held the token has no wallet, and the node answers `Balance: null`
+            let balance: f64 = match client.query_balance(token, None).await {
+                Ok(wallet) => wallet.balance.parse().unwrap_or(0.0),
+                Err(KnishIOError::InvalidResponse) => 0.0,
+                Err(error) => return Err(error.into()),
+            };
+            if (balance - expect).abs() > f64::EPSILON {
+                bail!("expected {} {}, found {}", expect, token, balance);
+            }
+            Ok(format!("balance {} {}", balance, token))
+        }
+        Action::Wait { ms } => {
+            tokio::time::sleep(Duration::from_millis(*ms)).await;
+            Ok(String::new())
+        }
+    }
+}
+
+/// Run one scenario, printing each step as it completes; returns true if every step passed
+async fn run_scenario(scenario: &Scenario, options: &Options) -> Result<bool> {
+    // Flags win over the scenario's own values, which win over the environment
+    let node = options.node.clone()
+        .or_else(|| scenario.node.clone())
+        .or_else(|| env::var("KNISHIO_API_URL").ok())
+        .ok_or_else(|| anyhow!("no node given (use --node, KNISHIO_API_URL or `node:`)"))?;
+    let cell_slug = options.cell_slug.clone()
+        .or_else(|| scenario.cell_slug.clone())
+        .or_else(|| env::var("KNISHIO_CELL_SLUG").ok());
+    let mut identities = resolve_identities(scenario);
+
+    println!("{}▶ {}{} ({})", CYAN, scenario.name, RESET, node);
+    if let Some(description) = &scenario.description {
+        println!("  {}", description);
+    }
+
+    let mut outcomes = Vec::new();
+    for step in &scenario.steps {
+        let started = Instant::now();
+        let result = run_action(&step.action, &mut identities, &node, cell_slug.as_deref()).await;
+        let outcome = StepOutcome { label: step.label(), result, elapsed: started.elapsed() };
+
+        match &outcome.result {
+            Ok(detail) => println!(
+                "  {}PASS{} {} [{}ms] {}",
+                GREEN, RESET, outcome.label, outcome.elapsed.as_millis(), detail
+            ),
+            Err(error) => println!(
+                "  {}FAIL{} {} [{}ms] {:#}",
+                RED, RESET, outcome.label, outcome.elapsed.as_millis(), error
+            ),
+        }
+
+        let failed = outcome.result.is_err();
+        outcomes.push(outcome);
+        if failed && !options.keep_going {
+            break;
+        }
+    }
+
+    let passed = outcomes.iter().filter(|o| o.result.is_ok()).count();
+    let skipped = scenario.steps.len() - outcomes.len();
+    let colour = if passed == scenario.steps.len() { GREEN } else { RED };
+    println!(
+        "  {}{}/{} steps passed{}{}",
+        colour, passed, scenario.steps.len(), RESET,
+        if skipped > 0 { format!(" ({}{} skipped{})", YELLOW, skipped, RESET) } else { String::new() }
+    );
+
+    Ok(passed == scenario.steps.len())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let Some(options) = parse_args()? else {
+        return Ok(());
+    };
+
+    let run = run_tag();
+    let vars = |name: &str| if name == "RUN" { Some(run.clone()) } else { env::var(name).ok() };
+
+    let mut all_passed = true;
+    for path in &options.files {
+        let scenario = match Scenario::load(path, &vars) {
+            Ok(scenario) => scenario,
+            Err(error) => {
+                println!("{}FAIL{} {}: {:#}", RED, RESET, path.display(), error);
+                all_passed = false;
+                continue;
+            }
+        };
+
+        if options.dry_run {
+            println!("{}OK{}   {} ({} steps)", GREEN, RESET, scenario.name, scenario.steps.len());
+            continue;
+        }
+
+        match run_scenario(&scenario, &options).await {
+            Ok(passed) => all_passed &= passed,
+            Err(error) => {
+                println!("{}FAIL{} {}: {:#}", RED, RESET, scenario.name, error);
+                all_passed = false;
+            }
+        }
+    }
+
+    if !all_passed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
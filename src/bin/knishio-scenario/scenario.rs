@@ -0,0 +1,299 @@
+//! Scenario model
+//!
+//! A scenario names a set of identities and a list of steps run against one node:
+//!
+//! ```yaml
+//! name: Token round trip
+//! identities:
+//!   alice:
+//!     seed: ${ALICE_SEED}
+//!   bob: {}
+//! steps:
+//!   - action: authenticate
+//!     identity: alice
+//!   - action: create_token
+//!     identity: alice
+//!     token: SCN${RUN}
+//!     amount: 1000
+//! ```
+//!
+//! `${NAME}` is replaced by the environment variable `NAME` before the scenario is
+//! deserialized; `${RUN}` is a short random tag unique to each run, so token slugs do
+//! not collide on a shared node.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use super::yaml;
+
+/// A parsed scenario file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// Display name
+    pub name: String,
+    /// What the scenario demonstrates
+    #[serde(default)]
+    pub description: Option<String>,
+    /// GraphQL endpoint (overridden by `--node`)
+    #[serde(default)]
+    pub node: Option<String>,
+    /// Cell slug used for every identity (overridden by `--cell`)
+    #[serde(default)]
+    pub cell_slug: Option<String>,
+    /// Named identities referenced by the steps
+    #[serde(default)]
+    pub identities: BTreeMap<String, IdentitySpec>,
+    /// Steps, run in order
+    pub steps: Vec<Step>,
+}
+
+/// How an identity's secret is obtained
+///
+/// Exactly one of `secret` / `seed` may be given; with neither a random secret is
+/// generated for the run.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IdentitySpec {
+    /// Full 2048-character secret
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Seed the secret is derived from
+    #[serde(default)]
+    pub seed: Option<String>,
+}
+
+/// A single scenario step
+#[derive(Debug, Deserialize)]
+pub struct Step {
+    /// Optional label shown in the report (defaults to a summary of the action)
+    #[serde(default)]
+    pub name: Option<String>,
+    /// What the step does
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+/// Step actions
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Action {
+    /// Request an auth token for the identity
+    Authenticate { identity: String },
+    /// Issue a new token to the identity
+    CreateToken {
+        identity: String,
+        token: String,
+        amount: f64,
+        #[serde(default)]
+        meta: HashMap<String, Value>,
+    },
+    /// Send tokens to another identity (by name) or a bundle hash
+    Transfer {
+        from: String,
+        to: String,
+        token: String,
+        amount: f64,
+    },
+    /// Assert the identity's balance for a token
+    VerifyBalance {
+        identity: String,
+        token: String,
+        expect: f64,
+    },
+    /// Pause, e.g. to let the node settle a molecule
+    Wait { ms: u64 },
+}
+
+impl Step {
+    /// Label used in the report
+    pub fn label(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+
+        match &self.action {
+            Action::Authenticate { identity } => format!("authenticate {}", identity),
+            Action::CreateToken { identity, token, amount, .. } => {
+                format!("create {} {} for {}", amount, token, identity)
+            }
+            Action::Transfer { from, to, token, amount } => {
+                format!("transfer {} {} from {} to {}", amount, token, from, to)
+            }
+            Action::VerifyBalance { identity, token, expect } => {
+                format!("verify {} holds {} {}", identity, expect, token)
+            }
+            Action::Wait { ms } => format!("wait {}ms", ms),
+        }
+    }
+
+    /// Identities this step refers to by name
+    fn identities(&self) -> Vec<&str> {
+        match &self.action {
+            Action::Authenticate { identity }
+            | Action::CreateToken { identity, .. }
+            | Action::VerifyBalance { identity, .. } => vec![identity],
+            Action::Transfer { from, to, .. } => {
+                if knishio_client::Wallet::is_bundle_hash(to) {
+                    vec![from]
+                } else {
+                    vec![from, to]
+                }
+            }
+            Action::Wait { .. } => Vec::new(),
+        }
+    }
+}
+
+impl Scenario {
+    /// Load a scenario from a `.yaml` / `.yml` or `.json` file
+    pub fn load(path: &Path, vars: &dyn Fn(&str) -> Option<String>) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("reading {}", path.display()))?;
+        let is_json = path.extension().is_some_and(|ext| ext == "json");
+
+        Self::parse(&source, is_json, vars).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// Parse scenario source, substituting `${NAME}` placeholders through `vars`
+    pub fn parse(source: &str, is_json: bool, vars: &dyn Fn(&str) -> Option<String>) -> Result<Self> {
+        let mut document = if is_json {
+            serde_json::from_str(source)?
+        } else {
+            yaml::parse(source)?
+        };
+        substitute(&mut document, vars)?;
+
+        let scenario: Scenario = serde_json::from_value(document)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Check cross-references that serde cannot
+    fn validate(&self) -> Result<()> {
+        if self.steps.is_empty() {
+            bail!("scenario `{}` has no steps", self.name);
+        }
+
+        for (name, identity) in &self.identities {
+            if identity.secret.is_some() && identity.seed.is_some() {
+                bail!("identity `{}` sets both `secret` and `seed`", name);
+            }
+        }
+
+        for (index, step) in self.steps.iter().enumerate() {
+            for identity in step.identities() {
+                if !self.identities.contains_key(identity) {
+                    bail!("step {} ({}) uses undeclared identity `{}`", index + 1, step.label(), identity);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Replace `${NAME}` in every string of `value`
+fn substitute(value: &mut Value, vars: &dyn Fn(&str) -> Option<String>) -> Result<()> {
+    match value {
+        Value::String(text) => {
+            let mut output = String::with_capacity(text.len());
+            let mut rest = text.as_str();
+
+            while let Some(start) = rest.find("${") {
+                let end = rest[start..].find('}')
+                    .ok_or_else(|| anyhow!("unterminated placeholder in `{}`", text))?;
+                let name = &rest[start + 2..start + end];
+                let replacement = vars(name)
+                    .ok_or_else(|| anyhow!("variable `{}` is not set", name))?;
+
+                output.push_str(&rest[..start]);
+                output.push_str(&replacement);
+                rest = &rest[start + end + 1..];
+            }
+
+            output.push_str(rest);
+            *text = output;
+        }
+        Value::Array(items) => {
+            for item in items {
+                substitute(item, vars)?;
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                substitute(item, vars)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(name: &str) -> Option<String> {
+        match name {
+            "RUN" => Some("AB12".to_string()),
+            "ALICE_SEED" => Some("alice".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_parse_example_scenarios() {
+        for source in [
+            include_str!("../../../examples/scenarios/token_flow.yaml"),
+            include_str!("../../../examples/scenarios/auth_smoke.yaml"),
+        ] {
+            let scenario = Scenario::parse(source, false, &|name| vars(name).or(Some("x".to_string()))).unwrap();
+            assert!(!scenario.steps.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_substitution_and_labels() {
+        let source = r#"
+name: demo
+identities:
+  alice:
+    seed: ${ALICE_SEED}
+steps:
+  - action: create_token
+    identity: alice
+    token: SCN${RUN}
+    amount: 10
+  - name: settle
+    action: wait
+    ms: 5
+"#;
+        let scenario = Scenario::parse(source, false, &vars).unwrap();
+        assert_eq!(scenario.identities["alice"].seed.as_deref(), Some("alice"));
+        assert_eq!(scenario.steps[0].label(), "create 10 SCNAB12 for alice");
+        assert_eq!(scenario.steps[1].label(), "settle");
+
+        let json = r#"{"name":"j","identities":{"a":{}},"steps":[{"action":"authenticate","identity":"a"}]}"#;
+        assert!(Scenario::parse(json, true, &vars).is_ok());
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let undeclared = "name: x\nsteps:\n  - action: authenticate\n    identity: carol\n";
+        assert!(Scenario::parse(undeclared, false, &vars).is_err());
+
+        let unknown_action = "name: x\nsteps:\n  - action: mint\n";
+        assert!(Scenario::parse(unknown_action, false, &vars).is_err());
+
+        let unset_variable = "name: ${NOPE}\nsteps:\n  - action: wait\n    ms: 1\n";
+        assert!(Scenario::parse(unset_variable, false, &vars).is_err());
+
+        let both = "name: x\nidentities:\n  a:\n    seed: s\n    secret: t\nsteps:\n  - action: wait\n    ms: 1\n";
+        assert!(Scenario::parse(both, false, &vars).is_err());
+    }
+}
@@ -0,0 +1,312 @@
+//! Minimal YAML reader for scenario files
+//!
+//! Scenario files only need a small slice of YAML: block mappings, block sequences
+//! (including `- key: value` items), plain/quoted scalars, empty `[]` / `{}` and
+//! `#` comments. This reader covers exactly that and parses into `serde_json::Value`,
+//! so scenarios deserialize through serde like any other JSON document. Anchors,
+//! tags, flow collections with content and multi-line (`|` / `>`) scalars are rejected.
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::{Map, Number, Value};
+
+/// A non-blank, non-comment line: indentation, content and 1-based line number
+struct Line {
+    indent: usize,
+    text: String,
+    number: usize,
+}
+
+/// Parse a YAML document into a JSON value
+pub fn parse(source: &str) -> Result<Value> {
+    let mut lines = Vec::new();
+
+    for (index, raw) in source.lines().enumerate() {
+        let number = index + 1;
+        if raw.starts_with('\t') {
+            bail!("line {}: tabs are not allowed for indentation", number);
+        }
+
+        let text = strip_comment(raw).trim_end().to_string();
+        let trimmed = text.trim_start();
+        if trimmed.is_empty() || trimmed == "---" {
+            continue;
+        }
+
+        lines.push(Line {
+            indent: text.len() - trimmed.len(),
+            text: trimmed.to_string(),
+            number,
+        });
+    }
+
+    if lines.is_empty() {
+        return Ok(Value::Null);
+    }
+
+    let mut pos = 0;
+    let indent = lines[0].indent;
+    let value = parse_block(&mut lines, &mut pos, indent)?;
+
+    if let Some(line) = lines.get(pos) {
+        bail!("line {}: unexpected indentation", line.number);
+    }
+
+    Ok(value)
+}
+
+fn parse_block(lines: &mut [Line], pos: &mut usize, indent: usize) -> Result<Value> {
+    if is_sequence_item(&lines[*pos].text) {
+        parse_sequence(lines, pos, indent)
+    } else {
+        parse_mapping(lines, pos, indent)
+    }
+}
+
+fn parse_sequence(lines: &mut [Line], pos: &mut usize, indent: usize) -> Result<Value> {
+    let mut items = Vec::new();
+
+    while let Some(line) = lines.get(*pos) {
+        if line.indent < indent {
+            break;
+        }
+        if line.indent > indent {
+            bail!("line {}: unexpected indentation", line.number);
+        }
+        if !is_sequence_item(&line.text) {
+            break;
+        }
+
+        let rest = line.text[1..].trim_start().to_string();
+        let number = line.number;
+
+        if rest.is_empty() {
+            // "-" alone: the item is the nested block below
+            *pos += 1;
+            items.push(parse_nested(lines, pos, indent)?);
+        } else if is_sequence_item(&rest) || split_key(&rest).is_some() {
+            // "- key: value" / "- - x": re-read the item body as a block indented past the dash
+            let item_indent = line.indent + (line.text.len() - rest.len());
+            lines[*pos].indent = item_indent;
+            lines[*pos].text = rest;
+            items.push(parse_block(lines, pos, item_indent)?);
+        } else {
+            *pos += 1;
+            items.push(parse_scalar(&rest, number)?);
+        }
+    }
+
+    Ok(Value::Array(items))
+}
+
+fn parse_mapping(lines: &mut [Line], pos: &mut usize, indent: usize) -> Result<Value> {
+    let mut map = Map::new();
+
+    while let Some(line) = lines.get(*pos) {
+        if line.indent < indent {
+            break;
+        }
+        if line.indent > indent {
+            bail!("line {}: unexpected indentation", line.number);
+        }
+        if is_sequence_item(&line.text) {
+            break;
+        }
+
+        let number = line.number;
+        let (key, rest) = split_key(&line.text)
+            .ok_or_else(|| anyhow!("line {}: expected `key: value`", number))?;
+        *pos += 1;
+
+        let value = if rest.is_empty() {
+            // A sequence may sit at the same indentation as its key
+            match lines.get(*pos) {
+                Some(next) if next.indent == indent && is_sequence_item(&next.text) => {
+                    parse_sequence(lines, pos, indent)?
+                }
+                _ => parse_nested(lines, pos, indent)?,
+            }
+        } else {
+            parse_scalar(&rest, number)?
+        };
+
+        if map.insert(key.clone(), value).is_some() {
+            bail!("line {}: duplicate key `{}`", number, key);
+        }
+    }
+
+    Ok(Value::Object(map))
+}
+
+/// Parse the block indented deeper than `parent`, or null if there is none
+fn parse_nested(lines: &mut [Line], pos: &mut usize, parent: usize) -> Result<Value> {
+    match lines.get(*pos) {
+        Some(next) if next.indent > parent => {
+            let indent = next.indent;
+            parse_block(lines, pos, indent)
+        }
+        _ => Ok(Value::Null),
+    }
+}
+
+fn is_sequence_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+/// Split `key: rest` at the first unquoted `: ` (or trailing `:`)
+fn split_key(text: &str) -> Option<(String, String)> {
+    let mut quote = None;
+
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') if i == 0 => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, ':') => {
+                let after = &text[i + 1..];
+                if after.is_empty() || after.starts_with(' ') {
+                    let key = unquote(text[..i].trim());
+                    return Some((key, after.trim().to_string()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Remove a trailing `# comment` that is not inside quotes
+///
+/// Quotes only open after whitespace, so apostrophes inside plain scalars are literal.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') if previous == ' ' => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '#') if previous == ' ' => return &line[..i],
+            _ => {}
+        }
+        previous = c;
+    }
+
+    line
+}
+
+fn unquote(text: &str) -> String {
+    let bytes = text.as_bytes();
+    if text.len() >= 2 && (bytes[0] == b'"' || bytes[0] == b'\'') && bytes[text.len() - 1] == bytes[0] {
+        let inner = &text[1..text.len() - 1];
+        if bytes[0] == b'"' {
+            inner.replace("\\\"", "\"").replace("\\n", "\n").replace("\\\\", "\\")
+        } else {
+            inner.replace("''", "'")
+        }
+    } else {
+        text.to_string()
+    }
+}
+
+fn parse_scalar(text: &str, number: usize) -> Result<Value> {
+    if text.starts_with('"') || text.starts_with('\'') {
+        let quote = &text[..1];
+        if text.len() < 2 || !text.ends_with(quote) {
+            bail!("line {}: unterminated string", number);
+        }
+        return Ok(Value::String(unquote(text)));
+    }
+
+    match text {
+        "~" | "null" | "Null" | "NULL" => return Ok(Value::Null),
+        "true" | "True" | "TRUE" => return Ok(Value::Bool(true)),
+        "false" | "False" | "FALSE" => return Ok(Value::Bool(false)),
+        "[]" => return Ok(Value::Array(Vec::new())),
+        "{}" => return Ok(Value::Object(Map::new())),
+        _ => {}
+    }
+
+    if matches!(text.chars().next(), Some('|' | '>' | '&' | '*' | '!' | '[' | '{')) {
+        bail!("line {}: unsupported YAML syntax `{}`", number, text);
+    }
+
+    if let Ok(int) = text.parse::<i64>() {
+        return Ok(Value::Number(int.into()));
+    }
+    if let Some(float) = text.parse::<f64>().ok().and_then(Number::from_f64) {
+        return Ok(Value::Number(float));
+    }
+
+    Ok(Value::String(text.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_mappings_sequences_and_scalars() {
+        let source = r#"
+# leading comment
+name: Token flow   # trailing comment
+count: 3
+ratio: 0.5
+enabled: true
+missing: ~
+quoted: "a # not a comment"
+plain: it's fine # comment
+identities:
+  alice:
+    seed: alice-seed
+  bob: {}
+steps:
+  - action: authenticate
+    identity: alice
+  - action: wait
+    ms: 10
+tags:
+- one
+- 'two'
+"#;
+
+        assert_eq!(parse(source).unwrap(), json!({
+            "name": "Token flow",
+            "count": 3,
+            "ratio": 0.5,
+            "enabled": true,
+            "missing": null,
+            "quoted": "a # not a comment",
+            "plain": "it's fine",
+            "identities": { "alice": { "seed": "alice-seed" }, "bob": {} },
+            "steps": [
+                { "action": "authenticate", "identity": "alice" },
+                { "action": "wait", "ms": 10 }
+            ],
+            "tags": ["one", "two"]
+        }));
+    }
+
+    #[test]
+    fn test_nested_sequence_items() {
+        let source = "
+- - 1
+  - 2
+-
+  key: value
+- url: http://node:8080/graphql
+";
+        assert_eq!(parse(source).unwrap(), json!([[1, 2], { "key": "value" }, { "url": "http://node:8080/graphql" }]));
+    }
+
+    #[test]
+    fn test_rejects_unsupported_and_malformed_input() {
+        assert!(parse("key: |\n  text").is_err());
+        assert!(parse("key: [1, 2]").is_err());
+        assert!(parse("a: 1\na: 2").is_err());
+        assert!(parse("a: 1\n    b: 2").is_err());
+        assert!(parse("just a line").is_err());
+        assert!(parse("a: \"open").is_err());
+        assert_eq!(parse("# only comments\n").unwrap(), Value::Null);
+    }
+}
@@ -0,0 +1,194 @@
+//! Command line parsing for the `knishio` tool
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Result};
+
+/// Options shared by every command
+#[derive(Debug, Default, PartialEq)]
+pub struct Globals {
+    pub node: Option<String>,
+    pub cell_slug: Option<String>,
+    pub secret: Option<String>,
+    /// Token snapshot written by `auth login --save`, reused instead of re-authenticating
+    pub session: Option<PathBuf>,
+}
+
+/// A parsed command
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    WalletCreate { token: String },
+    Balance { token: String, bundle: Option<String> },
+    Transfer { recipient: String, token: String, amount: f64 },
+    MetaSet { meta_type: String, meta_id: String, meta: Vec<(String, String)> },
+    MetaGet { meta_type: String, meta_id: Option<String>, key: Option<String> },
+    TokenCreate { token: String, amount: f64, meta: Vec<(String, String)> },
+    MoleculeInspect { source: String },
+    MoleculeVerify { source: String },
+    AuthLogin { save: Option<PathBuf> },
+    Help,
+}
+
+pub fn print_usage() {
+    println!("Knish.IO command line client");
+    println!();
+    println!("Usage:");
+    println!("  knishio [options] <command>");
+    println!();
+    println!("Commands:");
+    println!("  wallet create [--token <slug>]              Derive a wallet (new secret if none is given)");
+    println!("  balance <token> [--bundle <hash>]           Query a wallet balance");
+    println!("  transfer <bundle> <token> <amount>          Send tokens to a bundle");
+    println!("  meta set <type> <id> <key=value>...         Attach metadata to an asset");
+    println!("  meta get <type> [id] [--key <key>]          Query metadata");
+    println!("  token create <slug> <amount> [key=value]... Issue a new token");
+    println!("  molecule inspect <file|->                   Summarise a molecule JSON document");
+    println!("  molecule verify <file|->                    Check a molecule's hash, signature and rules");
+    println!("  auth login [--save <file>]                  Authenticate and print (or save) the token");
+    println!();
+    println!("Options:");
+    println!("  --node <url>       GraphQL endpoint (default: KNISHIO_API_URL)");
+    println!("  --cell <slug>      Cell slug (default: KNISHIO_CELL_SLUG)");
+    println!("  --secret <secret>  User secret (default: KNISHIO_SECRET)");
+    println!("  --session <file>   Reuse a token saved by `auth login --save`");
+    println!("  -h, --help         Show this help");
+}
+
+/// Split `args` (without the program name) into global options and a command
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<(Globals, Command)> {
+    let mut globals = Globals::default();
+    let mut flags: Vec<(String, String)> = Vec::new();
+    let mut words: Vec<String> = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| anyhow!("{} needs a value", name));
+        match arg.as_str() {
+            "--node" | "--url" => globals.node = Some(value(&arg)?),
+            "--cell" => globals.cell_slug = Some(value(&arg)?),
+            "--secret" => globals.secret = Some(value(&arg)?),
+            "--session" => globals.session = Some(PathBuf::from(value(&arg)?)),
+            "-h" | "--help" => return Ok((globals, Command::Help)),
+            "-" => words.push(arg),
+            flag if flag.starts_with('-') => {
+                let name = flag.trim_start_matches('-').to_string();
+                flags.push((name, value(flag)?));
+            }
+            _ => words.push(arg),
+        }
+    }
+
+    let command = command(&words, &mut flags)?;
+    if let Some((name, _)) = flags.first() {
+        bail!("unknown option --{}", name);
+    }
+    Ok((globals, command))
+}
+
+fn command(words: &[String], flags: &mut Vec<(String, String)>) -> Result<Command> {
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let mut flag = |name: &str| flags.iter()
+        .position(|(flag, _)| flag == name)
+        .map(|index| flags.remove(index).1);
+
+    Ok(match words.as_slice() {
+        [] | ["help"] => Command::Help,
+        ["wallet", "create"] => Command::WalletCreate {
+            token: flag("token").unwrap_or_else(|| "USER".to_string()),
+        },
+        ["balance", token] => Command::Balance {
+            token: token.to_string(),
+            bundle: flag("bundle"),
+        },
+        ["transfer", recipient, token, amount] => Command::Transfer {
+            recipient: recipient.to_string(),
+            token: token.to_string(),
+            amount: amount_of(amount)?,
+        },
+        ["meta", "set", meta_type, meta_id, meta @ ..] if !meta.is_empty() => Command::MetaSet {
+            meta_type: meta_type.to_string(),
+            meta_id: meta_id.to_string(),
+            meta: pairs(meta)?,
+        },
+        ["meta", "get", meta_type, rest @ ..] if rest.len() <= 1 => Command::MetaGet {
+            meta_type: meta_type.to_string(),
+            meta_id: rest.first().map(|id| id.to_string()),
+            key: flag("key"),
+        },
+        ["token", "create", token, amount, meta @ ..] => Command::TokenCreate {
+            token: token.to_string(),
+            amount: amount_of(amount)?,
+            meta: pairs(meta)?,
+        },
+        ["molecule", "inspect", source] => Command::MoleculeInspect { source: source.to_string() },
+        ["molecule", "verify", source] => Command::MoleculeVerify { source: source.to_string() },
+        ["auth", "login"] => Command::AuthLogin { save: flag("save").map(PathBuf::from) },
+        _ => bail!("unrecognised command `{}` (see --help)", words.join(" ")),
+    })
+}
+
+fn amount_of(text: &str) -> Result<f64> {
+    match text.parse::<f64>() {
+        Ok(amount) if amount.is_finite() && amount > 0.0 => Ok(amount),
+        _ => bail!("invalid amount {}", text),
+    }
+}
+
+fn pairs(items: &[&str]) -> Result<Vec<(String, String)>> {
+    items.iter()
+        .map(|item| item.split_once('=')
+            .filter(|(key, _)| !key.is_empty())
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .ok_or_else(|| anyhow!("expected key=value, got {}", item)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_line(line: &str) -> Result<(Globals, Command)> {
+        parse(line.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn test_globals_anywhere() {
+        let (globals, command) = parse_line("--node http://n/graphql balance DEMO --cell c --bundle abc").unwrap();
+        assert_eq!(globals.node.as_deref(), Some("http://n/graphql"));
+        assert_eq!(globals.cell_slug.as_deref(), Some("c"));
+        assert_eq!(command, Command::Balance { token: "DEMO".to_string(), bundle: Some("abc".to_string()) });
+    }
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(parse_line("wallet create").unwrap().1, Command::WalletCreate { token: "USER".to_string() });
+        assert_eq!(
+            parse_line("transfer b DEMO 2.5").unwrap().1,
+            Command::Transfer { recipient: "b".to_string(), token: "DEMO".to_string(), amount: 2.5 }
+        );
+        assert_eq!(
+            parse_line("meta set car VIN1 color=red seats=4").unwrap().1,
+            Command::MetaSet {
+                meta_type: "car".to_string(),
+                meta_id: "VIN1".to_string(),
+                meta: vec![("color".to_string(), "red".to_string()), ("seats".to_string(), "4".to_string())],
+            }
+        );
+        assert_eq!(
+            parse_line("meta get car --key color").unwrap().1,
+            Command::MetaGet { meta_type: "car".to_string(), meta_id: None, key: Some("color".to_string()) }
+        );
+        assert_eq!(parse_line("molecule verify -").unwrap().1, Command::MoleculeVerify { source: "-".to_string() });
+        assert_eq!(parse_line("auth login --save s.json").unwrap().1, Command::AuthLogin { save: Some(PathBuf::from("s.json")) });
+        assert_eq!(parse_line("").unwrap().1, Command::Help);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(parse_line("transfer b DEMO -1").is_err());
+        assert!(parse_line("meta set car VIN1 color").is_err());
+        assert!(parse_line("balance DEMO --colour red").is_err());
+        assert!(parse_line("wallet delete").is_err());
+        assert!(parse_line("balance DEMO --node").is_err());
+    }
+}
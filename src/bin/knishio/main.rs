@@ -0,0 +1,255 @@
+/**
+ * Knish.IO command line client
+ *
+ * Wraps the common ledger operations (wallets, balances, transfers, metadata, token
+ * issuance, molecule inspection and authentication) so operators can script them
+ * without writing Rust. Every command prints JSON on stdout; failures exit non-zero.
+ *
+ * Usage:
+ *   cargo run --features cli --bin knishio -- wallet create
+ *   KNISHIO_API_URL=http://localhost:8000/graphql KNISHIO_SECRET=... \
+ *     cargo run --features cli --bin knishio -- balance DEMO
+ *   cargo run --features cli --bin knishio -- molecule verify molecule.json
+ */
+
+mod cli;
+
+use std::collections::HashMap;
+use std::env;
+use std::io::Read;
+
+use anyhow::{anyhow, bail, Context, Result};
+use knishio_client::{
+    crypto::{generate_bundle_hash, generate_random_secret},
+    CheckMolecule, KnishIOClient, KnishIOError, Molecule, Response, Wallet, WalletParams,
+};
+use serde_json::{json, Value};
+
+use cli::{Command, Globals};
+
+/// Storage key passed to the client's token save/load calls
+const SESSION_KEY: &str = "knishio-cli";
+
+fn print_json(value: &Value) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn secret_of(globals: &Globals) -> Result<String> {
+    globals.secret.clone()
+        .or_else(|| env::var("KNISHIO_SECRET").ok())
+        .ok_or_else(|| anyhow!("no secret given (use --secret or KNISHIO_SECRET)"))
+}
+
+/// Client logged in as the configured secret, reusing a saved session when given
+async fn client_for(globals: &Globals) -> Result<KnishIOClient> {
+    let node = globals.node.clone()
+        .or_else(|| env::var("KNISHIO_API_URL").ok())
+        .ok_or_else(|| anyhow!("no node given (use --node or KNISHIO_API_URL)"))?;
+    let cell_slug = globals.cell_slug.clone().or_else(|| env::var("KNISHIO_CELL_SLUG").ok());
+    let secret = secret_of(globals)?;
+
+    let mut client = KnishIOClient::new(node.as_str(), cell_slug, None, None, None, Some(false));
+    client.set_secret(secret.clone());
+    if let Some(path) = &globals.session {
+        let snapshot = std::fs::read_to_string(path)
+            .with_context(|| format!("reading session {}", path.display()))?;
+        client.load_auth_token(SESSION_KEY, &snapshot)?;
+    }
+    // Picks up a loaded, unexpired session token; authenticates otherwise
+    client.login(&secret).await?;
+    Ok(client)
+}
+
+fn response_json(response: &dyn Response) -> Value {
+    json!({
+        "success": response.success(),
+        "status": response.status(),
+        "reason": response.reason().or_else(|| response.error()),
+        "payload": response.payload(),
+    })
+}
+
+/// Print a mutation response, failing the command if the node rejected it
+fn finish(response: &dyn Response) -> Result<()> {
+    print_json(&response_json(response))?;
+    if !response.success() {
+        bail!("rejected: {}", response.reason().or_else(|| response.error()).unwrap_or_else(|| "unknown reason".to_string()));
+    }
+    Ok(())
+}
+
+fn meta_map(pairs: &[(String, String)]) -> HashMap<String, Value> {
+    pairs.iter().map(|(key, value)| (key.clone(), Value::String(value.clone()))).collect()
+}
+
+fn wallet_json(wallet: &Wallet) -> Value {
+    json!({
+        "token": wallet.token,
+        "balance": wallet.balance,
+        "bundle": wallet.bundle,
+        "address": wallet.address,
+        "position": wallet.position,
+        "batchId": wallet.batch_id,
+    })
+}
+
+fn read_source(source: &str) -> Result<String> {
+    if source == "-" {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text)?;
+        Ok(text)
+    } else {
+        std::fs::read_to_string(source).with_context(|| format!("reading {}", source))
+    }
+}
+
+/// Molecule JSON as exported by the SDK, or an unmodified node query result
+fn load_molecule(document: &Value) -> Result<Molecule> {
+    if is_server_data(document) {
+        return Ok(CheckMolecule::from_server_data(document)?);
+    }
+    let options = knishio_client::types::MoleculeFromJsonOptions {
+        include_validation_context: false,
+        validate_structure: true,
+        strict_mode: false,
+    };
+    Ok(Molecule::from_json(document, options)?)
+}
+
+/// Node query results name the token `tokenSlug` and carry metadata as `metasJson`
+fn is_server_data(document: &Value) -> bool {
+    document["atoms"].as_array()
+        .and_then(|atoms| atoms.first())
+        .is_some_and(|atom| atom.get("tokenSlug").is_some() || atom.get("metasJson").is_some())
+}
+
+fn inspect(molecule: &Molecule) -> Value {
+    let atoms: Vec<Value> = molecule.atoms.iter()
+        .map(|atom| json!({
+            "index": atom.index,
+            "isotope": atom.isotope.as_str(),
+            "token": atom.token,
+            "value": atom.value,
+            "walletAddress": atom.wallet_address,
+            "position": atom.position,
+            "metaType": atom.meta_type,
+            "metaId": atom.meta_id,
+            "signed": atom.ots_fragment.is_some(),
+        }))
+        .collect();
+
+    json!({
+        "molecularHash": molecule.molecular_hash,
+        "bundle": molecule.bundle,
+        "cellSlug": molecule.cell_slug,
+        "status": molecule.status,
+        "atomCount": atoms.len(),
+        "atoms": atoms,
+    })
+}
+
+async fn run(globals: &Globals, command: Command) -> Result<()> {
+    match command {
+        Command::Help => {
+            cli::print_usage();
+            Ok(())
+        }
+        Command::WalletCreate { token } => {
+            let (secret, generated) = match secret_of(globals) {
+                Ok(secret) => (secret, false),
+                Err(_) => (generate_random_secret()?, true),
+            };
+            let wallet = Wallet::from_params(WalletParams::new().secret(secret.as_str()).token(token))?;
+            let mut output = wallet_json(&wallet);
+            output["bundle"] = json!(generate_bundle_hash(&secret));
+            if generated {
+                output["secret"] = json!(secret);
+            }
+            print_json(&output)
+        }
+        Command::Balance { token, bundle } => {
+            let client = client_for(globals).await?;
+            // A bundle that never held the token has no wallet, and the node answers `Balance: null`
+            match client.query_balance(&token, bundle.as_deref()).await {
+                Ok(wallet) => print_json(&wallet_json(&wallet)),
+                Err(KnishIOError::InvalidResponse) => print_json(&json!({ "token": token, "balance": "0" })),
+                Err(error) => Err(error.into()),
+            }
+        }
+        Command::Transfer { recipient, token, amount } => {
+            let mut client = client_for(globals).await?;
+            let response = client.transfer_token(&recipient, &token, Some(amount), Vec::new(), None, None).await?;
+            finish(response.as_ref())
+        }
+        Command::MetaSet { meta_type, meta_id, meta } => {
+            let mut client = client_for(globals).await?;
+            let response = client.create_meta(&meta_type, &meta_id, meta_map(&meta), None).await?;
+            finish(response.as_ref())
+        }
+        Command::MetaGet { meta_type, meta_id, key } => {
+            let client = client_for(globals).await?;
+            let result = client.query_meta(&meta_type, meta_id.as_deref(), key.as_deref(), None, None).await?;
+            print_json(&result)
+        }
+        Command::TokenCreate { token, amount, meta } => {
+            let mut client = client_for(globals).await?;
+            let meta = (!meta.is_empty()).then(|| meta_map(&meta));
+            let response = client.create_token(&token, Some(amount), meta, None, Vec::new()).await?;
+            finish(response.as_ref())
+        }
+        Command::MoleculeInspect { source } => {
+            let document: Value = serde_json::from_str(&read_source(&source)?)?;
+            print_json(&inspect(&load_molecule(&document)?))
+        }
+        Command::MoleculeVerify { source } => {
+            let document: Value = serde_json::from_str(&read_source(&source)?)?;
+            let molecule = load_molecule(&document)?;
+            let result = molecule.check(None);
+            print_json(&json!({
+                "molecularHash": molecule.molecular_hash,
+                "verified": result.is_ok(),
+                "error": result.as_ref().err().map(ToString::to_string),
+            }))?;
+            result.map(|_| ()).map_err(Into::into)
+        }
+        Command::AuthLogin { save } => {
+            let client = client_for(globals).await?;
+            let token = client.get_auth_token().ok_or_else(|| anyhow!("node returned no auth token"))?;
+            match save {
+                Some(path) => {
+                    std::fs::write(&path, client.save_auth_token(SESSION_KEY)?)
+                        .with_context(|| format!("writing session {}", path.display()))?;
+                    print_json(&json!({
+                        "bundle": client.get_bundle(),
+                        "scope": token.scope().to_string(),
+                        "session": path.display().to_string(),
+                    }))
+                }
+                None => print_json(&json!({
+                    "bundle": client.get_bundle(),
+                    "scope": token.scope().to_string(),
+                    "token": token.token(),
+                    "expired": token.is_expired(),
+                })),
+            }
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let (globals, command) = match cli::parse(env::args().skip(1)) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            eprintln!("error: {:#}", error);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(error) = run(&globals, command).await {
+        eprintln!("error: {:#}", error);
+        std::process::exit(1);
+    }
+    Ok(())
+}
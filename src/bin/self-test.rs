@@ -27,7 +27,7 @@ use chrono::{DateTime, Utc};
 
 // KnishIO SDK imports
 use knishio_client::{
-    Molecule, Wallet, Atom, Isotope,
+    Molecule, MoleculeParams, Wallet, WalletParams, Atom, Isotope,
     crypto::{generate_secret, generate_bundle_hash},
     types::MetaItem,
     wallet::EncryptedMessage,
@@ -57,14 +57,12 @@ fn set_fixed_timestamps(molecule: &mut Molecule) {
 /// Helper function to create fixed remainder wallets for deterministic testing
 fn create_fixed_remainder_wallet(secret: &str, token: &str) -> Result<Wallet> {
     let bundle = generate_bundle_hash(secret);
-    Ok(Wallet::new(
-        Some(secret),
-        Some(&bundle),
-        Some(token),
-        None, // address
-        Some("bbbb000000000000cccc111111111111dddd222222222222eeee333333333333"), // Fixed deterministic position
-        None, // batch_id
-        None  // characters
+    Ok(Wallet::from_params(
+        WalletParams::new()
+            .secret(secret)
+            .bundle(&bundle)
+            .token(token)
+            .position("bbbb000000000000cccc111111111111dddd222222222222eeee333333333333"),
     )?)
 }
 
@@ -698,14 +696,12 @@ impl SelfTestRunner {
 
         // Generate secret and create source wallet
         let secret = generate_secret(&seed);
-        let source_wallet = Wallet::new(
-            Some(&secret),
-            None,
-            Some(&token),
-            None,
-            Some(&source_position),
-            None,
-            Some("BASE64"),
+        let source_wallet = Wallet::from_params(
+            WalletParams::new()
+                .secret(&secret)
+                .token(&token)
+                .position(&source_position)
+                .characters("BASE64"),
         ).context("Failed to create source wallet")?;
 
         Logger::test("Source wallet creation", true, None);
@@ -717,13 +713,11 @@ impl SelfTestRunner {
         let source_wallet_for_validation = source_wallet.clone();
 
         // Create molecule for metadata with fixed remainder wallet
-        let mut molecule = Molecule::with_params(
-            Some(secret.clone()),
-            None,
-            Some(source_wallet),
-            Some(remainder_wallet),
-            None,
-            None,
+        let mut molecule = Molecule::from_params(
+            MoleculeParams::new()
+                .secret(secret.clone())
+                .source_wallet(source_wallet)
+                .remainder_wallet(remainder_wallet),
         );
 
         // Create metadata (JavaScript compatibility)
@@ -801,14 +795,12 @@ impl SelfTestRunner {
 
         // Create source wallet
         let source_secret = generate_secret(&source_seed);
-        let mut source_wallet = Wallet::new(
-            Some(&source_secret),
-            None,
-            Some(&token),
-            None,
-            Some(&source_position),
-            None,
-            Some("BASE64"),
+        let mut source_wallet = Wallet::from_params(
+            WalletParams::new()
+                .secret(&source_secret)
+                .token(&token)
+                .position(&source_position)
+                .characters("BASE64"),
         ).context("Failed to create source wallet")?;
 
         source_wallet.set_balance_f64(balance);  // Set balance for testing
@@ -816,14 +808,12 @@ impl SelfTestRunner {
 
         // Create recipient wallet
         let recipient_secret = generate_secret(&recipient_seed);
-        let recipient_wallet = Wallet::new(
-            Some(&recipient_secret),
-            None,
-            Some(&token),
-            None,
-            Some(&recipient_position),
-            None,
-            Some("BASE64"),
+        let recipient_wallet = Wallet::from_params(
+            WalletParams::new()
+                .secret(&recipient_secret)
+                .token(&token)
+                .position(&recipient_position)
+                .characters("BASE64"),
         ).context("Failed to create recipient wallet")?;
 
         Logger::test("Recipient wallet creation", true, None);
@@ -835,13 +825,11 @@ impl SelfTestRunner {
         let source_wallet_for_validation = source_wallet.clone();
 
         // Create molecule for value transfer
-        let mut molecule = Molecule::with_params(
-            Some(source_secret.clone()),
-            None,
-            Some(source_wallet),
-            Some(remainder_wallet),
-            None,
-            None,
+        let mut molecule = Molecule::from_params(
+            MoleculeParams::new()
+                .secret(source_secret.clone())
+                .source_wallet(source_wallet)
+                .remainder_wallet(remainder_wallet),
         );
 
         // Initialize value transfer (now uses JavaScript UTXO pattern)
@@ -904,14 +892,12 @@ impl SelfTestRunner {
 
         // Create source wallet
         let source_secret = generate_secret(&source_seed);
-        let mut source_wallet = Wallet::new(
-            Some(&source_secret),
-            None,
-            Some(&token),
-            None,
-            Some(&source_position),
-            None,
-            Some("BASE64"),
+        let mut source_wallet = Wallet::from_params(
+            WalletParams::new()
+                .secret(&source_secret)
+                .token(&token)
+                .position(&source_position)
+                .characters("BASE64"),
         ).context("Failed to create source wallet")?;
 
         source_wallet.set_balance_f64(balance);
@@ -924,14 +910,12 @@ impl SelfTestRunner {
 
         // Create recipient wallet
         let recipient_secret = generate_secret(&recipient_seed);
-        let recipient_wallet = Wallet::new(
-            Some(&recipient_secret),
-            None,
-            Some(&token),
-            None,
-            Some(&recipient_position),
-            None,
-            Some("BASE64"),
+        let recipient_wallet = Wallet::from_params(
+            WalletParams::new()
+                .secret(&recipient_secret)
+                .token(&token)
+                .position(&recipient_position)
+                .characters("BASE64"),
         ).context("Failed to create recipient wallet")?;
 
         Logger::test("Recipient wallet creation", true, None);
@@ -939,13 +923,11 @@ impl SelfTestRunner {
         // Create molecule for value transfer with remainder
         // Clone source wallet to keep a reference for validation
         let source_wallet_for_validation = source_wallet.clone();
-        let mut molecule = Molecule::with_params(
-            Some(source_secret.clone()),
-            None,
-            Some(source_wallet),
-            Some(remainder_wallet),
-            None,
-            None,
+        let mut molecule = Molecule::from_params(
+            MoleculeParams::new()
+                .secret(source_secret.clone())
+                .source_wallet(source_wallet)
+                .remainder_wallet(remainder_wallet),
         );
 
         // Initialize value transfer with remainder (JavaScript UTXO pattern)
@@ -1011,27 +993,23 @@ impl SelfTestRunner {
 
         // Create source wallet (USER token)
         let source_secret = generate_secret(&source_seed);
-        let source_wallet = Wallet::new(
-            Some(&source_secret),
-            None,
-            Some(&source_token),
-            None,
-            Some(&source_position),
-            None,
-            Some("BASE64"),
+        let source_wallet = Wallet::from_params(
+            WalletParams::new()
+                .secret(&source_secret)
+                .token(&source_token)
+                .position(&source_position)
+                .characters("BASE64"),
         ).context("Failed to create source wallet")?;
         Logger::test("Source wallet creation", true, None);
 
         // Create recipient wallet for the new token
         let recipient_secret = generate_secret(&recipient_seed);
-        let recipient_wallet = Wallet::new(
-            Some(&recipient_secret),
-            None,
-            Some(&new_token),
-            None,
-            Some(&recipient_position),
-            None,
-            Some("BASE64"),
+        let recipient_wallet = Wallet::from_params(
+            WalletParams::new()
+                .secret(&recipient_secret)
+                .token(&new_token)
+                .position(&recipient_position)
+                .characters("BASE64"),
         ).context("Failed to create recipient wallet")?;
         Logger::test("Recipient wallet creation", true, None);
 
@@ -1040,13 +1018,11 @@ impl SelfTestRunner {
         Logger::test("Remainder wallet creation", true, None);
 
         let source_wallet_for_validation = source_wallet.clone();
-        let mut molecule = Molecule::with_params(
-            Some(source_secret.clone()),
-            None,
-            Some(source_wallet),
-            Some(remainder_wallet),
-            None,
-            None,
+        let mut molecule = Molecule::from_params(
+            MoleculeParams::new()
+                .secret(source_secret.clone())
+                .source_wallet(source_wallet)
+                .remainder_wallet(remainder_wallet),
         );
 
         // User token meta in JS insertion order (name, fungibility, supply, decimals)
@@ -1108,14 +1084,22 @@ impl SelfTestRunner {
         let new_wallet_position = self.config.get_string("tests.walletCreation.newWalletPosition").unwrap_or_default();
 
         let source_secret = generate_secret(&source_seed);
-        let source_wallet = Wallet::new(
-            Some(&source_secret), None, Some(&source_token), None, Some(&source_position), None, Some("BASE64"),
+        let source_wallet = Wallet::from_params(
+            WalletParams::new()
+                .secret(&source_secret)
+                .token(&source_token)
+                .position(&source_position)
+                .characters("BASE64"),
         ).context("Failed to create source wallet")?;
         Logger::test("Source wallet creation", true, None);
 
         let new_secret = generate_secret(&new_wallet_seed);
-        let new_wallet = Wallet::new(
-            Some(&new_secret), None, Some(&new_token), None, Some(&new_wallet_position), None, Some("BASE64"),
+        let new_wallet = Wallet::from_params(
+            WalletParams::new()
+                .secret(&new_secret)
+                .token(&new_token)
+                .position(&new_wallet_position)
+                .characters("BASE64"),
         ).context("Failed to create new wallet")?;
         Logger::test("New wallet creation", true, None);
 
@@ -1123,8 +1107,11 @@ impl SelfTestRunner {
         Logger::test("Remainder wallet creation", true, None);
 
         let source_wallet_for_validation = source_wallet.clone();
-        let mut molecule = Molecule::with_params(
-            Some(source_secret.clone()), None, Some(source_wallet), Some(remainder_wallet), None, None,
+        let mut molecule = Molecule::from_params(
+            MoleculeParams::new()
+                .secret(source_secret.clone())
+                .source_wallet(source_wallet)
+                .remainder_wallet(remainder_wallet),
         );
 
         molecule.init_wallet_creation(&new_wallet, vec![])
@@ -1174,14 +1161,22 @@ impl SelfTestRunner {
         let claim_position = self.config.get_string("tests.shadowWalletClaim.claimPosition").unwrap_or_default();
 
         let source_secret = generate_secret(&source_seed);
-        let source_wallet = Wallet::new(
-            Some(&source_secret), None, Some(&source_token), None, Some(&source_position), None, Some("BASE64"),
+        let source_wallet = Wallet::from_params(
+            WalletParams::new()
+                .secret(&source_secret)
+                .token(&source_token)
+                .position(&source_position)
+                .characters("BASE64"),
         ).context("Failed to create source wallet")?;
         Logger::test("Source wallet creation", true, None);
 
         let claim_secret = generate_secret(&claim_seed);
-        let claim_wallet = Wallet::new(
-            Some(&claim_secret), None, Some(&claim_token), None, Some(&claim_position), None, Some("BASE64"),
+        let claim_wallet = Wallet::from_params(
+            WalletParams::new()
+                .secret(&claim_secret)
+                .token(&claim_token)
+                .position(&claim_position)
+                .characters("BASE64"),
         ).context("Failed to create claim wallet")?;
         Logger::test("Claim wallet creation", true, None);
 
@@ -1189,8 +1184,11 @@ impl SelfTestRunner {
         Logger::test("Remainder wallet creation", true, None);
 
         let source_wallet_for_validation = source_wallet.clone();
-        let mut molecule = Molecule::with_params(
-            Some(source_secret.clone()), None, Some(source_wallet), Some(remainder_wallet), None, None,
+        let mut molecule = Molecule::from_params(
+            MoleculeParams::new()
+                .secret(source_secret.clone())
+                .source_wallet(source_wallet)
+                .remainder_wallet(remainder_wallet),
         );
 
         molecule.init_shadow_wallet_claim(&claim_wallet)
@@ -1249,14 +1247,12 @@ impl SelfTestRunner {
             let secret = generate_secret(&seed);
             let _bundle = generate_bundle_hash(&secret);
 
-            let encryption_wallet = Wallet::new(
-                Some(&secret),
-                None,
-                Some(&token),
-                None,
-                Some(&position),
-                None,
-                Some("BASE64"),
+            let encryption_wallet = Wallet::from_params(
+                WalletParams::new()
+                    .secret(&secret)
+                    .token(&token)
+                    .position(&position)
+                    .characters("BASE64"),
             ).context("Failed to create encryption wallet")?;
 
             Logger::test("Encryption wallet creation", true, None);
@@ -1342,26 +1338,21 @@ impl SelfTestRunner {
             let secret = generate_secret(&seed);
             let bundle = generate_bundle_hash(&secret);
 
-            let mut source_wallet = Wallet::new(
-                Some(&secret),
-                None,
-                Some("TEST"),
-                None,
-                Some("0123456789abcdeffedcba9876543210fedcba9876543210fedcba9876543210"),
-                None,
-                None
+            let mut source_wallet = Wallet::from_params(
+                WalletParams::new()
+                    .secret(&secret)
+                    .token("TEST")
+                    .position("0123456789abcdeffedcba9876543210fedcba9876543210fedcba9876543210"),
             )?;
             source_wallet.balance = "1000".to_string();
 
             // Test 1: Missing Molecular Hash (should fail)
             {
-                let mut invalid_molecule = Molecule::with_params(
-                    Some(secret.clone()),
-                    Some(bundle.clone()),
-                    Some(source_wallet.clone()),
-                    None,
-                    None,
-                    None,
+                let mut invalid_molecule = Molecule::from_params(
+                    MoleculeParams::new()
+                        .secret(secret.clone())
+                        .bundle(bundle.clone())
+                        .source_wallet(source_wallet.clone()),
                 );
 
                 // Add a valid atom but don't sign (no molecular hash)
@@ -1398,13 +1389,11 @@ impl SelfTestRunner {
 
             // Test 2: Invalid Molecular Hash (should fail)
             {
-                let mut invalid_molecule = Molecule::with_params(
-                    Some(secret.clone()),
-                    Some(bundle.clone()),
-                    Some(source_wallet.clone()),
-                    None,
-                    None,
-                    None,
+                let mut invalid_molecule = Molecule::from_params(
+                    MoleculeParams::new()
+                        .secret(secret.clone())
+                        .bundle(bundle.clone())
+                        .source_wallet(source_wallet.clone()),
                 );
 
                 let atom = Atom {
@@ -1445,13 +1434,11 @@ impl SelfTestRunner {
 
             // Test 3: Unbalanced Transfer (should fail)
             {
-                let mut invalid_molecule = Molecule::with_params(
-                    Some(secret.clone()),
-                    Some(bundle.clone()),
-                    Some(source_wallet.clone()),
-                    None,
-                    None,
-                    None,
+                let mut invalid_molecule = Molecule::from_params(
+                    MoleculeParams::new()
+                        .secret(secret.clone())
+                        .bundle(bundle.clone())
+                        .source_wallet(source_wallet.clone()),
                 );
 
                 // Create unbalanced atoms (doesn't sum to zero)
@@ -1688,14 +1675,12 @@ impl SelfTestRunner {
         let position = test_config["position"].as_str().unwrap_or("1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef");
 
         let secret = generate_secret(seed);
-        let our_wallet = Wallet::new(
-            Some(&secret),
-            None,
-            Some(token),
-            None,
-            Some(position),
-            None,
-            Some("BASE64"),
+        let our_wallet = Wallet::from_params(
+            WalletParams::new()
+                .secret(&secret)
+                .token(token)
+                .position(position)
+                .characters("BASE64"),
         )?;
 
         // STRONG cross-SDK check (cycle 138): decrypt THEIR encryptedData with our TESTSEED
@@ -0,0 +1,504 @@
+//! Node certification suite
+//!
+//! Enabled with the `certification` feature. `CertificationSuite::generate` builds a
+//! fixed list of signed molecules, one per case, covering every isotope and the edge
+//! cases nodes disagree on: compressed and hex signatures, empty, unicode and oversized
+//! metadata, amounts beyond 64 bits, forged signatures and tampered molecules. `run`
+//! proposes the cases in order and returns a `CertificationReport`.
+//!
+//! A case's expectation is not hand-written: it is the verdict of the JavaScript
+//! `CheckMolecule` ruleset (`CheckMolecule::verify`) on the molecule, with the sender
+//! wallet holding what the node holds, and the failing rule is kept as the case's
+//! `basis`. Molecules that pass but carry an isotope those rules say nothing about (B, F,
+//! P and A) are `Unspecified`: the node's verdict is reported but not judged.
+//!
+//! Molecules are derived from a seed with the `testkit` fixtures, so the suite needs one
+//! thing from the operator: `CertificationSuite::funding_wallet(seed, token)` must hold
+//! `balance` of `token` before the run. Accepted cases spend that wallet and advance the
+//! seed's ContinuID, so a seed certifies a node once; use a fresh seed for the next run.
+//!
+//! ```no_run
+//! # async fn demo(client: &mut knishio_client::KnishIOClient) -> knishio_client::Result<()> {
+//! use knishio_client::certification::CertificationSuite;
+//!
+//! let seed = "certification-2026-10";
+//! println!("fund {:?}", CertificationSuite::funding_wallet(seed, "CERT")?.address);
+//!
+//! let report = CertificationSuite::generate(seed, "CERT", 10.0)?.run(client).await;
+//! println!("{}", report.summary());
+//! assert!(report.is_certified());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::atom::{Atom, AtomCreateParams, WalletInfo};
+use crate::check_molecule::{CheckMolecule, ValidatorRegistry};
+use crate::client::KnishIOClient;
+use crate::crypto::shake256;
+use crate::error::{KnishIOError, Result};
+use crate::molecule::Molecule;
+use crate::testkit::{FixtureMolecule, FixtureWallet};
+use crate::types::{Isotope, MetaItem};
+use crate::wallet::Wallet;
+use std::collections::HashMap;
+
+/// Smallest funding balance `generate` accepts: three accepted cases spend one unit each
+pub const CERTIFICATION_MIN_BALANCE: f64 = 3.0;
+
+/// Length of the oversized metadata value, in bytes
+pub const HUGE_META_BYTES: usize = 64 * 1024;
+
+/// Position index of the wallet created by the `wallet-creation` case
+const CREATED_WALLET_INDEX: u32 = 1000;
+
+/// Host named by the `peering` case; `.invalid` never resolves
+const PEER_HOST: &str = "certification.invalid";
+
+/// What a conforming node does with a case
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expectation {
+    Accept,
+    Reject,
+    /// No client-side rule decides the case; any verdict passes
+    Unspecified,
+}
+
+/// One generated molecule and the verdict a conforming node reaches on it
+#[derive(Debug, Clone)]
+pub struct CertificationCase {
+    /// Short stable identifier, e.g. `meta-unicode`
+    pub name: &'static str,
+    /// What the case exercises
+    pub description: &'static str,
+    /// Expected verdict
+    pub expect: Expectation,
+    /// Why: the `CheckMolecule` error for `Reject`, the unchecked isotopes for `Unspecified`
+    pub basis: String,
+    /// The signed molecule to propose
+    pub molecule: Molecule,
+}
+
+impl CertificationCase {
+    /// Case whose expectation is what `CheckMolecule` says of `molecule` sent from `sender`
+    fn new(name: &'static str, description: &'static str, molecule: Molecule, sender: Option<&Wallet>) -> Self {
+        let verified = CheckMolecule::new(&molecule).and_then(|check| check.verify(sender));
+        let rules = ValidatorRegistry::default();
+        let unchecked: Vec<Isotope> = isotopes_of(&molecule)
+            .into_iter()
+            .filter(|isotope| rules.validators_for(*isotope).next().is_none() && !matches!(isotope, Isotope::I))
+            .collect();
+
+        let (expect, basis) = match verified {
+            Err(e) => (Expectation::Reject, e.to_string().lines().next().unwrap_or_default().to_string()),
+            Ok(_) if !unchecked.is_empty() => (Expectation::Unspecified, format!("no CheckMolecule rule for {:?}", unchecked)),
+            Ok(_) => (Expectation::Accept, "passes CheckMolecule".to_string()),
+        };
+        CertificationCase { name, description, expect, basis, molecule }
+    }
+
+    /// Isotopes of the molecule's atoms, in first-seen order
+    pub fn isotopes(&self) -> Vec<Isotope> {
+        isotopes_of(&self.molecule)
+    }
+}
+
+fn isotopes_of(molecule: &Molecule) -> Vec<Isotope> {
+    let mut isotopes = Vec::new();
+    for atom in &molecule.atoms {
+        if !isotopes.contains(&atom.isotope) {
+            isotopes.push(atom.isotope);
+        }
+    }
+    isotopes
+}
+
+/// What the node did with a case
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Accepted,
+    /// Rejected, with the node's reason
+    Rejected(String),
+    /// The proposal did not get a verdict, e.g. a transport error
+    Failed(String),
+}
+
+/// Outcome of one case
+#[derive(Debug, Clone)]
+pub struct CaseOutcome {
+    pub name: &'static str,
+    pub expect: Expectation,
+    pub verdict: Verdict,
+}
+
+impl CaseOutcome {
+    /// True when the node reached the expected verdict
+    pub fn passed(&self) -> bool {
+        matches!(
+            (&self.verdict, self.expect),
+            (Verdict::Accepted, Expectation::Accept)
+                | (Verdict::Rejected(_), Expectation::Reject)
+                | (Verdict::Accepted | Verdict::Rejected(_), Expectation::Unspecified)
+        )
+    }
+}
+
+/// Outcomes of a suite run against one node
+#[derive(Debug, Clone, Default)]
+pub struct CertificationReport {
+    /// Node URI
+    pub node: String,
+    /// One outcome per case, in run order
+    pub outcomes: Vec<CaseOutcome>,
+}
+
+impl CertificationReport {
+    /// Number of cases with the expected verdict
+    pub fn passed(&self) -> usize {
+        self.outcomes.iter().filter(|outcome| outcome.passed()).count()
+    }
+
+    /// Cases with an unexpected verdict or none
+    pub fn failures(&self) -> Vec<&CaseOutcome> {
+        self.outcomes.iter().filter(|outcome| !outcome.passed()).collect()
+    }
+
+    /// True when every case reached its expected verdict
+    pub fn is_certified(&self) -> bool {
+        !self.outcomes.is_empty() && self.failures().is_empty()
+    }
+
+    /// One line per failure after a count, e.g. for a CI log
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{}: {}/{} cases as expected", self.node, self.passed(), self.outcomes.len());
+        for failure in self.failures() {
+            let verdict = match &failure.verdict {
+                Verdict::Accepted => "accepted".to_string(),
+                Verdict::Rejected(reason) => format!("rejected ({})", reason),
+                Verdict::Failed(error) => format!("failed ({})", error),
+            };
+            summary.push_str(&format!("\n  {}: expected {:?}, {}", failure.name, failure.expect, verdict));
+        }
+        summary
+    }
+}
+
+/// The generated cases for one seed and funding token
+#[derive(Debug, Clone)]
+pub struct CertificationSuite {
+    seed: String,
+    token: String,
+    cases: Vec<CertificationCase>,
+}
+
+impl CertificationSuite {
+    /// The wallet the operator funds before running the suite for `seed`
+    pub fn funding_wallet(seed: &str, token: &str) -> Result<Wallet> {
+        Ok(FixtureWallet::for_seed(seed, token)?.wallet)
+    }
+
+    /// Build every case for `seed`, whose funding wallet holds `balance` of `token`
+    ///
+    /// # Errors
+    ///
+    /// `InvalidAmount` for a balance under `CERTIFICATION_MIN_BALANCE`; otherwise
+    /// whatever molecule construction or signing reports
+    pub fn generate(seed: &str, token: &str, balance: f64) -> Result<Self> {
+        if balance < CERTIFICATION_MIN_BALANCE {
+            return Err(KnishIOError::InvalidAmount(format!(
+                "Certification needs a funding balance of at least {}, got {}",
+                CERTIFICATION_MIN_BALANCE, balance
+            )));
+        }
+
+        let mut cases = value_cases(seed, token, balance)?;
+        cases.extend(user_cases(seed, token)?);
+        Ok(CertificationSuite { seed: seed.to_string(), token: token.to_string(), cases })
+    }
+
+    /// Seed the molecules are derived from
+    pub fn seed(&self) -> &str {
+        &self.seed
+    }
+
+    /// Token of the funding wallet
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// The cases, in run order
+    pub fn cases(&self) -> &[CertificationCase] {
+        &self.cases
+    }
+
+    /// Propose every case to the node behind `client` and record its verdict
+    ///
+    /// Cases run in order; an accepted case whose predecessor was wrongly rejected
+    /// usually fails too, since it spends that predecessor's remainder.
+    pub async fn run(&self, client: &mut KnishIOClient) -> CertificationReport {
+        let mut report = CertificationReport { node: client.get_uri().unwrap_or_default(), outcomes: Vec::new() };
+
+        for case in &self.cases {
+            let verdict = match client.propose_molecule(case.molecule.clone()).await {
+                Ok(response) if response.success() => Verdict::Accepted,
+                Ok(response) => Verdict::Rejected(response.reason().unwrap_or_default()),
+                Err(e) => Verdict::Failed(e.to_string()),
+            };
+            client.log("info", &format!(
+                "CertificationSuite::run() - {}: expected {:?}, got {:?}",
+                case.name, case.expect, verdict
+            ));
+            report.outcomes.push(CaseOutcome { name: case.name, expect: case.expect, verdict });
+        }
+        report
+    }
+}
+
+/// V, B and F cases spending the funding wallet; each accepted case moves to the next position
+fn value_cases(seed: &str, token: &str, balance: f64) -> Result<Vec<CertificationCase>> {
+    let recipient = FixtureWallet::for_seed(&format!("{}-recipient", seed), token)?.wallet;
+    let funded = |index: u32, balance: f64| -> Result<FixtureWallet> {
+        let mut fixture = FixtureWallet::at(seed, token, index)?;
+        fixture.wallet.set_balance_f64(balance);
+        Ok(fixture)
+    };
+    // What the node holds at `index` once the accepted cases before it are in
+    let held = |index: u32| -> Result<Wallet> { Ok(funded(index, balance - f64::from(index))?.wallet) };
+    let unsigned_transfer = |from: &FixtureWallet, amount: f64| -> Result<Molecule> {
+        let mut molecule = from.molecule(from.wallet.clone(), from.remainder()?);
+        molecule.init_value(&recipient, amount)?;
+        Ok(molecule)
+    };
+
+    let transfer = FixtureMolecule::transfer(&funded(0, balance)?, &recipient, 1.0)?;
+
+    let mut unbalanced = unsigned_transfer(&funded(1, balance - 1.0)?, 1.0)?;
+    unbalanced.atoms[1].value = Some("2".to_string());
+    unbalanced.sign(None, false, true)?;
+
+    // Built against a balance the wallet does not have
+    let overdraw = FixtureMolecule::transfer(&funded(1, balance + 1_000_000.0)?, &recipient, 1_000_000.0)?;
+
+    // 2^64: past every 64-bit integer type a node might parse amounts into, so a node
+    // that wraps it around sees a small debit it can cover
+    let huge = 18_446_744_073_709_551_616.0;
+    let huge_value = FixtureMolecule::transfer(&funded(1, huge)?, &recipient, huge)?;
+
+    let mut uncompressed = unsigned_transfer(&funded(1, balance - 1.0)?, 1.0)?;
+    uncompressed.sign(None, false, false)?;
+
+    let mut forged = FixtureMolecule::transfer(&funded(2, balance - 2.0)?, &recipient, 1.0)?;
+    if let Some(fragment) = forged.atoms[0].ots_fragment.as_mut() {
+        let flipped = if fragment.starts_with('A') { "B" } else { "A" };
+        fragment.replace_range(..1, flipped);
+    }
+
+    let depositor = funded(2, balance - 2.0)?;
+    let mut deposit = depositor.molecule(depositor.wallet.clone(), depositor.remainder()?);
+    deposit.init_deposit_buffer(1.0, HashMap::new())?;
+    deposit.sign(None, false, true)?;
+
+    // Fuses two units of a wallet the node never funded
+    let fuser = FixtureWallet::at(&format!("{}-fusion", seed), token, 0)?;
+    let mut fuser_wallet = fuser.wallet.clone();
+    fuser_wallet.set_balance_f64(2.0);
+    let fused = FixtureWallet::for_seed(&format!("{}-fusion", seed), &format!("{}F", token))?.wallet;
+    let mut fusion = fuser.molecule(fuser_wallet, fuser.remainder()?);
+    fusion.fuse_token(vec!["unit-1".to_string(), "unit-2".to_string()], &fused)?;
+    fusion.sign(None, false, true)?;
+
+    Ok(vec![
+        CertificationCase::new("value-transfer", "V transfer with a compressed signature", transfer, Some(&held(0)?)),
+        CertificationCase::new("value-unbalanced", "V atoms that do not sum to zero", unbalanced, Some(&held(1)?)),
+        CertificationCase::new("value-overdraw", "V debit above the wallet's balance", overdraw, Some(&held(1)?)),
+        CertificationCase::new("value-huge", "V amounts of 2^64, beyond 64-bit integers", huge_value, Some(&held(1)?)),
+        CertificationCase::new("value-uncompressed", "V transfer with a hexadecimal signature", uncompressed, Some(&held(1)?)),
+        CertificationCase::new("signature-forged", "V transfer whose signature was altered", forged, Some(&held(2)?)),
+        CertificationCase::new("buffer-deposit", "V debit into a B buffer wallet", deposit, Some(&held(2)?)),
+        CertificationCase::new("fusion-unfunded", "V debit of an empty wallet into an F fusion", fusion, Some(&fuser.wallet)),
+    ])
+}
+
+/// USER-signed cases; each accepted case moves the ContinuID to the next USER position
+fn user_cases(seed: &str, token: &str) -> Result<Vec<CertificationCase>> {
+    let owner = |index: u32| FixtureWallet::at(seed, "USER", index);
+    let user_molecule = |index: u32| -> Result<Molecule> {
+        let from = owner(index)?;
+        Ok(from.molecule(from.wallet.clone(), owner(index + 1)?.wallet))
+    };
+
+    let meta = FixtureMolecule::meta(&owner(0)?, "certification", seed, vec![MetaItem::new("case", "meta")])?;
+
+    let unicode = FixtureMolecule::meta(&owner(1)?, "certification", seed, vec![
+        MetaItem::new("名前", "ノード認証"),
+        MetaItem::new("emoji", "✅🚀🧪"),
+        MetaItem::new("rtl", "مرحبا بالعالم"),
+        MetaItem::new("combining", "e\u{301}te\u{301}"),
+    ])?;
+
+    let oversized = "x".repeat(HUGE_META_BYTES);
+    let huge_meta = FixtureMolecule::meta(&owner(2)?, "certification", seed, vec![MetaItem::new("blob", oversized.as_str())])?;
+
+    let empty_meta = FixtureMolecule::meta(&owner(3)?, "certification", seed, Vec::new())?;
+
+    let mut tampered = FixtureMolecule::meta(&owner(3)?, "certification", seed, vec![MetaItem::new("case", "tampered")])?;
+    tampered.atoms[0].meta_id = Some(format!("{}-tampered", seed));
+
+    let created = FixtureWallet::at(seed, token, CREATED_WALLET_INDEX)?.wallet;
+    let mut wallet_creation = user_molecule(3)?;
+    wallet_creation.init_wallet_creation(&created, Vec::new())?;
+    wallet_creation.sign(None, false, true)?;
+
+    let slug = format!("C{}", shake256(seed, 8).to_uppercase());
+    let issued = FixtureWallet::for_seed(seed, &slug)?.wallet;
+    let mut token_creation = user_molecule(4)?;
+    token_creation.init_token_creation(&issued, 1000.0, vec![
+        MetaItem::new("name", "Certification token"),
+        MetaItem::new("fungibility", "fungible"),
+        MetaItem::new("supply", "limited"),
+        MetaItem::new("decimals", "0"),
+    ])?;
+    token_creation.sign(None, false, true)?;
+
+    let mut rule = user_molecule(5)?;
+    rule.create_rule("certification", seed, r#"[{"condition":[],"callback":[]}]"#, None)?;
+    rule.sign(None, false, true)?;
+
+    let mut token_request = user_molecule(6)?;
+    token_request.init_token_request(token, 1.0, "walletBundle", &owner(0)?.bundle(), Vec::new(), None)?;
+    // init_token_request leaves the ContinuID atom a USER-signed molecule needs to the caller
+    token_request.add_continuid_atom()?;
+    token_request.sign(None, false, true)?;
+
+    let signer = FixtureWallet::at(seed, "AUTH", 0)?;
+    let mut authorization = signer.molecule(signer.wallet.clone(), owner(8)?.wallet);
+    authorization.init_authorization(Vec::new())?;
+    authorization.sign(None, false, true)?;
+
+    // Peering and append requests have no SDK constructor; each gets a seed of its own
+    // so that whatever the node decides cannot move the ContinuID of the cases above
+    let peering = user_atom(&format!("{}-peer", seed), Isotope::P, "peer", PEER_HOST, vec![MetaItem::new("peerHost", PEER_HOST)])?;
+    let append = user_atom(&format!("{}-append", seed), Isotope::A, "certification", seed, vec![MetaItem::new("case", "append")])?;
+
+    Ok(vec![
+        CertificationCase::new("meta", "M metadata with a ContinuID I atom", meta, None),
+        CertificationCase::new("meta-unicode", "M metadata with non-ASCII keys and values", unicode, None),
+        CertificationCase::new("meta-huge", "M metadata with a 64 KiB value", huge_meta, None),
+        CertificationCase::new("meta-empty", "M atom without metadata", empty_meta, None),
+        CertificationCase::new("meta-tampered", "M atom changed after signing", tampered, None),
+        CertificationCase::new("wallet-creation", "C wallet creation", wallet_creation, None),
+        CertificationCase::new("token-creation", "C token creation", token_creation, None),
+        CertificationCase::new("rule", "R rule with a ContinuID I atom", rule, None),
+        CertificationCase::new("token-request", "T token request", token_request, None),
+        CertificationCase::new("authorization", "U authorization request", authorization, None),
+        CertificationCase::new("peering", "P peering request", peering, None),
+        CertificationCase::new("append", "A append request", append, None),
+    ])
+}
+
+/// Signed molecule of `seed`'s first USER wallet with one `isotope` atom and a ContinuID atom
+fn user_atom(seed: &str, isotope: Isotope, meta_type: &str, meta_id: &str, meta: Vec<MetaItem>) -> Result<Molecule> {
+    let owner = FixtureWallet::at(seed, "USER", 0)?;
+    let mut molecule = owner.molecule(owner.wallet.clone(), FixtureWallet::at(seed, "USER", 1)?.wallet);
+    molecule.add_atom(Atom::create(AtomCreateParams {
+        isotope,
+        wallet_info: Some(WalletInfo {
+            position: owner.wallet.position.clone().unwrap_or_default(),
+            address: owner.wallet.address.clone().unwrap_or_default(),
+            token: owner.wallet.token.clone(),
+            batch_id: owner.wallet.batch_id.clone(),
+        }),
+        meta_type: Some(meta_type.to_string()),
+        meta_id: Some(meta_id.to_string()),
+        meta: Some(meta),
+        ..Default::default()
+    }));
+    molecule.add_continuid_atom()?;
+    molecule.sign(None, false, true)?;
+    Ok(molecule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_ledger::TestLedger;
+    use crate::testkit;
+
+    #[test]
+    fn test_generated_cases() {
+        let suite = CertificationSuite::generate("certification-cases", "CERT", 10.0).unwrap();
+        let names: Vec<_> = suite.cases().iter().map(|case| case.name).collect();
+        let mut unique = names.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(names.len(), unique.len());
+
+        let isotopes: Vec<_> = suite.cases().iter().map(CertificationCase::isotopes).collect();
+        for expected in [
+            vec![Isotope::V],
+            vec![Isotope::V, Isotope::B],
+            vec![Isotope::V, Isotope::F],
+            vec![Isotope::M, Isotope::I],
+            vec![Isotope::C, Isotope::I],
+            vec![Isotope::R, Isotope::I],
+            vec![Isotope::T, Isotope::I],
+            vec![Isotope::U, Isotope::I],
+            vec![Isotope::P, Isotope::I],
+            vec![Isotope::A, Isotope::I],
+        ] {
+            assert!(isotopes.contains(&expected), "{:?}", expected);
+        }
+
+        // Expectations come from CheckMolecule, with the failing rule as the basis
+        let case = |name: &str| suite.cases().iter().find(|case| case.name == name).unwrap();
+        for (name, rejection) in [
+            ("value-transfer", None),
+            ("value-unbalanced", Some(KnishIOError::TransferUnbalanced)),
+            ("value-overdraw", Some(KnishIOError::TransferBalance)),
+            ("signature-forged", Some(KnishIOError::SignatureMismatch)),
+            ("meta-empty", Some(KnishIOError::MetaMissing)),
+            ("meta-tampered", Some(KnishIOError::MolecularHashMismatch)),
+            ("rule", None),
+            ("token-request", None),
+        ] {
+            match rejection {
+                Some(error) => {
+                    assert_eq!(case(name).expect, Expectation::Reject, "{}", name);
+                    assert_eq!(case(name).basis, error.to_string(), "{}", name);
+                }
+                None => assert_eq!(case(name).expect, Expectation::Accept, "{}: {}", name, case(name).basis),
+            }
+        }
+        assert_eq!(case("value-huge").expect, Expectation::Reject);
+        for name in ["buffer-deposit", "fusion-unfunded", "peering", "append"] {
+            assert_eq!(case(name).expect, Expectation::Unspecified, "{}: {}", name, case(name).basis);
+        }
+
+        let unspecified = CaseOutcome { name: "peering", expect: Expectation::Unspecified, verdict: Verdict::Rejected("unknown isotope".to_string()) };
+        assert!(unspecified.passed());
+        assert!(!CaseOutcome { verdict: Verdict::Failed("timeout".to_string()), ..unspecified }.passed());
+        assert!(suite.cases().iter().all(|case| case.molecule.molecular_hash.is_some()));
+
+        assert!(matches!(
+            CertificationSuite::generate("certification-cases", "CERT", 2.0),
+            Err(KnishIOError::InvalidAmount(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_certify_test_ledger() {
+        let ledger = TestLedger::start().await.unwrap();
+        let seed = "certification-ledger";
+        ledger.fund_wallet(&CertificationSuite::funding_wallet(seed, "CERT").unwrap(), 10.0);
+
+        let mut client = ledger.client(&testkit::secret(seed));
+        let report = CertificationSuite::generate(seed, "CERT", 10.0).unwrap().run(&mut client).await;
+        assert!(report.is_certified(), "{}", report.summary());
+        assert_eq!(report.outcomes.len(), ledger.molecules().len());
+        assert_eq!(ledger.balance(&testkit::bundle(seed), "CERT"), 7.0);
+
+        // Spent wallets and a moved ContinuID: a second run is rejected throughout
+        let rerun = CertificationSuite::generate(seed, "CERT", 10.0).unwrap().run(&mut client).await;
+        assert!(!rerun.is_certified());
+        assert!(rerun.summary().contains("value-transfer: expected Accept, rejected"));
+    }
+}
@@ -2,16 +2,22 @@
 //!
 //! This module contains the CheckMolecule implementation that provides comprehensive
 //! validation of molecular transactions, ensuring exact compatibility with the
-//! JavaScript SDK's CheckMolecule.js class.
+//! JavaScript SDK's CheckMolecule.js class. Isotope-specific rules live in
+//! [`validators`] and can be swapped per `CheckMolecule` with `with_validators`.
+
+pub mod validators;
+
+pub use validators::{
+    AuthorizationValidator, BufferValidator, CreationValidator, IdentityValidator, IsotopeValidator, MetaValidator,
+    PolicyValidator, RuleValidator, TokenRequestValidator, ValidationContext, ValidatorRegistry, ValueValidator,
+};
 
 use crate::atom::Atom;
 use crate::molecule::Molecule;
 use crate::wallet::Wallet;
-use crate::types::Isotope;
+use crate::types::{Isotope, MAX_EXPONENT};
 use crate::error::{KnishIOError, Result};
-use crate::meta::Meta;
-use crate::crypto::shake256;
-use crate::rules::Rule;
+use crate::crypto::{hash_chains, shake256};
 use serde::{Serialize, Deserialize};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -44,6 +50,7 @@ pub struct IntegrityReport {
 #[derive(Debug)]
 pub struct CheckMolecule<'a> {
     molecule: &'a Molecule,
+    validators: &'a ValidatorRegistry,
 }
 
 impl<'a> CheckMolecule<'a> {
@@ -78,7 +85,13 @@ impl<'a> CheckMolecule<'a> {
             }
         }
 
-        Ok(CheckMolecule { molecule })
+        Ok(CheckMolecule { molecule, validators: &validators::DEFAULT_VALIDATORS })
+    }
+
+    /// Validate isotopes with `registry` instead of the JavaScript-parity ruleset
+    pub fn with_validators(mut self, registry: &'a ValidatorRegistry) -> Self {
+        self.validators = registry;
+        self
     }
 
     /// Comprehensive verification of the molecule
@@ -94,18 +107,22 @@ impl<'a> CheckMolecule<'a> {
     /// True if all validations pass, error otherwise
     pub fn verify(&self, sender_wallet: Option<&Wallet>) -> Result<bool> {
         // Run all validation checks in order (matching JS CheckMolecule.verify)
+        self.atom_index()?;
         self.molecular_hash()?;
         self.ots()?;
         self.batch_id()?;
         self.continu_id()?;
-        self.isotope_m()?;
-        self.isotope_t()?;
-        self.isotope_c()?;
-        self.isotope_u()?;
-        self.isotope_i()?;
-        self.isotope_r()?;
-        self.isotope_v(sender_wallet)?;
+        self.validators.validate(&ValidationContext::new(self.molecule, sender_wallet))?;
+
+        Ok(true)
+    }
 
+    /// Validate that atom indices are contiguous from 0 in atom order
+    ///
+    /// Not part of the JavaScript CheckMolecule: the hash is computed over index-sorted
+    /// atoms, so gaps, duplicates or misordered atoms would otherwise still verify.
+    fn atom_index(&self) -> Result<bool> {
+        self.molecule.check_atom_indices()?;
         Ok(true)
     }
 
@@ -115,7 +132,7 @@ impl<'a> CheckMolecule<'a> {
     fn continu_id(&self) -> Result<bool> {
         let first_atom = &self.molecule.atoms[0];
 
-        if first_atom.token == "USER" && self.get_isotopes(&[Isotope::I]).is_empty() {
+        if first_atom.token == self.molecule.system_tokens.user && self.get_isotopes(&[Isotope::I]).is_empty() {
             return Err(KnishIOError::AtomsMissing);
         }
 
@@ -150,306 +167,6 @@ impl<'a> CheckMolecule<'a> {
         Err(KnishIOError::BatchId)
     }
 
-    /// Validate Identity isotope atoms
-    ///
-    /// Equivalent to CheckMolecule.isotopeI() in JavaScript
-    fn isotope_i(&self) -> Result<bool> {
-        for atom in self.get_isotopes(&[Isotope::I]) {
-            if atom.token != "USER" {
-                return Err(KnishIOError::WrongTokenType);
-            }
-
-            if atom.index == Some(0) {
-                return Err(KnishIOError::AtomIndex);
-            }
-        }
-
-        Ok(true)
-    }
-
-    /// Validate Authorization isotope atoms
-    ///
-    /// Equivalent to CheckMolecule.isotopeU() in JavaScript
-    fn isotope_u(&self) -> Result<bool> {
-        for atom in self.get_isotopes(&[Isotope::U]) {
-            if atom.token != "AUTH" {
-                return Err(KnishIOError::WrongTokenType);
-            }
-
-            if atom.index != Some(0) {
-                return Err(KnishIOError::AtomIndex);
-            }
-        }
-
-        Ok(true)
-    }
-
-    /// Validate Metadata isotope atoms
-    ///
-    /// Equivalent to CheckMolecule.isotopeM() in JavaScript
-    fn isotope_m(&self) -> Result<bool> {
-        let policy_array = ["readPolicy", "writePolicy"];
-
-        for atom in self.get_isotopes(&[Isotope::M]) {
-            if atom.meta.is_empty() {
-                return Err(KnishIOError::MetaMissing);
-            }
-
-            if atom.token != "USER" {
-                return Err(KnishIOError::WrongTokenType);
-            }
-
-            let metas = Meta::aggregate_meta(&atom.meta);
-
-            for key in &policy_array {
-                if let Some(policy_json) = metas.get(*key) {
-                    let policy: HashMap<String, serde_json::Value> = 
-                        serde_json::from_str(policy_json)
-                            .map_err(|_| KnishIOError::PolicyInvalid)?;
-
-                    for (policy_name, policy_value) in policy {
-                        if !policy_array.contains(&policy_name.as_str()) {
-                            if !metas.contains_key(&policy_name) {
-                                return Err(KnishIOError::PolicyInvalid);
-                            }
-
-                            if let Some(values) = policy_value.as_array() {
-                                for value in values {
-                                    if let Some(val_str) = value.as_str() {
-                                        if !Wallet::is_bundle_hash(val_str) && 
-                                           !["all", "self"].contains(&val_str) {
-                                            return Err(KnishIOError::PolicyInvalid);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        Ok(true)
-    }
-
-    /// Validate Creation isotope atoms
-    ///
-    /// Equivalent to CheckMolecule.isotopeC() in JavaScript
-    fn isotope_c(&self) -> Result<bool> {
-        for atom in self.get_isotopes(&[Isotope::C]) {
-            if atom.token != "USER" {
-                return Err(KnishIOError::WrongTokenType);
-            }
-
-            if atom.index != Some(0) {
-                return Err(KnishIOError::AtomIndex);
-            }
-        }
-
-        Ok(true)
-    }
-
-    /// Validate Token isotope atoms
-    ///
-    /// Equivalent to CheckMolecule.isotopeT() in JavaScript
-    fn isotope_t(&self) -> Result<bool> {
-        for atom in self.get_isotopes(&[Isotope::T]) {
-            let meta = atom.aggregated_meta();
-            let meta_type = atom.meta_type.as_deref().unwrap_or("").to_lowercase();
-
-            if meta_type == "wallet" {
-                for key in &["position", "bundle"] {
-                    if !meta.contains_key(*key) || meta.get(*key).unwrap_or(&String::new()).is_empty() {
-                        return Err(KnishIOError::MetaMissing);
-                    }
-                }
-            }
-
-            for key in &["token"] {
-                if !meta.contains_key(*key) || meta.get(*key).unwrap_or(&String::new()).is_empty() {
-                    return Err(KnishIOError::MetaMissing);
-                }
-            }
-
-            if atom.token != "USER" {
-                return Err(KnishIOError::WrongTokenType);
-            }
-
-            if atom.index != Some(0) {
-                return Err(KnishIOError::AtomIndex);
-            }
-        }
-
-        Ok(true)
-    }
-
-    /// Validate Rule isotope atoms
-    ///
-    /// Equivalent to CheckMolecule.isotopeR() in JavaScript
-    fn isotope_r(&self) -> Result<bool> {
-        for atom in self.get_isotopes(&[Isotope::R]) {
-            let metas = atom.aggregated_meta();
-
-            if let Some(policy_json) = metas.get("policy") {
-                let policy: HashMap<String, serde_json::Value> = 
-                    serde_json::from_str(policy_json)
-                        .map_err(|_| KnishIOError::MetaMissing)?;
-
-                for key in policy.keys() {
-                    if !["read", "write"].contains(&key.as_str()) {
-                        return Err(KnishIOError::MetaMissing);
-                    }
-                }
-            }
-
-            if let Some(rule_json) = metas.get("rule") {
-                let rules: serde_json::Value = 
-                    serde_json::from_str(rule_json)
-                        .map_err(|_| KnishIOError::MetaMissing)?;
-
-                if !rules.is_array() {
-                    return Err(KnishIOError::MetaMissing);
-                }
-
-                let rules_array = rules.as_array().unwrap();
-                
-                if rules_array.is_empty() {
-                    return Err(KnishIOError::MetaMissing);
-                }
-
-                // Validate individual rules using Rule::from_object (equivalent to Rule.toObject in JS)
-                for rule_data in rules_array {
-                    // Validate that each rule can be properly parsed using Rule::from_object
-                    Rule::from_object(rule_data)
-                        .map_err(|_| KnishIOError::MetaMissing)?;
-                }
-            }
-        }
-
-        Ok(true)
-    }
-
-    /// Validate Value isotope atoms (transfer validation)
-    ///
-    /// Equivalent to CheckMolecule.isotopeV() in JavaScript
-    fn isotope_v(&self, sender_wallet: Option<&Wallet>) -> Result<bool> {
-        let isotope_v = self.get_isotopes(&[Isotope::V]);
-
-        if isotope_v.is_empty() {
-            return Ok(true);
-        }
-
-        // B/F isotope molecules have V-atoms that don't sum to zero on their own (the B/F
-        // atom absorbs the difference), so the plain V-conservation check is skipped when a
-        // cross-isotope is present — mirroring JS CheckMolecule's `!hasCrossIsotope` gate.
-        let has_cross_isotope = !self.get_isotopes(&[Isotope::B, Isotope::F]).is_empty();
-
-        let first_atom = &self.molecule.atoms[0];
-
-        // Handle simple 2-atom transfer case (e.g., B-isotope deposit: V-debit + V-remainder)
-        if first_atom.isotope == Isotope::V && isotope_v.len() == 2 {
-            let end_atom = &isotope_v[isotope_v.len() - 1];
-
-            if first_atom.token != end_atom.token {
-                return Err(KnishIOError::TransferMismatched);
-            }
-
-            let end_value: f64 = end_atom.value.as_ref()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.0);
-
-            if end_value < 0.0 {
-                return Err(KnishIOError::TransferMalformed);
-            }
-
-            // A plain 2-atom V transfer (no B/F isotope to absorb the difference) must
-            // balance to zero, mirroring JS CheckMolecule.isotopeV's firstAtom+endAtom sum.
-            if !has_cross_isotope {
-                let first_value: f64 = first_atom.value.as_ref()
-                    .and_then(|v| v.parse().ok())
-                    .unwrap_or(0.0);
-                if first_value + end_value != 0.0 {
-                    return Err(KnishIOError::TransferUnbalanced);
-                }
-            }
-
-            return Ok(true);
-        }
-
-        let mut sum = 0.0;
-        let mut value: f64 = 0.0;
-
-        for (index, atom) in self.molecule.atoms.iter().enumerate() {
-            // Not V? Next...
-            if atom.isotope != Isotope::V {
-                continue;
-            }
-
-            // Making sure we're in number land
-            value = atom.value.as_ref()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.0);
-
-            if value.is_nan() {
-                return Err(KnishIOError::Custom("Invalid isotope V values".to_string()));
-            }
-
-            // Making sure all V atoms of the same token
-            if atom.token != first_atom.token {
-                return Err(KnishIOError::TransferMismatched);
-            }
-
-            // Checking non-primary atoms
-            if index > 0 {
-                // Negative V atom in a non-primary position?
-                if value < 0.0 {
-                    return Err(KnishIOError::TransferMalformed);
-                }
-
-                // Cannot be sending and receiving from the same address
-                if atom.wallet_address == first_atom.wallet_address {
-                    return Err(KnishIOError::TransferToSelf);
-                }
-            }
-
-            // Adding this Atom's value to the total sum
-            sum += value;
-        }
-
-        // All atoms must sum to zero for a balanced transaction
-        if sum != 0.0 {
-            return Err(KnishIOError::TransferUnbalanced);
-        }
-
-        // If we're provided with a senderWallet argument, we can perform additional checks
-        if let Some(sender) = sender_wallet {
-            value = first_atom.value.as_ref()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.0);
-
-            if value.is_nan() {
-                return Err(KnishIOError::Custom("Invalid isotope V values".to_string()));
-            }
-
-            let remainder = sender.balance_as_i128() as f64 + value;
-
-            // Is there enough balance to send?
-            if remainder < 0.0 {
-                return Err(KnishIOError::TransferBalance);
-            }
-
-            // Does the remainder match what should be there in the source wallet, if provided?
-            if remainder != sum {
-                return Err(KnishIOError::TransferRemainder);
-            }
-        } else if value != 0.0 {
-            // No senderWallet, but have a remainder?
-            return Err(KnishIOError::TransferRemainder);
-        }
-
-        Ok(true)
-    }
-
     /// Verify molecular hash integrity
     ///
     /// Equivalent to CheckMolecule.molecularHash() in JavaScript
@@ -494,21 +211,13 @@ impl<'a> CheckMolecule<'a> {
         // Subdivide Kk into 16 segments of 256 bytes (128 characters) each
         let ots_chunks = Self::chunk_substr(&ots, 128);
 
-        let mut key_fragments = String::new();
-
-        for (index, chunk) in ots_chunks.iter().enumerate() {
-            let mut working_chunk = chunk.clone();
-
-            // WOTS+ verification: condition should be 8 + normalized_hash[index]
-            // This is opposite of signing which uses (8 - normalizedHash[index])
-            // normalized_hash[index] is -8 to 8, so condition is 0 to 16
-            let condition = (8 + normalized_hash[index] as i32) as usize;
-            for _ in 0..condition {
-                working_chunk = shake256(&working_chunk, 512);
-            }
-
-            key_fragments.push_str(&working_chunk);
-        }
+        // WOTS+ verification: condition should be 8 + normalized_hash[index]
+        // This is opposite of signing which uses (8 - normalizedHash[index])
+        // normalized_hash[index] is -8 to 8, so condition is 0 to 16
+        let conditions: Vec<usize> = (0..ots_chunks.len())
+            .map(|index| (8 + normalized_hash[index] as i32) as usize)
+            .collect();
+        let key_fragments = hash_chains(ots_chunks, &conditions).concat();
 
         // The reconstructed key_fragments is now the original signing key
         // JavaScript doesn't use generate_address here - it uses a simpler process:
@@ -606,9 +315,7 @@ impl<'a> CheckMolecule<'a> {
         let status = molecule_data.get("status")
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
-        let created_at = molecule_data.get("createdAt")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
+        let created_at = molecule_data.get("createdAt").cloned().unwrap_or(Value::Null);
 
         // Map server atoms to SDK atom JSON format
         let mapped_atoms: Vec<Value> = molecule_data.get("atoms")
@@ -649,7 +356,7 @@ impl<'a> CheckMolecule<'a> {
                         "meta": meta,
                         "index": server_atom.get("index").and_then(|v| v.as_u64()),
                         "otsFragment": server_atom.get("otsFragment").and_then(|v| v.as_str()),
-                        "createdAt": server_atom.get("createdAt").and_then(|v| v.as_str()),
+                        "createdAt": server_atom.get("createdAt"),
                     })
                 }).collect()
             })
@@ -736,6 +443,159 @@ impl<'a> CheckMolecule<'a> {
     }
 }
 
+/// Exact V-isotope ledger math of one molecule
+///
+/// Every V atom's value (and the sender's balance) is parsed as a decimal and scaled to
+/// a common number of decimal places, so sums are exact i128 arithmetic.
+struct ValueLedger {
+    /// `(atom index, wallet address, scaled value)` of every V atom, in molecule order
+    entries: Vec<(usize, String, i128)>,
+    /// Sender balance, scaled like the entries
+    balance: Option<i128>,
+    /// Number of decimal places all amounts are scaled to
+    scale: u32,
+    token: String,
+}
+
+impl ValueLedger {
+    fn new(atoms: &[Atom], sender_wallet: Option<&Wallet>) -> Result<Self> {
+        Self::of(atoms, sender_wallet, |atom| atom.isotope == Isotope::V)
+    }
+
+    /// Ledger of the V, B and F atoms of `token`, for molecules moving value between
+    /// wallets and buffers
+    fn with_buffers(atoms: &[Atom], token: &str, sender_wallet: Option<&Wallet>) -> Result<Self> {
+        Self::of(atoms, sender_wallet, |atom| {
+            matches!(atom.isotope, Isotope::V | Isotope::B | Isotope::F) && atom.token == token
+        })
+    }
+
+    fn of(atoms: &[Atom], sender_wallet: Option<&Wallet>, counts: impl Fn(&Atom) -> bool) -> Result<Self> {
+        let invalid = || KnishIOError::Custom("Invalid isotope V values".to_string());
+
+        let parsed = atoms.iter()
+            .enumerate()
+            .filter(|(_, atom)| counts(atom))
+            .map(|(index, atom)| {
+                let value = parse_decimal(atom.value.as_deref().unwrap_or("")).ok_or_else(invalid)?;
+                Ok((index, atom.wallet_address.clone(), value))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let balance = sender_wallet
+            .map(|wallet| parse_decimal(&wallet.balance).ok_or_else(invalid))
+            .transpose()?;
+
+        let scale = parsed.iter().map(|(_, _, (_, scale))| *scale)
+            .chain(balance.map(|(_, scale)| scale))
+            .max()
+            .unwrap_or(0);
+        let rescale = |(mantissa, from): (i128, u32)| {
+            10i128.checked_pow(scale - from)
+                .and_then(|factor| mantissa.checked_mul(factor))
+                .ok_or_else(|| KnishIOError::Custom("Isotope V value out of range".to_string()))
+        };
+
+        Ok(ValueLedger {
+            entries: parsed.into_iter()
+                .map(|(index, address, value)| Ok((index, address, rescale(value)?)))
+                .collect::<Result<Vec<_>>>()?,
+            balance: balance.map(rescale).transpose()?,
+            scale,
+            token: atoms.iter().find(|a| counts(a)).map(|a| a.token.clone()).unwrap_or_default(),
+        })
+    }
+
+    fn value_of(&self, index: usize) -> i128 {
+        self.entries.iter().find(|(i, _, _)| *i == index).map_or(0, |(_, _, value)| *value)
+    }
+
+    fn first_value(&self) -> i128 {
+        self.entries.first().map_or(0, |(_, _, value)| *value)
+    }
+
+    fn last_value(&self) -> i128 {
+        self.entries.last().map_or(0, |(_, _, value)| *value)
+    }
+
+    fn sum(&self) -> i128 {
+        self.entries.iter().map(|(_, _, value)| *value).sum()
+    }
+
+    /// Sender balance after the primary atom's debit (None without a sender wallet)
+    fn remainder(&self) -> Option<i128> {
+        self.balance.map(|balance| balance + self.first_value())
+    }
+
+    /// Wrap `reason` with this ledger's breakdown
+    fn violation(&self, reason: KnishIOError) -> KnishIOError {
+        KnishIOError::TransferInvariant { reason: Box::new(reason), breakdown: self.breakdown() }
+    }
+
+    fn breakdown(&self) -> String {
+        let mut lines = vec![format!("V-isotope ledger for token {}:", self.token)];
+        for (index, address, value) in &self.entries {
+            let short = address.get(..16).unwrap_or(address);
+            lines.push(format!("  atom {} ({}…): {}", index, short, self.format(*value)));
+        }
+        lines.push(format!("  sum: {}", self.format(self.sum())));
+        if let (Some(balance), Some(remainder)) = (self.balance, self.remainder()) {
+            lines.push(format!("  sender balance: {}", self.format(balance)));
+            lines.push(format!("  remainder after debit: {}", self.format(remainder)));
+        }
+        lines.join("\n")
+    }
+
+    /// Render a scaled amount as a signed decimal
+    fn format(&self, value: i128) -> String {
+        let sign = if value < 0 { "-" } else if value > 0 { "+" } else { "" };
+        let digits = format!("{:0>width$}", value.unsigned_abs(), width = self.scale as usize + 1);
+        if self.scale == 0 {
+            return format!("{}{}", sign, digits);
+        }
+
+        let (int, frac) = digits.split_at(digits.len() - self.scale as usize);
+        let frac = frac.trim_end_matches('0');
+        if frac.is_empty() {
+            format!("{}{}", sign, int)
+        } else {
+            format!("{}{}.{}", sign, int, frac)
+        }
+    }
+}
+
+/// Parse a decimal amount ("-100", "12.50", "1e+21") into `(mantissa, decimal places)`
+///
+/// Missing values count as zero, as in JS where `Number(null)` is 0.
+fn parse_decimal(text: &str) -> Option<(i128, u32)> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Some((0, 0));
+    }
+
+    let (number, exponent) = match text.split_once(['e', 'E']) {
+        Some((number, exponent)) => (number, exponent.parse::<i64>().ok().filter(|e| e.abs() <= MAX_EXPONENT)?),
+        None => (text, 0),
+    };
+    let (negative, number) = match number.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, number.strip_prefix('+').unwrap_or(number)),
+    };
+    let (int, frac) = number.split_once('.').unwrap_or((number, ""));
+    if (int.is_empty() && frac.is_empty()) || !int.chars().chain(frac.chars()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let frac = frac.trim_end_matches('0');
+
+    let mut mantissa: i128 = format!("{}{}", int, frac).parse().ok()?;
+    let mut scale = i64::try_from(frac.len()).ok()?.checked_sub(exponent)?;
+    if scale < 0 {
+        mantissa = mantissa.checked_mul(10i128.checked_pow(u32::try_from(-scale).ok()?)?)?;
+        scale = 0;
+    }
+
+    Some((if negative { -mantissa } else { mantissa }, u32::try_from(scale).ok()?))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -779,6 +639,152 @@ mod tests {
         assert!(matches!(check_molecule.unwrap_err(), KnishIOError::AtomsMissing));
     }
 
+    fn transfer(values: &[&str]) -> Molecule {
+        let mut molecule = Molecule::new();
+        molecule.molecular_hash = Some("test_hash".to_string());
+        for (index, value) in values.iter().enumerate() {
+            let mut atom = Atom::new(format!("pos{}", index), format!("address{:02}", index), Isotope::V, "TEST");
+            atom.index = Some(index as u32);
+            atom.value = Some(value.to_string());
+            molecule.atoms.push(atom);
+        }
+        molecule
+    }
+
+    #[test]
+    fn test_misordered_atoms_are_rejected() {
+        let mut molecule = transfer(&["-10", "10"]);
+        molecule.molecular_hash = Some(Atom::hash_atoms(&molecule.atoms, "base17").unwrap());
+
+        // The hash is taken over index-sorted atoms, so it still matches after a swap
+        molecule.atoms.swap(0, 1);
+        let check = CheckMolecule::new(&molecule).unwrap();
+        assert!(check.molecular_hash().is_ok());
+        assert!(matches!(check.verify(None), Err(KnishIOError::AtomIndex)));
+    }
+
+    fn sender(balance: &str) -> Wallet {
+        let mut wallet = Wallet::default();
+        wallet.balance = balance.to_string();
+        wallet
+    }
+
+    #[test]
+    fn test_isotope_v_exact_beyond_f64_precision() {
+        // 2^53 + 1 is not representable as f64; float math would call this balanced
+        let molecule = transfer(&["-9007199254740993", "9007199254740992", "0"]);
+        let error = ValueValidator.validate(&ValidationContext::new(&molecule, Some(&sender("9007199254740993")))).unwrap_err();
+        assert!(matches!(error.transfer_reason(), KnishIOError::TransferUnbalanced));
+        assert!(error.is_balance_error());
+
+        let molecule = transfer(&["-9007199254740993", "9007199254740992", "1"]);
+        assert!(ValueValidator.validate(&ValidationContext::new(&molecule, Some(&sender("9007199254740993")))).is_ok());
+    }
+
+    #[test]
+    fn test_isotope_v_decimal_amounts_and_breakdown() {
+        let molecule = transfer(&["-10.5", "7.25", "3.25"]);
+        assert!(ValueValidator.validate(&ValidationContext::new(&molecule, Some(&sender("10.50")))).is_ok());
+
+        // Sender holds more than the primary atom debits: remainder math does not match
+        let error = ValueValidator.validate(&ValidationContext::new(&molecule, Some(&sender("12")))).unwrap_err();
+        assert!(matches!(error.transfer_reason(), KnishIOError::TransferRemainder));
+        let message = error.to_string();
+        assert!(message.starts_with("Transfer remainder error\nV-isotope ledger for token TEST:"), "{}", message);
+        assert!(message.contains("atom 0 (address00…): -10.5"), "{}", message);
+        assert!(message.contains("atom 1 (address01…): +7.25"), "{}", message);
+        assert!(message.contains("sender balance: +12"), "{}", message);
+        assert!(message.contains("remainder after debit: +1.5"), "{}", message);
+
+        let molecule = transfer(&["-10", "abc", "10"]);
+        assert!(ValueValidator.validate(&ValidationContext::new(&molecule, None)).is_err());
+    }
+
+    #[test]
+    fn test_buffer_validator_conserves_value_against_buffer_atoms() {
+        // Withdrawal: the buffer's B debit pays the V credits, the change stays in a B remainder
+        let mut molecule = transfer(&["-10", "4", "6"]);
+        molecule.atoms[0].isotope = Isotope::B;
+        molecule.atoms[2].isotope = Isotope::B;
+        assert!(BufferValidator.validate(&ValidationContext::new(&molecule, Some(&sender("10")))).is_ok());
+
+        molecule.atoms[1].value = Some("9".to_string());
+        let error = BufferValidator.validate(&ValidationContext::new(&molecule, Some(&sender("10")))).unwrap_err();
+        assert!(matches!(error.transfer_reason(), KnishIOError::TransferUnbalanced));
+        // Like the JavaScript CheckMolecule, the default V rules leave such molecules alone
+        assert!(ValueValidator.validate(&ValidationContext::new(&molecule, Some(&sender("10")))).is_ok());
+
+        // Deposit: a V debit into a B credit, the change in a V remainder
+        let mut molecule = transfer(&["-10", "4", "6"]);
+        molecule.atoms[1].isotope = Isotope::B;
+        assert!(BufferValidator.validate(&ValidationContext::new(&molecule, Some(&sender("10")))).is_ok());
+        let error = BufferValidator.validate(&ValidationContext::new(&molecule, Some(&sender("12")))).unwrap_err();
+        assert!(matches!(error.transfer_reason(), KnishIOError::TransferRemainder));
+
+        molecule.atoms[2].value = Some("7".to_string());
+        let error = BufferValidator.validate(&ValidationContext::new(&molecule, None)).unwrap_err();
+        assert!(matches!(error.transfer_reason(), KnishIOError::TransferUnbalanced));
+        assert!(ValueValidator.validate(&ValidationContext::new(&molecule, None)).is_ok());
+
+        // Plain transfers are not its business
+        let molecule = transfer(&["-10", "4", "7"]);
+        assert!(BufferValidator.validate(&ValidationContext::new(&molecule, None)).is_ok());
+        assert_eq!(ValidatorRegistry::default().validators_for(Isotope::B).count(), 0);
+    }
+
+    #[test]
+    fn test_custom_validators_replace_the_default_ruleset() {
+        use crate::testkit::{FixtureMolecule, FixtureWallet};
+
+        /// Node-side rule capping transfers at 50
+        struct TransferCap;
+
+        impl IsotopeValidator for TransferCap {
+            fn isotope(&self) -> Isotope {
+                Isotope::V
+            }
+
+            fn validate(&self, context: &ValidationContext<'_>) -> Result<()> {
+                ValueValidator.validate(context)?;
+                let over_cap = context.atoms(&[Isotope::V]).iter()
+                    .any(|atom| atom.value.as_deref().and_then(|v| v.parse::<f64>().ok()).unwrap_or(0.0) < -50.0);
+                if over_cap {
+                    return Err(KnishIOError::custom("Transfer cap exceeded"));
+                }
+                Ok(())
+            }
+        }
+
+        let alice = FixtureWallet::funded("GOLD", 100.0).unwrap();
+        let bob = FixtureWallet::for_seed("bob", "GOLD").unwrap();
+        let molecule = FixtureMolecule::transfer(&alice, &bob.wallet, 40.0).unwrap();
+        assert!(molecule.check(Some(&alice.wallet)).unwrap());
+
+        let capped = ValidatorRegistry::default().replace(TransferCap);
+        let error = molecule.check_with(Some(&alice.wallet), &capped).unwrap_err();
+        assert_eq!(error.to_string(), "Transfer cap exceeded");
+
+        // Structural checks still run whatever the registry holds
+        let mut tampered = molecule.clone();
+        tampered.molecular_hash = Some("0".repeat(64));
+        let check = CheckMolecule::new(&tampered).unwrap().with_validators(&capped);
+        assert!(matches!(check.verify(Some(&alice.wallet)), Err(KnishIOError::MolecularHashMismatch)));
+    }
+
+    #[test]
+    fn test_parse_decimal() {
+        assert_eq!(parse_decimal("100"), Some((100, 0)));
+        assert_eq!(parse_decimal("-12.500"), Some((-125, 1)));
+        assert_eq!(parse_decimal("1e+21"), Some((1_000_000_000_000_000_000_000, 0)));
+        assert_eq!(parse_decimal("2.5e-3"), Some((25, 4)));
+        assert_eq!(parse_decimal(""), Some((0, 0)));
+        assert_eq!(parse_decimal("0.000"), Some((0, 0)));
+        assert_eq!(parse_decimal("1.2.3"), None);
+        assert_eq!(parse_decimal("NaN"), None);
+        assert_eq!(parse_decimal("1e-2147483648"), None);
+        assert_eq!(parse_decimal("1e99999999999"), None);
+    }
+
     #[test]
     fn test_chunk_substr() {
         let result = CheckMolecule::chunk_substr("abcdefgh", 3);
@@ -792,7 +798,7 @@ mod tests {
             "bundleHash": "bundle456",
             "cellSlug": "test_cell",
             "status": "accepted",
-            "createdAt": "2026-01-01",
+            "createdAt": "2026-01-01T00:00:00Z",
             "atoms": [
                 {
                     "position": "pos1",
@@ -821,6 +827,17 @@ mod tests {
         assert_eq!(molecule.atoms[0].meta.len(), 1);
         assert_eq!(molecule.atoms[0].meta[0].key, "name");
         assert_eq!(molecule.atoms[0].meta[0].value, "test");
+        assert_eq!(molecule.created_at, "2026-01-01T00:00:00Z");
+        assert_eq!(molecule.created_at_millis().unwrap(), 1_767_225_600_000);
+
+        // Node dates are kept as sent; only the typed accessors check them
+        let mut undated = server_data;
+        undated["createdAt"] = serde_json::json!("2026-01-01 00:00:00");
+        undated["atoms"][0]["createdAt"] = serde_json::json!("soon");
+        let molecule = CheckMolecule::from_server_data(&undated).unwrap();
+        assert_eq!(molecule.created_at_millis().unwrap(), 1_767_225_600_000);
+        assert_eq!(molecule.atoms[0].created_at, "soon");
+        assert!(matches!(molecule.atoms[0].created_at_millis(), Err(KnishIOError::InvalidTimestamp(_))));
     }
 
     #[test]
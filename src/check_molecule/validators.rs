@@ -0,0 +1,669 @@
+//! Per-isotope validators
+//!
+//! `CheckMolecule` runs the structural checks every molecule must pass (atom indices,
+//! molecular hash, OTS, batch ID, ContinuID) itself, then hands the molecule to a
+//! `ValidatorRegistry` for the isotope-specific rules. The default registry reproduces
+//! the JavaScript CheckMolecule ruleset, in the JavaScript order: M, T, C, U, I, R, V.
+//!
+//! Node-side rule changes are expressed as `IsotopeValidator` implementations: `replace`
+//! swaps out the validators of an isotope, `add` runs another one next to them and
+//! `remove` drops an isotope's rules. The JavaScript SDK has no client-side B or F
+//! rules, so the default registry leaves those isotopes to whatever validators are added.
+//! Checks stricter than the JavaScript ones, like `PolicyValidator` and `BufferValidator`,
+//! are opt-in.
+//!
+//! ```
+//! use knishio_client::check_molecule::{IsotopeValidator, ValidationContext, ValidatorRegistry};
+//! use knishio_client::{KnishIOError, Result};
+//! use knishio_client::types::Isotope;
+//!
+//! /// Buffer atoms may only carry the token of the molecule's value atoms
+//! struct SameTokenBuffer;
+//!
+//! impl IsotopeValidator for SameTokenBuffer {
+//!     fn isotope(&self) -> Isotope {
+//!         Isotope::B
+//!     }
+//!
+//!     fn validate(&self, context: &ValidationContext<'_>) -> Result<()> {
+//!         let value_token = context.atoms(&[Isotope::V]).first().map(|a| a.token.clone());
+//!         for atom in context.atoms(&[Isotope::B]) {
+//!             if value_token.as_ref().is_some_and(|token| *token != atom.token) {
+//!                 return Err(KnishIOError::TransferMismatched);
+//!             }
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let registry = ValidatorRegistry::default().add(SameTokenBuffer);
+//! assert_eq!(registry.validators_for(Isotope::B).count(), 1);
+//! ```
+
+use super::ValueLedger;
+use crate::atom::Atom;
+use crate::error::{KnishIOError, Result};
+use crate::meta::{Meta, PolicyMeta};
+use crate::molecule::Molecule;
+use crate::rules::Rule;
+use crate::types::Isotope;
+use crate::wallet::Wallet;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, LazyLock};
+
+/// The JavaScript-parity ruleset shared by every `CheckMolecule` without custom validators
+pub(super) static DEFAULT_VALIDATORS: LazyLock<ValidatorRegistry> = LazyLock::new(ValidatorRegistry::default);
+
+/// What a validator gets to look at
+#[derive(Debug, Clone, Copy)]
+pub struct ValidationContext<'a> {
+    /// Molecule under validation
+    pub molecule: &'a Molecule,
+    /// Sender wallet, when the caller supplied one for balance checks
+    pub sender_wallet: Option<&'a Wallet>,
+}
+
+impl<'a> ValidationContext<'a> {
+    /// Context for validating `molecule`
+    pub fn new(molecule: &'a Molecule, sender_wallet: Option<&'a Wallet>) -> Self {
+        ValidationContext { molecule, sender_wallet }
+    }
+
+    /// Atoms of any of `isotopes`, in molecule order
+    pub fn atoms(&self, isotopes: &[Isotope]) -> Vec<&'a Atom> {
+        self.molecule.atoms.iter().filter(|atom| isotopes.contains(&atom.isotope)).collect()
+    }
+
+    /// Token slug of the molecule's ContinuID wallets
+    pub fn user_token(&self) -> &'a str {
+        &self.molecule.system_tokens.user
+    }
+
+    /// Token slug of the molecule's authorization wallets
+    pub fn auth_token(&self) -> &'a str {
+        &self.molecule.system_tokens.auth
+    }
+}
+
+/// Rules for the atoms of one isotope
+pub trait IsotopeValidator: Send + Sync {
+    /// Isotope this validator is registered under
+    fn isotope(&self) -> Isotope;
+
+    /// Check the molecule, returning the first violated rule
+    fn validate(&self, context: &ValidationContext<'_>) -> Result<()>;
+}
+
+/// Ordered set of isotope validators
+///
+/// Validators run in registration order; the first failure stops validation.
+#[derive(Clone)]
+pub struct ValidatorRegistry {
+    validators: Vec<Arc<dyn IsotopeValidator>>,
+}
+
+impl Default for ValidatorRegistry {
+    /// The JavaScript CheckMolecule ruleset
+    fn default() -> Self {
+        ValidatorRegistry::empty()
+            .add(MetaValidator)
+            .add(TokenRequestValidator)
+            .add(CreationValidator)
+            .add(AuthorizationValidator)
+            .add(IdentityValidator)
+            .add(RuleValidator)
+            .add(ValueValidator)
+    }
+}
+
+impl fmt::Debug for ValidatorRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.validators.iter().map(|v| v.isotope())).finish()
+    }
+}
+
+impl ValidatorRegistry {
+    /// A registry without any isotope rules
+    pub fn empty() -> Self {
+        ValidatorRegistry { validators: Vec::new() }
+    }
+
+    /// Run `validator` after the ones already registered
+    pub fn add(mut self, validator: impl IsotopeValidator + 'static) -> Self {
+        self.validators.push(Arc::new(validator));
+        self
+    }
+
+    /// Replace every validator of `validator`'s isotope with it
+    ///
+    /// The replacement takes the position of the first validator it replaces, so the
+    /// isotope keeps its place in the order; it is appended if there was none.
+    pub fn replace(mut self, validator: impl IsotopeValidator + 'static) -> Self {
+        let isotope = validator.isotope();
+        let position = self.validators.iter().position(|v| v.isotope() == isotope).unwrap_or(self.validators.len());
+        self.validators.retain(|v| v.isotope() != isotope);
+        self.validators.insert(position, Arc::new(validator));
+        self
+    }
+
+    /// Drop every validator of `isotope`
+    pub fn remove(mut self, isotope: Isotope) -> Self {
+        self.validators.retain(|v| v.isotope() != isotope);
+        self
+    }
+
+    /// Validators registered for `isotope`, in run order
+    pub fn validators_for(&self, isotope: Isotope) -> impl Iterator<Item = &dyn IsotopeValidator> {
+        self.validators.iter().map(|v| v.as_ref()).filter(move |v| v.isotope() == isotope)
+    }
+
+    /// Run every validator against `context`
+    pub fn validate(&self, context: &ValidationContext<'_>) -> Result<()> {
+        self.validators.iter().try_for_each(|validator| validator.validate(context))
+    }
+}
+
+/// Identity (I) atoms: USER token, never the signing atom
+///
+/// Equivalent to CheckMolecule.isotopeI() in JavaScript
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IdentityValidator;
+
+impl IsotopeValidator for IdentityValidator {
+    fn isotope(&self) -> Isotope {
+        Isotope::I
+    }
+
+    fn validate(&self, context: &ValidationContext<'_>) -> Result<()> {
+        for atom in context.atoms(&[Isotope::I]) {
+            if atom.token != context.user_token() {
+                return Err(KnishIOError::WrongTokenType);
+            }
+
+            if atom.index == Some(0) {
+                return Err(KnishIOError::AtomIndex);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Authorization (U) atoms: AUTH token, signing atom only
+///
+/// Equivalent to CheckMolecule.isotopeU() in JavaScript
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AuthorizationValidator;
+
+impl IsotopeValidator for AuthorizationValidator {
+    fn isotope(&self) -> Isotope {
+        Isotope::U
+    }
+
+    fn validate(&self, context: &ValidationContext<'_>) -> Result<()> {
+        for atom in context.atoms(&[Isotope::U]) {
+            if atom.token != context.auth_token() {
+                return Err(KnishIOError::WrongTokenType);
+            }
+
+            if atom.index != Some(0) {
+                return Err(KnishIOError::AtomIndex);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Metadata (M) atoms: USER token, non-empty meta and well-formed read/write policies
+///
+/// Equivalent to CheckMolecule.isotopeM() in JavaScript
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetaValidator;
+
+impl IsotopeValidator for MetaValidator {
+    fn isotope(&self) -> Isotope {
+        Isotope::M
+    }
+
+    fn validate(&self, context: &ValidationContext<'_>) -> Result<()> {
+        let policy_array = ["readPolicy", "writePolicy"];
+
+        for atom in context.atoms(&[Isotope::M]) {
+            if atom.meta.is_empty() {
+                return Err(KnishIOError::MetaMissing);
+            }
+
+            if atom.token != context.user_token() {
+                return Err(KnishIOError::WrongTokenType);
+            }
+
+            let metas = Meta::aggregate_meta(&atom.meta);
+
+            for key in &policy_array {
+                if let Some(policy_json) = metas.get(*key) {
+                    let policy: HashMap<String, serde_json::Value> =
+                        serde_json::from_str(policy_json)
+                            .map_err(|_| KnishIOError::PolicyInvalid)?;
+
+                    for (policy_name, policy_value) in policy {
+                        if !policy_array.contains(&policy_name.as_str()) {
+                            if !metas.contains_key(&policy_name) {
+                                return Err(KnishIOError::PolicyInvalid);
+                            }
+
+                            if let Some(values) = policy_value.as_array() {
+                                for value in values {
+                                    if let Some(val_str) = value.as_str() {
+                                        if !Wallet::is_bundle_hash(val_str) &&
+                                           !["all", "self"].contains(&val_str) {
+                                            return Err(KnishIOError::PolicyInvalid);
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Creation (C) atoms: USER token, signing atom only
+///
+/// Equivalent to CheckMolecule.isotopeC() in JavaScript
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CreationValidator;
+
+impl IsotopeValidator for CreationValidator {
+    fn isotope(&self) -> Isotope {
+        Isotope::C
+    }
+
+    fn validate(&self, context: &ValidationContext<'_>) -> Result<()> {
+        for atom in context.atoms(&[Isotope::C]) {
+            if atom.token != context.user_token() {
+                return Err(KnishIOError::WrongTokenType);
+            }
+
+            if atom.index != Some(0) {
+                return Err(KnishIOError::AtomIndex);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Token request (T) atoms: USER token, signing atom only, token (and wallet) meta present
+///
+/// Equivalent to CheckMolecule.isotopeT() in JavaScript
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenRequestValidator;
+
+impl IsotopeValidator for TokenRequestValidator {
+    fn isotope(&self) -> Isotope {
+        Isotope::T
+    }
+
+    fn validate(&self, context: &ValidationContext<'_>) -> Result<()> {
+        for atom in context.atoms(&[Isotope::T]) {
+            let meta = atom.aggregated_meta();
+            let meta_type = atom.meta_type.as_deref().unwrap_or("").to_lowercase();
+
+            if meta_type == "wallet" {
+                for key in &["position", "bundle"] {
+                    if !meta.contains_key(*key) || meta.get(*key).unwrap_or(&String::new()).is_empty() {
+                        return Err(KnishIOError::MetaMissing);
+                    }
+                }
+            }
+
+            for key in &["token"] {
+                if !meta.contains_key(*key) || meta.get(*key).unwrap_or(&String::new()).is_empty() {
+                    return Err(KnishIOError::MetaMissing);
+                }
+            }
+
+            if atom.token != context.user_token() {
+                return Err(KnishIOError::WrongTokenType);
+            }
+
+            if atom.index != Some(0) {
+                return Err(KnishIOError::AtomIndex);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Rule (R) atoms: read/write policy keys and parseable, non-empty rule lists
+///
+/// Equivalent to CheckMolecule.isotopeR() in JavaScript
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RuleValidator;
+
+impl IsotopeValidator for RuleValidator {
+    fn isotope(&self) -> Isotope {
+        Isotope::R
+    }
+
+    fn validate(&self, context: &ValidationContext<'_>) -> Result<()> {
+        for atom in context.atoms(&[Isotope::R]) {
+            let metas = atom.aggregated_meta();
+
+            if let Some(policy_json) = metas.get("policy") {
+                let policy: HashMap<String, serde_json::Value> =
+                    serde_json::from_str(policy_json)
+                        .map_err(|_| KnishIOError::MetaMissing)?;
+
+                for key in policy.keys() {
+                    if !["read", "write"].contains(&key.as_str()) {
+                        return Err(KnishIOError::MetaMissing);
+                    }
+                }
+            }
+
+            if let Some(rule_json) = metas.get("rule") {
+                let rules: serde_json::Value =
+                    serde_json::from_str(rule_json)
+                        .map_err(|_| KnishIOError::MetaMissing)?;
+
+                let rules_array = rules.as_array().ok_or(KnishIOError::MetaMissing)?;
+
+                if rules_array.is_empty() {
+                    return Err(KnishIOError::MetaMissing);
+                }
+
+                // Validate individual rules using Rule::from_object (equivalent to Rule.toObject in JS)
+                for rule_data in rules_array {
+                    // Validate that each rule can be properly parsed using Rule::from_object
+                    Rule::from_object(rule_data)
+                        .map_err(|_| KnishIOError::MetaMissing)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// `policy` meta of one isotope's atoms: well-formed policy objects (`PolicyMeta::validate`)
+///
+/// Stricter than JavaScript, whose CheckMolecule does not look inside `policy`, so it is
+/// not part of the default registry; opt in with
+/// `ValidatorRegistry::default().add(PolicyValidator::new(Isotope::M))`.
+#[derive(Debug, Clone, Copy)]
+pub struct PolicyValidator {
+    isotope: Isotope,
+}
+
+impl PolicyValidator {
+    /// Validate the `policy` meta of `isotope` atoms (M and R atoms carry one)
+    pub fn new(isotope: Isotope) -> Self {
+        PolicyValidator { isotope }
+    }
+}
+
+impl IsotopeValidator for PolicyValidator {
+    fn isotope(&self) -> Isotope {
+        self.isotope
+    }
+
+    fn validate(&self, context: &ValidationContext<'_>) -> Result<()> {
+        for atom in context.atoms(&[self.isotope]) {
+            if let Some(policy_json) = atom.aggregated_meta().get("policy") {
+                let policy = serde_json::from_str(policy_json).map_err(|_| KnishIOError::PolicyInvalid)?;
+                PolicyMeta::validate(&policy)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Value (V) atoms: one token, exact balance and remainder math
+///
+/// Equivalent to CheckMolecule.isotopeV() in JavaScript. Amounts are compared as exact
+/// decimals in i128 rather than floats; a failed balance check carries a per-atom
+/// breakdown (`KnishIOError::TransferInvariant`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValueValidator;
+
+impl IsotopeValidator for ValueValidator {
+    fn isotope(&self) -> Isotope {
+        Isotope::V
+    }
+
+    fn validate(&self, context: &ValidationContext<'_>) -> Result<()> {
+        let isotope_v = context.atoms(&[Isotope::V]);
+
+        if isotope_v.is_empty() {
+            return Ok(());
+        }
+
+        // B/F isotope molecules have V-atoms that don't sum to zero on their own (the B/F
+        // atom absorbs the difference), so the plain V-conservation check is skipped when a
+        // cross-isotope is present — mirroring JS CheckMolecule's `!hasCrossIsotope` gate.
+        let has_cross_isotope = !context.atoms(&[Isotope::B, Isotope::F]).is_empty();
+
+        let atoms = &context.molecule.atoms;
+        let ledger = ValueLedger::new(atoms, context.sender_wallet)?;
+        let first_atom = &atoms[0];
+
+        // Handle simple 2-atom transfer case (e.g., B-isotope deposit: V-debit + V-remainder)
+        if first_atom.isotope == Isotope::V && isotope_v.len() == 2 {
+            let end_atom = &isotope_v[isotope_v.len() - 1];
+
+            if first_atom.token != end_atom.token {
+                return Err(KnishIOError::TransferMismatched);
+            }
+
+            let end_value = ledger.last_value();
+            if end_value < 0 {
+                return Err(KnishIOError::TransferMalformed);
+            }
+
+            // A plain 2-atom V transfer (no B/F isotope to absorb the difference) must
+            // balance to zero, mirroring JS CheckMolecule.isotopeV's firstAtom+endAtom sum.
+            if !has_cross_isotope && ledger.first_value() + end_value != 0 {
+                return Err(ledger.violation(KnishIOError::TransferUnbalanced));
+            }
+
+            return Ok(());
+        }
+
+        for (index, atom) in atoms.iter().enumerate() {
+            // Not V? Next...
+            if atom.isotope != Isotope::V {
+                continue;
+            }
+
+            // Making sure all V atoms of the same token
+            if atom.token != first_atom.token {
+                return Err(KnishIOError::TransferMismatched);
+            }
+
+            // Checking non-primary atoms
+            if index > 0 {
+                // Negative V atom in a non-primary position?
+                if ledger.value_of(index) < 0 {
+                    return Err(KnishIOError::TransferMalformed);
+                }
+
+                // Cannot be sending and receiving from the same address
+                if atom.wallet_address == first_atom.wallet_address {
+                    return Err(KnishIOError::TransferToSelf);
+                }
+            }
+        }
+
+        // A withdrawal credits V atoms out of a B/F debit; the conservation checks below
+        // only see V atoms, so they apply to plain transfers alone
+        if has_cross_isotope {
+            return Ok(());
+        }
+
+        // All atoms must sum to zero for a balanced transaction
+        let sum = ledger.sum();
+        if sum != 0 {
+            return Err(ledger.violation(KnishIOError::TransferUnbalanced));
+        }
+
+        // If we're provided with a senderWallet argument, we can perform additional checks
+        if let Some(remainder) = ledger.remainder() {
+            // Is there enough balance to send?
+            if remainder < 0 {
+                return Err(ledger.violation(KnishIOError::TransferBalance));
+            }
+
+            // Does the remainder match what should be there in the source wallet, if provided?
+            if remainder != sum {
+                return Err(ledger.violation(KnishIOError::TransferRemainder));
+            }
+        } else if ledger.last_value() != 0 {
+            // No senderWallet, but have a remainder?
+            return Err(ledger.violation(KnishIOError::TransferRemainder));
+        }
+
+        Ok(())
+    }
+}
+
+/// Value moved between V wallets and B/F buffers: conserved across the three isotopes
+///
+/// A deposit debits a V wallet into a buffer's B atom and a withdrawal debits a buffer's
+/// B atom into V atoms. `ValueValidator` skips the V sums for such molecules, as the
+/// JavaScript CheckMolecule does; this validator checks them with the B and F atoms of the
+/// token included: the first atom of the token is the one debit, the atoms sum to zero,
+/// and the sender's remainder is what the debit leaves. Not part of the default registry;
+/// opt in with `ValidatorRegistry::default().add(BufferValidator)`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BufferValidator;
+
+impl IsotopeValidator for BufferValidator {
+    fn isotope(&self) -> Isotope {
+        Isotope::B
+    }
+
+    fn validate(&self, context: &ValidationContext<'_>) -> Result<()> {
+        let Some(token) = context.atoms(&[Isotope::V]).first().map(|atom| atom.token.clone()) else {
+            return Ok(());
+        };
+        if !context.atoms(&[Isotope::B, Isotope::F]).iter().any(|atom| atom.token == token) {
+            return Ok(());
+        }
+
+        if context.atoms(&[Isotope::V]).iter().any(|atom| atom.token != token) {
+            return Err(KnishIOError::TransferMismatched);
+        }
+
+        let ledger = ValueLedger::with_buffers(&context.molecule.atoms, &token, context.sender_wallet)?;
+        if ledger.first_value() >= 0 || ledger.entries.iter().skip(1).any(|(_, _, value)| *value < 0) {
+            return Err(KnishIOError::TransferMalformed);
+        }
+
+        let sum = ledger.sum();
+        if sum != 0 {
+            return Err(ledger.violation(KnishIOError::TransferUnbalanced));
+        }
+
+        if let Some(remainder) = ledger.remainder() {
+            if remainder < 0 {
+                return Err(ledger.violation(KnishIOError::TransferBalance));
+            }
+            if remainder != sum {
+                return Err(ledger.violation(KnishIOError::TransferRemainder));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rejects every atom of its isotope
+    struct Reject(Isotope);
+
+    impl IsotopeValidator for Reject {
+        fn isotope(&self) -> Isotope {
+            self.0
+        }
+
+        fn validate(&self, context: &ValidationContext<'_>) -> Result<()> {
+            if context.atoms(&[self.0]).is_empty() {
+                return Ok(());
+            }
+            Err(KnishIOError::custom(format!("{} atoms are disabled", self.0.as_str())))
+        }
+    }
+
+    fn isotopes(registry: &ValidatorRegistry) -> String {
+        format!("{:?}", registry)
+    }
+
+    #[test]
+    fn test_default_registry_keeps_javascript_order() {
+        let registry = ValidatorRegistry::default();
+        assert_eq!(isotopes(&registry), "[M, T, C, U, I, R, V]");
+        assert_eq!(registry.validators_for(Isotope::B).count(), 0);
+    }
+
+    #[test]
+    fn test_replace_add_and_remove() {
+        let registry = ValidatorRegistry::default()
+            .add(Reject(Isotope::C))
+            .replace(Reject(Isotope::T))
+            .add(Reject(Isotope::F))
+            .remove(Isotope::U);
+        assert_eq!(isotopes(&registry), "[M, T, C, I, R, V, C, F]");
+        assert_eq!(registry.validators_for(Isotope::C).count(), 2);
+
+        // Replacing collapses every validator of the isotope into one, in the first's place
+        let registry = registry.replace(CreationValidator);
+        assert_eq!(isotopes(&registry), "[M, T, C, I, R, V, F]");
+
+        let registry = ValidatorRegistry::empty().replace(Reject(Isotope::B));
+        assert_eq!(isotopes(&registry), "[B]");
+    }
+
+    #[test]
+    fn test_registry_runs_validators_in_order() {
+        let mut molecule = Molecule::new();
+        let mut atom = Atom::new("pos", "address", Isotope::C, "TEST");
+        atom.index = Some(0);
+        molecule.atoms.push(atom);
+        let context = ValidationContext::new(&molecule, None);
+
+        assert!(matches!(ValidatorRegistry::default().validate(&context), Err(KnishIOError::WrongTokenType)));
+
+        let registry = ValidatorRegistry::default().replace(Reject(Isotope::C));
+        assert_eq!(registry.validate(&context).unwrap_err().to_string(), "C atoms are disabled");
+        assert!(ValidatorRegistry::default().remove(Isotope::C).validate(&context).is_ok());
+    }
+
+    #[test]
+    fn test_policy_validator_is_opt_in() {
+        let mut molecule = Molecule::new();
+        let mut atom = Atom::new("pos", "address", Isotope::M, "USER");
+        atom.meta = vec![
+            crate::types::MetaItem::new("email", "a@b.c"),
+            crate::types::MetaItem::new("policy", r#"{"read":{"email":"self"}}"#),
+        ];
+        molecule.atoms.push(atom);
+        let context = ValidationContext::new(&molecule, None);
+
+        // JavaScript only checks readPolicy and writePolicy
+        assert!(ValidatorRegistry::default().validate(&context).is_ok());
+        let strict = ValidatorRegistry::default().add(PolicyValidator::new(Isotope::M));
+        assert!(matches!(strict.validate(&context), Err(KnishIOError::PolicyInvalid)));
+    }
+}
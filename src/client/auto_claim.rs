@@ -0,0 +1,279 @@
+//! Automatic shadow wallet claiming
+//!
+//! Tokens sent to a bundle that has no wallet for them yet land in a shadow wallet: one
+//! with a batch ID and a balance but no address or position. Until the owner claims it,
+//! the balance cannot be spent. `auto_claim` runs a background task that watches the
+//! client's bundle for new shadow wallets and claims them as they appear.
+//!
+//! The task rescans every `poll_interval`, and additionally right after a wallet status
+//! event for one of the watched tokens when a subscription can be opened. Each scan
+//! claims at most `batch_size` wallets, spaced at least `claim_interval` apart, so a
+//! flood of incoming transfers does not turn into a flood of molecules. A shadow wallet
+//! is claimed once per task; failed claims are reported and retried on the next scan.
+
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::subscribe::SubscriptionHandle;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+/// Pacing of the auto-claim task
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoClaimPolicy {
+    /// Interval between scans when no wallet status event arrives
+    pub poll_interval: Duration,
+    /// Most shadow wallets claimed per scan
+    pub batch_size: usize,
+    /// Minimum delay between two claims
+    pub claim_interval: Duration,
+    /// Rescan on wallet status events in addition to polling
+    pub subscribe: bool,
+}
+
+impl Default for AutoClaimPolicy {
+    fn default() -> Self {
+        AutoClaimPolicy {
+            poll_interval: Duration::from_secs(60),
+            batch_size: 10,
+            claim_interval: Duration::from_secs(1),
+            subscribe: true,
+        }
+    }
+}
+
+impl AutoClaimPolicy {
+    /// Set the interval between scans
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Set the most shadow wallets claimed per scan (at least one)
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// Set the minimum delay between two claims
+    pub fn claim_interval(mut self, interval: Duration) -> Self {
+        self.claim_interval = interval;
+        self
+    }
+
+    /// Set whether wallet status events trigger a scan
+    pub fn subscribe(mut self, subscribe: bool) -> Self {
+        self.subscribe = subscribe;
+        self
+    }
+}
+
+/// Outcome of one automatic claim
+#[derive(Debug, Clone, PartialEq)]
+pub enum AutoClaimEvent {
+    /// The shadow wallet was claimed
+    Claimed {
+        /// Token slug
+        token: String,
+        /// Batch ID of the claimed wallet
+        batch_id: Option<String>,
+        /// Balance of the shadow wallet when it was claimed
+        amount: String,
+        /// Molecular hash of the claim
+        molecular_hash: Option<String>,
+    },
+    /// The claim failed and will be retried on the next scan
+    Failed {
+        /// Token slug
+        token: String,
+        /// Batch ID of the shadow wallet
+        batch_id: Option<String>,
+        /// Why the claim failed
+        error: String,
+    },
+}
+
+impl fmt::Display for AutoClaimEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AutoClaimEvent::Claimed { token, batch_id, amount, .. } => {
+                write!(f, "claimed {} {} (batch {})", amount, token, batch_id.as_deref().unwrap_or("-"))
+            }
+            AutoClaimEvent::Failed { token, batch_id, error } => {
+                write!(f, "claim of {} batch {} failed: {}", token, batch_id.as_deref().unwrap_or("-"), error)
+            }
+        }
+    }
+}
+
+/// A shadow wallet waiting to be claimed
+struct PendingClaim {
+    token: String,
+    batch_id: Option<String>,
+    amount: String,
+}
+
+impl KnishIOClient {
+    /// Claim shadow wallets of `tokens` for a shared client's bundle as they appear
+    ///
+    /// `on_event` is called once per attempted claim. Scans that fail are logged and
+    /// retried on the next interval. Abort the returned handle to stop claiming.
+    pub fn auto_claim<F>(
+        client: Arc<tokio::sync::Mutex<KnishIOClient>>,
+        tokens: Vec<String>,
+        policy: AutoClaimPolicy,
+        on_event: F,
+    ) -> JoinHandle<()>
+    where
+        F: Fn(AutoClaimEvent) + Send + Sync + 'static,
+    {
+        tokio::spawn(async move {
+            let wake = Arc::new(Notify::new());
+            let mut subscriptions: Vec<SubscriptionHandle> = Vec::new();
+            if policy.subscribe {
+                let client = client.lock().await;
+                for token in &tokens {
+                    let wake = wake.clone();
+                    match client.subscribe_wallet_status(None, token.clone(), move |_| wake.notify_one()).await {
+                        Ok(handle) => subscriptions.push(handle),
+                        Err(e) => client.log("warn", &format!(
+                            "KnishIOClient::auto_claim() - No wallet status subscription for {}, polling only: {}", token, e
+                        )),
+                    }
+                }
+            }
+
+            let mut claimed: HashSet<(String, Option<String>)> = HashSet::new();
+            loop {
+                let pending = match Self::pending_claims(&client, &tokens, &claimed, policy.batch_size).await {
+                    Ok(pending) => pending,
+                    Err(e) => {
+                        client.lock().await.log("warn", &format!("KnishIOClient::auto_claim() - Scan failed: {}", e));
+                        Vec::new()
+                    }
+                };
+
+                for (index, shadow) in pending.into_iter().enumerate() {
+                    if index > 0 {
+                        tokio::time::sleep(policy.claim_interval).await;
+                    }
+
+                    let result = Self::claim_pending(&client, &shadow).await;
+                    let event = match result {
+                        Ok(molecular_hash) => {
+                            claimed.insert((shadow.token.clone(), shadow.batch_id.clone()));
+                            AutoClaimEvent::Claimed {
+                                token: shadow.token,
+                                batch_id: shadow.batch_id,
+                                amount: shadow.amount,
+                                molecular_hash,
+                            }
+                        }
+                        Err(e) => AutoClaimEvent::Failed {
+                            token: shadow.token,
+                            batch_id: shadow.batch_id,
+                            error: e.to_string(),
+                        },
+                    };
+                    client.lock().await.log("info", &format!("KnishIOClient::auto_claim() - {}", event));
+                    on_event(event);
+                }
+
+                tokio::select! {
+                    _ = tokio::time::sleep(policy.poll_interval) => {}
+                    _ = wake.notified() => {}
+                }
+            }
+        })
+    }
+
+    /// Unclaimed shadow wallets of `tokens`, at most `limit`
+    async fn pending_claims(
+        client: &tokio::sync::Mutex<KnishIOClient>,
+        tokens: &[String],
+        claimed: &HashSet<(String, Option<String>)>,
+        limit: usize,
+    ) -> Result<Vec<PendingClaim>> {
+        let client = client.lock().await;
+        let mut pending = Vec::new();
+        for token in tokens {
+            let wallets = client.query_wallets(None, Some(token)).await?;
+            pending.extend(wallets.into_iter()
+                .filter(|w| w.is_shadow() && w.balance_info().amount > 0)
+                .filter(|w| !claimed.contains(&(token.clone(), w.batch_id.clone())))
+                .map(|w| PendingClaim { token: token.clone(), batch_id: w.batch_id, amount: w.balance }));
+        }
+        pending.truncate(limit);
+        Ok(pending)
+    }
+
+    /// Claim one shadow wallet, returning the molecular hash of the accepted claim
+    async fn claim_pending(client: &tokio::sync::Mutex<KnishIOClient>, shadow: &PendingClaim) -> Result<Option<String>> {
+        let mut client = client.lock().await;
+        let response = client.claim_shadow_wallet(&shadow.token, shadow.batch_id.as_deref(), None).await?;
+        if !response.success() {
+            return Err(KnishIOError::custom(format!(
+                "Claim rejected: {}",
+                response.reason().unwrap_or_else(|| "unknown reason".to_string())
+            )));
+        }
+
+        Ok(response.get("molecularHash").and_then(|h| h.as_str()).map(str::to_string))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{generate_bundle_hash, generate_secret};
+    use crate::test_ledger::TestLedger;
+
+    #[tokio::test]
+    async fn test_shadow_wallets_are_claimed_in_batches() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("auto-claim-owner");
+        let bundle = generate_bundle_hash(&secret);
+        ledger.fund_shadow(&bundle, "GIFT", Some("batch-1"), 25.0);
+        ledger.fund_shadow(&bundle, "DROP", Some("batch-2"), 4.0);
+
+        let client = Arc::new(tokio::sync::Mutex::new(ledger.client(&secret)));
+        let (sender, mut events) = tokio::sync::mpsc::unbounded_channel();
+        let policy = AutoClaimPolicy::default()
+            .poll_interval(Duration::from_millis(50))
+            .batch_size(1)
+            .claim_interval(Duration::from_millis(10))
+            .subscribe(false);
+        let task = KnishIOClient::auto_claim(
+            client.clone(),
+            vec!["GIFT".to_string(), "DROP".to_string()],
+            policy,
+            move |event| { let _ = sender.send(event); },
+        );
+
+        let mut claimed = Vec::new();
+        for _ in 0..2 {
+            let event = tokio::time::timeout(Duration::from_secs(10), events.recv()).await.unwrap().unwrap();
+            match event {
+                AutoClaimEvent::Claimed { token, amount, molecular_hash, .. } => {
+                    assert!(molecular_hash.is_some());
+                    claimed.push((token, amount));
+                }
+                failed => panic!("unexpected {}", failed),
+            }
+        }
+        assert_eq!(claimed, vec![("GIFT".to_string(), "25".to_string()), ("DROP".to_string(), "4".to_string())]);
+
+        // Promoted wallets are regular, and nothing is claimed twice
+        let wallets = client.lock().await.query_wallets(None, Some("GIFT")).await.unwrap();
+        assert_eq!(wallets.len(), 1);
+        assert!(!wallets[0].is_shadow());
+        assert_eq!(ledger.balance(&bundle, "GIFT"), 25.0);
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(events.try_recv().is_err());
+
+        task.abort();
+    }
+}
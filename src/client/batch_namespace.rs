@@ -0,0 +1,126 @@
+//! Namespaced batch IDs
+//!
+//! Batch IDs the client generates are bare 64-hex hashes by default. Platforms serving
+//! several tenants from one bundle can set a namespace with `set_batch_namespace`; every
+//! batch ID the client then generates for token creation, token requests and transfer
+//! recipients reads `<namespace>:<64 hex>`, so a shadow wallet or stackable batch traces
+//! back to its tenant from the ID alone.
+//!
+//! Batch IDs passed in by the caller are checked with `validate_batch_id`: plain IDs are
+//! accepted as before, namespaced ones must be well formed.
+
+use crate::client::KnishIOClient;
+use crate::crypto::{generate_batch_id, generate_namespaced_batch_id, validate_batch_id, validate_batch_namespace};
+use crate::error::Result;
+use crate::wallet::Wallet;
+
+impl KnishIOClient {
+    /// Scope the batch IDs this client generates to `namespace`
+    ///
+    /// None goes back to plain batch IDs.
+    ///
+    /// # Errors
+    ///
+    /// `ConfigurationError` for a namespace that is not 1-32 ASCII letters, digits, `-` or `_`
+    pub fn set_batch_namespace(&mut self, namespace: Option<&str>) -> Result<()> {
+        if let Some(namespace) = namespace {
+            validate_batch_namespace(namespace)?;
+        }
+        self.batch_namespace = namespace.map(str::to_string);
+        Ok(())
+    }
+
+    /// Namespace of the batch IDs this client generates
+    pub fn batch_namespace(&self) -> Option<&str> {
+        self.batch_namespace.as_deref()
+    }
+
+    /// Generate a batch ID, in the client's namespace when one is set
+    pub fn new_batch_id(&self) -> String {
+        match self.batch_namespace {
+            Some(ref namespace) => generate_namespaced_batch_id(namespace).unwrap_or_else(|_| generate_batch_id()),
+            None => generate_batch_id(),
+        }
+    }
+
+    /// `batch_id` after validation, or a newly generated one when None
+    ///
+    /// # Errors
+    ///
+    /// `BatchId` for a malformed `batch_id`
+    pub(crate) fn batch_id_or_new(&self, batch_id: Option<&str>) -> Result<String> {
+        match batch_id {
+            Some(batch_id) => {
+                validate_batch_id(batch_id)?;
+                Ok(batch_id.to_string())
+            }
+            None => Ok(self.new_batch_id()),
+        }
+    }
+
+    /// Give a transfer recipient its batch ID: `batch_id` if given, else a new one when the
+    /// source wallet is batched
+    ///
+    /// # Errors
+    ///
+    /// `BatchId` for a malformed `batch_id`
+    pub(crate) fn init_recipient_batch_id(&self, recipient: &mut Wallet, source: &Wallet, batch_id: Option<&str>) -> Result<()> {
+        if let Some(batch_id) = batch_id {
+            validate_batch_id(batch_id)?;
+            recipient.batch_id = Some(batch_id.to_string());
+        } else if self.batch_namespace.is_some() && source.batch_id.is_some() {
+            recipient.batch_id = Some(self.new_batch_id());
+        } else {
+            recipient.init_batch_id(Some(source), false);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::KnishIOClient;
+    use crate::crypto::{batch_id_namespace, generate_bundle_hash, generate_secret};
+    use crate::error::KnishIOError;
+    use crate::test_ledger::TestLedger;
+    use crate::wallet::Wallet;
+    use crate::ClientBuilder;
+    use serde_json::Value;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_generated_batch_ids_carry_the_namespace() {
+        let mut client = KnishIOClient::new("http://localhost:8000/graphql", None, None, None, None, None);
+        assert_eq!(batch_id_namespace(&client.new_batch_id()), None);
+
+        client.set_batch_namespace(Some("acme")).unwrap();
+        assert_eq!(batch_id_namespace(&client.new_batch_id()), Some("acme"));
+        assert!(matches!(client.set_batch_namespace(Some("acme:eu")), Err(KnishIOError::ConfigurationError(_))));
+        assert_eq!(client.batch_namespace(), Some("acme"));
+
+        // A batched source gives the recipient a namespaced batch; an unbatched one none
+        let mut source = Wallet::create(Some(&generate_secret("batch-ns")), None, "SKU", None, None).unwrap();
+        source.batch_id = Some("batch-1".to_string());
+        let mut recipient = Wallet::create(Some(&generate_secret("batch-ns-to")), None, "SKU", None, None).unwrap();
+        client.init_recipient_batch_id(&mut recipient, &source, None).unwrap();
+        assert_eq!(recipient.batch_id.as_deref().and_then(batch_id_namespace), Some("acme"));
+        assert!(client.init_recipient_batch_id(&mut recipient, &source, Some("acme:xyz")).is_err());
+
+        let builder = ClientBuilder::new().uri("http://localhost:8000/graphql").batch_namespace("tenant-7");
+        assert_eq!(builder.build().unwrap().batch_namespace(), Some("tenant-7"));
+        assert!(ClientBuilder::new().uri("http://localhost:8000/graphql").batch_namespace("").build().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_malformed_batch_id_is_rejected() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("batch-ns-owner");
+        let mut client = ledger.client(&secret);
+        client.set_batch_namespace(Some("acme")).unwrap();
+
+        let meta: HashMap<String, Value> = [("fungibility".to_string(), Value::from("stackable"))].into();
+        let result = client.create_token("SKU", Some(10.0), Some(meta), Some("acme:not-a-hash"), Vec::new()).await;
+        assert!(matches!(result, Err(KnishIOError::BatchId)));
+        assert_eq!(ledger.balance(&generate_bundle_hash(&secret), "SKU"), 0.0);
+    }
+}
@@ -29,10 +29,18 @@
 //! # }
 //! ```
 
+use crate::auth::{Fingerprint, SecretSource};
 use crate::client::KnishIOClient;
-use crate::graphql::{GraphQLClient, ClientConfig, RetryConfig, SocketConfig};
+use crate::client::discovery::{discover, DiscoveryConfig};
+use crate::codec::WireFormat;
+use crate::crypto::KeyDerivationConfig;
+use crate::graphql::{GraphQLClient, ClientConfig, QueryCostConfig, RetryConfig, RetryPolicy, SchedulerConfig, SocketConfig};
 use crate::error::{KnishIOError, Result};
+use crate::token_unit::UnitSelection;
+use crate::types::SystemTokens;
+use crate::utils::clock::Clock;
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Builder for creating KnishIOClient instances with fluent API
@@ -48,6 +56,12 @@ pub struct ClientBuilder {
     cell_slug: Option<String>,
     /// User secret for cryptographic operations
     secret: Option<String>,
+    /// Where `build` fetches the secret from, instead of `secret`
+    secret_source: Option<SecretSource>,
+    /// Seed `build` derives the secret from, instead of `secret`
+    seed: Option<String>,
+    /// How secrets are derived from seeds
+    key_derivation: Option<KeyDerivationConfig>,
     /// WebSocket configuration for real-time subscriptions
     socket_config: Option<SocketConfig>,
     /// Custom GraphQL client (optional)
@@ -56,6 +70,8 @@ pub struct ClientBuilder {
     server_sdk_version: u32,
     /// Enable ML-KEM quantum encryption
     encryption: bool,
+    /// Propose molecules without the signer's bundle
+    anonymous: bool,
     /// Enable debug logging
     logging: bool,
     /// Connection timeout in seconds
@@ -70,6 +86,32 @@ pub struct ClientBuilder {
     auto_auth: bool,
     /// Accept invalid TLS certificates (for self-signed certs in dev)
     insecure_tls: bool,
+    /// Strategy for picking stackable units in amount-only transfers
+    unit_selection: UnitSelection,
+    /// Token slugs of the ContinuID and authorization wallets
+    system_tokens: SystemTokens,
+    /// Time source of auth token expiry checks
+    clock: Option<Arc<dyn Clock>>,
+    /// Introspect the node's schema on `build_async` and warn about drift
+    schema_check: bool,
+    /// Device fingerprint for guest authentication
+    fingerprint: Option<Arc<dyn Fingerprint>>,
+    /// Preferred request body format
+    wire_format: Option<WireFormat>,
+    /// Retry policy of `submit_molecule`
+    submit_policy: Option<RetryPolicy>,
+    /// Delay before a read query is duplicated to a second node
+    hedge_delay: Option<Duration>,
+    /// Slow and large operation thresholds
+    query_cost: Option<QueryCostConfig>,
+    /// Priority queue limits of outgoing requests
+    scheduler: Option<SchedulerConfig>,
+    /// Positions redrawn when a new wallet's address is taken
+    wallet_collision_retries: Option<u32>,
+    /// Namespace of generated batch IDs
+    batch_namespace: Option<String>,
+    /// Where `build_async` fetches the node list from
+    discovery: Option<DiscoveryConfig>,
 }
 
 impl Default for ClientBuilder {
@@ -93,10 +135,14 @@ impl ClientBuilder {
             uris: Vec::new(),
             cell_slug: None,
             secret: None,
+            secret_source: None,
+            seed: None,
+            key_derivation: None,
             socket_config: None,
             graphql_client: None,
             server_sdk_version: 3, // Default to SDK version 3
             encryption: false,
+            anonymous: false,
             logging: false,
             connection_timeout: None,
             request_timeout: None,
@@ -104,6 +150,19 @@ impl ClientBuilder {
             max_retries: None,
             auto_auth: true, // Enable auto-auth by default
             insecure_tls: false,
+            unit_selection: UnitSelection::default(),
+            system_tokens: SystemTokens::default(),
+            clock: None,
+            schema_check: false,
+            fingerprint: None,
+            wire_format: None,
+            hedge_delay: None,
+            query_cost: None,
+            scheduler: None,
+            wallet_collision_retries: None,
+            batch_namespace: None,
+            discovery: None,
+            submit_policy: None,
         }
     }
 
@@ -175,6 +234,61 @@ impl ClientBuilder {
     /// ```
     pub fn secret<S: Into<String>>(mut self, secret: S) -> Self {
         self.secret = Some(secret.into());
+        self.secret_source = None;
+        self.seed = None;
+        self
+    }
+
+    /// Fetch the user secret from `source` when the client is built
+    ///
+    /// Replaces any secret set with `secret`. `build` fails if the source cannot produce
+    /// a secret.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use knishio_client::ClientBuilder;
+    /// use knishio_client::auth::SecretSource;
+    ///
+    /// let builder = ClientBuilder::new().secret_source(SecretSource::env("KNISHIO_SECRET"));
+    /// ```
+    pub fn secret_source(mut self, source: SecretSource) -> Self {
+        self.secret_source = Some(source);
+        self.secret = None;
+        self.seed = None;
+        self
+    }
+
+    /// Derive the user secret from `seed` when the client is built
+    ///
+    /// Uses the `key_derivation` configuration, interop mode by default. Replaces any
+    /// secret set with `secret` or `secret_source`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use knishio_client::ClientBuilder;
+    /// let builder = ClientBuilder::new().seed("my-seed");
+    /// ```
+    pub fn seed<S: Into<String>>(mut self, seed: S) -> Self {
+        self.seed = Some(seed.into());
+        self.secret = None;
+        self.secret_source = None;
+        self
+    }
+
+    /// Set how secrets are derived from seeds (checked when building)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use knishio_client::ClientBuilder;
+    /// use knishio_client::crypto::KeyDerivationConfig;
+    ///
+    /// let builder = ClientBuilder::new().key_derivation(KeyDerivationConfig::strengthened());
+    /// ```
+    pub fn key_derivation(mut self, config: KeyDerivationConfig) -> Self {
+        self.key_derivation = Some(config);
         self
     }
 
@@ -212,6 +326,23 @@ impl ClientBuilder {
         self
     }
 
+    /// Propose molecules anonymously, without the signer's bundle hash
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether new molecules leave their bundle unset
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use knishio_client::ClientBuilder;
+    /// let builder = ClientBuilder::new().anonymous(true);
+    /// ```
+    pub fn anonymous(mut self, enabled: bool) -> Self {
+        self.anonymous = enabled;
+        self
+    }
+
     /// Enable or disable debug logging
     ///
     /// # Arguments
@@ -405,6 +536,227 @@ impl ClientBuilder {
         self
     }
 
+    /// Set the strategy for picking stackable token units in amount-only transfers
+    ///
+    /// # Arguments
+    ///
+    /// * `selection` - Unit selection strategy
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use knishio_client::ClientBuilder;
+    /// use knishio_client::token_unit::UnitSelection;
+    ///
+    /// let builder = ClientBuilder::new().unit_selection(UnitSelection::Random);
+    /// ```
+    pub fn unit_selection(mut self, selection: UnitSelection) -> Self {
+        self.unit_selection = selection;
+        self
+    }
+
+    /// Set the token slugs of the ContinuID and authorization wallets
+    ///
+    /// Only needed against deployments that renamed `USER` and `AUTH`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use knishio_client::{ClientBuilder, SystemTokens};
+    /// let builder = ClientBuilder::new().system_tokens(SystemTokens::new("MEMBER", "LOGIN"));
+    /// ```
+    pub fn system_tokens(mut self, tokens: SystemTokens) -> Self {
+        self.system_tokens = tokens;
+        self
+    }
+
+    /// Judge auth token expiry against `clock` instead of the wall clock
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use knishio_client::ClientBuilder;
+    /// use knishio_client::utils::ManualClock;
+    /// use std::sync::Arc;
+    ///
+    /// let builder = ClientBuilder::new().clock(Arc::new(ManualClock::starting_now()));
+    /// ```
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Set the device fingerprint the guest AUTH wallet is derived from
+    ///
+    /// # Arguments
+    ///
+    /// * `fingerprint` - Fingerprint provider (any `Fn() -> Result<String>` works)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use knishio_client::ClientBuilder;
+    /// use knishio_client::auth::PersistentIdFingerprint;
+    ///
+    /// let builder = ClientBuilder::new().fingerprint(PersistentIdFingerprint::new("/var/lib/myapp/guest-id"));
+    /// ```
+    pub fn fingerprint<F: Fingerprint + 'static>(mut self, fingerprint: F) -> Self {
+        self.fingerprint = Some(Arc::new(fingerprint));
+        self
+    }
+
+    /// Prefer a binary wire format for GraphQL requests
+    ///
+    /// Nodes that do not accept the format are detected on the first request, which is
+    /// then retried as JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use knishio_client::ClientBuilder;
+    /// use knishio_client::codec::WireFormat;
+    ///
+    /// let builder = ClientBuilder::new().wire_format(WireFormat::Json);
+    /// ```
+    pub fn wire_format(mut self, format: WireFormat) -> Self {
+        self.wire_format = Some(format);
+        self
+    }
+
+    /// Hedge read queries: a query unanswered after `delay` is also sent to a second URI
+    ///
+    /// The first node to answer wins and the other request is cancelled. Requires at
+    /// least two URIs; mutations are never duplicated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use knishio_client::ClientBuilder;
+    /// use std::time::Duration;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .uris(vec!["https://node1.knish.io", "https://node2.knish.io"])
+    ///     .hedged_reads(Duration::from_millis(50));
+    /// ```
+    pub fn hedged_reads(mut self, delay: Duration) -> Self {
+        self.hedge_delay = Some(delay);
+        self
+    }
+
+    /// Warn about queries and mutations slower or larger than `config` allows
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use knishio_client::ClientBuilder;
+    /// use knishio_client::QueryCostConfig;
+    /// use std::time::Duration;
+    ///
+    /// let builder = ClientBuilder::new().query_cost(
+    ///     QueryCostConfig::default().slow_after(Duration::from_millis(500)).large_response_bytes(1 << 20),
+    /// );
+    /// ```
+    pub fn query_cost(mut self, config: QueryCostConfig) -> Self {
+        self.query_cost = Some(config);
+        self
+    }
+
+    /// Send requests by priority, at most `config.max_in_flight` at once
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use knishio_client::ClientBuilder;
+    /// use knishio_client::SchedulerConfig;
+    ///
+    /// let builder = ClientBuilder::new().request_scheduler(
+    ///     SchedulerConfig::default().max_in_flight(4).rate_limit(20.0, 5),
+    /// );
+    /// ```
+    pub fn request_scheduler(mut self, config: SchedulerConfig) -> Self {
+        self.scheduler = Some(config);
+        self
+    }
+
+    /// Check each new wallet's address against the ledger, drawing up to `retries` new positions
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use knishio_client::ClientBuilder;
+    /// let builder = ClientBuilder::new().wallet_collision_check(3);
+    /// ```
+    pub fn wallet_collision_check(mut self, retries: u32) -> Self {
+        self.wallet_collision_retries = Some(retries);
+        self
+    }
+
+    /// Scope generated batch IDs to `namespace` (checked when building)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use knishio_client::ClientBuilder;
+    /// let builder = ClientBuilder::new().batch_namespace("tenant-42");
+    /// ```
+    pub fn batch_namespace<S: Into<String>>(mut self, namespace: S) -> Self {
+        self.batch_namespace = Some(namespace.into());
+        self
+    }
+
+    /// Fetch the node list from a bootstrap URL or DNS SRV name when building
+    ///
+    /// Only `build_async` performs discovery; the discovered nodes replace any URIs
+    /// configured with `uri`/`uris`, which remain the fallback if discovery fails.
+    /// Keep the list fresh afterwards with `KnishIOClient::spawn_node_discovery`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use knishio_client::ClientBuilder;
+    /// use knishio_client::client::DiscoveryConfig;
+    ///
+    /// let builder = ClientBuilder::new().discovery(DiscoveryConfig::srv("_knishio._tcp.knish.io"));
+    /// ```
+    pub fn discovery(mut self, config: DiscoveryConfig) -> Self {
+        self.discovery = Some(config);
+        self
+    }
+
+    /// Set the retry policy `submit_molecule` applies before dead-lettering a molecule
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use knishio_client::ClientBuilder;
+    /// use knishio_client::graphql::RetryPolicy;
+    ///
+    /// let builder = ClientBuilder::new().submit_policy(RetryPolicy::network_optimized());
+    /// ```
+    pub fn submit_policy(mut self, policy: RetryPolicy) -> Self {
+        self.submit_policy = Some(policy);
+        self
+    }
+
+    /// Check the node's schema against the fields this SDK uses during `build_async`
+    ///
+    /// Drift is logged as warnings and available afterwards via `client.schema_report()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to run the check
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use knishio_client::ClientBuilder;
+    /// let builder = ClientBuilder::new().schema_check(true);
+    /// ```
+    pub fn schema_check(mut self, enabled: bool) -> Self {
+        self.schema_check = enabled;
+        self
+    }
+
     /// Validate the builder configuration
     ///
     /// # Returns
@@ -487,6 +839,8 @@ impl ClientBuilder {
                 keep_alive_timeout: Duration::from_secs(90),
                 tcp_keepalive: Some(Duration::from_secs(60)),
                 insecure_tls: self.insecure_tls,
+                headers: self.custom_headers.clone(),
+                ..ClientConfig::default()
             };
 
             let retry_config = if let Some(max) = self.max_retries {
@@ -512,13 +866,47 @@ impl ClientBuilder {
             Some(self.logging),
         );
 
+        if let Some(config) = self.key_derivation {
+            client.set_key_derivation(config)?;
+        }
+
         // Set the secret if provided
-        if let Some(secret) = self.secret {
+        if let Some(ref source) = self.secret_source {
+            client.set_secret(source.resolve()?);
+        } else if let Some(ref seed) = self.seed {
+            client.set_secret_from_seed(seed)?;
+        } else if let Some(secret) = self.secret {
             client.set_secret(secret);
         }
 
         // Apply encryption setting
         client.set_encrypt(self.encryption);
+        client.set_anonymous(self.anonymous);
+        client.set_unit_selection(self.unit_selection);
+        client.set_system_tokens(self.system_tokens);
+        if let Some(clock) = self.clock {
+            client.set_clock(clock);
+        }
+        if let Some(fingerprint) = self.fingerprint {
+            client.fingerprint = fingerprint;
+        }
+        if let Some(format) = self.wire_format {
+            client.set_wire_format(format);
+        }
+        if let Some(policy) = self.submit_policy {
+            client.set_submit_policy(policy);
+        }
+        if let Some(delay) = self.hedge_delay {
+            client.set_hedged_reads(Some(delay))?;
+        }
+        if self.query_cost.is_some() {
+            client.set_query_cost(self.query_cost);
+        }
+        if self.scheduler.is_some() {
+            client.set_request_scheduler(self.scheduler);
+        }
+        client.set_wallet_collision_check(self.wallet_collision_retries);
+        client.set_batch_namespace(self.batch_namespace.as_deref())?;
 
         Ok(client)
     }
@@ -542,11 +930,25 @@ impl ClientBuilder {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn build_async(self) -> Result<KnishIOClient> {
+    pub async fn build_async(mut self) -> Result<KnishIOClient> {
         // Save values before self is moved
         let auto_auth = self.auto_auth;
         let logging = self.logging;
-        
+        let schema_check = self.schema_check;
+
+        // Discovered nodes replace the static list; without a static list discovery must succeed
+        if let Some(config) = self.discovery.take() {
+            match discover(&config).await {
+                Ok(directory) => self.uris = directory.uris_for_cell(self.cell_slug.as_deref()),
+                Err(e) if !self.uris.is_empty() => {
+                    if logging {
+                        eprintln!("[ClientBuilder] Node discovery failed, using configured URIs: {}", e);
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
         let mut client = self.build()?;
 
         // Perform initial setup if auto-auth is enabled
@@ -567,6 +969,15 @@ impl ClientBuilder {
             }
         }
 
+        // Report schema drift early; an unreachable node should not fail the build either
+        if schema_check {
+            if let Err(e) = client.check_schema().await {
+                if logging {
+                    eprintln!("[ClientBuilder] Schema check failed: {}", e);
+                }
+            }
+        }
+
         Ok(client)
     }
 }
@@ -692,6 +1103,7 @@ impl ClientBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::crypto::generate_secret;
 
     #[test]
     fn test_builder_default() {
@@ -703,6 +1115,40 @@ mod tests {
         assert!(builder.auto_auth);
     }
 
+    #[test]
+    fn test_builder_secret_source() {
+        let client = ClientBuilder::minimal("http://localhost:8000")
+            .secret("literal")
+            .secret_source(SecretSource::callback(|| Ok("sourced-secret".to_string())))
+            .build()
+            .unwrap();
+        assert_eq!(client.get_secret().unwrap(), "sourced-secret");
+
+        let failing = ClientBuilder::minimal("http://localhost:8000")
+            .secret_source(SecretSource::callback(|| Err(KnishIOError::SecretUnavailable("locked".to_string()))));
+        assert!(matches!(failing.build(), Err(KnishIOError::SecretUnavailable(_))));
+    }
+
+    #[test]
+    fn test_builder_key_derivation() {
+        let client = ClientBuilder::minimal("http://localhost:8000").seed("builder-seed").build().unwrap();
+        assert!(client.key_derivation().is_interop());
+        assert_eq!(client.get_secret().unwrap(), generate_secret("builder-seed"));
+
+        let config = KeyDerivationConfig::strengthened().with_iterations(1_000);
+        let client = ClientBuilder::minimal("http://localhost:8000")
+            .key_derivation(config.clone())
+            .seed("builder-seed")
+            .build()
+            .unwrap();
+        assert_eq!(client.key_derivation(), &config);
+        assert_eq!(client.get_secret().unwrap(), config.derive_secret("builder-seed").unwrap());
+
+        let strict = ClientBuilder::minimal("http://localhost:8000")
+            .key_derivation(KeyDerivationConfig::interop().with_iterations(1_000));
+        assert!(matches!(strict.build(), Err(KnishIOError::ConfigurationError(_))));
+    }
+
     #[test]
     fn test_builder_fluent_api() {
         let builder = ClientBuilder::new()
@@ -721,6 +1167,106 @@ mod tests {
         assert_eq!(builder.server_sdk_version, 3);
     }
 
+    #[test]
+    fn test_builder_unit_selection() {
+        let client = ClientBuilder::new()
+            .uri("https://api.knish.io")
+            .unit_selection(UnitSelection::Last)
+            .build()
+            .unwrap();
+
+        assert_eq!(client.get_unit_selection(), UnitSelection::Last);
+    }
+
+    #[test]
+    fn test_builder_system_tokens() {
+        let client = ClientBuilder::new().uri("https://api.knish.io").build().unwrap();
+        assert!(client.system_tokens().is_default());
+
+        let client = ClientBuilder::new()
+            .uri("https://api.knish.io")
+            .system_tokens(SystemTokens::new("MEMBER", "LOGIN"))
+            .build()
+            .unwrap();
+        assert_eq!(client.system_tokens(), &SystemTokens::new("MEMBER", "LOGIN"));
+    }
+
+    #[test]
+    fn test_builder_wallet_collision_check() {
+        let client = ClientBuilder::new().uri("https://api.knish.io").build().unwrap();
+        assert_eq!(client.wallet_collision_check(), None);
+
+        let client = ClientBuilder::new().uri("https://api.knish.io").wallet_collision_check(3).build().unwrap();
+        assert_eq!(client.wallet_collision_check(), Some(3));
+    }
+
+    #[test]
+    fn test_builder_fingerprint() {
+        let client = ClientBuilder::new()
+            .uri("https://api.knish.io")
+            .fingerprint(|| Ok("kiosk-7".to_string()))
+            .build()
+            .unwrap();
+
+        assert_eq!(client.get_fingerprint().unwrap(), "kiosk-7");
+    }
+
+    #[test]
+    fn test_builder_wire_format() {
+        let client = ClientBuilder::new()
+            .uri("https://api.knish.io")
+            .wire_format(WireFormat::Json)
+            .build()
+            .unwrap();
+
+        assert_eq!(client.wire_format(), WireFormat::Json);
+    }
+
+    #[test]
+    fn test_builder_hedged_reads() {
+        let client = ClientBuilder::new()
+            .uris(vec!["https://node1.knish.io", "https://node2.knish.io"])
+            .hedged_reads(Duration::from_millis(25))
+            .build()
+            .unwrap();
+
+        let hedge = client.hedged_reads().unwrap();
+        assert_ne!(Some(hedge.uri), client.get_uri());
+        assert_eq!(hedge.delay, Duration::from_millis(25));
+
+        let single = ClientBuilder::new()
+            .uri("https://api.knish.io")
+            .hedged_reads(Duration::from_millis(25))
+            .build();
+        assert!(matches!(single, Err(KnishIOError::ConfigurationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_builder_discovery_fallback() {
+        let unreachable = DiscoveryConfig::bootstrap("http://127.0.0.1:9/nodes");
+
+        let client = ClientBuilder::new()
+            .uri("https://api.knish.io")
+            .discovery(unreachable.clone())
+            .build_async()
+            .await
+            .unwrap();
+        assert_eq!(client.get_uris(), ["https://api.knish.io"]);
+
+        assert!(ClientBuilder::new().discovery(unreachable).build_async().await.is_err());
+    }
+
+    #[test]
+    fn test_builder_submit_policy() {
+        let client = ClientBuilder::new()
+            .uri("https://api.knish.io")
+            .submit_policy(RetryPolicy::new().with_max_attempts(7))
+            .build()
+            .unwrap();
+
+        assert_eq!(client.get_submit_policy().max_attempts, 7);
+    }
+
     #[test]
     fn test_builder_multiple_uris() {
         let builder = ClientBuilder::new()
@@ -0,0 +1,249 @@
+//! Bounded-concurrency bulk operations
+//!
+//! Mass transfers or meta writes are run through `KnishIOClient::for_each_concurrent`,
+//! which keeps at most `limit` operations in flight and collects every outcome into a
+//! `BulkSummary` instead of stopping at the first failure.
+//!
+//! Molecules signed by the same bundle must reach the ledger one at a time: each one
+//! consumes the bundle's current ContinuID wallet and names its successor. Operations
+//! therefore sign through a `BulkContext`, which hands out one shared client per bundle
+//! behind an async lock. Reads can run fully in parallel on `BulkContext::client()`;
+//! only the signing part of each operation is serialized per bundle.
+
+use crate::client::KnishIOClient;
+use crate::crypto::generate_bundle_hash;
+use crate::error::{KnishIOError, Result};
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+/// Outcome of one item of a bulk operation
+#[derive(Debug)]
+pub struct BulkOutcome<T> {
+    /// Position of the item in the input
+    pub index: usize,
+    /// What the operation returned for it
+    pub result: Result<T>,
+}
+
+/// Outcomes of a bulk operation, in input order
+#[derive(Debug)]
+pub struct BulkSummary<T> {
+    /// One outcome per input item
+    pub outcomes: Vec<BulkOutcome<T>>,
+    /// Wall-clock time of the whole run
+    pub elapsed: Duration,
+}
+
+impl<T> BulkSummary<T> {
+    /// Successful items as `(index, value)`
+    pub fn successes(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.outcomes.iter().filter_map(|o| o.result.as_ref().ok().map(|v| (o.index, v)))
+    }
+
+    /// Failed items as `(index, error)`
+    pub fn failures(&self) -> impl Iterator<Item = (usize, &KnishIOError)> {
+        self.outcomes.iter().filter_map(|o| o.result.as_ref().err().map(|e| (o.index, e)))
+    }
+
+    /// Number of successful items
+    pub fn success_count(&self) -> usize {
+        self.successes().count()
+    }
+
+    /// Number of failed items
+    pub fn failure_count(&self) -> usize {
+        self.failures().count()
+    }
+
+    /// True when every item succeeded
+    pub fn all_succeeded(&self) -> bool {
+        self.outcomes.iter().all(|o| o.result.is_ok())
+    }
+}
+
+/// Shared state handed to every operation of a bulk run
+pub struct BulkContext {
+    base: KnishIOClient,
+    signers: Mutex<HashMap<String, Arc<AsyncMutex<KnishIOClient>>>>,
+}
+
+impl BulkContext {
+    fn new(base: KnishIOClient) -> Self {
+        BulkContext {
+            base,
+            signers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Client for read-only queries; never blocks
+    pub fn client(&self) -> &KnishIOClient {
+        &self.base
+    }
+
+    /// Exclusive access to the client signing for the base client's bundle
+    ///
+    /// Operations holding the guard run one at a time, so each molecule builds on the
+    /// ContinuID left by the previous one.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MissingSecret` if the base client has no secret
+    pub async fn signer(&self) -> Result<OwnedMutexGuard<KnishIOClient>> {
+        let secret = self.base.secret.clone().ok_or(KnishIOError::MissingSecret)?;
+        Ok(self.signer_for(&secret).await)
+    }
+
+    /// Exclusive access to the client signing for `secret`'s bundle
+    ///
+    /// The client is created on first use from the base client's configuration and is
+    /// shared by every later operation for the same bundle. It starts without an auth
+    /// token of its own; authenticate it through the guard if the node requires one.
+    pub async fn signer_for(&self, secret: &str) -> OwnedMutexGuard<KnishIOClient> {
+        let bundle = generate_bundle_hash(secret);
+
+        let signer = {
+            let mut signers = self.signers.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            signers.entry(bundle.clone())
+                .or_insert_with(|| {
+                    let mut client = self.base.clone();
+                    if self.base.bundle.as_deref() != Some(bundle.as_str()) {
+                        client.set_secret(secret);
+                        client.remainder_wallet = None;
+                        client.last_molecule_query = None;
+                        client.auth_token = None;
+                    }
+                    Arc::new(AsyncMutex::new(client))
+                })
+                .clone()
+        };
+
+        signer.lock_owned().await
+    }
+}
+
+impl KnishIOClient {
+    /// Run `op` over `items` with at most `limit` operations in flight
+    ///
+    /// Each operation receives a `BulkContext` to sign through (see `BulkContext::signer`)
+    /// and its item. Failures are recorded in the summary and do not stop the run.
+    ///
+    /// ```no_run
+    /// # async fn demo(client: &knishio_client::KnishIOClient) {
+    /// let recipients = vec!["bundle-a".to_string(), "bundle-b".to_string()];
+    /// let summary = client.for_each_concurrent(recipients, 4, async |ctx, recipient: String| {
+    ///     let mut signer = ctx.signer().await?;
+    ///     signer.transfer_token(&recipient, "DEMO", Some(10.0), Vec::new(), None, None).await
+    /// }).await;
+    ///
+    /// println!("{} sent, {} failed", summary.success_count(), summary.failure_count());
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - Work items, one operation each
+    /// * `limit` - Maximum number of concurrent operations (0 is treated as 1)
+    /// * `op` - The operation to run per item
+    ///
+    /// # Returns
+    ///
+    /// Per-item outcomes in input order
+    pub async fn for_each_concurrent<I, T, F>(
+        &self,
+        items: impl IntoIterator<Item = I>,
+        limit: usize,
+        op: F,
+    ) -> BulkSummary<T>
+    where
+        F: AsyncFn(&BulkContext, I) -> Result<T>,
+    {
+        let started = Instant::now();
+        let ctx = BulkContext::new(self.clone());
+        let op = &op;
+        let ctx_ref = &ctx;
+
+        let mut outcomes: Vec<BulkOutcome<T>> = stream::iter(items.into_iter().enumerate())
+            .map(|(index, item)| async move {
+                BulkOutcome { index, result: op(ctx_ref, item).await }
+            })
+            .buffer_unordered(limit.max(1))
+            .collect()
+            .await;
+        outcomes.sort_by_key(|o| o.index);
+
+        let summary = BulkSummary { outcomes, elapsed: started.elapsed() };
+
+        self.log("info", &format!(
+            "KnishIOClient::for_each_concurrent() - {} succeeded, {} failed in {}ms",
+            summary.success_count(),
+            summary.failure_count(),
+            summary.elapsed.as_millis()
+        ));
+
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::generate_secret;
+    use crate::test_ledger::TestLedger;
+
+    #[tokio::test]
+    async fn test_bulk_transfers_collect_failures() {
+        let ledger = TestLedger::start().await.unwrap();
+        let alice_secret = generate_secret("bulk-alice");
+        let alice_bundle = generate_bundle_hash(&alice_secret);
+        let mut alice = ledger.client(&alice_secret);
+        let response = alice.create_token("BULK", Some(100.0), None, None, Vec::new()).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+
+        // The fourth transfer overspends and fails; the rest still go through
+        let amounts = [10.0, 20.0, 30.0, 500.0, 5.0];
+        let transfers: Vec<(String, f64)> = amounts.iter().enumerate()
+            .map(|(i, amount)| (generate_bundle_hash(&generate_secret(&format!("bulk-recipient-{}", i))), *amount))
+            .collect();
+
+        let summary = alice.for_each_concurrent(transfers.clone(), 3, async |ctx, (recipient, amount): (String, f64)| {
+            let mut signer = ctx.signer().await?;
+            let response = signer.transfer_token(&recipient, "BULK", Some(amount), Vec::new(), None, None).await?;
+            response.success().then_some(amount).ok_or_else(|| KnishIOError::custom("transfer rejected"))
+        }).await;
+
+        assert_eq!(summary.success_count(), 4);
+        assert_eq!(summary.failures().map(|(index, _)| index).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(ledger.balance(&alice_bundle, "BULK"), 35.0);
+        for (recipient, amount) in transfers.iter().filter(|(_, amount)| *amount < 100.0) {
+            assert_eq!(ledger.balance(recipient, "BULK"), *amount);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signers_are_per_bundle() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secrets: Vec<String> = (0..3).map(|i| generate_secret(&format!("bulk-sender-{}", i))).collect();
+        for secret in &secrets {
+            ledger.fund(secret, "PAR", 10.0).unwrap();
+        }
+        let sink = generate_bundle_hash(&generate_secret("bulk-sink"));
+
+        // Two transfers per sender, all submitted at once
+        let items: Vec<&String> = secrets.iter().chain(secrets.iter()).collect();
+        let client = ledger.client(&secrets[0]);
+        let summary = client.for_each_concurrent(items, 6, async |ctx, secret: &String| {
+            let mut signer = ctx.signer_for(secret).await;
+            signer.transfer_token(&sink, "PAR", Some(4.0), Vec::new(), None, None).await.map(|r| r.success())
+        }).await;
+
+        assert!(summary.all_succeeded(), "{:?}", summary.failures().collect::<Vec<_>>());
+        assert!(summary.successes().all(|(_, accepted)| *accepted));
+        assert_eq!(ledger.balance(&sink, "PAR"), 24.0);
+        for secret in &secrets {
+            assert_eq!(ledger.balance(&generate_bundle_hash(secret), "PAR"), 2.0);
+        }
+    }
+}
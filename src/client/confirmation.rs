@@ -0,0 +1,143 @@
+//! Molecule status lookup
+//!
+//! `ProposeMolecule` answers with the node's verdict when the node processes the molecule
+//! right away, but a busy node (or one that queues proposals) answers `pending`. Nodes
+//! have no query for a molecule's status, so acceptance is established through the `Atom`
+//! query: once a node holds atoms with the molecular hash, it accepted the molecule.
+//! `query_molecule` looks that up once and `await_confirmation` polls until the atoms
+//! appear. A rejection leaves no atoms, so only the `ProposeMolecule` answer can report
+//! it; `confirm_proposal` takes that answer and polls only while it is pending.
+
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::query::molecule_status::{MoleculeInfo, QueryMoleculeStatus};
+use crate::query::Query;
+use crate::response::Response;
+use std::time::{Duration, Instant};
+
+/// Pause between two status lookups in `await_confirmation`
+pub const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+impl KnishIOClient {
+    /// Status of the molecule with `molecular_hash`
+    ///
+    /// # Returns
+    ///
+    /// An accepted status once the node holds the molecule's atoms; None while the
+    /// molecule is pending, when it was rejected, or when the node does not know it
+    pub async fn query_molecule(&self, molecular_hash: &str) -> Result<Option<MoleculeInfo>> {
+        let client = self.client.as_ref().ok_or(KnishIOError::NoClient)?;
+        let response = QueryMoleculeStatus::new(molecular_hash).execute(client, None, None).await?;
+
+        Ok(MoleculeInfo::from_atoms(molecular_hash, response.data()))
+    }
+
+    /// Poll the node until it has accepted the molecule with `molecular_hash`
+    ///
+    /// A rejected molecule never shows up, so this ends in `ConfirmationTimeout`; use
+    /// `confirm_proposal` when the `ProposeMolecule` answer is at hand.
+    ///
+    /// ```no_run
+    /// # async fn demo(client: &knishio_client::KnishIOClient) -> knishio_client::Result<()> {
+    /// use std::time::Duration;
+    ///
+    /// let info = client.await_confirmation("0123abcd", Duration::from_secs(30)).await?;
+    /// println!("accepted at {:?}", info.created_at);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfirmationTimeout` when the node still holds no atoms of the molecule
+    /// after `timeout`
+    pub async fn await_confirmation(&self, molecular_hash: &str, timeout: Duration) -> Result<MoleculeInfo> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if let Some(info) = self.query_molecule(molecular_hash).await? {
+                self.log("info", &format!("KnishIOClient::await_confirmation() - {} accepted", molecular_hash));
+                return Ok(info);
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(KnishIOError::ConfirmationTimeout(format!(
+                    "{} still pending after {:?}",
+                    molecular_hash, timeout
+                )));
+            }
+            tokio::time::sleep(remaining.min(CONFIRMATION_POLL_INTERVAL)).await;
+        }
+    }
+
+    /// Verdict on a proposed molecule, polling the node while the `ProposeMolecule`
+    /// answer `response` is pending
+    ///
+    /// A rejection is returned as a status, not an error; check `MoleculeInfo::accepted`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidResponse` when `response` carries no molecular hash, and
+    /// `ConfirmationTimeout` when a pending molecule is not accepted within `timeout`
+    pub async fn confirm_proposal(&self, response: &dyn Response, timeout: Duration) -> Result<MoleculeInfo> {
+        let info = MoleculeInfo::from_response_data(response.data())
+            .ok_or(KnishIOError::InvalidResponse)?;
+
+        if info.is_final() {
+            return Ok(info);
+        }
+        self.await_confirmation(&info.molecular_hash, timeout).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{generate_bundle_hash, generate_secret};
+    use crate::test_ledger::TestLedger;
+    use crate::types::{MetaItem, MoleculeStatus};
+
+    #[tokio::test]
+    async fn test_query_molecule_and_await_confirmation() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("confirmation-owner");
+        ledger.fund(&secret, "GOLD", 10.0).unwrap();
+        let mut client = ledger.client(&secret);
+        let recipient = generate_bundle_hash(&generate_secret("confirmation-recipient"));
+
+        let response = client.transfer_token(&recipient, "GOLD", Some(4.0), Vec::new(), None, None).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        let hash = ledger.molecules().last().unwrap().molecular_hash.clone();
+
+        let info = client.query_molecule(&hash).await.unwrap().unwrap();
+        assert_eq!(info.status, MoleculeStatus::Accepted);
+        assert!(info.created_at.is_some());
+        assert_eq!(client.await_confirmation(&hash, Duration::from_secs(1)).await.unwrap(), info);
+        assert!(client.confirm_proposal(response.as_ref(), Duration::from_secs(1)).await.unwrap().accepted());
+
+        // Two molecules signed by the same ContinuID head: the second is rejected, and final too
+        let mut molecules = Vec::new();
+        for label in ["first", "second"] {
+            let mut molecule = client.create_molecule(None, None, None, None).await.unwrap();
+            molecule.init_meta(vec![MetaItem::new("label", label)], "note", label, None).unwrap();
+            molecule.sign(None, false, true).unwrap();
+            molecules.push(molecule);
+        }
+        let mut responses = Vec::new();
+        for molecule in molecules {
+            responses.push(client.propose_molecule(molecule).await.unwrap());
+        }
+        let stale = ledger.molecules().last().unwrap().clone();
+        let info = client.confirm_proposal(responses[1].as_ref(), Duration::from_secs(1)).await.unwrap();
+        assert_eq!(info.status, MoleculeStatus::Rejected);
+        assert_eq!(info.reason, stale.reason);
+
+        // A rejected molecule leaves no atoms to find
+        assert_eq!(client.query_molecule(&stale.molecular_hash).await.unwrap(), None);
+
+        assert_eq!(client.query_molecule("unknown").await.unwrap(), None);
+        let result = client.await_confirmation("unknown", Duration::from_millis(50)).await;
+        assert!(matches!(result, Err(KnishIOError::ConfirmationTimeout(_))));
+    }
+}
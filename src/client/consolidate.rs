@@ -0,0 +1,294 @@
+//! Wallet consolidation
+//!
+//! Long-lived bundles accumulate many small wallets per token: every incoming transfer
+//! lands in a fresh wallet. Consolidation sweeps them into a single fresh wallet per
+//! batch ID. A molecule can only debit one wallet, so each source wallet costs one
+//! molecule that moves its whole balance (and, for stackable tokens, all of its units)
+//! into the target. Wallets of different batch IDs are never merged.
+//!
+//! `plan_consolidation` computes what would happen without touching the ledger;
+//! `consolidate_wallets` logs the plan and then executes it.
+//!
+//! Molecules move whole amounts only, so wallets holding a fractional balance are left
+//! where they are and listed in the plan's `skipped`.
+
+use crate::auth::AuthScope;
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::mutation::transfer_tokens::{MutationTransferTokens, TransferTokensParams};
+use crate::mutation::Mutation;
+use crate::wallet::{Balance, Wallet};
+use std::fmt;
+
+/// Wallets of one batch ID that will be swept into one target wallet
+#[derive(Debug, Clone)]
+pub struct ConsolidationGroup {
+    /// Batch ID shared by the wallets (None for fungible tokens)
+    pub batch_id: Option<String>,
+    /// Fresh wallet receiving every balance of the group
+    pub target: Wallet,
+    /// Wallets to be emptied, largest balance first
+    pub sources: Vec<Wallet>,
+}
+
+impl ConsolidationGroup {
+    /// Combined balance of the source wallets; None when it overflows
+    pub fn total(&self) -> Option<Balance> {
+        self.sources.iter().try_fold(Balance::default(), |total, source| total.checked_add(&source.balance_info()))
+    }
+}
+
+/// What consolidating a token's wallets would do
+#[derive(Debug, Clone)]
+pub struct ConsolidationPlan {
+    /// Token slug
+    pub token: String,
+    /// One group per batch ID with more than one funded wallet
+    pub groups: Vec<ConsolidationGroup>,
+    /// Funded wallets left out because their balance is not a whole number
+    pub skipped: Vec<Wallet>,
+}
+
+impl ConsolidationPlan {
+    /// Number of molecules executing the plan proposes
+    pub fn molecule_count(&self) -> usize {
+        self.groups.iter().map(|g| g.sources.len()).sum()
+    }
+
+    /// True when the token's wallets are already consolidated
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+}
+
+impl fmt::Display for ConsolidationPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            write!(f, "{}: nothing to consolidate", self.token)?;
+        } else {
+            write!(f, "{}: {} molecules", self.token, self.molecule_count())?;
+        }
+        for group in &self.groups {
+            write!(
+                f,
+                "\n  batch {}: {} wallets ({} total) -> {}",
+                group.batch_id.as_deref().unwrap_or("-"),
+                group.sources.len(),
+                group.total().map_or_else(|| "overflowing".to_string(), |total| total.to_string()),
+                group.target.address.as_deref().unwrap_or_default()
+            )?;
+        }
+        if !self.skipped.is_empty() {
+            write!(f, "\n  skipped {} wallets with fractional balances", self.skipped.len())?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of sweeping one source wallet
+#[derive(Debug)]
+pub struct SweepOutcome {
+    /// Address of the emptied (or not) source wallet
+    pub source: String,
+    /// Amount moved
+    pub amount: Balance,
+    /// Molecular hash of the accepted molecule, or why the sweep failed
+    pub result: Result<Option<String>>,
+}
+
+/// Result of executing a consolidation plan
+#[derive(Debug)]
+pub struct ConsolidationReport {
+    /// The plan that was executed
+    pub plan: ConsolidationPlan,
+    /// One outcome per source wallet, in plan order
+    pub sweeps: Vec<SweepOutcome>,
+}
+
+impl ConsolidationReport {
+    /// True when every source wallet was swept
+    pub fn is_complete(&self) -> bool {
+        self.sweeps.iter().all(|s| s.result.is_ok())
+    }
+
+    /// Number of failed sweeps
+    pub fn failure_count(&self) -> usize {
+        self.sweeps.iter().filter(|s| s.result.is_err()).count()
+    }
+}
+
+impl KnishIOClient {
+    /// Work out how this bundle's `token` wallets would be consolidated, without proposing anything
+    ///
+    /// Funded wallets are grouped by batch ID; every group with more than one wallet gets
+    /// a fresh target wallet. Wallets with a fractional balance go to `skipped`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MissingSecret` without a secret, `InvalidAmount` for a wallet whose balance
+    /// is not a decimal, or the wallet query's error
+    pub async fn plan_consolidation(&self, token: &str) -> Result<ConsolidationPlan> {
+        let secret = self.secret.clone().ok_or(KnishIOError::MissingSecret)?;
+        let bundle = self.bundle.clone();
+
+        let mut wallets = Vec::new();
+        let mut skipped = Vec::new();
+        for wallet in self.query_wallets(bundle.as_deref(), Some(token)).await? {
+            let balance = wallet.try_balance_info()?;
+            if balance.amount <= 0 || wallet.position.is_none() || wallet.address.is_none() {
+                continue;
+            }
+            match balance.whole_exact() {
+                Some(whole) => wallets.push((whole, wallet)),
+                None => skipped.push(wallet),
+            }
+        }
+        wallets.sort_by_key(|(balance, _)| std::cmp::Reverse(*balance));
+
+        let mut groups: Vec<ConsolidationGroup> = Vec::new();
+        for (_, wallet) in wallets {
+            let source = self.signing_wallet(&wallet, token)?;
+            match groups.iter_mut().find(|g| g.batch_id == source.batch_id) {
+                Some(group) => group.sources.push(source),
+                None => {
+                    let mut target = Wallet::create(Some(&secret), None, token, None, source.characters.as_deref())?;
+                    target.batch_id = source.batch_id.clone();
+                    groups.push(ConsolidationGroup { batch_id: source.batch_id.clone(), target, sources: vec![source] });
+                }
+            }
+        }
+        groups.retain(|g| g.sources.len() > 1);
+
+        Ok(ConsolidationPlan { token: token.to_string(), groups, skipped })
+    }
+
+    /// Sweep this bundle's `token` wallets into one fresh wallet per batch ID
+    ///
+    /// The plan is logged before anything is proposed. Sweeps run one after another; a
+    /// failed sweep is recorded in the report and the remaining ones still run.
+    ///
+    /// # Returns
+    ///
+    /// The executed plan with one outcome per source wallet
+    pub async fn consolidate_wallets(&mut self, token: &str) -> Result<ConsolidationReport> {
+        let plan = self.plan_consolidation(token).await?;
+        self.log("info", &format!("KnishIOClient::consolidate_wallets() - Plan: {}", plan));
+        self.execute_consolidation(plan).await
+    }
+
+    /// Execute a plan from `plan_consolidation`
+    pub async fn execute_consolidation(&mut self, plan: ConsolidationPlan) -> Result<ConsolidationReport> {
+        if !plan.is_empty() {
+            self.ensure_authentication(None).await?;
+            self.require_auth_scope("consolidate_wallets", AuthScope::Profile)?;
+        }
+
+        let mut sweeps = Vec::with_capacity(plan.molecule_count());
+        for group in &plan.groups {
+            for source in &group.sources {
+                let amount = source.balance_info();
+                let result = self.sweep_wallet(source.clone(), group.target.clone()).await;
+                if let Err(ref e) = result {
+                    self.log("warn", &format!("KnishIOClient::consolidate_wallets() - Sweep of {} failed: {}",
+                        source.address.as_deref().unwrap_or_default(), e));
+                }
+                sweeps.push(SweepOutcome {
+                    source: source.address.clone().unwrap_or_default(),
+                    amount,
+                    result,
+                });
+            }
+        }
+
+        let report = ConsolidationReport { plan, sweeps };
+        self.log("info", &format!(
+            "KnishIOClient::consolidate_wallets() - {} of {} sweeps succeeded",
+            report.sweeps.len() - report.failure_count(),
+            report.sweeps.len()
+        ));
+
+        Ok(report)
+    }
+
+    /// Move the whole balance (and every unit) of `source` into `target`
+    async fn sweep_wallet(&mut self, mut source: Wallet, mut target: Wallet) -> Result<Option<String>> {
+        let secret = self.secret.clone().ok_or(KnishIOError::MissingSecret)?;
+        let mut remainder_wallet = source.create_remainder(&secret)?;
+
+        let units: Vec<String> = source.token_units.iter().map(|u| u.id.clone()).collect();
+        source.split_units(&units, &mut remainder_wallet, Some(&mut target));
+
+        let amount = source.whole_balance()? as f64;
+        let mut molecule = self.new_molecule();
+        molecule.secret = Some(secret);
+        molecule.bundle = if self.anonymous { None } else { self.bundle.clone() };
+        molecule.source_wallet = Some(source);
+        molecule.remainder_wallet = Some(remainder_wallet);
+
+        let mut mutation = MutationTransferTokens::from_molecule(molecule).anonymous(self.anonymous);
+        mutation.fill_molecule(TransferTokensParams { recipient_wallet: target, amount })?;
+
+        let client = self.client.as_ref().ok_or(KnishIOError::NoClient)?;
+        let response = mutation.execute(client, None, None).await?;
+        if !response.success() {
+            return Err(KnishIOError::custom(format!(
+                "Sweep rejected: {}",
+                response.reason().unwrap_or_else(|| "unknown reason".to_string())
+            )));
+        }
+
+        Ok(response.get("molecularHash").and_then(|h| h.as_str()).map(str::to_string))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::{generate_bundle_hash, generate_secret};
+    use crate::test_ledger::TestLedger;
+
+    #[tokio::test]
+    async fn test_fragmented_wallets_are_swept_into_one() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("consolidate-owner");
+        let bundle = generate_bundle_hash(&secret);
+        for amount in [5.0, 30.0, 12.0] {
+            ledger.fund(&secret, "FRAG", amount).unwrap();
+        }
+
+        let mut client = ledger.client(&secret);
+        let plan = client.plan_consolidation("FRAG").await.unwrap();
+        assert_eq!(plan.molecule_count(), 3);
+        assert_eq!(plan.groups[0].total().unwrap().to_string(), "47");
+        assert_eq!(plan.groups[0].sources[0].whole_balance().unwrap(), 30);
+        assert!(plan.to_string().starts_with("FRAG: 3 molecules"));
+
+        // Planning proposes nothing
+        assert!(ledger.molecules().is_empty());
+
+        let report = client.execute_consolidation(plan).await.unwrap();
+        assert!(report.is_complete(), "{:?}", report.sweeps);
+        assert_eq!(ledger.balance(&bundle, "FRAG"), 47.0);
+
+        let wallets = client.query_wallets(Some(&bundle), Some("FRAG")).await.unwrap();
+        assert_eq!(wallets.len(), 1);
+        assert_eq!(wallets[0].address, report.plan.groups[0].target.address);
+
+        assert!(client.plan_consolidation("FRAG").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fractional_wallets_are_skipped() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("consolidate-fractional");
+        for amount in [5.0, 2.5, 12.0] {
+            ledger.fund(&secret, "DEC", amount).unwrap();
+        }
+
+        let client = ledger.client(&secret);
+        let plan = client.plan_consolidation("DEC").await.unwrap();
+        assert_eq!(plan.molecule_count(), 2);
+        assert_eq!(plan.skipped.len(), 1);
+        assert_eq!(plan.skipped[0].balance, "2.5");
+        assert!(plan.to_string().ends_with("skipped 1 wallets with fractional balances"), "{}", plan);
+    }
+}
@@ -0,0 +1,426 @@
+//! Two-phase transfers across cells
+//!
+//! A molecule belongs to exactly one cell, so moving value between two cells takes one
+//! molecule in each. `CrossCellTransaction` pairs them as a swap between two signers:
+//! the first leg pays the second signer in the first cell, the second leg is paid by the
+//! second signer in the second cell.
+//!
+//! `prepare` builds and signs both molecules before anything is proposed, so balance and
+//! validation problems surface while nothing has moved yet. `commit` proposes the first
+//! leg and then the second. Should the second leg be rejected, the second signer hands the
+//! first leg's amount back in the first cell (claiming the shadow wallet it arrived in
+//! first), and the report says whether that compensation went through.
+//!
+//! Only a rejection the node actually sent triggers compensation. When a proposal ends in
+//! a transport error or timeout the molecule may still have landed, so the leg is looked
+//! up with `query_molecule`; unless the node holds its atoms, nothing is reversed and the
+//! transaction is reported `Unknown`.
+
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::molecule::Molecule;
+use crate::types::Isotope;
+
+/// One transfer of a cross-cell transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrossCellLeg {
+    /// Cell the transfer's molecule is proposed in
+    pub cell_slug: String,
+    /// Fungible token moved
+    pub token: String,
+    /// Amount moved
+    pub amount: f64,
+    /// Receiving bundle
+    pub recipient: String,
+}
+
+impl CrossCellLeg {
+    /// Transfer of `amount` `token` to `recipient` inside cell `cell_slug`
+    pub fn new(cell_slug: impl Into<String>, token: impl Into<String>, amount: f64, recipient: impl Into<String>) -> Self {
+        CrossCellLeg {
+            cell_slug: cell_slug.into(),
+            token: token.into(),
+            amount,
+            recipient: recipient.into(),
+        }
+    }
+}
+
+/// How a cross-cell transaction ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrossCellStatus {
+    /// Both legs were accepted
+    Committed,
+    /// The first leg was rejected; nothing moved
+    Aborted,
+    /// The second leg was rejected and the first leg was paid back
+    Compensated,
+    /// The second leg was rejected and paying the first leg back failed too; the second
+    /// signer holds the first leg's amount
+    CompensationFailed,
+    /// A proposal failed without a verdict and the node could not say whether the leg
+    /// landed; nothing was reversed
+    Unknown,
+}
+
+/// Consolidated outcome of `PreparedCrossCell::commit`
+#[derive(Debug, Clone)]
+pub struct CrossCellReport {
+    /// How the transaction ended
+    pub status: CrossCellStatus,
+    /// Molecular hash of the first leg, once accepted
+    pub first_leg: Option<String>,
+    /// Molecular hash of the second leg, once accepted
+    pub second_leg: Option<String>,
+    /// Molecular hash of the reversal of the first leg, once accepted
+    pub compensation: Option<String>,
+    /// Rejection reasons and errors, in the order they occurred
+    pub errors: Vec<String>,
+}
+
+impl CrossCellReport {
+    /// True when both legs went through
+    pub fn is_committed(&self) -> bool {
+        self.status == CrossCellStatus::Committed
+    }
+
+    /// True when value may be stuck with the second signer and needs manual reconciliation
+    pub fn needs_attention(&self) -> bool {
+        matches!(self.status, CrossCellStatus::CompensationFailed | CrossCellStatus::Unknown)
+    }
+}
+
+/// A value movement spanning two cells, as a pair of transfers
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrossCellTransaction {
+    /// Paid by the first signer to the second signer
+    pub first: CrossCellLeg,
+    /// Paid by the second signer
+    pub second: CrossCellLeg,
+}
+
+/// A cross-cell transaction whose two molecules are signed and ready to propose
+pub struct PreparedCrossCell {
+    transaction: CrossCellTransaction,
+    first: Molecule,
+    second: Molecule,
+    /// Bundle the first leg is paid back to
+    first_signer: String,
+}
+
+impl CrossCellTransaction {
+    /// Pair `first` and `second` into one transaction
+    pub fn new(first: CrossCellLeg, second: CrossCellLeg) -> Self {
+        CrossCellTransaction { first, second }
+    }
+
+    /// Build and sign both legs without proposing either
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigurationError` when the legs share a cell or the first leg does not
+    /// pay the second signer (it could not be reversed), `MissingBundle` without signer
+    /// bundles, or the error of building either transfer
+    pub async fn prepare(self, first_signer: &mut KnishIOClient, second_signer: &mut KnishIOClient) -> Result<PreparedCrossCell> {
+        if self.first.cell_slug == self.second.cell_slug {
+            return Err(KnishIOError::ConfigurationError(format!(
+                "Both legs are in cell {}; use a single transfer",
+                self.first.cell_slug
+            )));
+        }
+        let first_bundle = first_signer.bundle.clone().ok_or(KnishIOError::MissingBundle)?;
+        if second_signer.bundle.as_deref() != Some(self.first.recipient.as_str()) {
+            return Err(KnishIOError::ConfigurationError(
+                "The first leg must pay the second signer so that it can be reversed".to_string(),
+            ));
+        }
+
+        let first = prepare_leg(first_signer, &self.first).await?;
+        let second = prepare_leg(second_signer, &self.second).await?;
+
+        Ok(PreparedCrossCell { transaction: self, first, second, first_signer: first_bundle })
+    }
+
+    /// Prepare and commit in one go
+    ///
+    /// ```no_run
+    /// # async fn demo(alice: &mut knishio_client::KnishIOClient, bridge: &mut knishio_client::KnishIOClient, bob: String, bridge_bundle: String) -> knishio_client::Result<()> {
+    /// use knishio_client::client::{CrossCellLeg, CrossCellTransaction};
+    ///
+    /// let transaction = CrossCellTransaction::new(
+    ///     CrossCellLeg::new("cell-eu", "EUR", 100.0, bridge_bundle),
+    ///     CrossCellLeg::new("cell-us", "USD", 108.0, bob),
+    /// );
+    /// let report = transaction.execute(alice, bridge).await?;
+    /// assert!(report.is_committed() || !report.needs_attention());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn execute(self, first_signer: &mut KnishIOClient, second_signer: &mut KnishIOClient) -> Result<CrossCellReport> {
+        let prepared = self.prepare(first_signer, second_signer).await?;
+        Ok(prepared.commit(first_signer, second_signer).await)
+    }
+}
+
+impl PreparedCrossCell {
+    /// The transaction being committed
+    pub fn transaction(&self) -> &CrossCellTransaction {
+        &self.transaction
+    }
+
+    /// Propose the first leg, then the second, reversing the first if the second is rejected
+    ///
+    /// Pass the same signers as to `prepare`.
+    pub async fn commit(self, first_signer: &mut KnishIOClient, second_signer: &mut KnishIOClient) -> CrossCellReport {
+        let mut report = CrossCellReport {
+            status: CrossCellStatus::Aborted,
+            first_leg: None,
+            second_leg: None,
+            compensation: None,
+            errors: Vec::new(),
+        };
+        let first_batch = recipient_batch_id(&self.first, &self.transaction.first.recipient);
+
+        match propose(first_signer, self.first).await {
+            LegOutcome::Accepted(hash) => report.first_leg = hash,
+            LegOutcome::Rejected(reason) => {
+                report.errors.push(format!("First leg: {}", reason));
+                return report;
+            }
+            LegOutcome::Unknown(reason) => {
+                report.errors.push(format!("First leg: {}", reason));
+                report.status = CrossCellStatus::Unknown;
+                return report;
+            }
+        }
+
+        match propose(second_signer, self.second).await {
+            LegOutcome::Accepted(hash) => {
+                report.second_leg = hash;
+                report.status = CrossCellStatus::Committed;
+                return report;
+            }
+            LegOutcome::Rejected(reason) => report.errors.push(format!("Second leg: {}", reason)),
+            LegOutcome::Unknown(reason) => {
+                report.errors.push(format!("Second leg: {}", reason));
+                report.status = CrossCellStatus::Unknown;
+                second_signer.log("warn", &format!(
+                    "KnishIOClient::cross_cell() - second leg in cell {} has no verdict, first leg left in place",
+                    self.transaction.second.cell_slug
+                ));
+                return report;
+            }
+        }
+
+        let leg = &self.transaction.first;
+        match compensate(second_signer, leg, first_batch.as_deref(), &self.first_signer).await {
+            Ok(hash) => {
+                report.compensation = hash;
+                report.status = CrossCellStatus::Compensated;
+                second_signer.log("warn", &format!(
+                    "KnishIOClient::cross_cell() - second leg failed, {} {} returned in cell {}",
+                    leg.amount, leg.token, leg.cell_slug
+                ));
+            }
+            Err(e) => {
+                report.errors.push(format!("Compensation: {}", e));
+                report.status = CrossCellStatus::CompensationFailed;
+                second_signer.log("warn", &format!(
+                    "KnishIOClient::cross_cell() - {} {} could not be returned in cell {}: {}",
+                    leg.amount, leg.token, leg.cell_slug, e
+                ));
+            }
+        }
+
+        report
+    }
+}
+
+/// Sign `leg` with `signer` switched to the leg's cell
+async fn prepare_leg(signer: &mut KnishIOClient, leg: &CrossCellLeg) -> Result<Molecule> {
+    let previous = signer.cell_slug.replace(leg.cell_slug.clone());
+    let mutation = signer.prepare_transfer_token(&leg.recipient, &leg.token, Some(leg.amount), Vec::new(), None, None).await;
+    signer.cell_slug = previous;
+
+    Ok(mutation?.molecule().clone())
+}
+
+/// What became of a proposed leg
+enum LegOutcome {
+    /// Accepted, with its molecular hash
+    Accepted(Option<String>),
+    /// Rejected by the node, with its reason
+    Rejected(String),
+    /// No verdict could be established; the molecule may have landed
+    Unknown(String),
+}
+
+/// Propose a signed molecule, looking it up when the proposal fails without a verdict
+async fn propose(signer: &mut KnishIOClient, molecule: Molecule) -> LegOutcome {
+    let hash = molecule.molecular_hash.clone();
+    let error = match signer.propose_molecule(molecule).await {
+        Ok(response) if response.success() => return LegOutcome::Accepted(hash),
+        Ok(response) => {
+            return LegOutcome::Rejected(format!(
+                "rejected: {}",
+                response.reason().unwrap_or_else(|| "unknown reason".to_string())
+            ));
+        }
+        Err(e) => e,
+    };
+
+    let Some(molecular_hash) = hash.as_deref() else {
+        return LegOutcome::Unknown(error.to_string());
+    };
+    match signer.query_molecule(molecular_hash).await {
+        Ok(Some(_)) => LegOutcome::Accepted(hash),
+        Ok(None) => LegOutcome::Unknown(format!("{}; the node holds no atoms of the molecule", error)),
+        Err(lookup) => LegOutcome::Unknown(format!("{}; lookup failed: {}", error, lookup)),
+    }
+}
+
+/// Batch ID of the wallet `molecule` credits to `recipient`
+fn recipient_batch_id(molecule: &Molecule, recipient: &str) -> Option<String> {
+    molecule.atoms.iter()
+        .find(|atom| atom.isotope == Isotope::V && atom.meta_id.as_deref() == Some(recipient))
+        .and_then(|atom| atom.batch_id.clone())
+}
+
+/// Pay `leg` back from its recipient (`signer`) to `first_signer`, inside the leg's cell
+async fn compensate(signer: &mut KnishIOClient, leg: &CrossCellLeg, batch_id: Option<&str>, first_signer: &str) -> Result<Option<String>> {
+    let previous = signer.cell_slug.replace(leg.cell_slug.clone());
+    let result = reverse_leg(signer, leg, batch_id, first_signer).await;
+    signer.cell_slug = previous;
+    result
+}
+
+async fn reverse_leg(signer: &mut KnishIOClient, leg: &CrossCellLeg, batch_id: Option<&str>, first_signer: &str) -> Result<Option<String>> {
+    // The first leg landed in a shadow wallet unless the signer already had one for the
+    // token; a failed claim is not fatal as long as the transfer back finds the balance
+    if let Err(e) = signer.claim_shadow_wallet(&leg.token, batch_id, None).await {
+        signer.log("info", &format!("KnishIOClient::cross_cell() - no shadow wallet claimed: {}", e));
+    }
+
+    let response = signer.transfer_token(first_signer, &leg.token, Some(leg.amount), Vec::new(), None, None).await?;
+    if !response.success() {
+        return Err(KnishIOError::custom(format!(
+            "rejected: {}",
+            response.reason().unwrap_or_else(|| "unknown reason".to_string())
+        )));
+    }
+
+    Ok(response.get("molecularHash").and_then(|h| h.as_str()).map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{generate_bundle_hash, generate_secret};
+    use crate::test_ledger::TestLedger;
+
+    #[tokio::test]
+    async fn test_cross_cell_commit() {
+        let ledger = TestLedger::start().await.unwrap();
+        let alice_secret = generate_secret("cross-cell-alice");
+        let bridge_secret = generate_secret("cross-cell-bridge");
+        let alice = generate_bundle_hash(&alice_secret);
+        let bridge = generate_bundle_hash(&bridge_secret);
+        let bob = generate_bundle_hash(&generate_secret("cross-cell-bob"));
+        ledger.fund(&alice_secret, "EUR", 100.0).unwrap();
+        ledger.fund(&bridge_secret, "USD", 500.0).unwrap();
+        let mut alice_client = ledger.client(&alice_secret);
+        let mut bridge_client = ledger.client(&bridge_secret);
+
+        let same_cell = CrossCellTransaction::new(
+            CrossCellLeg::new("cell-a", "EUR", 10.0, bridge.as_str()),
+            CrossCellLeg::new("cell-a", "USD", 11.0, bob.as_str()),
+        );
+        assert!(same_cell.execute(&mut alice_client, &mut bridge_client).await.is_err());
+        let irreversible = CrossCellTransaction::new(
+            CrossCellLeg::new("cell-a", "EUR", 10.0, bob.as_str()),
+            CrossCellLeg::new("cell-b", "USD", 11.0, bob.as_str()),
+        );
+        assert!(irreversible.execute(&mut alice_client, &mut bridge_client).await.is_err());
+
+        let transaction = CrossCellTransaction::new(
+            CrossCellLeg::new("cell-a", "EUR", 40.0, bridge.as_str()),
+            CrossCellLeg::new("cell-b", "USD", 44.0, bob.as_str()),
+        );
+        let report = transaction.execute(&mut alice_client, &mut bridge_client).await.unwrap();
+        assert!(report.is_committed(), "{:?}", report.errors);
+        assert!(report.first_leg.is_some() && report.second_leg.is_some());
+
+        assert_eq!(ledger.balance(&alice, "EUR"), 60.0);
+        assert_eq!(ledger.balance(&bridge, "EUR"), 40.0);
+        assert_eq!(ledger.balance(&bob, "USD"), 44.0);
+        let cells: Vec<_> = ledger.molecules().iter().map(|m| m.cell_slug.clone()).collect();
+        assert!(cells.ends_with(&[Some("cell-a".to_string()), Some("cell-b".to_string())]));
+        assert_eq!(alice_client.get_cell_slug(), None);
+    }
+
+    #[tokio::test]
+    async fn test_cross_cell_compensation() {
+        let ledger = TestLedger::start().await.unwrap();
+        let alice_secret = generate_secret("cross-cell-comp-alice");
+        let bridge_secret = generate_secret("cross-cell-comp-bridge");
+        let alice = generate_bundle_hash(&alice_secret);
+        let bridge = generate_bundle_hash(&bridge_secret);
+        let bob = generate_bundle_hash(&generate_secret("cross-cell-comp-bob"));
+        let carol = generate_bundle_hash(&generate_secret("cross-cell-comp-carol"));
+        ledger.fund(&alice_secret, "EUR", 100.0).unwrap();
+        ledger.fund(&bridge_secret, "USD", 50.0).unwrap();
+        let mut alice_client = ledger.client(&alice_secret);
+        let mut bridge_client = ledger.client(&bridge_secret);
+
+        let prepared = CrossCellTransaction::new(
+            CrossCellLeg::new("cell-a", "EUR", 40.0, bridge.as_str()),
+            CrossCellLeg::new("cell-b", "USD", 44.0, bob.as_str()),
+        ).prepare(&mut alice_client, &mut bridge_client).await.unwrap();
+
+        // The bridge spends its USD before the commit: the prepared second leg is stale
+        let response = bridge_client.transfer_token(&carol, "USD", Some(50.0), Vec::new(), None, None).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+
+        let report = prepared.commit(&mut alice_client, &mut bridge_client).await;
+        assert_eq!(report.status, CrossCellStatus::Compensated, "{:?}", report.errors);
+        assert!(report.second_leg.is_none());
+        assert!(report.errors[0].starts_with("Second leg"));
+
+        assert_eq!(ledger.balance(&alice, "EUR"), 100.0);
+        assert_eq!(ledger.balance(&bridge, "EUR"), 0.0);
+        assert_eq!(ledger.balance(&bob, "USD"), 0.0);
+    }
+
+    #[cfg(feature = "fault-injection")]
+    #[tokio::test]
+    async fn test_cross_cell_unknown_second_leg() {
+        use crate::graphql::FaultInjector;
+
+        let ledger = TestLedger::start().await.unwrap();
+        let alice_secret = generate_secret("cross-cell-unknown-alice");
+        let bridge_secret = generate_secret("cross-cell-unknown-bridge");
+        let alice = generate_bundle_hash(&alice_secret);
+        let bridge = generate_bundle_hash(&bridge_secret);
+        let bob = generate_bundle_hash(&generate_secret("cross-cell-unknown-bob"));
+        ledger.fund(&alice_secret, "EUR", 100.0).unwrap();
+        ledger.fund(&bridge_secret, "USD", 50.0).unwrap();
+        let mut alice_client = ledger.client(&alice_secret);
+        let mut bridge_client = ledger.client(&bridge_secret);
+
+        let prepared = CrossCellTransaction::new(
+            CrossCellLeg::new("cell-a", "EUR", 40.0, bridge.as_str()),
+            CrossCellLeg::new("cell-b", "USD", 44.0, bob.as_str()),
+        ).prepare(&mut alice_client, &mut bridge_client).await.unwrap();
+
+        // The second leg lands but its reply, and the lookup after it, are lost
+        bridge_client.set_fault_injector(Some(FaultInjector::new().malformed_responses(1.0)));
+        let report = prepared.commit(&mut alice_client, &mut bridge_client).await;
+        assert_eq!(report.status, CrossCellStatus::Unknown, "{:?}", report.errors);
+        assert!(report.needs_attention() && report.compensation.is_none());
+
+        // Nothing was reversed while the second leg settled
+        assert_eq!(ledger.balance(&alice, "EUR"), 60.0);
+        assert_eq!(ledger.balance(&bridge, "EUR"), 40.0);
+        assert_eq!(ledger.balance(&bob, "USD"), 44.0);
+    }
+}
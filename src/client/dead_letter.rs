@@ -0,0 +1,358 @@
+//! Dead letter queue for failed submissions
+//!
+//! `submit_molecule` proposes a signed molecule under the client's submit policy,
+//! retrying transient failures with backoff. A molecule that still has not landed when
+//! the policy gives up, or that the node rejects outright, is parked in the client's
+//! dead letter queue together with the error that sank it instead of being dropped.
+//!
+//! Dead letters can be listed and inspected, exported to and imported from JSON so they
+//! survive a restart, and resubmitted explicitly with `resubmit_dead_letter`. By the time
+//! a dead letter is resubmitted its ContinuID has usually moved on, so resubmission
+//! rebuilds the molecule's ContinuID atom against the current head and re-signs it.
+
+use crate::atom::Atom;
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::molecule::Molecule;
+use crate::response::Response;
+use crate::types::Isotope;
+use crate::wallet::Wallet;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// Why a molecule ended up in the dead letter queue
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DeadLetterCause {
+    /// Every attempt allowed by the submit policy failed with a retryable error
+    RetriesExhausted,
+    /// The node answered and rejected the molecule
+    Rejected {
+        /// Rejection reason reported by the node
+        reason: String,
+    },
+    /// The submission failed with an error the submit policy does not retry
+    Failed,
+}
+
+impl fmt::Display for DeadLetterCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeadLetterCause::RetriesExhausted => f.write_str("retries exhausted"),
+            DeadLetterCause::Rejected { reason } => write!(f, "rejected: {}", reason),
+            DeadLetterCause::Failed => f.write_str("failed"),
+        }
+    }
+}
+
+/// A molecule that could not be submitted, with the context of its last failure
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetter {
+    /// Queue-local identifier
+    pub id: String,
+    /// The molecule as it was last proposed (its secret is never stored)
+    pub molecule: Molecule,
+    /// Why it was dead-lettered
+    pub cause: DeadLetterCause,
+    /// Message of the last error or rejection
+    pub error: String,
+    /// Number of proposals made before giving up
+    pub attempts: u32,
+    /// When the last attempt failed
+    pub failed_at: DateTime<Utc>,
+}
+
+impl DeadLetter {
+    /// Molecular hash of the failed molecule
+    pub fn molecular_hash(&self) -> Option<&str> {
+        self.molecule.molecular_hash.as_deref()
+    }
+}
+
+impl fmt::Display for DeadLetter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} after {} attempts ({}): {}",
+            self.id,
+            self.molecular_hash().unwrap_or("-"),
+            self.attempts,
+            self.cause,
+            self.error
+        )
+    }
+}
+
+/// Dead letters in the order they failed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeadLetterQueue {
+    letters: Vec<DeadLetter>,
+}
+
+impl DeadLetterQueue {
+    /// An empty queue
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Park a molecule and return the new dead letter's ID
+    pub fn push(&mut self, mut molecule: Molecule, cause: DeadLetterCause, error: impl Into<String>, attempts: u32) -> String {
+        molecule.secret = None;
+        let id = uuid::Uuid::new_v4().to_string();
+        self.letters.push(DeadLetter {
+            id: id.clone(),
+            molecule,
+            cause,
+            error: error.into(),
+            attempts,
+            failed_at: Utc::now(),
+        });
+        id
+    }
+
+    /// Every dead letter, oldest first
+    pub fn list(&self) -> &[DeadLetter] {
+        &self.letters
+    }
+
+    /// Dead letter with the given ID
+    pub fn get(&self, id: &str) -> Option<&DeadLetter> {
+        self.letters.iter().find(|letter| letter.id == id)
+    }
+
+    /// Remove and return the dead letter with the given ID
+    pub fn take(&mut self, id: &str) -> Option<DeadLetter> {
+        let index = self.letters.iter().position(|letter| letter.id == id)?;
+        Some(self.letters.remove(index))
+    }
+
+    /// Number of dead letters
+    pub fn len(&self) -> usize {
+        self.letters.len()
+    }
+
+    /// True when nothing has failed
+    pub fn is_empty(&self) -> bool {
+        self.letters.is_empty()
+    }
+
+    /// Serialize the queue for persistent storage
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Restore a queue serialized with `to_json`
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+impl KnishIOClient {
+    /// Propose a signed molecule, retrying transient failures under the submit policy
+    ///
+    /// Errors the policy considers retryable (network failures, timeouts, 5xx and rate
+    /// limiting by default) are retried with the policy's backoff. When the node rejects
+    /// the molecule, a non-retryable error occurs or the attempts run out, the molecule
+    /// is moved to the dead letter queue.
+    ///
+    /// # Returns
+    ///
+    /// The node's response; a rejected molecule's response is returned as well
+    ///
+    /// # Errors
+    ///
+    /// Returns the last transport error when the molecule never got an answer
+    pub async fn submit_molecule(&mut self, molecule: Molecule) -> Result<Box<dyn Response>> {
+        let policy = self.submit_policy.clone();
+        let max_attempts = policy.max_attempts.max(1);
+
+        let mut attempt = 0;
+        let mut delay = Duration::ZERO;
+        loop {
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+
+            let error = match self.propose_molecule(molecule.clone()).await {
+                Ok(response) if response.success() => return Ok(response),
+                Ok(response) => {
+                    let reason = response.reason().unwrap_or_else(|| "unknown reason".to_string());
+                    self.dead_letter(molecule, DeadLetterCause::Rejected { reason: reason.clone() }, &reason, attempt);
+                    return Ok(response);
+                }
+                Err(e) => e,
+            };
+
+            if !policy.should_retry(&error) {
+                self.dead_letter(molecule, DeadLetterCause::Failed, &error.to_string(), attempt);
+                return Err(error);
+            }
+            if attempt >= max_attempts {
+                self.dead_letter(molecule, DeadLetterCause::RetriesExhausted, &error.to_string(), attempt);
+                return Err(error);
+            }
+            self.log("warn", &format!("KnishIOClient::submit_molecule() - Attempt {} of {} failed, retrying: {}", attempt, max_attempts, error));
+            delay = policy.delay_for(attempt, &error);
+        }
+    }
+
+    /// Molecules that could not be submitted
+    pub fn dead_letters(&self) -> &DeadLetterQueue {
+        &self.dead_letters
+    }
+
+    /// Serialize the dead letter queue for persistent storage
+    pub fn export_dead_letters(&self) -> Result<String> {
+        self.dead_letters.to_json()
+    }
+
+    /// Add dead letters exported by `export_dead_letters`, skipping IDs already queued
+    ///
+    /// # Returns
+    ///
+    /// Number of dead letters added
+    pub fn import_dead_letters(&mut self, json: &str) -> Result<usize> {
+        let imported = DeadLetterQueue::from_json(json)?;
+        let before = self.dead_letters.len();
+        for letter in imported.letters {
+            if self.dead_letters.get(&letter.id).is_none() {
+                self.dead_letters.letters.push(letter);
+            }
+        }
+        Ok(self.dead_letters.len() - before)
+    }
+
+    /// Remove a dead letter without resubmitting it
+    pub fn discard_dead_letter(&mut self, id: &str) -> Option<DeadLetter> {
+        self.dead_letters.take(id)
+    }
+
+    /// Re-sign a dead letter against the current ContinuID and submit it again
+    ///
+    /// The molecule's ContinuID atom is rebuilt with a fresh USER remainder wallet. For
+    /// USER-signed molecules the atoms of the stale source wallet are moved to the
+    /// bundle's current ContinuID wallet; other molecules keep their source wallet and
+    /// only pick up the current ContinuID position. The dead letter leaves the queue
+    /// while it is in flight; if the new submission fails it is queued again under a
+    /// new ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ID is unknown, the original molecule has landed in the
+    /// meantime, re-signing fails, or the new submission fails
+    pub async fn resubmit_dead_letter(&mut self, id: &str) -> Result<Box<dyn Response>> {
+        let letter = self.dead_letters.get(id).cloned()
+            .ok_or_else(|| KnishIOError::custom(format!("No dead letter with ID {}", id)))?;
+
+        if let Some(hash) = letter.molecular_hash() {
+            let atoms = self.query_atom(Some(hash), None, None, None, None, None, None, None, None).await?;
+            if !atoms.is_empty() {
+                return Err(KnishIOError::custom(format!(
+                    "Dead letter {} has already landed as molecule {}; discard it instead", id, hash
+                )));
+            }
+        }
+
+        let mut molecule = letter.molecule;
+        self.refresh_continuid(&mut molecule).await?;
+        molecule.sign(None, false, true)?;
+        molecule.check(None)?;
+
+        self.log("info", &format!(
+            "KnishIOClient::resubmit_dead_letter() - Resubmitting dead letter {} as molecule {}",
+            id,
+            molecule.molecular_hash.as_deref().unwrap_or_default()
+        ));
+        self.dead_letters.take(id);
+        self.submit_molecule(molecule).await
+    }
+
+    /// Park a molecule in the dead letter queue
+    fn dead_letter(&mut self, molecule: Molecule, cause: DeadLetterCause, error: &str, attempts: u32) -> String {
+        let hash = molecule.molecular_hash.clone().unwrap_or_default();
+        let id = self.dead_letters.push(molecule, cause.clone(), error, attempts);
+        self.log("warn", &format!("KnishIOClient::submit_molecule() - Molecule {} dead-lettered as {} ({})", hash, id, cause));
+        id
+    }
+
+    /// Point an unsigned copy of `molecule` at the bundle's current ContinuID
+    async fn refresh_continuid(&mut self, molecule: &mut Molecule) -> Result<()> {
+        let secret = self.secret.clone().ok_or(KnishIOError::MissingSecret)?;
+        let source = molecule.source_wallet.clone()
+            .ok_or_else(|| KnishIOError::custom("Dead letter has no source wallet"))?;
+        molecule.secret = Some(secret.clone());
+
+        if source.token == molecule.system_tokens.user {
+            let head = self.get_source_wallet().await?;
+            if head.position != source.position {
+                for atom in molecule.atoms.iter_mut().filter(|atom| Some(&atom.position) == source.position.as_ref()) {
+                    atom.position = head.position.clone().unwrap_or_default();
+                    atom.wallet_address = head.address.clone().unwrap_or_default();
+                }
+            }
+            molecule.source_wallet = Some(head);
+            molecule.continuid_position = None;
+        } else {
+            let bundle = molecule.bundle.clone().or_else(|| self.bundle.clone());
+            molecule.continuid_position = self.query_continu_id(bundle.as_deref()).await?
+                .and_then(|wallet| wallet.position);
+        }
+
+        molecule.atoms.retain(|atom| !is_continuid_atom(atom));
+        molecule.reindex();
+
+        if molecule.remainder_wallet.as_ref().is_none_or(|w| w.token == molecule.system_tokens.user) {
+            let remainder = Wallet::create(Some(&secret), molecule.bundle.as_deref(), &molecule.system_tokens.user, None, source.characters.as_deref())?;
+            self.remainder_wallet = Some(remainder.clone());
+            molecule.remainder_wallet = Some(remainder);
+        }
+        molecule.add_continuid_atom()?;
+        molecule.molecular_hash = None;
+
+        Ok(())
+    }
+}
+
+fn is_continuid_atom(atom: &Atom) -> bool {
+    atom.isotope == Isotope::I && atom.meta_type.as_deref() == Some("walletBundle")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::RetryPolicy;
+    use crate::molecule::MoleculeParams;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_exhausted_retries_are_dead_lettered() {
+        let secret = crate::crypto::generate_secret("dead-letter-offline");
+        let mut client = KnishIOClient::new("http://127.0.0.1:9/graphql", None, None, None, None, Some(false));
+        client.set_secret(&secret);
+        client.set_submit_policy(RetryPolicy::new().with_max_attempts(2).with_initial_delay(Duration::from_millis(1)).with_jitter(0.0));
+
+        let source = Wallet::create(Some(&secret), None, "USER", None, None).unwrap();
+        let mut molecule = Molecule::from_params(MoleculeParams::new().secret(secret).source_wallet(source));
+        molecule.init_meta(vec![crate::types::MetaItem::new("k", "v")], "note", "n-1", None).unwrap();
+        molecule.sign(None, false, true).unwrap();
+
+        assert!(client.submit_molecule(molecule.clone()).await.is_err());
+
+        let letter = client.dead_letters().list()[0].clone();
+        assert_eq!(letter.cause, DeadLetterCause::RetriesExhausted);
+        assert_eq!(letter.attempts, 2);
+        assert_eq!(letter.molecular_hash(), molecule.molecular_hash.as_deref());
+        assert!(letter.molecule.secret.is_none());
+
+        let exported = client.export_dead_letters().unwrap();
+        let restored = DeadLetterQueue::from_json(&exported).unwrap();
+        assert_eq!(restored.get(&letter.id).unwrap().molecule.atoms.len(), molecule.atoms.len());
+        assert_eq!(client.import_dead_letters(&exported).unwrap(), 0);
+
+        let id = letter.id.clone();
+        assert!(client.discard_dead_letter(&id).is_some());
+        assert!(client.dead_letters().is_empty());
+    }
+}
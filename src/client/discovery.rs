@@ -0,0 +1,487 @@
+//! Node discovery
+//!
+//! Instead of hard-coding node URIs, a client can be pointed at a bootstrap URL or a DNS
+//! SRV name and fetch the current node list from it. Operators then rotate the node
+//! fleet by updating the bootstrap document or DNS zone, without redeploying clients.
+//!
+//! A bootstrap URL answers a GET with JSON, either a plain list of GraphQL URIs or a
+//! list of nodes with their cell assignments:
+//!
+//! ```json
+//! { "nodes": [
+//!     { "uri": "https://node1.knish.io/graphql", "cells": ["MAINNET"] },
+//!     { "uri": "https://node2.knish.io/graphql" }
+//! ] }
+//! ```
+//!
+//! An SRV name such as `_knishio._tcp.knish.io` is resolved against the system
+//! nameserver; each record becomes `{scheme}://{target}:{port}{path}`, ordered by
+//! priority and then weight. SRV records carry no cell assignments.
+//!
+//! `discover_nodes` resolves once and applies the result; `spawn_node_discovery`
+//! keeps a shared client up to date in the background.
+
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+
+/// DNS record type of SRV records
+const SRV_TYPE: u16 = 33;
+
+/// How long a bootstrap request or DNS query may take
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where the node list comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiscoverySource {
+    /// URL answering with a JSON node list
+    Bootstrap(String),
+    /// DNS SRV name, e.g. `_knishio._tcp.knish.io`
+    Srv(String),
+}
+
+/// Node discovery settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveryConfig {
+    /// Where the node list comes from
+    pub source: DiscoverySource,
+    /// Interval between refreshes by `spawn_node_discovery`
+    pub refresh_interval: Duration,
+    /// URI scheme of SRV targets
+    pub scheme: String,
+    /// GraphQL path appended to SRV targets
+    pub path: String,
+    /// Nameserver for SRV lookups (defaults to the first one in `/etc/resolv.conf`)
+    pub nameserver: Option<SocketAddr>,
+}
+
+impl DiscoveryConfig {
+    fn with_source(source: DiscoverySource) -> Self {
+        DiscoveryConfig {
+            source,
+            refresh_interval: Duration::from_secs(300),
+            scheme: "https".to_string(),
+            path: "/graphql".to_string(),
+            nameserver: None,
+        }
+    }
+
+    /// Discover nodes from a bootstrap URL
+    pub fn bootstrap(url: impl Into<String>) -> Self {
+        Self::with_source(DiscoverySource::Bootstrap(url.into()))
+    }
+
+    /// Discover nodes from DNS SRV records
+    pub fn srv(name: impl Into<String>) -> Self {
+        Self::with_source(DiscoverySource::Srv(name.into()))
+    }
+
+    /// Set the interval between background refreshes (default 5 minutes)
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Set the scheme of URIs built from SRV records (default `https`)
+    pub fn scheme(mut self, scheme: impl Into<String>) -> Self {
+        self.scheme = scheme.into();
+        self
+    }
+
+    /// Set the path of URIs built from SRV records (default `/graphql`)
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Query `nameserver` for SRV records instead of the system resolver
+    pub fn nameserver(mut self, nameserver: SocketAddr) -> Self {
+        self.nameserver = Some(nameserver);
+        self
+    }
+}
+
+/// Nodes found by discovery
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeDirectory {
+    /// Every discovered GraphQL URI, in preference order
+    pub uris: Vec<String>,
+    /// URIs serving each cell slug, for nodes that declared cell assignments
+    pub cells: HashMap<String, Vec<String>>,
+}
+
+impl NodeDirectory {
+    /// URIs to use for `cell_slug`: its assigned nodes, or every node if none are assigned
+    pub fn uris_for_cell(&self, cell_slug: Option<&str>) -> Vec<String> {
+        cell_slug
+            .and_then(|cell| self.cells.get(cell))
+            .filter(|uris| !uris.is_empty())
+            .unwrap_or(&self.uris)
+            .clone()
+    }
+
+    /// Parse a bootstrap document (see the module documentation for the format)
+    pub fn from_bootstrap(document: &Value) -> Result<Self> {
+        let nodes = document.get("nodes").unwrap_or(document).as_array()
+            .ok_or_else(|| KnishIOError::ConfigurationError("bootstrap document has no node list".to_string()))?;
+
+        let mut directory = NodeDirectory::default();
+        for node in nodes {
+            let uri = node.as_str()
+                .or_else(|| node.get("uri").and_then(Value::as_str))
+                .ok_or_else(|| KnishIOError::ConfigurationError(format!("bootstrap node without a uri: {}", node)))?
+                .to_string();
+
+            for cell in node.get("cells").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+                directory.cells.entry(cell.to_string()).or_default().push(uri.clone());
+            }
+            if !directory.uris.contains(&uri) {
+                directory.uris.push(uri);
+            }
+        }
+        Ok(directory)
+    }
+
+    /// Build a directory from SRV records, best record first
+    pub fn from_srv(mut records: Vec<SrvRecord>, scheme: &str, path: &str) -> Self {
+        records.retain(|record| record.target != "." && !record.target.is_empty());
+        records.sort_by(|a, b| a.priority.cmp(&b.priority).then(b.weight.cmp(&a.weight)));
+
+        let uris = records.iter()
+            .map(|record| format!("{}://{}:{}{}", scheme, record.target.trim_end_matches('.'), record.port, path))
+            .collect();
+        NodeDirectory { uris, cells: HashMap::new() }
+    }
+}
+
+/// One DNS SRV record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrvRecord {
+    /// Lower is preferred
+    pub priority: u16,
+    /// Relative weight among records of equal priority
+    pub weight: u16,
+    /// Port of the service
+    pub port: u16,
+    /// Host name of the node
+    pub target: String,
+}
+
+/// Fetch the node list described by `config`
+pub async fn discover(config: &DiscoveryConfig) -> Result<NodeDirectory> {
+    let directory = match config.source {
+        DiscoverySource::Bootstrap(ref url) => {
+            let client = reqwest::Client::builder().timeout(LOOKUP_TIMEOUT).build()?;
+            let document: Value = client.get(url).send().await?.error_for_status()?.json().await?;
+            NodeDirectory::from_bootstrap(&document)?
+        }
+        DiscoverySource::Srv(ref name) => {
+            let nameserver = match config.nameserver {
+                Some(nameserver) => nameserver,
+                None => system_nameserver()?,
+            };
+            NodeDirectory::from_srv(lookup_srv(name, nameserver).await?, &config.scheme, &config.path)
+        }
+    };
+
+    if directory.uris.is_empty() {
+        return Err(KnishIOError::ConfigurationError("node discovery returned no nodes".to_string()));
+    }
+    Ok(directory)
+}
+
+/// First nameserver listed in `/etc/resolv.conf`
+fn system_nameserver() -> Result<SocketAddr> {
+    let resolv = std::fs::read_to_string("/etc/resolv.conf")?;
+    resolv.lines()
+        .filter_map(|line| line.trim().strip_prefix("nameserver"))
+        .find_map(|address| address.trim().parse::<IpAddr>().ok())
+        .map(|ip| SocketAddr::new(ip, 53))
+        .ok_or_else(|| KnishIOError::ConfigurationError("no nameserver in /etc/resolv.conf".to_string()))
+}
+
+/// Resolve the SRV records of `name` with a single UDP query to `nameserver`
+pub async fn lookup_srv(name: &str, nameserver: SocketAddr) -> Result<Vec<SrvRecord>> {
+    let id: u16 = rand::random();
+    let query = encode_srv_query(id, name)?;
+
+    let local: SocketAddr = if nameserver.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }.parse()
+        .map_err(|_| KnishIOError::custom("invalid local address"))?;
+    let socket = UdpSocket::bind(local).await?;
+    socket.connect(nameserver).await?;
+    socket.send(&query).await?;
+
+    let mut buffer = [0u8; 4096];
+    let length = tokio::time::timeout(LOOKUP_TIMEOUT, socket.recv(&mut buffer)).await
+        .map_err(|_| KnishIOError::Network(format!("SRV lookup of {} timed out", name)))??;
+    decode_srv_response(id, &buffer[..length])
+}
+
+fn encode_srv_query(id: u16, name: &str) -> Result<Vec<u8>> {
+    let mut query = Vec::with_capacity(name.len() + 18);
+    query.extend_from_slice(&id.to_be_bytes());
+    // Recursion desired, one question
+    query.extend_from_slice(&[0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(KnishIOError::ConfigurationError(format!("invalid SRV name {}", name)));
+        }
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+    query.extend_from_slice(&SRV_TYPE.to_be_bytes());
+    query.extend_from_slice(&1u16.to_be_bytes()); // IN
+    Ok(query)
+}
+
+fn decode_srv_response(id: u16, message: &[u8]) -> Result<Vec<SrvRecord>> {
+    let malformed = || KnishIOError::Network("malformed DNS response".to_string());
+    let u16_at = |pos: usize| message.get(pos..pos + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(malformed);
+
+    if u16_at(0)? != id {
+        return Err(KnishIOError::Network("DNS response does not match the query".to_string()));
+    }
+    let flags = u16_at(2)?;
+    if flags & 0x0200 != 0 {
+        return Err(KnishIOError::Network("DNS response truncated".to_string()));
+    }
+    match flags & 0x000f {
+        0 => {}
+        3 => return Ok(Vec::new()),
+        rcode => return Err(KnishIOError::Network(format!("DNS lookup failed with rcode {}", rcode))),
+    }
+
+    let questions = u16_at(4)?;
+    let answers = u16_at(6)?;
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = read_name(message, pos)?.1 + 4;
+    }
+
+    let mut records = Vec::new();
+    for _ in 0..answers {
+        pos = read_name(message, pos)?.1;
+        let record_type = u16_at(pos)?;
+        let length = u16_at(pos + 8)? as usize;
+        let data = pos + 10;
+        if message.len() < data + length {
+            return Err(malformed());
+        }
+        if record_type == SRV_TYPE {
+            records.push(SrvRecord {
+                priority: u16_at(data)?,
+                weight: u16_at(data + 2)?,
+                port: u16_at(data + 4)?,
+                target: read_name(message, data + 6)?.0,
+            });
+        }
+        pos = data + length;
+    }
+    Ok(records)
+}
+
+/// Read a possibly compressed domain name; returns it and the offset just past it
+fn read_name(message: &[u8], start: usize) -> Result<(String, usize)> {
+    let malformed = || KnishIOError::Network("malformed DNS name".to_string());
+    let mut labels: Vec<String> = Vec::new();
+    let mut pos = start;
+    let mut end = None;
+
+    for _ in 0..128 {
+        let length = *message.get(pos).ok_or_else(malformed)? as usize;
+        match length {
+            0 => {
+                let name = if labels.is_empty() { ".".to_string() } else { labels.join(".") };
+                return Ok((name, end.unwrap_or(pos + 1)));
+            }
+            pointer if pointer & 0xc0 == 0xc0 => {
+                let low = *message.get(pos + 1).ok_or_else(malformed)? as usize;
+                end.get_or_insert(pos + 2);
+                pos = ((pointer & 0x3f) << 8) | low;
+            }
+            _ => {
+                let label = message.get(pos + 1..pos + 1 + length).ok_or_else(malformed)?;
+                labels.push(String::from_utf8_lossy(label).into_owned());
+                pos += 1 + length;
+            }
+        }
+    }
+    Err(malformed())
+}
+
+impl KnishIOClient {
+    /// Fetch the node list described by `config` and switch the client to it
+    ///
+    /// With a cell slug set, only the nodes assigned to that cell are used (all nodes if
+    /// none declare it). The current node is kept when it is still listed.
+    pub async fn discover_nodes(&mut self, config: &DiscoveryConfig) -> Result<NodeDirectory> {
+        let directory = discover(config).await?;
+        self.apply_node_directory(&directory)?;
+        Ok(directory)
+    }
+
+    /// Switch the client to the nodes of `directory`
+    pub fn apply_node_directory(&mut self, directory: &NodeDirectory) -> Result<()> {
+        let uris = directory.uris_for_cell(self.cell_slug.as_deref());
+        if uris.is_empty() {
+            return Err(KnishIOError::ConfigurationError("node directory lists no nodes".to_string()));
+        }
+
+        self.uris = uris;
+        self.current_uri_index = 0;
+        let replacement = self.get_random_uri();
+        if let Some(ref mut client) = self.client {
+            if !self.uris.iter().any(|uri| uri == client.get_uri()) {
+                client.set_uri(replacement);
+            }
+        }
+
+        // Re-pick the hedge node from the new list, or stop hedging if it is too short
+        if let Some(hedge) = self.hedged_reads() {
+            if self.set_hedged_reads(Some(hedge.delay)).is_err() {
+                self.set_hedged_reads(None)?;
+            }
+        }
+
+        self.log("info", &format!("KnishIOClient::apply_node_directory() - Using {} discovered nodes", self.uris.len()));
+        Ok(())
+    }
+
+    /// Refresh the node list of a shared client every `config.refresh_interval`
+    ///
+    /// Failed refreshes are logged and leave the current nodes in place. Abort the
+    /// returned handle to stop refreshing.
+    pub fn spawn_node_discovery(client: Arc<tokio::sync::Mutex<KnishIOClient>>, config: DiscoveryConfig) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(config.refresh_interval).await;
+                let result = discover(&config).await;
+
+                let mut client = client.lock().await;
+                if let Err(e) = result.and_then(|directory| client.apply_node_directory(&directory)) {
+                    client.log("warn", &format!("KnishIOClient::spawn_node_discovery() - Refresh failed: {}", e));
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Answer for `_knishio._tcp.test` with two SRV records, targets compressed
+    fn srv_response(id: u16) -> Vec<u8> {
+        let mut message = id.to_be_bytes().to_vec();
+        message.extend_from_slice(&[0x81, 0x80, 0x00, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00]);
+        message.extend_from_slice(&encode_srv_query(0, "_knishio._tcp.test").unwrap()[12..]);
+        // offset of "test" in the question, reused by both targets
+        let test_label = 12 + 1 + "_knishio".len() + 1 + "_tcp".len();
+
+        for (priority, weight, port, host) in [(20u16, 5u16, 8443u16, "b"), (10, 5, 443, "a")] {
+            message.extend_from_slice(&[0xc0, 0x0c]);
+            message.extend_from_slice(&SRV_TYPE.to_be_bytes());
+            message.extend_from_slice(&[0x00, 0x01, 0x00, 0x00, 0x0e, 0x10]);
+            message.extend_from_slice(&(6 + 1 + host.len() as u16 + 2).to_be_bytes());
+            message.extend_from_slice(&priority.to_be_bytes());
+            message.extend_from_slice(&weight.to_be_bytes());
+            message.extend_from_slice(&port.to_be_bytes());
+            message.push(host.len() as u8);
+            message.extend_from_slice(host.as_bytes());
+            message.extend_from_slice(&[0xc0, test_label as u8]);
+        }
+        message
+    }
+
+    #[test]
+    fn test_bootstrap_documents() {
+        let plain = NodeDirectory::from_bootstrap(&json!(["https://a/graphql", "https://b/graphql"])).unwrap();
+        assert_eq!(plain.uris, vec!["https://a/graphql", "https://b/graphql"]);
+        assert!(plain.cells.is_empty());
+
+        let assigned = NodeDirectory::from_bootstrap(&json!({ "nodes": [
+            { "uri": "https://a/graphql", "cells": ["RED"] },
+            { "uri": "https://b/graphql", "cells": ["RED", "BLUE"] },
+            { "uri": "https://c/graphql" },
+        ] })).unwrap();
+        assert_eq!(assigned.uris.len(), 3);
+        assert_eq!(assigned.uris_for_cell(Some("RED")), vec!["https://a/graphql", "https://b/graphql"]);
+        assert_eq!(assigned.uris_for_cell(Some("BLUE")), vec!["https://b/graphql"]);
+        assert_eq!(assigned.uris_for_cell(Some("GREEN")), assigned.uris);
+        assert_eq!(assigned.uris_for_cell(None), assigned.uris);
+
+        assert!(NodeDirectory::from_bootstrap(&json!({ "nodes": [{ "cells": ["RED"] }] })).is_err());
+        assert!(NodeDirectory::from_bootstrap(&json!({ "status": "ok" })).is_err());
+    }
+
+    #[test]
+    fn test_srv_response_decoding() {
+        let records = decode_srv_response(7, &srv_response(7)).unwrap();
+        assert_eq!(records, vec![
+            SrvRecord { priority: 20, weight: 5, port: 8443, target: "b.test".to_string() },
+            SrvRecord { priority: 10, weight: 5, port: 443, target: "a.test".to_string() },
+        ]);
+        assert_eq!(
+            NodeDirectory::from_srv(records, "https", "/graphql").uris,
+            vec!["https://a.test:443/graphql", "https://b.test:8443/graphql"]
+        );
+
+        assert!(decode_srv_response(8, &srv_response(7)).is_err());
+        assert!(decode_srv_response(7, &srv_response(7)[..40]).is_err());
+        assert!(encode_srv_query(1, "bad..name").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_discover_from_nameserver_and_bootstrap() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Nameserver answering every query with the two records above
+        let dns = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let nameserver = dns.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buffer = [0u8; 512];
+            while let Ok((_, peer)) = dns.recv_from(&mut buffer).await {
+                let id = u16::from_be_bytes([buffer[0], buffer[1]]);
+                dns.send_to(&srv_response(id), peer).await.unwrap();
+            }
+        });
+
+        let config = DiscoveryConfig::srv("_knishio._tcp.test").scheme("http").nameserver(nameserver);
+        let directory = discover(&config).await.unwrap();
+        assert_eq!(directory.uris, vec!["http://a.test:443/graphql", "http://b.test:8443/graphql"]);
+
+        // Bootstrap server assigning cell RED to node b only
+        let http = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let bootstrap = format!("http://{}/nodes", http.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = http.accept().await {
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request).await;
+                let body = json!({ "nodes": [
+                    { "uri": "https://a.test/graphql" },
+                    { "uri": "https://b.test/graphql", "cells": ["RED"] },
+                ] }).to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(), body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let mut client = KnishIOClient::new("https://old.test/graphql", Some("RED".to_string()), None, None, None, Some(false));
+        client.discover_nodes(&DiscoveryConfig::bootstrap(bootstrap)).await.unwrap();
+        assert_eq!(client.get_uris(), ["https://b.test/graphql"]);
+        assert_eq!(client.get_uri().as_deref(), Some("https://b.test/graphql"));
+    }
+}
@@ -0,0 +1,716 @@
+//! Buffer-based escrow
+//!
+//! An escrow parks tokens in a fresh buffer (B isotope) wallet of the depositor. The
+//! depositing molecule also carries an `escrow` meta record, keyed by the buffer wallet's
+//! address, naming the depositor, arbiter and beneficiary and the conditions agreed on.
+//! `release_escrow` pays the buffer out to the beneficiary and `refund_escrow` back to the
+//! depositor; either one drains the buffer and marks the record settled in the same
+//! molecule, so the funds and the record cannot disagree.
+//!
+//! The buffer is derived from the depositor's secret, so only the depositor can sign a
+//! settlement, and nothing on the ledger stops the depositor from draining it directly.
+//! The arbiter's part is advisory: `arbitrate_escrow` records a ruling from the arbiter's
+//! bundle, and `release_escrow` and `refund_escrow` refuse to contradict it. Without a
+//! ruling they always release, and refund only once the escrow's expiry has passed. These
+//! checks run in this client; a node enforces none of them, so an escrow protects the
+//! beneficiary only as far as the depositor is trusted to settle through this API.
+//!
+//! Anyone can write `escrow` metas, so records are read from the atoms that wrote them:
+//! the opening record counts only when the depositor's bundle wrote it in the molecule
+//! that funded the buffer, status updates only when the depositor wrote them, and rulings
+//! only when the arbiter did. Payouts land in the recipient's shadow wallet and are
+//! claimed with `claim_shadow_wallet`.
+
+use crate::atom::{Atom, AtomCreateParams, WalletInfo};
+use crate::auth::AuthScope;
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::molecule::Molecule;
+use crate::mutation::propose_molecule::MutationProposeMolecule;
+use crate::mutation::Mutation;
+use crate::query::atom::QueryAtom;
+use crate::query::balance::QueryBalance;
+use crate::query::Query;
+use crate::response::Response;
+use crate::types::{created_at_millis, Isotope, MetaItem};
+use crate::wallet::{Wallet, WalletParams};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Meta type of escrow records
+pub const ESCROW_META_TYPE: &str = "escrow";
+
+/// Terms an escrow is created under
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscrowConditions {
+    /// Bundle paid on release
+    pub beneficiary: String,
+    /// Unix time (seconds) from which the depositor may take a refund without a ruling
+    pub expires_at: Option<u64>,
+    /// Free-form terms, e.g. a contract reference
+    pub terms: Option<String>,
+}
+
+impl EscrowConditions {
+    /// Conditions paying `beneficiary`, refundable only on the arbiter's ruling
+    pub fn new(beneficiary: impl Into<String>) -> Self {
+        EscrowConditions {
+            beneficiary: beneficiary.into(),
+            expires_at: None,
+            terms: None,
+        }
+    }
+
+    /// Let the depositor take a refund from `expires_at` (Unix seconds) on, unless the
+    /// arbiter has ruled
+    pub fn with_expiry(mut self, expires_at: u64) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Attach free-form terms
+    pub fn with_terms(mut self, terms: impl Into<String>) -> Self {
+        self.terms = Some(terms.into());
+        self
+    }
+}
+
+/// Lifecycle of an escrow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EscrowStatus {
+    /// Funds are held in the buffer
+    Open,
+    /// Funds were paid to the beneficiary
+    Released,
+    /// Funds were returned to the depositor
+    Refunded,
+}
+
+impl EscrowStatus {
+    /// Value of the record's `status` meta
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EscrowStatus::Open => "open",
+            EscrowStatus::Released => "released",
+            EscrowStatus::Refunded => "refunded",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "open" => Some(EscrowStatus::Open),
+            "released" => Some(EscrowStatus::Released),
+            "refunded" => Some(EscrowStatus::Refunded),
+            _ => None,
+        }
+    }
+}
+
+/// An escrow record as stored on the ledger
+#[derive(Debug, Clone, PartialEq)]
+pub struct Escrow {
+    /// Address of the buffer wallet holding the funds
+    pub id: String,
+    /// Token slug
+    pub token: String,
+    /// Amount held
+    pub amount: f64,
+    /// Bundle that funded the escrow
+    pub depositor: String,
+    /// Bundle arbitrating disputes
+    pub arbiter: String,
+    /// Position of the buffer wallet
+    pub position: String,
+    /// Batch id of the buffered tokens
+    pub batch_id: Option<String>,
+    /// Release and refund terms
+    pub conditions: EscrowConditions,
+    /// Current state
+    pub status: EscrowStatus,
+    /// Outcome the arbiter ruled, if any
+    pub ruling: Option<EscrowStatus>,
+}
+
+impl Escrow {
+    /// True while the funds are held
+    pub fn is_open(&self) -> bool {
+        self.status == EscrowStatus::Open
+    }
+
+    /// True while the funds may go to the beneficiary
+    pub fn is_releasable(&self) -> bool {
+        self.is_open() && self.ruling != Some(EscrowStatus::Refunded)
+    }
+
+    /// True once the depositor may take a refund at `now` (Unix seconds)
+    pub fn is_refundable_at(&self, now: u64) -> bool {
+        self.is_open() && match self.ruling {
+            Some(ruling) => ruling == EscrowStatus::Refunded,
+            None => self.conditions.expires_at.is_some_and(|expires_at| now >= expires_at),
+        }
+    }
+
+    /// True if `bundle` is the depositor, arbiter or beneficiary
+    pub fn involves(&self, bundle: &str) -> bool {
+        [self.depositor.as_str(), self.arbiter.as_str(), self.conditions.beneficiary.as_str()].contains(&bundle)
+    }
+
+    /// Meta record written by `create_escrow`
+    fn to_meta(&self) -> Vec<MetaItem> {
+        let mut meta = vec![
+            MetaItem::new("token", self.token.as_str()),
+            MetaItem::new("amount", self.amount.to_string()),
+            MetaItem::new("depositor", self.depositor.as_str()),
+            MetaItem::new("arbiter", self.arbiter.as_str()),
+            MetaItem::new("beneficiary", self.conditions.beneficiary.as_str()),
+            MetaItem::new("position", self.position.as_str()),
+            MetaItem::new("status", self.status.as_str()),
+        ];
+        if let Some(ref batch_id) = self.batch_id {
+            meta.push(MetaItem::new("batchId", batch_id.as_str()));
+        }
+        if let Some(expires_at) = self.conditions.expires_at {
+            meta.push(MetaItem::new("expiresAt", expires_at.to_string()));
+        }
+        if let Some(ref terms) = self.conditions.terms {
+            meta.push(MetaItem::new("terms", terms.as_str()));
+        }
+        meta
+    }
+
+    /// Read an opening record; None if it is not a complete one
+    fn from_metas(id: &str, metas: &HashMap<String, String>) -> Option<Self> {
+        let field = |key: &str| metas.get(key).cloned();
+
+        Some(Escrow {
+            id: id.to_string(),
+            token: field("token")?,
+            amount: metas.get("amount")?.parse().ok()?,
+            depositor: field("depositor")?,
+            arbiter: field("arbiter")?,
+            position: field("position")?,
+            batch_id: field("batchId"),
+            conditions: EscrowConditions {
+                beneficiary: field("beneficiary")?,
+                expires_at: metas.get("expiresAt").and_then(|value| value.parse().ok()),
+                terms: field("terms"),
+            },
+            status: EscrowStatus::parse(metas.get("status")?)?,
+            ruling: None,
+        })
+    }
+}
+
+impl KnishIOClient {
+    /// Move `amount` of `token` into a new escrow buffer with `arbiter_bundle` as arbiter
+    ///
+    /// ```no_run
+    /// # async fn demo(client: &mut knishio_client::KnishIOClient, seller: &str, arbiter: &str) -> knishio_client::Result<()> {
+    /// use knishio_client::client::EscrowConditions;
+    ///
+    /// let conditions = EscrowConditions::new(seller).with_terms("order 1187");
+    /// let escrow = client.create_escrow("GOLD", 25.0, arbiter, conditions).await?;
+    /// // ... goods delivered
+    /// client.release_escrow(&escrow.id).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// `InvalidAmount` for a non-positive amount, `TransferToSelf` when this bundle is the
+    /// beneficiary, `BalanceInsufficient` without enough `token`, or the node's rejection
+    pub async fn create_escrow(
+        &mut self,
+        token: &str,
+        amount: f64,
+        arbiter_bundle: &str,
+        conditions: EscrowConditions,
+    ) -> Result<Escrow> {
+        if !amount.is_finite() || amount <= 0.0 {
+            return Err(KnishIOError::InvalidAmount(amount.to_string()));
+        }
+
+        self.ensure_authentication(None).await?;
+        self.require_auth_scope("create_escrow", AuthScope::Profile)?;
+
+        let secret = self.secret.clone().ok_or(KnishIOError::MissingSecret)?;
+        let depositor = self.bundle.clone().ok_or(KnishIOError::MissingBundle)?;
+        if conditions.beneficiary == depositor {
+            return Err(KnishIOError::TransferToSelf);
+        }
+
+        self.log("info", &format!(
+            "KnishIOClient::create_escrow() - Escrowing {} of {} for {}...",
+            amount, token, conditions.beneficiary
+        ));
+
+        let source_wallet = self.query_source_wallet(token, amount, None).await?;
+        let mut molecule = self.new_molecule();
+        molecule.remainder_wallet = Some(source_wallet.create_remainder(&secret)?);
+        molecule.secret = Some(secret);
+        molecule.bundle = Some(depositor.clone());
+        molecule.cell_slug = self.cell_slug.clone();
+        molecule.source_wallet = Some(source_wallet);
+        molecule.init_deposit_buffer(amount, HashMap::new())?;
+
+        let buffer = molecule
+            .atoms
+            .iter()
+            .find(|atom| atom.isotope == Isotope::B)
+            .ok_or(KnishIOError::AtomsMissing)?;
+        let escrow = Escrow {
+            id: buffer.wallet_address.clone(),
+            token: token.to_string(),
+            amount,
+            depositor,
+            arbiter: arbiter_bundle.to_string(),
+            position: buffer.position.clone(),
+            batch_id: buffer.batch_id.clone(),
+            conditions,
+            status: EscrowStatus::Open,
+            ruling: None,
+        };
+        let record = escrow_record(&molecule, &escrow.id, escrow.to_meta())?;
+        molecule.add_atom(record);
+
+        self.propose_escrow_molecule(molecule).await?;
+        Ok(escrow)
+    }
+
+    /// Pay an open escrow out to its beneficiary
+    ///
+    /// # Errors
+    ///
+    /// `EscrowUnavailable` if the escrow is unknown, settled, held by another bundle or
+    /// ruled to be refunded
+    pub async fn release_escrow(&mut self, escrow_id: &str) -> Result<Box<dyn Response>> {
+        self.settle_escrow(escrow_id, EscrowStatus::Released).await
+    }
+
+    /// Return an open escrow to its depositor, as the arbiter ruled or once it expired
+    ///
+    /// # Errors
+    ///
+    /// `EscrowUnavailable` if the escrow is unknown, settled, held by another bundle,
+    /// ruled to be released, or neither ruled on nor expired
+    pub async fn refund_escrow(&mut self, escrow_id: &str) -> Result<Box<dyn Response>> {
+        self.settle_escrow(escrow_id, EscrowStatus::Refunded).await
+    }
+
+    /// Rule, as the escrow's arbiter, whether an open escrow is released or refunded
+    ///
+    /// The depositor's client carries the ruling out with `release_escrow` or
+    /// `refund_escrow`; the first ruling stands. The ruling is a record, not a lock: only
+    /// those client calls honour it, and the node does not hold the depositor to it.
+    ///
+    /// # Errors
+    ///
+    /// `EscrowUnavailable` if the escrow is unknown, settled or already ruled on, if this
+    /// bundle is not its arbiter, or if `ruling` is `Open`
+    pub async fn arbitrate_escrow(&mut self, escrow_id: &str, ruling: EscrowStatus) -> Result<Box<dyn Response>> {
+        if ruling == EscrowStatus::Open {
+            return Err(KnishIOError::EscrowUnavailable("a ruling must release or refund".to_string()));
+        }
+
+        self.ensure_authentication(None).await?;
+        self.require_auth_scope("arbitrate_escrow", AuthScope::Profile)?;
+
+        let escrow = self.open_escrow(escrow_id).await?;
+        if self.bundle.as_deref() != Some(escrow.arbiter.as_str()) {
+            return Err(KnishIOError::EscrowUnavailable(format!("{} is arbitrated by another bundle", escrow_id)));
+        }
+        if let Some(ruled) = escrow.ruling {
+            return Err(KnishIOError::EscrowUnavailable(format!("{} was already ruled {}", escrow_id, ruled.as_str())));
+        }
+
+        self.log("info", &format!("KnishIOClient::arbitrate_escrow() - Escrow {} ruled {}...", escrow_id, ruling.as_str()));
+
+        let mut molecule = self.create_molecule(None, None, None, None).await?;
+        molecule.init_meta(vec![MetaItem::new("ruling", ruling.as_str())], ESCROW_META_TYPE, escrow_id, None)?;
+        self.propose_escrow_molecule(molecule).await
+    }
+
+    /// The escrow record `escrow_id`, if any
+    pub async fn query_escrow(&self, escrow_id: &str) -> Result<Option<Escrow>> {
+        Ok(self.query_escrow_records(Some(escrow_id)).await?.into_iter().next())
+    }
+
+    /// Open escrows in which `bundle` is depositor, arbiter or beneficiary
+    pub async fn query_open_escrows(&self, bundle: &str) -> Result<Vec<Escrow>> {
+        let mut escrows = self.query_escrow_records(None).await?;
+        escrows.retain(|escrow| escrow.is_open() && escrow.involves(bundle));
+        Ok(escrows)
+    }
+
+    /// The open escrow `escrow_id`
+    async fn open_escrow(&self, escrow_id: &str) -> Result<Escrow> {
+        let escrow = self
+            .query_escrow(escrow_id)
+            .await?
+            .ok_or_else(|| KnishIOError::EscrowUnavailable(format!("{} is not an escrow", escrow_id)))?;
+        if !escrow.is_open() {
+            return Err(KnishIOError::EscrowUnavailable(format!("{} is already {}", escrow_id, escrow.status.as_str())));
+        }
+        Ok(escrow)
+    }
+
+    /// Drain an open escrow's buffer to the party `status` pays and record the outcome
+    async fn settle_escrow(&mut self, escrow_id: &str, status: EscrowStatus) -> Result<Box<dyn Response>> {
+        self.ensure_authentication(None).await?;
+        self.require_auth_scope("settle_escrow", AuthScope::Profile)?;
+
+        let escrow = self.open_escrow(escrow_id).await?;
+        if self.bundle.as_deref() != Some(escrow.depositor.as_str()) {
+            return Err(KnishIOError::EscrowUnavailable(format!("{} is held by another bundle", escrow_id)));
+        }
+        match status {
+            EscrowStatus::Refunded if !escrow.is_refundable_at(unix_now()) => {
+                return Err(KnishIOError::EscrowUnavailable(match escrow.ruling {
+                    Some(_) => format!("{} was ruled to be released", escrow_id),
+                    None => format!("{} cannot be refunded before the arbiter rules or it expires", escrow_id),
+                }));
+            }
+            EscrowStatus::Released if !escrow.is_releasable() => {
+                return Err(KnishIOError::EscrowUnavailable(format!("{} was ruled to be refunded", escrow_id)));
+            }
+            _ => {}
+        }
+
+        let recipient = match status {
+            EscrowStatus::Refunded => escrow.depositor.clone(),
+            _ => escrow.conditions.beneficiary.clone(),
+        };
+        self.log("info", &format!(
+            "KnishIOClient::settle_escrow() - Escrow {} {} to {}...",
+            escrow_id, status.as_str(), recipient
+        ));
+
+        // Rebuild the buffer wallet from the record; it must reproduce the escrow's address
+        let secret = self.secret.clone().ok_or(KnishIOError::MissingSecret)?;
+        let mut buffer = Wallet::from_params(WalletParams {
+            bundle: Some(escrow.depositor.clone()),
+            position: Some(escrow.position.clone()),
+            batch_id: escrow.batch_id.clone(),
+            ..WalletParams::new().secret(&secret).token(&escrow.token)
+        })?;
+        if buffer.address.as_deref() != Some(escrow.id.as_str()) {
+            return Err(KnishIOError::EscrowUnavailable(format!("{} is held by another bundle", escrow_id)));
+        }
+        // Pay out what the buffer holds, not what the record says was deposited
+        buffer.balance = self.buffer_balance(&escrow).await?;
+        let amount = buffer.balance.parse::<f64>().map_err(|_| KnishIOError::InvalidResponse)?;
+
+        let mut molecule = self.new_molecule();
+        molecule.secret = Some(secret);
+        molecule.bundle = self.bundle.clone();
+        molecule.cell_slug = self.cell_slug.clone();
+        molecule.source_wallet = Some(buffer);
+        molecule.init_withdraw_buffer(HashMap::from([(recipient, amount)]), None)?;
+        let record = escrow_record(&molecule, &escrow.id, vec![MetaItem::new("status", status.as_str())])?;
+        molecule.add_atom(record);
+
+        self.propose_escrow_molecule(molecule).await
+    }
+
+    /// Sign, check and propose an escrow molecule, failing on rejection
+    ///
+    /// Refused with `EscrowUnavailable` for an anonymous client, whose records would not count.
+    async fn propose_escrow_molecule(&self, mut molecule: Molecule) -> Result<Box<dyn Response>> {
+        if self.is_anonymous() {
+            return Err(KnishIOError::EscrowUnavailable("escrow molecules must carry the signer's bundle".to_string()));
+        }
+        molecule.sign(None, false, true)?;
+        molecule.check(None)?;
+
+        let client = self.client.as_ref().ok_or(KnishIOError::NoClient)?;
+        let mutation = MutationProposeMolecule::from_molecule(molecule);
+        let response = Mutation::execute(&mutation, client, None, None).await?;
+        if !response.success() {
+            return Err(KnishIOError::Custom(response.reason().unwrap_or_else(|| "Escrow molecule rejected".to_string())));
+        }
+        Ok(response)
+    }
+
+    /// Balance of an escrow's buffer wallet, as the ledger reports it
+    async fn buffer_balance(&self, escrow: &Escrow) -> Result<String> {
+        let query = QueryBalance::new()
+            .with_address(escrow.id.as_str())
+            .with_type("buffer")
+            .with_token(escrow.token.as_str());
+        let client = self.client.as_ref().ok_or(KnishIOError::NoClient)?;
+        let data = query.execute(client, None, None).await?.into_data();
+        let wallet = data.get("Balance").unwrap_or(&data);
+
+        if wallet.is_null() {
+            return Err(KnishIOError::EscrowUnavailable(format!("{} holds no funds", escrow.id)));
+        }
+        if wallet["bundleHash"].as_str() != Some(escrow.depositor.as_str()) {
+            return Err(KnishIOError::EscrowUnavailable(format!("{} is held by another bundle", escrow.id)));
+        }
+        match &wallet["amount"] {
+            Value::String(amount) => Ok(amount.clone()),
+            Value::Number(amount) => Ok(amount.to_string()),
+            _ => Err(KnishIOError::InvalidResponse),
+        }
+    }
+
+    /// Escrow records, all or the one of `escrow_id`, as their trusted atoms left them
+    async fn query_escrow_records(&self, escrow_id: Option<&str>) -> Result<Vec<Escrow>> {
+        let client = self.client.as_ref().ok_or(KnishIOError::NoClient)?;
+
+        let mut query = QueryAtom::new().add_meta_type(ESCROW_META_TYPE).add_isotope("M");
+        if let Some(id) = escrow_id {
+            query = query.add_meta_id(id);
+        }
+        if let Some(ref cell) = self.cell_slug {
+            query = query.add_cell_slug(cell);
+        }
+        let mut writes: Vec<RecordWrite> = query.fetch_all(client).await?.iter().filter_map(RecordWrite::read).collect();
+        // Later writes win; nodes do not promise to list atoms in order
+        writes.sort_by_key(|write| write.created_at);
+
+        let mut ids: Vec<String> = Vec::new();
+        for write in &writes {
+            if !ids.contains(&write.escrow_id) {
+                ids.push(write.escrow_id.clone());
+            }
+        }
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Molecules that credited each buffer: only those can open its escrow
+        let deposits: Vec<(String, String)> = QueryAtom::new()
+            .add_isotope("B")
+            .add_wallet_addresses(ids.clone())
+            .fetch_all(client)
+            .await?
+            .iter()
+            .filter(|atom| atom["value"].as_str().and_then(|value| value.parse::<f64>().ok()).is_some_and(|value| value > 0.0))
+            .filter_map(|atom| Some((atom["walletAddress"].as_str()?.to_string(), atom["molecularHash"].as_str()?.to_string())))
+            .collect();
+
+        Ok(ids
+            .iter()
+            .filter_map(|id| {
+                let escrow_writes: Vec<&RecordWrite> = writes.iter().filter(|write| &write.escrow_id == id).collect();
+                let (opening, opened) = escrow_writes.iter().enumerate().find_map(|(index, write)| {
+                    let escrow = Escrow::from_metas(id, &write.metas)?;
+                    let funded = deposits.contains(&(id.clone(), write.molecular_hash.clone()));
+                    (funded && write.bundles.contains(&escrow.depositor)).then_some((index, escrow))
+                })?;
+
+                let mut escrow = opened;
+                for write in &escrow_writes[opening + 1..] {
+                    if write.bundles.contains(&escrow.depositor) {
+                        if let Some(status) = write.metas.get("status").and_then(|status| EscrowStatus::parse(status)) {
+                            escrow.status = status;
+                        }
+                    }
+                    if escrow.ruling.is_none() && write.bundles.contains(&escrow.arbiter) {
+                        escrow.ruling = write.metas.get("ruling").and_then(|ruling| EscrowStatus::parse(ruling));
+                    }
+                }
+                Some(escrow)
+            })
+            .collect())
+    }
+}
+
+/// One M atom writing to an escrow record
+struct RecordWrite {
+    escrow_id: String,
+    molecular_hash: String,
+    bundles: Vec<String>,
+    created_at: i64,
+    metas: HashMap<String, String>,
+}
+
+impl RecordWrite {
+    /// Read an Atom query instance; None if it lacks what a record write needs
+    fn read(atom: &Value) -> Option<Self> {
+        let items: Vec<Value> = serde_json::from_str(atom["metasJson"].as_str()?).ok()?;
+        let metas = items
+            .iter()
+            .filter_map(|item| Some((item["key"].as_str()?.to_string(), item["value"].as_str()?.to_string())))
+            .collect();
+
+        Some(RecordWrite {
+            escrow_id: atom["metaId"].as_str()?.to_string(),
+            molecular_hash: atom["molecularHash"].as_str()?.to_string(),
+            bundles: serde_json::from_value(atom["bundleHashes"].clone()).ok()?,
+            created_at: created_at_millis(atom["createdAt"].as_str()?).ok()?,
+            metas,
+        })
+    }
+}
+
+/// M atom writing `meta` to the escrow record `escrow_id`
+///
+/// Meta atoms must be USER atoms, so the record is written from a fresh USER wallet of
+/// the molecule's bundle.
+fn escrow_record(molecule: &Molecule, escrow_id: &str, meta: Vec<MetaItem>) -> Result<Atom> {
+    let wallet = Wallet::create(molecule.secret.as_deref(), molecule.bundle.as_deref(), &molecule.system_tokens.user, None, None)?;
+    Ok(Atom::create(AtomCreateParams {
+        isotope: Isotope::M,
+        wallet_info: Some(WalletInfo {
+            position: wallet.position.clone().unwrap_or_default(),
+            address: wallet.address.clone().unwrap_or_default(),
+            token: wallet.token.clone(),
+            batch_id: None,
+        }),
+        meta_type: Some(ESCROW_META_TYPE.to_string()),
+        meta_id: Some(escrow_id.to_string()),
+        meta: Some(meta),
+        ..Default::default()
+    }))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{generate_bundle_hash, generate_secret};
+    use crate::test_ledger::TestLedger;
+
+    #[tokio::test]
+    async fn test_escrow_release_and_refund() {
+        let ledger = TestLedger::start().await.unwrap();
+        let buyer_secret = generate_secret("escrow-buyer");
+        let buyer = generate_bundle_hash(&buyer_secret);
+        let seller = generate_bundle_hash(&generate_secret("escrow-seller"));
+        let arbiter_secret = generate_secret("escrow-arbiter");
+        let arbiter = generate_bundle_hash(&arbiter_secret);
+        ledger.fund(&buyer_secret, "GOLD", 100.0).unwrap();
+        let mut client = ledger.client(&buyer_secret);
+
+        let conditions = EscrowConditions::new(seller.as_str()).with_terms("order 1187");
+        let escrow = client.create_escrow("GOLD", 30.0, &arbiter, conditions).await.unwrap();
+        assert_eq!(ledger.balance(&buyer, "GOLD"), 70.0);
+        assert_eq!(client.query_escrow(&escrow.id).await.unwrap(), Some(escrow.clone()));
+        for bundle in [&buyer, &seller, &arbiter] {
+            assert_eq!(client.query_open_escrows(bundle).await.unwrap(), vec![escrow.clone()]);
+        }
+        assert!(client.query_open_escrows(&generate_bundle_hash(&generate_secret("escrow-stranger"))).await.unwrap().is_empty());
+
+        // Without an expiry or a ruling the depositor cannot take the funds back
+        assert!(matches!(client.refund_escrow(&escrow.id).await, Err(KnishIOError::EscrowUnavailable(_))));
+        let response = client.release_escrow(&escrow.id).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        assert_eq!(ledger.balance(&seller, "GOLD"), 30.0);
+        assert_eq!(client.query_escrow(&escrow.id).await.unwrap().unwrap().status, EscrowStatus::Released);
+        assert!(client.query_open_escrows(&seller).await.unwrap().is_empty());
+        assert!(matches!(client.refund_escrow(&escrow.id).await, Err(KnishIOError::EscrowUnavailable(_))));
+
+        // Refunds wait for the expiry
+        let locked = EscrowConditions::new(seller.as_str()).with_expiry(unix_now() + 3600);
+        let locked = client.create_escrow("GOLD", 20.0, &arbiter, locked).await.unwrap();
+        assert!(matches!(client.refund_escrow(&locked.id).await, Err(KnishIOError::EscrowUnavailable(_))));
+
+        let expired = EscrowConditions::new(seller.as_str()).with_expiry(unix_now() - 1);
+        let expired = client.create_escrow("GOLD", 10.0, &arbiter, expired).await.unwrap();
+        assert_eq!(client.query_open_escrows(&buyer).await.unwrap().len(), 2);
+        let response = client.refund_escrow(&expired.id).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        assert_eq!(client.query_escrow(&expired.id).await.unwrap().unwrap().status, EscrowStatus::Refunded);
+        assert_eq!(ledger.balance(&buyer, "GOLD"), 50.0);
+
+        // The arbiter's ruling overrides the expiry, and binds the depositor
+        let mut arbiter_client = ledger.client(&arbiter_secret);
+        let response = arbiter_client.arbitrate_escrow(&locked.id, EscrowStatus::Refunded).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        assert!(matches!(
+            arbiter_client.arbitrate_escrow(&locked.id, EscrowStatus::Released).await,
+            Err(KnishIOError::EscrowUnavailable(_))
+        ));
+        assert_eq!(client.query_escrow(&locked.id).await.unwrap().unwrap().ruling, Some(EscrowStatus::Refunded));
+        assert!(matches!(client.release_escrow(&locked.id).await, Err(KnishIOError::EscrowUnavailable(_))));
+        let response = client.refund_escrow(&locked.id).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        assert_eq!(ledger.balance(&buyer, "GOLD"), 70.0);
+        assert!(client.query_open_escrows(&buyer).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_escrow_rejections() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("escrow-depositor");
+        let bundle = generate_bundle_hash(&secret);
+        let arbiter = generate_bundle_hash(&generate_secret("escrow-arbiter"));
+        ledger.fund(&secret, "GOLD", 10.0).unwrap();
+        let mut client = ledger.client(&secret);
+
+        let beneficiary = generate_bundle_hash(&generate_secret("escrow-beneficiary"));
+        assert!(matches!(
+            client.create_escrow("GOLD", 0.0, &arbiter, EscrowConditions::new(beneficiary.as_str())).await,
+            Err(KnishIOError::InvalidAmount(_))
+        ));
+        assert!(matches!(
+            client.create_escrow("GOLD", 5.0, &arbiter, EscrowConditions::new(bundle.as_str())).await,
+            Err(KnishIOError::TransferToSelf)
+        ));
+        assert!(matches!(client.release_escrow("missing").await, Err(KnishIOError::EscrowUnavailable(_))));
+
+        // Only the depositor's client can settle, and only the arbiter can rule
+        let escrow = client.create_escrow("GOLD", 5.0, &arbiter, EscrowConditions::new(beneficiary.as_str())).await.unwrap();
+        let mut other = ledger.client(&generate_secret("escrow-beneficiary"));
+        assert!(matches!(other.release_escrow(&escrow.id).await, Err(KnishIOError::EscrowUnavailable(_))));
+        assert!(matches!(
+            other.arbitrate_escrow(&escrow.id, EscrowStatus::Released).await,
+            Err(KnishIOError::EscrowUnavailable(_))
+        ));
+        assert!(matches!(
+            client.arbitrate_escrow(&escrow.id, EscrowStatus::Refunded).await,
+            Err(KnishIOError::EscrowUnavailable(_))
+        ));
+        assert_eq!(ledger.balance(&bundle, "GOLD"), 5.0);
+
+        // Records written anonymously would not count, so anonymous clients are refused
+        client.set_anonymous(true);
+        assert!(matches!(
+            client.create_escrow("GOLD", 1.0, &arbiter, EscrowConditions::new(beneficiary.as_str())).await,
+            Err(KnishIOError::EscrowUnavailable(_))
+        ));
+        assert!(matches!(client.release_escrow(&escrow.id).await, Err(KnishIOError::EscrowUnavailable(_))));
+        assert_eq!(ledger.balance(&bundle, "GOLD"), 5.0);
+    }
+
+    #[tokio::test]
+    async fn test_escrow_metas_from_other_bundles_are_ignored() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("escrow-depositor");
+        let arbiter = generate_bundle_hash(&generate_secret("escrow-arbiter"));
+        let beneficiary = generate_bundle_hash(&generate_secret("escrow-beneficiary"));
+        ledger.fund(&secret, "GOLD", 10.0).unwrap();
+        let mut client = ledger.client(&secret);
+        let escrow = client.create_escrow("GOLD", 5.0, &arbiter, EscrowConditions::new(beneficiary.as_str())).await.unwrap();
+
+        // A stranger rewrites the record: new parties, a ruling and a settled status
+        let forger_secret = generate_secret("escrow-forger");
+        let forger = generate_bundle_hash(&forger_secret);
+        let mut forger_client = ledger.client(&forger_secret);
+        let mut forged = Escrow { depositor: forger.clone(), arbiter: forger.clone(), ..escrow.clone() };
+        forged.conditions.beneficiary = forger.clone();
+        let mut metas = forged.to_meta();
+        metas.push(MetaItem::new("ruling", "released"));
+        let mut molecule = forger_client.create_molecule(None, None, None, None).await.unwrap();
+        molecule.init_meta(metas, ESCROW_META_TYPE, &escrow.id, None).unwrap();
+        forger_client.propose_escrow_molecule(molecule).await.unwrap();
+
+        // An escrow record that no deposit funded
+        let mut molecule = forger_client.create_molecule(None, None, None, None).await.unwrap();
+        let phantom = Escrow { id: "phantom-buffer".to_string(), ..forged.clone() };
+        molecule.init_meta(phantom.to_meta(), ESCROW_META_TYPE, &phantom.id, None).unwrap();
+        forger_client.propose_escrow_molecule(molecule).await.unwrap();
+
+        assert_eq!(client.query_escrow(&escrow.id).await.unwrap(), Some(escrow.clone()));
+        assert_eq!(client.query_escrow("phantom-buffer").await.unwrap(), None);
+        assert!(client.query_open_escrows(&forger).await.unwrap().is_empty());
+        assert!(matches!(forger_client.release_escrow(&escrow.id).await, Err(KnishIOError::EscrowUnavailable(_))));
+    }
+}
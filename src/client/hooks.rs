@@ -0,0 +1,427 @@
+//! Operation hooks
+//!
+//! Logic that belongs around every transfer or token creation (attaching invoice metadata,
+//! holding transfers for compliance review, notifying a bookkeeping service) registers once
+//! as a hook instead of wrapping each call site.
+//!
+//! Pre hooks run in registration order before the operation is built. Each receives the
+//! `OperationContext` the previous one returned and can change its `meta` or stop the
+//! operation by returning an error (`KnishIOError::OperationHeld` for a hold), which the
+//! operation returns as is. Post hooks run after the operation, whether it succeeded or
+//! not, with the final context and an `OperationOutcome`; an operation a pre hook stopped
+//! never ran and gets no post hooks.
+//!
+//! Hooks are async closures returning `'static` futures; capture what they need by
+//! cloning (a client clone, an `Arc`'d store) rather than borrowing.
+
+use crate::client::KnishIOClient;
+use crate::error::Result;
+use crate::response::{Response, ResponseUtils};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+/// Client operation a hook can be registered for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// `create_token`
+    CreateToken,
+    /// `request_tokens`
+    RequestTokens,
+    /// `transfer_token`
+    TransferToken,
+    /// `transfer_tokens`
+    TransferTokens,
+    /// `burn_tokens`
+    BurnTokens,
+    /// `replenish_token`
+    ReplenishToken,
+    /// `create_meta`
+    CreateMeta,
+}
+
+impl Operation {
+    /// Name of the client method
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operation::CreateToken => "create_token",
+            Operation::RequestTokens => "request_tokens",
+            Operation::TransferToken => "transfer_token",
+            Operation::TransferTokens => "transfer_tokens",
+            Operation::BurnTokens => "burn_tokens",
+            Operation::ReplenishToken => "replenish_token",
+            Operation::CreateMeta => "create_meta",
+        }
+    }
+
+    /// Whether pre hook changes to `OperationContext::meta` reach the molecule
+    pub fn writes_meta(&self) -> bool {
+        matches!(self, Operation::CreateToken | Operation::RequestTokens | Operation::CreateMeta)
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What an operation is about to do
+///
+/// Only `meta` is read back after the pre hooks, and only for operations that write
+/// metadata (`Operation::writes_meta`); the other fields describe the call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationContext {
+    pub operation: Operation,
+    /// Token slug (None for `create_meta`)
+    pub token: Option<String>,
+    /// Amount as passed by the caller, or the unit count when units were given
+    pub amount: Option<f64>,
+    /// Recipient bundle hashes of a transfer or token request
+    pub recipients: Vec<String>,
+    /// Meta type of `create_meta`
+    pub meta_type: Option<String>,
+    /// Meta ID of `create_meta`
+    pub meta_id: Option<String>,
+    /// Metadata the operation writes
+    pub meta: HashMap<String, Value>,
+}
+
+impl OperationContext {
+    /// Context of `operation` with no details filled in
+    pub fn new(operation: Operation) -> Self {
+        OperationContext {
+            operation,
+            token: None,
+            amount: None,
+            recipients: Vec::new(),
+            meta_type: None,
+            meta_id: None,
+            meta: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    pub(crate) fn amount(mut self, amount: Option<f64>, units: &[String]) -> Self {
+        self.amount = if units.is_empty() { amount } else { Some(units.len() as f64) };
+        self
+    }
+
+    pub(crate) fn recipients(mut self, recipients: Vec<String>) -> Self {
+        self.recipients = recipients;
+        self
+    }
+
+    pub(crate) fn meta(mut self, meta: HashMap<String, Value>) -> Self {
+        self.meta = meta;
+        self
+    }
+}
+
+/// How an operation ended, as post hooks see it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationOutcome {
+    /// Whether the node accepted the operation
+    pub success: bool,
+    /// Molecular hash of the proposed molecule, when the response has one
+    pub molecular_hash: Option<String>,
+    /// Rejection reason or error message of a failed operation
+    pub reason: Option<String>,
+}
+
+impl OperationOutcome {
+    /// Outcome of an operation's result
+    pub fn from_result(result: &Result<Box<dyn Response>>) -> Self {
+        match result {
+            Ok(response) => OperationOutcome {
+                success: response.success(),
+                molecular_hash: ResponseUtils::extract_molecular_hash(response.as_ref()),
+                reason: if response.success() { None } else { response.reason() },
+            },
+            Err(e) => OperationOutcome { success: false, molecular_hash: None, reason: Some(e.to_string()) },
+        }
+    }
+}
+
+/// Hook run before an operation; returns the context the operation proceeds with
+pub type PreHook = Arc<dyn Fn(OperationContext) -> BoxFuture<'static, Result<OperationContext>> + Send + Sync>;
+
+/// Hook run after an operation
+pub type PostHook = Arc<dyn Fn(OperationContext, OperationOutcome) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// Pre and post hooks registered per operation
+///
+/// A clone starts with the hooks registered so far; hooks registered on it later do not run
+/// on the original.
+#[derive(Clone, Default)]
+pub struct OperationHooks {
+    pre: HashMap<Operation, Vec<PreHook>>,
+    post: HashMap<Operation, Vec<PostHook>>,
+}
+
+impl fmt::Debug for OperationHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = |hooks: HashMap<Operation, usize>| {
+            let mut counts: Vec<_> = hooks.into_iter().collect();
+            counts.sort_by_key(|(operation, _)| operation.as_str());
+            counts
+        };
+        f.debug_struct("OperationHooks")
+            .field("pre", &counts(self.pre.iter().map(|(op, hooks)| (*op, hooks.len())).collect()))
+            .field("post", &counts(self.post.iter().map(|(op, hooks)| (*op, hooks.len())).collect()))
+            .finish()
+    }
+}
+
+impl OperationHooks {
+    /// Registry without hooks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a pre hook for `operation`
+    pub fn before<F, Fut>(&mut self, operation: Operation, hook: F)
+    where
+        F: Fn(OperationContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<OperationContext>> + Send + 'static,
+    {
+        let hook: PreHook = Arc::new(move |context| hook(context).boxed());
+        self.pre.entry(operation).or_default().push(hook);
+    }
+
+    /// Register a post hook for `operation`
+    pub fn after<F, Fut>(&mut self, operation: Operation, hook: F)
+    where
+        F: Fn(OperationContext, OperationOutcome) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let hook: PostHook = Arc::new(move |context, outcome| hook(context, outcome).boxed());
+        self.post.entry(operation).or_default().push(hook);
+    }
+
+    /// Drop every hook of `operation`
+    pub fn clear(&mut self, operation: Operation) {
+        self.pre.remove(&operation);
+        self.post.remove(&operation);
+    }
+
+    /// Whether any hook is registered
+    pub fn is_empty(&self) -> bool {
+        self.pre.values().all(Vec::is_empty) && self.post.values().all(Vec::is_empty)
+    }
+
+    /// Run the pre hooks of the context's operation in order
+    ///
+    /// # Errors
+    ///
+    /// The first error a hook returns; later hooks do not run
+    pub async fn run_before(&self, mut context: OperationContext) -> Result<OperationContext> {
+        let hooks = self.pre.get(&context.operation).cloned().unwrap_or_default();
+        for hook in hooks {
+            context = hook(context).await?;
+        }
+        Ok(context)
+    }
+
+    /// Run the post hooks of the context's operation in order
+    pub async fn run_after(&self, context: &OperationContext, outcome: &OperationOutcome) {
+        let hooks = self.post.get(&context.operation).cloned().unwrap_or_default();
+        for hook in hooks {
+            hook(context.clone(), outcome.clone()).await;
+        }
+    }
+}
+
+impl KnishIOClient {
+    /// Get the operation hooks
+    pub fn hooks(&self) -> &OperationHooks {
+        &self.hooks
+    }
+
+    /// Get the operation hooks for registering
+    pub fn hooks_mut(&mut self) -> &mut OperationHooks {
+        &mut self.hooks
+    }
+
+    /// Run `hook` before every `transfer_token` and `transfer_tokens`
+    ///
+    /// ```no_run
+    /// # fn demo(client: &mut knishio_client::KnishIOClient) {
+    /// use knishio_client::KnishIOError;
+    ///
+    /// // Hold transfers above 10,000 for compliance review
+    /// client.before_transfer(|context| async move {
+    ///     if context.amount.unwrap_or(0.0) > 10_000.0 {
+    ///         return Err(KnishIOError::OperationHeld(format!(
+    ///             "{} of {:?} awaits compliance review", context.operation, context.amount
+    ///         )));
+    ///     }
+    ///     Ok(context)
+    /// });
+    /// # }
+    /// ```
+    pub fn before_transfer<F, Fut>(&mut self, hook: F)
+    where
+        F: Fn(OperationContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<OperationContext>> + Send + 'static,
+    {
+        let hook = Arc::new(hook);
+        let single = hook.clone();
+        self.hooks.before(Operation::TransferToken, move |context| single(context));
+        self.hooks.before(Operation::TransferTokens, move |context| hook(context));
+    }
+
+    /// Run `hook` after every `transfer_token` and `transfer_tokens`
+    pub fn after_transfer<F, Fut>(&mut self, hook: F)
+    where
+        F: Fn(OperationContext, OperationOutcome) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let hook = Arc::new(hook);
+        let single = hook.clone();
+        self.hooks.after(Operation::TransferToken, move |context, outcome| single(context, outcome));
+        self.hooks.after(Operation::TransferTokens, move |context, outcome| hook(context, outcome));
+    }
+
+    /// Run `hook` before every `create_token`; it may add token metadata
+    ///
+    /// ```no_run
+    /// # fn demo(client: &mut knishio_client::KnishIOClient) {
+    /// // Stamp every new token with the issuing invoice
+    /// client.before_token_create(|mut context| async move {
+    ///     context.meta.insert("invoice".to_string(), serde_json::json!("INV-2026-0042"));
+    ///     Ok(context)
+    /// });
+    /// # }
+    /// ```
+    pub fn before_token_create<F, Fut>(&mut self, hook: F)
+    where
+        F: Fn(OperationContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<OperationContext>> + Send + 'static,
+    {
+        self.hooks.before(Operation::CreateToken, hook);
+    }
+
+    /// Run `hook` after every `create_token`
+    pub fn after_token_create<F, Fut>(&mut self, hook: F)
+    where
+        F: Fn(OperationContext, OperationOutcome) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.hooks.after(Operation::CreateToken, hook);
+    }
+
+    /// Run `hook` before every `create_meta`; it may add or change meta items
+    pub fn before_meta_create<F, Fut>(&mut self, hook: F)
+    where
+        F: Fn(OperationContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<OperationContext>> + Send + 'static,
+    {
+        self.hooks.before(Operation::CreateMeta, hook);
+    }
+
+    /// Run `hook` after every `create_meta`
+    pub fn after_meta_create<F, Fut>(&mut self, hook: F)
+    where
+        F: Fn(OperationContext, OperationOutcome) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.hooks.after(Operation::CreateMeta, hook);
+    }
+
+    /// Run the post hooks of `context` on `result` and hand the result back
+    pub(crate) async fn finish_operation(
+        &self,
+        context: OperationContext,
+        result: Result<Box<dyn Response>>,
+    ) -> Result<Box<dyn Response>> {
+        if self.hooks.post.get(&context.operation).is_some_and(|hooks| !hooks.is_empty()) {
+            self.hooks.run_after(&context, &OperationOutcome::from_result(&result)).await;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{generate_bundle_hash, generate_secret};
+    use crate::error::KnishIOError;
+    use crate::test_ledger::TestLedger;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_pre_hooks_chain_and_may_stop_the_operation() {
+        let mut hooks = OperationHooks::new();
+        hooks.before(Operation::CreateMeta, |mut context| async move {
+            context.meta.insert("invoice".to_string(), json!("INV-1"));
+            Ok(context)
+        });
+        hooks.before(Operation::CreateMeta, |context| async move {
+            match context.meta.get("invoice") {
+                Some(_) => Ok(context),
+                None => Err(KnishIOError::OperationHeld("no invoice".to_string())),
+            }
+        });
+        let context = hooks.run_before(OperationContext::new(Operation::CreateMeta)).await.unwrap();
+        assert_eq!(context.meta["invoice"], json!("INV-1"));
+        assert!(Operation::CreateMeta.writes_meta() && !Operation::TransferToken.writes_meta());
+
+        // Hooks of other operations do not run
+        let context = hooks.run_before(OperationContext::new(Operation::CreateToken)).await.unwrap();
+        assert!(context.meta.is_empty());
+
+        hooks.clear(Operation::CreateMeta);
+        assert!(hooks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_transfer_hooks() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("hooks-owner");
+        ledger.fund(&secret, "GOLD", 100.0).unwrap();
+        let mut client = ledger.client(&secret);
+        let recipient = generate_bundle_hash(&generate_secret("hooks-recipient"));
+
+        let outcomes = Arc::new(Mutex::new(Vec::new()));
+        let seen = outcomes.clone();
+        client.after_transfer(move |context, outcome| {
+            let seen = seen.clone();
+            async move { seen.lock().unwrap().push((context, outcome)) }
+        });
+        client.before_transfer(|context| async move {
+            if context.amount.unwrap_or(0.0) > 50.0 {
+                return Err(KnishIOError::OperationHeld(format!("{} awaits review", context.operation)));
+            }
+            Ok(context)
+        });
+
+        // A compliance hold stops the transfer before anything is proposed
+        let held = client.transfer_token(&recipient, "GOLD", Some(60.0), Vec::new(), None, None).await;
+        assert!(matches!(held, Err(KnishIOError::OperationHeld(ref reason)) if reason == "transfer_token awaits review"));
+        assert!(ledger.molecules().is_empty());
+        assert!(outcomes.lock().unwrap().is_empty());
+
+        let response = client.transfer_token(&recipient, "GOLD", Some(10.0), Vec::new(), None, None).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+
+        let outcomes = outcomes.lock().unwrap();
+        assert_eq!(outcomes.len(), 1);
+        let (context, outcome) = &outcomes[0];
+        assert_eq!(context.operation, Operation::TransferToken);
+        assert_eq!(context.token.as_deref(), Some("GOLD"));
+        assert_eq!(context.amount, Some(10.0));
+        assert_eq!(context.recipients, vec![recipient.clone()]);
+        assert!(outcome.success);
+        assert_eq!(outcome.molecular_hash.as_ref(), ledger.molecules().last().map(|m| &m.molecular_hash));
+    }
+}
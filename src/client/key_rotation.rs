@@ -0,0 +1,61 @@
+//! Node encryption key rotation
+//!
+//! Auth exchanges hand out the pubkey the node wants encrypted traffic keyed to, and
+//! `GraphQLClient` keeps the one from the session's last exchange. The client does not
+//! encrypt request bodies itself, so nothing it sends can fail on a stale key and there is
+//! no retry here. What it offers is picking up a rotated key: `refresh_encryption_key`
+//! runs a fresh auth exchange and reports whether the node handed out a different pubkey,
+//! for callers that encrypt payloads to `node_pubkey` and see the node refuse one.
+
+use crate::client::KnishIOClient;
+use crate::error::Result;
+
+impl KnishIOClient {
+    /// Fetch the node's current encryption pubkey with a fresh auth exchange
+    ///
+    /// Authenticates again the way the session did, with the stored secret or as a guest.
+    /// Returns whether the pubkey changed.
+    pub async fn refresh_encryption_key(&mut self) -> Result<bool> {
+        let previous = self.node_pubkey();
+        let secret = self.secret.clone();
+        let cell_slug = self.cell_slug.clone();
+        let encrypt = self.encrypt;
+        self.request_auth_token(secret.as_deref(), None, cell_slug.as_deref(), Some(encrypt)).await?;
+
+        let current = self.node_pubkey();
+        self.log("info", &format!(
+            "KnishIOClient::refresh_encryption_key() - Node pubkey {}",
+            if current == previous { "unchanged" } else { "rotated" }
+        ));
+        Ok(current != previous)
+    }
+
+    /// Node pubkey from the session's last auth exchange
+    pub fn node_pubkey(&self) -> Option<String> {
+        self.client.as_ref().and_then(|client| client.get_pubkey()).map(str::to_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::generate_secret;
+    use crate::test_ledger::TestLedger;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_rotated_key_is_refetched() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("key-rotation");
+        let mut client = ledger.client(&secret);
+        let first_key = ledger.rotate_node_key();
+        client.authenticate(HashMap::new()).await.unwrap();
+        assert_eq!(client.node_pubkey().as_deref(), Some(first_key.as_str()));
+        assert!(!client.refresh_encryption_key().await.unwrap());
+
+        // The session keeps the old key until it asks again
+        let second_key = ledger.rotate_node_key();
+        assert_eq!(client.node_pubkey().as_deref(), Some(first_key.as_str()));
+        assert!(client.refresh_encryption_key().await.unwrap());
+        assert_eq!(client.node_pubkey().as_deref(), Some(second_key.as_str()));
+    }
+}
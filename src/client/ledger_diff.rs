@@ -0,0 +1,440 @@
+//! Ledger snapshots and diffs
+//!
+//! Moving a bundle to another node version means showing that the new node holds the same
+//! data as the old one. `ledger_snapshot` records what a node reports for a bundle: the
+//! balance of each token and the latest value of every metadata key the bundle wrote.
+//! `LedgerSnapshot::diff` compares two snapshots, taken from two nodes or from one node at
+//! two times. Snapshots serialize, so the earlier side can be stored and diffed later.
+//!
+//! Balances are per token, summed exactly in decimal across the bundle's wallets of that
+//! token. A token missing on one side counts as a zero balance. A snapshot is taken from
+//! every meta atom of the bundle, page by page, and a balance, timestamp or meta item the
+//! node reports malformed fails it rather than being read as empty.
+
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::query::atom::QueryAtom;
+use crate::types::created_at_millis;
+use crate::wallet::Balance;
+use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// A bundle's balances and metadata as one node reported them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerSnapshot {
+    /// Node the snapshot was taken from
+    pub node: Option<String>,
+    /// Bundle hash
+    pub bundle: String,
+    /// When the snapshot was taken
+    pub taken_at: DateTime<Utc>,
+    /// Balance of each token
+    pub balances: BTreeMap<String, Balance>,
+    /// Latest value of each key, by meta type and meta ID
+    pub metas: BTreeMap<String, BTreeMap<String, BTreeMap<String, String>>>,
+}
+
+/// How an entry differs between two snapshots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A token whose balance differs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceChange {
+    pub token: String,
+    pub before: Balance,
+    pub after: Balance,
+}
+
+impl BalanceChange {
+    /// Balance gained (negative when lost); None when it overflows
+    pub fn delta(&self) -> Option<Balance> {
+        self.after.checked_sub(&self.before)
+    }
+}
+
+/// A metadata key whose value differs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetaChange {
+    pub meta_type: String,
+    pub meta_id: String,
+    pub key: String,
+    /// Value in the earlier snapshot; None if the key was not there
+    pub before: Option<String>,
+    /// Value in the later snapshot; None if the key is gone
+    pub after: Option<String>,
+}
+
+impl MetaChange {
+    pub fn kind(&self) -> ChangeKind {
+        match (&self.before, &self.after) {
+            (None, _) => ChangeKind::Added,
+            (_, None) => ChangeKind::Removed,
+            _ => ChangeKind::Changed,
+        }
+    }
+}
+
+/// Differences between two snapshots of a bundle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerDiff {
+    pub bundle: String,
+    /// Node of the earlier snapshot
+    pub before_node: Option<String>,
+    /// Node of the later snapshot
+    pub after_node: Option<String>,
+    /// Tokens whose balance differs, by token slug
+    pub balances: Vec<BalanceChange>,
+    /// Metadata keys added, removed or changed, by meta type, meta ID and key
+    pub metas: Vec<MetaChange>,
+}
+
+impl LedgerDiff {
+    /// True if both snapshots hold the same data
+    pub fn is_empty(&self) -> bool {
+        self.balances.is_empty() && self.metas.is_empty()
+    }
+
+    /// Metadata changes of one kind
+    pub fn metas_of(&self, kind: ChangeKind) -> impl Iterator<Item = &MetaChange> {
+        self.metas.iter().filter(move |change| change.kind() == kind)
+    }
+
+    /// One line per difference
+    pub fn summary(&self) -> String {
+        let mut lines = vec![format!(
+            "{} vs {}: {} balance and {} metadata differences",
+            self.before_node.as_deref().unwrap_or("(unknown)"),
+            self.after_node.as_deref().unwrap_or("(unknown)"),
+            self.balances.len(),
+            self.metas.len()
+        )];
+        for change in &self.balances {
+            lines.push(format!("  balance {}: {} -> {}", change.token, change.before, change.after));
+        }
+        for change in &self.metas {
+            lines.push(format!(
+                "  meta {}/{} {} {:?}: {:?} -> {:?}",
+                change.meta_type, change.meta_id, change.key, change.kind(), change.before, change.after
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+impl LedgerSnapshot {
+    /// What changed from this snapshot to `later`
+    ///
+    /// # Errors
+    ///
+    /// `Custom` if the snapshots are of different bundles
+    pub fn diff(&self, later: &LedgerSnapshot) -> Result<LedgerDiff> {
+        if self.bundle != later.bundle {
+            return Err(KnishIOError::custom(format!(
+                "Cannot diff snapshots of bundles {} and {}",
+                self.bundle, later.bundle
+            )));
+        }
+
+        let mut tokens: Vec<&String> = self.balances.keys().chain(later.balances.keys()).collect();
+        tokens.sort();
+        tokens.dedup();
+        let balances = tokens.into_iter()
+            .map(|token| BalanceChange {
+                token: token.clone(),
+                before: self.balances.get(token).copied().unwrap_or_default(),
+                after: later.balances.get(token).copied().unwrap_or_default(),
+            })
+            .filter(|change| change.before.cmp_amount(&change.after) != Ordering::Equal)
+            .collect();
+
+        let mut metas = Vec::new();
+        let empty = BTreeMap::new();
+        let mut meta_types: Vec<&String> = self.metas.keys().chain(later.metas.keys()).collect();
+        meta_types.sort();
+        meta_types.dedup();
+        for meta_type in meta_types {
+            let before_ids = self.metas.get(meta_type).unwrap_or(&empty);
+            let after_ids = later.metas.get(meta_type).unwrap_or(&empty);
+            let mut meta_ids: Vec<&String> = before_ids.keys().chain(after_ids.keys()).collect();
+            meta_ids.sort();
+            meta_ids.dedup();
+            for meta_id in meta_ids {
+                let before = before_ids.get(meta_id);
+                let after = after_ids.get(meta_id);
+                let mut keys: Vec<&String> = before.into_iter().chain(after).flat_map(|keys| keys.keys()).collect();
+                keys.sort();
+                keys.dedup();
+                for key in keys {
+                    let before = before.and_then(|keys| keys.get(key));
+                    let after = after.and_then(|keys| keys.get(key));
+                    if before != after {
+                        metas.push(MetaChange {
+                            meta_type: meta_type.clone(),
+                            meta_id: meta_id.clone(),
+                            key: key.clone(),
+                            before: before.cloned(),
+                            after: after.cloned(),
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(LedgerDiff {
+            bundle: self.bundle.clone(),
+            before_node: self.node.clone(),
+            after_node: later.node.clone(),
+            balances,
+            metas,
+        })
+    }
+}
+
+impl KnishIOClient {
+    /// Record the balances and metadata this node reports for a bundle
+    ///
+    /// Metadata is what the bundle's meta atoms wrote, the latest value of each key.
+    ///
+    /// # Arguments
+    ///
+    /// * `bundle_hash` - Bundle to record (defaults to the client's bundle)
+    ///
+    /// # Errors
+    ///
+    /// `ResponseShape` when the node reports a balance, `createdAt`, meta type or ID, or meta
+    /// item that cannot be read, and `InvalidAmount` when a token's balances overflow
+    pub async fn ledger_snapshot(&self, bundle_hash: Option<&str>) -> Result<LedgerSnapshot> {
+        let bundle = bundle_hash.or(self.bundle.as_deref())
+            .ok_or(KnishIOError::MissingBundle)?
+            .to_string();
+        let client = self.client.as_ref().ok_or(KnishIOError::NoClient)?;
+
+        let mut balances: BTreeMap<String, Balance> = BTreeMap::new();
+        for wallet in self.query_wallets(Some(&bundle), None).await? {
+            let amount = Balance::parse(&wallet.balance, wallet.token_units.len())
+                .ok_or_else(|| malformed("Wallet.balance", format!("{:?} is not a decimal", wallet.balance)))?;
+            let balance = balances.entry(wallet.token.clone()).or_default();
+            *balance = balance.checked_add(&amount)
+                .ok_or_else(|| KnishIOError::InvalidAmount(format!("balance of {} overflows", wallet.token)))?;
+        }
+
+        let query = QueryAtom::new().add_bundle_hash(bundle.as_str()).add_isotope("M");
+        let mut atoms = Vec::new();
+        for atom in query.fetch_all(client).await? {
+            let created_at = atom["createdAt"].as_str()
+                .ok_or_else(|| malformed("Atom.createdAt", atom["createdAt"].to_string()))
+                .and_then(|created_at| created_at_millis(created_at).map_err(|error| malformed("Atom.createdAt", error.to_string())))?;
+            atoms.push((created_at, atom));
+        }
+        // Later writes win; nodes do not promise to list atoms in order
+        atoms.sort_by_key(|(created_at, _)| *created_at);
+
+        let mut metas: BTreeMap<String, BTreeMap<String, BTreeMap<String, String>>> = BTreeMap::new();
+        for (_, atom) in atoms {
+            let meta_type = atom["metaType"].as_str().ok_or_else(|| malformed("Atom.metaType", atom["metaType"].to_string()))?;
+            let meta_id = atom["metaId"].as_str().ok_or_else(|| malformed("Atom.metaId", atom["metaId"].to_string()))?;
+            let keys = metas
+                .entry(meta_type.to_string())
+                .or_default()
+                .entry(meta_id.to_string())
+                .or_default();
+            for (key, value) in meta_items(&atom)? {
+                keys.insert(key, value);
+            }
+        }
+
+        let snapshot = LedgerSnapshot {
+            node: self.get_current_uri(),
+            bundle,
+            taken_at: Utc::now(),
+            balances,
+            metas,
+        };
+        self.log("info", &format!(
+            "KnishIOClient::ledger_snapshot() - {} tokens and {} meta types for bundle {}",
+            snapshot.balances.len(), snapshot.metas.len(), snapshot.bundle
+        ));
+        Ok(snapshot)
+    }
+
+    /// Compare a bundle on this node with the same bundle on `other`
+    ///
+    /// This node is the earlier side of the diff.
+    ///
+    /// ```no_run
+    /// # async fn demo(old: &knishio_client::KnishIOClient, new: &knishio_client::KnishIOClient) -> knishio_client::Result<()> {
+    /// let diff = old.diff_ledger(new, Some("0f3a...")).await?;
+    /// if !diff.is_empty() {
+    ///     println!("{}", diff.summary());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn diff_ledger(&self, other: &KnishIOClient, bundle_hash: Option<&str>) -> Result<LedgerDiff> {
+        let bundle = bundle_hash.or(self.bundle.as_deref()).ok_or(KnishIOError::MissingBundle)?;
+        let (before, after) = tokio::try_join!(self.ledger_snapshot(Some(bundle)), other.ledger_snapshot(Some(bundle)))?;
+        before.diff(&after)
+    }
+}
+
+/// Error for a field of the node's response that cannot be read
+fn malformed(path: &str, message: impl Into<String>) -> KnishIOError {
+    KnishIOError::ResponseShape { path: path.to_string(), message: message.into() }
+}
+
+/// Key and value of each meta item of an atom; items with a null value are skipped
+fn meta_items(atom: &Value) -> Result<Vec<(String, String)>> {
+    let metas: Value = match &atom["metasJson"] {
+        Value::String(json) => serde_json::from_str(json).map_err(|error| malformed("Atom.metasJson", error.to_string()))?,
+        Value::Null => atom["metas"].clone(),
+        other => return Err(malformed("Atom.metasJson", other.to_string())),
+    };
+    let items = match metas {
+        Value::Array(items) => items,
+        Value::Null => Vec::new(),
+        other => return Err(malformed("Atom.metas", other.to_string())),
+    };
+
+    let mut pairs = Vec::new();
+    for item in items {
+        let key = item["key"].as_str().ok_or_else(|| malformed("Atom.metas.key", item.to_string()))?;
+        let value = match &item["value"] {
+            Value::String(value) => value.clone(),
+            Value::Null => continue,
+            value => value.to_string(),
+        };
+        pairs.push((key.to_string(), value));
+    }
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{generate_bundle_hash, generate_secret};
+    use crate::query::atom::ATOM_PAGE_SIZE;
+    use crate::test_ledger::TestLedger;
+    use crate::types::MetaItem;
+
+    async fn write(client: &mut KnishIOClient, meta_id: &str, items: Vec<MetaItem>) {
+        let report = client.create_meta_bulk("profile", meta_id, items, 10).await.unwrap();
+        assert!(report.is_complete(), "{:?}", report.chunks);
+    }
+
+    #[tokio::test]
+    async fn test_diff_between_nodes() {
+        let secret = generate_secret("migrating-bundle");
+        let bundle = generate_bundle_hash(&secret);
+        let old = TestLedger::start().await.unwrap();
+        let new = TestLedger::start().await.unwrap();
+        let mut old_client = old.client(&secret);
+        let mut new_client = new.client(&secret);
+
+        for (ledger, client) in [(&old, &mut old_client), (&new, &mut new_client)] {
+            ledger.fund(&secret, "GOLD", 10.0).unwrap();
+            write(client, "alice", vec![MetaItem::new("name", "Alice"), MetaItem::new("city", "Oslo")]).await;
+        }
+        assert!(old_client.diff_ledger(&new_client, None).await.unwrap().is_empty());
+
+        // The new node lost a key, changed another and gained a token and an instance
+        old.fund(&secret, "SILVER", 2.0).unwrap();
+        write(&mut new_client, "alice", vec![MetaItem::new("city", "Bergen")]).await;
+        write(&mut new_client, "bob", vec![MetaItem::new("name", "Bob")]).await;
+        write(&mut old_client, "alice", vec![MetaItem::new("email", "a@example.com")]).await;
+        new.fund(&secret, "GOLD", 1.5).unwrap();
+
+        let diff = old_client.diff_ledger(&new_client, Some(&bundle)).await.unwrap();
+        assert_eq!(diff.before_node, old_client.get_current_uri());
+        let balances: Vec<_> = diff.balances.iter()
+            .map(|change| (change.token.as_str(), change.before.to_string(), change.after.to_string(), change.delta().unwrap().to_string()))
+            .collect();
+        assert_eq!(balances, vec![
+            ("GOLD", "10".to_string(), "11.5".to_string(), "1.5".to_string()),
+            ("SILVER", "2".to_string(), "0".to_string(), "-2".to_string()),
+        ]);
+
+        let changes: Vec<_> = diff.metas.iter()
+            .map(|change| (change.meta_id.as_str(), change.key.as_str(), change.kind()))
+            .collect();
+        assert_eq!(changes, vec![
+            ("alice", "city", ChangeKind::Changed),
+            ("alice", "email", ChangeKind::Removed),
+            ("bob", "name", ChangeKind::Added),
+        ]);
+        assert_eq!(diff.metas[0].after.as_deref(), Some("Bergen"));
+        assert_eq!(diff.metas_of(ChangeKind::Added).count(), 1);
+        assert_eq!(diff.summary().lines().count(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_diff_over_time() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("audited-bundle");
+        ledger.fund(&secret, "GOLD", 3.0).unwrap();
+        let mut client = ledger.client(&secret);
+        write(&mut client, "carol", vec![MetaItem::new("tier", "silver")]).await;
+
+        // Stored and reloaded, as between two runs of a migration check
+        let stored = serde_json::to_string(&client.ledger_snapshot(None).await.unwrap()).unwrap();
+        let before: LedgerSnapshot = serde_json::from_str(&stored).unwrap();
+
+        write(&mut client, "carol", vec![MetaItem::new("tier", "gold")]).await;
+        let diff = before.diff(&client.ledger_snapshot(None).await.unwrap()).unwrap();
+        assert!(diff.balances.is_empty());
+        assert_eq!(diff.metas.len(), 1);
+        assert_eq!((diff.metas[0].before.as_deref(), diff.metas[0].after.as_deref()), (Some("silver"), Some("gold")));
+
+        let other = LedgerSnapshot { bundle: "other".to_string(), ..before };
+        assert!(other.diff(&client.ledger_snapshot(None).await.unwrap()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_reads_every_page() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("prolific-bundle");
+        let mut client = ledger.client(&secret);
+        let items: Vec<_> = (0..=ATOM_PAGE_SIZE).map(|n| MetaItem::new(format!("key{}", n), "value")).collect();
+        let report = client.create_meta_bulk("profile", "dave", items, 1).await.unwrap();
+        assert!(report.is_complete(), "{:?}", report.chunks);
+
+        let snapshot = client.ledger_snapshot(None).await.unwrap();
+        assert_eq!(snapshot.metas["profile"]["dave"].len(), ATOM_PAGE_SIZE + 1);
+    }
+
+    #[test]
+    fn test_balances_compare_exactly() {
+        let snapshot = |balance: &str| LedgerSnapshot {
+            node: None,
+            bundle: "bundle".to_string(),
+            taken_at: Utc::now(),
+            balances: BTreeMap::from([("GOLD".to_string(), Balance::parse(balance, 0).unwrap())]),
+            metas: BTreeMap::new(),
+        };
+        assert!(snapshot("1.50").diff(&snapshot("1.5")).unwrap().is_empty());
+        // One apart beyond f64's integer precision
+        let diff = snapshot("9007199254740993").diff(&snapshot("9007199254740992")).unwrap();
+        assert_eq!(diff.balances[0].delta().unwrap().to_string(), "-1");
+    }
+
+    #[test]
+    fn test_malformed_meta_items_fail() {
+        let atom = serde_json::json!({ "metasJson": "[{\"key\":\"name\",\"value\":\"Alice\"},{\"key\":\"gone\",\"value\":null}]" });
+        assert_eq!(meta_items(&atom).unwrap(), vec![("name".to_string(), "Alice".to_string())]);
+        for atom in [
+            serde_json::json!({ "metasJson": "not json" }),
+            serde_json::json!({ "metasJson": 5 }),
+            serde_json::json!({ "metas": [{ "value": "keyless" }] }),
+        ] {
+            assert!(matches!(meta_items(&atom), Err(KnishIOError::ResponseShape { .. })), "{}", atom);
+        }
+    }
+}
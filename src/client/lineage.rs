@@ -0,0 +1,378 @@
+//! Stackable batch lineage
+//!
+//! Every split or transfer of a stackable token moves units from one batch into a new
+//! one. `BatchHistory` returns the records of a single batch, whose `fromWallet` and
+//! `toWallet` name the neighbouring batches. `KnishIOClient::query_batch_lineage` follows
+//! those links in both directions and assembles the result into a tree rooted at the
+//! earliest ancestor it reaches.
+
+use crate::client::KnishIOClient;
+use crate::error::Result;
+use crate::response::deserialize_optional_amount;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Maximum number of batches `query_batch_lineage` queries before giving up
+pub const MAX_LINEAGE_BATCHES: usize = 256;
+
+/// Wallet side of a batch history record
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchWalletRef {
+    /// Wallet address
+    pub address: Option<String>,
+    /// Bundle owning the wallet
+    pub bundle_hash: Option<String>,
+    /// Wallet amount
+    #[serde(default, deserialize_with = "deserialize_optional_amount")]
+    pub amount: Option<String>,
+    /// Batch the wallet holds
+    pub batch_id: Option<String>,
+}
+
+/// One `BatchHistory` record
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchRecord {
+    /// Batch the record belongs to
+    pub batch_id: String,
+    /// Molecule that produced the record
+    pub molecular_hash: Option<String>,
+    /// Record type (e.g. transfer)
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    /// Record status
+    pub status: Option<String>,
+    /// Creation time
+    #[serde(default, deserialize_with = "deserialize_optional_amount")]
+    pub created_at: Option<String>,
+    /// Wallet holding the batch
+    pub wallet: Option<BatchWalletRef>,
+    /// Wallet the units came from
+    pub from_wallet: Option<BatchWalletRef>,
+    /// Wallet the units went to
+    pub to_wallet: Option<BatchWalletRef>,
+}
+
+impl BatchRecord {
+    /// Batch the units came from, if it is not this one
+    pub fn parent_batch_id(&self) -> Option<&str> {
+        self.from_wallet.as_ref()
+            .and_then(|w| w.batch_id.as_deref())
+            .filter(|id| *id != self.batch_id)
+    }
+
+    /// Batch the units went to, if it is not this one
+    pub fn child_batch_id(&self) -> Option<&str> {
+        self.to_wallet.as_ref()
+            .and_then(|w| w.batch_id.as_deref())
+            .filter(|id| *id != self.batch_id)
+    }
+}
+
+/// The split or transfer that created a batch out of its parent
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchHop {
+    /// Batch the units came from
+    pub parent_batch_id: String,
+    /// Molecule that moved them
+    pub molecular_hash: Option<String>,
+    /// Record type (e.g. transfer)
+    pub kind: Option<String>,
+    /// Amount received by the new batch
+    pub amount: Option<String>,
+    /// Bundle receiving the new batch
+    pub bundle_hash: Option<String>,
+    /// When the hop happened
+    pub created_at: Option<String>,
+}
+
+/// A batch and everything split or transferred out of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchLineageNode {
+    /// Batch ID
+    pub batch_id: String,
+    /// How this batch was created (None for the root)
+    pub hop: Option<BatchHop>,
+    /// History records of this batch
+    pub history: Vec<BatchRecord>,
+    /// Batches created out of this one
+    pub children: Vec<BatchLineageNode>,
+}
+
+impl BatchLineageNode {
+    /// Bundle holding this batch: the hop's recipient, else the batch's own wallet
+    pub fn bundle_hash(&self) -> Option<&str> {
+        self.hop.as_ref().and_then(|h| h.bundle_hash.as_deref())
+            .or_else(|| self.wallet().and_then(|w| w.bundle_hash.as_deref()))
+    }
+
+    /// Amount in this batch: the hop's amount, else the batch's own wallet amount
+    pub fn amount(&self) -> Option<&str> {
+        self.hop.as_ref().and_then(|h| h.amount.as_deref())
+            .or_else(|| self.wallet().and_then(|w| w.amount.as_deref()))
+    }
+
+    /// Node for `batch_id` in this subtree
+    pub fn find(&self, batch_id: &str) -> Option<&BatchLineageNode> {
+        if self.batch_id == batch_id {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(batch_id))
+    }
+
+    /// Number of batches in this subtree
+    pub fn len(&self) -> usize {
+        1 + self.children.iter().map(BatchLineageNode::len).sum::<usize>()
+    }
+
+    /// Always false: a subtree contains at least its own batch
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    fn wallet(&self) -> Option<&BatchWalletRef> {
+        self.history.iter().rev().find_map(|r| r.wallet.as_ref())
+    }
+
+    fn path_to<'a>(&'a self, batch_id: &str, path: &mut Vec<&'a BatchLineageNode>) -> bool {
+        path.push(self);
+        if self.batch_id == batch_id || self.children.iter().any(|c| c.path_to(batch_id, path)) {
+            return true;
+        }
+        path.pop();
+        false
+    }
+}
+
+/// Lineage of a stackable batch
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchLineage {
+    /// Batch the walk started from
+    pub batch_id: String,
+    /// Earliest ancestor reached, with all known descendants
+    pub root: BatchLineageNode,
+    /// True if the walk stopped at the batch limit, leaving the tree incomplete
+    pub truncated: bool,
+}
+
+impl BatchLineage {
+    /// Node of the queried batch
+    pub fn node(&self) -> Option<&BatchLineageNode> {
+        self.root.find(&self.batch_id)
+    }
+
+    /// Chain of batches from the root down to `batch_id` (empty if it is not in the tree)
+    pub fn path_to(&self, batch_id: &str) -> Vec<&BatchLineageNode> {
+        let mut path = Vec::new();
+        self.root.path_to(batch_id, &mut path);
+        path
+    }
+
+    /// Walk the history links from `batch_id`, fetching at most `limit` batches
+    pub(crate) async fn walk<F>(batch_id: &str, limit: usize, fetch: F) -> Result<Self>
+    where
+        F: AsyncFn(&str) -> Result<Vec<Value>>,
+    {
+        let mut history: HashMap<String, Vec<BatchRecord>> = HashMap::new();
+        let mut hops: HashMap<String, BatchHop> = HashMap::new();
+        let mut children: HashMap<String, Vec<String>> = HashMap::new();
+        let mut seen = HashSet::from([batch_id.to_string()]);
+        let mut queue = VecDeque::from([batch_id.to_string()]);
+        let mut fetched = 0;
+
+        while let Some(current) = queue.pop_front() {
+            if fetched >= limit.max(1) {
+                queue.push_front(current);
+                break;
+            }
+            fetched += 1;
+
+            let records: Vec<BatchRecord> = fetch(&current).await?
+                .into_iter()
+                .filter_map(|record| BatchRecord::deserialize(record).ok())
+                .collect();
+
+            for record in records {
+                let edges = [
+                    record.parent_batch_id().map(|parent| (parent.to_string(), record.batch_id.clone())),
+                    record.child_batch_id().map(|child| (record.batch_id.clone(), child.to_string())),
+                ];
+                for (parent, child) in edges.into_iter().flatten() {
+                    if !hops.contains_key(&child) {
+                        // A record of the new batch holds it in `wallet`; a record of the
+                        // parent names it in `toWallet`
+                        let recipient = if child == record.batch_id {
+                            record.wallet.as_ref()
+                        } else {
+                            record.to_wallet.as_ref()
+                        };
+                        hops.insert(child.clone(), BatchHop {
+                            parent_batch_id: parent.clone(),
+                            molecular_hash: record.molecular_hash.clone(),
+                            kind: record.kind.clone(),
+                            amount: recipient.and_then(|w| w.amount.clone()),
+                            bundle_hash: recipient.and_then(|w| w.bundle_hash.clone()),
+                            created_at: record.created_at.clone(),
+                        });
+                        children.entry(parent.clone()).or_default().push(child.clone());
+                    }
+                    for id in [parent, child] {
+                        if seen.insert(id.clone()) {
+                            queue.push_back(id);
+                        }
+                    }
+                }
+
+                let entries = history.entry(record.batch_id.clone()).or_default();
+                if !entries.contains(&record) {
+                    entries.push(record);
+                }
+            }
+        }
+
+        // Climb to the earliest ancestor, guarding against cyclic data
+        let mut root = batch_id.to_string();
+        let mut climbed = HashSet::from([root.clone()]);
+        while let Some(hop) = hops.get(&root) {
+            if !climbed.insert(hop.parent_batch_id.clone()) {
+                break;
+            }
+            root = hop.parent_batch_id.clone();
+        }
+
+        let mut placed = HashSet::new();
+        let root = build_node(&root, &mut hops, &mut history, &children, &mut placed);
+
+        Ok(BatchLineage {
+            batch_id: batch_id.to_string(),
+            root,
+            truncated: !queue.is_empty(),
+        })
+    }
+}
+
+fn build_node(
+    batch_id: &str,
+    hops: &mut HashMap<String, BatchHop>,
+    history: &mut HashMap<String, Vec<BatchRecord>>,
+    children: &HashMap<String, Vec<String>>,
+    placed: &mut HashSet<String>,
+) -> BatchLineageNode {
+    placed.insert(batch_id.to_string());
+
+    let mut node = BatchLineageNode {
+        batch_id: batch_id.to_string(),
+        hop: None,
+        history: history.remove(batch_id).unwrap_or_default(),
+        children: Vec::new(),
+    };
+    for child in children.get(batch_id).into_iter().flatten() {
+        if !placed.contains(child) {
+            let mut child_node = build_node(child, hops, history, children, placed);
+            child_node.hop = hops.remove(child);
+            node.children.push(child_node);
+        }
+    }
+    node
+}
+
+impl KnishIOClient {
+    /// Trace where a stackable batch came from and where its units went
+    ///
+    /// Follows `BatchHistory` links from `batch_id` up to its earliest ancestor and down
+    /// to every batch split or transferred out of it, querying at most
+    /// `MAX_LINEAGE_BATCHES` batches.
+    ///
+    /// # Returns
+    ///
+    /// The lineage tree; `truncated` is set if the limit cut the walk short
+    pub async fn query_batch_lineage(&self, batch_id: &str) -> Result<BatchLineage> {
+        let lineage = BatchLineage::walk(batch_id, MAX_LINEAGE_BATCHES, async |id: &str| {
+            self.query_batch_history(id).await
+        }).await?;
+
+        self.log("info", &format!(
+            "KnishIOClient::query_batch_lineage() - {} batches under {}{}",
+            lineage.root.len(),
+            lineage.root.batch_id,
+            if lineage.truncated { " (truncated)" } else { "" }
+        ));
+
+        Ok(lineage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record(batch: &str, from: Option<&str>, to: Option<&str>, bundle: &str, amount: u64) -> Value {
+        json!({
+            "batchId": batch,
+            "molecularHash": format!("mol-{}", batch),
+            "type": "transfer",
+            "wallet": to.is_none().then(|| json!({ "bundleHash": bundle, "amount": amount.to_string(), "batchId": batch })),
+            "fromWallet": from.map(|id| json!({ "bundleHash": "origin", "amount": "0", "batchId": id })),
+            "toWallet": to.map(|id| json!({ "bundleHash": bundle, "amount": amount, "batchId": id })),
+        })
+    }
+
+    /// root -> a -> a1, root -> b; each batch only reports its own records
+    fn fixture() -> HashMap<&'static str, Vec<Value>> {
+        HashMap::from([
+            ("root", vec![
+                record("root", None, None, "farm", 100),
+                record("root", Some("root"), Some("a"), "mill", 60),
+                record("root", Some("root"), Some("b"), "shop", 40),
+            ]),
+            ("a", vec![record("a", Some("root"), None, "mill", 60), record("a", Some("a"), Some("a1"), "bakery", 25)]),
+            ("a1", vec![record("a1", Some("a"), None, "bakery", 25)]),
+            ("b", vec![record("b", Some("root"), None, "shop", 40)]),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_lineage_walks_up_and_down() {
+        let history = fixture();
+        let fetch = async |id: &str| Ok(history.get(id).cloned().unwrap_or_default());
+
+        let lineage = BatchLineage::walk("a", 16, fetch).await.unwrap();
+        assert!(!lineage.truncated);
+        assert_eq!(lineage.root.batch_id, "root");
+        assert_eq!(lineage.root.len(), 4);
+        assert!(lineage.root.hop.is_none());
+        assert_eq!(lineage.root.amount(), Some("100"));
+
+        let path: Vec<&str> = lineage.path_to("a1").iter().map(|n| n.batch_id.as_str()).collect();
+        assert_eq!(path, ["root", "a", "a1"]);
+
+        let a1 = lineage.root.find("a1").unwrap();
+        let hop = a1.hop.as_ref().unwrap();
+        assert_eq!(hop.parent_batch_id, "a");
+        assert_eq!(hop.molecular_hash.as_deref(), Some("mol-a"));
+        assert_eq!(a1.bundle_hash(), Some("bakery"));
+        assert_eq!(a1.amount(), Some("25"));
+        assert_eq!(lineage.node().unwrap().children.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_lineage_stops_at_limit_and_survives_cycles() {
+        let history = fixture();
+        let fetch = async |id: &str| Ok(history.get(id).cloned().unwrap_or_default());
+        let lineage = BatchLineage::walk("a1", 2, fetch).await.unwrap();
+        assert!(lineage.truncated);
+        assert_eq!(lineage.root.batch_id, "root");
+        assert!(lineage.root.find("b").is_none());
+
+        let cyclic = HashMap::from([
+            ("x", vec![record("x", Some("y"), Some("y"), "p", 1)]),
+            ("y", vec![record("y", Some("x"), Some("x"), "q", 1)]),
+        ]);
+        let fetch = async |id: &str| Ok(cyclic.get(id).cloned().unwrap_or_default());
+        let lineage = BatchLineage::walk("x", 16, fetch).await.unwrap();
+        assert_eq!(lineage.root.len(), 2);
+    }
+}
@@ -0,0 +1,81 @@
+//! Meta queries for large binary values
+//!
+//! `query_meta` hands back a clone of the response data, and `query_meta_as` copies each
+//! value again into the caller's type. For base64 payloads of several megabytes that is
+//! wasteful; `query_meta_blobs` moves every value string out of the response into a
+//! `MetaBlob` and leaves decoding to the caller.
+
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::query::meta_type::QueryMetaType;
+use crate::query::Query;
+use crate::response::{take_meta_blobs, MetaBlob};
+use std::collections::HashMap;
+
+impl KnishIOClient {
+    /// Latest meta values of one meta instance as bytes-backed blobs, keyed by meta key
+    ///
+    /// ```no_run
+    /// # async fn demo(client: &knishio_client::KnishIOClient) -> knishio_client::Result<()> {
+    /// let blobs = client.query_meta_blobs("document", "contract-7").await?;
+    /// if let Some(file) = blobs.get("file") {
+    ///     let pdf = file.decoded()?;
+    ///     println!("{} bytes", pdf.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn query_meta_blobs(&self, meta_type: &str, meta_id: &str) -> Result<HashMap<String, MetaBlob>> {
+        let mut query = QueryMetaType::new()
+            .with_meta_type(meta_type)
+            .with_meta_id(meta_id);
+        if let Some(ref cell) = self.cell_slug {
+            query = query.with_cell_slug(cell);
+        }
+
+        let client = self.client.as_ref().ok_or(KnishIOError::NoClient)?;
+        let mut data = query.execute(client, None, None).await?.into_data();
+        if let Some(meta_type) = data.get_mut("MetaType") {
+            data = meta_type.take();
+        }
+
+        let blobs = take_meta_blobs(data, meta_id);
+        let large = blobs.values().filter(|blob| blob.is_large()).count();
+        if large > 0 {
+            self.log("info", &format!(
+                "KnishIOClient::query_meta_blobs() - {} large values on {}/{}",
+                large, meta_type, meta_id
+            ));
+        }
+
+        Ok(blobs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::generate_secret;
+    use crate::response::LARGE_META_THRESHOLD;
+    use crate::test_ledger::TestLedger;
+    use crate::types::MetaItem;
+    use base64::Engine as _;
+
+    #[tokio::test]
+    async fn test_query_meta_blobs() {
+        let ledger = TestLedger::start().await.unwrap();
+        let mut client = ledger.client(&generate_secret("meta-blob-owner"));
+
+        let payload: Vec<u8> = (0..LARGE_META_THRESHOLD * 2).map(|i| (i % 251) as u8).collect();
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&payload);
+        let meta = vec![MetaItem::new("file", encoded), MetaItem::new("name", "scan.bin")];
+        let report = client.create_meta_bulk("document", "doc-1", meta, 10).await.unwrap();
+        assert!(report.is_complete(), "{:?}", report.chunks);
+
+        let blobs = client.query_meta_blobs("document", "doc-1").await.unwrap();
+        assert!(blobs["file"].is_large());
+        assert_eq!(&blobs["file"].decoded().unwrap()[..], &payload[..]);
+        assert_eq!(blobs["name"].as_str(), "scan.bin");
+
+        assert!(client.query_meta_blobs("document", "missing").await.unwrap().is_empty());
+    }
+}
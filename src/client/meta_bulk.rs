@@ -0,0 +1,190 @@
+//! Chunked metadata writes
+//!
+//! Nodes cap the size of a molecule, so thousands of meta items for one instance cannot
+//! go out in a single M-atom. `create_meta_bulk` splits the items into chunks of
+//! `chunk_size` and proposes one molecule per chunk, each carrying its own ContinuID
+//! atom. Chunks are proposed one after another and every molecule is built on the
+//! bundle's ContinuID head as the node reports it, so the relay stays unbroken even when
+//! a chunk is rejected. Failed chunks are recorded in the report and can be retried with
+//! the items of their range.
+
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::graphql::{RequestOptions, RequestPriority};
+use crate::meta::SCHEMA_VERSION_KEY;
+use crate::mutation::propose_molecule::MutationProposeMolecule;
+use crate::mutation::Mutation;
+use crate::types::MetaItem;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Outcome of writing one chunk
+#[derive(Debug)]
+pub struct MetaChunkOutcome {
+    /// Position of the chunk
+    pub chunk: usize,
+    /// Indices of the input items carried by the chunk
+    pub items: Range<usize>,
+    /// Molecular hash of the accepted molecule, or why the chunk failed
+    pub result: Result<Option<String>>,
+}
+
+/// Result of a chunked metadata write
+#[derive(Debug)]
+pub struct MetaBulkReport {
+    /// Meta type written
+    pub meta_type: String,
+    /// Meta ID written
+    pub meta_id: String,
+    /// One outcome per chunk, in input order
+    pub chunks: Vec<MetaChunkOutcome>,
+}
+
+impl MetaBulkReport {
+    /// True when every chunk was accepted
+    pub fn is_complete(&self) -> bool {
+        self.chunks.iter().all(|c| c.result.is_ok())
+    }
+
+    /// Number of failed chunks
+    pub fn failure_count(&self) -> usize {
+        self.chunks.iter().filter(|c| c.result.is_err()).count()
+    }
+
+    /// Item ranges of the failed chunks, for a retry
+    pub fn failed_items(&self) -> Vec<Range<usize>> {
+        self.chunks.iter().filter(|c| c.result.is_err()).map(|c| c.items.clone()).collect()
+    }
+}
+
+impl KnishIOClient {
+    /// Write `items` to one meta instance, `chunk_size` items per molecule
+    ///
+    /// Registered meta types get their schema version stamp in every chunk (it counts
+    /// towards the chunk size), unless the items already carry one. A failed chunk does
+    /// not stop the remaining ones.
+    ///
+    /// ```no_run
+    /// # async fn demo(client: &mut knishio_client::KnishIOClient) -> knishio_client::Result<()> {
+    /// use knishio_client::MetaItem;
+    ///
+    /// let items: Vec<MetaItem> = (0..5000).map(|i| MetaItem::new(format!("sku{}", i), "in stock")).collect();
+    /// let report = client.create_meta_bulk("inventory", "warehouse-1", items, 200).await?;
+    /// println!("{} chunks, {} failed", report.chunks.len(), report.failure_count());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigurationError` if `chunk_size` is 0; per-chunk failures are in the report
+    pub async fn create_meta_bulk(
+        &mut self,
+        meta_type: &str,
+        meta_id: &str,
+        items: Vec<MetaItem>,
+        chunk_size: usize,
+    ) -> Result<MetaBulkReport> {
+        if chunk_size == 0 {
+            return Err(KnishIOError::ConfigurationError("create_meta_bulk chunk size must be at least 1".to_string()));
+        }
+
+        let stamp = if items.iter().any(|item| item.key == SCHEMA_VERSION_KEY) {
+            None
+        } else {
+            self.schema_stamp(meta_type)
+        };
+        let per_chunk = if stamp.is_some() { chunk_size.saturating_sub(1).max(1) } else { chunk_size };
+
+        let mut chunks = Vec::with_capacity(items.len().div_ceil(per_chunk));
+        for (chunk, batch) in items.chunks(per_chunk).enumerate() {
+            let start = chunk * per_chunk;
+            let mut meta = batch.to_vec();
+            meta.extend(stamp.clone());
+
+            let result = self.write_meta_chunk(meta_type, meta_id, meta).await;
+            if let Err(ref e) = result {
+                self.log("warn", &format!("KnishIOClient::create_meta_bulk() - Chunk {} of {} {} failed: {}",
+                    chunk, meta_type, meta_id, e));
+            }
+            chunks.push(MetaChunkOutcome { chunk, items: start..start + batch.len(), result });
+        }
+
+        let report = MetaBulkReport { meta_type: meta_type.to_string(), meta_id: meta_id.to_string(), chunks };
+        self.log("info", &format!(
+            "KnishIOClient::create_meta_bulk() - {} of {} chunks written for {} {}",
+            report.chunks.len() - report.failure_count(),
+            report.chunks.len(),
+            meta_type,
+            meta_id
+        ));
+
+        Ok(report)
+    }
+
+    /// Schema version item of a registered `meta_type`
+    pub(crate) fn schema_stamp(&self, meta_type: &str) -> Option<MetaItem> {
+        let mut stamped = HashMap::new();
+        if let Some(ref registry) = self.schema_registry {
+            registry.stamp(meta_type, &mut stamped);
+        }
+        stamped.remove(SCHEMA_VERSION_KEY).map(|version| MetaItem::new(SCHEMA_VERSION_KEY, version.to_string()))
+    }
+
+    /// Propose one M-atom molecule on the current ContinuID head
+    async fn write_meta_chunk(&mut self, meta_type: &str, meta_id: &str, meta: Vec<MetaItem>) -> Result<Option<String>> {
+        let mut molecule = self.create_molecule(None, None, None, None).await?;
+        molecule.init_meta(meta, meta_type, meta_id, None)?;
+        molecule.sign(None, false, true)?;
+        molecule.check(None)?;
+
+        let client = self.client_with(RequestOptions::new().priority(RequestPriority::Background))?;
+        let response = MutationProposeMolecule::from_molecule(molecule).execute(&client, None, None).await?;
+        if !response.success() {
+            return Err(KnishIOError::custom(format!(
+                "Meta chunk rejected: {}",
+                response.reason().unwrap_or_else(|| "unknown reason".to_string())
+            )));
+        }
+
+        Ok(response.get("molecularHash").and_then(|h| h.as_str()).map(str::to_string))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::{generate_bundle_hash, generate_secret};
+    use crate::meta::{MetaSchema, SchemaRegistry};
+    use crate::test_ledger::TestLedger;
+    use crate::types::MetaItem;
+
+    #[tokio::test]
+    async fn test_meta_is_written_in_chunks() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("meta-bulk-owner");
+        let mut client = ledger.client(&secret);
+
+        let items: Vec<MetaItem> = (0..7).map(|i| MetaItem::new(format!("key{}", i), format!("value{}", i))).collect();
+        assert!(client.create_meta_bulk("inventory", "W1", items.clone(), 0).await.is_err());
+
+        let report = client.create_meta_bulk("inventory", "W1", items.clone(), 3).await.unwrap();
+        assert!(report.is_complete(), "{:?}", report.chunks);
+        let ranges: Vec<_> = report.chunks.iter().map(|c| c.items.clone()).collect();
+        assert_eq!(ranges, vec![0..3, 3..6, 6..7]);
+
+        // Every chunk landed on the ContinuID left by the previous one
+        let molecules = ledger.molecules();
+        assert_eq!(molecules.len(), 3);
+        assert!(molecules.iter().all(|m| m.accepted()));
+        assert!(ledger.continu_id(&generate_bundle_hash(&secret)).is_some());
+
+        // A registered type's stamp takes one slot per chunk
+        let mut registry = SchemaRegistry::new();
+        registry.register(MetaSchema::new("inventory", 1)).unwrap();
+        client.set_schema_registry(registry);
+        let report = client.create_meta_bulk("inventory", "W2", items, 4).await.unwrap();
+        assert!(report.is_complete(), "{:?}", report.chunks);
+        assert_eq!(report.chunks.len(), 3);
+        assert_eq!(report.failed_items(), Vec::new());
+    }
+}
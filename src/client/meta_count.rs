@@ -0,0 +1,138 @@
+//! Meta instance counting
+//!
+//! The `MetaType` query can count instances instead of returning them: the paginator's
+//! `total` is the number of matching instances, and with `countBy` set the node groups
+//! them by the latest value of a meta key and reports each group's size in
+//! `instanceCount`. `count_meta` and `count_meta_by` wrap both and return plain numbers.
+
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::query::meta_type::{MetaFilter, QueryMetaType};
+use crate::query::Query;
+use serde_json::{json, Value};
+
+/// Number of instances sharing one value of the `countBy` key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetaCount {
+    /// Value of the counted key
+    pub value: String,
+    /// Instances holding it
+    pub count: u64,
+}
+
+impl KnishIOClient {
+    /// Number of `meta_type` instances matching every filter
+    ///
+    /// ```no_run
+    /// # async fn demo(client: &knishio_client::KnishIOClient) -> knishio_client::Result<()> {
+    /// use knishio_client::query::MetaFilter;
+    ///
+    /// let open = client.count_meta("ticket", &[MetaFilter::eq("status", "open")]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn count_meta(&self, meta_type: &str, filters: &[MetaFilter]) -> Result<u64> {
+        let entry = self.query_meta_counts(meta_type, filters, None).await?;
+        Ok(entry.as_ref().map_or(0, |entry| count_of(&entry["paginatorInfo"]["total"])))
+    }
+
+    /// Matching `meta_type` instances grouped by the latest value of `key`
+    ///
+    /// Groups come largest first, ties ordered by value; instances without `key` are not
+    /// counted.
+    pub async fn count_meta_by(&self, meta_type: &str, key: &str, filters: &[MetaFilter]) -> Result<Vec<MetaCount>> {
+        let entry = self.query_meta_counts(meta_type, filters, Some(key)).await?;
+
+        let mut counts: Vec<MetaCount> = entry
+            .as_ref()
+            .and_then(|entry| entry["instanceCount"].as_array())
+            .map(|groups| groups.iter().filter_map(|group| {
+                let value = group["key"].as_str()?.to_string();
+                Some(MetaCount { value, count: count_of(&group["value"]) })
+            }).collect())
+            .unwrap_or_default();
+        counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+
+        Ok(counts)
+    }
+
+    /// The `MetaType` entry for `meta_type` of a counting query, None when nothing matched
+    async fn query_meta_counts(&self, meta_type: &str, filters: &[MetaFilter], count_by: Option<&str>) -> Result<Option<Value>> {
+        let mut query = QueryMetaType::new()
+            .with_meta_type(meta_type)
+            .with_latest(true)
+            .with_query_args(json!({ "limit": 1 }));
+        if !filters.is_empty() {
+            query = query.with_meta_filters(filters);
+        }
+        if let Some(key) = count_by {
+            query = query.with_count_by(key);
+        }
+        if let Some(ref cell) = self.cell_slug {
+            query = query.with_cell_slug(cell);
+        }
+
+        let client = self.client.as_ref().ok_or(KnishIOError::NoClient)?;
+        let response = query.execute(client, None, None).await?;
+        let data = response.data();
+        let entries = match data.get("MetaType").unwrap_or(data) {
+            Value::Array(entries) => entries.clone(),
+            Value::Null => Vec::new(),
+            entry => vec![entry.clone()],
+        };
+
+        Ok(entries.into_iter().find(|entry| entry["metaType"].as_str() == Some(meta_type)))
+    }
+}
+
+/// A count sent as a number or a numeric string
+fn count_of(value: &Value) -> u64 {
+    match value {
+        Value::Number(n) => n.as_u64().unwrap_or_default(),
+        Value::String(s) => s.parse().unwrap_or_default(),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::generate_secret;
+    use crate::test_ledger::TestLedger;
+    use crate::types::MetaItem;
+
+    #[tokio::test]
+    async fn test_count_meta() {
+        let ledger = TestLedger::start().await.unwrap();
+        let mut client = ledger.client(&generate_secret("meta-count-owner"));
+
+        for (id, status, priority) in [("T1", "open", "high"), ("T2", "open", "low"), ("T3", "closed", "high"), ("T4", "open", "high")] {
+            let meta = vec![MetaItem::new("status", status), MetaItem::new("priority", priority)];
+            let report = client.create_meta_bulk("ticket", id, meta, 10).await.unwrap();
+            assert!(report.is_complete(), "{:?}", report.chunks);
+        }
+        // T2 is closed later; only its latest status counts
+        let report = client.create_meta_bulk("ticket", "T2", vec![MetaItem::new("status", "closed")], 10).await.unwrap();
+        assert!(report.is_complete(), "{:?}", report.chunks);
+
+        assert_eq!(client.count_meta("ticket", &[]).await.unwrap(), 4);
+        assert_eq!(client.count_meta("ticket", &[MetaFilter::eq("status", "open")]).await.unwrap(), 2);
+        assert_eq!(client.count_meta("ticket", &[MetaFilter::ne("status", "open")]).await.unwrap(), 2);
+        assert_eq!(client.count_meta("ticket", &[
+            MetaFilter::eq("status", "open"),
+            MetaFilter::eq("priority", "low").or(),
+        ]).await.unwrap(), 3);
+        assert_eq!(client.count_meta("nothing", &[]).await.unwrap(), 0);
+
+        let by_status = client.count_meta_by("ticket", "status", &[]).await.unwrap();
+        assert_eq!(by_status, vec![
+            MetaCount { value: "closed".to_string(), count: 2 },
+            MetaCount { value: "open".to_string(), count: 2 },
+        ]);
+        let high_by_status = client.count_meta_by("ticket", "status", &[MetaFilter::eq("priority", "high")]).await.unwrap();
+        assert_eq!(high_by_status, vec![
+            MetaCount { value: "open".to_string(), count: 2 },
+            MetaCount { value: "closed".to_string(), count: 1 },
+        ]);
+    }
+}
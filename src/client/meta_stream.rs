@@ -0,0 +1,305 @@
+//! Streaming metadata imports
+//!
+//! `create_meta_bulk` takes its items as a `Vec`, and building every molecule before the
+//! first is sent would hold the import twice: once as items, once as atoms. `stream_meta`
+//! pulls items from an iterator instead, closes a molecule when it reaches
+//! `MetaStreamConfig::max_items` items or `max_bytes` of keys and values, signs it and
+//! hands it to a `MoleculeSink` before reading further. At most one chunk of items and
+//! one molecule are in memory at a time, whatever the size of the import.
+//!
+//! Each molecule is built on the remainder of the one before it, so the ContinuID relay is
+//! chained locally rather than queried per chunk. After a sink refuses a molecule the next
+//! one starts from the bundle's ContinuID head as the node reports it.
+//!
+//! `ProposeSink` proposes every molecule to the node as it comes; any
+//! `tokio::sync::mpsc::Sender<Molecule>` is a sink too, for signing now and submitting
+//! elsewhere or later.
+
+use crate::client::{KnishIOClient, MetaBulkReport, MetaChunkOutcome};
+use crate::error::{KnishIOError, Result};
+use crate::graphql::{GraphQLClient, RequestOptions, RequestPriority};
+use crate::meta::SCHEMA_VERSION_KEY;
+use crate::molecule::Molecule;
+use crate::mutation::propose_molecule::MutationProposeMolecule;
+use crate::mutation::Mutation;
+use crate::types::MetaItem;
+use crate::wallet::Wallet;
+use async_trait::async_trait;
+use std::iter::Peekable;
+
+/// Where `stream_meta` delivers each signed molecule
+#[async_trait]
+pub trait MoleculeSink: Send {
+    /// Take one signed molecule; returns its molecular hash once accepted
+    ///
+    /// An error marks the molecule's chunk as failed. The next molecule is then built on
+    /// the ContinuID head the node reports rather than on this molecule's remainder.
+    async fn submit(&mut self, molecule: Molecule) -> Result<Option<String>>;
+}
+
+/// Proposes every molecule to the node at background priority
+pub struct ProposeSink {
+    client: GraphQLClient,
+}
+
+impl ProposeSink {
+    /// Propose through `client`
+    pub fn new(client: GraphQLClient) -> Self {
+        ProposeSink { client }
+    }
+}
+
+#[async_trait]
+impl MoleculeSink for ProposeSink {
+    async fn submit(&mut self, molecule: Molecule) -> Result<Option<String>> {
+        let response = MutationProposeMolecule::from_molecule(molecule).execute(&self.client, None, None).await?;
+        if !response.success() {
+            return Err(KnishIOError::custom(format!(
+                "Meta chunk rejected: {}",
+                response.reason().unwrap_or_else(|| "unknown reason".to_string())
+            )));
+        }
+
+        Ok(response.get("molecularHash").and_then(|h| h.as_str()).map(str::to_string))
+    }
+}
+
+#[async_trait]
+impl MoleculeSink for tokio::sync::mpsc::Sender<Molecule> {
+    async fn submit(&mut self, molecule: Molecule) -> Result<Option<String>> {
+        let hash = molecule.molecular_hash.clone();
+        self.send(molecule).await.map_err(|_| KnishIOError::custom("Molecule sink is closed"))?;
+        Ok(hash)
+    }
+}
+
+/// Size of the molecules `stream_meta` builds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetaStreamConfig {
+    /// Most meta items per molecule, schema stamp included
+    pub max_items: usize,
+    /// Most bytes of keys and values per molecule (None for no byte cap)
+    ///
+    /// A single item larger than the cap still goes out, alone.
+    pub max_bytes: Option<usize>,
+}
+
+impl Default for MetaStreamConfig {
+    fn default() -> Self {
+        MetaStreamConfig { max_items: 200, max_bytes: None }
+    }
+}
+
+impl MetaStreamConfig {
+    /// Set the most meta items per molecule (at least one)
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        self.max_items = max_items.max(1);
+        self
+    }
+
+    /// Set the most bytes of keys and values per molecule
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Pull the next chunk from `items`, leaving `reserved` item slots free
+    fn take_chunk<I: Iterator<Item = MetaItem>>(&self, items: &mut Peekable<I>, reserved: usize) -> Vec<MetaItem> {
+        let max_items = self.max_items.saturating_sub(reserved).max(1);
+        let mut chunk = Vec::new();
+        let mut bytes = 0;
+        while chunk.len() < max_items {
+            let size = match items.peek() {
+                Some(item) => item.key.len() + item.value.len(),
+                None => break,
+            };
+            if !chunk.is_empty() && self.max_bytes.is_some_and(|max| bytes + size > max) {
+                break;
+            }
+            bytes += size;
+            chunk.extend(items.next());
+        }
+        chunk
+    }
+}
+
+impl KnishIOClient {
+    /// Sink proposing molecules through this client
+    ///
+    /// # Errors
+    ///
+    /// `NoClient` without a GraphQL client
+    pub fn propose_sink(&self) -> Result<ProposeSink> {
+        Ok(ProposeSink::new(self.client_with(RequestOptions::new().priority(RequestPriority::Background))?))
+    }
+
+    /// Write `items` to one meta instance, one molecule per chunk, as they are read
+    ///
+    /// Registered meta types get their schema version stamp in every chunk that does not
+    /// carry one (it counts towards `max_items`). A failed chunk does not stop the
+    /// remaining ones; the report has the item range of every chunk for a retry.
+    ///
+    /// ```no_run
+    /// # async fn demo(client: &mut knishio_client::KnishIOClient) -> knishio_client::Result<()> {
+    /// use knishio_client::client::MetaStreamConfig;
+    /// use knishio_client::MetaItem;
+    ///
+    /// let items = (0..100_000).map(|i| MetaItem::new(format!("sku{}", i), "in stock"));
+    /// let mut sink = client.propose_sink()?;
+    /// let config = MetaStreamConfig::default().max_items(500).max_bytes(64 * 1024);
+    /// let report = client.stream_meta("inventory", "warehouse-1", items, &config, &mut sink).await?;
+    /// println!("{} chunks, {} failed", report.chunks.len(), report.failure_count());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// `MissingSecret` or the ContinuID query error when the first molecule has no source
+    /// wallet; per-chunk failures are in the report
+    pub async fn stream_meta<I, S>(
+        &mut self,
+        meta_type: &str,
+        meta_id: &str,
+        items: I,
+        config: &MetaStreamConfig,
+        sink: &mut S,
+    ) -> Result<MetaBulkReport>
+    where
+        I: IntoIterator<Item = MetaItem>,
+        S: MoleculeSink + ?Sized,
+    {
+        let stamp = self.schema_stamp(meta_type);
+        let mut items = items.into_iter().peekable();
+        let mut source = Some(self.get_source_wallet().await?);
+        let mut chunks = Vec::new();
+        let mut start = 0;
+
+        while items.peek().is_some() {
+            let mut meta = config.take_chunk(&mut items, usize::from(stamp.is_some()));
+            let count = meta.len();
+            if !meta.iter().any(|item| item.key == SCHEMA_VERSION_KEY) {
+                meta.extend(stamp.clone());
+            }
+
+            let source_wallet = match source.take() {
+                Some(wallet) => wallet,
+                None => self.get_source_wallet().await?,
+            };
+            let result = match self.stream_meta_chunk(meta_type, meta_id, meta, source_wallet, sink).await {
+                Ok((hash, remainder)) => {
+                    source = remainder;
+                    Ok(hash)
+                }
+                Err(e) => {
+                    self.log("warn", &format!("KnishIOClient::stream_meta() - Chunk {} of {} {} failed: {}",
+                        chunks.len(), meta_type, meta_id, e));
+                    Err(e)
+                }
+            };
+            chunks.push(MetaChunkOutcome { chunk: chunks.len(), items: start..start + count, result });
+            start += count;
+        }
+
+        let report = MetaBulkReport { meta_type: meta_type.to_string(), meta_id: meta_id.to_string(), chunks };
+        self.log("info", &format!(
+            "KnishIOClient::stream_meta() - {} of {} chunks written for {} {}",
+            report.chunks.len() - report.failure_count(),
+            report.chunks.len(),
+            meta_type,
+            meta_id
+        ));
+
+        Ok(report)
+    }
+
+    /// Sign one M-atom molecule on `source_wallet` and submit it; returns the hash and the
+    /// remainder the next molecule builds on
+    async fn stream_meta_chunk<S: MoleculeSink + ?Sized>(
+        &mut self,
+        meta_type: &str,
+        meta_id: &str,
+        meta: Vec<MetaItem>,
+        source_wallet: Wallet,
+        sink: &mut S,
+    ) -> Result<(Option<String>, Option<Wallet>)> {
+        let mut molecule = self.create_molecule(None, None, Some(source_wallet), None).await?;
+        molecule.init_meta(meta, meta_type, meta_id, None)?;
+        molecule.sign(None, false, true)?;
+        molecule.check(None)?;
+
+        let remainder = molecule.remainder_wallet.clone();
+        let hash = sink.submit(molecule).await?;
+        Ok((hash, remainder))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{generate_bundle_hash, generate_secret};
+    use crate::test_ledger::TestLedger;
+
+    fn items(count: usize) -> impl Iterator<Item = MetaItem> {
+        (0..count).map(|i| MetaItem::new(format!("key{}", i), format!("value{}", i)))
+    }
+
+    #[test]
+    fn test_chunks_close_at_items_or_bytes() {
+        let config = MetaStreamConfig::default().max_items(3).max_bytes(20);
+        let mut source = items(5).peekable();
+        // "key0value0" is 10 bytes: two items fill 20
+        assert_eq!(config.take_chunk(&mut source, 0).len(), 2);
+        assert_eq!(config.take_chunk(&mut source, 2).len(), 1);
+        assert_eq!(config.take_chunk(&mut source, 0).len(), 2);
+        assert!(config.take_chunk(&mut source, 0).is_empty());
+
+        let oversized = MetaStreamConfig::default().max_bytes(4);
+        assert_eq!(oversized.take_chunk(&mut items(2).peekable(), 0).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_meta_is_streamed_to_the_node() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("meta-stream-owner");
+        let mut client = ledger.client(&secret);
+
+        let mut sink = client.propose_sink().unwrap();
+        let config = MetaStreamConfig::default().max_items(3);
+        let report = client.stream_meta("inventory", "W1", items(7), &config, &mut sink).await.unwrap();
+        assert!(report.is_complete(), "{:?}", report.chunks);
+        let ranges: Vec<_> = report.chunks.iter().map(|c| c.items.clone()).collect();
+        assert_eq!(ranges, vec![0..3, 3..6, 6..7]);
+
+        let molecules = ledger.molecules();
+        assert_eq!(molecules.len(), 3);
+        assert!(molecules.iter().all(|m| m.accepted()));
+        assert!(ledger.continu_id(&generate_bundle_hash(&secret)).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_signed_molecules_chain_through_a_channel() {
+        let ledger = TestLedger::start().await.unwrap();
+        let mut client = ledger.client(&generate_secret("meta-stream-channel"));
+
+        let (mut sender, mut receiver) = tokio::sync::mpsc::channel(8);
+        let config = MetaStreamConfig::default().max_items(2);
+        let report = client.stream_meta("inventory", "W2", items(5), &config, &mut sender).await.unwrap();
+        assert_eq!(report.chunks.len(), 3);
+        assert!(ledger.molecules().is_empty());
+
+        // Proposed later, in order, each lands on the ContinuID left by the one before
+        drop(sender);
+        let mut sink = client.propose_sink().unwrap();
+        while let Some(molecule) = receiver.recv().await {
+            sink.submit(molecule).await.unwrap();
+        }
+        assert_eq!(ledger.molecules().iter().filter(|m| m.accepted()).count(), 3);
+
+        // A closed sink fails every chunk without stopping the stream
+        let (mut closed, receiver) = tokio::sync::mpsc::channel(1);
+        drop(receiver);
+        let report = client.stream_meta("inventory", "W3", items(3), &config, &mut closed).await.unwrap();
+        assert_eq!(report.failed_items(), vec![0..2, 2..3]);
+    }
+}
@@ -3,15 +3,58 @@
 //! This module provides the main client interface for interacting with
 //! KnishIO distributed ledger nodes.
 
+pub mod auto_claim;
+pub mod batch_namespace;
 pub mod builder;
+pub mod bulk;
+pub mod confirmation;
+pub mod consolidate;
+pub mod cross_cell;
+pub mod dead_letter;
+pub mod discovery;
+pub mod escrow;
+pub mod hooks;
+pub mod key_rotation;
+pub mod ledger_diff;
+pub mod lineage;
+pub mod meta_blob;
+pub mod meta_bulk;
+pub mod meta_count;
+pub mod meta_stream;
+pub mod multi_source;
+pub mod ownership_proof;
+pub mod quorum;
+pub mod rotate;
+pub mod schema;
+pub mod session;
+pub mod subscription_fallback;
+pub mod subscription_limits;
+pub mod token_builder;
+pub mod token_distribution;
+pub mod token_registry;
+pub mod trade_rates;
+pub mod unit_search;
+pub mod user_activity;
+pub mod wallet_collision;
 
 use crate::error::{KnishIOError, Result};
-use crate::wallet::Wallet;
-use crate::auth::AuthToken;
+use crate::wallet::{Wallet, WalletHydration, WalletParams, WatchWallet};
+use crate::auth::{AuthScope, AuthToken, SealedSnapshot, SnapshotKey};
 use crate::molecule::Molecule;
-use crate::response::{Response};
+use crate::identity_bridge::{ExternalSigner, ExternalVerifier, IdentityProof, VerifiedIdentityProof};
+use crate::token_unit::{HeldTokenUnit, TokenUnitFilter, UnitSelection};
+use crate::meta::SchemaRegistry;
+use crate::policy_meta::{EffectivePolicy, PolicyLevel, PolicySource};
+use crate::codec::WireFormat;
+use crate::crypto::KeyDerivationConfig;
+use crate::auth::{AuthTokenStore, DefaultFingerprint, Fingerprint};
+use crate::utils::clock::{system_clock, Clock};
+use crate::types::{MetaItem, SystemTokens};
+use crate::response::{decode_payload, AuthPayload, Response};
 use crate::graphql::{
-    GraphQLClient, SocketConfig
+    GraphQLClient, HedgeConfig, QueryCostConfig, QueryCostListener, QueryCostStats, RequestOptions, RequestPriority,
+    RetryPolicy, SchedulerConfig, SchedulerStats, SocketConfig,
+    WebSocketManager
 };
 use crate::subscribe::{
     SubscriptionManager, SubscriptionEvent, SubscriptionHandle, Subscribe,
@@ -20,9 +63,34 @@ use crate::subscribe::{
 use crate::subscribe::simple_websocket::SimpleWebSocketClient;
 use serde_json::{json, Value};
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
 use rand;
 
+pub use auto_claim::{AutoClaimEvent, AutoClaimPolicy};
+pub use bulk::{BulkContext, BulkOutcome, BulkSummary};
+pub use confirmation::CONFIRMATION_POLL_INTERVAL;
+pub use consolidate::{ConsolidationGroup, ConsolidationPlan, ConsolidationReport, SweepOutcome};
+pub use cross_cell::{CrossCellLeg, CrossCellReport, CrossCellStatus, CrossCellTransaction, PreparedCrossCell};
+pub use dead_letter::{DeadLetter, DeadLetterCause, DeadLetterQueue};
+pub use escrow::{Escrow, EscrowConditions, EscrowStatus, ESCROW_META_TYPE};
+pub use discovery::{DiscoveryConfig, DiscoverySource, NodeDirectory, SrvRecord};
+pub use hooks::{Operation, OperationContext, OperationHooks, OperationOutcome, PostHook, PreHook};
+pub use ledger_diff::{BalanceChange, ChangeKind, LedgerDiff, LedgerSnapshot, MetaChange};
+pub use lineage::{BatchHop, BatchLineage, BatchLineageNode, BatchRecord, BatchWalletRef, MAX_LINEAGE_BATCHES};
+pub use meta_bulk::{MetaBulkReport, MetaChunkOutcome};
+pub use meta_stream::{MetaStreamConfig, MoleculeSink, ProposeSink};
+pub use meta_count::MetaCount;
+pub use multi_source::SourceLeg;
+pub use quorum::{NodeOutcome, NodeSubmission, QuorumReport, QuorumStatus};
+pub use schema::{RootType, SchemaDrift, SchemaReport};
+pub use session::{SessionState, SessionToken, SESSION_STATE_VERSION};
+pub use subscription_fallback::SUBSCRIPTION_POLL_INTERVAL;
+pub use token_builder::{TokenBuilder, TokenCreationReport, TokenPreset};
+pub use token_distribution::TokenDistributionReport;
+pub use user_activity::{activity_by, activity_per, ActivityCount, ActivityInstance, DeviceInfo, UserActivityRecord};
+pub use token_registry::{Fungibility, TokenInfo, TokenRegistry};
+
 /// Recipient type for request_tokens() method
 ///
 /// Handles different ways to specify a token recipient:
@@ -40,6 +108,62 @@ pub enum RecipientType {
     Wallet(Wallet),
 }
 
+/// Token of the remainder wallet `create_molecule_with` generates
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum RemainderToken {
+    /// USER, continuing the ContinuID chain (the `create_molecule` behavior)
+    #[default]
+    User,
+    /// Same token as the source wallet (the JS SDK's per-operation remainder)
+    Source,
+    /// A specific token slug
+    Token(String),
+}
+
+/// Per-operation remainder wallet selection for `create_molecule_with`
+///
+/// The default reproduces `create_molecule`: a USER remainder carrying the source's batch ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemainderOptions {
+    /// Token of the generated remainder wallet
+    pub token: RemainderToken,
+    /// Copy the source wallet's batch ID onto the remainder
+    pub inherit_batch_id: bool,
+}
+
+impl Default for RemainderOptions {
+    fn default() -> Self {
+        RemainderOptions { token: RemainderToken::User, inherit_batch_id: true }
+    }
+}
+
+impl RemainderOptions {
+    /// Remainder in the source wallet's token, inheriting its batch ID
+    pub fn source_token() -> Self {
+        RemainderOptions { token: RemainderToken::Source, ..Default::default() }
+    }
+
+    /// Remainder in `token`, inheriting the source's batch ID
+    pub fn token(token: impl Into<String>) -> Self {
+        RemainderOptions { token: RemainderToken::Token(token.into()), ..Default::default() }
+    }
+
+    /// Set whether the remainder inherits the source wallet's batch ID
+    pub fn inherit_batch_id(mut self, inherit: bool) -> Self {
+        self.inherit_batch_id = inherit;
+        self
+    }
+
+    /// Token slug of the remainder for `source`
+    pub fn token_for<'a>(&'a self, source: &'a Wallet) -> &'a str {
+        match &self.token {
+            RemainderToken::User => "USER",
+            RemainderToken::Source => &source.token,
+            RemainderToken::Token(token) => token,
+        }
+    }
+}
+
 /// One destination in a multi-recipient transfer (WP line 544).
 ///
 /// Provide `units` for a stackable per-unit transfer (its amount is `units.len()`), or `amount`
@@ -91,7 +215,9 @@ pub struct KnishIOClient {
     /// Current authentication token for server requests
     auth_token: Option<AuthToken>,
     /// Map of authentication tokens by context
-    auth_token_objects: HashMap<String, AuthToken>,
+    auth_token_objects: AuthTokenStore,
+    /// Time source of auth token expiry checks
+    clock: Arc<dyn Clock>,
     /// Flag indicating if authentication is in progress
     auth_in_process: bool,
     
@@ -99,6 +225,8 @@ pub struct KnishIOClient {
     server_sdk_version: u32,
     /// Whether to encrypt communications (ML-KEM quantum encryption)
     encrypt: bool,
+    /// Whether molecules are proposed without the signer's bundle
+    anonymous: bool,
     /// Whether to enable debug logging
     logging: bool,
     
@@ -111,6 +239,8 @@ pub struct KnishIOClient {
     websocket_client: Option<SimpleWebSocketClient>,
     /// Subscription manager for handling real-time subscriptions
     subscription_manager: Option<Arc<SubscriptionManager>>,
+    /// WebSocket connection kept on the current auth token
+    websocket_manager: Option<WebSocketManager>,
     
     /// Last remainder wallet from molecule operations
     remainder_wallet: Option<Wallet>,
@@ -119,6 +249,31 @@ pub struct KnishIOClient {
     
     /// Abort controllers for cancelling in-flight requests
     abort_controllers: Arc<Mutex<HashMap<String, bool>>>,
+
+    /// Strategy for picking stackable units when a transfer gives only an amount
+    unit_selection: UnitSelection,
+    /// Result of the last server schema check
+    schema_report: Option<SchemaReport>,
+    /// Token slugs of the ContinuID and authorization wallets
+    system_tokens: SystemTokens,
+    /// Versioned meta schemas applied when writing and reading metadata
+    schema_registry: Option<SchemaRegistry>,
+    /// Device fingerprint the guest AUTH wallet is derived from
+    fingerprint: Arc<dyn Fingerprint>,
+    /// Retry policy of `submit_molecule`
+    submit_policy: RetryPolicy,
+    /// Molecules `submit_molecule` gave up on
+    dead_letters: DeadLetterQueue,
+    /// Token metadata cache consulted by amount validation
+    token_registry: TokenRegistry,
+    /// Positions redrawn when a new wallet's address is on the ledger; None skips the check
+    wallet_collision_retries: Option<u32>,
+    /// Pre and post hooks of the high-level operations
+    hooks: OperationHooks,
+    /// Namespace of the batch IDs the client generates; None for plain IDs
+    batch_namespace: Option<String>,
+    /// How secrets are derived from seeds
+    key_derivation: KeyDerivationConfig,
 }
 
 impl KnishIOClient {
@@ -138,18 +293,33 @@ impl KnishIOClient {
             secret: None,
             bundle: None,
             auth_token: None,
-            auth_token_objects: HashMap::new(),
+            auth_token_objects: AuthTokenStore::default(),
+            clock: system_clock(),
             auth_in_process: false,
             server_sdk_version: server_sdk_version.unwrap_or(3),
             encrypt: false,
+            anonymous: false,
             logging: logging.unwrap_or(false),
             client: None,
             socket_config: socket.clone(),
             websocket_client: None,
             subscription_manager: None,
+            websocket_manager: None,
             remainder_wallet: None,
             last_molecule_query: None,
             abort_controllers: Arc::new(Mutex::new(HashMap::new())),
+            unit_selection: UnitSelection::default(),
+            schema_report: None,
+            system_tokens: SystemTokens::default(),
+            schema_registry: None,
+            fingerprint: Arc::new(DefaultFingerprint::default()),
+            submit_policy: RetryPolicy::default(),
+            dead_letters: DeadLetterQueue::new(),
+            token_registry: TokenRegistry::new(),
+            wallet_collision_retries: None,
+            hooks: OperationHooks::new(),
+            batch_namespace: None,
+            key_derivation: KeyDerivationConfig::default(),
         };
 
         client_instance.initialize(uri, cell_slug, socket, client, server_sdk_version, logging);
@@ -181,12 +351,6 @@ impl KnishIOClient {
             self.set_cell_slug(cell);
         }
 
-        for uri in &self.uris {
-            // Create an empty AuthToken for now
-            let auth_token = AuthToken::new(String::new(), None, None, None);
-            self.auth_token_objects.insert(uri.clone(), auth_token);
-        }
-
         self.log("info", &format!("KnishIOClient::initialize() - Initializing new Knish.IO client session for SDK version {}...", self.server_sdk_version));
 
         if let Some(client) = client {
@@ -201,6 +365,9 @@ impl KnishIOClient {
             // Initialize subscription manager with the new GraphQL client
             self.subscription_manager = Some(Arc::new(SubscriptionManager::new(Arc::new(new_client))));
         }
+        if let (Some(manager), Some(socket)) = (&self.subscription_manager, &self.websocket_manager) {
+            manager.attach_socket(socket.clone());
+        }
 
         self.server_sdk_version = server_sdk_version.unwrap_or(3);
     }
@@ -212,29 +379,95 @@ impl KnishIOClient {
             .ok_or_else(|| KnishIOError::custom("Subscription manager not initialized"))
     }
 
+    /// Attach a WebSocket manager whose `connection_init` follows the client's auth token
+    ///
+    /// The manager is given the current token right away; whenever the token rotates
+    /// afterwards (authentication, login, logout, `set_auth_token`), its connection is
+    /// re-initialised with the new one and live subscriptions carry on.
+    ///
+    /// The manager replaces any earlier one in the subscription manager's `stats`.
+    pub fn set_websocket_manager(&mut self, manager: WebSocketManager) {
+        let token = self.auth_token.as_ref().map(|token| token.token().to_string());
+        if let Err(e) = manager.set_auth_token(token) {
+            self.log("warn", &format!("KnishIOClient::set_websocket_manager() - {}", e));
+        }
+        if let Some(ref subscriptions) = self.subscription_manager {
+            if let Some(ref previous) = self.websocket_manager {
+                subscriptions.detach_socket(previous);
+            }
+            subscriptions.attach_socket(manager.clone());
+        }
+        self.websocket_manager = Some(manager);
+    }
+
+    /// Get the attached WebSocket manager
+    pub fn get_websocket_manager(&self) -> Option<&WebSocketManager> {
+        self.websocket_manager.as_ref()
+    }
+
+    /// Statistics of the attached WebSocket connection (see `WebSocketManager::stats`)
+    pub async fn websocket_stats(&self) -> Option<crate::graphql::WebSocketStats> {
+        match self.websocket_manager {
+            Some(ref manager) => Some(manager.stats().await),
+            None => None,
+        }
+    }
+
+    /// Hand a rotated auth token to the attached WebSocket manager
+    fn rotate_socket_auth(&self, token: Option<String>) {
+        if let Some(ref manager) = self.websocket_manager {
+            match manager.set_auth_token(token) {
+                Ok(true) => self.log("info", "KnishIOClient::rotate_socket_auth() - Auth token rotated, re-initialising subscriptions"),
+                Ok(false) => {}
+                Err(e) => self.log("warn", &format!("KnishIOClient::rotate_socket_auth() - {}", e)),
+            }
+        }
+    }
+
+    /// Open `subscription` on the client's WebSocket connection, handing its `field` events
+    /// to `callback` with duplicates dropped
+    async fn open_subscription<S, F>(&self, subscription: &S, field: &str, variables: Value, callback: F) -> Result<SubscriptionHandle>
+    where
+        S: Subscribe,
+        F: Fn(SubscriptionEvent) + Send + Sync + 'static,
+    {
+        let manager = self.get_subscription_manager()?;
+        let request = manager.create_subscribe_request(
+            subscription.get_subscription_query(),
+            subscription.compiled_variables(Some(variables)),
+        );
+
+        let events = manager.clone();
+        let field = field.to_string();
+        manager.open(request, move |data: Value| {
+            if events.accept_event(&field, &data) {
+                callback(SubscriptionEvent::new(field.clone(), data));
+            }
+        }).await
+    }
+
     /// Subscribe to CreateMolecule events (equivalent to subscribeCreateMolecule in JS)
     pub async fn subscribe_create_molecule<F>(&self, bundle: Option<String>, callback: F) -> Result<SubscriptionHandle>
     where
         F: Fn(SubscriptionEvent) + Send + Sync + 'static,
     {
-        let _manager = self.get_subscription_manager()?;
-        let graphql_client = self.client.as_ref()
-            .ok_or_else(|| KnishIOError::custom("GraphQL client not initialized"))?;
-        
-        let subscription = CreateMoleculeSubscribe::new(Arc::new(graphql_client.clone()));
-        
         let bundle = bundle.unwrap_or_else(|| self.get_bundle().unwrap_or_default().to_string());
         let variables = json!({
             "bundle": bundle
         });
+        let opened = Arc::new(OnceLock::new());
+        let callback = self.mark_activity(&opened, callback);
+        if self.subscription_needs_polling("CreateMolecule")? {
+            let handle = self.poll_subscription("CreateMolecule", variables, SUBSCRIPTION_POLL_INTERVAL, callback)?;
+            return self.track_subscription(handle, &opened).await;
+        }
+
+        let graphql_client = self.client.as_ref()
+            .ok_or_else(|| KnishIOError::custom("GraphQL client not initialized"))?;
         
-        // Convert callback to Box<dyn Fn(Value)> for JavaScript compatibility
-        let boxed_callback = Box::new(move |data: Value| {
-            let event = SubscriptionEvent::new("CreateMolecule".to_string(), data);
-            callback(event);
-        });
-        
-        subscription.execute(variables, boxed_callback).await
+        let subscription = CreateMoleculeSubscribe::new(Arc::new(graphql_client.clone()));
+        let handle = self.open_subscription(&subscription, "CreateMolecule", variables, callback).await?;
+        self.track_subscription(handle, &opened).await
     }
 
     /// Subscribe to WalletStatus events (equivalent to subscribeWalletStatus in JS)
@@ -246,25 +479,24 @@ impl KnishIOClient {
             return Err(KnishIOError::custom("Token parameter is required for wallet status subscription"));
         }
 
-        let _manager = self.get_subscription_manager()?;
-        let graphql_client = self.client.as_ref()
-            .ok_or_else(|| KnishIOError::custom("GraphQL client not initialized"))?;
-        
-        let subscription = WalletStatusSubscribe::new(Arc::new(graphql_client.clone()));
-        
         let bundle = bundle.unwrap_or_else(|| self.get_bundle().unwrap_or_default().to_string());
         let variables = json!({
             "bundle": bundle,
             "token": token
         });
+        let opened = Arc::new(OnceLock::new());
+        let callback = self.mark_activity(&opened, callback);
+        if self.subscription_needs_polling("WalletStatus")? {
+            let handle = self.poll_subscription("WalletStatus", variables, SUBSCRIPTION_POLL_INTERVAL, callback)?;
+            return self.track_subscription(handle, &opened).await;
+        }
+
+        let graphql_client = self.client.as_ref()
+            .ok_or_else(|| KnishIOError::custom("GraphQL client not initialized"))?;
         
-        // Convert callback to Box<dyn Fn(Value)> for JavaScript compatibility
-        let boxed_callback = Box::new(move |data: Value| {
-            let event = SubscriptionEvent::new("WalletStatus".to_string(), data);
-            callback(event);
-        });
-        
-        subscription.execute(variables, boxed_callback).await
+        let subscription = WalletStatusSubscribe::new(Arc::new(graphql_client.clone()));
+        let handle = self.open_subscription(&subscription, "WalletStatus", variables, callback).await?;
+        self.track_subscription(handle, &opened).await
     }
 
     /// Subscribe to ActiveWallet events (equivalent to subscribeActiveWallet in JS)
@@ -272,24 +504,23 @@ impl KnishIOClient {
     where
         F: Fn(SubscriptionEvent) + Send + Sync + 'static,
     {
-        let _manager = self.get_subscription_manager()?;
-        let graphql_client = self.client.as_ref()
-            .ok_or_else(|| KnishIOError::custom("GraphQL client not initialized"))?;
-        
-        let subscription = ActiveWalletSubscribe::new(Arc::new(graphql_client.clone()));
-        
         let bundle = bundle.unwrap_or_else(|| self.get_bundle().unwrap_or_default().to_string());
         let variables = json!({
             "bundle": bundle
         });
+        let opened = Arc::new(OnceLock::new());
+        let callback = self.mark_activity(&opened, callback);
+        if self.subscription_needs_polling("ActiveWallet")? {
+            let handle = self.poll_subscription("ActiveWallet", variables, SUBSCRIPTION_POLL_INTERVAL, callback)?;
+            return self.track_subscription(handle, &opened).await;
+        }
+
+        let graphql_client = self.client.as_ref()
+            .ok_or_else(|| KnishIOError::custom("GraphQL client not initialized"))?;
         
-        // Convert callback to Box<dyn Fn(Value)> for JavaScript compatibility
-        let boxed_callback = Box::new(move |data: Value| {
-            let event = SubscriptionEvent::new("ActiveWallet".to_string(), data);
-            callback(event);
-        });
-        
-        subscription.execute(variables, boxed_callback).await
+        let subscription = ActiveWalletSubscribe::new(Arc::new(graphql_client.clone()));
+        let handle = self.open_subscription(&subscription, "ActiveWallet", variables, callback).await?;
+        self.track_subscription(handle, &opened).await
     }
 
     /// Subscribe to ActiveSession events (equivalent to subscribeActiveSession in JS)
@@ -297,24 +528,23 @@ impl KnishIOClient {
     where
         F: Fn(SubscriptionEvent) + Send + Sync + 'static,
     {
-        let _manager = self.get_subscription_manager()?;
-        let graphql_client = self.client.as_ref()
-            .ok_or_else(|| KnishIOError::custom("GraphQL client not initialized"))?;
-        
-        let subscription = ActiveSessionSubscribe::new(Arc::new(graphql_client.clone()));
-        
         let variables = json!({
             "metaType": meta_type,
             "metaId": meta_id
         });
+        let opened = Arc::new(OnceLock::new());
+        let callback = self.mark_activity(&opened, callback);
+        if self.subscription_needs_polling("ActiveSession")? {
+            let handle = self.poll_subscription("ActiveSession", variables, SUBSCRIPTION_POLL_INTERVAL, callback)?;
+            return self.track_subscription(handle, &opened).await;
+        }
+
+        let graphql_client = self.client.as_ref()
+            .ok_or_else(|| KnishIOError::custom("GraphQL client not initialized"))?;
         
-        // Convert callback to Box<dyn Fn(Value)> for JavaScript compatibility
-        let boxed_callback = Box::new(move |data: Value| {
-            let event = SubscriptionEvent::new("ActiveSession".to_string(), data);
-            callback(event);
-        });
-        
-        subscription.execute(variables, boxed_callback).await
+        let subscription = ActiveSessionSubscribe::new(Arc::new(graphql_client.clone()));
+        let handle = self.open_subscription(&subscription, "ActiveSession", variables, callback).await?;
+        self.track_subscription(handle, &opened).await
     }
 
     /// Create a Query instance of the specified type (equivalent to createQuery in TS)
@@ -613,15 +843,7 @@ impl KnishIOClient {
             let secret = self.secret.as_ref()
                 .ok_or(KnishIOError::MissingSecret)?;
 
-            Wallet::new(
-                Some(secret.as_str()),
-                None,  // bundle will be auto-generated from secret
-                None,  // token defaults to "USER"
-                None,  // address
-                None,  // position will be auto-generated
-                None,  // batch_id
-                None,  // characters
-            )?
+            Wallet::from_params(WalletParams::new().secret(secret.as_str()).token(self.system_tokens.user.as_str()))?
         };
 
         // Generate wallet key if we have position
@@ -668,6 +890,42 @@ impl KnishIOClient {
         bundle: Option<String>,
         source_wallet: Option<Wallet>,
         remainder_wallet: Option<Wallet>,
+    ) -> Result<Molecule> {
+        self.create_molecule_with(secret, bundle, source_wallet, remainder_wallet, RemainderOptions::default()).await
+    }
+
+    /// Create a new Molecule, choosing the generated remainder wallet per operation
+    ///
+    /// Same as `create_molecule`, except that when no remainder wallet is given, `options`
+    /// decides its token and whether it inherits the source's batch ID. Only USER remainders
+    /// are kept as the next ContinuID source, so operating on another token wallet does not
+    /// break the USER chain.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use knishio_client::{KnishIOClient, RemainderOptions};
+    ///
+    /// # async fn example(client: &mut KnishIOClient, source: knishio_client::Wallet) -> knishio_client::Result<()> {
+    /// // Keep the change in the source's own token, without its batch ID
+    /// let molecule = client.create_molecule_with(
+    ///     None,
+    ///     None,
+    ///     Some(source),
+    ///     None,
+    ///     RemainderOptions::source_token().inherit_batch_id(false),
+    /// ).await?;
+    /// # let _ = molecule;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_molecule_with(
+        &mut self,
+        secret: Option<String>,
+        bundle: Option<String>,
+        source_wallet: Option<Wallet>,
+        remainder_wallet: Option<Wallet>,
+        options: RemainderOptions,
     ) -> Result<Molecule> {
         self.log("info", "KnishIOClient::create_molecule() - Creating a new molecule...");
 
@@ -683,7 +941,7 @@ impl KnishIOClient {
         } else if let Some(remainder) = &self.remainder_wallet {
             // Try to use last remainder wallet (ContinuID relay race)
             // Check conditions: token === 'USER' and last molecule was successful
-            if remainder.token == "USER" && self.last_molecule_query.is_some() {
+            if remainder.token == self.system_tokens.user && self.last_molecule_query.is_some() {
                 // Use remainder wallet as source for continuity
                 remainder.clone()
             } else {
@@ -699,29 +957,33 @@ impl KnishIOClient {
         let remainder = if let Some(wallet) = remainder_wallet {
             wallet
         } else {
-            // Create new remainder wallet
-            Wallet::new(
-                Some(&secret),
-                bundle.as_deref(),
-                Some("USER"),  // Always USER token for remainder
-                None,  // address will be generated
-                None,  // position will be generated
-                source_wallet.batch_id.as_deref(),
-                source_wallet.characters.as_deref(),
-            )?
+            // Create new remainder wallet (USER unless overridden; address and position are generated)
+            let token = match options.token {
+                RemainderToken::User => self.system_tokens.user.as_str(),
+                _ => options.token_for(&source_wallet),
+            };
+            let wallet = Wallet::from_params(WalletParams {
+                bundle: bundle.clone(),
+                batch_id: if options.inherit_batch_id { source_wallet.batch_id.clone() } else { None },
+                characters: source_wallet.characters.clone(),
+                ..WalletParams::new().secret(&secret).token(token)
+            })?;
+            self.avoid_position_collision(wallet, &secret).await?
         };
 
-        // Store remainder wallet for next molecule
-        self.remainder_wallet = Some(remainder.clone());
+        // Store a USER remainder as the next ContinuID source
+        if remainder.token == self.system_tokens.user {
+            self.remainder_wallet = Some(remainder.clone());
+        }
 
         // Create and configure molecule
-        let mut molecule = Molecule::new();
+        let mut molecule = self.new_molecule();
         molecule.secret = Some(secret);
         molecule.source_wallet = Some(source_wallet);
         molecule.remainder_wallet = Some(remainder);
         molecule.cell_slug = self.cell_slug.clone();
         molecule.version = Some(self.server_sdk_version.to_string());
-        molecule.bundle = bundle;
+        molecule.bundle = if self.anonymous { None } else { bundle };
 
         Ok(molecule)
     }
@@ -741,7 +1003,8 @@ impl KnishIOClient {
     ///
     /// # Returns
     ///
-    /// Result containing the server response
+    /// Result containing the server response; its `annotations()` are the molecule's
+    /// client annotations
     ///
     /// # Errors
     ///
@@ -750,12 +1013,22 @@ impl KnishIOClient {
         use crate::mutation::propose_molecule::MutationProposeMolecule;
         use crate::mutation::Mutation;
 
+        let annotations = molecule.annotations.clone();
+        let hash = molecule.molecular_hash.clone().unwrap_or_default();
         let mutation = MutationProposeMolecule::from_molecule(molecule);
 
         let client = self.client.as_ref()
             .ok_or(KnishIOError::NoClient)?;
 
-        mutation.execute(client, None, None).await
+        let result = mutation.execute(client, None, None).await;
+        if !annotations.is_empty() {
+            let outcome = match &result {
+                Ok(response) => response.status().unwrap_or_default(),
+                Err(e) => format!("failed: {}", e),
+            };
+            self.log("info", &format!("KnishIOClient::propose_molecule() - Molecule {} {:?}: {}", hash, annotations, outcome));
+        }
+        result
     }
 
     /// Log a message if logging is enabled
@@ -796,20 +1069,12 @@ impl KnishIOClient {
         // Inner block captures Result so we can always reset the flag
         let result: Result<bool> = async {
             // Create AUTH wallet from secret
-            let auth_wallet = Wallet::new(
-                Some(&secret),
-                None,
-                Some("AUTH"),
-                None,
-                None,
-                None,
-                None,
-            )?;
+            let auth_wallet = Wallet::from_params(WalletParams::new().secret(&secret).token(self.system_tokens.auth.as_str()))?;
 
             // Create molecule with secret and source wallet
-            let mut molecule = Molecule::new();
+            let mut molecule = self.new_molecule();
             molecule.secret = Some(secret.clone());
-            molecule.source_wallet = Some(auth_wallet.clone());
+            molecule.source_wallet = Some(auth_wallet);
 
             // Convert meta HashMap to Vec<MetaItem> if provided
             let meta_items: Vec<MetaItem> = meta.unwrap_or_default()
@@ -882,9 +1147,9 @@ impl KnishIOClient {
             encrypt
         ).await?;
 
-        // Store token for current URI (maintain backward compatibility)
+        // Store token for the current URI and bundle
         if let Some(current_uri) = self.get_current_uri() {
-            self.auth_token_objects.insert(current_uri, auth_token.clone());
+            self.auth_token_objects.insert(&current_uri, self.bundle.as_deref(), auth_token.clone());
         }
 
         self.log("info", "Authentication successful");
@@ -938,11 +1203,13 @@ impl KnishIOClient {
     ///
     /// * `token` - AuthToken to set as current
     pub fn set_auth_token(&mut self, token: AuthToken) {
+        let token = token.with_clock(self.clock.clone());
         self.auth_token = Some(token.clone());
+        self.rotate_socket_auth(Some(token.token().to_string()));
         
-        // Store for current URI
+        // Store for the current URI and bundle
         if let Some(current_uri) = self.get_current_uri() {
-            self.auth_token_objects.insert(current_uri, token);
+            self.auth_token_objects.insert(&current_uri, self.bundle.as_deref(), token);
         }
     }
     
@@ -950,9 +1217,113 @@ impl KnishIOClient {
     pub fn clear_auth_token(&mut self) {
         self.auth_token = None;
         self.auth_token_objects.clear();
+        self.rotate_socket_auth(None);
         self.log("info", "Authentication token cleared");
     }
     
+    /// Switch the client to the user owning `secret` and make sure it is authenticated
+    ///
+    /// A still-valid token stored for this user on the current node is reused; otherwise
+    /// a new one is requested. Tokens of other users stay in the store, so switching back
+    /// and forth between users does not re-authenticate each time.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - Secret of the user to log in
+    ///
+    /// # Returns
+    ///
+    /// Result containing the user's auth token
+    pub async fn login(&mut self, secret: &str) -> Result<AuthToken> {
+        self.set_secret(secret);
+        self.remainder_wallet = None;
+        self.last_molecule_query = None;
+
+        let bundle = self.bundle.clone();
+        let cached = self.get_current_uri()
+            .and_then(|uri| self.auth_token_objects.get(&uri, bundle.as_deref()).cloned())
+            .filter(|token| !token.is_expired() && token.scope().satisfies(AuthScope::Profile));
+
+        let token = match cached {
+            Some(token) => {
+                if let Some(ref mut client) = self.client {
+                    client.set_auth_data(token.token().to_string(), token.get_pubkey().map(str::to_string), None);
+                }
+                self.rotate_socket_auth(Some(token.token().to_string()));
+                self.auth_token = Some(token.clone());
+                token
+            }
+            None => {
+                self.auth_token = None;
+                self.authenticate(HashMap::new()).await?
+            }
+        };
+
+        self.log("info", &format!("KnishIOClient::login() - Logged in bundle {}", bundle.unwrap_or_default()));
+
+        Ok(token)
+    }
+
+    /// Log the current user out
+    ///
+    /// Drops the user's token for the current node, clears the session's auth data and
+    /// resets the secret and bundle. Tokens stored for other users are kept.
+    pub fn logout(&mut self) {
+        if let Some(current_uri) = self.get_current_uri() {
+            self.auth_token_objects.remove(&current_uri, self.bundle.as_deref());
+        }
+        if let Some(ref mut client) = self.client {
+            client.clear_auth_data();
+        }
+        self.rotate_socket_auth(None);
+
+        self.log("info", &format!("KnishIOClient::logout() - Logged out bundle {}", self.bundle.as_deref().unwrap_or_default()));
+        self.reset();
+    }
+
+    /// Drop every stored token of `bundle`, on all nodes
+    ///
+    /// # Returns
+    ///
+    /// Number of tokens removed
+    pub fn logout_bundle(&mut self, bundle: &str) -> usize {
+        if self.bundle.as_deref() == Some(bundle) {
+            self.auth_token = None;
+            if let Some(ref mut client) = self.client {
+                client.clear_auth_data();
+            }
+            self.rotate_socket_auth(None);
+        }
+        self.auth_token_objects.remove_bundle(bundle)
+    }
+
+    /// Get the store of auth tokens kept per node and bundle
+    pub fn auth_token_store(&self) -> &AuthTokenStore {
+        &self.auth_token_objects
+    }
+
+    /// Set how many auth tokens are kept before the least recently used one is evicted
+    pub fn set_auth_store_capacity(&mut self, capacity: usize) {
+        self.auth_token_objects.set_capacity(capacity);
+    }
+
+    /// Judge auth token expiry against `clock` instead of the wall clock
+    ///
+    /// Applies to the current token, the stored ones and every token issued later. Tests
+    /// pass a `ManualClock` and advance it to expire tokens without waiting.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        if let Some(ref mut token) = self.auth_token {
+            token.set_clock(clock.clone());
+        }
+        self.auth_token_objects.set_clock(clock.clone());
+        self.clock = clock;
+    }
+
+    /// Time source of auth token expiry checks
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
     /// Auto-authenticate if needed for requests (equivalent to ensureAuth in JS)
     ///
     /// # Arguments
@@ -977,19 +1348,69 @@ impl KnishIOClient {
         Ok(())
     }
     
+    /// Check that the session's auth token may perform `operation`
+    ///
+    /// Called by client methods before they sign anything, so a guest-scoped session is
+    /// refused locally instead of by the node. Passes when no token is held yet (the
+    /// caller authenticates first) and on nodes older than SDK version 3, which issue no
+    /// tokens. A guest session becomes a profile session through `login(secret)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InsufficientAuthScope` if the token's scope is below `required`
+    pub fn require_auth_scope(&self, operation: &str, required: AuthScope) -> Result<()> {
+        if self.server_sdk_version < 3 {
+            return Ok(());
+        }
+
+        match self.auth_token {
+            Some(ref token) if !token.scope().satisfies(required) => {
+                self.log("warn", &format!(
+                    "KnishIOClient::require_auth_scope() - {} refused: {} auth required, session holds {} auth",
+                    operation, required, token.scope()
+                ));
+                Err(KnishIOError::InsufficientAuthScope {
+                    operation: operation.to_string(),
+                    required,
+                    actual: token.scope(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Save authentication token to persistent storage (equivalent to saveAuth in JS)
     ///
+    /// The snapshot is encrypted with a key derived from the client secret; see
+    /// `auth::sealed` for the format.
+    ///
     /// # Arguments
     ///
     /// * `storage_key` - Key for storing the token snapshot
     ///
     /// # Returns
     ///
-    /// Result with the serialized token snapshot
+    /// Result with the serialized, encrypted token snapshot
     pub fn save_auth_token(&self, storage_key: &str) -> Result<String> {
+        let secret = self.secret.as_deref()
+            .ok_or_else(|| KnishIOError::custom("Secret must be set before saving auth token"))?;
+        self.save_auth_token_with_key(storage_key, &SnapshotKey::Secret(secret.to_string()))
+    }
+
+    /// Save authentication token encrypted under `key` instead of the client secret
+    ///
+    /// # Arguments
+    ///
+    /// * `storage_key` - Key for storing the token snapshot
+    /// * `key` - Key to seal the snapshot with
+    ///
+    /// # Returns
+    ///
+    /// Result with the serialized, encrypted token snapshot
+    pub fn save_auth_token_with_key(&self, storage_key: &str, key: &SnapshotKey) -> Result<String> {
         if let Some(ref token) = self.auth_token {
-            let snapshot = token.get_snapshot();
-            let serialized = serde_json::to_string(&snapshot)?;
+            let sealed = token.get_snapshot().seal(key)?;
+            let serialized = serde_json::to_string(&sealed)?;
             
             // In a real implementation, you would save to persistent storage
             // For now, we just return the serialized data
@@ -1006,21 +1427,41 @@ impl KnishIOClient {
     /// # Arguments
     ///
     /// * `storage_key` - Key for retrieving the token snapshot
+    /// * `serialized_data` - Serialized token data from `save_auth_token`
+    ///
+    /// # Returns
+    ///
+    /// Result with the restored auth token; `DecryptionKey` if the snapshot was sealed
+    /// under another secret
+    pub fn load_auth_token(&mut self, storage_key: &str, serialized_data: &str) -> Result<AuthToken> {
+        let secret = self.secret.clone()
+            .ok_or_else(|| KnishIOError::custom("Secret must be set before loading auth token"))?;
+        self.load_auth_token_with_key(storage_key, serialized_data, &SnapshotKey::Secret(secret))
+    }
+
+    /// Load authentication token saved with `save_auth_token_with_key`
+    ///
+    /// The client secret is still required to restore the token's wallet.
+    ///
+    /// # Arguments
+    ///
+    /// * `storage_key` - Key for retrieving the token snapshot
     /// * `serialized_data` - Serialized token data
+    /// * `key` - Key the snapshot was sealed with
     ///
     /// # Returns
     ///
     /// Result with the restored auth token
-    pub fn load_auth_token(&mut self, storage_key: &str, serialized_data: &str) -> Result<AuthToken> {
-        // Deserialize the token snapshot
-        let snapshot: crate::auth::AuthTokenSnapshot = serde_json::from_str(serialized_data)?;
+    pub fn load_auth_token_with_key(&mut self, storage_key: &str, serialized_data: &str, key: &SnapshotKey) -> Result<AuthToken> {
+        // Decrypt the token snapshot
+        let snapshot = SealedSnapshot::parse(serialized_data)?.open(key)?;
         
         // Get secret for restoration
         let secret = self.secret.as_deref()
             .ok_or_else(|| KnishIOError::custom("Secret must be set before loading auth token"))?;
             
         // Restore the auth token
-        let restored_token = AuthToken::restore(snapshot, secret)?;
+        let restored_token = AuthToken::restore_with_tokens(snapshot, secret, &self.system_tokens)?;
         
         // Set as current token
         self.set_auth_token(restored_token.clone());
@@ -1038,9 +1479,9 @@ impl KnishIOClient {
     ///
     /// # Returns
     ///
-    /// Optional reference to the auth token for the URI
+    /// Optional reference to the current bundle's auth token for the URI
     pub fn get_auth_token_for_uri(&self, uri: &str) -> Option<&AuthToken> {
-        self.auth_token_objects.get(uri)
+        self.auth_token_objects.peek(uri, self.bundle.as_deref())
     }
     
     /// Check if authentication is in progress (equivalent to isAuthInProgress in JS)
@@ -1060,6 +1501,11 @@ impl KnishIOClient {
     pub fn get_current_uri(&self) -> Option<String> {
         self.uris.get(self.current_uri_index).cloned()
     }
+
+    /// Get every configured node URI
+    pub fn get_uris(&self) -> &[String] {
+        &self.uris
+    }
     
     /// Set the user secret for cryptographic operations
     ///
@@ -1085,6 +1531,264 @@ impl KnishIOClient {
         self.encrypt = encrypt;
         self.log("info", &format!("Encryption {}", if encrypt { "enabled" } else { "disabled" }));
     }
+
+    /// Propose molecules anonymously
+    ///
+    /// Anonymous molecules are signed as usual but carry no bundle hash, matching JS
+    /// `Molecule.sign({ anonymous: true })`. This covers token transfers, metadata, rules,
+    /// token requests, identifiers, consolidation sweeps, buffer deposits and withdrawals
+    /// and trade rate updates. Escrows are refused: their records count only when the
+    /// depositor's or arbiter's bundle wrote them.
+    ///
+    /// # Arguments
+    ///
+    /// * `anonymous` - Whether to leave the bundle off new molecules
+    pub fn set_anonymous(&mut self, anonymous: bool) {
+        self.anonymous = anonymous;
+        self.log("info", &format!("Anonymous molecules {}", if anonymous { "enabled" } else { "disabled" }));
+    }
+
+    /// True if new molecules are proposed without the signer's bundle
+    pub fn is_anonymous(&self) -> bool {
+        self.anonymous
+    }
+
+    /// The bundle new molecules carry: this client's, or None when anonymous
+    pub(crate) fn molecule_bundle(&self) -> Option<String> {
+        self.bundle.clone().filter(|_| !self.anonymous)
+    }
+
+    /// Set the strategy used to pick stackable token units for amount-only transfers
+    ///
+    /// # Arguments
+    ///
+    /// * `selection` - Unit selection strategy (defaults to `UnitSelection::First`)
+    pub fn set_unit_selection(&mut self, selection: UnitSelection) {
+        self.unit_selection = selection;
+    }
+
+    /// Get the configured unit selection strategy
+    pub fn get_unit_selection(&self) -> UnitSelection {
+        self.unit_selection
+    }
+
+    /// Derive secrets from seeds with `config` instead of plain `generate_secret`
+    ///
+    /// Applies to `set_secret_from_seed` and the seed of `request_auth_token`. The
+    /// default is interop mode, which the other SDKs can reproduce.
+    ///
+    /// # Errors
+    ///
+    /// `ConfigurationError` if `config` does not validate
+    pub fn set_key_derivation(&mut self, config: KeyDerivationConfig) -> Result<()> {
+        config.validate()?;
+        self.key_derivation = config;
+        Ok(())
+    }
+
+    /// Get the configured secret derivation
+    pub fn key_derivation(&self) -> &KeyDerivationConfig {
+        &self.key_derivation
+    }
+
+    /// Derive the user secret from `seed` with the configured `KeyDerivationConfig`
+    ///
+    /// # Errors
+    ///
+    /// `ConfigurationError` if the derivation configuration does not validate
+    pub fn set_secret_from_seed(&mut self, seed: &str) -> Result<()> {
+        let secret = self.key_derivation.derive_secret(seed)?;
+        self.set_secret(secret);
+        Ok(())
+    }
+
+    /// Use `tokens` for ContinuID and authorization wallets instead of `USER` and `AUTH`
+    ///
+    /// For private deployments that renamed them. The stored ContinuID remainder is
+    /// dropped, since it belongs to the previous ContinuID token.
+    pub fn set_system_tokens(&mut self, tokens: SystemTokens) {
+        if tokens != self.system_tokens {
+            self.remainder_wallet = None;
+        }
+        self.system_tokens = tokens;
+    }
+
+    /// Token slugs of the ContinuID and authorization wallets
+    pub fn system_tokens(&self) -> &SystemTokens {
+        &self.system_tokens
+    }
+
+    /// An empty molecule using this client's system tokens
+    pub(crate) fn new_molecule(&self) -> Molecule {
+        let mut molecule = Molecule::new();
+        molecule.system_tokens = self.system_tokens.clone();
+        molecule
+    }
+
+    /// Set the retry policy `submit_molecule` applies before dead-lettering a molecule
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Retry policy (defaults to `RetryPolicy::default()`)
+    pub fn set_submit_policy(&mut self, policy: RetryPolicy) {
+        self.submit_policy = policy;
+    }
+
+    /// Get the retry policy of `submit_molecule`
+    pub fn get_submit_policy(&self) -> &RetryPolicy {
+        &self.submit_policy
+    }
+
+    /// Prefer `format` for GraphQL request and response bodies
+    ///
+    /// Falls back to JSON automatically if the node refuses the format.
+    pub fn set_wire_format(&mut self, format: WireFormat) {
+        if let Some(ref mut client) = self.client {
+            client.set_wire_format(format);
+        }
+        self.log("info", &format!("KnishIOClient::set_wire_format() - Using {}", format.content_type()));
+    }
+
+    /// Format GraphQL requests are currently sent in
+    pub fn wire_format(&self) -> WireFormat {
+        self.client.as_ref().map(GraphQLClient::wire_format).unwrap_or_default()
+    }
+
+    /// Hedge read queries across two of the configured URIs
+    ///
+    /// A query the current node has not answered within `delay` is also sent to the next
+    /// configured URI; whichever answers first wins and the slower request is cancelled.
+    /// Mutations always go to a single node. Pass `None` to turn hedging off.
+    ///
+    /// The duplicate carries the token stored for the current bundle on the hedge node,
+    /// if it is still valid. An authenticated client without such a token does not hedge;
+    /// authenticate against the hedge node and call this again to start.
+    ///
+    /// # Errors
+    ///
+    /// `ConfigurationError` when fewer than two distinct URIs are configured.
+    pub fn set_hedged_reads(&mut self, delay: Option<Duration>) -> Result<()> {
+        let Some(ref mut client) = self.client else {
+            return Err(KnishIOError::custom("GraphQL client not initialized"));
+        };
+
+        let hedge = match delay {
+            Some(delay) => {
+                let primary = client.get_uri().to_string();
+                let uri = self.uris.iter()
+                    .find(|uri| **uri != primary)
+                    .cloned()
+                    .ok_or_else(|| KnishIOError::ConfigurationError(
+                        "hedged reads need at least two distinct URIs".to_string()
+                    ))?;
+                let auth_token = self.auth_token_objects.peek(&uri, self.bundle.as_deref())
+                    .filter(|token| !token.is_expired())
+                    .map(|token| token.token().to_string());
+                Some(HedgeConfig { uri, delay, auth_token })
+            }
+            None => None,
+        };
+
+        let message = match hedge {
+            Some(ref hedge) => format!("Hedging reads to {} after {:?}", hedge.uri, hedge.delay),
+            None => "Hedging disabled".to_string(),
+        };
+        client.set_hedge(hedge);
+        self.log("info", &format!("KnishIOClient::set_hedged_reads() - {}", message));
+        Ok(())
+    }
+
+    /// Current hedged read settings, if enabled
+    pub fn hedged_reads(&self) -> Option<HedgeConfig> {
+        self.client.as_ref().and_then(|client| client.get_hedge().cloned())
+    }
+
+    /// Warn about queries and mutations slower or larger than `config` allows
+    ///
+    /// See `GraphQLClient::set_query_cost`; pass `None` to stop reporting.
+    pub fn set_query_cost(&mut self, config: Option<QueryCostConfig>) {
+        if let Some(ref mut client) = self.client {
+            client.set_query_cost(config);
+        }
+    }
+
+    /// Call `listener` with every slow or large operation warning
+    pub fn set_query_cost_listener(&mut self, listener: Option<QueryCostListener>) {
+        if let Some(ref mut client) = self.client {
+            client.set_query_cost_listener(listener);
+        }
+    }
+
+    /// Slow and large operation counters, if thresholds are set
+    pub fn query_cost_stats(&self) -> Option<QueryCostStats> {
+        self.client.as_ref().and_then(GraphQLClient::query_cost_stats)
+    }
+
+    /// Send queries and mutations by priority under `config`'s limits
+    ///
+    /// Balance queries go out at `RequestPriority::High` and chunked meta writes at
+    /// `Background`; see `GraphQLClient::set_scheduler`. Pass `None` to turn it off.
+    pub fn set_request_scheduler(&mut self, config: Option<SchedulerConfig>) {
+        if let Some(ref mut client) = self.client {
+            client.set_scheduler(config);
+        }
+    }
+
+    /// In-flight, waiting and sent request counters, if scheduling is on
+    pub fn request_scheduler_stats(&self) -> Option<SchedulerStats> {
+        self.client.as_ref().and_then(GraphQLClient::scheduler_stats)
+    }
+
+    /// The GraphQL client with `options` for the requests sent through it
+    pub(crate) fn client_with(&self, options: RequestOptions) -> Result<GraphQLClient> {
+        self.client.as_ref().map(|client| client.with_options(options)).ok_or(KnishIOError::NoClient)
+    }
+
+    /// Inject transport failures into this client's GraphQL traffic
+    ///
+    /// See `graphql::FaultInjector`. Pass `None` to stop injecting.
+    #[cfg(feature = "fault-injection")]
+    pub fn set_fault_injector(&mut self, injector: Option<crate::graphql::FaultInjector>) {
+        if let Some(ref mut client) = self.client {
+            client.set_fault_injector(injector);
+        }
+    }
+
+    /// Record this client's GraphQL traffic into a cassette, or replay it from one
+    ///
+    /// See `graphql::Cassette`. Pass `None` to go back to the network.
+    #[cfg(feature = "vcr")]
+    pub fn set_cassette(&mut self, cassette: Option<crate::graphql::Cassette>) {
+        if let Some(ref mut client) = self.client {
+            client.set_cassette(cassette);
+        }
+    }
+
+    /// Set the meta schema registry
+    ///
+    /// With a registry set, `create_meta` stamps registered meta types with their current
+    /// `schemaVersion`, and `query_meta_as` migrates older records before deserializing.
+    pub fn set_schema_registry(&mut self, registry: SchemaRegistry) {
+        self.schema_registry = Some(registry);
+    }
+
+    /// Get the meta schema registry
+    pub fn schema_registry(&self) -> Option<&SchemaRegistry> {
+        self.schema_registry.as_ref()
+    }
+
+    /// Set the fingerprint used to derive the guest AUTH wallet
+    ///
+    /// Defaults to `DefaultFingerprint` (the OS machine ID, falling back to a random
+    /// per-process ID).
+    pub fn set_fingerprint<F: Fingerprint + 'static>(&mut self, fingerprint: F) {
+        self.fingerprint = Arc::new(fingerprint);
+    }
+
+    /// Get the device fingerprint (matches JS getFingerprint)
+    pub fn get_fingerprint(&self) -> Result<String> {
+        self.fingerprint.fingerprint()
+    }
     
     // set_cell_slug already exists above
     
@@ -1179,6 +1883,27 @@ impl KnishIOClient {
     /// # Returns
     /// Balance information for the specified wallet/token
     pub async fn query_balance(&self, token: &str, bundle_hash: Option<&str>) -> Result<Wallet> {
+        self.query_balance_with_hydration(token, bundle_hash, WalletHydration::Standard).await
+    }
+
+    /// Query wallet balance, parsing only as much of the response as `hydration` asks for
+    ///
+    /// `WalletHydration::Minimal` is meant for tight balance-polling loops;
+    /// `WalletHydration::Full` also regenerates keys when the wallet belongs to this client's secret.
+    ///
+    /// # Parameters
+    /// - `token`: Token slug to query balance for
+    /// - `bundle_hash`: Optional bundle hash (uses client's bundle if not provided)
+    /// - `hydration`: Hydration level
+    ///
+    /// # Returns
+    /// Wallet with balance information
+    pub async fn query_balance_with_hydration(
+        &self,
+        token: &str,
+        bundle_hash: Option<&str>,
+        hydration: WalletHydration,
+    ) -> Result<Wallet> {
         use crate::query::balance::QueryBalance;
         use crate::query::Query;
 
@@ -1191,9 +1916,9 @@ impl KnishIOClient {
             query = query.with_bundle_hash(bundle);
         }
 
-        // Execute query through GraphQL client
-        if let Some(ref client) = self.client {
-            let response = query.execute(client, None, None).await?;
+        // Execute query through GraphQL client, ahead of bulk traffic
+        if let Ok(client) = self.client_with(RequestOptions::new().priority(RequestPriority::High)) {
+            let response = query.execute(&client, None, None).await?;
 
             // get_data() already navigates the data_key ("data.Balance") to the Balance wallet
             // object, so response.data() IS that object (not a wrapper). Tolerate the legacy
@@ -1201,7 +1926,7 @@ impl KnishIOClient {
             let response_data = response.data();
             let balance_data = response_data.get("Balance").unwrap_or(response_data);
             if balance_data.is_object() {
-                let wallet = Wallet::from_response_data(balance_data.clone())?;
+                let wallet = Wallet::from_response_data_with(balance_data.clone(), hydration, self.secret.as_deref())?;
                 return Ok(wallet);
             }
 
@@ -1220,6 +1945,24 @@ impl KnishIOClient {
     /// # Returns
     /// List of wallets matching the criteria
     pub async fn query_wallets(&self, bundle_hash: Option<&str>, token: Option<&str>) -> Result<Vec<Wallet>> {
+        self.query_wallets_with_hydration(bundle_hash, token, WalletHydration::Standard).await
+    }
+
+    /// Query wallets by bundle or token with a chosen hydration level
+    ///
+    /// # Parameters
+    /// - `bundle_hash`: Optional bundle hash to filter wallets
+    /// - `token`: Optional token to filter wallets
+    /// - `hydration`: Hydration level applied to every wallet
+    ///
+    /// # Returns
+    /// List of wallets matching the criteria
+    pub async fn query_wallets_with_hydration(
+        &self,
+        bundle_hash: Option<&str>,
+        token: Option<&str>,
+        hydration: WalletHydration,
+    ) -> Result<Vec<Wallet>> {
         use crate::query::wallet_list::QueryWalletList;
         use crate::query::Query;
 
@@ -1247,7 +1990,7 @@ impl KnishIOClient {
                 .or_else(|| response_data.get("WalletList").and_then(|v| v.as_array())) {
                 let wallets: Result<Vec<Wallet>> = wallets_data
                     .iter()
-                    .map(|wallet_data| Wallet::from_response_data(wallet_data.clone()))
+                    .map(|wallet_data| Wallet::from_response_data_with(wallet_data.clone(), hydration, self.secret.as_deref()))
                     .collect();
                 return wallets;
             }
@@ -1258,6 +2001,31 @@ impl KnishIOClient {
         }
     }
 
+    /// List the token units a bundle holds, across all of its wallets for `token`
+    ///
+    /// # Parameters
+    /// - `token`: Stackable token slug
+    /// - `bundle_hash`: Bundle to inventory (defaults to the client's bundle)
+    /// - `filter`: Optional metadata conditions every returned unit must satisfy
+    ///
+    /// # Returns
+    /// The matching units, each with the address, position and batch ID of its wallet
+    pub async fn query_token_units(
+        &self,
+        token: &str,
+        bundle_hash: Option<&str>,
+        filter: Option<&TokenUnitFilter>,
+    ) -> Result<Vec<HeldTokenUnit>> {
+        let wallets = self.query_wallets(bundle_hash, Some(token)).await?;
+        let units = HeldTokenUnit::collect(&wallets, filter.unwrap_or(&TokenUnitFilter::default()));
+
+        self.log("info", &format!(
+            "KnishIOClient::query_token_units() - {} {} units across {} wallets",
+            units.len(), token, wallets.len()
+        ));
+        Ok(units)
+    }
+
     /// Query bundle information
     ///
     /// # Parameters
@@ -1451,8 +2219,8 @@ impl KnishIOClient {
         // Query balance for this token
         let queried = self.query_balance(token, None).await?;
 
-        // Check if we have enough tokens (i128 for precision-safe comparison)
-        if queried.balance_as_i128() < (amount as i128) {
+        // Check if we have enough tokens (compared exactly, not as floats)
+        if !queried.balance_info().covers(amount) {
             return Err(KnishIOError::TransferBalance);
         }
 
@@ -1461,23 +2229,26 @@ impl KnishIOClient {
             return Err(KnishIOError::WalletCredential);
         }
 
+        self.signing_wallet(&queried, token)
+    }
+
+    /// Re-derive a queried wallet of this bundle so it can sign
+    fn signing_wallet(&self, queried: &Wallet, token: &str) -> Result<Wallet> {
         // The queried wallet has no secret/key (from_response_data sets secret=None), so it can't
         // sign — re-derive OUR signing wallet at the on-ledger position from our secret (matches JS
         // getSourceWallet: sourceWallet.key = generateKey(secret, token, position)). Same secret +
         // token + position reproduces the registered key/address, so the OTS verifies; without this
         // the molecule signs with no key -> "Signature malformed".
         let secret = self.secret.clone().ok_or(KnishIOError::Unauthenticated)?;
-        let mut source_wallet = Wallet::new(
-            Some(&secret),
-            queried.bundle.as_deref(),
-            Some(token),
-            None, // derive address from the key (must reproduce the registered address)
-            queried.position.as_deref(),
-            None,
-            queried.characters.as_deref(),
-        )?;
+        // The address is derived from the key (must reproduce the registered address)
+        let mut source_wallet = Wallet::from_params(WalletParams {
+            bundle: queried.bundle.clone(),
+            position: queried.position.clone(),
+            characters: queried.characters.clone(),
+            ..WalletParams::new().secret(&secret).token(token)
+        })?;
         source_wallet.balance = queried.balance.clone();
-        // Preserve the queried stackable token units on the signing wallet. Wallet::new above
+        // Preserve the queried stackable token units on the signing wallet. Wallet::from_params above
         // re-derives only the key/address, so without this the source carries NO token_units and a
         // stackable transfer silently degrades to fungible (the molecule emits no tokenUnits meta →
         // the validator's per-unit routing no-ops → units never move). The source's batch id
@@ -1502,7 +2273,7 @@ impl KnishIOClient {
         let bundle = bundle_hash.or(self.bundle.as_deref())
             .ok_or(KnishIOError::MissingBundle)?;
 
-        let query = QueryContinuId::new(bundle);
+        let query = QueryContinuId::new(bundle).with_token(self.system_tokens.user.as_str());
 
         // Execute query through GraphQL client
         if let Some(ref client) = self.client {
@@ -1558,6 +2329,63 @@ impl KnishIOClient {
         }
     }
 
+    /// Fetch every policy governing a meta instance and merge them by precedence
+    ///
+    /// Collects the instance policy, the meta type policy and, with `token` given, the
+    /// policy of that token (stored under meta type `token`). See
+    /// `policy_meta::effective` for the precedence rules. `self` permissions only match
+    /// once the instance owner is set with `EffectivePolicy::with_owner`.
+    ///
+    /// # Parameters
+    /// - `meta_type`: Meta type of the instance
+    /// - `meta_id`: Meta ID of the instance
+    /// - `token`: Slug of the token the instance belongs to, if any
+    pub async fn query_effective_policy(&self, meta_type: &str, meta_id: &str, token: Option<&str>) -> Result<EffectivePolicy> {
+        use crate::query::policy::QueryPolicy;
+
+        let mut levels = vec![
+            (PolicyLevel::Instance, QueryPolicy::by_meta(meta_type, meta_id)),
+            (PolicyLevel::MetaType, QueryPolicy::by_meta_type(meta_type)),
+        ];
+        if let Some(token) = token {
+            levels.push((PolicyLevel::Token, QueryPolicy::by_meta("token", token)));
+        }
+
+        let mut effective = EffectivePolicy::new();
+        for (level, query) in levels {
+            for record in self.policy_records(query).await? {
+                // A meta type query can also return the policies of individual instances
+                let record_id = record.get("metaId").and_then(Value::as_str).filter(|id| !id.is_empty());
+                if level == PolicyLevel::MetaType && record_id.is_some() {
+                    continue;
+                }
+                if let Some(source) = PolicySource::from_record(level, &record) {
+                    effective.add(source);
+                }
+            }
+        }
+
+        self.log("info", &format!(
+            "KnishIOClient::query_effective_policy() - {} policies apply to {}:{}",
+            effective.sources().len(), meta_type, meta_id
+        ));
+        Ok(effective)
+    }
+
+    /// Records returned by a `Policy` query, whether the node answers with one or a list
+    async fn policy_records(&self, query: crate::query::policy::QueryPolicy) -> Result<Vec<Value>> {
+        use crate::query::Query;
+
+        let client = self.client.as_ref().ok_or(KnishIOError::NoClient)?;
+        let response = query.execute(client, None, None).await?;
+        let data = response.data();
+        Ok(match data.get("Policy").unwrap_or(data) {
+            Value::Array(records) => records.clone(),
+            Value::Null => Vec::new(),
+            record => vec![record.clone()],
+        })
+    }
+
     /// Query active session information (matches JS queryActiveSession)
     ///
     /// # Parameters
@@ -1725,13 +2553,83 @@ impl KnishIOClient {
                     return Ok(meta_data.clone());
                 }
 
-                Ok(serde_json::json!(null))
-            } else {
-                Err(KnishIOError::NoClient)
-            }
+                Ok(serde_json::json!(null))
+            } else {
+                Err(KnishIOError::NoClient)
+            }
+        }
+    }
+
+    // =================== Watch-Only Methods ===================
+
+    /// Query the current balance of a watch-only wallet
+    ///
+    /// Uses the watched bundle hash rather than the client's own bundle, so no secret is
+    /// required on the client.
+    ///
+    /// # Parameters
+    /// - `watch`: Watch-only wallet to refresh
+    ///
+    /// # Returns
+    /// A new WatchWallet carrying the ledger's current balance and token units
+    pub async fn query_watch_balance(&self, watch: &WatchWallet) -> Result<WatchWallet> {
+        let wallet = self.query_balance(&watch.token, Some(&watch.bundle)).await?;
+        let mut refreshed = WatchWallet::try_from(&wallet)?;
+
+        // Keep the caller's address narrowing when the ledger didn't report one
+        if refreshed.address.is_none() {
+            refreshed.address = watch.address.clone();
+        }
+
+        Ok(refreshed)
+    }
+
+    /// Query all wallets of a watched bundle
+    ///
+    /// # Parameters
+    /// - `watch`: Watch-only wallet whose bundle is listed
+    /// - `token`: Optional token filter (all tokens when None)
+    ///
+    /// # Returns
+    /// Watch-only views of every wallet in the bundle
+    pub async fn query_watch_wallets(&self, watch: &WatchWallet, token: Option<&str>) -> Result<Vec<WatchWallet>> {
+        let wallets = self.query_wallets(Some(&watch.bundle), token).await?;
+        wallets.iter().map(WatchWallet::try_from).collect()
+    }
+
+    /// Query the atom activity of a watch-only wallet
+    ///
+    /// Filters by the watched address when one is set, otherwise by bundle and token.
+    ///
+    /// # Returns
+    /// List of atoms touching the watched wallet
+    pub async fn query_watch_activity(&self, watch: &WatchWallet) -> Result<Vec<serde_json::Value>> {
+        match watch.address.as_deref() {
+            Some(address) => self.query_atom(
+                None, None, None, Some(address), None, Some(&watch.token), None, None, None,
+            ).await,
+            None => self.query_atom(
+                None, Some(&watch.bundle), None, None, None, Some(&watch.token), None, None, None,
+            ).await,
         }
     }
 
+    /// Subscribe to wallet status changes of a watch-only wallet
+    pub async fn subscribe_watch_wallet<F>(&self, watch: &WatchWallet, callback: F) -> Result<SubscriptionHandle>
+    where
+        F: Fn(SubscriptionEvent) + Send + Sync + 'static,
+    {
+        self.subscribe_wallet_status(Some(watch.bundle.clone()), watch.token.clone(), callback).await
+    }
+
+    /// Subscribe to molecules created for a watch-only wallet's bundle
+    pub async fn subscribe_watch_molecules<F>(&self, watch: &WatchWallet, callback: F) -> Result<SubscriptionHandle>
+    where
+        F: Fn(SubscriptionEvent) + Send + Sync + 'static,
+    {
+        self.subscribe_create_molecule(Some(watch.bundle.clone()), callback).await
+    }
+
     // =================== Creation Methods ===================
 
     /// Create a new wallet
@@ -1748,18 +2646,12 @@ impl KnishIOClient {
         use crate::mutation::Mutation;
 
         // Create new wallet (matches JS line 1013-1016)
-        let new_wallet = Wallet::new(
-            Some(self.secret.as_ref().ok_or(KnishIOError::MissingSecret)?),
-            None,
-            Some(token),
-            None,
-            None,
-            None,
-            None,
-        )?;
+        let secret = self.secret.as_ref().ok_or(KnishIOError::MissingSecret)?;
+        let new_wallet = Wallet::from_params(WalletParams::new().secret(secret).token(token))?;
+        let new_wallet = self.avoid_position_collision(new_wallet, secret).await?;
 
         // Create mutation (matches JS lines 1021-1023)
-        let mut mutation = MutationCreateWallet::from_molecule(Molecule::new());
+        let mut mutation = MutationCreateWallet::from_molecule(self.new_molecule());
 
         // Fill molecule with wallet (matches JS line 1025)
         mutation.fill_molecule(&new_wallet)?;
@@ -1794,6 +2686,25 @@ impl KnishIOClient {
     /// # Returns
     /// Token creation response
     pub async fn create_token(
+        &mut self,
+        token: &str,
+        amount: Option<f64>,
+        meta: Option<HashMap<String, Value>>,
+        batch_id: Option<&str>,
+        units: Vec<String>
+    ) -> Result<Box<dyn Response>> {
+        let context = self.hooks.run_before(OperationContext::new(Operation::CreateToken)
+            .token(token)
+            .amount(amount, &units)
+            .meta(meta.clone().unwrap_or_default())).await?;
+        let meta = if meta.is_none() && context.meta.is_empty() { None } else { Some(context.meta.clone()) };
+
+        let result = self.run_create_token(token, amount, meta, batch_id, units).await;
+        self.finish_operation(context, result).await
+    }
+
+    /// Body of `create_token`, run between its hooks
+    async fn run_create_token(
         &mut self,
         token: &str,
         mut amount: Option<f64>,
@@ -1803,10 +2714,10 @@ impl KnishIOClient {
     ) -> Result<Box<dyn Response>> {
         use crate::mutation::create_token::{MutationCreateToken, CreateTokenParams};
         use crate::mutation::Mutation;
-        use crate::crypto::generate_batch_id;
 
         // Ensure we have authentication
         self.ensure_authentication(None).await?;
+        self.require_auth_scope("create_token", AuthScope::Profile)?;
 
         // Get fungibility mode from meta (matches JS line 1160)
         let fungibility = meta.as_ref()
@@ -1816,14 +2727,9 @@ impl KnishIOClient {
 
         // For stackable tokens - create a batch ID (matches JS lines 1163-1165)
         let final_batch_id = if fungibility == "stackable" {
-            if let Some(bid) = batch_id {
-                Some(bid.to_string())
-            } else {
-                // Generate batch ID using crypto utility
-                Some(generate_batch_id())
-            }
+            Some(self.batch_id_or_new(batch_id)?)
         } else {
-            batch_id.map(|s| s.to_string())
+            batch_id.map(|bid| self.batch_id_or_new(Some(bid))).transpose()?
         };
 
         // Special logic for token unit initialization (nonfungible || stackable) (matches JS lines 1168-1184)
@@ -1862,17 +2768,14 @@ impl KnishIOClient {
         }
 
         // Creating the wallet that will receive the new tokens (matches JS lines 1187-1192).
-        // Wallet::new args: (secret, bundle, token, address, position, batch_id, characters) —
-        // final_batch_id belongs in the batch_id (6th) slot, NOT the address slot.
-        let recipient_wallet = Wallet::new(
-            Some(self.secret.as_ref().ok_or(KnishIOError::MissingSecret)?),
-            Some(self.bundle.as_ref().ok_or(KnishIOError::MissingBundle)?),
-            Some(token),
-            None,                       // address (derived from the key)
-            None,                       // position (auto-generated)
-            final_batch_id.as_deref(),  // batch_id
-            None,                       // characters
-        )?;
+        // final_batch_id is the wallet's batch ID, NOT its address.
+        let recipient_wallet = Wallet::from_params(WalletParams {
+            batch_id: final_batch_id.clone(),
+            ..WalletParams::new()
+                .secret(self.secret.as_ref().ok_or(KnishIOError::MissingSecret)?)
+                .bundle(self.bundle.as_ref().ok_or(KnishIOError::MissingBundle)?)
+                .token(token)
+        })?;
 
         // Resolve the USER source wallet (ContinuID chain head) + a remainder, and set them on the
         // molecule. init_token_creation builds the C-atom + ContinuID I-atom FROM source_wallet, so
@@ -1881,7 +2784,7 @@ impl KnishIOClient {
         let secret = self.secret.clone().ok_or(KnishIOError::MissingSecret)?;
         let remainder_wallet = source_wallet.create_remainder(&secret)?;
 
-        let mut molecule = Molecule::new();
+        let mut molecule = self.new_molecule();
         molecule.secret = Some(secret);             // sign() derives the OTS key from molecule.secret
         molecule.source_wallet = Some(source_wallet);
         molecule.remainder_wallet = Some(remainder_wallet);
@@ -1920,7 +2823,8 @@ impl KnishIOClient {
     /// - `bundle_hash`: Recipient bundle hash
     /// - `token`: Token slug to transfer
     /// - `amount`: Amount to transfer (optional if units provided)
-    /// - `units`: Token units to transfer (optional)
+    /// - `units`: Token units to transfer (optional; for stackable tokens an amount alone
+    ///   selects that many units using the client's `UnitSelection` strategy)
     /// - `batch_id`: Batch ID for recipient (optional)
     /// - `source_wallet`: Source wallet (optional, will be queried if not provided)
     ///
@@ -1930,16 +2834,56 @@ impl KnishIOClient {
         &mut self,
         bundle_hash: &str,
         token: &str,
-        mut amount: Option<f64>,
+        amount: Option<f64>,
+        units: Vec<String>,
+        batch_id: Option<&str>,
+        source_wallet: Option<Wallet>
+    ) -> Result<Box<dyn Response>> {
+        let context = self.hooks.run_before(OperationContext::new(Operation::TransferToken)
+            .token(token)
+            .amount(amount, &units)
+            .recipients(vec![bundle_hash.to_string()])).await?;
+
+        let result = self.run_transfer_token(bundle_hash, token, amount, units, batch_id, source_wallet).await;
+        self.finish_operation(context, result).await
+    }
+
+    /// Body of `transfer_token`, run between its hooks
+    async fn run_transfer_token(
+        &mut self,
+        bundle_hash: &str,
+        token: &str,
+        amount: Option<f64>,
         units: Vec<String>,
         batch_id: Option<&str>,
         source_wallet: Option<Wallet>
     ) -> Result<Box<dyn Response>> {
-        use crate::mutation::transfer_tokens::{MutationTransferTokens, TransferTokensParams};
         use crate::mutation::Mutation;
 
+        let mutation = self.prepare_transfer_token(bundle_hash, token, amount, units, batch_id, source_wallet).await?;
+
+        // Execute mutation (matches JS line 1716)
+        let client = self.client.as_ref()
+            .ok_or(KnishIOError::NoClient)?;
+
+        mutation.execute(client, None, None).await
+    }
+
+    /// Build and sign the molecule of `transfer_token` without proposing it
+    pub(crate) async fn prepare_transfer_token(
+        &mut self,
+        bundle_hash: &str,
+        token: &str,
+        mut amount: Option<f64>,
+        mut units: Vec<String>,
+        batch_id: Option<&str>,
+        source_wallet: Option<Wallet>
+    ) -> Result<crate::mutation::transfer_tokens::MutationTransferTokens> {
+        use crate::mutation::transfer_tokens::{MutationTransferTokens, TransferTokensParams};
+
         // Ensure we have authentication
         self.ensure_authentication(None).await?;
+        self.require_auth_scope("transfer_token", AuthScope::Profile)?;
 
         // Calculate amount & set meta key (matches JS lines 1649-1656)
         if !units.is_empty() {
@@ -1951,6 +2895,9 @@ impl KnishIOClient {
             amount = Some(units.len() as f64);
         }
 
+        // Reject amounts more precise than the token's decimals
+        self.validate_token_amount(token, amount.unwrap_or(0.0)).await?;
+
         // Get a source wallet (matches JS lines 1659-1664)
         let mut source_wallet = if let Some(wallet) = source_wallet {
             wallet
@@ -1958,8 +2905,23 @@ impl KnishIOClient {
             self.query_source_wallet(token, amount.unwrap_or(0.0), None).await?
         };
 
-        // Do you have enough tokens? (i128 for precision-safe comparison)
-        if source_wallet.balance_as_i128() < (amount.unwrap_or(0.0) as i128) {
+        // Stackable token with only an amount: pick that many units from the source wallet
+        if units.is_empty() && !source_wallet.token_units.is_empty() {
+            let requested = amount.unwrap_or(0.0);
+            if requested <= 0.0 || requested.fract() != 0.0 {
+                return Err(KnishIOError::StackableUnitAmount);
+            }
+
+            units = self.unit_selection.select(&source_wallet.token_units, requested as usize)?;
+            self.log("info", &format!(
+                "KnishIOClient::transfer_token() - Selected {} units ({:?}) for amount-only transfer",
+                units.len(),
+                self.unit_selection
+            ));
+        }
+
+        // Do you have enough tokens? (compared exactly, not as floats)
+        if !source_wallet.balance_info().covers(amount.unwrap_or(0.0)) {
             return Err(KnishIOError::TransferBalance);
         }
 
@@ -1973,16 +2935,12 @@ impl KnishIOClient {
         )?;
 
         // Compute the batch ID for the recipient (matches JS lines 1678-1685)
-        if let Some(bid) = batch_id {
-            recipient_wallet.batch_id = Some(bid.to_string());
-        } else {
-            recipient_wallet.init_batch_id(Some(&source_wallet), false);
-        }
+        self.init_recipient_batch_id(&mut recipient_wallet, &source_wallet, batch_id)?;
 
         // Create a remainder from the source wallet (matches JS line 1688)
         let secret = self.secret.as_ref()
             .ok_or(KnishIOError::MissingSecret)?;
-        let mut remainder_wallet = source_wallet.create_remainder(secret)?;
+        let mut remainder_wallet = self.avoid_position_collision(source_wallet.create_remainder(secret)?, secret).await?;
 
         // Token units splitting (matches JS lines 1691-1695)
         if !units.is_empty() {
@@ -1990,15 +2948,16 @@ impl KnishIOClient {
         }
 
         // Build the molecule itself (matches JS lines 1699-1702)
-        let mut molecule = Molecule::new();
+        let mut molecule = self.new_molecule();
         // sign() derives the OTS key from molecule.secret (generate_key(secret, token, position));
         // without it the signing block is skipped -> unsigned molecule -> "Signature malformed".
         molecule.secret = Some(secret.clone());
-        molecule.source_wallet = Some(source_wallet.clone());
-        molecule.remainder_wallet = Some(remainder_wallet.clone());
+        molecule.source_wallet = Some(source_wallet);
+        molecule.remainder_wallet = Some(remainder_wallet);
+        molecule.cell_slug = self.cell_slug.clone();
 
         // Create mutation (matches JS lines 1706-1709)
-        let mut mutation = MutationTransferTokens::from_molecule(molecule);
+        let mut mutation = MutationTransferTokens::from_molecule(molecule).anonymous(self.anonymous);
 
         // Fill molecule (matches JS lines 1711-1714)
         mutation.fill_molecule(TransferTokensParams {
@@ -2006,11 +2965,7 @@ impl KnishIOClient {
             amount: amount.unwrap_or(0.0),
         })?;
 
-        // Execute mutation (matches JS line 1716)
-        let client = self.client.as_ref()
-            .ok_or(KnishIOError::NoClient)?;
-
-        mutation.execute(client, None, None).await
+        Ok(mutation)
     }
 
     /// Transfer tokens to MULTIPLE recipients in a single molecule (WP line 544).
@@ -2029,12 +2984,32 @@ impl KnishIOClient {
         token: &str,
         recipients: Vec<TransferRecipient>,
         source_wallet: Option<Wallet>,
+    ) -> Result<Box<dyn Response>> {
+        let total = recipients.iter()
+            .map(|r| if r.units.is_empty() { r.amount.unwrap_or(0.0) } else { r.units.len() as f64 })
+            .sum();
+        let context = self.hooks.run_before(OperationContext::new(Operation::TransferTokens)
+            .token(token)
+            .amount(Some(total), &[])
+            .recipients(recipients.iter().map(|r| r.bundle_hash.clone()).collect())).await?;
+
+        let result = self.run_transfer_tokens(token, recipients, source_wallet).await;
+        self.finish_operation(context, result).await
+    }
+
+    /// Body of `transfer_tokens`, run between its hooks
+    async fn run_transfer_tokens(
+        &mut self,
+        token: &str,
+        recipients: Vec<TransferRecipient>,
+        source_wallet: Option<Wallet>,
     ) -> Result<Box<dyn Response>> {
         use crate::mutation::transfer_tokens::{MutationTransferTokens, MultiTransferTokensParams};
         use crate::mutation::Mutation;
 
         // Ensure we have authentication
         self.ensure_authentication(None).await?;
+        self.require_auth_scope("transfer_tokens", AuthScope::Profile)?;
 
         // Per-recipient amount: units.len() for stackable, else the explicit amount
         let mut amounts: Vec<f64> = Vec::with_capacity(recipients.len());
@@ -2050,6 +3025,9 @@ impl KnishIOClient {
             }
         }
         let total: f64 = amounts.iter().sum();
+        for amount in &amounts {
+            self.validate_token_amount(token, *amount).await?;
+        }
 
         // Get a source wallet (loads its token units)
         let mut source_wallet = if let Some(wallet) = source_wallet {
@@ -2059,7 +3037,7 @@ impl KnishIOClient {
         };
 
         // Do you have enough tokens?
-        if source_wallet.balance_as_i128() < (total as i128) {
+        if !source_wallet.balance_info().covers(total) {
             return Err(KnishIOError::TransferBalance);
         }
 
@@ -2073,11 +3051,7 @@ impl KnishIOClient {
                 None,
                 None,
             )?;
-            if let Some(ref bid) = recipient.batch_id {
-                recipient_wallet.batch_id = Some(bid.clone());
-            } else {
-                recipient_wallet.init_batch_id(Some(&source_wallet), false);
-            }
+            self.init_recipient_batch_id(&mut recipient_wallet, &source_wallet, recipient.batch_id.as_deref())?;
             recipient_wallets.push(recipient_wallet);
         }
 
@@ -2092,13 +3066,13 @@ impl KnishIOClient {
         source_wallet.split_units_multi(&unit_lists, &mut recipient_wallets, &mut remainder_wallet);
 
         // Build the molecule itself
-        let mut molecule = Molecule::new();
+        let mut molecule = self.new_molecule();
         molecule.secret = Some(secret.clone());
-        molecule.source_wallet = Some(source_wallet.clone());
-        molecule.remainder_wallet = Some(remainder_wallet.clone());
+        molecule.source_wallet = Some(source_wallet);
+        molecule.remainder_wallet = Some(remainder_wallet);
 
         // Create mutation + fill (multi) + execute
-        let mut mutation = MutationTransferTokens::from_molecule(molecule);
+        let mut mutation = MutationTransferTokens::from_molecule(molecule).anonymous(self.anonymous);
         mutation.fill_molecule_multi(MultiTransferTokensParams {
             recipient_wallets,
             amounts,
@@ -2125,6 +3099,33 @@ impl KnishIOClient {
     /// # Returns
     /// Token request response
     pub async fn request_tokens(
+        &mut self,
+        token: &str,
+        to: Option<RecipientType>,
+        amount: Option<f64>,
+        units: Vec<String>,
+        meta: Option<HashMap<String, Value>>,
+        batch_id: Option<&str>
+    ) -> Result<Box<dyn Response>> {
+        let recipient = match &to {
+            Some(RecipientType::BundleHash(bundle_hash)) => Some(bundle_hash.clone()),
+            Some(RecipientType::Secret(secret)) => Some(crate::crypto::generate_bundle_hash(secret)),
+            Some(RecipientType::Wallet(wallet)) => wallet.bundle.clone(),
+            None => self.bundle.clone(),
+        };
+        let context = self.hooks.run_before(OperationContext::new(Operation::RequestTokens)
+            .token(token)
+            .amount(amount, &units)
+            .recipients(recipient.into_iter().collect())
+            .meta(meta.clone().unwrap_or_default())).await?;
+        let meta = if meta.is_none() && context.meta.is_empty() { None } else { Some(context.meta.clone()) };
+
+        let result = self.run_request_tokens(token, to, amount, units, meta, batch_id).await;
+        self.finish_operation(context, result).await
+    }
+
+    /// Body of `request_tokens`, run between its hooks
+    async fn run_request_tokens(
         &mut self,
         token: &str,
         to: Option<RecipientType>,
@@ -2135,10 +3136,10 @@ impl KnishIOClient {
     ) -> Result<Box<dyn Response>> {
         use crate::mutation::request_tokens::{MutationRequestTokens, RequestTokensParams};
         use crate::mutation::Mutation;
-        use crate::crypto::generate_batch_id;
 
         // Ensure we have authentication
         self.ensure_authentication(None).await?;
+        self.require_auth_scope("request_tokens", AuthScope::Profile)?;
 
         // Initialize meta (matches JS line 1482)
         let mut meta_map = meta.unwrap_or_default();
@@ -2158,11 +3159,11 @@ impl KnishIOClient {
         let final_batch_id = if !is_stackable && batch_id.is_some() {
             // NON-stackable tokens & batch ID is NOT NULL - error
             return Err(KnishIOError::BatchId);
-        } else if is_stackable && batch_id.is_none() {
-            // Stackable tokens & batch ID is NULL - generate new one
-            Some(generate_batch_id())
+        } else if is_stackable {
+            // Stackable tokens - validate the batch ID, or generate a new one when NULL
+            Some(self.batch_id_or_new(batch_id)?)
         } else {
-            batch_id.map(|s| s.to_string())
+            None
         };
 
         // Calculate amount & set meta key (matches JS lines 1501-1510)
@@ -2215,7 +3216,7 @@ impl KnishIOClient {
         };
 
         // Create mutation (matches JS lines 1544-1546)
-        let mut mutation = MutationRequestTokens::from_molecule(Molecule::new());
+        let mut mutation = MutationRequestTokens::from_molecule(self.new_molecule()).anonymous(self.anonymous);
 
         // Fill molecule (matches JS lines 1548-1555)
         mutation.fill_molecule(RequestTokensParams {
@@ -2255,6 +3256,22 @@ impl KnishIOClient {
     /// # Returns
     /// Burn response
     pub async fn burn_tokens(
+        &mut self,
+        token: &str,
+        amount: Option<f64>,
+        units: Vec<String>,
+        source_wallet: Option<Wallet>
+    ) -> Result<Box<dyn Response>> {
+        let context = self.hooks.run_before(OperationContext::new(Operation::BurnTokens)
+            .token(token)
+            .amount(amount, &units)).await?;
+
+        let result = self.run_burn_tokens(token, amount, units, source_wallet).await;
+        self.finish_operation(context, result).await
+    }
+
+    /// Body of `burn_tokens`, run between its hooks
+    async fn run_burn_tokens(
         &mut self,
         token: &str,
         mut amount: Option<f64>,
@@ -2266,6 +3283,9 @@ impl KnishIOClient {
 
         // Ensure we have authentication
         self.ensure_authentication(None).await?;
+        self.require_auth_scope("burn_tokens", AuthScope::Profile)?;
+
+        self.validate_token_amount(token, amount.unwrap_or(0.0)).await?;
 
         // Get a source wallet (matches JS lines 1831-1836)
         let mut source_wallet = if let Some(wallet) = source_wallet {
@@ -2294,13 +3314,13 @@ impl KnishIOClient {
         }
 
         // Create a molecule (matches JS lines 1860-1863)
-        let mut molecule = Molecule::new();
+        let mut molecule = self.new_molecule();
         // Set the molecule secret so sign() can derive the OTS key — Molecule::new() leaves it
         // None; without this, sign() hits the no-secret branch and returns SignatureMalformed.
         // (transfer_token sets this too; burn_tokens' path was previously unexercised.)
         molecule.secret = Some(secret.clone());
-        molecule.source_wallet = Some(source_wallet.clone());
-        molecule.remainder_wallet = Some(remainder_wallet.clone());
+        molecule.source_wallet = Some(source_wallet);
+        molecule.remainder_wallet = Some(remainder_wallet);
 
         // Burn token (matches JS line 1864)
         molecule.burn_token(amount.unwrap_or(0.0), None)?;
@@ -2309,10 +3329,10 @@ impl KnishIOClient {
         let bundle = self.bundle.clone();
         molecule.sign(bundle, false, false)?;
 
-        // Check molecule (matches JS line 1868). Pass the source wallet so CheckMolecule::isotope_v
+        // Check molecule (matches JS line 1868). Pass the source wallet so ValueValidator
         // validates the 3-atom value molecule via its sender branch (remainder = balance + (-balance)
-        // = 0 == sum). With sender=None, isotope_v rejects any 3-atom V molecule (TransferRemainder).
-        molecule.check(Some(&source_wallet))?;
+        // = 0 == sum). With sender=None, it rejects any 3-atom V molecule (TransferRemainder).
+        molecule.check(molecule.source_wallet.as_ref())?;
 
         // Create & execute a mutation (matches JS lines 1871-1875)
         let mutation = MutationProposeMolecule::from_molecule(molecule);
@@ -2349,12 +3369,29 @@ impl KnishIOClient {
         amount: Option<f64>,
         units: Vec<String>,
         source_wallet: Option<Wallet>
+    ) -> Result<Box<dyn Response>> {
+        let context = self.hooks.run_before(OperationContext::new(Operation::ReplenishToken)
+            .token(token)
+            .amount(amount, &units)).await?;
+
+        let result = self.run_replenish_token(token, amount, units, source_wallet).await;
+        self.finish_operation(context, result).await
+    }
+
+    /// Body of `replenish_token`, run between its hooks
+    async fn run_replenish_token(
+        &mut self,
+        token: &str,
+        amount: Option<f64>,
+        units: Vec<String>,
+        source_wallet: Option<Wallet>
     ) -> Result<Box<dyn Response>> {
         use crate::mutation::propose_molecule::MutationProposeMolecule;
         use crate::mutation::Mutation;
 
         // Ensure we have authentication
         self.ensure_authentication(None).await?;
+        self.require_auth_scope("replenish_token", AuthScope::Profile)?;
 
         // If no source wallet, query balance (matches JS lines 1893-1898)
         let source_wallet = if let Some(wallet) = source_wallet {
@@ -2375,9 +3412,9 @@ impl KnishIOClient {
         let remainder_wallet = source_wallet.create_remainder(secret)?;
 
         // Create a molecule (matches JS lines 1904-1907)
-        let mut molecule = Molecule::new();
-        molecule.source_wallet = Some(source_wallet.clone());
-        molecule.remainder_wallet = Some(remainder_wallet.clone());
+        let mut molecule = self.new_molecule();
+        molecule.source_wallet = Some(source_wallet);
+        molecule.remainder_wallet = Some(remainder_wallet);
 
         // Replenish token (matches JS lines 1908-1911)
         molecule.replenish_token(amount.unwrap_or(0.0), Some(units))?;
@@ -2432,6 +3469,7 @@ impl KnishIOClient {
 
         // Ensure we have authentication
         self.ensure_authentication(None).await?;
+        self.require_auth_scope("fuse_token", AuthScope::Profile)?;
 
         // Get source wallet (matches JS lines 1941-1943)
         let mut source_wallet = if let Some(wallet) = source_wallet {
@@ -2479,7 +3517,7 @@ impl KnishIOClient {
         )?;
 
         // Set batch ID (matches JS line 1974)
-        recipient_wallet.init_batch_id(Some(&source_wallet), false);
+        self.init_recipient_batch_id(&mut recipient_wallet, &source_wallet, None)?;
 
         // Create remainder wallet (matches JS line 1977)
         let secret = self.secret.as_ref()
@@ -2497,16 +3535,17 @@ impl KnishIOClient {
         );
         recipient_wallet.token_units = vec![new_token_unit];
 
-        // Create a molecule (matches JS lines 1987-1990)
-        let mut molecule = Molecule::new();
-        molecule.source_wallet = Some(source_wallet.clone());
-        molecule.remainder_wallet = Some(remainder_wallet);
-
-        // Fuse token (matches JS line 1991)
         // Extract IDs from token units (after split_units, source_wallet contains only fused units)
         let fused_ids: Vec<String> = source_wallet.token_units.iter()
             .map(|unit| unit.id.clone())
             .collect();
+
+        // Create a molecule (matches JS lines 1987-1990)
+        let mut molecule = self.new_molecule();
+        molecule.source_wallet = Some(source_wallet);
+        molecule.remainder_wallet = Some(remainder_wallet);
+
+        // Fuse token (matches JS line 1991)
         molecule.fuse_token(fused_ids, &recipient_wallet)?;
 
         // Sign molecule (matches JS lines 1992-1994)
@@ -2549,6 +3588,7 @@ impl KnishIOClient {
 
         // Ensure we have authentication
         self.ensure_authentication(None).await?;
+        self.require_auth_scope("deposit_buffer_token", AuthScope::Profile)?;
 
         self.log("info", &format!("KnishIOClient::deposit_buffer_token() - Depositing {} of {} to buffer...", amount, token));
 
@@ -2559,12 +3599,16 @@ impl KnishIOClient {
             self.query_source_wallet(token, amount, None).await?
         };
 
-        // Create molecule with source wallet
-        let mut molecule = Molecule::new();
+        // Create molecule with source wallet; the change goes to a fresh remainder wallet
+        let secret = self.secret.clone().ok_or(KnishIOError::MissingSecret)?;
+        let mut molecule = self.new_molecule();
+        molecule.remainder_wallet = Some(source_wallet.create_remainder(&secret)?);
+        molecule.secret = Some(secret);
+        molecule.bundle = self.molecule_bundle();
         molecule.source_wallet = Some(source_wallet);
 
         // Create mutation (matches TS line 1851)
-        let mut mutation = MutationDepositBufferToken::from_molecule(molecule);
+        let mut mutation = MutationDepositBufferToken::from_molecule(molecule).anonymous(self.anonymous);
 
         // Fill molecule (matches TS lines 1854-1859)
         mutation.fill_molecule(DepositBufferTokenParams {
@@ -2603,6 +3647,7 @@ impl KnishIOClient {
 
         // Ensure we have authentication
         self.ensure_authentication(None).await?;
+        self.require_auth_scope("withdraw_buffer_token", AuthScope::Profile)?;
 
         self.log("info", &format!("KnishIOClient::withdraw_buffer_token() - Withdrawing {} of {} from buffer...", amount, token));
 
@@ -2614,11 +3659,11 @@ impl KnishIOClient {
         };
 
         // Create molecule with source wallet
-        let mut molecule = Molecule::new();
+        let mut molecule = self.new_molecule();
         molecule.source_wallet = Some(source_wallet);
 
         // Create mutation (matches TS line 1895)
-        let mut mutation = MutationWithdrawBufferToken::from_molecule(molecule);
+        let mut mutation = MutationWithdrawBufferToken::from_molecule(molecule).anonymous(self.anonymous);
 
         // Fill molecule (matches TS lines 1898-1903 and JS lines 1806-1811)
         // Create recipients map: bundle -> amount (matches JS lines 1806-1807)
@@ -2758,11 +3803,11 @@ impl KnishIOClient {
         let secret = self.secret.as_ref()
             .ok_or(KnishIOError::MissingSecret)?;
 
-        let mut molecule = Molecule::new();
+        let mut molecule = self.new_molecule();
         molecule.secret = Some(secret.clone());
 
         // Create mutation (matches JS lines 1228-1235)
-        let mut mutation = MutationCreateRule::from_molecule(molecule);
+        let mut mutation = MutationCreateRule::from_molecule(molecule).anonymous(self.anonymous);
 
         // Fill molecule with rule data (matches JS lines 1237-1242)
         mutation.fill_molecule(CreateRuleParams {
@@ -2801,22 +3846,39 @@ impl KnishIOClient {
         meta_id: &str,
         meta: HashMap<String, Value>,
         policy: Option<HashMap<String, Value>>
+    ) -> Result<Box<dyn Response>> {
+        let mut context = OperationContext::new(Operation::CreateMeta).meta(meta);
+        context.meta_type = Some(meta_type.to_string());
+        context.meta_id = Some(meta_id.to_string());
+        let context = self.hooks.run_before(context).await?;
+
+        let result = self.run_create_meta(meta_type, meta_id, context.meta.clone(), policy).await;
+        self.finish_operation(context, result).await
+    }
+
+    /// Body of `create_meta`, run between its hooks
+    async fn run_create_meta(
+        &mut self,
+        meta_type: &str,
+        meta_id: &str,
+        mut meta: HashMap<String, Value>,
+        policy: Option<HashMap<String, Value>>
     ) -> Result<Box<dyn Response>> {
         use crate::mutation::create_meta::{MutationCreateMeta, CreateMetaParams};
         use crate::mutation::Mutation;
 
-        // Create molecule with secret and source wallet (matches JS lines 1267-1271)
-        let secret = self.secret.as_ref()
-            .ok_or(KnishIOError::MissingSecret)?;
-
-        let mut molecule = Molecule::new();
-        molecule.secret = Some(secret.clone());
+        // Stamp the schema version of registered meta types (unless the caller already did)
+        if let Some(ref registry) = self.schema_registry {
+            if !meta.contains_key(crate::meta::SCHEMA_VERSION_KEY) {
+                registry.stamp(meta_type, &mut meta);
+            }
+        }
 
-        // For now, we'll skip the complex source_wallet logic and just set the secret
-        // The mutation's fill_molecule will handle the rest
+        // Create molecule with secret and source wallet (matches JS lines 1267-1271)
+        let molecule = self.create_molecule(None, None, None, None).await?;
 
         // Create mutation (matches JS lines 1265-1272)
-        let mut mutation = MutationCreateMeta::from_molecule(molecule);
+        let mut mutation = MutationCreateMeta::from_molecule(molecule).anonymous(self.anonymous);
 
         // Fill molecule with metadata (matches JS lines 1276-1281)
         mutation.fill_molecule(CreateMetaParams {
@@ -2833,6 +3895,40 @@ impl KnishIOClient {
         mutation.execute(client, None, None).await
     }
 
+    /// Create metadata from a serializable value
+    ///
+    /// The value must serialize to a JSON object; each field becomes a meta key. Registered
+    /// meta types are stamped with their current schema version.
+    pub async fn create_meta_as<T: serde::Serialize>(
+        &mut self,
+        meta_type: &str,
+        meta_id: &str,
+        value: &T,
+        policy: Option<HashMap<String, Value>>
+    ) -> Result<Box<dyn Response>> {
+        let meta = self.schema_registry.clone().unwrap_or_default().encode(meta_type, value)?;
+        self.create_meta(meta_type, meta_id, meta, policy).await
+    }
+
+    /// Query the latest metadata of one meta instance into `T`
+    ///
+    /// Records written under an older schema version are migrated through the schema
+    /// registry first.
+    ///
+    /// # Returns
+    /// None if the instance has no metadata
+    pub async fn query_meta_as<T: serde::de::DeserializeOwned>(&self, meta_type: &str, meta_id: &str) -> Result<Option<T>> {
+        let result = self.query_meta(meta_type, Some(meta_id), None, None, Some(false)).await?;
+        let items = latest_meta_items(&result, meta_id);
+
+        if items.is_empty() {
+            return Ok(None);
+        }
+
+        let registry = self.schema_registry.clone().unwrap_or_default();
+        registry.decode(meta_type, &items).map(Some)
+    }
+
     /// Create identifier
     ///
     /// Matches JS createIdentifier({ type, contact, code }) at lines 1294-1313
@@ -2849,7 +3945,7 @@ impl KnishIOClient {
         use crate::mutation::Mutation;
 
         // Create mutation (matches JS lines 1302-1304)
-        let mut mutation = MutationCreateIdentifier::from_molecule(Molecule::new());
+        let mut mutation = MutationCreateIdentifier::from_molecule(self.new_molecule()).anonymous(self.anonymous);
 
         // Fill molecule with identifier data (matches JS lines 1306-1310)
         mutation.fill_molecule(CreateIdentifierParams {
@@ -2865,6 +3961,64 @@ impl KnishIOClient {
         mutation.execute(client, None, None).await
     }
 
+    /// Link an external keypair to this client's bundle
+    ///
+    /// The external key signs a challenge bound to the bundle hash and the proof is
+    /// stored as meta on an identifier molecule (identifier type = key scheme).
+    ///
+    /// # Parameters
+    /// - `signer`: External key to link
+    /// - `nonce`: Challenge nonce (a random UUID when `None`)
+    ///
+    /// # Returns
+    /// Propose molecule response
+    pub async fn create_identity_proof(
+        &mut self,
+        signer: &dyn ExternalSigner,
+        nonce: Option<&str>,
+    ) -> Result<Box<dyn Response>> {
+        let bundle = self.get_bundle()
+            .ok_or(KnishIOError::MissingBundle)?
+            .to_string();
+        let nonce = nonce.map(|n| n.to_string())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+        self.log("info", &format!("KnishIOClient::create_identity_proof() - Linking {} key to bundle {}...", signer.scheme(), bundle));
+
+        let proof = IdentityProof::create(signer, &bundle, &nonce)?;
+
+        let mut molecule = self.create_molecule(None, None, None, None).await?;
+        molecule.init_identity_proof(&proof)?;
+        molecule.sign(None, false, true)?;
+        molecule.check(None)?;
+
+        self.propose_molecule(molecule).await
+    }
+
+    /// Read external-key identity proofs linked to a bundle and verify them
+    ///
+    /// Only proofs of the verifier's scheme are returned; identifiers without a
+    /// complete proof are skipped, and a proof that cannot be checked is reported invalid.
+    ///
+    /// # Parameters
+    /// - `bundle_hash`: Bundle whose identifiers to read
+    /// - `verifier`: Signature verifier for one key scheme
+    ///
+    /// # Returns
+    /// Each proof with its verification result
+    pub async fn query_identity_proofs(
+        &self,
+        bundle_hash: &str,
+        verifier: &dyn ExternalVerifier,
+    ) -> Result<Vec<VerifiedIdentityProof>> {
+        let atoms = self.query_atom(
+            None, Some(bundle_hash), None, None, Some("C"), None, None,
+            Some("identifier"), Some(verifier.scheme().as_str()),
+        ).await?;
+
+        Ok(VerifiedIdentityProof::from_atoms(&atoms, bundle_hash, verifier))
+    }
+
     /// Link an identifier to a wallet bundle
     ///
     /// Matches TS linkIdentifier({ type, contact }) at lines 1731-1763
@@ -2979,41 +4133,47 @@ impl KnishIOClient {
     /// # Parameters
     /// - `meta_type`: Type of metadata
     /// - `meta_id`: Metadata ID
-    /// - `policy`: Policy definition
+    /// - `policy`: Policy definition: `read` and/or `write`, each mapping meta keys to
+    ///   lists of `all`, `self` or bundle hashes
     ///
     /// # Returns
     /// Created policy response
+    ///
+    /// # Errors
+    /// `PolicyInvalid` if `policy` is not a policy object (see `PolicyMeta::validate`)
     pub async fn create_policy(
         &mut self,
         meta_type: &str,
         meta_id: &str,
-        _policy: HashMap<String, Value>
+        policy: HashMap<String, Value>
     ) -> Result<Box<dyn Response>> {
         use crate::mutation::propose_molecule::MutationProposeMolecule;
         use crate::mutation::Mutation;
 
+        let policy = Value::Object(policy.into_iter().collect());
+        crate::policy_meta::PolicyMeta::validate(&policy)?;
+
         // Ensure we have authentication (matches JS: client must be authenticated)
         self.ensure_authentication(None).await?;
+        self.require_auth_scope("create_policy", AuthScope::Profile)?;
 
         // Create molecule with secret and source wallet (matches JS line 1330)
         let secret = self.secret.as_ref()
             .ok_or(KnishIOError::MissingSecret)?;
 
-        let mut molecule = Molecule::new();
+        let mut molecule = self.new_molecule();
         molecule.secret = Some(secret.clone());
 
         // Get source wallet for the molecule (amount=0.0 since we're just creating a policy atom)
-        let source_wallet = self.query_source_wallet("USER", 0.0, None).await?;
+        let source_wallet = self.query_source_wallet(&self.system_tokens.user, 0.0, None).await?;
         molecule.source_wallet = Some(source_wallet);
 
         // Add policy atom (matches JS lines 1331-1336)
-        // Note: Currently passes empty Vec for meta (matching JS's meta: {})
-        // Policy parameter is not yet used by add_policy_atom (TODO in TIER 6.1)
         molecule.add_policy_atom(
             meta_type,
             meta_id,
             Vec::new(), // Empty meta matching JS's meta: {}
-            None, // Policy handling is TODO in add_policy_atom
+            Some(&policy.to_string()),
         )?;
 
         // Add ContinuID atom (matches JS line 1337)
@@ -3053,20 +4213,10 @@ impl KnishIOClient {
             self.cell_slug = Some(slug.to_string());
         }
 
-        // Create wallet from fingerprint alternative
-        // JS: generateSecret(await this.getFingerprint())
-        // Rust: Use fixed seed since we don't have browser fingerprinting
-        let secret = generate_secret("guest-default-seed");
+        // Create wallet from fingerprint (matches JS: generateSecret(await this.getFingerprint()))
+        let secret = generate_secret(&self.get_fingerprint()?);
 
-        let wallet = Wallet::new(
-            Some(&secret),
-            None,
-            Some("AUTH"),
-            None,
-            None,
-            None,
-            None,
-        )?;
+        let wallet = Wallet::from_params(WalletParams::new().secret(&secret).token(self.system_tokens.auth.as_str()))?;
 
         // Create mutation
         if let Some(ref client) = self.client.clone() {
@@ -3117,9 +4267,10 @@ impl KnishIOClient {
                     encrypt_setting,
                     pubkey,
                     wallet,
-                );
+                ).with_scope(AuthScope::Guest).with_clock(self.clock.clone());
 
                 // Set in client (matches JS: this.setAuthToken(authToken))
+                self.rotate_socket_auth(Some(auth_token.token().to_string()));
                 self.auth_token = Some(auth_token.clone());
 
                 Ok(auth_token)
@@ -3152,18 +4303,10 @@ impl KnishIOClient {
         self.secret = Some(secret.to_string());
 
         // Create AUTH wallet from secret
-        let wallet = Wallet::new(
-            Some(secret),
-            None,
-            Some("AUTH"),
-            None,
-            None,
-            None,
-            None,
-        )?;
+        let wallet = Wallet::from_params(WalletParams::new().secret(secret).token(self.system_tokens.auth.as_str()))?;
 
         // Create molecule with secret and source wallet
-        let mut molecule = Molecule::new();
+        let mut molecule = self.new_molecule();
         molecule.secret = Some(secret.to_string());
         molecule.source_wallet = Some(wallet.clone());
 
@@ -3172,7 +4315,7 @@ impl KnishIOClient {
         // secret-fallback (which silently swallows Wallet::create errors with .ok()). The I-atom
         // registers the bundle's ContinuID relay head on-ledger so subsequent molecules advance the
         // chain instead of falling to fresh genesis. Carries the AUTH source's characters for parity.
-        let remainder = Wallet::create(Some(secret), None, "USER", None, wallet.characters.as_deref())?;
+        let remainder = Wallet::create(Some(secret), None, &self.system_tokens.user, None, wallet.characters.as_deref())?;
         molecule.remainder_wallet = Some(remainder);
 
         // Create mutation
@@ -3203,24 +4346,7 @@ impl KnishIOClient {
                     .ok_or(KnishIOError::InvalidResponse)?;
                 let payload_field = pm.get("payload")
                     .ok_or(KnishIOError::InvalidResponse)?;
-                let payload: serde_json::Value = if payload_field.is_string() {
-                    serde_json::from_str(payload_field.as_str().unwrap_or(""))
-                        .map_err(|_| KnishIOError::InvalidResponse)?
-                } else {
-                    payload_field.clone()
-                };
-
-                let token_str = payload.get("token")
-                    .and_then(|t| t.as_str())
-                    .ok_or(KnishIOError::InvalidResponse)?
-                    .to_string();
-
-                let expires_at = payload.get("expiresAt")
-                    .and_then(|e| e.as_i64());
-
-                let pubkey = payload.get("pubkey")
-                    .and_then(|p| p.as_str())
-                    .map(|s| s.to_string());
+                let AuthPayload { token: token_str, expires_at, pubkey, .. } = decode_payload(payload_field)?;
 
                 // Propagate the JWT to the GraphQL client so subsequent (non-public) requests
                 // carry the X-Auth-Token header — the client's mutate()/query() read their OWN
@@ -3229,6 +4355,7 @@ impl KnishIOClient {
                 if let Some(ref mut client) = self.client {
                     client.set_auth_data(token_str.clone(), pubkey.clone(), None);
                 }
+                self.rotate_socket_auth(Some(token_str.clone()));
 
                 // Create AuthToken (matches JS: AuthToken.create(response.payload(), wallet))
                 let auth_token = AuthToken::create(
@@ -3237,7 +4364,7 @@ impl KnishIOClient {
                     encrypt,
                     pubkey,
                     wallet,
-                );
+                ).with_scope(AuthScope::Profile).with_clock(self.clock.clone());
 
                 // Store in self.auth_token
                 self.auth_token = Some(auth_token.clone());
@@ -3269,7 +4396,7 @@ impl KnishIOClient {
     ///
     /// # Parameters
     /// - `secret`: Optional user secret for profile auth
-    /// - `seed`: Optional seed to generate secret from
+    /// - `seed`: Optional seed to derive the secret from, with the configured `KeyDerivationConfig`
     /// - `cell_slug`: Optional cell slug for guest auth
     /// - `encrypt`: Optional encryption setting
     ///
@@ -3282,8 +4409,6 @@ impl KnishIOClient {
         cell_slug: Option<&str>,
         encrypt: Option<bool>
     ) -> Result<AuthToken> {
-        use crate::crypto::generate_secret;
-
         // SDK versions 2 and below do not utilize an authorization token (matches JS line 2118-2122)
         if self.server_sdk_version < 3 {
             self.log("warn", "KnishIOClient::request_auth_token() - Server SDK version does not require an authorization...");
@@ -3294,7 +4419,7 @@ impl KnishIOClient {
         let mut working_secret = secret.map(|s| s.to_string());
         if working_secret.is_none() {
             if let Some(s) = seed {
-                working_secret = Some(generate_secret(s));
+                working_secret = Some(self.key_derivation.derive_secret(s)?);
             }
         }
 
@@ -3367,6 +4492,30 @@ impl KnishIOClient {
     }
 }
 
+/// Meta items of `meta_id` in a `MetaType` query result, keeping the latest value per key
+fn latest_meta_items(result: &Value, meta_id: &str) -> Vec<MetaItem> {
+    let meta_types = match result {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+
+    let mut items: Vec<MetaItem> = Vec::new();
+    for instance in meta_types.iter()
+        .filter_map(|t| t.get("instances").and_then(|i| i.as_array()))
+        .flatten()
+        .filter(|i| i.get("metaId").and_then(|id| id.as_str()) == Some(meta_id))
+    {
+        for meta in instance.get("metas").and_then(|m| m.as_array()).into_iter().flatten() {
+            if let (Some(key), Some(value)) = (meta.get("key").and_then(|k| k.as_str()), meta.get("value").and_then(|v| v.as_str())) {
+                items.retain(|item| item.key != key);
+                items.push(MetaItem::new(key, value));
+            }
+        }
+    }
+
+    items
+}
+
 /// URI parameter enum to support both single URI and multiple URIs
 pub enum UriParam {
     Single(String),
@@ -3411,6 +4560,7 @@ impl Clone for KnishIOClient {
             bundle: self.bundle.clone(),
             auth_token: self.auth_token.clone(),
             auth_token_objects: self.auth_token_objects.clone(),
+            clock: self.clock.clone(),
             auth_in_process: self.auth_in_process,
             server_sdk_version: self.server_sdk_version,
             encrypt: self.encrypt,
@@ -3419,9 +4569,23 @@ impl Clone for KnishIOClient {
             socket_config: self.socket_config.clone(),
             websocket_client: None, // Don't clone websocket client
             subscription_manager: self.subscription_manager.clone(),
+            websocket_manager: self.websocket_manager.clone(),
             remainder_wallet: self.remainder_wallet.clone(),
             last_molecule_query: self.last_molecule_query.clone(),
             abort_controllers: Arc::new(Mutex::new(HashMap::new())), // Create new Arc for clone
+            unit_selection: self.unit_selection,
+            schema_report: self.schema_report.clone(),
+            system_tokens: self.system_tokens.clone(),
+            schema_registry: self.schema_registry.clone(),
+            fingerprint: self.fingerprint.clone(),
+            submit_policy: self.submit_policy.clone(),
+            dead_letters: self.dead_letters.clone(),
+            token_registry: self.token_registry.clone(),
+            wallet_collision_retries: self.wallet_collision_retries,
+            hooks: self.hooks.clone(),
+            batch_namespace: self.batch_namespace.clone(),
+            key_derivation: self.key_derivation.clone(),
+            anonymous: self.anonymous,
         }
     }
 }
@@ -0,0 +1,92 @@
+//! Transfers paid from several source wallets
+//!
+//! A molecule carries one signature, made with the key of its first atom's wallet, so it
+//! can debit one source wallet only; the node protocol has no way to sign a second debit.
+//! `transfer_from_sources` takes the legs of a payment (e.g. a price in one token and a
+//! fee in another) and sends a single leg as a plain transfer, but refuses several with
+//! `MultiSourceUnsupported` rather than splitting them over molecules that could settle
+//! apart.
+
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::response::Response;
+
+/// One token of a multi-source transfer
+#[derive(Debug, Clone)]
+pub struct SourceLeg {
+    /// Token slug
+    pub token: String,
+    /// Amount of `token` to send
+    pub amount: f64,
+    /// Recipient bundle hash
+    pub bundle_hash: String,
+    /// Optional explicit batch ID for the recipient's wallet
+    pub batch_id: Option<String>,
+}
+
+impl SourceLeg {
+    /// Send `amount` of `token` to `bundle_hash`
+    pub fn new(token: &str, amount: f64, bundle_hash: &str) -> Self {
+        SourceLeg {
+            token: token.to_string(),
+            amount,
+            bundle_hash: bundle_hash.to_string(),
+            batch_id: None,
+        }
+    }
+}
+
+impl KnishIOClient {
+    /// Send every leg atomically, each debiting the client's wallet of its token
+    ///
+    /// Only one leg fits in a molecule; it goes through `transfer_token`, hooks included.
+    ///
+    /// # Errors
+    ///
+    /// `AtomsMissing` without legs, `MultiSourceUnsupported` for more than one, or the
+    /// errors of `transfer_token`
+    pub async fn transfer_from_sources(&mut self, legs: Vec<SourceLeg>) -> Result<Box<dyn Response>> {
+        let leg = match legs.as_slice() {
+            [] => return Err(KnishIOError::AtomsMissing),
+            [leg] => leg,
+            _ => {
+                return Err(KnishIOError::MultiSourceUnsupported(format!(
+                    "{} source wallets in one molecule; a molecule is signed by one wallet",
+                    legs.len()
+                )))
+            }
+        };
+
+        self.transfer_token(&leg.bundle_hash, &leg.token, Some(leg.amount), Vec::new(), leg.batch_id.as_deref(), None).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SourceLeg;
+    use crate::crypto::{generate_bundle_hash, generate_secret};
+    use crate::error::KnishIOError;
+    use crate::test_ledger::TestLedger;
+
+    #[tokio::test]
+    async fn test_only_one_source_fits_a_molecule() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("multi-source-payer");
+        let merchant = generate_bundle_hash(&generate_secret("multi-source-merchant"));
+        let payer = generate_bundle_hash(&secret);
+        ledger.fund(&secret, "GOLD", 100.0).unwrap();
+        ledger.fund(&secret, "FEE", 10.0).unwrap();
+        let mut client = ledger.client(&secret);
+
+        let legs = vec![SourceLeg::new("GOLD", 40.0, &merchant), SourceLeg::new("FEE", 1.0, &merchant)];
+        assert!(matches!(client.transfer_from_sources(legs).await, Err(KnishIOError::MultiSourceUnsupported(_))));
+        assert!(matches!(client.transfer_from_sources(Vec::new()).await, Err(KnishIOError::AtomsMissing)));
+        assert!(ledger.molecules().is_empty());
+        assert_eq!(ledger.balance(&payer, "FEE"), 10.0);
+
+        let response = client.transfer_from_sources(vec![SourceLeg::new("GOLD", 40.0, &merchant)]).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        assert_eq!(ledger.balance(&merchant, "GOLD"), 40.0);
+        assert_eq!(ledger.balance(&payer, "GOLD"), 60.0);
+    }
+}
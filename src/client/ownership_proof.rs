@@ -0,0 +1,83 @@
+//! Ownership proofs from the client's secret
+//!
+//! `ProofTree` leaf keys can be derived from any secret for any bundle, so a proof only
+//! counts under a tree root the verifier already trusts for the bundle. Trees stay local:
+//! `proof_tree` builds one without proposing anything, the application hands its root to
+//! the verifier once, and `prove_ownership` answers challenges off-ledger. The verifier
+//! checks answers with `Wallet::verify_ownership_proof` against the roots it keeps.
+
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::wallet::{OwnershipProof, ProofTree};
+
+impl KnishIOClient {
+    /// Build the client's proof tree of `height`
+    ///
+    /// The tree is derived from the secret, so the same height always yields the same root.
+    ///
+    /// # Errors
+    ///
+    /// `MissingSecret` without a secret, `ConfigurationError` for an unsupported height
+    pub fn proof_tree(&self, height: u32) -> Result<ProofTree> {
+        let secret = self.secret.as_deref().ok_or(KnishIOError::MissingSecret)?;
+        ProofTree::new(secret, height)
+    }
+
+    /// Answer `challenge` off-ledger with leaf `leaf` of `tree`
+    ///
+    /// Nothing is proposed. The caller picks a leaf it has not used before and persists
+    /// that choice; signing two challenges with one leaf leaks its key.
+    ///
+    /// # Errors
+    ///
+    /// `MissingSecret` without a secret, `WalletCredential` for another bundle's tree
+    pub fn prove_ownership(&self, tree: &ProofTree, leaf: u32, challenge: &str) -> Result<OwnershipProof> {
+        let secret = self.secret.as_deref().ok_or(KnishIOError::MissingSecret)?;
+        tree.prove(secret, challenge, leaf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::{generate_bundle_hash, generate_secret};
+    use crate::test_ledger::TestLedger;
+    use crate::wallet::{ProofTree, Wallet};
+
+    #[tokio::test]
+    async fn test_proofs_are_answered_off_ledger() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("proof-owner");
+        let bundle = generate_bundle_hash(&secret);
+        let client = ledger.client(&secret);
+        let proposed = ledger.molecules().len();
+
+        let tree = client.proof_tree(2).unwrap();
+        let trusted = vec![tree.root().to_string()];
+        assert_eq!(client.proof_tree(2).unwrap().root(), tree.root());
+
+        let first = client.prove_ownership(&tree, 0, "login-7").unwrap();
+        let second = client.prove_ownership(&tree, 1, "login-8").unwrap();
+        assert_eq!(ledger.molecules().len(), proposed);
+        assert!(Wallet::verify_ownership_proof(&first, &bundle, "login-7", &trusted));
+        assert!(Wallet::verify_ownership_proof(&second, &bundle, "login-8", &trusted));
+        assert!(!Wallet::verify_ownership_proof(&first, &bundle, "login-8", &trusted));
+    }
+
+    #[tokio::test]
+    async fn test_proof_tree_is_bound_to_the_trusted_root() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("proof-owner");
+        let bundle = generate_bundle_hash(&secret);
+        let client = ledger.client(&secret);
+        let trusted = vec![client.proof_tree(1).unwrap().root().to_string()];
+
+        // A tree of the same bundle the verifier was never given, and a foreign tree relabelled for it
+        let untrusted = client.prove_ownership(&client.proof_tree(2).unwrap(), 0, "login-9").unwrap();
+        assert!(!Wallet::verify_ownership_proof(&untrusted, &bundle, "login-9", &trusted));
+
+        let forger = generate_secret("proof-forger");
+        let mut forged = ProofTree::new(&forger, 1).unwrap().prove(&forger, "login-9", 0).unwrap();
+        forged.bundle = bundle.clone();
+        assert!(!Wallet::verify_ownership_proof(&forged, &bundle, "login-9", &trusted));
+    }
+}
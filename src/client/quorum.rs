@@ -0,0 +1,311 @@
+//! Multi-node quorum submission
+//!
+//! For high-assurance writes the same signed molecule can be proposed to several
+//! configured nodes at once. Each node's accept/reject answer is collected and
+//! reconciled into a `QuorumReport`, which states whether a majority of the
+//! contacted nodes accepted the molecule and whether they agreed on its hash.
+
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::graphql::GraphQLClient;
+use crate::molecule::Molecule;
+use crate::mutation::propose_molecule::MutationProposeMolecule;
+use crate::mutation::Mutation;
+use crate::response::{Response, ResponseUtils};
+
+/// Result of proposing the molecule to a single node
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeOutcome {
+    /// The node accepted the molecule
+    Accepted {
+        /// Molecular hash reported by the node
+        molecular_hash: Option<String>,
+    },
+    /// The node answered but rejected the molecule
+    Rejected {
+        /// Rejection reason reported by the node
+        reason: Option<String>,
+    },
+    /// The node could not be reached or returned an unusable response
+    Failed {
+        /// Transport or protocol error message
+        error: String,
+    },
+}
+
+/// One node's part in a quorum submission
+#[derive(Debug, Clone)]
+pub struct NodeSubmission {
+    /// URI of the node the molecule was proposed to
+    pub uri: String,
+    /// What that node answered
+    pub outcome: NodeOutcome,
+}
+
+/// Overall verdict of a quorum submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuorumStatus {
+    /// A majority of nodes accepted and all accepting nodes agree on the molecular hash
+    Reached,
+    /// A majority of nodes rejected the molecule
+    Rejected,
+    /// Neither side reached a majority (failures, split votes or conflicting hashes)
+    Inconclusive,
+}
+
+/// Reconciled answers of all nodes a molecule was proposed to
+#[derive(Debug, Clone)]
+pub struct QuorumReport {
+    /// Number of agreeing nodes required for a verdict
+    pub threshold: usize,
+    /// Per-node outcomes, in the order the nodes were contacted
+    pub submissions: Vec<NodeSubmission>,
+}
+
+impl QuorumReport {
+    /// Reconcile per-node outcomes using a simple-majority threshold
+    pub fn new(submissions: Vec<NodeSubmission>) -> Self {
+        QuorumReport {
+            threshold: submissions.len() / 2 + 1,
+            submissions,
+        }
+    }
+
+    /// Number of nodes that accepted the molecule
+    pub fn accepted_count(&self) -> usize {
+        self.submissions.iter()
+            .filter(|s| matches!(s.outcome, NodeOutcome::Accepted { .. }))
+            .count()
+    }
+
+    /// Number of nodes that rejected the molecule
+    pub fn rejected_count(&self) -> usize {
+        self.submissions.iter()
+            .filter(|s| matches!(s.outcome, NodeOutcome::Rejected { .. }))
+            .count()
+    }
+
+    /// Number of nodes that could not give an answer
+    pub fn failed_count(&self) -> usize {
+        self.submissions.iter()
+            .filter(|s| matches!(s.outcome, NodeOutcome::Failed { .. }))
+            .count()
+    }
+
+    /// Distinct molecular hashes reported by accepting nodes
+    pub fn accepted_hashes(&self) -> Vec<String> {
+        let mut hashes: Vec<String> = self.submissions.iter()
+            .filter_map(|s| match &s.outcome {
+                NodeOutcome::Accepted { molecular_hash } => molecular_hash.clone(),
+                _ => None,
+            })
+            .collect();
+        hashes.sort();
+        hashes.dedup();
+        hashes
+    }
+
+    /// Overall quorum verdict
+    pub fn status(&self) -> QuorumStatus {
+        if self.accepted_count() >= self.threshold && self.accepted_hashes().len() <= 1 {
+            QuorumStatus::Reached
+        } else if self.rejected_count() >= self.threshold {
+            QuorumStatus::Rejected
+        } else {
+            QuorumStatus::Inconclusive
+        }
+    }
+
+    /// True when the quorum accepted the molecule
+    pub fn is_reached(&self) -> bool {
+        self.status() == QuorumStatus::Reached
+    }
+}
+
+impl KnishIOClient {
+    /// Propose the same signed molecule to `n` configured nodes concurrently
+    ///
+    /// The first `n` configured URIs are contacted, each through its own GraphQL client
+    /// carrying this client's encryption setting and the token stored for the current
+    /// bundle on that node. Tokens are issued per node, so once this client is
+    /// authenticated a node without a valid token of its own is reported as failed and
+    /// not contacted. Individual node failures are recorded in the report rather than
+    /// returned as errors.
+    ///
+    /// # Arguments
+    ///
+    /// * `molecule` - A pre-built and pre-signed Molecule
+    /// * `n` - Number of nodes to submit to
+    ///
+    /// # Returns
+    ///
+    /// Quorum status plus per-node outcomes
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigurationError` if `n` is zero or exceeds the number of configured URIs
+    pub async fn propose_molecule_quorum(&self, molecule: Molecule, n: usize) -> Result<QuorumReport> {
+        if n == 0 || n > self.uris.len() {
+            return Err(KnishIOError::ConfigurationError(format!(
+                "Quorum of {} nodes requested but {} URIs are configured",
+                n,
+                self.uris.len()
+            )));
+        }
+
+        self.log("info", &format!("KnishIOClient::propose_molecule_quorum() - Proposing molecule to {} nodes...", n));
+
+        let current_uri = self.get_current_uri();
+        let current_token = self.client.as_ref().and_then(|c| c.get_auth_token());
+        let authenticated = current_token.is_some() || self.auth_token.is_some();
+
+        let wire_format = self.wire_format();
+        let submissions = self.uris.iter().take(n).map(|uri| {
+            // The node's own token; the live token only ever goes to the node it came from
+            let credentials = self.auth_token_objects.peek(uri, self.bundle.as_deref())
+                .filter(|token| !token.is_expired())
+                .map(|token| (token.token().to_string(), token.get_pubkey().map(str::to_string)))
+                .or_else(|| {
+                    let token = current_token.clone().filter(|_| current_uri.as_deref() == Some(uri.as_str()))?;
+                    Some((token, self.auth_token.as_ref().and_then(|t| t.get_pubkey()).map(str::to_string)))
+                });
+
+            let mut node_client = GraphQLClient::new(uri.clone());
+            if let Some((ref token, ref pubkey)) = credentials {
+                node_client.set_auth_data(token.clone(), pubkey.clone(), None);
+            }
+            node_client.set_encryption(self.encrypt);
+            node_client.set_wire_format(wire_format);
+
+            let mutation = MutationProposeMolecule::from_molecule(molecule.clone());
+            let uri = uri.clone();
+            let unauthenticated = authenticated && credentials.is_none();
+
+            async move {
+                if unauthenticated {
+                    let error = format!("No auth token for {}; authenticate against this node first", uri);
+                    return NodeSubmission { uri, outcome: NodeOutcome::Failed { error } };
+                }
+                let outcome = match mutation.execute(&node_client, None, None).await {
+                    Ok(response) => Self::node_outcome(response.as_ref()),
+                    Err(e) => NodeOutcome::Failed { error: e.to_string() },
+                };
+                NodeSubmission { uri, outcome }
+            }
+        });
+
+        let report = QuorumReport::new(futures::future::join_all(submissions).await);
+
+        self.log("info", &format!(
+            "KnishIOClient::propose_molecule_quorum() - {} accepted, {} rejected, {} failed: {:?}",
+            report.accepted_count(),
+            report.rejected_count(),
+            report.failed_count(),
+            report.status()
+        ));
+
+        Ok(report)
+    }
+
+    /// Classify a single node's ProposeMolecule response
+    fn node_outcome(response: &dyn Response) -> NodeOutcome {
+        if ResponseUtils::is_molecular_accepted(response) {
+            NodeOutcome::Accepted {
+                molecular_hash: ResponseUtils::extract_molecular_hash(response),
+            }
+        } else if response.status().is_some() {
+            NodeOutcome::Rejected { reason: response.reason() }
+        } else {
+            NodeOutcome::Failed {
+                error: response.error().unwrap_or_else(|| "Empty response".to_string()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submission(uri: &str, outcome: NodeOutcome) -> NodeSubmission {
+        NodeSubmission { uri: uri.to_string(), outcome }
+    }
+
+    fn accepted(hash: &str) -> NodeOutcome {
+        NodeOutcome::Accepted { molecular_hash: Some(hash.to_string()) }
+    }
+
+    fn rejected() -> NodeOutcome {
+        NodeOutcome::Rejected { reason: Some("rejected".to_string()) }
+    }
+
+    #[test]
+    fn test_majority_accept_reaches_quorum() {
+        let report = QuorumReport::new(vec![
+            submission("https://a", accepted("hash")),
+            submission("https://b", accepted("hash")),
+            submission("https://c", rejected()),
+        ]);
+
+        assert_eq!(report.threshold, 2);
+        assert_eq!(report.accepted_count(), 2);
+        assert_eq!(report.rejected_count(), 1);
+        assert_eq!(report.status(), QuorumStatus::Reached);
+        assert!(report.is_reached());
+    }
+
+    #[test]
+    fn test_majority_reject() {
+        let report = QuorumReport::new(vec![
+            submission("https://a", rejected()),
+            submission("https://b", rejected()),
+            submission("https://c", accepted("hash")),
+        ]);
+
+        assert_eq!(report.status(), QuorumStatus::Rejected);
+    }
+
+    #[test]
+    fn test_failures_and_hash_conflicts_are_inconclusive() {
+        let failures = QuorumReport::new(vec![
+            submission("https://a", accepted("hash")),
+            submission("https://b", NodeOutcome::Failed { error: "timeout".to_string() }),
+        ]);
+        assert_eq!(failures.threshold, 2);
+        assert_eq!(failures.failed_count(), 1);
+        assert_eq!(failures.status(), QuorumStatus::Inconclusive);
+
+        let conflict = QuorumReport::new(vec![
+            submission("https://a", accepted("hash-1")),
+            submission("https://b", accepted("hash-2")),
+            submission("https://c", accepted("hash-1")),
+        ]);
+        assert_eq!(conflict.accepted_hashes().len(), 2);
+        assert_eq!(conflict.status(), QuorumStatus::Inconclusive);
+    }
+
+    #[tokio::test]
+    async fn test_quorum_size_validation() {
+        let client = KnishIOClient::new("http://localhost:8080", None, None, None, Some(3), Some(false));
+
+        assert!(client.propose_molecule_quorum(Molecule::new(), 0).await.is_err());
+        assert!(client.propose_molecule_quorum(Molecule::new(), 2).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_nodes_without_their_own_token_fail() {
+        use crate::auth::AuthToken;
+        use crate::client::builder::ClientBuilder;
+
+        let ledger = crate::test_ledger::TestLedger::start().await.unwrap();
+        let other = "http://127.0.0.1:9/graphql";
+        let mut client = ClientBuilder::new().uris(vec![ledger.uri(), other]).build().unwrap();
+        let expires = chrono::Utc::now().timestamp_millis() + 3_600_000;
+        client.set_auth_token(AuthToken::new("ledger-token".to_string(), Some(expires), None, None));
+
+        let report = client.propose_molecule_quorum(Molecule::new(), 2).await.unwrap();
+        let outcome = |uri: &str| report.submissions.iter().find(|s| s.uri == uri).map(|s| s.outcome.clone()).unwrap();
+        assert!(matches!(outcome(other), NodeOutcome::Failed { error } if error.starts_with("No auth token")));
+        assert!(!matches!(outcome(ledger.uri()), NodeOutcome::Failed { error } if error.starts_with("No auth token")));
+    }
+}
@@ -0,0 +1,117 @@
+//! Wallet key rotation
+//!
+//! A wallet's position determines its signing key and its ML-KEM public key. After a
+//! suspected key exposure the usual hygiene is to abandon the position: `rotate_wallet`
+//! creates a fresh position for the token and moves the wallet's whole balance (and, for
+//! stackable tokens, every unit) there in a single molecule. The old position is spent by
+//! that molecule and never signs again; the new wallet's pubkey and characters are
+//! published on the credit atom.
+
+use crate::auth::AuthScope;
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::mutation::propose_molecule::MutationProposeMolecule;
+use crate::mutation::Mutation;
+use crate::response::Response;
+use crate::wallet::Wallet;
+
+impl KnishIOClient {
+    /// Move this bundle's `token` wallet to a fresh position and pubkey
+    ///
+    /// The new wallet keeps the old one's batch ID and characters.
+    ///
+    /// ```no_run
+    /// # async fn demo(client: &mut knishio_client::KnishIOClient) -> knishio_client::Result<()> {
+    /// let response = client.rotate_wallet("GOLD").await?;
+    /// assert!(response.success());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `WalletNotFound` when the bundle holds no funded `token` wallet, or
+    /// `WalletShadow` when its balance sits in an unclaimed shadow wallet
+    pub async fn rotate_wallet(&mut self, token: &str) -> Result<Box<dyn Response>> {
+        self.ensure_authentication(None).await?;
+        self.require_auth_scope("rotate_wallet", AuthScope::Profile)?;
+
+        let secret = self.secret.clone().ok_or(KnishIOError::MissingSecret)?;
+        // No Balance object comes back when the bundle holds no funded `token` wallet
+        let current = self.query_balance(token, None).await.map_err(|e| match e {
+            KnishIOError::InvalidResponse => KnishIOError::WalletNotFound,
+            e => e,
+        })?;
+        if current.is_shadow() {
+            return Err(KnishIOError::WalletShadow);
+        }
+        let source_wallet = self.signing_wallet(&current, token)?;
+
+        let new_wallet = Wallet::create(Some(&secret), None, token, None, source_wallet.characters.as_deref())?;
+        let mut new_wallet = self.avoid_position_collision(new_wallet, &secret).await?;
+        new_wallet.batch_id = source_wallet.batch_id.clone();
+        new_wallet.token_units = source_wallet.token_units.clone();
+
+        self.log("info", &format!(
+            "KnishIOClient::rotate_wallet() - Moving {} {} from {} to {}...",
+            source_wallet.balance,
+            token,
+            source_wallet.address.as_deref().unwrap_or_default(),
+            new_wallet.address.as_deref().unwrap_or_default()
+        ));
+
+        let mut molecule = self.new_molecule();
+        molecule.secret = Some(secret);
+        molecule.bundle = if self.anonymous { None } else { self.bundle.clone() };
+        molecule.source_wallet = Some(source_wallet);
+
+        molecule.init_wallet_rotation(&new_wallet)?;
+        molecule.sign(None, self.anonymous, true)?;
+        molecule.check(molecule.source_wallet.as_ref())?;
+
+        let client = self.client.as_ref().ok_or(KnishIOError::NoClient)?;
+        MutationProposeMolecule::from_molecule(molecule).execute(client, None, None).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::{generate_bundle_hash, generate_secret};
+    use crate::error::KnishIOError;
+    use crate::test_ledger::TestLedger;
+
+    #[tokio::test]
+    async fn test_rotate_wallet() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("rotation-owner");
+        let bundle = generate_bundle_hash(&secret);
+        let funded = ledger.fund(&secret, "GOLD", 75.0).unwrap();
+        let mut client = ledger.client(&secret);
+
+        assert!(matches!(client.rotate_wallet("NONE").await, Err(KnishIOError::WalletNotFound)));
+
+        let response = client.rotate_wallet("GOLD").await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        assert_eq!(ledger.balance(&bundle, "GOLD"), 75.0);
+
+        let rotated = client.query_balance("GOLD", None).await.unwrap();
+        assert_eq!(rotated.whole_balance().unwrap(), 75);
+        assert_ne!(rotated.address, funded.address);
+        assert_ne!(rotated.position, funded.position);
+
+        // The credit atom publishes the new wallet's pubkey
+        let hash = response.get("molecularHash").and_then(|h| h.as_str()).unwrap().to_string();
+        let atoms = client.query_atom(Some(&hash), None, None, rotated.address.as_deref(), None, None, None, None, None).await.unwrap();
+        let metas: Vec<crate::types::MetaItem> = serde_json::from_str(atoms[0]["metasJson"].as_str().unwrap()).unwrap();
+        let pubkey = metas.iter().find(|m| m.key == "pubkey").map(|m| m.value.clone());
+        assert!(pubkey.is_some());
+        assert_ne!(pubkey, funded.pubkey);
+
+        // The rotated wallet signs the next rotation
+        let response = client.rotate_wallet("GOLD").await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        let again = client.query_balance("GOLD", None).await.unwrap();
+        assert_ne!(again.address, rotated.address);
+        assert_eq!(ledger.balance(&bundle, "GOLD"), 75.0);
+    }
+}
@@ -0,0 +1,216 @@
+//! Server schema drift detection
+//!
+//! The SDK hard-codes the root fields of every query, mutation and subscription it
+//! sends. When a node runs a different validator version those fields can disappear or
+//! be renamed, and the first symptom is an opaque GraphQL error deep inside a transfer.
+//! `check_schema` introspects the node's root types once and compares them with the
+//! fields this SDK uses, so mismatches are reported up front. A test in
+//! `graphql::schema_check` keeps the lists below in step with the bundled documents.
+
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::graphql::create_query_request;
+use serde_json::Value;
+
+/// Root query fields used by the SDK
+pub const EXPECTED_QUERY_FIELDS: &[&str] = &[
+    "ActiveUser", "Atom", "Balance", "Batch", "BatchHistory", "ContinuId",
+    "MetaType", "MetaTypeViaAtom", "Policy", "Token", "UserActivity", "Wallet", "WalletBundle",
+];
+
+/// Root mutation fields used by the SDK
+pub const EXPECTED_MUTATION_FIELDS: &[&str] = &[
+    "AccessToken", "ActiveSession", "LinkIdentifier", "ProposeMolecule",
+];
+
+/// Root subscription fields used by the SDK
+pub const EXPECTED_SUBSCRIPTION_FIELDS: &[&str] = &[
+    "ActiveUser", "ActiveWallet", "CreateMolecule", "WalletStatus",
+];
+
+const INTROSPECTION_QUERY: &str = r#"query {
+  __schema {
+    queryType { fields { name } }
+    mutationType { fields { name } }
+    subscriptionType { fields { name } }
+  }
+}"#;
+
+/// GraphQL root operation type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RootType {
+    Query,
+    Mutation,
+    Subscription,
+}
+
+impl RootType {
+    fn schema_key(&self) -> &'static str {
+        match self {
+            RootType::Query => "queryType",
+            RootType::Mutation => "mutationType",
+            RootType::Subscription => "subscriptionType",
+        }
+    }
+
+    fn expected_fields(&self) -> &'static [&'static str] {
+        match self {
+            RootType::Query => EXPECTED_QUERY_FIELDS,
+            RootType::Mutation => EXPECTED_MUTATION_FIELDS,
+            RootType::Subscription => EXPECTED_SUBSCRIPTION_FIELDS,
+        }
+    }
+}
+
+/// A single root field present on one side only
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaDrift {
+    /// Root type the field belongs to
+    pub root: RootType,
+    /// Field name
+    pub field: String,
+}
+
+/// Comparison between the SDK's expected root fields and a node's schema
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaReport {
+    /// URI of the node that was introspected
+    pub uri: String,
+    /// Fields the SDK uses but the node does not expose
+    pub missing: Vec<SchemaDrift>,
+    /// Fields the node exposes but the SDK does not use
+    pub unused: Vec<SchemaDrift>,
+}
+
+impl SchemaReport {
+    /// Build a report from the `data` of the introspection query
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidResponse` if `data` has no `__schema` object
+    pub fn from_introspection(uri: impl Into<String>, data: &Value) -> Result<Self> {
+        let schema = data.get("__schema")
+            .filter(|s| s.is_object())
+            .ok_or(KnishIOError::InvalidResponse)?;
+
+        let mut report = SchemaReport {
+            uri: uri.into(),
+            ..Default::default()
+        };
+
+        for root in [RootType::Query, RootType::Mutation, RootType::Subscription] {
+            let exposed: Vec<&str> = schema.get(root.schema_key())
+                .and_then(|t| t.get("fields"))
+                .and_then(|f| f.as_array())
+                .map(|fields| fields.iter().filter_map(|f| f.get("name")?.as_str()).collect())
+                .unwrap_or_default();
+            let expected = root.expected_fields();
+
+            report.missing.extend(expected.iter()
+                .filter(|field| !exposed.contains(field))
+                .map(|field| SchemaDrift { root, field: field.to_string() }));
+            report.unused.extend(exposed.iter()
+                .filter(|field| !field.starts_with("__") && !expected.contains(field))
+                .map(|field| SchemaDrift { root, field: field.to_string() }));
+        }
+
+        Ok(report)
+    }
+
+    /// True when the node exposes every field the SDK uses
+    pub fn is_compatible(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+impl KnishIOClient {
+    /// Introspect the current node and compare its schema with the fields this SDK uses
+    ///
+    /// Every missing field is logged as a warning; the report is kept and can be read
+    /// later through `schema_report()`.
+    ///
+    /// # Returns
+    ///
+    /// The drift report for the current node
+    pub async fn check_schema(&mut self) -> Result<&SchemaReport> {
+        let client = self.client.as_ref().ok_or(KnishIOError::NoClient)?;
+        let uri = self.get_uri().unwrap_or_default();
+
+        self.log("info", &format!("KnishIOClient::check_schema() - Introspecting schema of {}...", uri));
+
+        let response = client.query(create_query_request(INTROSPECTION_QUERY, None)).await?;
+        let data = response.data.ok_or(KnishIOError::InvalidResponse)?;
+        let report = SchemaReport::from_introspection(uri, &data)?;
+
+        for drift in &report.missing {
+            self.log("warn", &format!(
+                "KnishIOClient::check_schema() - Node does not expose {:?} field {}; related SDK calls will fail",
+                drift.root, drift.field
+            ));
+        }
+        if !report.unused.is_empty() {
+            self.log("info", &format!(
+                "KnishIOClient::check_schema() - Node exposes {} root fields this SDK does not use",
+                report.unused.len()
+            ));
+        }
+
+        Ok(self.schema_report.insert(report))
+    }
+
+    /// Most recent schema drift report (None until `check_schema` has run)
+    pub fn schema_report(&self) -> Option<&SchemaReport> {
+        self.schema_report.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fields(names: &[&str]) -> Value {
+        json!({ "fields": names.iter().map(|n| json!({ "name": n })).collect::<Vec<_>>() })
+    }
+
+    #[test]
+    fn test_matching_schema_is_compatible() {
+        let data = json!({ "__schema": {
+            "queryType": fields(EXPECTED_QUERY_FIELDS),
+            "mutationType": fields(EXPECTED_MUTATION_FIELDS),
+            "subscriptionType": fields(EXPECTED_SUBSCRIPTION_FIELDS),
+        }});
+
+        let report = SchemaReport::from_introspection("http://node", &data).unwrap();
+        assert!(report.is_compatible());
+        assert!(report.unused.is_empty());
+    }
+
+    #[test]
+    fn test_drift_in_both_directions() {
+        let mut queries: Vec<&str> = EXPECTED_QUERY_FIELDS.iter()
+            .copied()
+            .filter(|f| *f != "BatchHistory")
+            .collect();
+        queries.push("Peers");
+
+        let data = json!({ "__schema": {
+            "queryType": fields(&queries),
+            "mutationType": fields(EXPECTED_MUTATION_FIELDS),
+            "subscriptionType": null,
+        }});
+
+        let report = SchemaReport::from_introspection("http://node", &data).unwrap();
+        assert!(!report.is_compatible());
+        assert!(report.missing.contains(&SchemaDrift { root: RootType::Query, field: "BatchHistory".to_string() }));
+        assert_eq!(report.missing.iter().filter(|d| d.root == RootType::Subscription).count(), EXPECTED_SUBSCRIPTION_FIELDS.len());
+        assert_eq!(report.unused, vec![SchemaDrift { root: RootType::Query, field: "Peers".to_string() }]);
+    }
+
+    #[test]
+    fn test_invalid_introspection() {
+        assert!(SchemaReport::from_introspection("http://node", &json!({})).is_err());
+        let client = KnishIOClient::new("http://localhost:8080", None, None, None, None, None);
+        assert!(client.schema_report().is_none());
+    }
+}
@@ -0,0 +1,285 @@
+//! Session snapshots
+//!
+//! Short-lived processes (serverless functions, CLI invocations) pay for authentication
+//! and a ContinuID lookup every time they build a client. `snapshot` captures what those
+//! steps produce — auth tokens, the ContinuID remainder wallet, the node list and the
+//! negotiated wire format — as a serializable `SessionState`, and `restore` builds a
+//! ready client from it and the user secret without talking to a node.
+//!
+//! The state holds bearer tokens and wallet positions but never the secret or private
+//! keys; keys are re-derived from the secret on restore. Store it sealed
+//! (`SessionState::seal`) unless the storage itself is secret.
+
+use crate::auth::{AuthToken, AuthTokenSnapshot, SealedSnapshot, SnapshotKey};
+use crate::client::KnishIOClient;
+use crate::codec::WireFormat;
+use crate::error::{KnishIOError, Result};
+use crate::wallet::{Wallet, WalletParams};
+use serde::{Deserialize, Serialize};
+
+/// Format version written by `KnishIOClient::snapshot`
+pub const SESSION_STATE_VERSION: u32 = 1;
+
+/// An auth token held for one node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionToken {
+    /// Node the token was issued by
+    pub uri: String,
+    /// The token
+    pub token: AuthTokenSnapshot,
+}
+
+/// Everything a client needs to resume a session, apart from the user secret
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionState {
+    /// Format version (`SESSION_STATE_VERSION`)
+    pub version: u32,
+    /// Configured node URIs
+    pub uris: Vec<String>,
+    /// Node the client was talking to
+    pub current_uri: Option<String>,
+    /// Cell slug
+    pub cell_slug: Option<String>,
+    /// Server SDK version
+    pub server_sdk_version: u32,
+    /// Whether ML-KEM encryption was on
+    pub encrypt: bool,
+    /// Whether molecules were proposed anonymously
+    #[serde(default)]
+    pub anonymous: bool,
+    /// Content type of the wire format in use (JSON once a node refused binary)
+    pub wire_format: String,
+    /// Bundle the session belongs to
+    pub bundle: Option<String>,
+    /// Current auth token
+    pub auth_token: Option<AuthTokenSnapshot>,
+    /// The bundle's tokens for every node, the current one included
+    pub auth_tokens: Vec<SessionToken>,
+    /// Next ContinuID source, without its private key
+    pub remainder_wallet: Option<Wallet>,
+    /// Last molecule proposed, which makes the remainder wallet usable
+    pub last_molecule_query: Option<String>,
+}
+
+impl SessionState {
+    /// Encrypt the state under `key`
+    ///
+    /// A raw key (`SnapshotKey::Key`) opens in microseconds; a secret-derived key costs a
+    /// PBKDF2 derivation on every restore.
+    pub fn seal(&self, key: &SnapshotKey) -> Result<SealedSnapshot> {
+        SealedSnapshot::seal_bytes(&serde_json::to_vec(self)?, key, &aad(self.version))
+    }
+
+    /// Decrypt a state sealed with `seal`
+    ///
+    /// # Errors
+    ///
+    /// `DecryptionKey` for the wrong key, a tampered envelope or a sealed auth token
+    pub fn open(sealed: &SealedSnapshot, key: &SnapshotKey) -> Result<Self> {
+        let plaintext = sealed.open_bytes(key, &aad(SESSION_STATE_VERSION))?;
+        serde_json::from_slice(&plaintext).map_err(|_| KnishIOError::DecryptionKey)
+    }
+}
+
+impl KnishIOClient {
+    /// Capture the session for `restore`
+    ///
+    /// ```no_run
+    /// # async fn demo(client: &mut knishio_client::KnishIOClient, secret: &str) -> knishio_client::Result<()> {
+    /// use knishio_client::auth::{SealedSnapshot, SnapshotKey};
+    /// use knishio_client::client::SessionState;
+    /// use knishio_client::KnishIOClient;
+    ///
+    /// let key = SnapshotKey::Key([7u8; 32]);
+    /// let stored = serde_json::to_string(&client.snapshot().seal(&key)?)?;
+    ///
+    /// // Next invocation
+    /// let state = SessionState::open(&SealedSnapshot::parse(&stored)?, &key)?;
+    /// let client = KnishIOClient::restore(state, secret)?;
+    /// assert!(client.is_authenticated());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn snapshot(&self) -> SessionState {
+        let bundle = self.bundle.clone();
+        let auth_tokens = self.auth_token_objects
+            .keys()
+            .filter(|key| key.bundle == bundle)
+            .filter_map(|key| {
+                let token = self.auth_token_objects.peek(&key.uri, key.bundle.as_deref())?;
+                Some(SessionToken { uri: key.uri.clone(), token: token.get_snapshot() })
+            })
+            .collect();
+
+        SessionState {
+            version: SESSION_STATE_VERSION,
+            uris: self.uris.clone(),
+            current_uri: self.client.as_ref().map(|client| client.get_uri().to_string()),
+            cell_slug: self.cell_slug.clone(),
+            server_sdk_version: self.server_sdk_version,
+            encrypt: self.encrypt,
+            anonymous: self.anonymous,
+            wire_format: self.wire_format().content_type().to_string(),
+            bundle,
+            auth_token: self.auth_token.as_ref().map(AuthToken::get_snapshot),
+            auth_tokens,
+            remainder_wallet: self.remainder_wallet.clone(),
+            last_molecule_query: self.last_molecule_query.clone(),
+        }
+    }
+
+    /// Build a client from a `snapshot` and the secret it was taken with
+    ///
+    /// No request is made: tokens are used as they are, so an expired one is replaced on
+    /// the next authenticated call as usual. Subscriptions and listeners are not part of
+    /// the state and have to be set up again.
+    ///
+    /// # Errors
+    ///
+    /// `WalletCredential` if `secret` does not own the session's bundle; an error for
+    /// an unknown state version
+    pub fn restore(state: SessionState, secret: &str) -> Result<Self> {
+        if state.version != SESSION_STATE_VERSION {
+            return Err(KnishIOError::custom(format!("Unsupported session state version {}", state.version)));
+        }
+
+        let mut client = KnishIOClient::new(state.uris, state.cell_slug, None, None, Some(state.server_sdk_version), None);
+        client.set_secret(secret);
+        if state.bundle.is_some() && state.bundle != client.bundle {
+            return Err(KnishIOError::WalletCredential);
+        }
+        client.set_encrypt(state.encrypt);
+        client.set_anonymous(state.anonymous);
+        if let Some(format) = WireFormat::from_content_type(&state.wire_format) {
+            client.set_wire_format(format);
+        }
+        if let Some(uri) = state.current_uri {
+            if let Some(index) = client.uris.iter().position(|known| *known == uri) {
+                client.current_uri_index = index;
+            }
+            if let Some(ref mut graphql) = client.client {
+                graphql.set_uri(uri);
+            }
+        }
+
+        for SessionToken { uri, token } in state.auth_tokens {
+            let token = AuthToken::restore(token, secret)?;
+            client.auth_token_objects.insert(&uri, client.bundle.as_deref(), token);
+        }
+        if let Some(snapshot) = state.auth_token {
+            let token = AuthToken::restore(snapshot, secret)?;
+            if let Some(ref mut graphql) = client.client {
+                graphql.set_auth_data(token.token().to_string(), token.get_pubkey().map(str::to_string), None);
+            }
+            client.set_auth_token(token);
+        }
+
+        client.remainder_wallet = state.remainder_wallet.map(|wallet| rederive(wallet, secret)).transpose()?;
+        client.last_molecule_query = state.last_molecule_query;
+
+        client.log("info", &format!(
+            "KnishIOClient::restore() - Resumed session of bundle {}",
+            client.bundle.as_deref().unwrap_or_default()
+        ));
+        Ok(client)
+    }
+}
+
+/// `wallet` with its signing key derived again from `secret`
+fn rederive(wallet: Wallet, secret: &str) -> Result<Wallet> {
+    let mut derived = Wallet::from_params(WalletParams {
+        position: wallet.position.clone(),
+        characters: wallet.characters.clone(),
+        ..WalletParams::new().secret(secret).token(wallet.token.as_str())
+    })?;
+    if derived.address != wallet.address {
+        return Err(KnishIOError::WalletCredential);
+    }
+
+    derived.balance = wallet.balance;
+    derived.batch_id = wallet.batch_id;
+    derived.token_units = wallet.token_units;
+    derived.trade_rates = wallet.trade_rates;
+    Ok(derived)
+}
+
+/// Associated data binding a sealed state to its format version
+fn aad(version: u32) -> Vec<u8> {
+    format!("KnishIO session state v{}", version).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::generate_secret;
+    use crate::test_ledger::TestLedger;
+    use crate::types::MetaItem;
+    use crate::utils::ManualClock;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_snapshot_and_restore() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("session-owner");
+        let mut client = ledger.client(&secret);
+        client.login(&secret).await.unwrap();
+        let report = client.create_meta_bulk("profile", "owner", vec![MetaItem::new("name", "Owner")], 10).await.unwrap();
+        assert!(report.is_complete(), "{:?}", report.chunks);
+
+        let key = SnapshotKey::Key([3u8; 32]);
+        let state = client.snapshot();
+        let stored = serde_json::to_string(&state.seal(&key).unwrap()).unwrap();
+        assert!(!stored.contains(client.get_auth_token().unwrap().token()));
+
+        let state = SessionState::open(&SealedSnapshot::parse(&stored).unwrap(), &key).unwrap();
+        assert!(matches!(
+            KnishIOClient::restore(state.clone(), &generate_secret("someone-else")),
+            Err(KnishIOError::WalletCredential)
+        ));
+        let mut restored = KnishIOClient::restore(state, &secret).unwrap();
+
+        assert!(restored.is_authenticated());
+        assert_eq!(restored.get_auth_token().unwrap().token(), client.get_auth_token().unwrap().token());
+        assert_eq!(restored.get_current_uri(), client.get_current_uri());
+        let remainder = restored.get_remainder_wallet().unwrap();
+        assert_eq!(remainder.address, client.get_remainder_wallet().unwrap().address);
+        assert!(remainder.key.is_some());
+
+        // The restored client continues the ContinuID chain
+        let before = ledger.molecules().len();
+        let report = restored.create_meta_bulk("profile", "owner", vec![MetaItem::new("name", "Again")], 10).await.unwrap();
+        assert!(report.is_complete(), "{:?}", report.chunks);
+        assert_eq!(ledger.molecules().len(), before + 1);
+
+        // An auth token envelope is not a session
+        let token = client.get_auth_token().unwrap().get_snapshot().seal(&key).unwrap();
+        assert!(matches!(SessionState::open(&token, &key), Err(KnishIOError::DecryptionKey)));
+    }
+
+    #[tokio::test]
+    async fn test_session_expires_on_client_clock() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("session-clock");
+        let mut client = ledger.client(&secret);
+        let clock = ManualClock::starting_now();
+        client.set_clock(Arc::new(clock.clone()));
+
+        let first = client.login(&secret).await.unwrap();
+        assert!(client.is_authenticated());
+
+        // The ledger issues hour-long tokens
+        clock.advance(Duration::from_secs(3599));
+        assert!(client.is_authenticated());
+        assert_eq!(client.login(&secret).await.unwrap().token(), first.token());
+
+        clock.advance(Duration::from_secs(2));
+        assert!(!client.is_authenticated());
+        assert!(client.get_auth_token().unwrap().is_expired());
+        // An expired token is not reused; the ledger's new one expires by its own clock
+        let second = client.login(&secret).await.unwrap();
+        assert_ne!(second.token(), first.token());
+    }
+}
@@ -0,0 +1,335 @@
+//! Subscriptions on nodes that do not serve them
+//!
+//! Older nodes have no subscription root type, and a `subscribe_*` call against one used
+//! to fail somewhere in the transport with an unrelated error. Once `check_schema` has
+//! run, the subscribe methods consult its report: a subscription the node does not
+//! expose is refused with `SubscriptionsUnsupported`, or, with the `subscription-polling`
+//! feature, replaced by `poll_subscription`.
+//!
+//! Polling runs the query counterpart of the subscription at a fixed interval and hands
+//! the callback every item that was not in the previous result: new molecules of the
+//! bundle for `CreateMolecule`, changed wallets for `WalletStatus` and `ActiveWallet`,
+//! changed sessions for `ActiveSession`. Events carry query data, which has the same
+//! fields as the subscription payloads for the most part but is not guaranteed to match.
+
+use crate::client::{KnishIOClient, RootType};
+use crate::error::{KnishIOError, Result};
+use crate::graphql::GraphQLClient;
+use crate::query::active_session::QueryActiveSession;
+use crate::query::atom::QueryAtom;
+use crate::query::balance::QueryBalance;
+use crate::query::wallet_list::QueryWalletList;
+use crate::query::Query;
+use crate::subscribe::{SubscriptionEvent, SubscriptionHandle};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Pause between two polls of a subscription replaced by polling
+pub const SUBSCRIPTION_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Query counterpart of a subscription
+#[derive(Debug, Clone)]
+enum PollSource {
+    CreateMolecule { bundle: String },
+    WalletStatus { bundle: String, token: String },
+    ActiveWallet { bundle: String },
+    ActiveSession { meta_type: String, meta_id: String },
+}
+
+impl PollSource {
+    /// Source for the subscription `operation` with its subscription variables
+    fn new(operation: &str, variables: &Value) -> Result<Self> {
+        let variable = |name: &str| variables[name].as_str().unwrap_or_default().to_string();
+        match operation {
+            "CreateMolecule" => Ok(PollSource::CreateMolecule { bundle: variable("bundle") }),
+            "WalletStatus" => Ok(PollSource::WalletStatus { bundle: variable("bundle"), token: variable("token") }),
+            "ActiveWallet" => Ok(PollSource::ActiveWallet { bundle: variable("bundle") }),
+            "ActiveSession" => Ok(PollSource::ActiveSession { meta_type: variable("metaType"), meta_id: variable("metaId") }),
+            other => Err(KnishIOError::SubscriptionsUnsupported(format!("{} has no polling counterpart", other))),
+        }
+    }
+
+    /// Current items, each with the key that tells whether it is new
+    async fn fetch(&self, client: &GraphQLClient) -> Result<Vec<(String, Value)>> {
+        let snapshot = |data: Value| -> Vec<(String, Value)> {
+            let items = match data {
+                Value::Array(items) => items,
+                Value::Null => Vec::new(),
+                item => vec![item],
+            };
+            items.into_iter().map(|item| (item.to_string(), item)).collect()
+        };
+
+        match self {
+            PollSource::CreateMolecule { bundle } => {
+                let query = QueryAtom::new().add_bundle_hash(bundle.as_str());
+                let data = query.execute(client, None, None).await?.into_data();
+
+                // One item per molecule, in first-seen order
+                let mut molecules: Vec<(String, Value)> = Vec::new();
+                for atom in data["instances"].as_array().into_iter().flatten() {
+                    let hash = atom["molecularHash"].as_str().unwrap_or_default();
+                    match molecules.iter_mut().find(|(key, _)| key == hash) {
+                        Some((_, molecule)) => molecule["atoms"].as_array_mut().into_iter().for_each(|atoms| atoms.push(atom.clone())),
+                        None => molecules.push((hash.to_string(), json!({
+                            "molecularHash": hash,
+                            "bundleHash": bundle,
+                            "atoms": [atom],
+                        }))),
+                    }
+                }
+                Ok(molecules)
+            }
+            PollSource::WalletStatus { bundle, token } => {
+                let query = QueryBalance::new().with_bundle_hash(bundle.as_str()).with_token(token.as_str());
+                Ok(snapshot(query.execute(client, None, None).await?.into_data()))
+            }
+            PollSource::ActiveWallet { bundle } => {
+                let query = QueryWalletList::by_bundle_hash(bundle.as_str());
+                Ok(snapshot(query.execute(client, None, None).await?.into_data()))
+            }
+            PollSource::ActiveSession { meta_type, meta_id } => {
+                let query = QueryActiveSession::new().with_meta(meta_type.as_str(), meta_id.as_str());
+                Ok(snapshot(query.execute(client, None, None).await?.into_data()))
+            }
+        }
+    }
+}
+
+impl KnishIOClient {
+    /// Whether the node serves the `field` subscription
+    ///
+    /// True until `check_schema` has reported otherwise.
+    pub fn supports_subscription(&self, field: &str) -> bool {
+        self.schema_report.as_ref().is_none_or(|report| {
+            !report.missing.iter().any(|drift| drift.root == RootType::Subscription && drift.field == field)
+        })
+    }
+
+    /// Deliver the `operation` subscription by polling its query counterpart
+    ///
+    /// `variables` are the subscription's variables. The first poll only records the
+    /// current state; later polls report what changed since the one before. Polling
+    /// stops when the handle is unsubscribed. `update_variables` on the handle switches
+    /// the polled query over; the next poll records the new baseline.
+    ///
+    /// ```no_run
+    /// # async fn demo(client: &knishio_client::KnishIOClient) -> knishio_client::Result<()> {
+    /// use std::time::Duration;
+    ///
+    /// let variables = serde_json::json!({ "bundle": client.get_bundle().unwrap_or_default() });
+    /// let handle = client.poll_subscription("ActiveWallet", variables, Duration::from_secs(2), |event| {
+    ///     println!("{}: {}", event.operation_name, event.data);
+    /// })?;
+    /// handle.unsubscribe();
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// `SubscriptionsUnsupported` for a subscription without a query counterpart
+    pub fn poll_subscription<F>(&self, operation: &str, variables: Value, interval: Duration, callback: F) -> Result<SubscriptionHandle>
+    where
+        F: Fn(SubscriptionEvent) + Send + Sync + 'static,
+    {
+        let source = PollSource::new(operation, &variables)?;
+        let client = self.client.clone().ok_or(KnishIOError::NoClient)?;
+        let operation = operation.to_string();
+
+        self.log("info", &format!(
+            "KnishIOClient::poll_subscription() - Polling {} every {:?}",
+            operation, interval
+        ));
+
+        let (sources, mut current) = tokio::sync::watch::channel(source);
+        let name = operation.clone();
+        let task = tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            let mut previous: Option<HashSet<String>> = None;
+            loop {
+                ticks.tick().await;
+                if current.has_changed().unwrap_or(false) {
+                    previous = None;
+                }
+                let source = current.borrow_and_update().clone();
+                // A failed poll is retried at the next tick against the same baseline
+                let Ok(items) = source.fetch(&client).await else {
+                    continue;
+                };
+                if let Some(ref previous) = previous {
+                    for (_, item) in items.iter().filter(|(key, _)| !previous.contains(key)) {
+                        callback(SubscriptionEvent::new(name.clone(), item.clone()));
+                    }
+                }
+                previous = Some(items.into_iter().map(|(key, _)| key).collect());
+            }
+        });
+
+        let abort = task.abort_handle();
+        let handle = SubscriptionHandle::new(
+            format!("poll_{}_{}", operation, uuid::Uuid::new_v4()),
+            Box::new(move || abort.abort()),
+        );
+        Ok(handle.with_update_fn(Box::new(move |variables: Value| {
+            let source = PollSource::new(&operation, &variables)?;
+            sources.send(source)
+                .map_err(|_| KnishIOError::WebSocketError(format!("Polling of {} has stopped", operation)))
+        })))
+    }
+
+    /// True if the `field` subscription has to be polled
+    ///
+    /// # Errors
+    ///
+    /// `SubscriptionsUnsupported` when the node does not serve `field` and polling is
+    /// not enabled
+    pub(crate) fn subscription_needs_polling(&self, field: &str) -> Result<bool> {
+        if self.supports_subscription(field) {
+            return Ok(false);
+        }
+        if cfg!(feature = "subscription-polling") {
+            self.log("warn", &format!(
+                "KnishIOClient::subscribe() - Node does not serve {} subscriptions, polling instead",
+                field
+            ));
+            return Ok(true);
+        }
+        Err(KnishIOError::SubscriptionsUnsupported(format!(
+            "{} does not serve {} subscriptions",
+            self.get_uri().unwrap_or_default(),
+            field
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::schema::{SchemaDrift, SchemaReport, EXPECTED_SUBSCRIPTION_FIELDS};
+    use crate::crypto::{generate_bundle_hash, generate_secret};
+    use crate::test_ledger::TestLedger;
+    use crate::types::MetaItem;
+    use std::sync::{Arc, Mutex};
+
+    /// Report of a node without any subscriptions
+    fn no_subscriptions() -> SchemaReport {
+        SchemaReport {
+            missing: EXPECTED_SUBSCRIPTION_FIELDS.iter()
+                .map(|field| SchemaDrift { root: RootType::Subscription, field: field.to_string() })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    async fn wait_for(events: &Mutex<Vec<SubscriptionEvent>>, count: usize) {
+        for _ in 0..100 {
+            if events.lock().unwrap().len() >= count {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_poll_subscription() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("poll-subscriber");
+        let bundle = generate_bundle_hash(&secret);
+        ledger.fund(&secret, "GOLD", 10.0).unwrap();
+        let mut client = ledger.client(&secret);
+
+        let wallets = Arc::new(Mutex::new(Vec::new()));
+        let seen = wallets.clone();
+        let wallet_handle = client.poll_subscription("ActiveWallet", json!({ "bundle": bundle }), Duration::from_millis(20), move |event| {
+            seen.lock().unwrap().push(event);
+        }).unwrap();
+        let molecules = Arc::new(Mutex::new(Vec::new()));
+        let seen = molecules.clone();
+        let molecule_handle = client.poll_subscription("CreateMolecule", json!({ "bundle": bundle }), Duration::from_millis(20), move |event| {
+            seen.lock().unwrap().push(event);
+        }).unwrap();
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(wallets.lock().unwrap().is_empty());
+
+        ledger.fund(&secret, "SILVER", 4.0).unwrap();
+        wait_for(&wallets, 1).await;
+        {
+            let wallets = wallets.lock().unwrap();
+            assert_eq!(wallets.len(), 1);
+            assert_eq!(wallets[0].operation_name, "ActiveWallet");
+            assert_eq!(wallets[0].data["tokenSlug"], "SILVER");
+        }
+
+        let report = client.create_meta_bulk("note", "n1", vec![MetaItem::new("text", "hello")], 10).await.unwrap();
+        assert!(report.is_complete(), "{:?}", report.chunks);
+        wait_for(&molecules, 1).await;
+        assert_eq!(molecules.lock().unwrap()[0].data["bundleHash"], bundle.as_str());
+
+        wallet_handle.unsubscribe();
+        molecule_handle.unsubscribe();
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        ledger.fund(&secret, "COPPER", 1.0).unwrap();
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert_eq!(wallets.lock().unwrap().len(), 1);
+
+        assert!(matches!(
+            client.poll_subscription("Unknown", json!({}), Duration::from_millis(20), |_| {}),
+            Err(KnishIOError::SubscriptionsUnsupported(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_poll_subscription_variables_update() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("poll-update-first");
+        let other_secret = generate_secret("poll-update-second");
+        let client = ledger.client(&secret);
+
+        let wallets = Arc::new(Mutex::new(Vec::new()));
+        let seen = wallets.clone();
+        let handle = client.poll_subscription("ActiveWallet", json!({ "bundle": generate_bundle_hash(&secret) }), Duration::from_millis(20), move |event| {
+            seen.lock().unwrap().push(event);
+        }).unwrap();
+        assert!(handle.supports_update());
+
+        // Switched to the other bundle: its existing wallets are the new baseline
+        ledger.fund(&other_secret, "GOLD", 1.0).unwrap();
+        handle.update_variables(json!({ "bundle": generate_bundle_hash(&other_secret) })).unwrap();
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert!(wallets.lock().unwrap().is_empty());
+
+        ledger.fund(&other_secret, "SILVER", 2.0).unwrap();
+        ledger.fund(&secret, "COPPER", 3.0).unwrap();
+        wait_for(&wallets, 1).await;
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        {
+            let wallets = wallets.lock().unwrap();
+            assert_eq!(wallets.len(), 1);
+            assert_eq!(wallets[0].data["tokenSlug"], "SILVER");
+        }
+
+        assert!(matches!(handle.update_variables(json!({})), Ok(())));
+        handle.unsubscribe();
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert!(handle.update_variables(json!({ "bundle": "x" })).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_subscriptions() {
+        let ledger = TestLedger::start().await.unwrap();
+        let mut client = ledger.client(&generate_secret("poll-subscriber"));
+        assert!(client.supports_subscription("WalletStatus"));
+
+        client.schema_report = Some(no_subscriptions());
+        assert!(!client.supports_subscription("WalletStatus"));
+
+        let handle = client.subscribe_active_wallet(None, |_| {}).await;
+        if cfg!(feature = "subscription-polling") {
+            assert!(handle.unwrap().operation_name.starts_with("poll_ActiveWallet"));
+        } else {
+            assert!(matches!(handle, Err(KnishIOError::SubscriptionsUnsupported(_))));
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Subscription limits of a client
+//!
+//! The `subscribe_*` methods register every subscription they open, over a WebSocket or by
+//! polling, with the client's `SubscriptionManager`, so the manager's `SubscriptionLimits`
+//! apply per client. Each event delivered to a callback marks its subscription active, so
+//! a busy subscription is neither the first evicted nor reaped as idle.
+//!
+//! Configure the limits on the manager:
+//!
+//! ```rust,no_run
+//! use knishio_client::KnishIOClient;
+//! use knishio_client::subscribe::{LimitPolicy, SubscriptionLimits};
+//! use std::time::Duration;
+//!
+//! # fn main() -> knishio_client::Result<()> {
+//! let client = KnishIOClient::new(vec!["https://node.knishio.com/graphql".to_string()], None, None, None, None, None);
+//! let manager = client.get_subscription_manager()?;
+//! manager.set_limits(SubscriptionLimits::default()
+//!     .max_active(16)
+//!     .on_limit(LimitPolicy::EvictLeastRecent)
+//!     .idle_timeout(Duration::from_secs(300)));
+//! manager.on_limit_event(|event| eprintln!("subscription {}", event));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::KnishIOClient;
+use crate::error::Result;
+use crate::subscribe::{SubscriptionEvent, SubscriptionHandle};
+use std::sync::{Arc, OnceLock};
+
+impl KnishIOClient {
+    /// `callback`, also marking the subscription named in `opened` active on every event
+    pub(crate) fn mark_activity<F>(&self, opened: &Arc<OnceLock<String>>, callback: F) -> impl Fn(SubscriptionEvent) + Send + Sync + 'static
+    where
+        F: Fn(SubscriptionEvent) + Send + Sync + 'static,
+    {
+        let manager = self.subscription_manager.clone();
+        let opened = opened.clone();
+        move |event| {
+            if let (Some(manager), Some(operation_name)) = (&manager, opened.get()) {
+                manager.touch(operation_name);
+            }
+            callback(event);
+        }
+    }
+
+    /// Register a subscription just opened with `handle` against the client's limits
+    ///
+    /// # Errors
+    ///
+    /// `SubscriptionLimit` when the client is at `max_active` under `LimitPolicy::Reject`;
+    /// the subscription is closed again
+    pub(crate) async fn track_subscription(&self, handle: SubscriptionHandle, opened: &OnceLock<String>) -> Result<SubscriptionHandle> {
+        let _ = opened.set(handle.operation_name.clone());
+        match self.subscription_manager {
+            Some(ref manager) => manager.track(handle).await,
+            None => Ok(handle),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::generate_secret;
+    use crate::error::KnishIOError;
+    use crate::graphql::{WebSocketManager, WebSocketReconnectConfig};
+    use crate::subscribe::{LimitPolicy, SubscriptionLimits};
+    use crate::test_ledger::TestLedger;
+
+    #[tokio::test]
+    async fn test_client_subscriptions_count_against_limits() {
+        let ledger = TestLedger::start().await.unwrap();
+        let mut client = ledger.client(&generate_secret("subscription-limits"));
+        let manager = client.get_subscription_manager().unwrap();
+        manager.set_limits(SubscriptionLimits::default().max_active(1).on_limit(LimitPolicy::Reject));
+
+        // Nothing to open the subscription on
+        let unattached = client.subscribe_create_molecule(None, |_| {}).await.err().unwrap();
+        assert!(matches!(unattached, KnishIOError::WebSocketError(_)), "{:?}", unattached);
+        assert_eq!(client.active_subscription_count().await, 0);
+
+        // Subscriptions open on an attached connection while it is still connecting
+        client.set_websocket_manager(WebSocketManager::new(
+            "ws://127.0.0.1:9/graphql".to_string(),
+            None,
+            "knishio".to_string(),
+            WebSocketReconnectConfig::default(),
+            false,
+        ));
+
+        let handle = client.subscribe_create_molecule(None, |_| {}).await.unwrap();
+        assert_eq!(client.list_active_subscriptions().await, vec![handle.operation_name.clone()]);
+        let refused = client.subscribe_active_wallet(None, |_| {}).await.err().unwrap();
+        assert!(matches!(refused, KnishIOError::SubscriptionLimit(_)), "{:?}", refused);
+
+        client.unsubscribe(&handle.operation_name).await;
+        assert_eq!(client.active_subscription_count().await, 0);
+        client.subscribe_active_wallet(None, |_| {}).await.unwrap();
+    }
+}
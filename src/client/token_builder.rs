@@ -0,0 +1,407 @@
+//! Token creation presets
+//!
+//! A token's fungibility, decimals and supply model are fixed once it is created, and the
+//! combinations that make sense are few. `TokenPreset` names the common ones:
+//!
+//! | Preset          | Fungibility   | Decimals | Supply          |
+//! |-----------------|---------------|----------|-----------------|
+//! | `Currency`      | fungible      | 2        | `replenishable` |
+//! | `NFTCollection` | nonfungible   | 0        | `limited`       |
+//! | `Voucher`       | stackable     | 0        | `replenishable` |
+//! | `LoyaltyPoints` | fungible      | 0        | `replenishable` |
+//!
+//! Every preset also attaches a token policy: anyone may read the token's descriptive
+//! meta, only its creator may change it. `KnishIOClient::token_builder` starts a
+//! `TokenBuilder`; setters called after `preset` override the preset's choices, and
+//! `validate` refuses combinations the node would reject or that leave the token unusable
+//! before any molecule is built:
+//!
+//! ```no_run
+//! # async fn demo(client: &mut knishio_client::KnishIOClient) -> knishio_client::Result<()> {
+//! use knishio_client::client::TokenPreset;
+//!
+//! let report = client.token_builder("ART")
+//!     .preset(TokenPreset::NFTCollection)
+//!     .name("Gallery One")
+//!     .units(vec!["art-1".to_string(), "art-2".to_string()])
+//!     .create()
+//!     .await?;
+//! assert!(report.is_complete());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::{Fungibility, KnishIOClient, TokenInfo};
+use crate::error::{KnishIOError, Result};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// Recommended settings for a kind of token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenPreset {
+    /// Divisible money-like token, minted as needed
+    Currency,
+    /// Fixed set of unique items, one unit ID each
+    NFTCollection,
+    /// Redeemable batches, each tracked by batch ID
+    Voucher,
+    /// Whole reward points, minted as earned
+    LoyaltyPoints,
+}
+
+impl TokenPreset {
+    /// How the preset divides the supply
+    pub fn fungibility(&self) -> Fungibility {
+        match self {
+            TokenPreset::Currency | TokenPreset::LoyaltyPoints => Fungibility::Fungible,
+            TokenPreset::NFTCollection => Fungibility::NonFungible,
+            TokenPreset::Voucher => Fungibility::Stackable,
+        }
+    }
+
+    /// Decimal places of the preset
+    pub fn decimals(&self) -> u32 {
+        match self {
+            TokenPreset::Currency => 2,
+            _ => 0,
+        }
+    }
+
+    /// Supply model of the preset
+    pub fn supply(&self) -> &'static str {
+        match self {
+            TokenPreset::NFTCollection => "limited",
+            _ => "replenishable",
+        }
+    }
+
+    /// Token policy of the preset: meta readable by all, writable by the creator
+    pub fn policy(&self) -> HashMap<String, Value> {
+        let mut keys = vec!["name", "icon"];
+        if self.fungibility() != Fungibility::Fungible {
+            keys.push("tokenUnits");
+        }
+        let clause = |entry: &str| Value::Object(keys.iter().map(|key| (key.to_string(), json!([entry]))).collect::<Map<_, _>>());
+        HashMap::from([("read".to_string(), clause("all")), ("write".to_string(), clause("self"))])
+    }
+}
+
+/// Result of `TokenBuilder::create`
+#[derive(Debug)]
+pub struct TokenCreationReport {
+    /// Slug of the created token
+    pub token: String,
+    /// Molecular hash of the creation molecule
+    pub creation_hash: Option<String>,
+    /// Molecular hash of the policy molecule, or why it failed; None without a policy
+    pub policy: Option<Result<Option<String>>>,
+}
+
+impl TokenCreationReport {
+    /// True when the token exists with its policy
+    pub fn is_complete(&self) -> bool {
+        self.policy.as_ref().is_none_or(|result| result.is_ok())
+    }
+}
+
+/// Token creation from a preset and overrides; see `KnishIOClient::token_builder`
+pub struct TokenBuilder<'a> {
+    client: &'a mut KnishIOClient,
+    slug: String,
+    amount: Option<f64>,
+    fungibility: Fungibility,
+    decimals: u32,
+    supply: Option<String>,
+    meta: HashMap<String, Value>,
+    units: Vec<String>,
+    batch_id: Option<String>,
+    policy: Option<HashMap<String, Value>>,
+}
+
+impl<'a> TokenBuilder<'a> {
+    /// Take fungibility, decimals, supply and policy from `preset`
+    pub fn preset(mut self, preset: TokenPreset) -> Self {
+        self.fungibility = preset.fungibility();
+        self.decimals = preset.decimals();
+        self.supply = Some(preset.supply().to_string());
+        self.policy = Some(preset.policy());
+        self
+    }
+
+    /// Set the amount minted at creation (not for tokens created from unit IDs)
+    pub fn amount(mut self, amount: f64) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    /// Set the display name
+    pub fn name(self, name: impl Into<String>) -> Self {
+        self.meta("name", name.into())
+    }
+
+    /// Set the icon reference
+    pub fn icon(self, icon: impl Into<String>) -> Self {
+        self.meta("icon", icon.into())
+    }
+
+    /// Set how the supply is divided
+    pub fn fungibility(mut self, fungibility: Fungibility) -> Self {
+        self.fungibility = fungibility;
+        self
+    }
+
+    /// Set the decimal places amounts may carry
+    pub fn decimals(mut self, decimals: u32) -> Self {
+        self.decimals = decimals;
+        self
+    }
+
+    /// Set the supply model, e.g. `limited` or `replenishable`
+    pub fn supply(mut self, supply: impl Into<String>) -> Self {
+        self.supply = Some(supply.into());
+        self
+    }
+
+    /// Set any other token meta
+    pub fn meta(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.meta.insert(key.into(), value.into());
+        self
+    }
+
+    /// Mint these unit IDs (nonfungible and stackable tokens); the amount is their count
+    pub fn units(mut self, units: Vec<String>) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Set the batch ID of a stackable token's first batch
+    pub fn batch_id(mut self, batch_id: impl Into<String>) -> Self {
+        self.batch_id = Some(batch_id.into());
+        self
+    }
+
+    /// Attach `policy` as the token policy instead of the preset's
+    pub fn policy(mut self, policy: HashMap<String, Value>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Create the token without a token policy
+    pub fn without_policy(mut self) -> Self {
+        self.policy = None;
+        self
+    }
+
+    /// Check the settings before anything is sent
+    ///
+    /// # Errors
+    ///
+    /// - `StackableUnitDecimals` / `StackableUnitAmount` for unit IDs with decimals or an amount
+    /// - `ConfigurationError` for units on a fungible token, a nonfungible token without
+    ///   units, a limited supply with nothing minted, or meta that contradicts the setters
+    /// - `InvalidAmount` / `NegativeAmount` for an amount finer than the decimals allow
+    /// - `PolicyInvalid` for a malformed policy
+    pub fn validate(&self) -> Result<()> {
+        let misconfigured = |reason: &str| Err(KnishIOError::ConfigurationError(format!("token {} {}", self.slug, reason)));
+
+        for key in ["fungibility", "decimals", "supply"] {
+            if self.meta.contains_key(key) {
+                return misconfigured(&format!("sets {} as meta; use the {} setter", key, key));
+            }
+        }
+        if !self.units.is_empty() {
+            if self.fungibility == Fungibility::Fungible {
+                return misconfigured("is fungible and cannot have unit IDs");
+            }
+            if self.decimals > 0 {
+                return Err(KnishIOError::StackableUnitDecimals);
+            }
+            if self.amount.unwrap_or(0.0) > 0.0 {
+                return Err(KnishIOError::StackableUnitAmount);
+            }
+        } else if self.fungibility == Fungibility::NonFungible {
+            return misconfigured("is nonfungible and needs unit IDs");
+        }
+
+        let minted = self.units.len() as f64 + self.amount.unwrap_or(0.0);
+        if self.supply.as_deref() == Some("limited") && minted <= 0.0 {
+            return misconfigured("has a limited supply but mints nothing at creation");
+        }
+        if let Some(amount) = self.amount {
+            let info = TokenInfo {
+                slug: self.slug.clone(),
+                name: None,
+                fungibility: self.fungibility,
+                supply: self.supply.clone(),
+                decimals: self.decimals,
+                amount: None,
+                icon: None,
+            };
+            info.validate_amount(amount)?;
+        }
+        if let Some(ref policy) = self.policy {
+            crate::policy_meta::PolicyMeta::validate(&Value::Object(policy.clone().into_iter().collect()))?;
+        }
+        Ok(())
+    }
+
+    /// Token meta as `create_token` takes it
+    fn token_meta(&self) -> HashMap<String, Value> {
+        let mut meta = self.meta.clone();
+        meta.insert("fungibility".to_string(), Value::from(self.fungibility.as_str()));
+        meta.insert("decimals".to_string(), Value::from(self.decimals));
+        if let Some(ref supply) = self.supply {
+            meta.insert("supply".to_string(), Value::from(supply.as_str()));
+        }
+        meta
+    }
+
+    /// Validate, create the token, then attach its policy
+    ///
+    /// A rejected policy leaves the token without one and is reported rather than
+    /// returned as an error, since the token exists either way; `create_policy` with
+    /// meta type `token` retries it.
+    ///
+    /// # Errors
+    ///
+    /// The `validate` errors, or the creation error when the token could not be created
+    pub async fn create(self) -> Result<TokenCreationReport> {
+        self.validate()?;
+        let meta = self.token_meta();
+        let TokenBuilder { client, slug, amount, units, batch_id, policy, .. } = self;
+
+        let amount = if units.is_empty() { amount } else { None };
+        let response = client.create_token(&slug, amount, Some(meta), batch_id.as_deref(), units).await?;
+        if !response.success() {
+            return Err(KnishIOError::custom(format!(
+                "Token creation rejected: {}",
+                response.reason().unwrap_or_else(|| "unknown reason".to_string())
+            )));
+        }
+        let creation_hash = response.get("molecularHash").and_then(|h| h.as_str()).map(str::to_string);
+
+        let policy = match policy {
+            Some(policy) => {
+                let result = client.attach_token_policy(&slug, policy).await;
+                if let Err(ref e) = result {
+                    client.log("warn", &format!("KnishIOClient::token_builder() - {} created without its policy: {}", slug, e));
+                }
+                Some(result)
+            }
+            None => None,
+        };
+
+        Ok(TokenCreationReport { token: slug, creation_hash, policy })
+    }
+}
+
+impl KnishIOClient {
+    /// Start building token `slug`, usually from a `TokenPreset`
+    ///
+    /// Without a preset the token is fungible with no decimals, no supply model and no
+    /// policy, as `create_token` would make it.
+    pub fn token_builder(&mut self, slug: &str) -> TokenBuilder<'_> {
+        TokenBuilder {
+            client: self,
+            slug: slug.to_string(),
+            amount: None,
+            fungibility: Fungibility::Fungible,
+            decimals: 0,
+            supply: None,
+            meta: HashMap::new(),
+            units: Vec::new(),
+            batch_id: None,
+            policy: None,
+        }
+    }
+
+    async fn attach_token_policy(&mut self, slug: &str, policy: HashMap<String, Value>) -> Result<Option<String>> {
+        let response = self.create_policy("token", slug, policy).await?;
+        if !response.success() {
+            return Err(KnishIOError::custom(format!(
+                "Token policy rejected: {}",
+                response.reason().unwrap_or_else(|| "unknown reason".to_string())
+            )));
+        }
+
+        Ok(response.get("molecularHash").and_then(|h| h.as_str()).map(str::to_string))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{generate_bundle_hash, generate_secret};
+    use crate::test_ledger::TestLedger;
+
+    #[test]
+    fn test_presets_refuse_misconfiguration() {
+        let mut client = KnishIOClient::new("http://localhost:1/graphql", None, None, None, None, Some(false));
+
+        assert!(client.token_builder("CASH").preset(TokenPreset::Currency).amount(10.25).validate().is_ok());
+        assert!(matches!(
+            client.token_builder("CASH").preset(TokenPreset::Currency).amount(10.255).validate(),
+            Err(KnishIOError::InvalidAmount(_))
+        ));
+        assert!(matches!(
+            client.token_builder("ART").preset(TokenPreset::NFTCollection).validate(),
+            Err(KnishIOError::ConfigurationError(_))
+        ));
+        assert!(matches!(
+            client.token_builder("ART").preset(TokenPreset::NFTCollection).units(vec!["a".to_string()]).decimals(2).validate(),
+            Err(KnishIOError::StackableUnitDecimals)
+        ));
+        assert!(matches!(
+            client.token_builder("PTS").preset(TokenPreset::LoyaltyPoints).units(vec!["a".to_string()]).validate(),
+            Err(KnishIOError::ConfigurationError(_))
+        ));
+        assert!(matches!(
+            client.token_builder("PTS").meta("decimals", 3).validate(),
+            Err(KnishIOError::ConfigurationError(_))
+        ));
+
+        let policy = TokenPreset::Voucher.policy();
+        assert_eq!(policy["write"]["tokenUnits"], json!(["self"]));
+        assert_eq!(policy["read"]["name"], json!(["all"]));
+        assert!(TokenPreset::Currency.policy()["read"].get("tokenUnits").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_preset_token_is_created() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("token-preset");
+        let bundle = generate_bundle_hash(&secret);
+        let mut client = ledger.client(&secret);
+
+        let report = client.token_builder("CASH")
+            .preset(TokenPreset::Currency)
+            .without_policy()
+            .name("Cash")
+            .amount(250.5)
+            .create()
+            .await
+            .unwrap();
+        assert!(report.creation_hash.is_some());
+        assert!(report.policy.is_none() && report.is_complete());
+        assert_eq!(ledger.balance(&bundle, "CASH"), 250.5);
+        let info = client.token_info("CASH").await.unwrap().unwrap();
+        assert_eq!((info.fungibility, info.decimals, info.supply.as_deref()), (Fungibility::Fungible, 2, Some("replenishable")));
+        assert_eq!(info.name.as_deref(), Some("Cash"));
+
+        client.token_builder("ART")
+            .preset(TokenPreset::NFTCollection)
+            .units(vec!["art-1".to_string(), "art-2".to_string()])
+            .without_policy()
+            .create()
+            .await
+            .unwrap();
+        assert_eq!(ledger.balance(&bundle, "ART"), 2.0);
+        let info = client.token_info("ART").await.unwrap().unwrap();
+        assert_eq!((info.fungibility, info.supply.as_deref()), (Fungibility::NonFungible, Some("limited")));
+
+        // Nothing is sent for a misconfigured token
+        let molecules = ledger.molecules().len();
+        assert!(client.token_builder("BAD").preset(TokenPreset::NFTCollection).create().await.is_err());
+        assert_eq!(ledger.molecules().len(), molecules);
+    }
+}
@@ -0,0 +1,203 @@
+//! Token creation with an initial distribution
+//!
+//! A C-atom can only credit the creating bundle, so a token whose supply starts out
+//! spread over several holders takes two molecules: `create_token` mints the whole
+//! supply to the creator, then one `transfer_tokens` molecule hands every other
+//! recipient its share. Shares are amounts, or for stackable tokens unit IDs; each
+//! recipient's wallet gets its own batch ID unless the recipient names one. The creator's
+//! own share, if listed, simply stays in the new token wallet.
+
+use crate::client::{KnishIOClient, TransferRecipient};
+use crate::error::{KnishIOError, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Result of `create_token_with_distribution`
+#[derive(Debug)]
+pub struct TokenDistributionReport {
+    /// Slug of the created token
+    pub token: String,
+    /// Supply minted to the creator
+    pub supply: f64,
+    /// Molecular hash of the creation molecule
+    pub creation_hash: Option<String>,
+    /// Molecular hash of the distribution molecule, or why it failed; None when every
+    /// share belonged to the creator
+    pub distribution: Option<Result<Option<String>>>,
+}
+
+impl TokenDistributionReport {
+    /// True when every recipient holds its share
+    pub fn is_complete(&self) -> bool {
+        self.distribution.as_ref().is_none_or(|result| result.is_ok())
+    }
+}
+
+impl KnishIOClient {
+    /// Create token `slug` and issue its initial balances to `recipients`
+    ///
+    /// The supply is the sum of the shares. A failed distribution leaves the supply with
+    /// the creator and is reported rather than returned as an error, since the token
+    /// exists either way; a `transfer_tokens` call with the same recipients retries it.
+    ///
+    /// ```no_run
+    /// # async fn demo(client: &mut knishio_client::KnishIOClient, alice: String, bob: String) -> knishio_client::Result<()> {
+    /// use knishio_client::client::TransferRecipient;
+    ///
+    /// let recipients = vec![
+    ///     TransferRecipient { bundle_hash: alice, amount: Some(600.0), units: Vec::new(), batch_id: None },
+    ///     TransferRecipient { bundle_hash: bob, amount: Some(400.0), units: Vec::new(), batch_id: None },
+    /// ];
+    /// let report = client.create_token_with_distribution("SHARE", None, recipients).await?;
+    /// assert!(report.is_complete());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigurationError` without recipients, `StackableUnitAmount` for a share
+    /// giving both an amount and units, or the creation error when the token could not be
+    /// created
+    pub async fn create_token_with_distribution(
+        &mut self,
+        slug: &str,
+        meta: Option<HashMap<String, Value>>,
+        recipients: Vec<TransferRecipient>,
+    ) -> Result<TokenDistributionReport> {
+        if recipients.is_empty() {
+            return Err(KnishIOError::ConfigurationError(
+                "create_token_with_distribution needs at least one recipient".to_string(),
+            ));
+        }
+
+        let mut supply = 0.0;
+        let mut units = Vec::new();
+        for recipient in &recipients {
+            if recipient.units.is_empty() {
+                supply += recipient.amount.unwrap_or(0.0);
+            } else if recipient.amount.unwrap_or(0.0) > 0.0 {
+                return Err(KnishIOError::StackableUnitAmount);
+            } else {
+                units.extend(recipient.units.iter().cloned());
+            }
+        }
+        supply += units.len() as f64;
+
+        let amount = if units.is_empty() { Some(supply) } else { None };
+        let response = self.create_token(slug, amount, meta, None, units).await?;
+        if !response.success() {
+            return Err(KnishIOError::custom(format!(
+                "Token creation rejected: {}",
+                response.reason().unwrap_or_else(|| "unknown reason".to_string())
+            )));
+        }
+        let creation_hash = response.get("molecularHash").and_then(|h| h.as_str()).map(str::to_string);
+
+        let creator = self.bundle.clone();
+        let others: Vec<TransferRecipient> = recipients
+            .into_iter()
+            .filter(|r| creator.as_deref() != Some(r.bundle_hash.as_str()))
+            .collect();
+
+        let distribution = if others.is_empty() {
+            None
+        } else {
+            let count = others.len();
+            let result = self.distribute_token(slug, others).await;
+            match result {
+                Ok(_) => self.log("info", &format!(
+                    "KnishIOClient::create_token_with_distribution() - {} {} issued to {} recipients",
+                    supply, slug, count
+                )),
+                Err(ref e) => self.log("warn", &format!(
+                    "KnishIOClient::create_token_with_distribution() - {} created but not distributed: {}",
+                    slug, e
+                )),
+            }
+            Some(result)
+        };
+
+        Ok(TokenDistributionReport {
+            token: slug.to_string(),
+            supply,
+            creation_hash,
+            distribution,
+        })
+    }
+
+    /// Transfer the recipients' shares out of the freshly created token wallet
+    async fn distribute_token(&mut self, slug: &str, recipients: Vec<TransferRecipient>) -> Result<Option<String>> {
+        let response = self.transfer_tokens(slug, recipients, None).await?;
+        if !response.success() {
+            return Err(KnishIOError::custom(format!(
+                "Distribution rejected: {}",
+                response.reason().unwrap_or_else(|| "unknown reason".to_string())
+            )));
+        }
+
+        Ok(response.get("molecularHash").and_then(|h| h.as_str()).map(str::to_string))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{generate_bundle_hash, generate_secret};
+    use crate::test_ledger::TestLedger;
+
+    fn share(bundle_hash: &str, amount: Option<f64>, units: &[&str]) -> TransferRecipient {
+        TransferRecipient {
+            bundle_hash: bundle_hash.to_string(),
+            amount,
+            units: units.iter().map(|u| u.to_string()).collect(),
+            batch_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_token_with_distribution() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("distribution-creator");
+        let creator = generate_bundle_hash(&secret);
+        let alice = generate_bundle_hash(&generate_secret("distribution-alice"));
+        let bob = generate_bundle_hash(&generate_secret("distribution-bob"));
+        let mut client = ledger.client(&secret);
+
+        assert!(client.create_token_with_distribution("NONE", None, Vec::new()).await.is_err());
+
+        let recipients = vec![share(&alice, Some(60.0), &[]), share(&bob, Some(30.0), &[]), share(&creator, Some(10.0), &[])];
+        let report = client.create_token_with_distribution("SHARE", None, recipients).await.unwrap();
+        assert!(report.is_complete(), "{:?}", report.distribution);
+        assert_eq!(report.supply, 100.0);
+        assert!(report.creation_hash.is_some());
+
+        assert_eq!(ledger.balance(&alice, "SHARE"), 60.0);
+        assert_eq!(ledger.balance(&bob, "SHARE"), 30.0);
+        assert_eq!(ledger.balance(&creator, "SHARE"), 10.0);
+
+        // Only the creator's own share: nothing to distribute
+        let report = client.create_token_with_distribution("SOLO", None, vec![share(&creator, Some(5.0), &[])]).await.unwrap();
+        assert!(report.distribution.is_none());
+        assert_eq!(ledger.balance(&creator, "SOLO"), 5.0);
+    }
+
+    #[tokio::test]
+    async fn test_stackable_distribution_splits_units() {
+        let ledger = TestLedger::start().await.unwrap();
+        let mut client = ledger.client(&generate_secret("distribution-stackable"));
+        let alice = generate_bundle_hash(&generate_secret("distribution-stackable-alice"));
+        let bob = generate_bundle_hash(&generate_secret("distribution-stackable-bob"));
+
+        let meta = HashMap::from([("fungibility".to_string(), Value::from("stackable"))]);
+        let result = client.create_token_with_distribution("SEAT", Some(meta.clone()), vec![share(&alice, Some(1.0), &["s1"])]).await;
+        assert!(matches!(result, Err(KnishIOError::StackableUnitAmount)));
+
+        let recipients = vec![share(&alice, None, &["s1", "s2"]), share(&bob, None, &["s3"])];
+        let report = client.create_token_with_distribution("SEAT", Some(meta), recipients).await.unwrap();
+        assert!(report.is_complete(), "{:?}", report.distribution);
+        assert_eq!(report.supply, 3.0);
+        assert_eq!(ledger.balance(&alice, "SEAT"), 2.0);
+        assert_eq!(ledger.balance(&bob, "SEAT"), 1.0);
+    }
+}
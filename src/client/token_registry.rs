@@ -0,0 +1,278 @@
+//! Cached token metadata
+//!
+//! `query_token` hands back the node's raw JSON. Amount checks need a token's decimals
+//! and fungibility on every transfer, so the client keeps a `TokenRegistry`: the first
+//! lookup of a slug queries the node, later ones are answered from the cache. Token
+//! metadata is fixed at creation (only the circulating amount changes), so entries never
+//! go stale in ways that matter for validation; `invalidate` drops one anyway.
+
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// How a token's supply is divided
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Fungibility {
+    /// Interchangeable amounts
+    #[default]
+    Fungible,
+    /// Individually identified units
+    NonFungible,
+    /// Batches of identified units
+    Stackable,
+}
+
+impl Fungibility {
+    /// Name as stored in the token's `fungibility` meta
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Fungibility::Fungible => "fungible",
+            Fungibility::NonFungible => "nonfungible",
+            Fungibility::Stackable => "stackable",
+        }
+    }
+
+    /// Parse a `fungibility` meta value; unknown or missing values are fungible
+    pub fn parse(value: Option<&str>) -> Self {
+        match value {
+            Some("nonfungible") => Fungibility::NonFungible,
+            Some("stackable") => Fungibility::Stackable,
+            _ => Fungibility::Fungible,
+        }
+    }
+}
+
+impl fmt::Display for Fungibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Metadata of one token
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenInfo {
+    /// Token slug
+    pub slug: String,
+    /// Display name
+    pub name: Option<String>,
+    /// How the supply is divided
+    pub fungibility: Fungibility,
+    /// Supply model, e.g. `limited` or `replenishable`
+    pub supply: Option<String>,
+    /// Decimal places an amount may carry
+    pub decimals: u32,
+    /// Amount in circulation when the token was looked up
+    pub amount: Option<String>,
+    /// Icon reference
+    pub icon: Option<String>,
+}
+
+impl TokenInfo {
+    /// Parse one entry of a `Token` query
+    ///
+    /// Numeric fields are accepted as numbers or strings, as nodes send either.
+    pub fn from_response_data(data: &Value) -> Result<Self> {
+        let text = |key: &str| match &data[key] {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        };
+
+        let slug = text("slug").ok_or(KnishIOError::InvalidResponse)?;
+        let decimals = match text("decimals") {
+            Some(decimals) => decimals.parse().map_err(|_| {
+                KnishIOError::InvalidAmount(format!("{} has invalid decimals {:?}", slug, decimals))
+            })?,
+            None => 0,
+        };
+
+        Ok(TokenInfo {
+            name: text("name"),
+            fungibility: Fungibility::parse(text("fungibility").as_deref()),
+            supply: text("supply"),
+            decimals,
+            amount: text("amount"),
+            icon: text("icon"),
+            slug,
+        })
+    }
+
+    /// True for stackable tokens
+    pub fn is_stackable(&self) -> bool {
+        self.fungibility == Fungibility::Stackable
+    }
+
+    /// Check that `amount` is a non-negative number with at most `decimals` decimal places
+    ///
+    /// # Errors
+    ///
+    /// Returns `NegativeAmount` for a negative amount, or `InvalidAmount` when the amount
+    /// is not finite or is more precise than the token allows
+    pub fn validate_amount(&self, amount: f64) -> Result<()> {
+        if amount < 0.0 {
+            return Err(KnishIOError::NegativeAmount);
+        }
+        if !amount.is_finite() {
+            return Err(KnishIOError::InvalidAmount(format!("{} is not a number", amount)));
+        }
+
+        let scaled = amount * 10f64.powi(self.decimals as i32);
+        if (scaled - scaled.round()).abs() > 1e-9 * scaled.abs().max(1.0) {
+            return Err(KnishIOError::InvalidAmount(format!(
+                "{} has more than {} decimal places, the precision of {}",
+                amount, self.decimals, self.slug
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Token metadata cache shared by a client and its clones
+#[derive(Debug, Clone, Default)]
+pub struct TokenRegistry {
+    tokens: Arc<RwLock<HashMap<String, TokenInfo>>>,
+}
+
+impl TokenRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cached metadata of `slug`
+    pub fn get(&self, slug: &str) -> Option<TokenInfo> {
+        self.tokens.read().unwrap_or_else(PoisonError::into_inner).get(slug).cloned()
+    }
+
+    /// Cache `info`, replacing any earlier entry for its slug
+    pub fn insert(&self, info: TokenInfo) {
+        self.tokens.write().unwrap_or_else(PoisonError::into_inner).insert(info.slug.clone(), info);
+    }
+
+    /// Drop the cached metadata of `slug`
+    pub fn invalidate(&self, slug: &str) {
+        self.tokens.write().unwrap_or_else(PoisonError::into_inner).remove(slug);
+    }
+
+    /// Drop every cached entry
+    pub fn clear(&self) {
+        self.tokens.write().unwrap_or_else(PoisonError::into_inner).clear();
+    }
+
+    /// Number of cached tokens
+    pub fn len(&self) -> usize {
+        self.tokens.read().unwrap_or_else(PoisonError::into_inner).len()
+    }
+
+    /// True when nothing is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl KnishIOClient {
+    /// The client's token metadata cache
+    pub fn token_registry(&self) -> &TokenRegistry {
+        &self.token_registry
+    }
+
+    /// Metadata of token `slug`, queried once and then served from the registry
+    ///
+    /// # Returns
+    ///
+    /// None when the node does not know the token
+    pub async fn token_info(&self, slug: &str) -> Result<Option<TokenInfo>> {
+        if let Some(info) = self.token_registry.get(slug) {
+            return Ok(Some(info));
+        }
+
+        let data = self.query_token(slug).await?;
+        let entry = match data {
+            Value::Array(tokens) => tokens.into_iter().find(|t| t["slug"].as_str() == Some(slug)),
+            Value::Object(_) if data["slug"].as_str() == Some(slug) => Some(data),
+            _ => None,
+        };
+
+        let Some(entry) = entry else {
+            return Ok(None);
+        };
+        let info = TokenInfo::from_response_data(&entry)?;
+        self.token_registry.insert(info.clone());
+        Ok(Some(info))
+    }
+
+    /// Check `amount` against the precision of `token`
+    ///
+    /// Tokens the node does not know, and lookups that fail, are left to the node to
+    /// judge; only a known token's decimals reject an amount here.
+    pub(crate) async fn validate_token_amount(&self, token: &str, amount: f64) -> Result<()> {
+        match self.token_info(token).await {
+            Ok(Some(info)) => info.validate_amount(amount),
+            Ok(None) => Ok(()),
+            Err(e) => {
+                self.log("warn", &format!("KnishIOClient::validate_token_amount() - No metadata for {}: {}", token, e));
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::generate_secret;
+    use crate::test_ledger::TestLedger;
+    use serde_json::json;
+
+    #[test]
+    fn test_token_info_validates_precision() {
+        let info = TokenInfo::from_response_data(&json!({
+            "slug": "CENT", "name": "Cent", "fungibility": "fungible", "supply": "limited", "decimals": "2", "amount": "100"
+        })).unwrap();
+        assert_eq!(info.decimals, 2);
+        assert_eq!(info.fungibility, Fungibility::Fungible);
+
+        assert!(info.validate_amount(1.25).is_ok());
+        assert!(info.validate_amount(0.1).is_ok());
+        assert!(matches!(info.validate_amount(1.255), Err(KnishIOError::InvalidAmount(_))));
+        assert!(matches!(info.validate_amount(-1.0), Err(KnishIOError::NegativeAmount)));
+        assert!(matches!(info.validate_amount(f64::NAN), Err(KnishIOError::InvalidAmount(_))));
+
+        let whole = TokenInfo::from_response_data(&json!({ "slug": "UNIT", "fungibility": "stackable", "decimals": null })).unwrap();
+        assert!(whole.is_stackable());
+        assert!(whole.validate_amount(3.0).is_ok());
+        assert!(whole.validate_amount(2.5).is_err());
+
+        assert!(TokenInfo::from_response_data(&json!({ "slug": "BAD", "decimals": "two" })).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_registry_caches_and_guards_transfers() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("token-registry-owner");
+        let mut client = ledger.client(&secret);
+
+        let response = client.create_token("WHOLE", Some(100.0), None, None, Vec::new()).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        assert!(client.token_registry().is_empty());
+
+        let info = client.token_info("WHOLE").await.unwrap().unwrap();
+        assert_eq!((info.slug.as_str(), info.decimals), ("WHOLE", 0));
+        assert_eq!(client.token_registry().len(), 1);
+        assert!(client.clone().token_registry().get("WHOLE").is_some());
+        assert_eq!(client.token_info("MISSING").await.unwrap(), None);
+
+        let recipient = crate::crypto::generate_bundle_hash(&generate_secret("token-registry-recipient"));
+        let result = client.transfer_token(&recipient, "WHOLE", Some(2.5), Vec::new(), None, None).await;
+        assert!(matches!(result, Err(KnishIOError::InvalidAmount(_))));
+        let response = client.transfer_token(&recipient, "WHOLE", Some(2.0), Vec::new(), None, None).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+
+        client.token_registry().invalidate("WHOLE");
+        assert!(client.token_registry().get("WHOLE").is_none());
+    }
+}
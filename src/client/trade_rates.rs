@@ -0,0 +1,162 @@
+//! Buffer wallet trade rates
+//!
+//! A buffer wallet's trade rates are carried by the B atom that funds it, so they cannot
+//! be edited in place. `update_trade_rates` moves the buffer's whole balance into a fresh
+//! buffer wallet whose B atom carries the new rates; `query_trade_rates` reads the rates
+//! of the current buffer wallet.
+
+use crate::auth::AuthScope;
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::mutation::propose_molecule::MutationProposeMolecule;
+use crate::mutation::Mutation;
+use crate::response::Response;
+use crate::types::TradeRate;
+use crate::wallet::{Wallet, WalletHydration};
+use std::collections::HashMap;
+
+impl KnishIOClient {
+    /// Trade rates of this bundle's `token` buffer wallet, sorted by target token
+    ///
+    /// Empty when the bundle has no funded buffer wallet for `token`.
+    pub async fn query_trade_rates(&self, token: &str) -> Result<Vec<TradeRate>> {
+        let buffer = self.query_buffer_wallet(token).await?;
+
+        let mut rates: Vec<TradeRate> = buffer
+            .map(|wallet| wallet.trade_rates)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(target_token, rate)| TradeRate { source_token: token.to_string(), target_token, rate })
+            .collect();
+        rates.sort_by(|a, b| a.target_token.cmp(&b.target_token));
+
+        Ok(rates)
+    }
+
+    /// Replace the trade rates of this bundle's `token` buffer wallet
+    ///
+    /// The rates given here replace the old ones entirely; an empty map removes them.
+    ///
+    /// ```no_run
+    /// # async fn demo(client: &mut knishio_client::KnishIOClient) -> knishio_client::Result<()> {
+    /// use std::collections::HashMap;
+    ///
+    /// let rates = HashMap::from([("USD".to_string(), 1.25)]);
+    /// let response = client.update_trade_rates("GOLD", rates).await?;
+    /// assert!(response.success());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `WalletNotFound` when the bundle has no funded `token` buffer wallet, or
+    /// `NegativeAmount` for a negative rate
+    pub async fn update_trade_rates(&mut self, token: &str, trade_rates: HashMap<String, f64>) -> Result<Box<dyn Response>> {
+        self.ensure_authentication(None).await?;
+        self.require_auth_scope("update_trade_rates", AuthScope::Profile)?;
+
+        self.log("info", &format!("KnishIOClient::update_trade_rates() - Updating {} trade rates of the {} buffer...", trade_rates.len(), token));
+
+        let secret = self.secret.clone().ok_or(KnishIOError::MissingSecret)?;
+        let buffer = self.query_buffer_wallet(token).await?.ok_or(KnishIOError::WalletNotFound)?;
+        let source_wallet = self.signing_wallet(&buffer, token)?;
+
+        let mut molecule = self.new_molecule();
+        molecule.remainder_wallet = Some(source_wallet.create_remainder(&secret)?);
+        molecule.secret = Some(secret);
+        molecule.bundle = self.molecule_bundle();
+        molecule.source_wallet = Some(source_wallet);
+
+        molecule.init_buffer_trade_rates(trade_rates)?;
+        molecule.sign(None, self.anonymous, true)?;
+        molecule.check(None)?;
+
+        let client = self.client.as_ref().ok_or(KnishIOError::NoClient)?;
+        MutationProposeMolecule::from_molecule(molecule).execute(client, None, None).await
+    }
+
+    /// This bundle's funded `token` buffer wallet, if any
+    async fn query_buffer_wallet(&self, token: &str) -> Result<Option<Wallet>> {
+        use crate::query::balance::QueryBalance;
+        use crate::query::Query;
+
+        let mut query = QueryBalance::new().with_token(token).with_type("buffer");
+        if let Some(ref bundle) = self.bundle {
+            query = query.with_bundle_hash(bundle);
+        }
+
+        let client = self.client.as_ref().ok_or(KnishIOError::NoClient)?;
+        let response = query.execute(client, None, None).await?;
+        let data = response.data();
+        if !data.is_object() {
+            return Ok(None);
+        }
+
+        Wallet::from_response_data_with(data.clone(), WalletHydration::Standard, self.secret.as_deref()).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::{generate_bundle_hash, generate_secret};
+    use crate::error::KnishIOError;
+    use crate::test_ledger::TestLedger;
+    use crate::types::TradeRate;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_update_trade_rates() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("buffer-owner");
+        let bundle = generate_bundle_hash(&secret);
+        ledger.fund(&secret, "GOLD", 100.0).unwrap();
+        let mut client = ledger.client(&secret);
+
+        assert!(client.query_trade_rates("GOLD").await.unwrap().is_empty());
+        assert!(matches!(client.update_trade_rates("GOLD", HashMap::new()).await, Err(KnishIOError::WalletNotFound)));
+
+        let rates = HashMap::from([("USD".to_string(), 2.0), ("EUR".to_string(), 1.5)]);
+        let response = client.deposit_buffer_token("GOLD", 40.0, rates, None).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        assert_eq!(ledger.balance(&bundle, "GOLD"), 60.0);
+
+        let rate = |target: &str, rate: f64| TradeRate { source_token: "GOLD".to_string(), target_token: target.to_string(), rate };
+        assert_eq!(client.query_trade_rates("GOLD").await.unwrap(), vec![rate("EUR", 1.5), rate("USD", 2.0)]);
+
+        assert!(matches!(
+            client.update_trade_rates("GOLD", HashMap::from([("USD".to_string(), -1.0)])).await,
+            Err(KnishIOError::NegativeAmount)
+        ));
+
+        let response = client.update_trade_rates("GOLD", HashMap::from([("USD".to_string(), 2.5)])).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        assert_eq!(client.query_trade_rates("GOLD").await.unwrap(), vec![rate("USD", 2.5)]);
+
+        // The balance moved with the rates; the spendable balance is untouched
+        let buffer = client.query_buffer_wallet("GOLD").await.unwrap().unwrap();
+        assert_eq!(buffer.whole_balance().unwrap(), 40);
+        assert_eq!(ledger.balance(&bundle, "GOLD"), 60.0);
+    }
+
+    #[tokio::test]
+    async fn test_anonymous_buffer_molecules() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("anonymous-buffer-owner");
+        ledger.fund(&secret, "GOLD", 100.0).unwrap();
+        let mut client = ledger.client(&secret);
+        client.set_anonymous(true);
+
+        let rates = HashMap::from([("USD".to_string(), 2.0)]);
+        let response = client.deposit_buffer_token("GOLD", 40.0, rates, None).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        let response = client.update_trade_rates("GOLD", HashMap::from([("USD".to_string(), 2.5)])).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+
+        // Neither molecule names the signer's bundle
+        let atoms = client.query_atom(None, None, None, None, Some("B"), Some("GOLD"), None, None, None).await.unwrap();
+        let molecules: std::collections::HashSet<_> = atoms.iter().map(|atom| atom["molecularHash"].as_str()).collect();
+        assert_eq!(molecules.len(), 2);
+        assert!(atoms.iter().all(|atom| atom["bundleHashes"].as_array().is_some_and(|b| b.is_empty())), "{:?}", atoms);
+    }
+}
@@ -0,0 +1,143 @@
+//! Token unit search across holders
+//!
+//! `query_token_units` inventories one bundle. Marketplaces need the reverse: every unit
+//! of a token, whoever holds it, whose metadata matches some conditions.
+//! The node schema has no unit filter on the `Wallet` query, so `find_token_units` asks
+//! for every wallet of the token and evaluates the filter here.
+
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::query::wallet_list::QueryWalletList;
+use crate::query::Query;
+use crate::token_unit::{HeldTokenUnit, TokenUnitFilter};
+use crate::wallet::{Wallet, WalletHydration};
+use serde_json::Value;
+
+impl KnishIOClient {
+    /// Units of `token` matching `filter`, with the bundle, wallet and batch holding each
+    ///
+    /// ```no_run
+    /// # async fn demo(client: &knishio_client::KnishIOClient) -> knishio_client::Result<()> {
+    /// use knishio_client::token_unit::TokenUnitFilter;
+    /// use serde_json::json;
+    ///
+    /// let filter = TokenUnitFilter::new()
+    ///     .meta_one_of("rarity", vec![json!("epic"), json!("legendary")])
+    ///     .meta_not_equals("listed", json!("false"));
+    /// for held in client.find_token_units("SWORD", &filter).await? {
+    ///     println!("{} held by {:?}", held.unit.id, held.bundle_hash);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn find_token_units(&self, token: &str, filter: &TokenUnitFilter) -> Result<Vec<HeldTokenUnit>> {
+        let client = self.client.as_ref().ok_or(KnishIOError::NoClient)?;
+        let data = QueryWalletList::by_token_slug(token).execute(client, None, None).await?.into_data();
+
+        let wallets = match data {
+            Value::Array(wallets) => wallets,
+            Value::Null => Vec::new(),
+            wallet => vec![wallet],
+        };
+        let wallets = wallets
+            .into_iter()
+            .map(|wallet| Wallet::from_response_data_with(wallet, WalletHydration::Standard, None))
+            .collect::<Result<Vec<_>>>()?;
+
+        let units = HeldTokenUnit::collect(&wallets, filter);
+        self.log("info", &format!(
+            "KnishIOClient::find_token_units() - {} matching {} units across {} wallets",
+            units.len(), token, wallets.len()
+        ));
+        Ok(units)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve GraphQL requests with `answer`, recording every request body
+    async fn stub_node(answer: fn(&Value) -> Value) -> (String, Arc<Mutex<Vec<Value>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let uri = format!("http://{}/graphql", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let seen = seen.clone();
+                tokio::spawn(async move {
+                    let mut buffer = Vec::new();
+                    let mut chunk = [0u8; 4096];
+                    loop {
+                        let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") else {
+                            match stream.read(&mut chunk).await {
+                                Ok(0) | Err(_) => return,
+                                Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+                            }
+                            continue;
+                        };
+                        let head = String::from_utf8_lossy(&buffer[..end]).to_lowercase();
+                        let length: usize = head.lines()
+                            .find_map(|line| line.strip_prefix("content-length:"))
+                            .and_then(|value| value.trim().parse().ok())
+                            .unwrap_or_default();
+                        while buffer.len() < end + 4 + length {
+                            let read = stream.read(&mut chunk).await.unwrap();
+                            buffer.extend_from_slice(&chunk[..read]);
+                        }
+                        let request: Value = serde_json::from_slice(&buffer[end + 4..end + 4 + length]).unwrap();
+                        buffer.drain(..end + 4 + length);
+
+                        let body = answer(&request).to_string();
+                        seen.lock().unwrap().push(request);
+                        let reply = format!("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+                        stream.write_all(reply.as_bytes()).await.unwrap();
+                    }
+                });
+            }
+        });
+        (uri, requests)
+    }
+
+    fn wallets() -> Value {
+        let unit = |id: &str, rarity: &str| json!({ "id": id, "name": id, "metas": { "rarity": rarity } });
+        json!([
+            { "address": "a1", "bundleHash": "b".repeat(64), "tokenSlug": "SWORD", "batchId": "batch-1", "position": "p1", "amount": "2",
+              "tokenUnits": [unit("u1", "epic"), unit("u2", "common")] },
+            { "address": "a2", "bundleHash": "c".repeat(64), "tokenSlug": "SWORD", "batchId": "batch-2", "position": "p2", "amount": "1",
+              "tokenUnits": [unit("u3", "legendary")] },
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_find_token_units_filters_locally() {
+        let (uri, requests) = stub_node(|_| json!({ "data": { "Wallet": wallets() } })).await;
+        let client = KnishIOClient::new(uri.as_str(), None, None, None, None, Some(false));
+
+        let filter = TokenUnitFilter::new().meta_one_of("rarity", vec![json!("epic"), json!("legendary")]);
+        let found = client.find_token_units("SWORD", &filter).await.unwrap();
+        assert_eq!(found.iter().map(|held| held.unit.id.as_str()).collect::<Vec<_>>(), vec!["u1", "u3"]);
+        assert_eq!(found[0].bundle_hash.as_deref(), Some("b".repeat(64).as_str()));
+        assert_eq!(found[0].wallet_address.as_deref(), Some("a1"));
+        assert_eq!(found[0].batch_id.as_deref(), Some("batch-1"));
+        assert_eq!(found[1].bundle_hash.as_deref(), Some("c".repeat(64).as_str()));
+
+        let everything = client.find_token_units("SWORD", &TokenUnitFilter::new()).await.unwrap();
+        assert_eq!(everything.len(), 3);
+
+        // One wallet query per search, never an unserved filter argument
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        for request in requests.iter() {
+            assert!(!request["query"].as_str().unwrap().contains("tokenUnitFilter"));
+            assert_eq!(request["variables"]["tokenSlug"], "SWORD");
+            assert!(request["variables"].get("bundleHash").is_none());
+        }
+    }
+}
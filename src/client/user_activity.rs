@@ -0,0 +1,333 @@
+//! User activity
+//!
+//! Apps record each user session against a meta instance (see `active_session`); the
+//! node's `UserActivity` query returns those sessions with the device each came from, and
+//! optionally counts them by device field or time bucket. `query_user_activity` reads the
+//! answer into `UserActivityRecord`s: the `jsonData` of every instance is parsed, and its
+//! device fields are available as a `DeviceInfo`.
+//!
+//! Dashboards built on the JS SDK bucket instances by day or by device field client-side;
+//! `activity_per` and `activity_by` do the same over any number of records, and each
+//! record has the per-record shorthands.
+
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::query::user_activity::{ActivityCountBy, ActivityInterval, QueryUserActivity, QueryUserActivityParams};
+use crate::query::Query;
+use crate::response::{deserialize_optional_amount, Response};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Timelike, Utc};
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Device a session came from, as recorded in its activity JSON
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceInfo {
+    /// Client IP address
+    pub ip_address: Option<String>,
+    /// Browser name and version
+    pub browser: Option<String>,
+    /// Operating system and CPU
+    pub os_cpu: Option<String>,
+    /// Screen resolution
+    pub resolution: Option<String>,
+    /// Time zone
+    pub time_zone: Option<String>,
+}
+
+impl DeviceInfo {
+    /// Value of one device field
+    pub fn field(&self, field: ActivityCountBy) -> Option<&str> {
+        match field {
+            ActivityCountBy::IpAddress => self.ip_address.as_deref(),
+            ActivityCountBy::Browser => self.browser.as_deref(),
+            ActivityCountBy::OsCpu => self.os_cpu.as_deref(),
+            ActivityCountBy::Resolution => self.resolution.as_deref(),
+            ActivityCountBy::TimeZone => self.time_zone.as_deref(),
+        }
+    }
+}
+
+/// One recorded session
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityInstance {
+    /// Bundle of the user
+    pub bundle_hash: Option<String>,
+    /// Meta type the session was recorded against
+    pub meta_type: Option<String>,
+    /// Meta ID the session was recorded against
+    pub meta_id: Option<String>,
+    /// Activity JSON, parsed (kept as a string if it is not JSON)
+    #[serde(default, deserialize_with = "deserialize_json_data")]
+    pub json_data: Value,
+    /// Session start
+    #[serde(default, deserialize_with = "deserialize_optional_amount")]
+    pub created_at: Option<String>,
+    /// Last update of the session
+    #[serde(default, deserialize_with = "deserialize_optional_amount")]
+    pub updated_at: Option<String>,
+}
+
+impl ActivityInstance {
+    /// Device fields of the activity JSON
+    pub fn device(&self) -> DeviceInfo {
+        DeviceInfo::deserialize(&self.json_data).unwrap_or_default()
+    }
+
+    /// Session start as a UTC time, when the node's timestamp is readable
+    pub fn created_time(&self) -> Option<DateTime<Utc>> {
+        self.created_at.as_deref().and_then(parse_activity_time)
+    }
+}
+
+/// One count of the node's `instanceCount`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ActivityCount {
+    /// Bucket or device field value counted
+    #[serde(default, deserialize_with = "deserialize_optional_amount")]
+    pub id: Option<String>,
+    /// Instances in the bucket
+    #[serde(default, deserialize_with = "deserialize_count")]
+    pub count: u64,
+}
+
+/// Activity recorded against one meta instance
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserActivityRecord {
+    /// Bundle of the user, when filtered by one
+    pub bundle_hash: Option<String>,
+    /// Meta type
+    pub meta_type: Option<String>,
+    /// Meta ID
+    pub meta_id: Option<String>,
+    /// Time of the first session
+    #[serde(default, deserialize_with = "deserialize_optional_amount")]
+    pub created_at: Option<String>,
+    /// Recorded sessions
+    #[serde(default, deserialize_with = "deserialize_list")]
+    pub instances: Vec<ActivityInstance>,
+    /// Counts by the requested `countBy` fields and interval
+    #[serde(default, deserialize_with = "deserialize_list")]
+    pub instance_count: Vec<ActivityCount>,
+}
+
+impl UserActivityRecord {
+    /// Sessions per UTC day
+    pub fn per_day(&self) -> BTreeMap<NaiveDate, usize> {
+        activity_per(std::slice::from_ref(self), ActivityInterval::Day)
+            .into_iter()
+            .map(|(start, count)| (start.date(), count))
+            .collect()
+    }
+
+    /// Sessions per `interval` bucket, keyed by bucket start (UTC)
+    pub fn per(&self, interval: ActivityInterval) -> BTreeMap<NaiveDateTime, usize> {
+        activity_per(std::slice::from_ref(self), interval)
+    }
+
+    /// Sessions per value of a device field
+    pub fn by(&self, field: ActivityCountBy) -> BTreeMap<String, usize> {
+        activity_by(std::slice::from_ref(self), field)
+    }
+
+    /// Sum of the node's counts
+    pub fn total_count(&self) -> u64 {
+        self.instance_count.iter().map(|c| c.count).sum()
+    }
+}
+
+/// Sessions of `records` per `interval` bucket, keyed by bucket start (UTC)
+///
+/// Sessions without a readable start time are left out.
+pub fn activity_per(records: &[UserActivityRecord], interval: ActivityInterval) -> BTreeMap<NaiveDateTime, usize> {
+    let mut counts = BTreeMap::new();
+    for time in records.iter().flat_map(|r| &r.instances).filter_map(ActivityInstance::created_time) {
+        *counts.entry(bucket_start(time.naive_utc(), interval)).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Sessions of `records` per value of a device field
+///
+/// Sessions without the field are left out.
+pub fn activity_by(records: &[UserActivityRecord], field: ActivityCountBy) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for instance in records.iter().flat_map(|r| &r.instances) {
+        if let Some(value) = instance.device().field(field) {
+            *counts.entry(value.to_string()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Start of the `interval` bucket holding `time`; weeks start on Monday
+fn bucket_start(time: NaiveDateTime, interval: ActivityInterval) -> NaiveDateTime {
+    let date = time.date();
+    let day = match interval {
+        ActivityInterval::Hour => return time.with_minute(0).and_then(|t| t.with_second(0)).and_then(|t| t.with_nanosecond(0)).unwrap_or(time),
+        ActivityInterval::Day => date,
+        ActivityInterval::Week => date - Duration::days(i64::from(date.weekday().num_days_from_monday())),
+        ActivityInterval::Month => date.with_day(1).unwrap_or(date),
+        ActivityInterval::Year => date.with_ordinal(1).unwrap_or(date),
+    };
+    day.and_hms_opt(0, 0, 0).unwrap_or(time)
+}
+
+/// Read a node timestamp: epoch milliseconds or seconds, RFC 3339, or `YYYY-MM-DD HH:MM:SS` (UTC)
+fn parse_activity_time(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(number) = value.parse::<i64>() {
+        // Anything past 1e11 is milliseconds (seconds would be beyond the year 5000)
+        return if number.abs() >= 100_000_000_000 {
+            DateTime::from_timestamp_millis(number)
+        } else {
+            DateTime::from_timestamp(number, 0)
+        };
+    }
+    DateTime::parse_from_rfc3339(value).map(|time| time.with_timezone(&Utc)).ok()
+        .or_else(|| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok().map(|time| time.and_utc()))
+}
+
+/// Records of a `UserActivity` response, whether the node answers with one or a list
+fn activity_records(response: &(dyn Response + 'static)) -> Result<Vec<UserActivityRecord>> {
+    Ok(match response.data() {
+        Value::Null => Vec::new(),
+        Value::Array(_) => response.deserialize_into()?,
+        _ => vec![response.deserialize_into()?],
+    })
+}
+
+fn deserialize_json_data<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Value, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(text) => serde_json::from_str(&text).unwrap_or(Value::String(text)),
+        value => value,
+    })
+}
+
+fn deserialize_count<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<u64, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Number(n) => n.as_u64().unwrap_or(0),
+        Value::String(s) => s.parse().unwrap_or(0),
+        _ => 0,
+    })
+}
+
+fn deserialize_list<'de, D, T>(deserializer: D) -> std::result::Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(Option::<Vec<T>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+impl KnishIOClient {
+    /// Query the activity recorded against meta instances (matches JS queryUserActivity)
+    ///
+    /// The node answers with one record or a list of them; either way a list is returned.
+    ///
+    /// ```no_run
+    /// # async fn demo(client: &knishio_client::KnishIOClient) -> knishio_client::Result<()> {
+    /// use knishio_client::query::{ActivityCountBy, ActivityInterval, QueryUserActivityParams};
+    ///
+    /// let records = client.query_user_activity(QueryUserActivityParams {
+    ///     meta_type: Some("app".to_string()),
+    ///     meta_id: Some("dashboard".to_string()),
+    ///     interval: Some("day".parse::<ActivityInterval>()?),
+    ///     ..Default::default()
+    /// }).await?;
+    /// for (day, sessions) in knishio_client::client::activity_per(&records, ActivityInterval::Day) {
+    ///     println!("{}: {}", day.date(), sessions);
+    /// }
+    /// let browsers = knishio_client::client::activity_by(&records, ActivityCountBy::Browser);
+    /// # let _ = browsers;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// `NoClient` without a GraphQL client, the query error, or `ResponseShape` when a
+    /// record does not have the expected fields
+    pub async fn query_user_activity(&self, params: QueryUserActivityParams) -> Result<Vec<UserActivityRecord>> {
+        let client = self.client.as_ref().ok_or(KnishIOError::NoClient)?;
+        let response = QueryUserActivity::new(params).execute(client, None, None).await?;
+
+        let records = activity_records(response.as_ref())?;
+        self.log("info", &format!("KnishIOClient::query_user_activity() - {} activity records", records.len()));
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record() -> UserActivityRecord {
+        serde_json::from_value(json!({
+            "createdAt": "1767225600000",
+            "bundleHash": null,
+            "metaType": "app",
+            "metaId": "dashboard",
+            "instances": [
+                { "bundleHash": "b1", "metaType": "app", "metaId": "dashboard", "createdAt": "1767225600000",
+                  "jsonData": "{\"ipAddress\":\"10.0.0.1\",\"browser\":\"Firefox 140\",\"timeZone\":\"UTC\"}" },
+                { "bundleHash": "b2", "metaType": "app", "metaId": "dashboard", "createdAt": "2026-01-01T23:59:00Z",
+                  "jsonData": "{\"browser\":\"Firefox 140\",\"osCpu\":\"Linux x86_64\"}" },
+                { "bundleHash": "b1", "metaType": "app", "metaId": "dashboard", "createdAt": 1767398400,
+                  "jsonData": "not json" },
+                { "bundleHash": "b3", "metaType": "app", "metaId": "dashboard", "createdAt": "soon", "jsonData": null },
+            ],
+            "instanceCount": [{ "id": "2026-01-01", "count": "2" }, { "id": 7, "count": 1 }],
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_record_is_typed() {
+        let record = record();
+        assert_eq!(record.instances.len(), 4);
+        let first = &record.instances[0];
+        assert_eq!(first.device().ip_address.as_deref(), Some("10.0.0.1"));
+        assert_eq!(first.device().field(ActivityCountBy::TimeZone), Some("UTC"));
+        assert_eq!(record.instances[2].json_data, json!("not json"));
+        assert_eq!(record.instances[2].device(), DeviceInfo::default());
+        assert_eq!(record.instance_count[1].id.as_deref(), Some("7"));
+        assert_eq!(record.total_count(), 3);
+
+        let empty: UserActivityRecord = serde_json::from_value(json!({ "instances": null })).unwrap();
+        assert!(empty.instances.is_empty() && empty.instance_count.is_empty());
+    }
+
+    #[test]
+    fn test_aggregates() {
+        let record = record();
+        let day = |d| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+        assert_eq!(record.per_day(), BTreeMap::from([(day(1), 2), (day(3), 1)]));
+
+        // 2026-01-01 is a Thursday; all three sessions fall in the week of Monday 2025-12-29
+        let week = record.per(ActivityInterval::Week);
+        assert_eq!(week.len(), 1);
+        assert_eq!(week.keys().next().unwrap().date(), NaiveDate::from_ymd_opt(2025, 12, 29).unwrap());
+        assert_eq!(record.per(ActivityInterval::Hour).len(), 3);
+
+        assert_eq!(record.by(ActivityCountBy::Browser), BTreeMap::from([("Firefox 140".to_string(), 2)]));
+        let both = activity_by(&[record.clone(), record], ActivityCountBy::OsCpu);
+        assert_eq!(both.get("Linux x86_64"), Some(&2));
+    }
+
+    #[test]
+    fn test_response_with_one_or_many_records() {
+        use crate::response::BaseResponse;
+
+        let one = json!({ "metaType": "app", "instances": [] });
+        for (data, expected) in [(one.clone(), 1), (json!([one.clone(), one]), 2), (Value::Null, 0)] {
+            let response = BaseResponse::new(json!({ "data": { "UserActivity": data } })).unwrap().with_data_key("data.UserActivity");
+            assert_eq!(activity_records(&response).unwrap().len(), expected);
+        }
+        let malformed = BaseResponse::new(json!({ "data": { "UserActivity": { "instances": 5 } } })).unwrap().with_data_key("data.UserActivity");
+        assert!(matches!(activity_records(&malformed), Err(KnishIOError::ResponseShape { .. })));
+    }
+}
@@ -0,0 +1,123 @@
+//! Wallet position collision checks
+//!
+//! New wallets get a random 64-hex position, so two wallets of one token sharing an address
+//! is astronomically unlikely, but a collision with an on-ledger wallet would reuse its
+//! one-time signing key. With `set_wallet_collision_check` on, the client asks the node for
+//! each fresh wallet's address before using it and draws a new position when the address
+//! is taken, up to the given number of times.
+//!
+//! The check covers the wallets the client generates itself: the remainders of
+//! `create_molecule` and `transfer_token`, `create_wallet` and `rotate_wallet`. It costs one
+//! `Balance` query per wallet and is off by default.
+
+use crate::client::KnishIOClient;
+use crate::error::{KnishIOError, Result};
+use crate::graphql::{RequestOptions, RequestPriority};
+use crate::query::balance::QueryBalance;
+use crate::query::Query;
+use crate::wallet::Wallet;
+
+impl KnishIOClient {
+    /// Check new wallets' addresses against the ledger, drawing up to `retries` new positions
+    ///
+    /// None turns the check off.
+    pub fn set_wallet_collision_check(&mut self, retries: Option<u32>) {
+        self.wallet_collision_retries = retries;
+    }
+
+    /// Positions redrawn per new wallet before giving up, when the check is on
+    pub fn wallet_collision_check(&self) -> Option<u32> {
+        self.wallet_collision_retries
+    }
+
+    /// `wallet`, or the same wallet at a new position if its address is already on the ledger
+    ///
+    /// Returns `wallet` unchanged while the check is off.
+    ///
+    /// # Errors
+    ///
+    /// `WalletPositionCollision` when every redrawn position collided too
+    pub(crate) async fn avoid_position_collision(&self, mut wallet: Wallet, secret: &str) -> Result<Wallet> {
+        let Some(retries) = self.wallet_collision_retries else {
+            return Ok(wallet);
+        };
+
+        for attempt in 0..=retries {
+            let Some(address) = wallet.address.clone() else {
+                return Ok(wallet);
+            };
+            if !self.address_in_use(&address, &wallet.token).await? {
+                return Ok(wallet);
+            }
+            self.log("warn", &format!(
+                "KnishIOClient::avoid_position_collision() - {} wallet address {} is taken (attempt {})",
+                wallet.token,
+                address,
+                attempt + 1
+            ));
+            if attempt < retries {
+                let token = wallet.token.clone();
+                let position = Wallet::generate_position(64);
+                wallet.set_key_from_secret(secret, &token, &position)?;
+            }
+        }
+
+        Err(KnishIOError::WalletPositionCollision(format!(
+            "{} wallet address {} is taken after {} retries",
+            wallet.token,
+            wallet.address.unwrap_or_default(),
+            retries
+        )))
+    }
+
+    /// Whether the node knows a `token` wallet at `address`
+    async fn address_in_use(&self, address: &str, token: &str) -> Result<bool> {
+        let client = self.client_with(RequestOptions::new().priority(RequestPriority::High))?;
+        let response = QueryBalance::new()
+            .with_address(address)
+            .with_token(token)
+            .execute(&client, None, None)
+            .await?;
+
+        let data = response.data();
+        let balance = data.get("Balance").unwrap_or(data);
+        Ok(balance.get("address").and_then(|value| value.as_str()) == Some(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::generate_secret;
+    use crate::error::KnishIOError;
+    use crate::test_ledger::TestLedger;
+    use crate::wallet::Wallet;
+
+    #[tokio::test]
+    async fn test_taken_position_is_redrawn() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("wallet-collision");
+        let taken = ledger.fund(&secret, "GOLD", 1.0).unwrap();
+        let mut client = ledger.client(&secret);
+
+        // Off by default: the wallet is used as is
+        let unchecked = client.avoid_position_collision(taken.clone(), &secret).await.unwrap();
+        assert_eq!(unchecked.address, taken.address);
+
+        client.set_wallet_collision_check(Some(2));
+        let fresh = client.avoid_position_collision(taken.clone(), &secret).await.unwrap();
+        assert_ne!(fresh.position, taken.position);
+        assert_ne!(fresh.address, taken.address);
+        assert_eq!((fresh.token.as_str(), &fresh.bundle), ("GOLD", &taken.bundle));
+        assert!(fresh.verify_address(Some(&secret)).unwrap());
+
+        client.set_wallet_collision_check(Some(0));
+        let error = client.avoid_position_collision(taken, &secret).await.unwrap_err();
+        assert!(matches!(error, KnishIOError::WalletPositionCollision(_)), "{:?}", error);
+
+        // A free address is kept
+        client.set_wallet_collision_check(Some(1));
+        let free = Wallet::create(Some(&secret), None, "GOLD", None, None).unwrap();
+        let kept = client.avoid_position_collision(free.clone(), &secret).await.unwrap();
+        assert_eq!(kept.address, free.address);
+    }
+}
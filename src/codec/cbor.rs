@@ -0,0 +1,270 @@
+//! CBOR (RFC 8949) encoding of JSON values
+//!
+//! Encoding uses the shortest argument form for every length and integer, and always
+//! writes floats as 64-bit so they decode to exactly the same `f64`. Decoding also
+//! accepts half and single precision floats, indefinite-length items and tags (which
+//! are skipped), as other encoders may produce them.
+
+use super::{capacity_hint, malformed, Reader, MAX_DECODE_DEPTH};
+use crate::error::Result;
+use serde_json::{Map, Number, Value};
+
+const UNSIGNED: u8 = 0;
+const NEGATIVE: u8 = 1;
+const BYTES: u8 = 2;
+const TEXT: u8 = 3;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+const TAG: u8 = 6;
+const SIMPLE: u8 = 7;
+
+/// Marker for indefinite-length items and their terminating "break"
+const INDEFINITE: u8 = 31;
+const BREAK: u8 = 0xff;
+
+/// Encode `value` as CBOR
+pub fn encode(value: &Value) -> Vec<u8> {
+    let mut output = Vec::new();
+    write_value(&mut output, value);
+    output
+}
+
+/// Decode a single CBOR item spanning all of `bytes`
+pub fn decode(bytes: &[u8]) -> Result<Value> {
+    let mut reader = Reader::new(bytes);
+    let value = read_value(&mut reader, 0)?;
+    reader.finish()?;
+    Ok(value)
+}
+
+fn write_head(output: &mut Vec<u8>, major: u8, arg: u64) {
+    let major = major << 5;
+    match arg {
+        0..=23 => output.push(major | arg as u8),
+        24..=0xff => output.extend_from_slice(&[major | 24, arg as u8]),
+        0x100..=0xffff => {
+            output.push(major | 25);
+            output.extend_from_slice(&(arg as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            output.push(major | 26);
+            output.extend_from_slice(&(arg as u32).to_be_bytes());
+        }
+        _ => {
+            output.push(major | 27);
+            output.extend_from_slice(&arg.to_be_bytes());
+        }
+    }
+}
+
+fn write_value(output: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => output.push(0xf6),
+        Value::Bool(false) => output.push(0xf4),
+        Value::Bool(true) => output.push(0xf5),
+        Value::Number(n) => write_number(output, n),
+        Value::String(s) => write_text(output, s),
+        Value::Array(items) => {
+            write_head(output, ARRAY, items.len() as u64);
+            for item in items {
+                write_value(output, item);
+            }
+        }
+        Value::Object(map) => {
+            write_head(output, MAP, map.len() as u64);
+            for (key, item) in map {
+                write_text(output, key);
+                write_value(output, item);
+            }
+        }
+    }
+}
+
+fn write_number(output: &mut Vec<u8>, n: &Number) {
+    if let Some(u) = n.as_u64() {
+        write_head(output, UNSIGNED, u);
+    } else if let Some(i) = n.as_i64() {
+        // Negative integers carry -1 - n
+        write_head(output, NEGATIVE, !(i as u64));
+    } else {
+        output.push((SIMPLE << 5) | 27);
+        output.extend_from_slice(&n.as_f64().unwrap_or(0.0).to_be_bytes());
+    }
+}
+
+fn write_text(output: &mut Vec<u8>, s: &str) {
+    write_head(output, TEXT, s.len() as u64);
+    output.extend_from_slice(s.as_bytes());
+}
+
+/// Argument of an item head; None for indefinite length
+fn read_arg(reader: &mut Reader<'_>, info: u8) -> Result<Option<u64>> {
+    match info {
+        0..=23 => Ok(Some(u64::from(info))),
+        24 => reader.uint(1).map(Some),
+        25 => reader.uint(2).map(Some),
+        26 => reader.uint(4).map(Some),
+        27 => reader.uint(8).map(Some),
+        INDEFINITE => Ok(None),
+        _ => Err(malformed("reserved additional information")),
+    }
+}
+
+fn at_break(reader: &mut Reader<'_>) -> Result<bool> {
+    if reader.peek()? == BREAK {
+        reader.byte()?;
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+fn read_value(reader: &mut Reader<'_>, depth: usize) -> Result<Value> {
+    if depth > MAX_DECODE_DEPTH {
+        return Err(malformed("nesting too deep"));
+    }
+
+    let head = reader.byte()?;
+    let (major, info) = (head >> 5, head & 0x1f);
+
+    match major {
+        UNSIGNED => {
+            let n = read_arg(reader, info)?.ok_or_else(|| malformed("indefinite integer"))?;
+            Ok(Value::from(n))
+        }
+        NEGATIVE => {
+            let n = read_arg(reader, info)?.ok_or_else(|| malformed("indefinite integer"))?;
+            let n = i64::try_from(n).map_err(|_| malformed("negative integer out of range"))?;
+            Ok(Value::from(-1 - n))
+        }
+        BYTES => Err(malformed("byte strings have no JSON representation")),
+        TEXT => match read_arg(reader, info)? {
+            Some(len) => reader.text(len).map(Value::String),
+            None => {
+                let mut text = String::new();
+                while !at_break(reader)? {
+                    let chunk = reader.byte()?;
+                    if chunk >> 5 != TEXT {
+                        return Err(malformed("non-text chunk in indefinite string"));
+                    }
+                    let len = read_arg(reader, chunk & 0x1f)?.ok_or_else(|| malformed("nested indefinite string"))?;
+                    text.push_str(&reader.text(len)?);
+                }
+                Ok(Value::String(text))
+            }
+        },
+        ARRAY => {
+            let len = read_arg(reader, info)?;
+            let mut items = Vec::with_capacity(len.map_or(0, capacity_hint));
+            match len {
+                Some(len) => {
+                    for _ in 0..len {
+                        items.push(read_value(reader, depth + 1)?);
+                    }
+                }
+                None => {
+                    while !at_break(reader)? {
+                        items.push(read_value(reader, depth + 1)?);
+                    }
+                }
+            }
+            Ok(Value::Array(items))
+        }
+        MAP => {
+            let len = read_arg(reader, info)?;
+            let mut map = Map::new();
+            let mut read_entry = |reader: &mut Reader<'_>| -> Result<()> {
+                let Value::String(key) = read_value(reader, depth + 1)? else {
+                    return Err(malformed("map keys must be text"));
+                };
+                let value = read_value(reader, depth + 1)?;
+                map.insert(key, value);
+                Ok(())
+            };
+            match len {
+                Some(len) => {
+                    for _ in 0..len {
+                        read_entry(reader)?;
+                    }
+                }
+                None => {
+                    while !at_break(reader)? {
+                        read_entry(reader)?;
+                    }
+                }
+            }
+            Ok(Value::Object(map))
+        }
+        TAG => {
+            read_arg(reader, info)?.ok_or_else(|| malformed("indefinite tag"))?;
+            read_value(reader, depth + 1)
+        }
+        _ => match info {
+            20 => Ok(Value::Bool(false)),
+            21 => Ok(Value::Bool(true)),
+            // null and undefined
+            22 | 23 => Ok(Value::Null),
+            25 => float(half_to_f64(reader.uint(2)? as u16)),
+            26 => float(f64::from(f32::from_bits(reader.uint(4)? as u32))),
+            27 => float(f64::from_bits(reader.uint(8)?)),
+            _ => Err(malformed("unsupported simple value")),
+        },
+    }
+}
+
+fn float(f: f64) -> Result<Value> {
+    // JSON has no NaN or infinity; serde_json maps them to null as well
+    Ok(Number::from_f64(f).map_or(Value::Null, Value::Number))
+}
+
+fn half_to_f64(bits: u16) -> f64 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = i32::from((bits >> 10) & 0x1f);
+    let mantissa = f64::from(bits & 0x3ff);
+    match exponent {
+        0 => sign * mantissa * 2f64.powi(-24),
+        31 if mantissa == 0.0 => sign * f64::INFINITY,
+        31 => f64::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f64.powi(exponent - 15),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_matches_rfc_examples() {
+        let cases: [(Value, &[u8]); 8] = [
+            (json!(0), &[0x00]),
+            (json!(24), &[0x18, 0x18]),
+            (json!(-1000), &[0x39, 0x03, 0xe7]),
+            (json!(1.1), &[0xfb, 0x3f, 0xf1, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a]),
+            (json!("\u{00fc}"), &[0x62, 0xc3, 0xbc]),
+            (json!([1, [2, 3]]), &[0x82, 0x01, 0x82, 0x02, 0x03]),
+            (json!({"a": 1}), &[0xa1, 0x61, 0x61, 0x01]),
+            (json!(null), &[0xf6]),
+        ];
+        for (value, bytes) in cases {
+            assert_eq!(encode(&value), bytes, "{}", value);
+            assert_eq!(decode(bytes).unwrap(), value);
+        }
+        assert_eq!(decode(&[0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]).unwrap(), json!(u64::MAX));
+    }
+
+    #[test]
+    fn test_decodes_foreign_encodings() {
+        // Half float 1.5, indefinite array and string, tagged date string
+        assert_eq!(decode(&[0xf9, 0x3e, 0x00]).unwrap(), json!(1.5));
+        assert_eq!(decode(&[0x9f, 0x01, 0x02, 0xff]).unwrap(), json!([1, 2]));
+        assert_eq!(decode(&[0x7f, 0x62, 0x61, 0x62, 0x61, 0x63, 0xff]).unwrap(), json!("abc"));
+        assert_eq!(decode(&[0xc0, 0x61, 0x7a]).unwrap(), json!("z"));
+
+        assert!(decode(&[0x42, 0x01, 0x02]).is_err());
+        assert!(decode(&[0xa1, 0x01, 0x01]).is_err());
+        assert!(decode(&[0x82, 0x01]).is_err());
+        assert!(decode(&[0x01, 0x01]).is_err());
+        assert!(decode(&[0x9b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]).is_err());
+        assert!(decode(&[0x81; 512]).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! Wire formats for molecule exchange
+//!
+//! JSON is always available. The `cbor` and `msgpack` features add binary encodings that
+//! are considerably smaller and faster to parse, which matters for mobile sync.
+//!
+//! Every format encodes the same `serde_json::Value` a molecule serializes to, and
+//! decodes back to an identical `Value`: integers stay integers, floats stay 64-bit
+//! floats and object keys are always strings. A molecule read from any format therefore
+//! yields the same canonical JSON and the same molecular hash as the original.
+
+#[cfg(feature = "cbor")]
+pub mod cbor;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+
+use crate::error::{KnishIOError, Result};
+use serde_json::Value;
+
+/// Maximum nesting depth accepted when decoding binary payloads
+pub const MAX_DECODE_DEPTH: usize = 128;
+
+/// Encoding used for request and response bodies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WireFormat {
+    /// `application/json`
+    #[default]
+    Json,
+    /// `application/cbor` (RFC 8949)
+    #[cfg(feature = "cbor")]
+    Cbor,
+    /// `application/msgpack`
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+}
+
+impl WireFormat {
+    /// MIME type sent as `Content-Type`
+    pub fn content_type(self) -> &'static str {
+        match self {
+            WireFormat::Json => "application/json",
+            #[cfg(feature = "cbor")]
+            WireFormat::Cbor => "application/cbor",
+            #[cfg(feature = "msgpack")]
+            WireFormat::MessagePack => "application/msgpack",
+        }
+    }
+
+    /// Format named by a `Content-Type` header value, if it is one this build supports
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        match mime.as_str() {
+            "application/json" | "application/graphql-response+json" => Some(WireFormat::Json),
+            #[cfg(feature = "cbor")]
+            "application/cbor" => Some(WireFormat::Cbor),
+            #[cfg(feature = "msgpack")]
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => Some(WireFormat::MessagePack),
+            _ => None,
+        }
+    }
+
+    /// True for the binary formats
+    pub fn is_binary(self) -> bool {
+        self != WireFormat::Json
+    }
+
+    /// Encode `value`
+    pub fn encode(self, value: &Value) -> Result<Vec<u8>> {
+        match self {
+            WireFormat::Json => serde_json::to_vec(value).map_err(|e| KnishIOError::Serialization(e.to_string())),
+            #[cfg(feature = "cbor")]
+            WireFormat::Cbor => Ok(cbor::encode(value)),
+            #[cfg(feature = "msgpack")]
+            WireFormat::MessagePack => Ok(msgpack::encode(value)),
+        }
+    }
+
+    /// Decode a complete payload
+    ///
+    /// # Errors
+    ///
+    /// Returns `Serialization` for malformed input, trailing bytes, or items JSON cannot
+    /// represent (byte strings, non-string map keys, extension types)
+    pub fn decode(self, bytes: &[u8]) -> Result<Value> {
+        match self {
+            WireFormat::Json => serde_json::from_slice(bytes).map_err(|e| KnishIOError::Serialization(e.to_string())),
+            #[cfg(feature = "cbor")]
+            WireFormat::Cbor => cbor::decode(bytes),
+            #[cfg(feature = "msgpack")]
+            WireFormat::MessagePack => msgpack::decode(bytes),
+        }
+    }
+}
+
+/// Cursor over a binary payload
+#[cfg_attr(not(any(feature = "cbor", feature = "msgpack")), allow(dead_code))]
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+#[cfg_attr(not(any(feature = "cbor", feature = "msgpack")), allow(dead_code))]
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.pos.checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| malformed("unexpected end of input"))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn peek(&self) -> Result<u8> {
+        self.bytes.get(self.pos).copied().ok_or_else(|| malformed("unexpected end of input"))
+    }
+
+    /// Big-endian unsigned integer of `len` bytes (1, 2, 4 or 8)
+    fn uint(&mut self, len: usize) -> Result<u64> {
+        Ok(self.take(len)?.iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b)))
+    }
+
+    fn text(&mut self, len: u64) -> Result<String> {
+        let len = usize::try_from(len).map_err(|_| malformed("string too long"))?;
+        std::str::from_utf8(self.take(len)?)
+            .map(str::to_string)
+            .map_err(|_| malformed("invalid UTF-8 in string"))
+    }
+
+    fn finish(&self) -> Result<()> {
+        if self.pos == self.bytes.len() {
+            Ok(())
+        } else {
+            Err(malformed("trailing bytes after value"))
+        }
+    }
+}
+
+#[cfg_attr(not(any(feature = "cbor", feature = "msgpack")), allow(dead_code))]
+fn malformed(reason: &str) -> KnishIOError {
+    KnishIOError::Serialization(format!("Malformed binary payload: {}", reason))
+}
+
+/// Container lengths come from untrusted input; never preallocate more than this
+#[cfg_attr(not(any(feature = "cbor", feature = "msgpack")), allow(dead_code))]
+fn capacity_hint(len: u64) -> usize {
+    usize::try_from(len).unwrap_or(usize::MAX).min(1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_content_type_negotiation() {
+        assert_eq!(WireFormat::from_content_type("application/json; charset=utf-8"), Some(WireFormat::Json));
+        assert_eq!(WireFormat::from_content_type("text/html"), None);
+        assert!(!WireFormat::default().is_binary());
+
+        let value = json!({"a": [1, -2, 2.5, "x", null, true]});
+        assert_eq!(WireFormat::Json.decode(&WireFormat::Json.encode(&value).unwrap()).unwrap(), value);
+    }
+
+    #[cfg(all(feature = "cbor", feature = "msgpack"))]
+    #[test]
+    fn test_molecule_hash_survives_binary_formats() {
+        use crate::molecule::{Molecule, MoleculeParams};
+        use crate::types::MetaItem;
+        use crate::wallet::Wallet;
+
+        let secret = crate::crypto::generate_secret("wire-format");
+        let source = Wallet::create(Some(&secret), None, "USER", None, None).unwrap();
+        let mut molecule = Molecule::from_params(
+            MoleculeParams::new()
+                .secret(secret)
+                .source_wallet(source)
+                .version("4"),
+        );
+        molecule.init_meta(vec![MetaItem::new("motto", "ünïcode ✓")], "note", "n-1", None).unwrap();
+        molecule.sign(None, false, true).unwrap();
+
+        let canonical = molecule.to_canonical_json().unwrap();
+        let json_size = molecule.to_wire(WireFormat::Json).unwrap().len();
+        for format in [WireFormat::Cbor, WireFormat::MessagePack] {
+            assert_eq!(WireFormat::from_content_type(format.content_type()), Some(format));
+
+            let bytes = molecule.to_wire(format).unwrap();
+            assert!(bytes.len() < json_size, "{:?} is not smaller than JSON", format);
+
+            let decoded = Molecule::from_wire(&bytes, format).unwrap();
+            assert_eq!(decoded.molecular_hash, molecule.molecular_hash);
+            assert_eq!(decoded.to_canonical_json().unwrap(), canonical);
+            assert_eq!(decoded.atoms.len(), molecule.atoms.len());
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! MessagePack encoding of JSON values
+//!
+//! Encoding picks the smallest representation for integers, strings and containers and
+//! always writes floats as float 64 so they decode to exactly the same `f64`. Binary and
+//! extension types have no JSON counterpart and are rejected when decoding.
+
+use super::{capacity_hint, malformed, Reader, MAX_DECODE_DEPTH};
+use crate::error::Result;
+use serde_json::{Map, Number, Value};
+
+/// Encode `value` as MessagePack
+pub fn encode(value: &Value) -> Vec<u8> {
+    let mut output = Vec::new();
+    write_value(&mut output, value);
+    output
+}
+
+/// Decode a single MessagePack object spanning all of `bytes`
+pub fn decode(bytes: &[u8]) -> Result<Value> {
+    let mut reader = Reader::new(bytes);
+    let value = read_value(&mut reader, 0)?;
+    reader.finish()?;
+    Ok(value)
+}
+
+fn write_value(output: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => output.push(0xc0),
+        Value::Bool(false) => output.push(0xc2),
+        Value::Bool(true) => output.push(0xc3),
+        Value::Number(n) => write_number(output, n),
+        Value::String(s) => write_str(output, s),
+        Value::Array(items) => {
+            write_len(output, items.len(), 0x90, 0xdc, 0xdd);
+            for item in items {
+                write_value(output, item);
+            }
+        }
+        Value::Object(map) => {
+            write_len(output, map.len(), 0x80, 0xde, 0xdf);
+            for (key, item) in map {
+                write_str(output, key);
+                write_value(output, item);
+            }
+        }
+    }
+}
+
+fn write_number(output: &mut Vec<u8>, n: &Number) {
+    if let Some(u) = n.as_u64() {
+        match u {
+            0..=0x7f => output.push(u as u8),
+            0x80..=0xff => output.extend_from_slice(&[0xcc, u as u8]),
+            0x100..=0xffff => {
+                output.push(0xcd);
+                output.extend_from_slice(&(u as u16).to_be_bytes());
+            }
+            0x1_0000..=0xffff_ffff => {
+                output.push(0xce);
+                output.extend_from_slice(&(u as u32).to_be_bytes());
+            }
+            _ => {
+                output.push(0xcf);
+                output.extend_from_slice(&u.to_be_bytes());
+            }
+        }
+    } else if let Some(i) = n.as_i64() {
+        // Only negative values reach this branch
+        match i {
+            -32..=-1 => output.push(i as u8),
+            -0x80..=-33 => output.extend_from_slice(&[0xd0, i as u8]),
+            -0x8000..=-0x81 => {
+                output.push(0xd1);
+                output.extend_from_slice(&(i as i16).to_be_bytes());
+            }
+            -0x8000_0000..=-0x8001 => {
+                output.push(0xd2);
+                output.extend_from_slice(&(i as i32).to_be_bytes());
+            }
+            _ => {
+                output.push(0xd3);
+                output.extend_from_slice(&i.to_be_bytes());
+            }
+        }
+    } else {
+        output.push(0xcb);
+        output.extend_from_slice(&n.as_f64().unwrap_or(0.0).to_be_bytes());
+    }
+}
+
+fn write_str(output: &mut Vec<u8>, s: &str) {
+    let len = s.len();
+    match len {
+        0..=31 => output.push(0xa0 | len as u8),
+        32..=0xff => output.extend_from_slice(&[0xd9, len as u8]),
+        0x100..=0xffff => {
+            output.push(0xda);
+            output.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        _ => {
+            output.push(0xdb);
+            output.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+    output.extend_from_slice(s.as_bytes());
+}
+
+/// Write a container header: fix form below 16 entries, else the 16 or 32 bit form
+fn write_len(output: &mut Vec<u8>, len: usize, fix: u8, marker16: u8, marker32: u8) {
+    match len {
+        0..=15 => output.push(fix | len as u8),
+        16..=0xffff => {
+            output.push(marker16);
+            output.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        _ => {
+            output.push(marker32);
+            output.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+}
+
+fn read_value(reader: &mut Reader<'_>, depth: usize) -> Result<Value> {
+    if depth > MAX_DECODE_DEPTH {
+        return Err(malformed("nesting too deep"));
+    }
+
+    let marker = reader.byte()?;
+    match marker {
+        0x00..=0x7f => Ok(Value::from(marker)),
+        0x80..=0x8f => read_map(reader, u64::from(marker & 0x0f), depth),
+        0x90..=0x9f => read_array(reader, u64::from(marker & 0x0f), depth),
+        0xa0..=0xbf => reader.text(u64::from(marker & 0x1f)).map(Value::String),
+        0xc0 => Ok(Value::Null),
+        0xc2 => Ok(Value::Bool(false)),
+        0xc3 => Ok(Value::Bool(true)),
+        0xc4..=0xc6 => Err(malformed("binary data has no JSON representation")),
+        0xc7..=0xc9 | 0xd4..=0xd8 => Err(malformed("extension types are not supported")),
+        0xca => float(f64::from(f32::from_bits(reader.uint(4)? as u32))),
+        0xcb => float(f64::from_bits(reader.uint(8)?)),
+        0xcc => reader.uint(1).map(Value::from),
+        0xcd => reader.uint(2).map(Value::from),
+        0xce => reader.uint(4).map(Value::from),
+        0xcf => reader.uint(8).map(Value::from),
+        0xd0 => Ok(Value::from(reader.uint(1)? as u8 as i8)),
+        0xd1 => Ok(Value::from(reader.uint(2)? as u16 as i16)),
+        0xd2 => Ok(Value::from(reader.uint(4)? as u32 as i32)),
+        0xd3 => Ok(Value::from(reader.uint(8)? as i64)),
+        0xd9 => {
+            let len = reader.uint(1)?;
+            reader.text(len).map(Value::String)
+        }
+        0xda => {
+            let len = reader.uint(2)?;
+            reader.text(len).map(Value::String)
+        }
+        0xdb => {
+            let len = reader.uint(4)?;
+            reader.text(len).map(Value::String)
+        }
+        0xdc => {
+            let len = reader.uint(2)?;
+            read_array(reader, len, depth)
+        }
+        0xdd => {
+            let len = reader.uint(4)?;
+            read_array(reader, len, depth)
+        }
+        0xde => {
+            let len = reader.uint(2)?;
+            read_map(reader, len, depth)
+        }
+        0xdf => {
+            let len = reader.uint(4)?;
+            read_map(reader, len, depth)
+        }
+        0xe0..=0xff => Ok(Value::from(marker as i8)),
+        // 0xc1 is never used
+        _ => Err(malformed("reserved marker")),
+    }
+}
+
+fn read_array(reader: &mut Reader<'_>, len: u64, depth: usize) -> Result<Value> {
+    let mut items = Vec::with_capacity(capacity_hint(len));
+    for _ in 0..len {
+        items.push(read_value(reader, depth + 1)?);
+    }
+    Ok(Value::Array(items))
+}
+
+fn read_map(reader: &mut Reader<'_>, len: u64, depth: usize) -> Result<Value> {
+    let mut map = Map::new();
+    for _ in 0..len {
+        let Value::String(key) = read_value(reader, depth + 1)? else {
+            return Err(malformed("map keys must be strings"));
+        };
+        let value = read_value(reader, depth + 1)?;
+        map.insert(key, value);
+    }
+    Ok(Value::Object(map))
+}
+
+fn float(f: f64) -> Result<Value> {
+    Ok(Number::from_f64(f).map_or(Value::Null, Value::Number))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_smallest_encodings() {
+        let cases: [(Value, &[u8]); 9] = [
+            (json!(127), &[0x7f]),
+            (json!(200), &[0xcc, 0xc8]),
+            (json!(-1), &[0xff]),
+            (json!(-33), &[0xd0, 0xdf]),
+            (json!(-40000), &[0xd2, 0xff, 0xff, 0x63, 0xc0]),
+            (json!(0.5), &[0xcb, 0x3f, 0xe0, 0, 0, 0, 0, 0, 0]),
+            (json!("hi"), &[0xa2, b'h', b'i']),
+            (json!([true, null]), &[0x92, 0xc3, 0xc0]),
+            (json!({"k": false}), &[0x81, 0xa1, b'k', 0xc2]),
+        ];
+        for (value, bytes) in cases {
+            assert_eq!(encode(&value), bytes, "{}", value);
+            assert_eq!(decode(bytes).unwrap(), value);
+        }
+
+        let long = json!({"s": "x".repeat(300), "a": (0..20).collect::<Vec<_>>(), "n": i64::MIN, "u": u64::MAX});
+        assert_eq!(decode(&encode(&long)).unwrap(), long);
+    }
+
+    #[test]
+    fn test_rejects_non_json_items() {
+        assert!(decode(&[0xc4, 0x01, 0x00]).is_err());
+        assert!(decode(&[0xd4, 0x01, 0x00]).is_err());
+        assert!(decode(&[0x81, 0x01, 0x01]).is_err());
+        assert!(decode(&[0xc1]).is_err());
+        assert!(decode(&[0x92, 0x01]).is_err());
+        assert!(decode(&[0xdd, 0xff, 0xff, 0xff, 0xff]).is_err());
+        assert!(decode(&[0x91; 512]).is_err());
+    }
+}
@@ -0,0 +1,198 @@
+//! Pluggable SHAKE256 backend
+//!
+//! `shake256`, `shake256_incremental`, `derive_public_digest` and seeded secret generation
+//! hash through one process-wide `HashBackend`. `PortableHash` is the pure-Rust default.
+//! With the `armv8-sha3` feature, aarch64 builds add `Armv8Sha3Hash`, which uses the
+//! ARMv8.2 SHA3 instructions and is picked automatically when the CPU has them.
+//!
+//! Other implementations (a hardware module, a platform library) can be installed with
+//! `set_hash_backend`. A candidate is checked against `PortableHash` first, so every
+//! backend produces byte-identical output and hashes stay compatible with other SDKs.
+//!
+//! This is separate from `ShakeBackend`, which batches independent inputs into SIMD lanes.
+
+use crate::error::{KnishIOError, Result};
+use std::sync::{Arc, LazyLock, PoisonError, RwLock};
+
+/// A SHAKE256 implementation
+pub trait HashBackend: Send + Sync {
+    /// Short name for logs and benchmarks
+    fn name(&self) -> &str;
+
+    /// Absorb `parts` in order and squeeze `output.len()` bytes into `output`
+    ///
+    /// Absorbing several parts must equal absorbing their concatenation.
+    fn shake256(&self, parts: &[&[u8]], output: &mut [u8]);
+}
+
+/// Pure-Rust SHAKE256 (tiny-keccak), available everywhere
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PortableHash;
+
+impl HashBackend for PortableHash {
+    fn name(&self) -> &str {
+        "portable"
+    }
+
+    fn shake256(&self, parts: &[&[u8]], output: &mut [u8]) {
+        use tiny_keccak::{Hasher, Shake};
+
+        let mut hasher = Shake::v256();
+        for part in parts {
+            hasher.update(part);
+        }
+        hasher.finalize(output);
+    }
+}
+
+/// SHAKE256 on the ARMv8.2 SHA3 instructions (`armv8-sha3` feature, aarch64)
+#[cfg(all(feature = "armv8-sha3", target_arch = "aarch64"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Armv8Sha3Hash;
+
+#[cfg(all(feature = "armv8-sha3", target_arch = "aarch64"))]
+impl Armv8Sha3Hash {
+    /// True when the CPU implements the SHA3 extension
+    pub fn is_available() -> bool {
+        std::arch::is_aarch64_feature_detected!("sha3")
+    }
+}
+
+#[cfg(all(feature = "armv8-sha3", target_arch = "aarch64"))]
+impl HashBackend for Armv8Sha3Hash {
+    fn name(&self) -> &str {
+        "armv8-sha3"
+    }
+
+    fn shake256(&self, parts: &[&[u8]], output: &mut [u8]) {
+        use sha3::{digest::{ExtendableOutput, Update, XofReader}, Shake256};
+
+        // The `asm` build of the keccak crate runs the permutation on the SHA3 instructions
+        let mut hasher = Shake256::default();
+        for part in parts {
+            hasher.update(part);
+        }
+        hasher.finalize_xof().read(output);
+    }
+}
+
+/// Fastest built-in backend for this build and CPU
+fn detect() -> Arc<dyn HashBackend> {
+    #[cfg(all(feature = "armv8-sha3", target_arch = "aarch64"))]
+    if Armv8Sha3Hash::is_available() {
+        return Arc::new(Armv8Sha3Hash);
+    }
+    Arc::new(PortableHash)
+}
+
+static BACKEND: LazyLock<RwLock<Arc<dyn HashBackend>>> = LazyLock::new(|| RwLock::new(detect()));
+
+/// Backend currently used for single-input SHAKE256
+pub fn hash_backend() -> Arc<dyn HashBackend> {
+    BACKEND.read().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+/// Install `backend` for every later SHAKE256 call in this process
+///
+/// # Errors
+///
+/// Returns an error, keeping the current backend, if `backend` does not reproduce
+/// `PortableHash` on a set of known inputs
+///
+/// # Example
+///
+/// ```rust
+/// use knishio_client::crypto::{hash_backend, set_hash_backend, shake256, PortableHash};
+/// use std::sync::Arc;
+///
+/// set_hash_backend(Arc::new(PortableHash)).unwrap();
+/// assert_eq!(hash_backend().name(), "portable");
+/// assert_eq!(shake256("test", 256), "b54ff7255705a71ee2925e4a3e30e41aed489a579d5595e0df13e32e1e4dd202");
+/// ```
+pub fn set_hash_backend(backend: Arc<dyn HashBackend>) -> Result<()> {
+    verify(backend.as_ref())?;
+    *BACKEND.write().unwrap_or_else(PoisonError::into_inner) = backend;
+    Ok(())
+}
+
+/// Go back to the detected built-in backend
+pub fn reset_hash_backend() {
+    *BACKEND.write().unwrap_or_else(PoisonError::into_inner) = detect();
+}
+
+/// Hash `parts` through the selected backend
+pub(crate) fn shake256_into(parts: &[&[u8]], output: &mut [u8]) {
+    BACKEND.read().unwrap_or_else(PoisonError::into_inner).shake256(parts, output);
+}
+
+/// Compare `backend` with `PortableHash` on empty, short, multi-block and split inputs,
+/// squeezing past one rate block
+fn verify(backend: &dyn HashBackend) -> Result<()> {
+    let long = "0123456789abcdef".repeat(20);
+    let cases: [&[&[u8]]; 4] = [
+        &[],
+        &[b"test"],
+        &[long.as_bytes()],
+        &[&long.as_bytes()[..7], &long.as_bytes()[7..150], &long.as_bytes()[150..]],
+    ];
+
+    for parts in cases {
+        for length in [32, 200] {
+            let mut expected = vec![0u8; length];
+            let mut actual = vec![0u8; length];
+            PortableHash.shake256(parts, &mut expected);
+            backend.shake256(parts, &mut actual);
+            if actual != expected {
+                return Err(KnishIOError::custom(format!(
+                    "SHAKE256 backend {} does not match the portable output",
+                    backend.name()
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Portable output with every byte flipped
+    struct Broken;
+
+    impl HashBackend for Broken {
+        fn name(&self) -> &str {
+            "broken"
+        }
+
+        fn shake256(&self, parts: &[&[u8]], output: &mut [u8]) {
+            PortableHash.shake256(parts, output);
+            output.iter_mut().for_each(|byte| *byte = !*byte);
+        }
+    }
+
+    #[test]
+    fn test_backend_verification() {
+        assert!(verify(&PortableHash).is_ok());
+        assert!(verify(detect().as_ref()).is_ok());
+
+        let before = hash_backend().name().to_string();
+        assert!(set_hash_backend(Arc::new(Broken)).is_err());
+        assert_eq!(hash_backend().name(), before);
+    }
+
+    #[test]
+    fn test_portable_matches_sha3() {
+        use sha3::{digest::{ExtendableOutput, Update, XofReader}, Shake256};
+
+        let input = "ab".repeat(100);
+        let mut expected = vec![0u8; 300];
+        let mut hasher = Shake256::default();
+        hasher.update(input.as_bytes());
+        hasher.finalize_xof().read(&mut expected);
+
+        let mut actual = vec![0u8; 300];
+        PortableHash.shake256(&[&input.as_bytes()[..50], &input.as_bytes()[50..]], &mut actual);
+        assert_eq!(actual, expected);
+    }
+}
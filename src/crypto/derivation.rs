@@ -0,0 +1,187 @@
+//! Configurable secret derivation
+//!
+//! `generate_secret(seed)` squeezes the seed through SHAKE256 once; every SDK does the
+//! same, which is what lets one seed open the same wallets everywhere. It also means a
+//! guessable seed is cheap to brute-force. `KeyDerivationConfig` lets deployments that
+//! control all of their clients stretch the seed first, with PBKDF2-HMAC-SHA256, and
+//! choose the secret length.
+//!
+//! The default configuration is interop mode: no stretching, 2048-character secrets,
+//! byte-identical to `generate_secret`. With `strict` set, any other setting is refused,
+//! so a deployment that must stay compatible with the other SDKs cannot drift by accident.
+//! Bundle hashes are derived from the resulting secret as usual. Clients take the
+//! configuration through `ClientBuilder::key_derivation` and apply it to seeds given to
+//! `ClientBuilder::seed`, `set_secret_from_seed` and `request_auth_token`.
+
+use super::{generate_secret, generate_secret_with_params, pbkdf2_derive_key};
+use crate::error::{KnishIOError, Result};
+
+/// Secret length, in hexadecimal characters, used by every SDK
+pub const INTEROP_SECRET_LENGTH: usize = 2048;
+
+/// PBKDF2 iterations of `KeyDerivationConfig::strengthened`
+pub const DEFAULT_STRETCH_ITERATIONS: u32 = 600_000;
+
+/// Shortest secret accepted, in hexadecimal characters (1024 bits)
+pub const MIN_SECRET_LENGTH: usize = 256;
+
+/// Salt of the stretching step unless a deployment sets its own
+pub const DEFAULT_STRETCH_SALT: &str = "knishio-secret-stretch";
+
+/// Bytes of stretched seed fed into the SHAKE256 squeeze
+const STRETCHED_SEED_BYTES: usize = 64;
+
+/// How secrets are derived from seeds
+///
+/// ```rust
+/// use knishio_client::crypto::{generate_secret, KeyDerivationConfig};
+///
+/// let interop = KeyDerivationConfig::default();
+/// assert_eq!(interop.derive_secret("seed").unwrap(), generate_secret("seed"));
+///
+/// let hardened = KeyDerivationConfig::strengthened().with_iterations(1_000);
+/// assert_ne!(hardened.derive_secret("seed").unwrap(), generate_secret("seed"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyDerivationConfig {
+    /// PBKDF2 iterations over the seed; 0 skips stretching
+    pub iterations: u32,
+    /// Length of derived secrets, in hexadecimal characters
+    pub secret_length: usize,
+    /// Salt of the stretching step
+    pub salt: String,
+    /// Refuse any setting that is not byte-compatible with the other SDKs
+    pub strict: bool,
+}
+
+impl Default for KeyDerivationConfig {
+    fn default() -> Self {
+        KeyDerivationConfig {
+            iterations: 0,
+            secret_length: INTEROP_SECRET_LENGTH,
+            salt: DEFAULT_STRETCH_SALT.to_string(),
+            strict: true,
+        }
+    }
+}
+
+impl KeyDerivationConfig {
+    /// Cross-SDK mode: `generate_secret` exactly, enforced
+    pub fn interop() -> Self {
+        Self::default()
+    }
+
+    /// Stretched derivation with `DEFAULT_STRETCH_ITERATIONS`
+    ///
+    /// Secrets derived this way only match clients using the same configuration.
+    pub fn strengthened() -> Self {
+        KeyDerivationConfig {
+            iterations: DEFAULT_STRETCH_ITERATIONS,
+            strict: false,
+            ..Self::default()
+        }
+    }
+
+    /// Set the PBKDF2 iteration count
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Set the secret length in hexadecimal characters
+    pub fn with_secret_length(mut self, secret_length: usize) -> Self {
+        self.secret_length = secret_length;
+        self
+    }
+
+    /// Set a deployment-specific stretching salt
+    pub fn with_salt(mut self, salt: impl Into<String>) -> Self {
+        self.salt = salt.into();
+        self
+    }
+
+    /// True when derived secrets are byte-compatible with the other SDKs
+    pub fn is_interop(&self) -> bool {
+        self.iterations == 0 && self.secret_length == INTEROP_SECRET_LENGTH
+    }
+
+    /// Check the configuration
+    ///
+    /// # Errors
+    ///
+    /// `ConfigurationError` for an odd or too short secret length, an empty salt while
+    /// stretching, or a non-interop setting in strict mode
+    pub fn validate(&self) -> Result<()> {
+        if self.strict && !self.is_interop() {
+            return Err(KnishIOError::ConfigurationError(format!(
+                "Strict key derivation requires {} character secrets without stretching (got {} characters, {} iterations)",
+                INTEROP_SECRET_LENGTH, self.secret_length, self.iterations
+            )));
+        }
+        if self.secret_length < MIN_SECRET_LENGTH || self.secret_length % 2 != 0 {
+            return Err(KnishIOError::ConfigurationError(format!(
+                "Secret length must be even and at least {} characters, got {}",
+                MIN_SECRET_LENGTH, self.secret_length
+            )));
+        }
+        if self.iterations > 0 && self.salt.is_empty() {
+            return Err(KnishIOError::ConfigurationError("Stretching needs a non-empty salt".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Derive the secret for `seed`
+    ///
+    /// # Errors
+    ///
+    /// `ConfigurationError` if the configuration does not `validate`
+    pub fn derive_secret(&self, seed: &str) -> Result<String> {
+        self.validate()?;
+        if self.is_interop() {
+            return Ok(generate_secret(seed));
+        }
+
+        let seed = if self.iterations > 0 {
+            pbkdf2_derive_key(seed, &hex::encode(&self.salt), self.iterations, STRETCHED_SEED_BYTES)?
+        } else {
+            seed.to_string()
+        };
+        Ok(generate_secret_with_params(Some(&seed), self.secret_length))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interop_and_strict_mode() {
+        let interop = KeyDerivationConfig::interop();
+        assert!(interop.is_interop());
+        assert_eq!(interop.derive_secret("derivation").unwrap(), generate_secret("derivation"));
+
+        for config in [interop.clone().with_iterations(10), interop.clone().with_secret_length(4096)] {
+            assert!(matches!(config.derive_secret("derivation"), Err(KnishIOError::ConfigurationError(_))));
+        }
+        let relaxed = KeyDerivationConfig { strict: false, ..interop };
+        assert_eq!(relaxed.derive_secret("derivation").unwrap(), generate_secret("derivation"));
+    }
+
+    #[test]
+    fn test_strengthened_derivation() {
+        let config = KeyDerivationConfig::strengthened().with_iterations(1_000);
+        assert!(!config.is_interop());
+        let secret = config.derive_secret("derivation").unwrap();
+        assert_eq!(secret.len(), INTEROP_SECRET_LENGTH);
+        assert_eq!(secret, config.derive_secret("derivation").unwrap());
+        assert_ne!(secret, generate_secret("derivation"));
+        assert_ne!(secret, config.clone().with_salt("deployment-b").derive_secret("derivation").unwrap());
+        assert_ne!(secret, config.clone().with_iterations(1_001).derive_secret("derivation").unwrap());
+
+        let long = config.clone().with_secret_length(4096).derive_secret("derivation").unwrap();
+        assert_eq!(long.len(), 4096);
+        assert!(config.clone().with_secret_length(255).validate().is_err());
+        assert!(config.clone().with_secret_length(128).validate().is_err());
+        assert!(config.with_salt("").validate().is_err());
+    }
+}
@@ -0,0 +1,202 @@
+//! Random secrets and seed quality checks
+//!
+//! `generate_secret(seed)` is deterministic: anyone who can guess the seed can rebuild
+//! the secret and every wallet derived from it. `generate_random_secret` instead seeds
+//! the same SHAKE256 derivation with 512 bits drawn from the operating system's CSPRNG.
+//! Targets without an OS RNG (or with a hardware RNG they trust more) implement
+//! `EntropySource` and call `generate_random_secret_with`.
+//!
+//! Bytes drawn from a source are sanity-checked before use, so a stuck or disconnected
+//! hardware RNG is reported instead of silently producing a predictable secret. Caller
+//! supplied seeds can be checked the same way with `check_seed_entropy`, or used through
+//! `generate_secret_checked`, which refuses seeds that look guessable.
+
+use super::generate_secret;
+use crate::error::{KnishIOError, Result};
+use rand::TryRngCore;
+use std::collections::{HashMap, HashSet};
+
+/// Bytes of entropy behind every random secret (512 bits)
+pub const SECRET_ENTROPY_BYTES: usize = 64;
+
+/// Estimated entropy a seed needs before `generate_secret_checked` accepts it
+pub const MIN_SEED_ENTROPY_BITS: f64 = 128.0;
+
+/// Fewest distinct byte values accepted in a draw of `SECRET_ENTROPY_BYTES`
+///
+/// A uniform source yields about 56; fewer than 16 means the source is stuck or biased.
+const MIN_DISTINCT_BYTES: usize = 16;
+
+/// A source of cryptographically secure random bytes
+pub trait EntropySource {
+    /// Fill `dest` entirely with random bytes
+    fn fill_bytes(&mut self, dest: &mut [u8]) -> Result<()>;
+}
+
+/// The operating system's CSPRNG (`getrandom(2)`, `BCryptGenRandom`, ...)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsEntropy;
+
+impl EntropySource for OsEntropy {
+    fn fill_bytes(&mut self, dest: &mut [u8]) -> Result<()> {
+        rand::rngs::OsRng.try_fill_bytes(dest)
+            .map_err(|e| KnishIOError::WeakEntropy(format!("OS random source failed: {}", e)))
+    }
+}
+
+/// Generate a random 2048-character secret from the operating system's CSPRNG
+///
+/// # Example
+///
+/// ```rust
+/// use knishio_client::crypto::generate_random_secret;
+///
+/// let secret = generate_random_secret().unwrap();
+/// assert_eq!(secret.len(), 2048);
+/// assert_ne!(secret, generate_random_secret().unwrap());
+/// ```
+pub fn generate_random_secret() -> Result<String> {
+    generate_random_secret_with(&mut OsEntropy)
+}
+
+/// Generate a random 2048-character secret from `source`
+///
+/// # Errors
+///
+/// `WeakEntropy` if the source fails or its output does not look random.
+pub fn generate_random_secret_with(source: &mut dyn EntropySource) -> Result<String> {
+    let mut first = [0u8; SECRET_ENTROPY_BYTES];
+    let mut second = [0u8; SECRET_ENTROPY_BYTES];
+    source.fill_bytes(&mut first)?;
+    source.fill_bytes(&mut second)?;
+
+    if first == second {
+        return Err(KnishIOError::WeakEntropy("entropy source repeated its output".to_string()));
+    }
+    for draw in [&first, &second] {
+        let distinct = draw.iter().collect::<HashSet<_>>().len();
+        if distinct < MIN_DISTINCT_BYTES {
+            return Err(KnishIOError::WeakEntropy(format!(
+                "entropy source produced only {} distinct byte values in {} bytes",
+                distinct, SECRET_ENTROPY_BYTES
+            )));
+        }
+    }
+
+    let mut seed = first.to_vec();
+    seed.extend_from_slice(&second);
+    Ok(generate_secret(&hex::encode(seed)))
+}
+
+/// Estimate the entropy of `seed` in bits
+///
+/// A heuristic, not a guarantee: the Shannon entropy of the seed's character
+/// distribution, counted once per distinct trigram so repeated patterns add nothing.
+/// Random hex seeds of 64 characters or more comfortably pass `MIN_SEED_ENTROPY_BITS`.
+pub fn estimate_seed_entropy(seed: &str) -> f64 {
+    let chars: Vec<char> = seed.chars().collect();
+    if chars.is_empty() {
+        return 0.0;
+    }
+
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in &chars {
+        *counts.entry(*c).or_default() += 1;
+    }
+    let total = chars.len() as f64;
+    let per_char: f64 = counts.values()
+        .map(|&count| {
+            let p = count as f64 / total;
+            -p * p.log2()
+        })
+        .sum();
+
+    let distinct_trigrams = chars.windows(3).collect::<HashSet<_>>().len();
+    per_char * distinct_trigrams.max(1).min(chars.len()) as f64
+}
+
+/// Reject seeds too predictable to protect a secret
+///
+/// # Errors
+///
+/// `WeakEntropy` if `estimate_seed_entropy(seed)` is below `MIN_SEED_ENTROPY_BITS`.
+pub fn check_seed_entropy(seed: &str) -> Result<()> {
+    let bits = estimate_seed_entropy(seed);
+    if bits < MIN_SEED_ENTROPY_BITS {
+        return Err(KnishIOError::WeakEntropy(format!(
+            "seed has about {:.0} bits of entropy, at least {:.0} required",
+            bits, MIN_SEED_ENTROPY_BITS
+        )));
+    }
+    Ok(())
+}
+
+/// `generate_secret(seed)`, refusing seeds that fail `check_seed_entropy`
+///
+/// # Example
+///
+/// ```rust
+/// use knishio_client::crypto::{generate_secret, generate_secret_checked, shake256};
+///
+/// assert!(generate_secret_checked("password123").is_err());
+///
+/// let seed = shake256("device-provisioning-record", 256);
+/// assert_eq!(generate_secret_checked(&seed).unwrap(), generate_secret(&seed));
+/// ```
+pub fn generate_secret_checked(seed: &str) -> Result<String> {
+    check_seed_entropy(seed)?;
+    Ok(generate_secret(seed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::shake256;
+
+    /// Replays a fixed byte pattern, like a hardware RNG stuck on one value
+    struct StuckSource(u8);
+
+    impl EntropySource for StuckSource {
+        fn fill_bytes(&mut self, dest: &mut [u8]) -> Result<()> {
+            dest.fill(self.0);
+            Ok(())
+        }
+    }
+
+    /// Deterministic counter-mode source standing in for a hardware RNG
+    struct ShakeSource(u64);
+
+    impl EntropySource for ShakeSource {
+        fn fill_bytes(&mut self, dest: &mut [u8]) -> Result<()> {
+            self.0 += 1;
+            let bytes = hex::decode(shake256(&self.0.to_string(), dest.len() * 8)).unwrap();
+            dest.copy_from_slice(&bytes);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_random_secret_sources() {
+        let secret = generate_random_secret().unwrap();
+        assert_eq!(secret.len(), 2048);
+        assert!(crate::utils::strings::is_hex(&secret));
+
+        let first = generate_random_secret_with(&mut ShakeSource(0)).unwrap();
+        assert_eq!(first, generate_random_secret_with(&mut ShakeSource(0)).unwrap());
+        assert_ne!(first, generate_random_secret_with(&mut ShakeSource(10)).unwrap());
+
+        let error = generate_random_secret_with(&mut StuckSource(0)).unwrap_err();
+        assert!(matches!(error, KnishIOError::WeakEntropy(_)), "{}", error);
+    }
+
+    #[test]
+    fn test_seed_entropy_checks() {
+        for weak in ["", "password", "correct horse battery staple", &"ab".repeat(500), &"0".repeat(2048)] {
+            assert!(check_seed_entropy(weak).is_err(), "accepted {:?}", weak);
+        }
+        for strong in [shake256("seed-a", 256), shake256("seed-b", 512)] {
+            assert!(check_seed_entropy(&strong).is_ok(), "rejected {}", strong);
+        }
+        assert!(generate_secret_checked("hunter2").is_err());
+    }
+}
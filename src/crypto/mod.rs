@@ -25,7 +25,6 @@
 //! - **Memory Pooling**: Zero-allocation buffer management for high-throughput operations
 //! - **Adaptive Selection**: Automatic fallback between SIMD and standard implementations
 
-use sha3::{Shake256, digest::{ExtendableOutput, Update, XofReader}};
 use crate::error::{KnishIOError, Result};
 use num_bigint;
 use num_traits;
@@ -33,6 +32,24 @@ use std::sync::LazyLock;
 
 // SIMD-optimized cryptographic operations
 pub mod simd;
+// Multi-buffer SHAKE256 for independent inputs
+pub mod lanes;
+// Random secrets and seed quality checks
+pub mod entropy;
+// Pluggable single-input SHAKE256 implementations
+pub mod backend;
+// Seed stretching and secret length settings
+pub mod derivation;
+
+pub use lanes::{set_shake_backend, shake_backend, shake256_lanes, ShakeBackend};
+pub use backend::{hash_backend, reset_hash_backend, set_hash_backend, HashBackend, PortableHash};
+#[cfg(all(feature = "armv8-sha3", target_arch = "aarch64"))]
+pub use backend::Armv8Sha3Hash;
+pub use derivation::{KeyDerivationConfig, DEFAULT_STRETCH_ITERATIONS, INTEROP_SECRET_LENGTH};
+pub use entropy::{
+    check_seed_entropy, estimate_seed_entropy, generate_random_secret, generate_random_secret_with,
+    generate_secret_checked, EntropySource, OsEntropy,
+};
 
 /// Global flag to enable/disable SIMD optimizations
 static SIMD_ENABLED: LazyLock<bool> = LazyLock::new(|| {
@@ -111,13 +128,10 @@ pub fn shake256(input: &str, output_length: usize) -> String {
 
 /// Standard SHAKE256 implementation (non-SIMD)
 ///
-/// This is the original implementation used as a fallback when SIMD is not available.
+/// Hashes through the selected `HashBackend`; used when SIMD is not enabled.
 fn shake256_standard(input: &str, output_length: usize) -> String {
-    let mut hasher = Shake256::default();
-    hasher.update(input.as_bytes());
-    let mut reader = hasher.finalize_xof();
     let mut output = vec![0u8; output_length / 8];
-    reader.read(&mut output);
+    backend::shake256_into(&[input.as_bytes()], &mut output);
     hex::encode(output)
 }
 
@@ -161,16 +175,10 @@ pub fn shake256_incremental(values: &[String], output_length: usize) -> String {
 
 /// Standard incremental SHAKE256 implementation (non-SIMD)
 fn shake256_incremental_standard(values: &[String], output_length: usize) -> String {
-    let mut hasher = Shake256::default();
-    
-    // Update the hasher incrementally with each value
-    for value in values {
-        hasher.update(value.as_bytes());
-    }
-    
-    let mut reader = hasher.finalize_xof();
+    // Absorb each value in turn through the selected backend
+    let parts: Vec<&[u8]> = values.iter().map(|value| value.as_bytes()).collect();
     let mut output = vec![0u8; output_length / 8];
-    reader.read(&mut output);
+    backend::shake256_into(&parts, &mut output);
     hex::encode(output)
 }
 
@@ -203,11 +211,8 @@ fn shake256_incremental_standard(values: &[String], output_length: usize) -> Str
 pub fn generate_secret_with_params(seed: Option<&str>, length: usize) -> String {
     if let Some(seed_str) = seed {
         // Generate from seed using SHAKE256
-        let mut hasher = Shake256::default();
-        hasher.update(seed_str.as_bytes());
-        let mut reader = hasher.finalize_xof();
         let mut output = vec![0u8; length / 2]; // length in hex chars = length/2 bytes
-        reader.read(&mut output);
+        backend::shake256_into(&[seed_str.as_bytes()], &mut output);
         hex::encode(output)
     } else {
         // Generate random secret
@@ -219,7 +224,8 @@ pub fn generate_secret_with_params(seed: Option<&str>, length: usize) -> String
 /// Generate a secret from a seed string (backward compatibility)
 ///
 /// Creates a 2048-character secret by repeatedly hashing the seed.
-/// This matches the JavaScript implementation exactly.
+/// This matches the JavaScript implementation exactly. The secret is only as strong
+/// as the seed; see `generate_random_secret` and `generate_secret_checked`.
 /// 
 /// Uses SIMD optimization when available for improved performance.
 ///
@@ -317,6 +323,64 @@ pub fn generate_batch_id() -> String {
     generate_random_hash()
 }
 
+/// Separator between the namespace and the hash of a namespaced batch ID
+pub const BATCH_NAMESPACE_SEPARATOR: char = ':';
+
+/// Longest batch ID namespace
+pub const MAX_BATCH_NAMESPACE_LEN: usize = 32;
+
+/// Check a batch ID namespace (1-32 ASCII letters, digits, `-` or `_`)
+///
+/// # Errors
+///
+/// `ConfigurationError` for any other namespace
+pub fn validate_batch_namespace(namespace: &str) -> Result<()> {
+    let valid = !namespace.is_empty()
+        && namespace.len() <= MAX_BATCH_NAMESPACE_LEN
+        && namespace.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(KnishIOError::ConfigurationError(format!(
+            "invalid batch namespace {:?}: expected 1 to {} ASCII letters, digits, '-' or '_'",
+            namespace, MAX_BATCH_NAMESPACE_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// Generate a random batch ID scoped to `namespace`, e.g. `acme:<64 hex>`
+///
+/// # Errors
+///
+/// `ConfigurationError` for an invalid namespace
+pub fn generate_namespaced_batch_id(namespace: &str) -> Result<String> {
+    validate_batch_namespace(namespace)?;
+    Ok(format!("{}{}{}", namespace, BATCH_NAMESPACE_SEPARATOR, generate_random_hash()))
+}
+
+/// Namespace of a namespaced batch ID (None for a plain one)
+pub fn batch_id_namespace(batch_id: &str) -> Option<&str> {
+    batch_id.split_once(BATCH_NAMESPACE_SEPARATOR).map(|(namespace, _)| namespace)
+}
+
+/// Check the shape of a batch ID
+///
+/// Plain IDs (anything non-empty without the namespace separator) pass as before; a
+/// namespaced ID needs a valid namespace and a 64-character hex hash.
+///
+/// # Errors
+///
+/// `BatchId` for an empty or malformed ID
+pub fn validate_batch_id(batch_id: &str) -> Result<()> {
+    match batch_id.split_once(BATCH_NAMESPACE_SEPARATOR) {
+        None if !batch_id.is_empty() => Ok(()),
+        Some((namespace, hash))
+            if validate_batch_namespace(namespace).is_ok()
+                && hash.len() == 64
+                && hash.chars().all(|c| c.is_ascii_hexdigit()) => Ok(()),
+        _ => Err(KnishIOError::BatchId),
+    }
+}
+
 /// Generate a cryptographic key for wallet operations
 ///
 /// This function generates keys used for signing and encryption.
@@ -332,15 +396,37 @@ pub fn generate_batch_id() -> String {
 ///
 /// A 2048-character hexadecimal key string
 pub fn generate_key(secret: &str, token: &str, position: &str) -> String {
-    use num_bigint::BigUint;
-    use num_traits::Num;
-
     // Algorithm (matches Kotlin/JS):
     // 1. Normalize secret/position to valid hex (hash if not already hex)
     // 2. Convert to BigInt, add together
     // 3. Hash with SHAKE256 (with token appended)
     // 4. Hash again with SHAKE256
 
+    // Generate intermediate hash (8192 bits = 2048 hex chars)
+    let intermediate_hash = shake256(&indexed_key_input(secret, token, position), 8192);
+    
+    // Second stage: hash the intermediate hash to get final key
+    shake256(&intermediate_hash, 8192)  // 8192 bits = 2048 hex chars
+}
+
+/// Generate several wallet keys at once
+///
+/// Same result as calling `generate_key` for each `(secret, token, position)`, but
+/// both hashing stages of all keys go through `shake256_lanes`.
+pub fn generate_keys(requests: &[(&str, &str, &str)]) -> Vec<String> {
+    let inputs: Vec<String> = requests
+        .iter()
+        .map(|(secret, token, position)| indexed_key_input(secret, token, position))
+        .collect();
+    let intermediate = shake256_lanes(&inputs.iter().map(String::as_str).collect::<Vec<_>>(), 8192);
+    shake256_lanes(&intermediate.iter().map(String::as_str).collect::<Vec<_>>(), 8192)
+}
+
+/// Input of the first key generation stage: hex(secret + position) followed by the token
+fn indexed_key_input(secret: &str, token: &str, position: &str) -> String {
+    use num_bigint::BigUint;
+    use num_traits::Num;
+
     // Normalize secret: if not valid hex, hash it to produce deterministic hex
     // (Matches Kotlin: Shake256.hash(secret, 128) = 128 bytes = 256 hex chars)
     let secret_hex = match BigUint::from_str_radix(secret, 16) {
@@ -367,17 +453,13 @@ pub fn generate_key(secret: &str, token: &str, position: &str) -> String {
     // Convert back to hex string (without 0x prefix)
     let indexed_key_hex = format!("{:x}", indexed_key);
     
-    // First stage: hash the indexed key (and optionally append token)
+    // First stage input: the indexed key (and optionally append token)
     let mut intermediate_input = indexed_key_hex;
     if !token.is_empty() {
         intermediate_input.push_str(token);
     }
     
-    // Generate intermediate hash (8192 bits = 2048 hex chars)
-    let intermediate_hash = shake256(&intermediate_input, 8192);
-    
-    // Second stage: hash the intermediate hash to get final key
-    shake256(&intermediate_hash, 8192)  // 8192 bits = 2048 hex chars
+    intermediate_input
 }
 
 /// Helper function to chunk a string into fragments of specified size
@@ -411,6 +493,40 @@ fn chunk_string(s: &str, chunk_size: usize) -> Vec<String> {
 ///
 /// A hexadecimal wallet address (64 characters)
 pub fn generate_address(key: &str) -> Result<String> {
+    Ok(derive_address(&derive_public_digest(key)?))
+}
+
+/// Advance WOTS+ hash chains
+///
+/// Each fragment is replaced by SHAKE256 (512 bits) of itself `rounds[i]` times. Every
+/// round hashes all chains that still have rounds left through `shake256_lanes`, so
+/// independent chains share SIMD lanes when a multi-buffer backend is active.
+pub fn hash_chains(mut fragments: Vec<String>, rounds: &[usize]) -> Vec<String> {
+    let longest = rounds.iter().copied().max().unwrap_or(0);
+    for round in 0..longest {
+        let active: Vec<usize> = (0..fragments.len()).filter(|&i| rounds.get(i).is_some_and(|&r| r > round)).collect();
+        let inputs: Vec<&str> = active.iter().map(|&i| fragments[i].as_str()).collect();
+        for (index, hashed) in active.iter().zip(shake256_lanes(&inputs, 512)) {
+            fragments[*index] = hashed;
+        }
+    }
+    fragments
+}
+
+/// Derive the WOTS+ public digest from a private key
+///
+/// First half of the address pipeline: each of the 16 key fragments is hashed
+/// through 16 SHAKE256 rounds (the top of its hash chain) and the results are
+/// absorbed into one 8192-bit digest.
+///
+/// # Arguments
+///
+/// * `key` - The cryptographic key (2048 characters)
+///
+/// # Returns
+///
+/// The public digest as 2048 hexadecimal characters
+pub fn derive_public_digest(key: &str) -> Result<String> {
     if key.len() != 2048 {
         return Err(KnishIOError::custom("Key must be 2048 characters"));
     }
@@ -418,32 +534,33 @@ pub fn generate_address(key: &str) -> Result<String> {
     // Subdivide private key into 16 fragments of 128 characters each
     let key_fragments = chunk_string(key, 128);
     
-    // Generating wallet digest - create a hasher that we'll update with each processed fragment
-    let mut digest_hasher = Shake256::default();
-    
-    for fragment in key_fragments {
-        let mut working_fragment = fragment;
-        
-        // Process each fragment through 16 rounds of SHAKE256
-        for _ in 0..16 {
-            // Each round produces 512 bits (64 bytes) of output
-            working_fragment = shake256(&working_fragment, 512);
-        }
-        
-        // Add the processed fragment to the digest
-        digest_hasher.update(working_fragment.as_bytes());
-    }
+    // Process each fragment through 16 rounds of SHAKE256 (512 bits each)
+    let public_fragments = hash_chains(key_fragments, &[16; 16]);
     
+    // Generating wallet digest - absorb each processed fragment in turn
+    let fragments: Vec<&[u8]> = public_fragments.iter().map(|fragment| fragment.as_bytes()).collect();
+
     // Get the final digest (8192 bits = 1024 bytes)
-    let mut digest_reader = digest_hasher.finalize_xof();
     let mut digest_output = vec![0u8; 1024]; // 8192 bits = 1024 bytes
-    digest_reader.read(&mut digest_output);
-    let digest = hex::encode(digest_output);
+    backend::shake256_into(&fragments, &mut digest_output);
     
+    Ok(hex::encode(digest_output))
+}
+
+/// Derive a wallet address from a WOTS+ public digest
+///
+/// Second half of the address pipeline (see `derive_public_digest`).
+///
+/// # Arguments
+///
+/// * `digest` - Public digest in hexadecimal
+///
+/// # Returns
+///
+/// A hexadecimal wallet address (64 characters)
+pub fn derive_address(digest: &str) -> String {
     // Producing wallet address - final SHAKE256 with 256-bit output
-    let final_address = shake256(&digest, 256);
-    
-    Ok(final_address)
+    shake256(digest, 256)
 }
 
 /// Generate a random position string
@@ -976,15 +1093,10 @@ pub fn generate_ots_signature(private_key: &str, molecular_hash: &str) -> Result
         key_chunks.push(chunk.to_string());
     }
 
-    // Step 3: Generate OTS fragment for each chunk
-    let mut ots_fragments = Vec::new();
-    for (i, key_chunk) in key_chunks.iter().enumerate() {
-        let normalized_value = normalized_hash[i];
-        let ots_fragment = generate_ots_fragment(key_chunk, normalized_value)?;
-        ots_fragments.push(ots_fragment);
-    }
+    // Step 3: Hash each chunk (8 - normalizedHash[index]) times
+    let rounds: Vec<usize> = normalized_hash.iter().take(16).map(|&n| (8 - n) as usize).collect();
 
-    Ok(ots_fragments)
+    Ok(hash_chains(key_chunks, &rounds))
 }
 
 /// Verify complete WOTS+ signature for a molecular hash
@@ -1019,16 +1131,12 @@ pub fn verify_ots_signature(
     // Step 1: Normalize the molecular hash  
     let normalized_hash = normalize_hash(molecular_hash);
     
-    // Step 2: Verify each OTS fragment to get public key fragments
-    let mut public_key_fragments = Vec::new();
-    for (i, ots_fragment) in ots_signature.iter().enumerate() {
-        let normalized_value = normalized_hash[i];
-        let public_key_fragment = match verify_ots_fragment(ots_fragment, normalized_value) {
-            Ok(fragment) => fragment,
-            Err(_) => return false,
-        };
-        public_key_fragments.push(public_key_fragment);
+    // Step 2: Hash each OTS fragment (8 + normalizedHash[index]) times to get public key fragments
+    if ots_signature.iter().any(|fragment| fragment.len() != 128) {
+        return false;
     }
+    let rounds: Vec<usize> = normalized_hash.iter().take(16).map(|&n| (8 + n) as usize).collect();
+    let public_key_fragments = hash_chains(ots_signature.to_vec(), &rounds);
     
     // Step 3: Hash all public key fragments together, then derive the address
     // (two-pass, matching generate_address and CheckMolecule::ots):
@@ -1179,7 +1287,26 @@ mod tests {
         let batch_id_diff = generate_batch_id_with_params(Some("different-hash"), Some(123));
         assert_ne!(batch_id_param, batch_id_diff);
     }
-    
+
+    #[test]
+    fn test_namespaced_batch_id() {
+        let batch_id = generate_namespaced_batch_id("tenant_42").unwrap();
+        assert_eq!(batch_id_namespace(&batch_id), Some("tenant_42"));
+        assert_eq!(batch_id.len(), "tenant_42:".len() + 64);
+        assert!(validate_batch_id(&batch_id).is_ok());
+
+        // Plain IDs stay valid
+        assert!(validate_batch_id(&generate_batch_id()).is_ok());
+        assert!(validate_batch_id("batch-1").is_ok());
+        assert_eq!(batch_id_namespace("batch-1"), None);
+
+        for invalid in ["", "tenant:abc", ":", &format!("bad ns:{}", "a".repeat(64))] {
+            assert!(matches!(validate_batch_id(invalid), Err(KnishIOError::BatchId)), "{}", invalid);
+        }
+        assert!(generate_namespaced_batch_id("").is_err());
+        assert!(generate_namespaced_batch_id(&"x".repeat(MAX_BATCH_NAMESPACE_LEN + 1)).is_err());
+    }
+
     #[test]
     fn test_generate_key() {
         let key = generate_key("test-secret", "TEST", "position123");
@@ -1187,6 +1314,35 @@ mod tests {
         assert!(key.chars().all(|c| c.is_ascii_hexdigit()));
     }
     
+    #[test]
+    fn test_address_pipeline() {
+        let key = generate_key("test-secret", "TEST", "position123");
+        let digest = derive_public_digest(&key).unwrap();
+        assert_eq!(digest.len(), 2048);
+
+        let address = derive_address(&digest);
+        assert!(verify_address_format(&address));
+        assert_eq!(address, generate_address(&key).unwrap());
+
+        assert!(derive_public_digest("too-short").is_err());
+    }
+
+    #[test]
+    fn test_batched_key_generation_and_chains() {
+        let positions = ["position123", "0f", "abc", "def", "0123"];
+        let requests: Vec<(&str, &str, &str)> = positions.iter().map(|p| ("test-secret", "TEST", *p)).collect();
+        let keys = generate_keys(&requests);
+        for (key, position) in keys.iter().zip(positions) {
+            assert_eq!(*key, generate_key("test-secret", "TEST", position));
+        }
+
+        let fragments = vec!["aa".repeat(64), "bb".repeat(64), "cc".repeat(64)];
+        let chained = hash_chains(fragments.clone(), &[0, 2, 1]);
+        assert_eq!(chained[0], fragments[0]);
+        assert_eq!(chained[1], shake256(&shake256(&fragments[1], 512), 512));
+        assert_eq!(chained[2], shake256(&fragments[2], 512));
+    }
+
     #[test]
     fn test_generate_position() {
         let pos = generate_position(32);
@@ -0,0 +1,307 @@
+//! Localized error messages
+//!
+//! `ErrorCatalog` maps `KnishIOError::error_code()` to a message template per locale.
+//! English (`en`) is built in and matches the errors' Display text; UI layers register
+//! their own locales, from a map or a JSON object of `code -> template`.
+//!
+//! Templates name the error's fields in braces: `{detail}` for the text carried by
+//! single-field errors (`Network`, `Custom`, ...), `{path}` / `{message}` for
+//! `ResponseShape`, `{operation}` / `{required}` / `{actual}` for
+//! `InsufficientAuthScope`, `{message}` for `RateLimited`, and `{reason}` / `{breakdown}`
+//! for `TransferInvariant`, whose reason is itself localized.
+//!
+//! A code missing from the selected locale falls back to its language (`pt-BR` to `pt`)
+//! and then to English.
+
+use super::{KnishIOError, Result};
+use std::collections::HashMap;
+
+/// Locale used when no other is selected, and the last fallback
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Built-in English templates
+const EN: &[(&str, &str)] = &[
+    ("ATOM_INDEX", "Atom index out of bounds"),
+    ("ATOMS_MISSING", "Atoms missing from molecule"),
+    ("AUTHORIZATION_REJECTED", "Authorization rejected"),
+    ("BALANCE_INSUFFICIENT", "Insufficient balance"),
+    ("BATCH_ID", "Invalid batch ID"),
+    ("CODE", "Invalid code: {detail}"),
+    ("DECRYPTION_KEY", "Decryption key error"),
+    ("ENCRYPTION", "Encryption error"),
+    ("INVALID_KEY", "Invalid key"),
+    ("WEAK_ENTROPY", "Weak entropy: {detail}"),
+    ("INVALID_QUERY", "Invalid GraphQL document: {detail}"),
+    ("INVALID_RESPONSE", "Invalid response from server"),
+    ("RESPONSE_SHAPE", "Response does not match the expected type at {path}: {message}"),
+    ("META_MISSING", "Required metadata missing"),
+    ("MOLECULAR_HASH_MISMATCH", "Molecular hash mismatch"),
+    ("MOLECULAR_HASH_MISSING", "Molecular hash missing"),
+    ("MOLECULE_MODIFIED_AFTER_SIGNING", "Molecule was modified after signing; sign it again"),
+    ("NEGATIVE_AMOUNT", "Amount cannot be negative"),
+    ("INVALID_AMOUNT", "Invalid amount: {detail}"),
+    ("INVALID_TIMESTAMP", "Invalid timestamp: {detail}"),
+    ("POLICY_INVALID", "Invalid policy"),
+    ("SIGNATURE_MALFORMED", "Signature malformed"),
+    ("SIGNATURE_MISMATCH", "Signature mismatch"),
+    ("STACKABLE_UNIT_AMOUNT", "Invalid stackable unit amount"),
+    ("STACKABLE_UNIT_DECIMALS", "Invalid stackable unit decimals"),
+    ("TRANSFER_BALANCE", "Transfer balance error"),
+    ("TRANSFER_MALFORMED", "Transfer malformed"),
+    ("TRANSFER_MISMATCHED", "Transfer mismatched"),
+    ("TRANSFER_REMAINDER", "Transfer remainder error"),
+    ("TRANSFER_TO_SELF", "Cannot transfer to self"),
+    ("TRANSFER_UNBALANCED", "Transfer unbalanced"),
+    ("TRANSFER_INVARIANT", "{reason}\n{breakdown}"),
+    ("UNAUTHENTICATED", "Unauthenticated"),
+    ("INSUFFICIENT_AUTH_SCOPE", "{operation} requires {required} auth, but the session holds {actual} auth"),
+    ("WALLET_CREDENTIAL", "Invalid wallet credentials"),
+    ("WALLET_SHADOW", "Shadow wallet error"),
+    ("WALLET_NOT_FOUND", "Wallet not found"),
+    ("WALLET_POSITION_COLLISION", "Wallet position collision: {detail}"),
+    ("MISSING_SECRET", "Missing secret"),
+    ("SECRET_UNAVAILABLE", "Secret unavailable: {detail}"),
+    ("ESCROW_UNAVAILABLE", "Escrow unavailable: {detail}"),
+    ("MULTI_SOURCE_UNSUPPORTED", "Multi-source molecules unsupported: {detail}"),
+    ("MISSING_BUNDLE", "Missing bundle"),
+    ("NO_CLIENT", "No client"),
+    ("AUTHENTICATION_FAILED", "Authentication failed"),
+    ("WRONG_TOKEN_TYPE", "Wrong token type"),
+    ("CONFIRMATION_TIMEOUT", "Molecule not confirmed: {detail}"),
+    ("NETWORK", "Network error: {detail}"),
+    ("SERIALIZATION", "Serialization error: {detail}"),
+    ("IO", "I/O error: {detail}"),
+    ("UTF8", "UTF-8 error: {detail}"),
+    ("WEBSOCKET", "WebSocket error: {detail}"),
+    ("SUBSCRIPTIONS_UNSUPPORTED", "Subscriptions unsupported: {detail}"),
+    ("SUBSCRIPTION_LIMIT", "Subscription limit reached: {detail}"),
+    ("CASSETTE_MISMATCH", "Cassette mismatch: {detail}"),
+    ("OPERATION_HELD", "Operation held: {detail}"),
+    ("RATE_LIMITED", "Rate limited: {message}"),
+    ("CONFIGURATION", "Configuration error: {detail}"),
+    ("CUSTOM", "{detail}"),
+];
+
+/// Error message templates by locale, with a selected locale
+///
+/// # Example
+///
+/// ```rust
+/// use knishio_client::{ErrorCatalog, KnishIOError};
+/// use std::collections::HashMap;
+///
+/// let mut catalog = ErrorCatalog::new();
+/// catalog.add_locale("de", HashMap::from([
+///     ("BALANCE_INSUFFICIENT".to_string(), "Guthaben nicht ausreichend".to_string()),
+///     ("NETWORK".to_string(), "Netzwerkfehler: {detail}".to_string()),
+/// ]));
+/// catalog.set_locale("de-AT");
+///
+/// assert_eq!(catalog.message(&KnishIOError::BalanceInsufficient), "Guthaben nicht ausreichend");
+/// assert_eq!(catalog.message(&KnishIOError::Network("timeout".into())), "Netzwerkfehler: timeout");
+/// // Not translated: English
+/// assert_eq!(catalog.message(&KnishIOError::MissingSecret), "Missing secret");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ErrorCatalog {
+    locale: String,
+    locales: HashMap<String, HashMap<String, String>>,
+}
+
+impl ErrorCatalog {
+    /// Catalog with the built-in English messages, set to `en`
+    pub fn new() -> Self {
+        let en = EN.iter().map(|(code, template)| (code.to_string(), template.to_string())).collect();
+        ErrorCatalog {
+            locale: DEFAULT_LOCALE.to_string(),
+            locales: HashMap::from([(DEFAULT_LOCALE.to_string(), en)]),
+        }
+    }
+
+    /// Same catalog with `locale` selected
+    pub fn with_locale(mut self, locale: &str) -> Self {
+        self.set_locale(locale);
+        self
+    }
+
+    /// Select the locale messages are rendered in, e.g. `fr` or `pt-BR`
+    pub fn set_locale(&mut self, locale: &str) {
+        self.locale = normalize(locale);
+    }
+
+    /// Selected locale
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Locales with at least one message, sorted
+    pub fn locales(&self) -> Vec<&str> {
+        let mut locales: Vec<&str> = self.locales.keys().map(String::as_str).collect();
+        locales.sort_unstable();
+        locales
+    }
+
+    /// Add or override templates for `locale`, keyed by error code
+    pub fn add_locale(&mut self, locale: &str, messages: HashMap<String, String>) -> &mut Self {
+        self.locales.entry(normalize(locale)).or_default().extend(messages);
+        self
+    }
+
+    /// Add templates for `locale` from a JSON object of `code -> template`
+    ///
+    /// # Errors
+    ///
+    /// `Serialization` if `json` is not an object of strings
+    pub fn add_locale_json(&mut self, locale: &str, json: &str) -> Result<&mut Self> {
+        let messages: HashMap<String, String> = serde_json::from_str(json)?;
+        Ok(self.add_locale(locale, messages))
+    }
+
+    /// Template for `code` in the selected locale, falling back to its language and English
+    pub fn template(&self, code: &str) -> Option<&str> {
+        let language = self.locale.split('-').next().unwrap_or_default();
+        [self.locale.as_str(), language, DEFAULT_LOCALE]
+            .into_iter()
+            .find_map(|locale| self.locales.get(locale).and_then(|messages| messages.get(code)))
+            .map(String::as_str)
+    }
+
+    /// `error` rendered in the selected locale
+    ///
+    /// Falls back to the error's Display text for a code no locale knows.
+    pub fn message(&self, error: &KnishIOError) -> String {
+        let Some(template) = self.template(error.error_code()) else {
+            return error.to_string();
+        };
+
+        self.params(error)
+            .into_iter()
+            .fold(template.to_string(), |message, (name, value)| message.replace(&format!("{{{}}}", name), &value))
+    }
+
+    /// Values for the placeholders of `error`'s template
+    fn params(&self, error: &KnishIOError) -> Vec<(&'static str, String)> {
+        match error {
+            KnishIOError::Code(detail)
+            | KnishIOError::WeakEntropy(detail)
+            | KnishIOError::InvalidAmount(detail)
+            | KnishIOError::InvalidTimestamp(detail)
+            | KnishIOError::InvalidQuery(detail)
+            | KnishIOError::SecretUnavailable(detail)
+            | KnishIOError::EscrowUnavailable(detail)
+            | KnishIOError::MultiSourceUnsupported(detail)
+            | KnishIOError::WalletPositionCollision(detail)
+            | KnishIOError::ConfirmationTimeout(detail)
+            | KnishIOError::Network(detail)
+            | KnishIOError::Serialization(detail)
+            | KnishIOError::Io(detail)
+            | KnishIOError::Utf8(detail)
+            | KnishIOError::WebSocketError(detail)
+            | KnishIOError::SubscriptionsUnsupported(detail)
+            | KnishIOError::SubscriptionLimit(detail)
+            | KnishIOError::CassetteMismatch(detail)
+            | KnishIOError::OperationHeld(detail)
+            | KnishIOError::ConfigurationError(detail)
+            | KnishIOError::Custom(detail) => vec![("detail", detail.clone())],
+            KnishIOError::ResponseShape { path, message } => vec![("path", path.clone()), ("message", message.clone())],
+            KnishIOError::RateLimited { message, .. } => vec![("message", message.clone())],
+            KnishIOError::InsufficientAuthScope { operation, required, actual } => vec![
+                ("operation", operation.clone()),
+                ("required", required.to_string()),
+                ("actual", actual.to_string()),
+            ],
+            KnishIOError::TransferInvariant { reason, breakdown } => {
+                vec![("reason", self.message(reason)), ("breakdown", breakdown.clone())]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Default for ErrorCatalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `pt_br` / `PT-BR` -> `pt-BR`
+fn normalize(locale: &str) -> String {
+    let mut parts = locale.trim().split(['-', '_']);
+    let language = parts.next().unwrap_or_default().to_lowercase();
+    let rest: Vec<String> = parts.map(str::to_uppercase).collect();
+    if rest.is_empty() {
+        language
+    } else {
+        format!("{}-{}", language, rest.join("-"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthScope;
+
+    #[test]
+    fn test_english_matches_display() {
+        let errors = vec![
+            KnishIOError::AtomIndex,
+            KnishIOError::Code("X1".to_string()),
+            KnishIOError::InvalidAmount("1,5".to_string()),
+            KnishIOError::InvalidTimestamp("2026-01-01".to_string()),
+            KnishIOError::InvalidQuery("1:7: Unclosed `{`".to_string()),
+            KnishIOError::SecretUnavailable("KNISHIO_SECRET is not set".to_string()),
+            KnishIOError::EscrowUnavailable("escrow is already released".to_string()),
+            KnishIOError::MultiSourceUnsupported("2 source wallets".to_string()),
+            KnishIOError::WalletPositionCollision("GOLD wallet address abc is taken after 3 retries".to_string()),
+            KnishIOError::SubscriptionsUnsupported("WalletStatus".to_string()),
+            KnishIOError::SubscriptionLimit("8 of 8 subscriptions open, subscription_1 refused".to_string()),
+            KnishIOError::CassetteMismatch("no unplayed Balance in transfer.json".to_string()),
+            KnishIOError::OperationHeld("transfer of 50000 GOLD awaits compliance review".to_string()),
+            KnishIOError::ResponseShape { path: "$.data".to_string(), message: "missing".to_string() },
+            KnishIOError::MoleculeModifiedAfterSigning,
+            KnishIOError::ConfirmationTimeout("abc123".to_string()),
+            KnishIOError::TransferInvariant {
+                reason: Box::new(KnishIOError::TransferUnbalanced),
+                breakdown: "  atom 0: -5".to_string(),
+            },
+            KnishIOError::InsufficientAuthScope {
+                operation: "transfer_token".to_string(),
+                required: AuthScope::Profile,
+                actual: AuthScope::Guest,
+            },
+            KnishIOError::RateLimited { message: "429".to_string(), retry_after: None },
+            KnishIOError::custom("anything"),
+        ];
+
+        let catalog = ErrorCatalog::default();
+        for error in &errors {
+            assert_eq!(catalog.message(error), error.to_string(), "{}", error.error_code());
+        }
+        assert_eq!(EN.len(), EN.iter().map(|(code, _)| code).collect::<std::collections::HashSet<_>>().len());
+    }
+
+    #[test]
+    fn test_locale_selection() {
+        let mut catalog = ErrorCatalog::new();
+        catalog
+            .add_locale_json("fr", r#"{ "TRANSFER_UNBALANCED": "Transfert déséquilibré", "CUSTOM": "Erreur : {detail}" }"#)
+            .unwrap();
+        catalog.add_locale("fr_ca", HashMap::from([("CUSTOM".to_string(), "Erreur québécoise : {detail}".to_string())]));
+        assert!(catalog.add_locale_json("fr", "[1]").is_err());
+        assert_eq!(catalog.locales(), vec!["en", "fr", "fr-CA"]);
+
+        let invariant = KnishIOError::TransferInvariant {
+            reason: Box::new(KnishIOError::TransferUnbalanced),
+            breakdown: "atoms".to_string(),
+        };
+
+        catalog.set_locale("FR-ca");
+        assert_eq!(catalog.locale(), "fr-CA");
+        assert_eq!(catalog.message(&KnishIOError::custom("x")), "Erreur québécoise : x");
+        assert_eq!(catalog.message(&invariant), "Transfert déséquilibré\natoms");
+        assert_eq!(catalog.message(&KnishIOError::NoClient), "No client");
+
+        let catalog = catalog.with_locale("ja");
+        assert_eq!(catalog.message(&KnishIOError::custom("x")), "x");
+    }
+}
@@ -6,6 +6,10 @@
 
 use thiserror::Error;
 
+pub mod catalog;
+
+pub use catalog::{ErrorCatalog, DEFAULT_LOCALE};
+
 /// Main error type for the KnishIO SDK
 ///
 /// This enum contains all possible errors that can occur during SDK operations,
@@ -59,12 +63,29 @@ pub enum KnishIOError {
     /// Invalid key format or size
     #[error("Invalid key")]
     InvalidKey,
+
+    /// Seed or entropy source too predictable to derive a secret from
+    #[error("Weak entropy: {0}")]
+    WeakEntropy(String),
     
     // Response errors
     
+    /// GraphQL document rejected by the local lint before sending
+    #[error("Invalid GraphQL document: {0}")]
+    InvalidQuery(String),
+
     /// Invalid response received from server
     #[error("Invalid response from server")]
     InvalidResponse,
+
+    /// Response data does not fit the type it is deserialized into
+    #[error("Response does not match the expected type at {path}: {message}")]
+    ResponseShape {
+        /// JSON path of the failing field
+        path: String,
+        /// What serde reported
+        message: String,
+    },
     
     // Metadata errors
     
@@ -81,12 +102,24 @@ pub enum KnishIOError {
     /// Molecular hash is missing when required
     #[error("Molecular hash missing")]
     MolecularHashMissing,
+
+    /// Atoms were added, removed or changed after the molecule was signed
+    #[error("Molecule was modified after signing; sign it again")]
+    MoleculeModifiedAfterSigning,
     
     // Amount errors
     
     /// Amount cannot be negative
     #[error("Amount cannot be negative")]
     NegativeAmount,
+
+    /// Amount is not a finite decimal number
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+
+    /// A `createdAt` that is not milliseconds since the Unix epoch
+    #[error("Invalid timestamp: {0}")]
+    InvalidTimestamp(String),
     
     // Policy errors
     
@@ -139,12 +172,33 @@ pub enum KnishIOError {
     /// Transfer is unbalanced (inputs != outputs)
     #[error("Transfer unbalanced")]
     TransferUnbalanced,
+
+    /// V-isotope ledger math failed; `reason` is the underlying transfer error and
+    /// `breakdown` lists every V atom's contribution
+    #[error("{reason}\n{breakdown}")]
+    TransferInvariant {
+        /// Underlying transfer error (`TransferUnbalanced`, `TransferRemainder`, ...)
+        reason: Box<KnishIOError>,
+        /// Per-atom ledger math
+        breakdown: String,
+    },
     
     // Authentication errors
     
     /// User is not authenticated
     #[error("Unauthenticated")]
     Unauthenticated,
+
+    /// The current auth token's scope does not allow the operation
+    #[error("{operation} requires {required} auth, but the session holds {actual} auth")]
+    InsufficientAuthScope {
+        /// Client method that was refused
+        operation: String,
+        /// Scope the operation needs
+        required: crate::auth::AuthScope,
+        /// Scope of the current token
+        actual: crate::auth::AuthScope,
+    },
     
     // Wallet errors
     
@@ -160,11 +214,27 @@ pub enum KnishIOError {
     #[error("Wallet not found")]
     WalletNotFound,    
 
+    /// Every position drawn for a new wallet gave an address already on the ledger
+    #[error("Wallet position collision: {0}")]
+    WalletPositionCollision(String),
+
     // Missing resource errors
     
     /// Missing secret for wallet operation
     #[error("Missing secret")]
     MissingSecret,
+
+    /// A secret source could not produce a secret
+    #[error("Secret unavailable: {0}")]
+    SecretUnavailable(String),
+
+    /// An escrow cannot be settled: unknown, already settled, held elsewhere or not expired
+    #[error("Escrow unavailable: {0}")]
+    EscrowUnavailable(String),
+
+    /// A molecule would have to debit more than one source wallet
+    #[error("Multi-source molecules unsupported: {0}")]
+    MultiSourceUnsupported(String),
     
     /// Missing bundle hash
     #[error("Missing bundle")]
@@ -181,6 +251,10 @@ pub enum KnishIOError {
     /// Wrong token type for requested operation
     #[error("Wrong token type")]
     WrongTokenType,
+
+    /// A molecule was still pending when the wait for its confirmation ran out
+    #[error("Molecule not confirmed: {0}")]
+    ConfirmationTimeout(String),
     
     // Network and external errors
     
@@ -204,6 +278,31 @@ pub enum KnishIOError {
     #[error("WebSocket error: {0}")]
     WebSocketError(String),
 
+    /// The node does not serve a subscription the SDK was asked for
+    #[error("Subscriptions unsupported: {0}")]
+    SubscriptionsUnsupported(String),
+
+    /// A subscription was refused because the client is at its subscription limit
+    #[error("Subscription limit reached: {0}")]
+    SubscriptionLimit(String),
+
+    /// A replaying cassette has no recorded reply for a request
+    #[error("Cassette mismatch: {0}")]
+    CassetteMismatch(String),
+
+    /// An operation hook stopped the operation, e.g. for a compliance hold
+    #[error("Operation held: {0}")]
+    OperationHeld(String),
+
+    /// The node is throttling requests (HTTP 429, or a throttling GraphQL error)
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        /// HTTP status line or GraphQL error text
+        message: String,
+        /// How long the node asked the client to wait, when it said so
+        retry_after: Option<std::time::Duration>,
+    },
+
     /// Configuration or builder validation error
     #[error("Configuration error: {0}")]
     ConfigurationError(String),
@@ -242,6 +341,14 @@ impl KnishIOError {
         matches!(self, KnishIOError::Network(_) | KnishIOError::WebSocketError(_))
     }
     
+    /// Wait requested by the node before the next attempt (`RateLimited` only)
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            KnishIOError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
     /// Check if this error is a cryptographic error
     pub fn is_crypto_error(&self) -> bool {
         matches!(
@@ -249,10 +356,12 @@ impl KnishIOError {
             KnishIOError::DecryptionKey
                 | KnishIOError::EncryptionError
                 | KnishIOError::InvalidKey
+                | KnishIOError::WeakEntropy(_)
                 | KnishIOError::SignatureMalformed
                 | KnishIOError::SignatureMismatch
                 | KnishIOError::MolecularHashMismatch
                 | KnishIOError::MolecularHashMissing
+                | KnishIOError::MoleculeModifiedAfterSigning
         )
     }
     
@@ -264,10 +373,13 @@ impl KnishIOError {
                 | KnishIOError::AtomsMissing
                 | KnishIOError::BatchId
                 | KnishIOError::Code(_)
+                | KnishIOError::InvalidQuery(_)
+                | KnishIOError::InvalidTimestamp(_)
                 | KnishIOError::InvalidResponse
                 | KnishIOError::MetaMissing
                 | KnishIOError::NegativeAmount
                 | KnishIOError::PolicyInvalid
+                | KnishIOError::ResponseShape { .. }
                 | KnishIOError::StackableUnitAmount
                 | KnishIOError::StackableUnitDecimals
                 | KnishIOError::TransferMalformed
@@ -282,6 +394,7 @@ impl KnishIOError {
             self,
             KnishIOError::AuthorizationRejected
                 | KnishIOError::Unauthenticated
+                | KnishIOError::InsufficientAuthScope { .. }
                 | KnishIOError::WalletCredential
         )
     }
@@ -295,8 +408,85 @@ impl KnishIOError {
                 | KnishIOError::TransferRemainder
                 | KnishIOError::TransferToSelf
                 | KnishIOError::TransferUnbalanced
+                | KnishIOError::TransferInvariant { .. }
         )
     }
+
+    /// Stable code identifying the kind of error, e.g. `BALANCE_INSUFFICIENT`
+    ///
+    /// Codes do not change between releases, so they can key translations
+    /// (`ErrorCatalog`) and UI logic; the Display text may change.
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            KnishIOError::AtomIndex => "ATOM_INDEX",
+            KnishIOError::AtomsMissing => "ATOMS_MISSING",
+            KnishIOError::AuthorizationRejected => "AUTHORIZATION_REJECTED",
+            KnishIOError::BalanceInsufficient => "BALANCE_INSUFFICIENT",
+            KnishIOError::BatchId => "BATCH_ID",
+            KnishIOError::Code(_) => "CODE",
+            KnishIOError::DecryptionKey => "DECRYPTION_KEY",
+            KnishIOError::EncryptionError => "ENCRYPTION",
+            KnishIOError::InvalidKey => "INVALID_KEY",
+            KnishIOError::WeakEntropy(_) => "WEAK_ENTROPY",
+            KnishIOError::InvalidQuery(_) => "INVALID_QUERY",
+            KnishIOError::InvalidResponse => "INVALID_RESPONSE",
+            KnishIOError::ResponseShape { .. } => "RESPONSE_SHAPE",
+            KnishIOError::MetaMissing => "META_MISSING",
+            KnishIOError::MolecularHashMismatch => "MOLECULAR_HASH_MISMATCH",
+            KnishIOError::MolecularHashMissing => "MOLECULAR_HASH_MISSING",
+            KnishIOError::MoleculeModifiedAfterSigning => "MOLECULE_MODIFIED_AFTER_SIGNING",
+            KnishIOError::NegativeAmount => "NEGATIVE_AMOUNT",
+            KnishIOError::InvalidAmount(_) => "INVALID_AMOUNT",
+            KnishIOError::InvalidTimestamp(_) => "INVALID_TIMESTAMP",
+            KnishIOError::PolicyInvalid => "POLICY_INVALID",
+            KnishIOError::SignatureMalformed => "SIGNATURE_MALFORMED",
+            KnishIOError::SignatureMismatch => "SIGNATURE_MISMATCH",
+            KnishIOError::StackableUnitAmount => "STACKABLE_UNIT_AMOUNT",
+            KnishIOError::StackableUnitDecimals => "STACKABLE_UNIT_DECIMALS",
+            KnishIOError::TransferBalance => "TRANSFER_BALANCE",
+            KnishIOError::TransferMalformed => "TRANSFER_MALFORMED",
+            KnishIOError::TransferMismatched => "TRANSFER_MISMATCHED",
+            KnishIOError::TransferRemainder => "TRANSFER_REMAINDER",
+            KnishIOError::TransferToSelf => "TRANSFER_TO_SELF",
+            KnishIOError::TransferUnbalanced => "TRANSFER_UNBALANCED",
+            KnishIOError::TransferInvariant { .. } => "TRANSFER_INVARIANT",
+            KnishIOError::Unauthenticated => "UNAUTHENTICATED",
+            KnishIOError::InsufficientAuthScope { .. } => "INSUFFICIENT_AUTH_SCOPE",
+            KnishIOError::WalletCredential => "WALLET_CREDENTIAL",
+            KnishIOError::WalletShadow => "WALLET_SHADOW",
+            KnishIOError::WalletNotFound => "WALLET_NOT_FOUND",
+            KnishIOError::WalletPositionCollision(_) => "WALLET_POSITION_COLLISION",
+            KnishIOError::MissingSecret => "MISSING_SECRET",
+            KnishIOError::SecretUnavailable(_) => "SECRET_UNAVAILABLE",
+            KnishIOError::EscrowUnavailable(_) => "ESCROW_UNAVAILABLE",
+            KnishIOError::MultiSourceUnsupported(_) => "MULTI_SOURCE_UNSUPPORTED",
+            KnishIOError::MissingBundle => "MISSING_BUNDLE",
+            KnishIOError::NoClient => "NO_CLIENT",
+            KnishIOError::AuthenticationFailed => "AUTHENTICATION_FAILED",
+            KnishIOError::WrongTokenType => "WRONG_TOKEN_TYPE",
+            KnishIOError::ConfirmationTimeout(_) => "CONFIRMATION_TIMEOUT",
+            KnishIOError::Network(_) => "NETWORK",
+            KnishIOError::Serialization(_) => "SERIALIZATION",
+            KnishIOError::Io(_) => "IO",
+            KnishIOError::Utf8(_) => "UTF8",
+            KnishIOError::WebSocketError(_) => "WEBSOCKET",
+            KnishIOError::SubscriptionsUnsupported(_) => "SUBSCRIPTIONS_UNSUPPORTED",
+            KnishIOError::SubscriptionLimit(_) => "SUBSCRIPTION_LIMIT",
+            KnishIOError::CassetteMismatch(_) => "CASSETTE_MISMATCH",
+            KnishIOError::OperationHeld(_) => "OPERATION_HELD",
+            KnishIOError::RateLimited { .. } => "RATE_LIMITED",
+            KnishIOError::ConfigurationError(_) => "CONFIGURATION",
+            KnishIOError::Custom(_) => "CUSTOM",
+        }
+    }
+
+    /// The transfer error behind a `TransferInvariant`, or `self` for any other error
+    pub fn transfer_reason(&self) -> &KnishIOError {
+        match self {
+            KnishIOError::TransferInvariant { reason, .. } => reason,
+            other => other,
+        }
+    }
 }
 
 // Implement From traits for easier error conversion
@@ -338,6 +528,8 @@ mod tests {
         
         let err = KnishIOError::custom("Custom error message");
         assert_eq!(err.to_string(), "Custom error message");
+        assert_eq!(err.error_code(), "CUSTOM");
+        assert_eq!(KnishIOError::BalanceInsufficient.error_code(), "BALANCE_INSUFFICIENT");
     }
     
     #[test]
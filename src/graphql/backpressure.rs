@@ -0,0 +1,428 @@
+//! Bounded delivery of subscription events
+//!
+//! The connection task hands every event to its subscription's channel before reading the
+//! next frame. An unbounded channel never pushes back, so a consumer that cannot keep up
+//! grows its queue without limit. A bounded channel caps the queue, and its
+//! `OverflowPolicy` decides what a full queue does with the next event. Consumers run on
+//! their own task (`EventReceiver::spawn_consumer`), and `ConsumerStats` shows how far
+//! behind each one is.
+
+use crate::GraphQLResponse;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// What a full subscription channel does with a new event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Discard the oldest queued event to make room
+    #[default]
+    DropOldest,
+    /// Hold the connection until the consumer makes room; every subscription on the
+    /// connection waits with it
+    Block,
+    /// End the subscription with an error response
+    Error,
+}
+
+/// Capacity and overflow policy of one subscription channel
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsumerConfig {
+    /// Most events queued for the consumer
+    pub capacity: usize,
+    /// What happens to an event arriving at a full queue
+    pub overflow: OverflowPolicy,
+}
+
+impl Default for ConsumerConfig {
+    fn default() -> Self {
+        ConsumerConfig {
+            capacity: 256,
+            overflow: OverflowPolicy::default(),
+        }
+    }
+}
+
+impl ConsumerConfig {
+    /// Set the most events queued (at least one)
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Set the overflow policy
+    pub fn overflow(mut self, overflow: OverflowPolicy) -> Self {
+        self.overflow = overflow;
+        self
+    }
+}
+
+/// How far behind a subscription consumer is
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConsumerStats {
+    /// Channel capacity
+    pub capacity: usize,
+    /// Events waiting for the consumer
+    pub queued: usize,
+    /// Most events ever waiting at once
+    pub high_water: usize,
+    /// Events the consumer has taken
+    pub delivered: u64,
+    /// Events discarded by `OverflowPolicy::DropOldest`
+    pub dropped: u64,
+    /// Times the connection waited for room under `OverflowPolicy::Block`
+    pub blocked: u64,
+    /// Age of the oldest waiting event
+    pub lag: Duration,
+    /// Longest any delivered event waited
+    pub max_lag: Duration,
+    /// True once `OverflowPolicy::Error` ended the subscription
+    pub overflowed: bool,
+}
+
+impl ConsumerStats {
+    /// True when the next event meets a full queue
+    pub fn is_full(&self) -> bool {
+        self.queued >= self.capacity
+    }
+}
+
+/// Why an event was not queued
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SendFailure {
+    /// The receiver is gone or the channel was closed
+    Closed,
+    /// The queue was full under `OverflowPolicy::Error`; the channel is now closed
+    Overflow,
+}
+
+#[derive(Debug, Default)]
+struct ChannelState {
+    queue: VecDeque<(GraphQLResponse, Instant)>,
+    stats: ConsumerStats,
+    sender_closed: bool,
+    receiver_closed: bool,
+}
+
+impl ChannelState {
+    fn push(&mut self, response: GraphQLResponse) {
+        self.queue.push_back((response, Instant::now()));
+        self.stats.high_water = self.stats.high_water.max(self.queue.len());
+    }
+}
+
+#[derive(Debug)]
+struct Channel {
+    config: ConsumerConfig,
+    state: Mutex<ChannelState>,
+    readable: Notify,
+    writable: Notify,
+}
+
+impl Channel {
+    fn state(&self) -> std::sync::MutexGuard<'_, ChannelState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn stats(&self) -> ConsumerStats {
+        let state = self.state();
+        let mut stats = state.stats.clone();
+        stats.capacity = self.config.capacity;
+        stats.queued = state.queue.len();
+        stats.lag = state.queue.front().map(|(_, at)| at.elapsed()).unwrap_or_default();
+        stats
+    }
+
+    fn close_sender(&self) {
+        self.state().sender_closed = true;
+        self.readable.notify_one();
+    }
+}
+
+/// Closes the channel when the last `EventSender` clone is dropped
+#[derive(Debug)]
+struct SenderGuard(Arc<Channel>);
+
+impl Drop for SenderGuard {
+    fn drop(&mut self) {
+        self.0.close_sender();
+    }
+}
+
+/// Producer side of a bounded subscription channel, held by the connection task
+#[derive(Debug, Clone)]
+pub(crate) struct EventSender {
+    channel: Arc<Channel>,
+    _guard: Arc<SenderGuard>,
+}
+
+impl EventSender {
+    /// Queue `response`, applying the overflow policy when the queue is full
+    pub(crate) async fn send(&self, response: GraphQLResponse) -> std::result::Result<(), SendFailure> {
+        let capacity = self.channel.config.capacity;
+        let mut waited = false;
+
+        loop {
+            {
+                let mut state = self.channel.state();
+                if state.sender_closed || state.receiver_closed {
+                    return Err(SendFailure::Closed);
+                }
+
+                if state.queue.len() < capacity {
+                    state.push(response);
+                    drop(state);
+                    self.channel.readable.notify_one();
+                    return Ok(());
+                }
+
+                match self.channel.config.overflow {
+                    OverflowPolicy::DropOldest => {
+                        state.queue.pop_front();
+                        state.stats.dropped += 1;
+                        state.push(response);
+                        drop(state);
+                        self.channel.readable.notify_one();
+                        return Ok(());
+                    }
+                    OverflowPolicy::Error => {
+                        state.stats.overflowed = true;
+                        state.sender_closed = true;
+                        state.push(overflow_response(capacity));
+                        drop(state);
+                        self.channel.readable.notify_one();
+                        return Err(SendFailure::Overflow);
+                    }
+                    OverflowPolicy::Block => {
+                        if !waited {
+                            state.stats.blocked += 1;
+                            waited = true;
+                        }
+                    }
+                }
+            }
+
+            self.channel.writable.notified().await;
+        }
+    }
+
+    /// Queue a final `response` regardless of capacity and close the channel
+    pub(crate) fn close_with(&self, response: GraphQLResponse) {
+        let mut state = self.channel.state();
+        if !state.sender_closed && !state.receiver_closed {
+            state.push(response);
+        }
+        state.sender_closed = true;
+        drop(state);
+        self.channel.readable.notify_one();
+    }
+
+    /// Current consumer statistics
+    pub(crate) fn stats(&self) -> ConsumerStats {
+        self.channel.stats()
+    }
+}
+
+/// Consumer side of a bounded subscription channel
+///
+/// Dropping the receiver discards anything still queued and releases a connection
+/// blocked on it.
+#[derive(Debug)]
+pub struct EventReceiver {
+    channel: Arc<Channel>,
+}
+
+impl EventReceiver {
+    /// Next event, or None once the subscription has ended and the queue is drained
+    pub async fn recv(&mut self) -> Option<GraphQLResponse> {
+        loop {
+            if let Some(response) = self.take() {
+                return Some(response);
+            }
+            if self.channel.state().sender_closed {
+                // The sender may have queued a last event just before closing
+                return self.take();
+            }
+            self.channel.readable.notified().await;
+        }
+    }
+
+    /// Next queued event without waiting
+    pub fn try_recv(&mut self) -> Option<GraphQLResponse> {
+        self.take()
+    }
+
+    /// Current consumer statistics
+    pub fn stats(&self) -> ConsumerStats {
+        self.channel.stats()
+    }
+
+    /// A handle reporting this consumer's statistics from elsewhere
+    pub fn monitor(&self) -> ConsumerMonitor {
+        ConsumerMonitor { channel: self.channel.clone() }
+    }
+
+    /// Run `callback` on every event from a task of its own
+    ///
+    /// The task ends when the subscription does.
+    pub fn spawn_consumer<F>(mut self, mut callback: F) -> tokio::task::JoinHandle<()>
+    where
+        F: FnMut(GraphQLResponse) + Send + 'static,
+    {
+        tokio::spawn(async move {
+            while let Some(response) = self.recv().await {
+                callback(response);
+            }
+        })
+    }
+
+    fn take(&mut self) -> Option<GraphQLResponse> {
+        let mut state = self.channel.state();
+        let (response, queued_at) = state.queue.pop_front()?;
+        state.stats.delivered += 1;
+        state.stats.max_lag = state.stats.max_lag.max(queued_at.elapsed());
+        drop(state);
+        self.channel.writable.notify_one();
+        Some(response)
+    }
+}
+
+impl Drop for EventReceiver {
+    fn drop(&mut self) {
+        let mut state = self.channel.state();
+        state.receiver_closed = true;
+        state.queue.clear();
+        drop(state);
+        self.channel.writable.notify_one();
+    }
+}
+
+/// Reports a subscription consumer's statistics without consuming its events
+#[derive(Debug, Clone)]
+pub struct ConsumerMonitor {
+    channel: Arc<Channel>,
+}
+
+impl ConsumerMonitor {
+    /// Current consumer statistics
+    pub fn stats(&self) -> ConsumerStats {
+        self.channel.stats()
+    }
+}
+
+/// Create a bounded subscription channel
+pub(crate) fn event_channel(config: ConsumerConfig) -> (EventSender, EventReceiver) {
+    let channel = Arc::new(Channel {
+        config: ConsumerConfig { capacity: config.capacity.max(1), ..config },
+        state: Mutex::new(ChannelState::default()),
+        readable: Notify::new(),
+        writable: Notify::new(),
+    });
+    let sender = EventSender {
+        channel: channel.clone(),
+        _guard: Arc::new(SenderGuard(channel.clone())),
+    };
+    (sender, EventReceiver { channel })
+}
+
+/// The response ending a subscription whose consumer fell behind
+fn overflow_response(capacity: usize) -> GraphQLResponse {
+    GraphQLResponse {
+        data: None,
+        errors: Some(vec![crate::GraphQLError {
+            message: format!("Subscription consumer fell behind with {} events queued", capacity),
+            locations: None,
+            path: None,
+            extensions: None,
+        }]),
+        extensions: None,
+        headers: Default::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(n: u64) -> GraphQLResponse {
+        GraphQLResponse { data: Some(json!({ "n": n })), errors: None, extensions: None, headers: Default::default() }
+    }
+
+    fn number(response: &GraphQLResponse) -> Option<u64> {
+        response.data.as_ref().and_then(|data| data["n"].as_u64())
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest_keeps_newest_events() {
+        let (sender, mut receiver) = event_channel(ConsumerConfig::default().capacity(2));
+        for n in 1..=5 {
+            sender.send(event(n)).await.unwrap();
+        }
+
+        let stats = receiver.stats();
+        assert_eq!((stats.queued, stats.high_water, stats.dropped), (2, 2, 3));
+        assert!(stats.is_full());
+
+        assert_eq!(receiver.recv().await.as_ref().and_then(number), Some(4));
+        assert_eq!(receiver.try_recv().as_ref().and_then(number), Some(5));
+        assert_eq!(receiver.stats().delivered, 2);
+
+        drop(sender);
+        assert!(receiver.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_error_policy_ends_subscription() {
+        let (sender, mut receiver) = event_channel(ConsumerConfig::default().capacity(1).overflow(OverflowPolicy::Error));
+        sender.send(event(1)).await.unwrap();
+        assert_eq!(sender.send(event(2)).await, Err(SendFailure::Overflow));
+        assert_eq!(sender.send(event(3)).await, Err(SendFailure::Closed));
+        assert!(receiver.stats().overflowed);
+
+        assert_eq!(receiver.recv().await.as_ref().and_then(number), Some(1));
+        let last = receiver.recv().await.unwrap();
+        assert!(last.errors.is_some());
+        assert!(receiver.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_block_policy_waits_for_consumer() {
+        let (sender, receiver) = event_channel(ConsumerConfig::default().capacity(1).overflow(OverflowPolicy::Block));
+        let monitor = receiver.monitor();
+        sender.send(event(1)).await.unwrap();
+
+        // The second send waits until the consumer takes the first event
+        let blocked = tokio::time::timeout(Duration::from_millis(20), sender.send(event(2))).await;
+        assert!(blocked.is_err());
+        assert_eq!(monitor.stats().blocked, 1);
+
+        let (seen_sender, mut seen) = tokio::sync::mpsc::unbounded_channel();
+        let consumer = receiver.spawn_consumer(move |response| {
+            let _ = seen_sender.send(number(&response));
+        });
+        sender.send(event(2)).await.unwrap();
+        assert_eq!(seen.recv().await, Some(Some(1)));
+        assert_eq!(seen.recv().await, Some(Some(2)));
+
+        drop(sender);
+        consumer.await.unwrap();
+        assert_eq!(monitor.stats().delivered, 2);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_receiver_releases_blocked_sender() {
+        let (sender, receiver) = event_channel(ConsumerConfig::default().capacity(1).overflow(OverflowPolicy::Block));
+        sender.send(event(1)).await.unwrap();
+
+        let pending = tokio::spawn({
+            let sender = sender.clone();
+            async move { sender.send(event(2)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(receiver);
+
+        assert_eq!(pending.await.unwrap(), Err(SendFailure::Closed));
+    }
+}
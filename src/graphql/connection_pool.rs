@@ -67,7 +67,7 @@ impl Default for PoolConfig {
             request_timeout: Duration::from_secs(60),
             keep_alive_timeout: Duration::from_secs(90),
             cleanup_interval: Duration::from_secs(60),
-            user_agent: format!("KnishIO-Rust-SDK/{}", env!("CARGO_PKG_VERSION")),
+            user_agent: super::default_user_agent(),
             insecure_tls: false,
         }
     }
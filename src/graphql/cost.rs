@@ -0,0 +1,259 @@
+//! Query cost hints
+//!
+//! With thresholds configured, every query or mutation that takes longer than
+//! `slow_after` or returns more than `large_response_bytes` is reported as a
+//! `QueryCostWarning`: a `tracing` warning on the `knishio::query_cost` target, the
+//! client's listener if one is set, and the counters in `QueryCostStats`.
+//!
+//! Warnings carry the operation name and a digest of the variables rather than the
+//! variables themselves, so they can be logged and grouped without leaking secrets.
+//! Anonymous operations are named by their first root field (`MetaType`, `Balance`, ...).
+
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::warn;
+
+/// Thresholds past which an operation is reported
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryCostConfig {
+    /// Operations taking longer than this are reported as slow
+    pub slow_after: Option<Duration>,
+    /// Replies with more body bytes than this are reported as large
+    pub large_response_bytes: Option<usize>,
+}
+
+impl QueryCostConfig {
+    /// Report operations slower than `duration`
+    pub fn slow_after(mut self, duration: Duration) -> Self {
+        self.slow_after = Some(duration);
+        self
+    }
+
+    /// Report replies larger than `bytes`
+    pub fn large_response_bytes(mut self, bytes: usize) -> Self {
+        self.large_response_bytes = Some(bytes);
+        self
+    }
+}
+
+/// Threshold an operation crossed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QueryCostReason {
+    /// Took longer than `slow_after`
+    Slow,
+    /// Returned more than `large_response_bytes`
+    LargeResponse,
+}
+
+/// One operation that crossed a threshold
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryCostWarning {
+    /// Operation name, or the first root field of an anonymous operation
+    pub operation: String,
+    /// `query` or `mutation`
+    pub kind: &'static str,
+    /// First 16 hex digits of the SHA-256 of the JSON variables
+    pub variables_digest: String,
+    /// Node that answered
+    pub uri: String,
+    /// Time from sending the request to reading the whole reply
+    pub duration_ms: u64,
+    /// Size of the reply body
+    pub response_bytes: usize,
+    /// Thresholds crossed
+    pub reasons: Vec<QueryCostReason>,
+}
+
+/// Callback receiving every `QueryCostWarning`
+pub type QueryCostListener = Arc<dyn Fn(&QueryCostWarning) + Send + Sync>;
+
+/// Warning counters since the thresholds were set
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct QueryCostStats {
+    /// Operations slower than `slow_after`
+    pub slow_operations: u64,
+    /// Replies larger than `large_response_bytes`
+    pub large_responses: u64,
+    /// Warnings per operation name
+    pub by_operation: HashMap<String, u64>,
+}
+
+/// Thresholds, listener and counters shared by the clones of a `GraphQLClient`
+pub(crate) struct QueryCostMonitor {
+    config: QueryCostConfig,
+    listener: Option<QueryCostListener>,
+    slow_operations: AtomicU64,
+    large_responses: AtomicU64,
+    by_operation: Mutex<HashMap<String, u64>>,
+}
+
+impl QueryCostMonitor {
+    pub(crate) fn new(config: QueryCostConfig, listener: Option<QueryCostListener>) -> Self {
+        QueryCostMonitor {
+            config,
+            listener,
+            slow_operations: AtomicU64::new(0),
+            large_responses: AtomicU64::new(0),
+            by_operation: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn config(&self) -> &QueryCostConfig {
+        &self.config
+    }
+
+    /// Check one finished request against the thresholds and report it if it crossed any
+    pub(crate) fn observe(&self, uri: &str, payload: &Value, duration: Duration, response_bytes: usize) -> Option<QueryCostWarning> {
+        let mut reasons = Vec::new();
+        if self.config.slow_after.is_some_and(|limit| duration > limit) {
+            reasons.push(QueryCostReason::Slow);
+            self.slow_operations.fetch_add(1, Ordering::Relaxed);
+        }
+        if self.config.large_response_bytes.is_some_and(|limit| response_bytes > limit) {
+            reasons.push(QueryCostReason::LargeResponse);
+            self.large_responses.fetch_add(1, Ordering::Relaxed);
+        }
+        if reasons.is_empty() {
+            return None;
+        }
+
+        let document = payload.get("query").and_then(Value::as_str).unwrap_or_default();
+        let warning = QueryCostWarning {
+            operation: payload.get("operationName")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| operation_name(document)),
+            kind: if document.trim_start().starts_with("mutation") { "mutation" } else { "query" },
+            variables_digest: variables_digest(payload.get("variables").unwrap_or(&Value::Null)),
+            uri: uri.to_string(),
+            duration_ms: u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+            response_bytes,
+            reasons,
+        };
+
+        if let Ok(mut counts) = self.by_operation.lock() {
+            *counts.entry(warning.operation.clone()).or_default() += 1;
+        }
+        warn!(
+            target: "knishio::query_cost",
+            operation = %warning.operation,
+            kind = warning.kind,
+            variables_digest = %warning.variables_digest,
+            uri = %warning.uri,
+            duration_ms = warning.duration_ms,
+            response_bytes = warning.response_bytes,
+            reasons = ?warning.reasons,
+            "Expensive GraphQL operation"
+        );
+        if let Some(ref listener) = self.listener {
+            listener(&warning);
+        }
+
+        Some(warning)
+    }
+
+    pub(crate) fn stats(&self) -> QueryCostStats {
+        QueryCostStats {
+            slow_operations: self.slow_operations.load(Ordering::Relaxed),
+            large_responses: self.large_responses.load(Ordering::Relaxed),
+            by_operation: self.by_operation.lock().map(|counts| counts.clone()).unwrap_or_default(),
+        }
+    }
+}
+
+/// Name of a GraphQL document's operation, or its first root field when it has none
+fn operation_name(document: &str) -> String {
+    let document = document.trim_start();
+    let header = document.split('{').next().unwrap_or_default();
+    let named = ["query", "mutation", "subscription"].iter()
+        .find_map(|keyword| header.strip_prefix(keyword))
+        .and_then(|rest| rest.trim_start().split(|c: char| !(c.is_alphanumeric() || c == '_')).next())
+        .filter(|name| !name.is_empty())
+        .map(str::to_string);
+
+    named.or_else(|| {
+        document.split_once('{')
+            .map(|(_, body)| body.trim_start())
+            .and_then(|body| body.split(|c: char| !(c.is_alphanumeric() || c == '_')).next())
+            .filter(|field| !field.is_empty())
+            .map(str::to_string)
+    }).unwrap_or_else(|| "anonymous".to_string())
+}
+
+/// Short SHA-256 digest of the JSON form of `variables`
+fn variables_digest(variables: &Value) -> String {
+    let mut digest = hex::encode(Sha256::digest(variables.to_string().as_bytes()));
+    digest.truncate(16);
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::generate_secret;
+    use crate::graphql::{create_query_request, GraphQLClient};
+    use crate::test_ledger::TestLedger;
+    use serde_json::json;
+
+    #[test]
+    fn test_operation_names() {
+        assert_eq!(operation_name("query MetaScan($type: String) { MetaType(metaType: $type) { id } }"), "MetaScan");
+        assert_eq!(operation_name("mutation ProposeMolecule { ProposeMolecule { status } }"), "ProposeMolecule");
+        assert_eq!(operation_name("query( $bundleHash: String ) { Balance( bundleHash: $bundleHash ) { amount } }"), "Balance");
+        assert_eq!(operation_name("{ __typename }"), "__typename");
+        assert_eq!(operation_name(""), "anonymous");
+
+        assert_eq!(variables_digest(&json!({ "a": 1 })), variables_digest(&json!({ "a": 1 })));
+        assert_ne!(variables_digest(&json!({ "a": 1 })), variables_digest(&json!({ "a": 2 })));
+        assert_eq!(variables_digest(&Value::Null).len(), 16);
+    }
+
+    #[tokio::test]
+    async fn test_expensive_queries_are_reported() {
+        let ledger = TestLedger::start().await.unwrap();
+        let wallet = ledger.fund(&generate_secret("query-cost"), "COST", 5.0).unwrap();
+        let balance = || create_query_request(
+            "query( $bundleHash: String, $token: String ) { Balance( bundleHash: $bundleHash, token: $token ) { amount } }",
+            Some(json!({ "bundleHash": wallet.bundle, "token": "COST" })),
+        );
+
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let seen = warnings.clone();
+        let mut client = GraphQLClient::new(ledger.uri().to_string());
+        client.set_query_cost(Some(QueryCostConfig::default().slow_after(Duration::from_secs(60)).large_response_bytes(1_000_000)));
+        client.set_query_cost_listener(Some(Arc::new(move |warning: &QueryCostWarning| {
+            seen.lock().unwrap().push(warning.clone());
+        })));
+
+        // Under both thresholds: nothing reported
+        client.query(balance()).await.unwrap();
+        assert!(warnings.lock().unwrap().is_empty());
+        assert_eq!(client.query_cost_stats(), Some(QueryCostStats::default()));
+
+        // Listener survives new thresholds; counters start over
+        client.set_query_cost(Some(QueryCostConfig::default().slow_after(Duration::ZERO).large_response_bytes(8)));
+        client.query(balance()).await.unwrap();
+
+        let warnings = warnings.lock().unwrap().clone();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].operation, "Balance");
+        assert_eq!(warnings[0].kind, "query");
+        assert_eq!(warnings[0].variables_digest, variables_digest(balance().variables.as_ref().unwrap()));
+        assert_eq!(warnings[0].reasons, vec![QueryCostReason::Slow, QueryCostReason::LargeResponse]);
+        assert!(warnings[0].response_bytes > 8);
+
+        let stats = client.query_cost_stats().unwrap();
+        assert_eq!((stats.slow_operations, stats.large_responses), (1, 1));
+        assert_eq!(stats.by_operation.get("Balance"), Some(&1));
+
+        client.set_query_cost(None);
+        assert_eq!(client.query_cost_stats(), None);
+    }
+}
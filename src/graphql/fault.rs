@@ -0,0 +1,226 @@
+//! Fault injection for transport testing
+//!
+//! Enabled with the `fault-injection` feature. A `FaultInjector` attached to a
+//! `GraphQLClient` makes a share of its requests fail the way a flaky node or network
+//! would, so applications can exercise their retry, backoff and resync handling
+//! without a misbehaving node. Injected failures surface as the same errors the real
+//! failures produce:
+//!
+//! - timeouts: `KnishIOError::Network`, after waiting `timeout_delay`
+//! - server errors: `"HTTP error: 503 Service Unavailable"` style errors, which the
+//!   retry policy treats as 5xx
+//! - malformed responses: the request reaches the node, but the reply body is cut in
+//!   half, giving a `KnishIOError::Serialization` for a write that may have landed
+//! - dropped frames: subscription messages silently lost
+//!
+//! Runs are reproducible when the injector is seeded.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// A failure injected into one request or frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The request times out
+    Timeout,
+    /// The node answers with this 5xx status
+    ServerError(u16),
+    /// The node processes the request but the reply is truncated
+    MalformedResponse,
+    /// A subscription frame is lost
+    DroppedFrame,
+}
+
+/// Number of faults injected so far, by kind
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FaultStats {
+    /// Injected timeouts
+    pub timeouts: u64,
+    /// Injected 5xx responses
+    pub server_errors: u64,
+    /// Injected malformed responses
+    pub malformed_responses: u64,
+    /// Dropped subscription frames
+    pub dropped_frames: u64,
+}
+
+/// Randomly fails requests and subscription frames with configured probabilities
+///
+/// ```
+/// # #[cfg(feature = "fault-injection")]
+/// # {
+/// use knishio_client::graphql::{FaultInjector, GraphQLClient};
+/// use std::time::Duration;
+///
+/// let mut client = GraphQLClient::new("https://node.example/graphql");
+/// client.set_fault_injector(Some(
+///     FaultInjector::new()
+///         .timeouts(0.05)
+///         .server_errors(0.1)
+///         .timeout_delay(Duration::from_millis(200))
+///         .seed(7),
+/// ));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FaultInjector {
+    timeout: f64,
+    server_error: f64,
+    malformed: f64,
+    dropped_frame: f64,
+    timeout_delay: Duration,
+    server_error_status: u16,
+    rng: Mutex<StdRng>,
+    timeouts: AtomicU64,
+    server_errors: AtomicU64,
+    malformed_responses: AtomicU64,
+    dropped_frames: AtomicU64,
+}
+
+impl Default for FaultInjector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FaultInjector {
+    /// An injector that injects nothing until probabilities are set
+    pub fn new() -> Self {
+        FaultInjector {
+            timeout: 0.0,
+            server_error: 0.0,
+            malformed: 0.0,
+            dropped_frame: 0.0,
+            timeout_delay: Duration::from_secs(1),
+            server_error_status: 503,
+            rng: Mutex::new(StdRng::from_os_rng()),
+            timeouts: AtomicU64::new(0),
+            server_errors: AtomicU64::new(0),
+            malformed_responses: AtomicU64::new(0),
+            dropped_frames: AtomicU64::new(0),
+        }
+    }
+
+    /// Probability that a request times out
+    pub fn timeouts(mut self, probability: f64) -> Self {
+        self.timeout = clamp(probability);
+        self
+    }
+
+    /// Probability that a request gets a 5xx response
+    pub fn server_errors(mut self, probability: f64) -> Self {
+        self.server_error = clamp(probability);
+        self
+    }
+
+    /// Probability that a reply body arrives truncated
+    pub fn malformed_responses(mut self, probability: f64) -> Self {
+        self.malformed = clamp(probability);
+        self
+    }
+
+    /// Probability that a subscription frame is dropped
+    pub fn dropped_frames(mut self, probability: f64) -> Self {
+        self.dropped_frame = clamp(probability);
+        self
+    }
+
+    /// How long an injected timeout waits before failing (default 1s)
+    pub fn timeout_delay(mut self, delay: Duration) -> Self {
+        self.timeout_delay = delay;
+        self
+    }
+
+    /// Status of injected server errors (default 503; values outside 500-599 become 500)
+    pub fn server_error_status(mut self, status: u16) -> Self {
+        self.server_error_status = if (500..600).contains(&status) { status } else { 500 };
+        self
+    }
+
+    /// Seed the random source so a run can be replayed
+    pub fn seed(self, seed: u64) -> Self {
+        *self.rng.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Delay of injected timeouts
+    pub fn get_timeout_delay(&self) -> Duration {
+        self.timeout_delay
+    }
+
+    /// Faults injected so far
+    pub fn stats(&self) -> FaultStats {
+        FaultStats {
+            timeouts: self.timeouts.load(Ordering::Relaxed),
+            server_errors: self.server_errors.load(Ordering::Relaxed),
+            malformed_responses: self.malformed_responses.load(Ordering::Relaxed),
+            dropped_frames: self.dropped_frames.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Decide the fate of one request
+    ///
+    /// At most one fault is injected per request; the probabilities are taken in the
+    /// order timeout, server error, malformed response.
+    pub fn roll_request(&self) -> Option<Fault> {
+        let roll = self.roll();
+        let fault = if roll < self.timeout {
+            self.timeouts.fetch_add(1, Ordering::Relaxed);
+            Fault::Timeout
+        } else if roll < self.timeout + self.server_error {
+            self.server_errors.fetch_add(1, Ordering::Relaxed);
+            Fault::ServerError(self.server_error_status)
+        } else if roll < self.timeout + self.server_error + self.malformed {
+            self.malformed_responses.fetch_add(1, Ordering::Relaxed);
+            Fault::MalformedResponse
+        } else {
+            return None;
+        };
+        Some(fault)
+    }
+
+    /// Decide whether one subscription frame is dropped
+    pub fn roll_frame(&self) -> Option<Fault> {
+        if self.dropped_frame > 0.0 && self.roll() < self.dropped_frame {
+            self.dropped_frames.fetch_add(1, Ordering::Relaxed);
+            return Some(Fault::DroppedFrame);
+        }
+        None
+    }
+
+    fn roll(&self) -> f64 {
+        self.rng.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).random()
+    }
+}
+
+fn clamp(probability: f64) -> f64 {
+    if probability.is_nan() { 0.0 } else { probability.clamp(0.0, 1.0) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probabilities_and_seeding() {
+        assert_eq!(FaultInjector::new().roll_request(), None);
+        assert_eq!(FaultInjector::new().server_errors(1.0).server_error_status(404).roll_request(), Some(Fault::ServerError(500)));
+        assert_eq!(FaultInjector::new().timeouts(2.0).roll_request(), Some(Fault::Timeout));
+
+        let run = |seed| {
+            let injector = FaultInjector::new().timeouts(0.2).server_errors(0.2).malformed_responses(0.2).dropped_frames(0.5).seed(seed);
+            let faults: Vec<_> = (0..200).map(|_| (injector.roll_request(), injector.roll_frame())).collect();
+            (faults, injector.stats())
+        };
+        let (faults, stats) = run(42);
+        assert_eq!(run(42).0, faults);
+
+        let injected = stats.timeouts + stats.server_errors + stats.malformed_responses;
+        assert!((80..160).contains(&injected), "{:?}", stats);
+        assert!((60..140).contains(&stats.dropped_frames), "{:?}", stats);
+        assert_eq!(faults.iter().filter(|(f, _)| *f == Some(Fault::Timeout)).count() as u64, stats.timeouts);
+    }
+}
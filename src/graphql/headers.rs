@@ -0,0 +1,114 @@
+//! Response headers
+//!
+//! Nodes send more in their HTTP headers than the GraphQL body carries: a request ID to
+//! quote in support requests, and rate-limit counters a client can slow down by before it
+//! gets throttled. `ResponseHeaders` keeps them on every `GraphQLResponse`, and responses
+//! built from one expose them through `Response::headers`.
+//!
+//! Both the `X-RateLimit-*` headers and the unprefixed `RateLimit-*` ones of the IETF
+//! draft are read; the prefixed ones win when a node sends both.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// HTTP headers of a node's reply, by lowercase name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResponseHeaders {
+    fields: BTreeMap<String, String>,
+}
+
+impl ResponseHeaders {
+    /// Headers from `(name, value)` pairs; a repeated name keeps its values comma-joined
+    pub fn new<N: AsRef<str>, V: Into<String>>(fields: impl IntoIterator<Item = (N, V)>) -> Self {
+        let mut headers = ResponseHeaders::default();
+        for (name, value) in fields {
+            headers.insert(name.as_ref(), value.into());
+        }
+        headers
+    }
+
+    pub(crate) fn from_header_map(map: &reqwest::header::HeaderMap) -> Self {
+        Self::new(map.iter().filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?))))
+    }
+
+    fn insert(&mut self, name: &str, value: String) {
+        self.fields.entry(name.to_ascii_lowercase())
+            .and_modify(|existing| {
+                existing.push_str(", ");
+                existing.push_str(&value);
+            })
+            .or_insert(value);
+    }
+
+    /// Value of the header `name` (any case)
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields.get(&name.to_ascii_lowercase()).map(String::as_str)
+    }
+
+    /// Every header, by lowercase name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.fields.iter().map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Number of headers
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Whether there are no headers (e.g. a response that did not come over HTTP)
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// ID the node gave the request (`X-Request-Id`)
+    pub fn request_id(&self) -> Option<&str> {
+        self.get("x-request-id")
+    }
+
+    /// Requests allowed in the current rate-limit window
+    pub fn rate_limit_limit(&self) -> Option<u64> {
+        self.rate_limit_number("limit")
+    }
+
+    /// Requests left in the current rate-limit window
+    pub fn rate_limit_remaining(&self) -> Option<u64> {
+        self.rate_limit_number("remaining")
+    }
+
+    /// Time until the rate-limit window resets
+    pub fn rate_limit_reset(&self) -> Option<Duration> {
+        self.rate_limit_number("reset").map(Duration::from_secs)
+    }
+
+    fn rate_limit_number(&self, field: &str) -> Option<u64> {
+        self.get(&format!("x-ratelimit-{}", field))
+            .or_else(|| self.get(&format!("ratelimit-{}", field)))
+            .and_then(|value| value.split(',').next())
+            .and_then(|value| value.trim().parse().ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_and_rate_limits() {
+        let headers = ResponseHeaders::new([
+            ("X-Request-Id", "req-7"),
+            ("RateLimit-Remaining", "99"),
+            ("X-RateLimit-Remaining", "12"),
+            ("RateLimit-Reset", "30"),
+            ("Vary", "Accept"),
+            ("vary", "Origin"),
+        ]);
+        assert_eq!(headers.request_id(), Some("req-7"));
+        assert_eq!(headers.get("VARY"), Some("Accept, Origin"));
+        assert_eq!(headers.rate_limit_remaining(), Some(12));
+        assert_eq!(headers.rate_limit_reset(), Some(Duration::from_secs(30)));
+        assert_eq!(headers.rate_limit_limit(), None);
+        assert_eq!(headers.len(), 5);
+        assert!(ResponseHeaders::default().request_id().is_none());
+    }
+}
@@ -0,0 +1,576 @@
+//! Local checks of GraphQL documents
+//!
+//! The SDK's own operations are fixed strings, but `BaseQuery` and `BaseMutation` send
+//! whatever document the caller wrote. A typo there costs a round trip and comes back as
+//! whatever the node makes of it. `lint_document` catches the common mistakes first:
+//! unbalanced brackets, unterminated strings, an unknown operation keyword, empty
+//! selection sets, and variables used without a declaration or declared without a use.
+//! It is a syntax check only; `NodeSchema::validate` resolves field names and arguments
+//! against a schema.
+
+use crate::error::{KnishIOError, Result};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Kind of a GraphQL operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationType {
+    Query,
+    Mutation,
+    Subscription,
+}
+
+impl OperationType {
+    /// Keyword introducing the operation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OperationType::Query => "query",
+            OperationType::Mutation => "mutation",
+            OperationType::Subscription => "subscription",
+        }
+    }
+}
+
+impl fmt::Display for OperationType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One operation of a linted document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationSummary {
+    /// Operation kind
+    pub operation_type: OperationType,
+    /// Operation name, None for anonymous operations
+    pub name: Option<String>,
+    /// Declared variables, without the `$`, in declaration order
+    pub variables: Vec<String>,
+    /// Fields selected at the root, aliases resolved
+    pub root_fields: Vec<String>,
+}
+
+/// Outline of a document that passed `lint_document`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintedDocument {
+    /// Operations in document order
+    pub operations: Vec<OperationSummary>,
+    /// Names of the fragments defined
+    pub fragments: Vec<String>,
+}
+
+impl LintedDocument {
+    /// Name of the first operation, or its first root field when it is anonymous
+    pub fn operation_name(&self) -> Option<&str> {
+        let operation = self.operations.first()?;
+        operation.name.as_deref().or_else(|| operation.root_fields.first().map(String::as_str))
+    }
+}
+
+/// Check `document` and outline its operations
+///
+/// ```
+/// use knishio_client::graphql::lint_document;
+///
+/// let document = lint_document("query Balance($bundle: String) { Balance(bundleHash: $bundle) { amount } }").unwrap();
+/// assert_eq!(document.operation_name(), Some("Balance"));
+///
+/// let error = lint_document("query ($bundle: String) { Balance(bundleHash: $bundel) { amount } }").unwrap_err();
+/// assert!(error.to_string().contains("$bundel"));
+/// ```
+///
+/// # Errors
+///
+/// Returns `InvalidQuery` naming the first problem and its line and column
+pub fn lint_document(document: &str) -> Result<LintedDocument> {
+    let tokens = tokenize(document)?;
+    check_brackets(&tokens)?;
+    Parser { tokens: &tokens, index: 0, end: end_position(document) }.document()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum TokenKind<'a> {
+    /// Punctuator; `...` is `.`
+    Punct(char),
+    Name(&'a str),
+    /// String or number literal
+    Literal,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Token<'a> {
+    pub(super) kind: TokenKind<'a>,
+    pub(super) position: Position,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Position {
+    line: usize,
+    column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+pub(super) fn invalid(position: Position, message: impl fmt::Display) -> KnishIOError {
+    KnishIOError::InvalidQuery(format!("{}: {}", position, message))
+}
+
+pub(super) fn end_position(document: &str) -> Position {
+    let line = document.lines().count().max(1);
+    let column = document.lines().last().map_or(0, |l| l.chars().count()) + 1;
+    Position { line, column }
+}
+
+pub(super) fn tokenize(document: &str) -> Result<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut chars = document.char_indices().peekable();
+    let (mut line, mut line_start) = (1, 0);
+    let column = |offset: usize, line_start: usize| document[line_start..offset].chars().count() + 1;
+
+    while let Some((offset, c)) = chars.next() {
+        let position = Position { line, column: column(offset, line_start) };
+        match c {
+            '\n' => {
+                line += 1;
+                line_start = offset + 1;
+            }
+            c if c.is_whitespace() || c == ',' || c == '\u{feff}' => {}
+            '#' => {
+                while chars.next_if(|(_, c)| *c != '\n').is_some() {}
+            }
+            '!' | '$' | '&' | '(' | ')' | ':' | '=' | '@' | '[' | ']' | '{' | '|' | '}' => {
+                tokens.push(Token { kind: TokenKind::Punct(c), position });
+            }
+            '.' => {
+                if !(chars.next_if(|(_, c)| *c == '.').is_some() && chars.next_if(|(_, c)| *c == '.').is_some()) {
+                    return Err(invalid(position, "Expected `...`"));
+                }
+                tokens.push(Token { kind: TokenKind::Punct('.'), position });
+            }
+            '"' => {
+                let block = document[offset..].starts_with("\"\"\"");
+                if block {
+                    chars.next();
+                    chars.next();
+                }
+                let mut closed = false;
+                while let Some((at, c)) = chars.next() {
+                    match c {
+                        '\\' if !block => {
+                            chars.next();
+                        }
+                        '"' if !block => {
+                            closed = true;
+                            break;
+                        }
+                        '"' if document[at..].starts_with("\"\"\"") => {
+                            chars.next();
+                            chars.next();
+                            closed = true;
+                            break;
+                        }
+                        '\n' if !block => break,
+                        '\n' => {
+                            line += 1;
+                            line_start = at + 1;
+                        }
+                        _ => {}
+                    }
+                }
+                if !closed {
+                    return Err(invalid(position, "Unterminated string"));
+                }
+                tokens.push(Token { kind: TokenKind::Literal, position });
+            }
+            c if c == '-' || c.is_ascii_digit() => {
+                while chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-')).is_some() {}
+                tokens.push(Token { kind: TokenKind::Literal, position });
+            }
+            c if c == '_' || c.is_ascii_alphabetic() => {
+                let mut end = offset + 1;
+                while let Some((at, _)) = chars.next_if(|(_, c)| *c == '_' || c.is_ascii_alphanumeric()) {
+                    end = at + 1;
+                }
+                tokens.push(Token { kind: TokenKind::Name(&document[offset..end]), position });
+            }
+            c => return Err(invalid(position, format!("Unexpected character `{}`", c))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Every `{`, `(` and `[` closed by its own counterpart
+pub(super) fn check_brackets(tokens: &[Token<'_>]) -> Result<()> {
+    let mut open: Vec<(char, Position)> = Vec::new();
+
+    for token in tokens {
+        let TokenKind::Punct(c) = token.kind else { continue };
+        let opener = match c {
+            '{' | '(' | '[' => {
+                open.push((c, token.position));
+                continue;
+            }
+            '}' => '{',
+            ')' => '(',
+            ']' => '[',
+            _ => continue,
+        };
+        match open.pop() {
+            Some((found, _)) if found == opener => {}
+            Some((found, at)) => {
+                return Err(invalid(token.position, format!("`{}` does not close `{}` opened at {}", c, found, at)));
+            }
+            None => return Err(invalid(token.position, format!("Unmatched `{}`", c))),
+        }
+    }
+
+    match open.pop() {
+        Some((c, at)) => Err(invalid(at, format!("Unclosed `{}`", c))),
+        None => Ok(()),
+    }
+}
+
+struct Parser<'t, 'a> {
+    tokens: &'t [Token<'a>],
+    index: usize,
+    end: Position,
+}
+
+/// Variables seen in one definition
+#[derive(Default)]
+struct VariableUses<'a> {
+    declared: Vec<(&'a str, Position)>,
+    used: Vec<(&'a str, Position)>,
+}
+
+impl<'t, 'a> Parser<'t, 'a> {
+    fn peek(&self) -> Option<TokenKind<'a>> {
+        self.tokens.get(self.index).map(|t| t.kind)
+    }
+
+    fn position(&self) -> Position {
+        self.tokens.get(self.index).map_or(self.end, |t| t.position)
+    }
+
+    fn next(&mut self) -> Option<TokenKind<'a>> {
+        let kind = self.peek();
+        self.index += 1;
+        kind
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(TokenKind::Punct(c));
+        if found {
+            self.index += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", c)))
+        }
+    }
+
+    fn name(&mut self, what: &str) -> Result<&'a str> {
+        match self.peek() {
+            Some(TokenKind::Name(name)) => {
+                self.index += 1;
+                Ok(name)
+            }
+            _ => Err(self.unexpected(what)),
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> KnishIOError {
+        let found = match self.peek() {
+            Some(TokenKind::Punct('.')) => "`...`".to_string(),
+            Some(TokenKind::Punct(c)) => format!("`{}`", c),
+            Some(TokenKind::Name(name)) => format!("`{}`", name),
+            Some(TokenKind::Literal) => "a literal".to_string(),
+            None => "the end of the document".to_string(),
+        };
+        invalid(self.position(), format!("Expected {}, found {}", expected, found))
+    }
+
+    fn document(mut self) -> Result<LintedDocument> {
+        let mut operations = Vec::new();
+        let mut fragments = Vec::new();
+        let mut operation_uses = Vec::new();
+        let mut fragment_uses = Vec::new();
+
+        while let Some(kind) = self.peek() {
+            let position = self.position();
+            match kind {
+                TokenKind::Punct('{') => {
+                    let mut uses = VariableUses::default();
+                    let root_fields = self.selection_set(&mut uses)?;
+                    operations.push(OperationSummary { operation_type: OperationType::Query, name: None, variables: Vec::new(), root_fields });
+                    operation_uses.push((position, uses));
+                }
+                TokenKind::Name(keyword @ ("query" | "mutation" | "subscription")) => {
+                    self.index += 1;
+                    let operation_type = match keyword {
+                        "mutation" => OperationType::Mutation,
+                        "subscription" => OperationType::Subscription,
+                        _ => OperationType::Query,
+                    };
+                    let name = match self.peek() {
+                        Some(TokenKind::Name(name)) => {
+                            self.index += 1;
+                            Some(name.to_string())
+                        }
+                        _ => None,
+                    };
+                    let mut uses = VariableUses::default();
+                    if self.eat('(') {
+                        self.variable_definitions(&mut uses)?;
+                    }
+                    self.directives(&mut uses)?;
+                    let root_fields = self.selection_set(&mut uses)?;
+                    let variables = uses.declared.iter().map(|(name, _)| name.to_string()).collect();
+                    operations.push(OperationSummary { operation_type, name, variables, root_fields });
+                    operation_uses.push((position, uses));
+                }
+                TokenKind::Name("fragment") => {
+                    self.index += 1;
+                    let name = self.name("a fragment name")?;
+                    if self.name("`on`")? != "on" {
+                        self.index -= 1;
+                        return Err(self.unexpected("`on`"));
+                    }
+                    self.name("a type condition")?;
+                    let mut uses = VariableUses::default();
+                    self.directives(&mut uses)?;
+                    self.selection_set(&mut uses)?;
+                    fragments.push(name.to_string());
+                    fragment_uses.extend(uses.used);
+                }
+                TokenKind::Name(other) => {
+                    return Err(invalid(position, format!(
+                        "Unknown operation type `{}` (expected query, mutation, subscription or fragment)", other
+                    )));
+                }
+                _ => return Err(self.unexpected("an operation")),
+            }
+        }
+
+        if operations.is_empty() {
+            return Err(invalid(self.end, "Document contains no operation"));
+        }
+
+        // Which operations spread a fragment is not tracked, so a variable used in a fragment
+        // counts as used by every operation, and fragments are not checked for declarations
+        let fragment_variables: BTreeSet<&str> = fragment_uses.iter().map(|(name, _)| *name).collect();
+        for (position, uses) in &operation_uses {
+            let declared: BTreeSet<&str> = uses.declared.iter().map(|(name, _)| *name).collect();
+            if let Some((name, at)) = uses.used.iter().find(|(name, _)| !declared.contains(name)) {
+                return Err(invalid(*at, format!("Variable `${}` is not declared by the operation starting at {}", name, position)));
+            }
+            let used: BTreeSet<&str> = uses.used.iter().map(|(name, _)| *name).collect();
+            if let Some((name, at)) = uses.declared.iter().find(|(name, _)| !used.contains(name) && !fragment_variables.contains(name)) {
+                return Err(invalid(*at, format!("Variable `${}` is declared but never used", name)));
+            }
+        }
+
+        Ok(LintedDocument { operations, fragments })
+    }
+
+    /// `$name: Type = default` entries up to the closing `)`
+    fn variable_definitions(&mut self, uses: &mut VariableUses<'a>) -> Result<()> {
+        while !self.eat(')') {
+            let position = self.position();
+            self.expect('$')?;
+            let name = self.name("a variable name")?;
+            if uses.declared.iter().any(|(declared, _)| *declared == name) {
+                return Err(invalid(position, format!("Variable `${}` is declared twice", name)));
+            }
+            uses.declared.push((name, position));
+            self.expect(':')?;
+            self.type_reference()?;
+            if self.eat('=') {
+                // Default values are constants; a variable inside one is reported as a use
+                self.value(uses)?;
+            }
+            self.directives(uses)?;
+        }
+        Ok(())
+    }
+
+    fn type_reference(&mut self) -> Result<()> {
+        if self.eat('[') {
+            self.type_reference()?;
+            self.expect(']')?;
+        } else {
+            self.name("a type")?;
+        }
+        self.eat('!');
+        Ok(())
+    }
+
+    fn value(&mut self, uses: &mut VariableUses<'a>) -> Result<()> {
+        let position = self.position();
+        match self.next() {
+            Some(TokenKind::Punct('$')) => {
+                let name = self.name("a variable name")?;
+                uses.used.push((name, position));
+            }
+            Some(TokenKind::Punct('[')) => {
+                while !self.eat(']') {
+                    self.value(uses)?;
+                }
+            }
+            Some(TokenKind::Punct('{')) => {
+                while !self.eat('}') {
+                    self.name("a field name")?;
+                    self.expect(':')?;
+                    self.value(uses)?;
+                }
+            }
+            Some(TokenKind::Name(_) | TokenKind::Literal) => {}
+            _ => {
+                self.index -= 1;
+                return Err(self.unexpected("a value"));
+            }
+        }
+        Ok(())
+    }
+
+    fn arguments(&mut self, uses: &mut VariableUses<'a>) -> Result<()> {
+        if self.eat('(') {
+            while !self.eat(')') {
+                self.name("an argument name")?;
+                self.expect(':')?;
+                self.value(uses)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn directives(&mut self, uses: &mut VariableUses<'a>) -> Result<()> {
+        while self.eat('@') {
+            self.name("a directive name")?;
+            self.arguments(uses)?;
+        }
+        Ok(())
+    }
+
+    /// `{ ... }`, returning the names of the fields selected directly in it
+    fn selection_set(&mut self, uses: &mut VariableUses<'a>) -> Result<Vec<String>> {
+        let position = self.position();
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        let mut selections = 0;
+
+        while !self.eat('}') {
+            selections += 1;
+            if self.eat('.') {
+                match self.peek() {
+                    Some(TokenKind::Name("on")) => {
+                        self.index += 1;
+                        self.name("a type condition")?;
+                        self.directives(uses)?;
+                        self.selection_set(uses)?;
+                    }
+                    Some(TokenKind::Name(_)) => {
+                        self.index += 1;
+                        self.directives(uses)?;
+                    }
+                    _ => {
+                        self.directives(uses)?;
+                        self.selection_set(uses)?;
+                    }
+                }
+                continue;
+            }
+
+            let mut field = self.name("a field")?;
+            if self.eat(':') {
+                field = self.name("a field after the alias")?;
+            }
+            fields.push(field.to_string());
+            self.arguments(uses)?;
+            self.directives(uses)?;
+            if self.peek() == Some(TokenKind::Punct('{')) {
+                self.selection_set(uses)?;
+            }
+        }
+
+        if selections == 0 {
+            return Err(invalid(position, "Empty selection set"));
+        }
+        Ok(fields)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint_error(document: &str) -> String {
+        match lint_document(document) {
+            Err(KnishIOError::InvalidQuery(message)) => message,
+            other => panic!("expected a lint error for {:?}, got {:?}", document, other),
+        }
+    }
+
+    #[test]
+    fn test_valid_documents() {
+        let document = lint_document(r#"
+            # Balance of one wallet
+            query WalletBalance($bundle: String!, $tokens: [String!] = ["USER"]) {
+              Balance(bundleHash: $bundle, tokens: $tokens, filter: { amount: -1.5e2, note: "a \"quoted\" }" }) @include(if: true) {
+                total: amount
+                ...WalletFields
+                ... on Balance { tokenSlug }
+              }
+            }
+            fragment WalletFields on Balance { address position }
+        "#).unwrap();
+
+        let operation = &document.operations[0];
+        assert_eq!(operation.operation_type, OperationType::Query);
+        assert_eq!(operation.name.as_deref(), Some("WalletBalance"));
+        assert_eq!(operation.variables, vec!["bundle", "tokens"]);
+        assert_eq!(operation.root_fields, vec!["Balance"]);
+        assert_eq!(document.fragments, vec!["WalletFields"]);
+
+        let shorthand = lint_document("{ alias: ContinuId(bundle: \"abc\") { address } }").unwrap();
+        assert_eq!(shorthand.operation_name(), Some("ContinuId"));
+        let anonymous = lint_document("mutation( $molecule: MoleculeInput! ) { ProposeMolecule( molecule: $molecule ) { status } }").unwrap();
+        assert_eq!(anonymous.operations[0].operation_type, OperationType::Mutation);
+        assert_eq!(anonymous.operation_name(), Some("ProposeMolecule"));
+
+        let block = lint_document("query { Token(note: \"\"\"multi\n\"line\"\n\"\"\") { slug } }").unwrap();
+        assert_eq!(block.operations[0].root_fields, vec!["Token"]);
+    }
+
+    #[test]
+    fn test_lint_errors() {
+        assert_eq!(lint_error("query { Balance { amount }"), "1:7: Unclosed `{`");
+        assert_eq!(lint_error("query { Balance(bundleHash: \"x\" { amount } }"), "1:44: `}` does not close `(` opened at 1:16");
+        assert_eq!(lint_error("query { Balance { amount } } }"), "1:30: Unmatched `}`");
+        assert_eq!(lint_error("query { Token(slug: \"GOLD) { slug } }"), "1:21: Unterminated string");
+        assert!(lint_error("quer { Balance { amount } }").starts_with("1:1: Unknown operation type `quer`"));
+        assert_eq!(lint_error("query { Balance { } }"), "1:17: Empty selection set");
+        assert_eq!(lint_error(""), "1:1: Document contains no operation");
+
+        assert_eq!(
+            lint_error("query ($bundle: String) {\n  Balance(bundleHash: $bundel) { amount }\n}"),
+            "2:23: Variable `$bundel` is not declared by the operation starting at 1:1"
+        );
+        assert_eq!(
+            lint_error("query ($bundle: String, $token: String) { Balance(bundleHash: $bundle) { amount } }"),
+            "1:25: Variable `$token` is declared but never used"
+        );
+        assert!(lint_error("query ($a: String, $a: Int) { Token(slug: $a) { slug } }").contains("declared twice"));
+        assert_eq!(lint_error("query ($a String) { Token(slug: $a) { slug } }"), "1:11: Expected `:`, found `String`");
+    }
+}
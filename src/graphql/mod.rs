@@ -22,32 +22,55 @@
 //! - Response formatting to match JavaScript output
 //! - WebSocket subscription handling
 
+use crate::codec::WireFormat;
 use crate::error::{KnishIOError, Result};
-use futures_util::{SinkExt, StreamExt};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, RwLock};
-use tokio_tungstenite::{connect_async, tungstenite::Message};
 
 // Sub-modules for advanced functionality
 mod websocket;
+mod backpressure;
 mod connection_pool;
 mod retry_policy;
+mod cost;
+mod headers;
+mod scheduler;
+mod lint;
+mod schema_check;
+mod telemetry;
+#[cfg(feature = "fault-injection")]
+mod fault;
+#[cfg(feature = "vcr")]
+mod vcr;
 
 // Re-export public types from sub-modules
 pub use websocket::{
-    WebSocketManager, ConnectionState, ReconnectConfig as WebSocketReconnectConfig
+    WebSocketManager, WebSocketStats, ConnectionState, ReconnectConfig as WebSocketReconnectConfig,
+    SubscriptionProtocol
 };
+pub use backpressure::{ConsumerConfig, ConsumerMonitor, ConsumerStats, EventReceiver, OverflowPolicy};
 pub use connection_pool::{
     ConnectionPool, PoolConfig as ConnectionPoolConfig, PoolStats, global_pool
 };
 pub use retry_policy::{
     RetryPolicy, RetryStrategy, RetryCondition, RetryExecutor, execute_with_retry
 };
+pub use lint::{lint_document, LintedDocument, OperationSummary, OperationType};
+pub use schema_check::NodeSchema;
+pub use telemetry::{default_sdk_header, default_user_agent, platform, RequestInterceptor, SDK_HEADER};
+pub use cost::{QueryCostConfig, QueryCostListener, QueryCostReason, QueryCostStats, QueryCostWarning};
+pub use headers::ResponseHeaders;
+pub use scheduler::{RequestOptions, RequestPriority, SchedulerConfig, SchedulerStats};
+#[cfg(feature = "fault-injection")]
+pub use fault::{Fault, FaultInjector, FaultStats};
+#[cfg(feature = "vcr")]
+pub use vcr::{Cassette, Interaction, Redactor, ReplayMatch};
 
 /// GraphQL request structure
 #[derive(Debug, Clone, Serialize)]
@@ -78,6 +101,9 @@ pub struct GraphQLResponse {
     pub errors: Option<Vec<GraphQLError>>,
     /// Response extensions (server metadata)
     pub extensions: Option<Value>,
+    /// HTTP headers of the reply (empty for subscription events)
+    #[serde(skip)]
+    pub headers: ResponseHeaders,
 }
 
 /// GraphQL error structure
@@ -93,6 +119,31 @@ pub struct GraphQLError {
     pub extensions: Option<HashMap<String, Value>>,
 }
 
+impl GraphQLError {
+    /// Whether the node marked this error as throttling, by `extensions.code` or by
+    /// asking for a retry delay
+    pub fn is_throttled(&self) -> bool {
+        let code = self.extensions.as_ref()
+            .and_then(|extensions| extensions.get("code"))
+            .and_then(Value::as_str)
+            .map(str::to_ascii_uppercase);
+        matches!(code.as_deref(), Some("RATE_LIMITED" | "TOO_MANY_REQUESTS" | "THROTTLED"))
+            || self.retry_after().is_some()
+    }
+
+    /// Wait requested in `extensions.retryAfter` (seconds) or `extensions.retryAfterMs`
+    pub fn retry_after(&self) -> Option<Duration> {
+        let extensions = self.extensions.as_ref()?;
+        let number = |key: &str| extensions.get(key).and_then(|value| {
+            value.as_f64().or_else(|| value.as_str().and_then(|text| text.trim().parse().ok()))
+        }).filter(|number: &f64| number.is_finite() && *number >= 0.0);
+
+        // Waits too long for a Duration are dropped rather than trusted
+        number("retryAfter").and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+            .or_else(|| number("retryAfterMs").and_then(|ms| Duration::try_from_secs_f64(ms / 1000.0).ok()))
+    }
+}
+
 /// GraphQL error location
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ErrorLocation {
@@ -117,6 +168,8 @@ pub struct SocketConfig {
     pub max_reconnect_attempts: Option<u32>,
     /// Reconnect delay
     pub reconnect_delay: Option<Duration>,
+    /// Sub-protocol offered to the node (negotiated by default)
+    pub protocol: SubscriptionProtocol,
 }
 
 /// Retry configuration
@@ -151,6 +204,23 @@ pub struct ClientConfig {
     pub tcp_keepalive: Option<Duration>,
     /// Accept invalid TLS certificates (for self-signed certs in dev)
     pub insecure_tls: bool,
+    /// `User-Agent` of every request
+    pub user_agent: String,
+    /// `X-KnishIO-SDK` value; None leaves the header out
+    pub sdk_header: Option<String>,
+    /// Further headers sent with every request
+    pub headers: HashMap<String, String>,
+}
+
+/// Hedged read settings: a second node that gets a read query when the first is slow
+#[derive(Debug, Clone, PartialEq)]
+pub struct HedgeConfig {
+    /// Node that receives the duplicate query
+    pub uri: String,
+    /// How long the primary node has to answer before the duplicate is sent
+    pub delay: Duration,
+    /// The hedge node's own auth token; an authenticated client does not hedge without one
+    pub auth_token: Option<String>,
 }
 
 /// Subscription handle for managing active subscriptions
@@ -181,6 +251,29 @@ pub struct GraphQLClient {
     encrypt: bool,
     /// HTTP client with connection pooling
     http_client: Arc<Client>,
+    /// Backup node for hedged queries
+    hedge: Option<HedgeConfig>,
+    /// Preferred request body format
+    wire_format: WireFormat,
+    /// Set once the node refuses the binary format; later requests use JSON
+    binary_refused: Arc<AtomicBool>,
+    /// Slow and large operation thresholds with their counters
+    query_cost: Option<Arc<cost::QueryCostMonitor>>,
+    /// Receives every slow or large operation warning
+    query_cost_listener: Option<QueryCostListener>,
+    /// Concurrency and rate limits shared by the clones of this client
+    scheduler: Option<Arc<scheduler::RequestScheduler>>,
+    /// Options of the requests sent through this client
+    request_options: RequestOptions,
+    /// SDK identification and configured headers sent with every request
+    default_headers: Arc<HashMap<String, String>>,
+    /// Sees and may change the headers of every request
+    request_interceptor: Option<RequestInterceptor>,
+    /// Injected transport failures
+    #[cfg(feature = "fault-injection")]
+    fault_injector: Option<Arc<FaultInjector>>,
+    #[cfg(feature = "vcr")]
+    cassette: Option<Arc<Cassette>>,
     /// Retry configuration
     #[allow(dead_code)]
     retry_config: RetryConfig,
@@ -203,6 +296,7 @@ impl Default for SocketConfig {
             keep_alive_interval: Some(Duration::from_secs(30)),
             max_reconnect_attempts: Some(5),
             reconnect_delay: Some(Duration::from_secs(2)),
+            protocol: SubscriptionProtocol::Auto,
         }
     }
 }
@@ -229,6 +323,9 @@ impl Default for ClientConfig {
             keep_alive_timeout: Duration::from_secs(90),
             tcp_keepalive: Some(Duration::from_secs(60)),
             insecure_tls: false,
+            user_agent: default_user_agent(),
+            sdk_header: Some(default_sdk_header()),
+            headers: HashMap::new(),
         }
     }
 }
@@ -250,8 +347,7 @@ impl GraphQLClient {
             .connect_timeout(client_config.connect_timeout)
             .pool_idle_timeout(client_config.keep_alive_timeout)
             .pool_max_idle_per_host(client_config.max_connections)
-            .tcp_keepalive(client_config.tcp_keepalive)
-            .user_agent(format!("KnishIO-Rust-SDK/{}", env!("CARGO_PKG_VERSION")));
+            .tcp_keepalive(client_config.tcp_keepalive);
 
         if client_config.insecure_tls {
             builder = builder.danger_accept_invalid_certs(true);
@@ -262,6 +358,12 @@ impl GraphQLClient {
             Client::new()
         });
 
+        let mut default_headers = client_config.headers;
+        default_headers.insert("User-Agent".to_string(), client_config.user_agent);
+        if let Some(sdk_header) = client_config.sdk_header {
+            default_headers.insert(SDK_HEADER.to_string(), sdk_header);
+        }
+
         GraphQLClient {
             server_uri: server_uri.into(),
             socket_config: None,
@@ -270,6 +372,19 @@ impl GraphQLClient {
             wallet: None,
             encrypt: false,
             http_client: Arc::new(http_client),
+            hedge: None,
+            wire_format: WireFormat::Json,
+            binary_refused: Arc::new(AtomicBool::new(false)),
+            query_cost: None,
+            query_cost_listener: None,
+            scheduler: None,
+            request_options: RequestOptions::default(),
+            default_headers: Arc::new(default_headers),
+            request_interceptor: None,
+            #[cfg(feature = "fault-injection")]
+            fault_injector: None,
+            #[cfg(feature = "vcr")]
+            cassette: None,
             retry_config,
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
             request_timeout: client_config.request_timeout,
@@ -296,6 +411,13 @@ impl GraphQLClient {
         self.wallet = wallet;
     }
 
+    /// Clear authentication data
+    pub fn clear_auth_data(&mut self) {
+        self.auth_token = None;
+        self.pubkey = None;
+        self.wallet = None;
+    }
+
     /// Set server URI
     pub fn set_uri(&mut self, uri: impl Into<String>) {
         self.server_uri = uri.into();
@@ -315,6 +437,150 @@ impl GraphQLClient {
     pub fn set_encryption(&mut self, encrypt: bool) {
         self.encrypt = encrypt;
     }
+
+    /// Prefer `format` for request and response bodies
+    ///
+    /// Binary formats are sent with a JSON fallback in `Accept`. A node answering
+    /// 415 or 406 gets the request again as JSON, and JSON is used from then on. A 400
+    /// to a binary request is retried as JSON too; JSON sticks only if that retry is
+    /// not refused as well, since a 400 may be about the request itself.
+    pub fn set_wire_format(&mut self, format: WireFormat) {
+        self.wire_format = format;
+        self.binary_refused = Arc::new(AtomicBool::new(false));
+    }
+
+    /// Inject transport failures into this client's requests and subscriptions
+    ///
+    /// Clones of the client share the injector. Pass `None` to stop injecting.
+    #[cfg(feature = "fault-injection")]
+    pub fn set_fault_injector(&mut self, injector: Option<FaultInjector>) {
+        self.fault_injector = injector.map(Arc::new);
+    }
+
+    /// The attached fault injector, for reading its statistics
+    #[cfg(feature = "fault-injection")]
+    pub fn fault_injector(&self) -> Option<&FaultInjector> {
+        self.fault_injector.as_deref()
+    }
+
+    /// Record this client's requests into a cassette, or serve them from one
+    ///
+    /// A replaying cassette answers every query and mutation without touching the
+    /// network. Clones of the client share the cassette. Pass `None` to detach it.
+    #[cfg(feature = "vcr")]
+    pub fn set_cassette(&mut self, cassette: Option<Cassette>) {
+        self.cassette = cassette.map(Arc::new);
+    }
+
+    /// The attached cassette
+    #[cfg(feature = "vcr")]
+    pub fn cassette(&self) -> Option<&Cassette> {
+        self.cassette.as_deref()
+    }
+
+    /// Hedge read queries against a second node
+    ///
+    /// A query the primary node has not answered within `hedge.delay` is sent to
+    /// `hedge.uri` as well; the first successful reply wins and the other request is
+    /// dropped. Mutations are never duplicated. Pass `None` to turn hedging off.
+    ///
+    /// The duplicate carries `hedge.auth_token`, never this client's token. While this
+    /// client holds a token and the hedge has none, or requests are encrypted to this
+    /// node's key, queries go to the primary node only.
+    pub fn set_hedge(&mut self, hedge: Option<HedgeConfig>) {
+        self.hedge = hedge;
+    }
+
+    /// Current hedged read settings
+    pub fn get_hedge(&self) -> Option<&HedgeConfig> {
+        self.hedge.as_ref()
+    }
+
+    /// Report queries and mutations that are slower or larger than `config` allows
+    ///
+    /// Each report is a `tracing` warning on the `knishio::query_cost` target carrying the
+    /// operation name and a digest of its variables; it also goes to the listener and is
+    /// counted in `query_cost_stats`. Setting thresholds resets the counters; pass `None`
+    /// to stop reporting.
+    pub fn set_query_cost(&mut self, config: Option<QueryCostConfig>) {
+        self.query_cost = config.map(|config| Arc::new(cost::QueryCostMonitor::new(config, self.query_cost_listener.clone())));
+    }
+
+    /// Current slow and large operation thresholds
+    pub fn get_query_cost(&self) -> Option<&QueryCostConfig> {
+        self.query_cost.as_deref().map(cost::QueryCostMonitor::config)
+    }
+
+    /// Call `listener` with every slow or large operation warning, e.g. to feed a metrics system
+    pub fn set_query_cost_listener(&mut self, listener: Option<QueryCostListener>) {
+        self.query_cost_listener = listener;
+        if let Some(config) = self.get_query_cost().cloned() {
+            self.set_query_cost(Some(config));
+        }
+    }
+
+    /// Warning counters since the thresholds were set, if any are
+    pub fn query_cost_stats(&self) -> Option<QueryCostStats> {
+        self.query_cost.as_deref().map(cost::QueryCostMonitor::stats)
+    }
+
+    /// Queue queries and mutations by priority under `config`'s limits
+    ///
+    /// Clones of the client share the queue. Requests already waiting keep the old one;
+    /// pass `None` to send requests as soon as they are made.
+    pub fn set_scheduler(&mut self, config: Option<SchedulerConfig>) {
+        self.scheduler = config.map(|config| Arc::new(scheduler::RequestScheduler::new(config)));
+    }
+
+    /// Current request scheduling limits
+    pub fn get_scheduler(&self) -> Option<&SchedulerConfig> {
+        self.scheduler.as_deref().map(scheduler::RequestScheduler::config)
+    }
+
+    /// In-flight, waiting and sent request counters, if scheduling is on
+    pub fn scheduler_stats(&self) -> Option<SchedulerStats> {
+        self.scheduler.as_deref().map(scheduler::RequestScheduler::stats)
+    }
+
+    /// A clone of this client sending its requests with `options`
+    ///
+    /// ```rust
+    /// use knishio_client::graphql::{GraphQLClient, RequestOptions, RequestPriority};
+    ///
+    /// let client = GraphQLClient::new("https://node.example/graphql");
+    /// let urgent = client.with_options(RequestOptions::new().priority(RequestPriority::High));
+    /// assert_eq!(urgent.request_options().priority, RequestPriority::High);
+    /// ```
+    pub fn with_options(&self, options: RequestOptions) -> GraphQLClient {
+        GraphQLClient { request_options: options, ..self.clone() }
+    }
+
+    /// Options of the requests sent through this client
+    pub fn request_options(&self) -> RequestOptions {
+        self.request_options
+    }
+
+    /// Headers sent with every request: `User-Agent`, `X-KnishIO-SDK` and those configured
+    pub fn default_headers(&self) -> &HashMap<String, String> {
+        &self.default_headers
+    }
+
+    /// Call `interceptor` with the headers of every request before it is sent
+    ///
+    /// The interceptor gets the full header set, SDK identification and auth token
+    /// included, and may add, change or remove headers. Pass `None` to remove it.
+    pub fn set_request_interceptor(&mut self, interceptor: Option<RequestInterceptor>) {
+        self.request_interceptor = interceptor;
+    }
+
+    /// Format requests are currently sent in (JSON once the node has refused binary)
+    pub fn wire_format(&self) -> WireFormat {
+        if self.binary_refused.load(Ordering::Relaxed) {
+            WireFormat::Json
+        } else {
+            self.wire_format
+        }
+    }
     
     /// Get socket configuration
     pub fn get_socket_config(&self) -> Option<&SocketConfig> {
@@ -326,6 +592,11 @@ impl GraphQLClient {
         self.auth_token.clone()
     }
 
+    /// Node public key requests are encrypted to, from the last auth exchange
+    pub fn get_pubkey(&self) -> Option<&str> {
+        self.pubkey.as_deref()
+    }
+
     /// Execute a GraphQL query
     pub async fn query(&self, request: GraphQLRequest) -> Result<GraphQLResponse> {
         let payload = json!({
@@ -334,45 +605,11 @@ impl GraphQLClient {
             "operationName": request.operation_name
         });
 
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert(
-            "Content-Type",
-            "application/json"
-                .parse()
-                .map_err(|_| KnishIOError::custom("Invalid Content-Type header"))?,
-        );
-        
-        if let Some(ref token) = self.auth_token {
-            headers.insert(
-                "X-Auth-Token",
-                token
-                    .parse()
-                    .map_err(|_| KnishIOError::custom("Invalid auth token header"))?,
-            );
+        let _slot = self.admit().await;
+        match self.hedge {
+            Some(ref hedge) if self.can_hedge(hedge) => self.hedged_post(&payload, hedge).await,
+            _ => self.post(&self.server_uri, self.auth_token.as_deref(), &payload).await,
         }
-
-        let response = self
-            .http_client
-            .post(&self.server_uri)
-            .headers(headers)
-            .json(&payload)
-            .send()
-            .await
-            .map_err(KnishIOError::from_network_error)?;
-
-        if !response.status().is_success() {
-            return Err(KnishIOError::custom(format!(
-                "HTTP error: {}",
-                response.status()
-            )));
-        }
-
-        let graphql_response: GraphQLResponse = response
-            .json()
-            .await
-            .map_err(KnishIOError::from_network_error)?;
-
-        self.format_response(graphql_response)
     }
 
     /// Execute a GraphQL mutation
@@ -383,31 +620,106 @@ impl GraphQLClient {
             "operationName": request.operation_name
         });
 
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert(
-            "Content-Type",
-            "application/json"
-                .parse()
-                .map_err(|_| KnishIOError::custom("Invalid Content-Type header"))?,
-        );
-        
-        if let Some(ref token) = self.auth_token {
-            headers.insert(
-                "X-Auth-Token",
-                token
-                    .parse()
-                    .map_err(|_| KnishIOError::custom("Invalid auth token header"))?,
-            );
+        let _slot = self.admit().await;
+        self.post(&self.server_uri, self.auth_token.as_deref(), &payload).await
+    }
+
+    /// Whether a query may be duplicated to `hedge` without handing it this node's credentials
+    fn can_hedge(&self, hedge: &HedgeConfig) -> bool {
+        hedge.uri != self.server_uri
+            && !self.encrypt
+            && (self.auth_token.is_none() || hedge.auth_token.is_some())
+    }
+
+    /// Wait for a scheduler slot at this client's priority, if scheduling is on
+    async fn admit(&self) -> Option<scheduler::SchedulerPermit> {
+        match self.scheduler {
+            Some(ref scheduler) => Some(scheduler.acquire(self.request_options.priority).await),
+            None => None,
         }
+    }
 
-        let response = self
-            .http_client
-            .post(&self.server_uri)
-            .headers(headers)
-            .json(&payload)
-            .send()
-            .await
-            .map_err(KnishIOError::from_network_error)?;
+    /// POST `payload` to the primary node, and to the hedge node too if the primary is slow
+    ///
+    /// The first successful reply is returned; the losing request is cancelled by dropping
+    /// it. If one node fails, the other's result is awaited instead.
+    async fn hedged_post(&self, payload: &Value, hedge: &HedgeConfig) -> Result<GraphQLResponse> {
+        let primary = self.post(&self.server_uri, self.auth_token.as_deref(), payload);
+        tokio::pin!(primary);
+
+        tokio::select! {
+            result = &mut primary => return result,
+            _ = tokio::time::sleep(hedge.delay) => {}
+        }
+
+        let backup = self.post(&hedge.uri, hedge.auth_token.as_deref(), payload);
+        tokio::pin!(backup);
+
+        tokio::select! {
+            result = &mut primary => match result {
+                Ok(response) => Ok(response),
+                Err(_) => backup.await,
+            },
+            result = &mut backup => match result {
+                Ok(response) => Ok(response),
+                Err(_) => primary.await,
+            },
+        }
+    }
+
+    /// POST `payload` to `uri` with `uri`'s auth token, in the negotiated format, and decode
+    /// the reply by its Content-Type
+    async fn post(&self, uri: &str, auth_token: Option<&str>, payload: &Value) -> Result<GraphQLResponse> {
+        #[cfg(feature = "fault-injection")]
+        let fault = self.fault_injector.as_ref().and_then(|injector| injector.roll_request());
+        #[cfg(feature = "fault-injection")]
+        match fault {
+            Some(Fault::Timeout) => {
+                if let Some(ref injector) = self.fault_injector {
+                    tokio::time::sleep(injector.get_timeout_delay()).await;
+                }
+                return Err(KnishIOError::Network("operation timed out (injected fault)".to_string()));
+            }
+            Some(Fault::ServerError(status)) => {
+                let status = reqwest::StatusCode::from_u16(status).unwrap_or(reqwest::StatusCode::INTERNAL_SERVER_ERROR);
+                return Err(KnishIOError::custom(format!("HTTP error: {}", status)));
+            }
+            _ => {}
+        }
+
+        #[cfg(feature = "vcr")]
+        if let Some(replayed) = self.cassette.as_ref().and_then(|cassette| cassette.play(payload)) {
+            let (reply, headers) = replayed?;
+            let mut graphql_response: GraphQLResponse = serde_json::from_value(reply)
+                .map_err(|e| KnishIOError::Serialization(e.to_string()))?;
+            graphql_response.headers = headers;
+            return self.format_response(graphql_response);
+        }
+
+        let started = Instant::now();
+        let format = self.wire_format();
+        let mut response = self.send(uri, auth_token, payload, format).await?;
+
+        if format.is_binary() {
+            match response.status() {
+                reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE | reqwest::StatusCode::NOT_ACCEPTABLE => {
+                    self.binary_refused.store(true, Ordering::Relaxed);
+                    response = self.send(uri, auth_token, payload, WireFormat::Json).await?;
+                }
+                // Nodes that cannot parse the body may just call the request bad
+                reqwest::StatusCode::BAD_REQUEST => {
+                    response = self.send(uri, auth_token, payload, WireFormat::Json).await?;
+                    if response.status() != reqwest::StatusCode::BAD_REQUEST {
+                        self.binary_refused.store(true, Ordering::Relaxed);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(error) = retry_policy::throttled(&response) {
+            return Err(error);
+        }
 
         if !response.status().is_success() {
             return Err(KnishIOError::custom(format!(
@@ -416,69 +728,91 @@ impl GraphQLClient {
             )));
         }
 
-        let graphql_response: GraphQLResponse = response
-            .json()
-            .await
-            .map_err(KnishIOError::from_network_error)?;
+        let reply_format = response.headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(WireFormat::from_content_type)
+            .unwrap_or(WireFormat::Json);
+        let headers = ResponseHeaders::from_header_map(response.headers());
+        let body = response.bytes().await.map_err(KnishIOError::from_network_error)?;
+        if let Some(ref monitor) = self.query_cost {
+            monitor.observe(uri, payload, started.elapsed(), body.len());
+        }
+        #[cfg(feature = "fault-injection")]
+        let body = if fault == Some(Fault::MalformedResponse) { body.slice(..body.len() / 2) } else { body };
+        let reply = reply_format.decode(&body)?;
+        #[cfg(feature = "vcr")]
+        if let Some(ref cassette) = self.cassette {
+            cassette.store(uri, payload, &reply, &headers)?;
+        }
+        let mut graphql_response: GraphQLResponse = serde_json::from_value(reply)
+            .map_err(|e| KnishIOError::Serialization(e.to_string()))?;
+        graphql_response.headers = headers;
 
         self.format_response(graphql_response)
     }
 
+    async fn send(&self, uri: &str, auth_token: Option<&str>, payload: &Value, format: WireFormat) -> Result<reqwest::Response> {
+        let accept = if format.is_binary() {
+            format!("{}, application/json;q=0.9", format.content_type())
+        } else {
+            format.content_type().to_string()
+        };
+
+        let mut fields = (*self.default_headers).clone();
+        fields.insert("Content-Type".to_string(), format.content_type().to_string());
+        fields.insert("Accept".to_string(), accept);
+        if let Some(token) = auth_token {
+            fields.insert("X-Auth-Token".to_string(), token.to_string());
+        }
+        if let Some(ref interceptor) = self.request_interceptor {
+            interceptor(uri, &mut fields);
+        }
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in fields {
+            let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| KnishIOError::custom(format!("Invalid header name {}", name)))?;
+            let value = reqwest::header::HeaderValue::from_str(&value)
+                .map_err(|_| KnishIOError::custom(format!("Invalid {} header", name)))?;
+            headers.insert(name, value);
+        }
+
+        self.http_client
+            .post(uri)
+            .headers(headers)
+            .body(format.encode(payload)?)
+            .send()
+            .await
+            .map_err(KnishIOError::from_network_error)
+    }
+
     /// Subscribe to GraphQL subscription (WebSocket-based)
+    ///
+    /// Runs on a `WebSocketManager` built from the socket config, so the sub-protocol is
+    /// negotiated and messages are framed as for every other subscription. `callback` gets
+    /// each response until the connection gives up.
     pub async fn subscribe<F>(&mut self, request: GraphQLRequest, mut callback: F) -> Result<()>
     where
         F: FnMut(GraphQLResponse) + Send + 'static,
     {
         let socket_config = self.socket_config.as_ref()
             .ok_or_else(|| KnishIOError::custom("Socket not configured for subscriptions"))?;
+        let query = request.query.or(request.mutation)
+            .ok_or_else(|| KnishIOError::InvalidQuery("Subscription request without a document".to_string()))?;
 
-        let ws_url = &socket_config.socket_uri;
-        let (ws_stream, _) = connect_async(ws_url)
-            .await
-            .map_err(|e| KnishIOError::custom(format!("WebSocket connection failed: {}", e)))?;
-
-        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
-
-        // Send connection init
-        let init_message = json!({
-            "type": "connection_init",
-            "payload": {
-                "authToken": self.auth_token
-            }
-        });
-        
-        ws_sender
-            .send(Message::Text(init_message.to_string().into()))
-            .await
-            .map_err(|e| KnishIOError::custom(format!("Failed to send init: {}", e)))?;
-
-        // Send subscription
-        let sub_message = json!({
-            "type": "start",
-            "payload": {
-                "query": request.query.or(request.mutation),
-                "variables": request.variables
-            }
-        });
-
-        ws_sender
-            .send(Message::Text(sub_message.to_string().into()))
-            .await
-            .map_err(|e| KnishIOError::custom(format!("Failed to send subscription: {}", e)))?;
+        let mut manager = WebSocketManager::from_config(socket_config, self.auth_token.clone(), self.debug);
+        #[cfg(feature = "fault-injection")]
+        {
+            manager = manager.with_fault_injector(self.fault_injector.clone());
+        }
+        let mut responses = manager.subscribe(query, request.variables, request.operation_name).await?;
 
-        // Handle incoming messages
         tokio::spawn(async move {
-            while let Some(message) = ws_receiver.next().await {
-                match message {
-                    Ok(Message::Text(text)) => {
-                        if let Ok(response) = serde_json::from_str::<GraphQLResponse>(&text) {
-                            callback(response);
-                        }
-                    }
-                    Ok(Message::Close(_)) => break,
-                    Err(_) => break,
-                    _ => {}
-                }
+            // The manager owns the connection; it lives as long as responses are read
+            let _manager = manager;
+            while let Some(response) = responses.recv().await {
+                callback(response);
             }
         });
 
@@ -496,6 +830,13 @@ impl GraphQLClient {
                     .map(|e| e.message.clone())
                     .collect::<Vec<_>>()
                     .join(", ");
+                if errors.iter().any(GraphQLError::is_throttled) {
+                    return Err(KnishIOError::RateLimited {
+                        message: format!("GraphQL errors: {}", error_msg),
+                        retry_after: errors.iter().find_map(GraphQLError::retry_after)
+                            .map(|wait| wait.min(self.retry_config.max_delay)),
+                    });
+                }
                 return Err(KnishIOError::custom(format!("GraphQL errors: {}", error_msg)));
             }
         }
@@ -587,4 +928,82 @@ impl GraphQLClient {
         // This is a simplified version for now - just a compatibility stub
         // Real implementations would use unsubscribe_all_async()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, feature = "cbor"))]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Answer 400 to non-JSON bodies and to queries containing `bad`, data otherwise
+    async fn strict_node() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let uri = format!("http://{}/graphql", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut buffer = Vec::new();
+                    let mut chunk = [0u8; 4096];
+                    loop {
+                        let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") else {
+                            match stream.read(&mut chunk).await {
+                                Ok(0) | Err(_) => return,
+                                Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+                            }
+                            continue;
+                        };
+                        let head = String::from_utf8_lossy(&buffer[..end]).to_lowercase();
+                        let length: usize = head.lines()
+                            .find_map(|line| line.strip_prefix("content-length:"))
+                            .and_then(|value| value.trim().parse().ok())
+                            .unwrap_or_default();
+                        while buffer.len() < end + 4 + length {
+                            let read = stream.read(&mut chunk).await.unwrap();
+                            buffer.extend_from_slice(&chunk[..read]);
+                        }
+                        let body = buffer[end + 4..end + 4 + length].to_vec();
+                        buffer.drain(..end + 4 + length);
+
+                        let json = head.contains("content-type: application/json");
+                        let reply = match serde_json::from_slice::<Value>(&body) {
+                            Ok(request) if json && !request["query"].as_str().unwrap_or_default().contains("bad") => {
+                                let body = serde_json::json!({ "data": { "Token": { "slug": "WIRE" } } }).to_string();
+                                format!("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", body.len(), body)
+                            }
+                            _ => "HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n".to_string(),
+                        };
+                        stream.write_all(reply.as_bytes()).await.unwrap();
+                    }
+                });
+            }
+        });
+        uri
+    }
+
+    fn request(query: &str) -> GraphQLRequest {
+        GraphQLRequest {
+            query: Some(query.to_string()),
+            mutation: None,
+            variables: None,
+            operation_name: None,
+            timeout: None,
+            headers: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_binary_bad_request_falls_back_to_json() {
+        let mut client = GraphQLClient::new(strict_node().await);
+
+        // A request the node rejects in any format keeps the binary format
+        client.set_wire_format(WireFormat::Cbor);
+        assert!(client.query(request("query { bad }")).await.is_err());
+        assert_eq!(client.wire_format(), WireFormat::Cbor);
+
+        let response = client.query(request("query { Token { slug } }")).await.unwrap();
+        assert_eq!(response.data.unwrap()["Token"]["slug"], "WIRE");
+        assert_eq!(client.wire_format(), WireFormat::Json);
+    }
+}
@@ -223,6 +223,17 @@ impl RetryPolicy {
         }
     }
     
+    /// Delay before retrying `error` after `attempt`
+    ///
+    /// A wait the node asked for (`KnishIOError::RateLimited`) replaces the computed
+    /// backoff, without jitter but still capped at `max_delay`.
+    pub fn delay_for(&self, attempt: u32, error: &KnishIOError) -> Duration {
+        match error.retry_after() {
+            Some(retry_after) => retry_after.min(self.max_delay),
+            None => self.calculate_delay(attempt),
+        }
+    }
+
     /// Check if an error should trigger a retry
     pub fn should_retry(&self, error: &KnishIOError) -> bool {
         for condition in &self.retry_conditions {
@@ -240,14 +251,14 @@ impl RetryPolicy {
                 matches!(error, KnishIOError::Network(_))
             },
             RetryCondition::ServerError => {
-                if let KnishIOError::Custom(msg) = error {
+                if let Some(msg) = status_message(error) {
                     msg.contains("HTTP error: 5") // Matches 5xx errors
                 } else {
                     false
                 }
             },
             RetryCondition::HttpStatus(status) => {
-                if let KnishIOError::Custom(msg) = error {
+                if let Some(msg) = status_message(error) {
                     msg.contains(&format!("HTTP error: {}", status))
                 } else {
                     false
@@ -257,7 +268,9 @@ impl RetryPolicy {
                 error.to_string().to_lowercase().contains(&message_contains.to_lowercase())
             },
             RetryCondition::RateLimit => {
-                if let KnishIOError::Custom(msg) = error {
+                if let KnishIOError::RateLimited { .. } = error {
+                    true
+                } else if let KnishIOError::Custom(msg) = error {
                     msg.contains("HTTP error: 429") || 
                     msg.to_lowercase().contains("rate limit")
                 } else {
@@ -332,8 +345,8 @@ impl RetryExecutor {
                         return Err(error);
                     }
                     
-                    // Calculate delay and wait
-                    let delay = self.policy.calculate_delay(attempt);
+                    // Calculate delay (or take the node's) and wait
+                    let delay = self.policy.delay_for(attempt, &error);
                     
                     if self.debug {
                         warn!(
@@ -399,7 +412,7 @@ impl RetryExecutor {
                         return Err(error);
                     }
                     
-                    let delay = self.policy.calculate_delay(attempt);
+                    let delay = self.policy.delay_for(attempt, &error);
                     
                     if self.debug {
                         warn!(
@@ -431,6 +444,40 @@ impl RetryExecutor {
     }
 }
 
+/// Message of errors that carry an HTTP status line
+fn status_message(error: &KnishIOError) -> Option<&str> {
+    match error {
+        KnishIOError::Custom(msg) | KnishIOError::RateLimited { message: msg, .. } => Some(msg),
+        _ => None,
+    }
+}
+
+/// `RateLimited` error for a throttling reply: any 429, or a 503 that says when to return
+pub(crate) fn throttled(response: &reqwest::Response) -> Option<KnishIOError> {
+    let status = response.status();
+    let retry_after = response.headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_retry_after);
+
+    let throttling = status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || (status == reqwest::StatusCode::SERVICE_UNAVAILABLE && retry_after.is_some());
+    throttling.then(|| KnishIOError::RateLimited {
+        message: format!("HTTP error: {}", status),
+        retry_after,
+    })
+}
+
+/// Parse a `Retry-After` value: delay-seconds or an HTTP date (past dates mean "now")
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().unwrap_or(Duration::ZERO))
+}
+
 /// Convenience function to execute an operation with retry logic
 pub async fn execute_with_retry<F, Fut, T>(
     policy: RetryPolicy,
@@ -448,6 +495,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::{json, Value};
     use std::sync::atomic::{AtomicU32, Ordering};
     use std::sync::Arc;
     
@@ -616,6 +664,78 @@ mod tests {
         assert_eq!(counter.load(Ordering::SeqCst), 1); // Only 1 attempt, no retries
     }
     
+    #[test]
+    fn test_retry_after_hints() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+        let later = (chrono::Utc::now() + chrono::Duration::seconds(90)).to_rfc2822();
+        assert!(parse_retry_after(&later).is_some_and(|wait| wait > Duration::from_secs(80)));
+        assert_eq!(parse_retry_after("soon"), None);
+
+        let throttled = |extensions: Value| crate::GraphQLError {
+            message: "slow down".to_string(),
+            locations: None,
+            path: None,
+            extensions: serde_json::from_value(extensions).ok(),
+        };
+        let error = throttled(json!({"code": "RATE_LIMITED"}));
+        assert!(error.is_throttled());
+        assert_eq!(error.retry_after(), None);
+        assert_eq!(throttled(json!({"retryAfter": "2"})).retry_after(), Some(Duration::from_secs(2)));
+        assert_eq!(throttled(json!({"retryAfterMs": 250})).retry_after(), Some(Duration::from_millis(250)));
+        assert!(!throttled(json!({"code": "BAD_USER_INPUT"})).is_throttled());
+        assert_eq!(throttled(json!({"retryAfter": 1e30})).retry_after(), None);
+        assert_eq!(throttled(json!({"retryAfterMs": "1e300"})).retry_after(), None);
+    }
+
+    #[test]
+    fn test_retry_after_overrides_backoff() {
+        let policy = RetryPolicy::new()
+            .with_initial_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_secs(5))
+            .with_jitter(0.0);
+        let limited = |retry_after| KnishIOError::RateLimited {
+            message: "HTTP error: 429 Too Many Requests".to_string(),
+            retry_after,
+        };
+
+        assert!(policy.should_retry(&limited(None)));
+        assert!(RetryPolicy::network_optimized().should_retry(&KnishIOError::RateLimited {
+            message: "HTTP error: 503 Service Unavailable".to_string(),
+            retry_after: Some(Duration::from_secs(1)),
+        }));
+        assert_eq!(policy.delay_for(2, &limited(Some(Duration::from_secs(3)))), Duration::from_secs(3));
+        assert_eq!(policy.delay_for(2, &limited(Some(Duration::from_secs(60)))), Duration::from_secs(5));
+        assert_eq!(policy.delay_for(2, &limited(None)), Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn test_retry_executor_honors_retry_after() {
+        // Computed backoff would be 10s; the node asks for 10ms
+        let policy = RetryPolicy::new()
+            .with_initial_delay(Duration::from_secs(10))
+            .with_max_attempts(2);
+        let counter = Arc::new(AtomicU32::new(0));
+        let counter_clone = counter.clone();
+
+        let result = tokio::time::timeout(Duration::from_secs(2), policy.executor(false).execute(move || {
+            let counter = counter_clone.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Err(KnishIOError::RateLimited {
+                        message: "HTTP error: 429 Too Many Requests".to_string(),
+                        retry_after: Some(Duration::from_millis(10)),
+                    })
+                } else {
+                    Ok("done")
+                }
+            }
+        })).await;
+
+        assert_eq!(result.unwrap().unwrap(), "done");
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+    }
+
     #[test]
     fn test_specialized_policies() {
         let network_policy = RetryPolicy::network_optimized();
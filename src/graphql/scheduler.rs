@@ -0,0 +1,369 @@
+//! Request priorities
+//!
+//! With a `SchedulerConfig` set, every query and mutation waits for a slot before it is
+//! sent: at most `max_in_flight` requests are outstanding, and with a rate limit at most
+//! `requests_per_second` start each second (bursts of up to `burst`). Waiting requests are
+//! served by `RequestPriority`, oldest first within a priority, so a balance check issued
+//! behind a thousand background meta writes goes out as soon as a slot frees up.
+//!
+//! Priorities are strict: background requests only go out while nothing of higher
+//! priority is waiting. The priority of a request is the one in the `RequestOptions` of
+//! the client it was sent with (`GraphQLClient::with_options`); it defaults to `Normal`.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// Longest a rate-limited waiter sleeps before looking at the bucket again
+const MAX_RATE_WAIT: Duration = Duration::from_secs(60);
+
+/// Order in which waiting requests are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum RequestPriority {
+    /// Interactive reads that must not wait behind bulk traffic, e.g. balance checks
+    High,
+    #[default]
+    Normal,
+    /// Bulk work that can wait, e.g. chunked meta writes
+    Background,
+}
+
+impl RequestPriority {
+    /// Every priority, most urgent first
+    pub const ALL: [RequestPriority; 3] = [RequestPriority::High, RequestPriority::Normal, RequestPriority::Background];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Per-request transport options
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RequestOptions {
+    /// Scheduling priority
+    pub priority: RequestPriority,
+}
+
+impl RequestOptions {
+    /// Default options: `Normal` priority
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the scheduling priority
+    pub fn priority(mut self, priority: RequestPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Concurrency and rate limits of a client's requests
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchedulerConfig {
+    /// Most requests outstanding at once; zero is taken as one
+    pub max_in_flight: usize,
+    /// Most requests started per second; None, or a rate that is not finite and positive,
+    /// for no limit
+    pub requests_per_second: Option<f64>,
+    /// Requests that may start back to back after an idle spell under the rate limit;
+    /// zero is taken as one
+    pub burst: u32,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        SchedulerConfig {
+            max_in_flight: 8,
+            requests_per_second: None,
+            burst: 1,
+        }
+    }
+}
+
+impl SchedulerConfig {
+    /// Set the most requests outstanding at once
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    /// Start at most `requests_per_second` requests each second, `burst` at once
+    pub fn rate_limit(mut self, requests_per_second: f64, burst: u32) -> Self {
+        self.requests_per_second = Some(requests_per_second).filter(|rate| rate.is_finite() && *rate > 0.0);
+        self.burst = burst.max(1);
+        self
+    }
+}
+
+/// Scheduler counters
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchedulerStats {
+    /// Requests sent and not yet answered
+    pub in_flight: usize,
+    /// Requests waiting, by priority (`RequestPriority::ALL` order)
+    pub queued: [usize; 3],
+    /// Requests sent, by priority
+    pub dispatched: [u64; 3],
+}
+
+impl SchedulerStats {
+    /// Requests waiting at `priority`
+    pub fn queued_at(&self, priority: RequestPriority) -> usize {
+        self.queued[priority.index()]
+    }
+
+    /// Requests sent at `priority`
+    pub fn dispatched_at(&self, priority: RequestPriority) -> u64 {
+        self.dispatched[priority.index()]
+    }
+}
+
+#[derive(Debug)]
+struct SchedulerState {
+    waiting: [VecDeque<u64>; 3],
+    next_ticket: u64,
+    in_flight: usize,
+    tokens: f64,
+    refilled_at: Instant,
+    dispatched: [u64; 3],
+}
+
+/// Slots and waiting requests shared by the clones of a `GraphQLClient`
+#[derive(Debug)]
+pub(crate) struct RequestScheduler {
+    config: SchedulerConfig,
+    state: Mutex<SchedulerState>,
+    changed: Notify,
+}
+
+impl RequestScheduler {
+    pub(crate) fn new(config: SchedulerConfig) -> Self {
+        // The fields are public, so the builder's clamps are applied again here
+        let config = SchedulerConfig {
+            max_in_flight: config.max_in_flight.max(1),
+            requests_per_second: config.requests_per_second.filter(|rate| rate.is_finite() && *rate > 0.0),
+            burst: config.burst.max(1),
+        };
+        RequestScheduler {
+            state: Mutex::new(SchedulerState {
+                waiting: Default::default(),
+                next_ticket: 0,
+                in_flight: 0,
+                tokens: f64::from(config.burst),
+                refilled_at: Instant::now(),
+                dispatched: [0; 3],
+            }),
+            config,
+            changed: Notify::new(),
+        }
+    }
+
+    pub(crate) fn config(&self) -> &SchedulerConfig {
+        &self.config
+    }
+
+    pub(crate) fn stats(&self) -> SchedulerStats {
+        let state = self.state();
+        SchedulerStats {
+            in_flight: state.in_flight,
+            queued: [state.waiting[0].len(), state.waiting[1].len(), state.waiting[2].len()],
+            dispatched: state.dispatched,
+        }
+    }
+
+    /// Wait for a slot at `priority`; the slot is held until the permit is dropped
+    pub(crate) async fn acquire(self: &Arc<Self>, priority: RequestPriority) -> SchedulerPermit {
+        let ticket = {
+            let mut state = self.state();
+            let ticket = state.next_ticket;
+            state.next_ticket += 1;
+            state.waiting[priority.index()].push_back(ticket);
+            ticket
+        };
+        // Leaves the queue if the request is dropped while waiting
+        let mut waiter = Waiter { scheduler: self, priority, ticket, admitted: false };
+
+        loop {
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            let retry_in = {
+                let mut state = self.state();
+                self.refill(&mut state);
+                let first = RequestPriority::ALL.iter().find(|p| !state.waiting[p.index()].is_empty());
+                let is_next = first == Some(&priority) && state.waiting[priority.index()].front() == Some(&ticket);
+
+                if is_next && state.in_flight < self.config.max_in_flight && state.tokens >= 1.0 {
+                    state.waiting[priority.index()].pop_front();
+                    state.in_flight += 1;
+                    state.dispatched[priority.index()] += 1;
+                    if self.config.requests_per_second.is_some() {
+                        state.tokens -= 1.0;
+                    }
+                    waiter.admitted = true;
+                    drop(state);
+                    // The next request in line may be admitted too
+                    self.changed.notify_waiters();
+                    return SchedulerPermit { scheduler: self.clone() };
+                }
+
+                match self.config.requests_per_second {
+                    Some(rate) if is_next && state.tokens < 1.0 => Some(
+                        Duration::try_from_secs_f64((1.0 - state.tokens) / rate).map_or(MAX_RATE_WAIT, |wait| wait.min(MAX_RATE_WAIT)),
+                    ),
+                    _ => None,
+                }
+            };
+
+            match retry_in {
+                Some(delay) => {
+                    tokio::select! {
+                        _ = &mut changed => {}
+                        _ = tokio::time::sleep(delay) => {}
+                    }
+                }
+                None => changed.await,
+            }
+        }
+    }
+
+    fn refill(&self, state: &mut SchedulerState) {
+        let Some(rate) = self.config.requests_per_second else {
+            state.tokens = f64::from(self.config.burst);
+            return;
+        };
+        let now = Instant::now();
+        let earned = now.duration_since(state.refilled_at).as_secs_f64() * rate;
+        state.tokens = (state.tokens + earned).min(f64::from(self.config.burst));
+        state.refilled_at = now;
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, SchedulerState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A request's place in the queue until it is admitted
+struct Waiter<'a> {
+    scheduler: &'a RequestScheduler,
+    priority: RequestPriority,
+    ticket: u64,
+    admitted: bool,
+}
+
+impl Drop for Waiter<'_> {
+    fn drop(&mut self) {
+        if !self.admitted {
+            self.scheduler.state().waiting[self.priority.index()].retain(|ticket| *ticket != self.ticket);
+            self.scheduler.changed.notify_waiters();
+        }
+    }
+}
+
+/// An outstanding request's slot; released on drop
+#[derive(Debug)]
+pub(crate) struct SchedulerPermit {
+    scheduler: Arc<RequestScheduler>,
+}
+
+impl Drop for SchedulerPermit {
+    fn drop(&mut self) {
+        self.scheduler.state().in_flight -= 1;
+        self.scheduler.changed.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_waiting_requests_are_served_by_priority() {
+        let scheduler = Arc::new(RequestScheduler::new(SchedulerConfig::default().max_in_flight(1)));
+        let first = scheduler.acquire(RequestPriority::Background).await;
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut tasks = Vec::new();
+        for priority in [RequestPriority::Background, RequestPriority::Background, RequestPriority::Normal, RequestPriority::High] {
+            let scheduler = scheduler.clone();
+            let order = order.clone();
+            tasks.push(tokio::spawn(async move {
+                let _permit = scheduler.acquire(priority).await;
+                order.lock().unwrap().push(priority);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }));
+            // Queue in a known order
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let stats = scheduler.stats();
+        assert_eq!((stats.in_flight, stats.queued), (1, [1, 1, 2]));
+        drop(first);
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(*order.lock().unwrap(), vec![
+            RequestPriority::High,
+            RequestPriority::Normal,
+            RequestPriority::Background,
+            RequestPriority::Background,
+        ]);
+        let stats = scheduler.stats();
+        assert_eq!((stats.in_flight, stats.dispatched_at(RequestPriority::Background)), (0, 3));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_and_cancelled_waiters() {
+        let scheduler = Arc::new(RequestScheduler::new(SchedulerConfig::default().max_in_flight(10).rate_limit(50.0, 2)));
+        let started = Instant::now();
+        for _ in 0..4 {
+            drop(scheduler.acquire(RequestPriority::Normal).await);
+        }
+        // Two from the burst, then one every 20ms
+        assert!(started.elapsed() >= Duration::from_millis(35), "{:?}", started.elapsed());
+
+        // A waiter that gives up leaves the queue and does not block the next one
+        let abandoned = tokio::time::timeout(Duration::from_millis(1), scheduler.acquire(RequestPriority::High)).await;
+        assert!(abandoned.is_err());
+        assert_eq!(scheduler.stats().queued_at(RequestPriority::High), 0);
+        drop(scheduler.acquire(RequestPriority::Background).await);
+        assert_eq!(scheduler.stats().dispatched, [0, 4, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_config_fields_are_clamped() {
+        let config = SchedulerConfig { max_in_flight: 0, requests_per_second: Some(0.0), burst: 0 };
+        let scheduler = Arc::new(RequestScheduler::new(config));
+        assert_eq!(scheduler.config(), &SchedulerConfig { max_in_flight: 1, requests_per_second: None, burst: 1 });
+        drop(tokio::time::timeout(Duration::from_secs(1), scheduler.acquire(RequestPriority::Normal)).await.unwrap());
+
+        // A rate too small for a Duration waits in capped steps instead of panicking
+        let scheduler = Arc::new(RequestScheduler::new(SchedulerConfig::default().rate_limit(1e-300, 1)));
+        drop(scheduler.acquire(RequestPriority::Normal).await);
+        let starved = tokio::time::timeout(Duration::from_millis(20), scheduler.acquire(RequestPriority::Normal)).await;
+        assert!(starved.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_client_requests_carry_their_priority() {
+        let ledger = crate::test_ledger::TestLedger::start().await.unwrap();
+        let secret = crate::crypto::generate_secret("scheduled-client");
+        ledger.fund(&secret, "GOLD", 5.0).unwrap();
+        let mut client = ledger.client(&secret);
+        assert!(client.request_scheduler_stats().is_none());
+
+        client.set_request_scheduler(Some(SchedulerConfig::default().max_in_flight(2)));
+        client.query_balance("GOLD", None).await.unwrap();
+        let report = client.create_meta_bulk("note", "n1", vec![crate::types::MetaItem::new("text", "hi")], 10).await.unwrap();
+        assert!(report.is_complete(), "{:?}", report.chunks);
+
+        let stats = client.request_scheduler_stats().unwrap();
+        assert_eq!(stats.in_flight, 0);
+        assert_eq!(stats.dispatched_at(RequestPriority::High), 1);
+        assert_eq!(stats.dispatched_at(RequestPriority::Background), 1);
+        assert!(stats.dispatched_at(RequestPriority::Normal) >= 1);
+    }
+}
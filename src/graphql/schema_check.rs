@@ -0,0 +1,776 @@
+//! Validation of GraphQL documents against a node schema
+//!
+//! `lint_document` stops at syntax. `NodeSchema` is read from SDL and resolves every field a
+//! document selects, level by level, along with its arguments and variable types, so a field
+//! the node no longer serves is reported before the request instead of coming back as
+//! "Cannot query field". The SDK vendors the part of the node schema the JS SDK's documents
+//! rely on (`node_schema.graphql`, loaded by `NodeSchema::bundled`; its header records where
+//! it comes from), and the tests below check every document in the query, mutation and
+//! subscribe modules against it: a field missing from the vendored schema fails the test
+//! run, naming each document that still selects it.
+
+use super::lint::{check_brackets, end_position, invalid, lint_document, tokenize, OperationType, Position, Token, TokenKind};
+use crate::error::{KnishIOError, Result};
+use std::collections::HashMap;
+
+/// Node schema SDL shipped with the SDK
+const BUNDLED_SCHEMA: &str = include_str!("node_schema.graphql");
+
+const BUILT_IN_SCALARS: &[&str] = &["Boolean", "Float", "ID", "Int", "String"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TypeKind {
+    Scalar,
+    Object,
+    Interface,
+    Union,
+    Enum,
+    InputObject,
+}
+
+impl TypeKind {
+    /// Whether a field of this type needs a selection set
+    fn is_composite(&self) -> bool {
+        matches!(self, TypeKind::Object | TypeKind::Interface | TypeKind::Union)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FieldDefinition {
+    arguments: Vec<String>,
+    /// Named type, list and non-null wrappers removed
+    type_name: String,
+}
+
+#[derive(Debug, Clone)]
+struct TypeDefinition {
+    kind: TypeKind,
+    fields: HashMap<String, FieldDefinition>,
+}
+
+/// Types and root operation types of a GraphQL schema, read from SDL
+#[derive(Debug, Clone)]
+pub struct NodeSchema {
+    types: HashMap<String, TypeDefinition>,
+    query: Option<String>,
+    mutation: Option<String>,
+    subscription: Option<String>,
+}
+
+impl NodeSchema {
+    /// The node schema vendored with the SDK
+    ///
+    /// # Errors
+    ///
+    /// None in practice: the vendored SDL is parsed by the tests
+    pub fn bundled() -> Result<Self> {
+        Self::parse(BUNDLED_SCHEMA)
+    }
+
+    /// Read a schema from SDL
+    ///
+    /// Type, interface, union, enum, input, scalar, directive and schema definitions are
+    /// understood, as are `extend` and descriptions. Without a `schema` block the root types
+    /// are `Query`, `Mutation` and `Subscription` when defined.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidQuery` for SDL that does not parse, a type defined twice, or a field
+    /// or root whose type is not defined
+    pub fn parse(sdl: &str) -> Result<Self> {
+        let tokens = tokenize(sdl)?;
+        check_brackets(&tokens)?;
+        let mut cursor = Cursor { tokens: &tokens, index: 0, end: end_position(sdl) };
+        let mut schema = NodeSchema {
+            types: BUILT_IN_SCALARS
+                .iter()
+                .map(|name| (name.to_string(), TypeDefinition { kind: TypeKind::Scalar, fields: HashMap::new() }))
+                .collect(),
+            query: None,
+            mutation: None,
+            subscription: None,
+        };
+        let mut roots = None;
+
+        while cursor.peek().is_some() {
+            cursor.description();
+            let position = cursor.position();
+            let mut keyword = cursor.name("a definition")?;
+            let extend = keyword == "extend";
+            if extend {
+                keyword = cursor.name("a definition")?;
+            }
+
+            let (name, kind, fields) = match keyword {
+                "schema" => {
+                    cursor.directives()?;
+                    cursor.expect('{')?;
+                    let mut operations = Vec::new();
+                    while !cursor.eat('}') {
+                        let at = cursor.position();
+                        let operation = cursor.name("an operation type")?;
+                        cursor.expect(':')?;
+                        operations.push((operation, cursor.name("a type")?, at));
+                    }
+                    roots = Some(operations);
+                    continue;
+                }
+                "directive" => {
+                    cursor.expect('@')?;
+                    cursor.name("a directive name")?;
+                    if cursor.eat('(') {
+                        while !cursor.eat(')') {
+                            cursor.input_value()?;
+                        }
+                    }
+                    cursor.eat_name("repeatable");
+                    cursor.expect_name("on")?;
+                    cursor.eat('|');
+                    cursor.name("a directive location")?;
+                    while cursor.eat('|') {
+                        cursor.name("a directive location")?;
+                    }
+                    continue;
+                }
+                "scalar" => {
+                    let name = cursor.name("a type name")?;
+                    cursor.directives()?;
+                    (name, TypeKind::Scalar, HashMap::new())
+                }
+                "type" | "interface" => {
+                    let name = cursor.name("a type name")?;
+                    if cursor.eat_name("implements") {
+                        cursor.eat('&');
+                        cursor.name("an interface")?;
+                        while cursor.eat('&') {
+                            cursor.name("an interface")?;
+                        }
+                    }
+                    cursor.directives()?;
+                    let fields = if cursor.peek() == Some(TokenKind::Punct('{')) { cursor.field_definitions()? } else { HashMap::new() };
+                    let kind = if keyword == "type" { TypeKind::Object } else { TypeKind::Interface };
+                    (name, kind, fields)
+                }
+                "union" => {
+                    let name = cursor.name("a type name")?;
+                    cursor.directives()?;
+                    if cursor.eat('=') {
+                        cursor.eat('|');
+                        cursor.name("a member type")?;
+                        while cursor.eat('|') {
+                            cursor.name("a member type")?;
+                        }
+                    }
+                    (name, TypeKind::Union, HashMap::new())
+                }
+                "enum" => {
+                    let name = cursor.name("a type name")?;
+                    cursor.directives()?;
+                    if cursor.eat('{') {
+                        while !cursor.eat('}') {
+                            cursor.description();
+                            cursor.name("an enum value")?;
+                            cursor.directives()?;
+                        }
+                    }
+                    (name, TypeKind::Enum, HashMap::new())
+                }
+                "input" => {
+                    let name = cursor.name("a type name")?;
+                    cursor.directives()?;
+                    if cursor.eat('{') {
+                        while !cursor.eat('}') {
+                            cursor.input_value()?;
+                        }
+                    }
+                    (name, TypeKind::InputObject, HashMap::new())
+                }
+                other => return Err(invalid(position, format!("Unknown definition `{}`", other))),
+            };
+
+            match schema.types.get_mut(name) {
+                Some(existing) if extend && existing.kind == kind => existing.fields.extend(fields),
+                Some(_) if extend => return Err(invalid(position, format!("`extend {}` does not match the kind of `{}`", keyword, name))),
+                Some(_) => return Err(invalid(position, format!("Type `{}` is defined twice", name))),
+                None if extend => return Err(invalid(position, format!("Cannot extend undefined type `{}`", name))),
+                None => {
+                    schema.types.insert(name.to_string(), TypeDefinition { kind, fields });
+                }
+            }
+        }
+
+        match roots {
+            Some(operations) => {
+                for (operation, type_name, at) in operations {
+                    let root = match operation {
+                        "query" => &mut schema.query,
+                        "mutation" => &mut schema.mutation,
+                        "subscription" => &mut schema.subscription,
+                        other => return Err(invalid(at, format!("Unknown operation type `{}`", other))),
+                    };
+                    if !schema.types.contains_key(type_name) {
+                        return Err(invalid(at, format!("Root type `{}` is not defined", type_name)));
+                    }
+                    *root = Some(type_name.to_string());
+                }
+            }
+            None => {
+                let defined = |name: &str| schema.types.contains_key(name).then(|| name.to_string());
+                (schema.query, schema.mutation, schema.subscription) = (defined("Query"), defined("Mutation"), defined("Subscription"));
+            }
+        }
+
+        for (type_name, definition) in &schema.types {
+            for (field, field_definition) in &definition.fields {
+                if !schema.types.contains_key(&field_definition.type_name) {
+                    return Err(KnishIOError::InvalidQuery(format!(
+                        "Field `{}.{}` has undefined type `{}`", type_name, field, field_definition.type_name
+                    )));
+                }
+            }
+        }
+
+        Ok(schema)
+    }
+
+    /// Name of the root type serving `operation_type`, None when the schema has none
+    pub fn root_type(&self, operation_type: OperationType) -> Option<&str> {
+        match operation_type {
+            OperationType::Query => self.query.as_deref(),
+            OperationType::Mutation => self.mutation.as_deref(),
+            OperationType::Subscription => self.subscription.as_deref(),
+        }
+    }
+
+    /// Whether `type_name` is defined and has a field `field`
+    pub fn has_field(&self, type_name: &str, field: &str) -> bool {
+        self.types.get(type_name).is_some_and(|definition| definition.fields.contains_key(field))
+    }
+
+    /// Check `document` against the schema
+    ///
+    /// The document is linted first. Every selected field must exist on its parent type,
+    /// take the arguments passed, and have a selection set exactly when its type is an
+    /// object, interface or union; variable types and type conditions must be defined.
+    /// Introspection fields (`__typename`, `__schema`, ...) are not checked.
+    ///
+    /// ```
+    /// use knishio_client::graphql::NodeSchema;
+    ///
+    /// let schema = NodeSchema::bundled().unwrap();
+    /// assert!(schema.validate("query { Token(slug: \"USER\") { slug, name } }").is_ok());
+    ///
+    /// let error = schema.validate("query { Token(slug: \"USER\") { slug, iconUrl } }").unwrap_err();
+    /// assert!(error.to_string().contains("Cannot query field `iconUrl` on type `Token`"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidQuery` naming the first problem and its line and column
+    pub fn validate(&self, document: &str) -> Result<()> {
+        lint_document(document)?;
+        let tokens = tokenize(document)?;
+        let mut cursor = Cursor { tokens: &tokens, index: 0, end: end_position(document) };
+
+        while let Some(kind) = cursor.peek() {
+            let position = cursor.position();
+            match kind {
+                TokenKind::Punct('{') => {
+                    let root = self.root(OperationType::Query, position)?;
+                    self.selection_set(&mut cursor, root)?;
+                }
+                TokenKind::Name("fragment") => {
+                    cursor.next();
+                    cursor.name("a fragment name")?;
+                    cursor.expect_name("on")?;
+                    let condition = self.type_condition(&mut cursor)?;
+                    cursor.directives()?;
+                    self.selection_set(&mut cursor, condition)?;
+                }
+                _ => {
+                    let operation_type = match cursor.name("an operation")? {
+                        "mutation" => OperationType::Mutation,
+                        "subscription" => OperationType::Subscription,
+                        _ => OperationType::Query,
+                    };
+                    if let Some(TokenKind::Name(_)) = cursor.peek() {
+                        cursor.next();
+                    }
+                    if cursor.eat('(') {
+                        while !cursor.eat(')') {
+                            cursor.expect('$')?;
+                            cursor.name("a variable name")?;
+                            cursor.expect(':')?;
+                            let at = cursor.position();
+                            let type_name = cursor.type_reference()?;
+                            match self.types.get(type_name) {
+                                None => return Err(invalid(at, format!("Unknown type `{}`", type_name))),
+                                Some(definition) if definition.kind.is_composite() => {
+                                    return Err(invalid(at, format!("Type `{}` cannot be used for a variable", type_name)));
+                                }
+                                Some(_) => {}
+                            }
+                            if cursor.eat('=') {
+                                cursor.skip_value()?;
+                            }
+                            cursor.directives()?;
+                        }
+                    }
+                    cursor.directives()?;
+                    let root = self.root(operation_type, position)?;
+                    self.selection_set(&mut cursor, root)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn root(&self, operation_type: OperationType, position: Position) -> Result<&str> {
+        self.root_type(operation_type)
+            .ok_or_else(|| invalid(position, format!("Schema has no {} type", operation_type)))
+    }
+
+    /// Type named by `... on Type` or `fragment F on Type`
+    fn type_condition<'a>(&self, cursor: &mut Cursor<'_, 'a>) -> Result<&'a str> {
+        let at = cursor.position();
+        let type_name = cursor.name("a type condition")?;
+        match self.types.get(type_name) {
+            Some(definition) if definition.kind.is_composite() => Ok(type_name),
+            Some(_) => Err(invalid(at, format!("Type `{}` has no fields to select", type_name))),
+            None => Err(invalid(at, format!("Unknown type `{}`", type_name))),
+        }
+    }
+
+    /// `{ ... }` selected on `type_name`
+    fn selection_set(&self, cursor: &mut Cursor<'_, '_>, type_name: &str) -> Result<()> {
+        let parent = self.types.get(type_name).ok_or_else(|| invalid(cursor.position(), format!("Unknown type `{}`", type_name)))?;
+        cursor.expect('{')?;
+
+        while !cursor.eat('}') {
+            if cursor.eat('.') {
+                match cursor.peek() {
+                    Some(TokenKind::Name("on")) => {
+                        cursor.next();
+                        let condition = self.type_condition(cursor)?;
+                        cursor.directives()?;
+                        self.selection_set(cursor, condition)?;
+                    }
+                    // Fragment spreads are checked where the fragment is defined
+                    Some(TokenKind::Name(_)) => {
+                        cursor.next();
+                        cursor.directives()?;
+                    }
+                    _ => {
+                        cursor.directives()?;
+                        self.selection_set(cursor, type_name)?;
+                    }
+                }
+                continue;
+            }
+
+            let position = cursor.position();
+            let mut field = cursor.name("a field")?;
+            if cursor.eat(':') {
+                field = cursor.name("a field after the alias")?;
+            }
+
+            if field.starts_with("__") {
+                cursor.skip_arguments()?;
+                cursor.directives()?;
+                if cursor.peek() == Some(TokenKind::Punct('{')) {
+                    cursor.skip_selection_set();
+                }
+                continue;
+            }
+
+            let definition = parent
+                .fields
+                .get(field)
+                .ok_or_else(|| invalid(position, format!("Cannot query field `{}` on type `{}`", field, type_name)))?;
+            if cursor.eat('(') {
+                while !cursor.eat(')') {
+                    let at = cursor.position();
+                    let argument = cursor.name("an argument name")?;
+                    if !definition.arguments.iter().any(|known| known == argument) {
+                        return Err(invalid(at, format!("Unknown argument `{}` on field `{}.{}`", argument, type_name, field)));
+                    }
+                    cursor.expect(':')?;
+                    cursor.skip_value()?;
+                }
+            }
+            cursor.directives()?;
+
+            let composite = self.types.get(&definition.type_name).is_some_and(|t| t.kind.is_composite());
+            match (composite, cursor.peek() == Some(TokenKind::Punct('{'))) {
+                (true, true) => self.selection_set(cursor, &definition.type_name)?,
+                (true, false) => {
+                    return Err(invalid(position, format!(
+                        "Field `{}.{}` of type `{}` must have a selection of subfields", type_name, field, definition.type_name
+                    )));
+                }
+                (false, true) => {
+                    return Err(invalid(position, format!(
+                        "Field `{}.{}` of type `{}` has no subfields", type_name, field, definition.type_name
+                    )));
+                }
+                (false, false) => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Position in a token stream, shared by the SDL and document walks
+struct Cursor<'t, 'a> {
+    tokens: &'t [Token<'a>],
+    index: usize,
+    end: Position,
+}
+
+impl<'t, 'a> Cursor<'t, 'a> {
+    fn peek(&self) -> Option<TokenKind<'a>> {
+        self.tokens.get(self.index).map(|t| t.kind)
+    }
+
+    fn position(&self) -> Position {
+        self.tokens.get(self.index).map_or(self.end, |t| t.position)
+    }
+
+    fn next(&mut self) -> Option<TokenKind<'a>> {
+        let kind = self.peek();
+        self.index += 1;
+        kind
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(TokenKind::Punct(c));
+        if found {
+            self.index += 1;
+        }
+        found
+    }
+
+    fn eat_name(&mut self, name: &str) -> bool {
+        let found = self.peek() == Some(TokenKind::Name(name));
+        if found {
+            self.index += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", c)))
+        }
+    }
+
+    fn expect_name(&mut self, name: &str) -> Result<()> {
+        if self.eat_name(name) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", name)))
+        }
+    }
+
+    fn name(&mut self, what: &str) -> Result<&'a str> {
+        match self.peek() {
+            Some(TokenKind::Name(name)) => {
+                self.index += 1;
+                Ok(name)
+            }
+            _ => Err(self.unexpected(what)),
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> KnishIOError {
+        let found = match self.peek() {
+            Some(TokenKind::Punct('.')) => "`...`".to_string(),
+            Some(TokenKind::Punct(c)) => format!("`{}`", c),
+            Some(TokenKind::Name(name)) => format!("`{}`", name),
+            Some(TokenKind::Literal) => "a literal".to_string(),
+            None => "the end of the document".to_string(),
+        };
+        invalid(self.position(), format!("Expected {}, found {}", expected, found))
+    }
+
+    /// Skip a description string, if any
+    fn description(&mut self) {
+        while self.peek() == Some(TokenKind::Literal) {
+            self.index += 1;
+        }
+    }
+
+    /// Named type of a type reference, list and non-null wrappers removed
+    fn type_reference(&mut self) -> Result<&'a str> {
+        let name = if self.eat('[') {
+            let name = self.type_reference()?;
+            self.expect(']')?;
+            name
+        } else {
+            self.name("a type")?
+        };
+        self.eat('!');
+        Ok(name)
+    }
+
+    fn skip_value(&mut self) -> Result<()> {
+        match self.next() {
+            Some(TokenKind::Punct('$')) => {
+                self.name("a variable name")?;
+            }
+            Some(TokenKind::Punct('[')) => {
+                while !self.eat(']') {
+                    self.skip_value()?;
+                }
+            }
+            Some(TokenKind::Punct('{')) => {
+                while !self.eat('}') {
+                    self.name("a field name")?;
+                    self.expect(':')?;
+                    self.skip_value()?;
+                }
+            }
+            Some(TokenKind::Name(_) | TokenKind::Literal) => {}
+            _ => {
+                self.index -= 1;
+                return Err(self.unexpected("a value"));
+            }
+        }
+        Ok(())
+    }
+
+    fn skip_arguments(&mut self) -> Result<()> {
+        if self.eat('(') {
+            while !self.eat(')') {
+                self.name("an argument name")?;
+                self.expect(':')?;
+                self.skip_value()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn directives(&mut self) -> Result<()> {
+        while self.eat('@') {
+            self.name("a directive name")?;
+            self.skip_arguments()?;
+        }
+        Ok(())
+    }
+
+    /// Skip a `{ ... }` the caller has checked is next; brackets are balanced already
+    fn skip_selection_set(&mut self) {
+        let mut depth = 0usize;
+        while let Some(kind) = self.next() {
+            match kind {
+                TokenKind::Punct('{') => depth += 1,
+                TokenKind::Punct('}') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// `name(arguments): Type` definitions up to the closing `}`
+    fn field_definitions(&mut self) -> Result<HashMap<String, FieldDefinition>> {
+        self.expect('{')?;
+        let mut fields = HashMap::new();
+        while !self.eat('}') {
+            self.description();
+            let name = self.name("a field name")?;
+            let mut arguments = Vec::new();
+            if self.eat('(') {
+                while !self.eat(')') {
+                    arguments.push(self.input_value()?.to_string());
+                }
+            }
+            self.expect(':')?;
+            let type_name = self.type_reference()?.to_string();
+            self.directives()?;
+            fields.insert(name.to_string(), FieldDefinition { arguments, type_name });
+        }
+        Ok(fields)
+    }
+
+    /// `name: Type = default` of an argument or input field, returning the name
+    fn input_value(&mut self) -> Result<&'a str> {
+        self.description();
+        let name = self.name("an argument name")?;
+        self.expect(':')?;
+        self.type_reference()?;
+        if self.eat('=') {
+            self.skip_value()?;
+        }
+        self.directives()?;
+        Ok(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::schema::{EXPECTED_MUTATION_FIELDS, EXPECTED_QUERY_FIELDS, EXPECTED_SUBSCRIPTION_FIELDS};
+    use std::fs;
+    use std::path::Path;
+
+    /// Documents in the raw string literals of `src/<module>/*.rs`, with their file
+    fn bundled_documents(module: &str) -> Vec<(String, String)> {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("src").join(module);
+        let mut files: Vec<_> = fs::read_dir(&directory).unwrap().map(|entry| entry.unwrap().path()).collect();
+        files.sort();
+
+        let mut documents = Vec::new();
+        for file in files {
+            let source = fs::read_to_string(&file).unwrap();
+            let mut rest = source.as_str();
+            while let Some(start) = rest.find("r#\"") {
+                rest = &rest[start + 3..];
+                let Some(end) = rest.find("\"#") else { break };
+                let literal = rest[..end].trim();
+                rest = &rest[end + 2..];
+                let keyword = literal.split(|c: char| !c.is_ascii_alphanumeric()).next().unwrap_or("");
+                if matches!(keyword, "query" | "mutation" | "subscription") {
+                    documents.push((file.display().to_string(), literal.to_string()));
+                }
+            }
+        }
+        documents
+    }
+
+    fn schema_error(schema: &NodeSchema, document: &str) -> String {
+        match schema.validate(document) {
+            Err(KnishIOError::InvalidQuery(message)) => message,
+            other => panic!("expected a schema error for {:?}, got {:?}", document, other),
+        }
+    }
+
+    /// Documents selecting something the node schema does not have, with the field at fault
+    ///
+    /// They fail against a real node; each stays listed until its document is fixed or removed.
+    const UNSUPPORTED_DOCUMENTS: &[(&str, &str)] = &[];
+
+    #[test]
+    fn test_bundled_documents_match_the_node_schema() {
+        let schema = NodeSchema::bundled().unwrap();
+        let mut failures = Vec::new();
+        let mut flagged = Vec::new();
+
+        for module in ["query", "mutation", "subscribe"] {
+            let documents = bundled_documents(module);
+            assert!(!documents.is_empty(), "no documents found in src/{}", module);
+            for (file, document) in documents {
+                let unsupported = UNSUPPORTED_DOCUMENTS.iter().find(|(path, _)| file.ends_with(path));
+                match (schema.validate(&document), unsupported) {
+                    (Ok(_), None) => {}
+                    (Err(KnishIOError::InvalidQuery(message)), Some((path, expected))) if message.ends_with(expected) => flagged.push(*path),
+                    (Ok(_), Some((path, _))) => failures.push(format!("{}: listed as unsupported but valid", path)),
+                    (Err(error), _) => failures.push(format!("{}: {}", file, error)),
+                }
+            }
+        }
+
+        assert!(failures.is_empty(), "documents out of step with node_schema.graphql:\n{}", failures.join("\n"));
+        assert_eq!(flagged, UNSUPPORTED_DOCUMENTS.iter().map(|(path, _)| *path).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_schema_drift_roots_are_vendored() {
+        let schema = NodeSchema::bundled().unwrap();
+        for (operation_type, fields) in [
+            (OperationType::Query, EXPECTED_QUERY_FIELDS),
+            (OperationType::Mutation, EXPECTED_MUTATION_FIELDS),
+            (OperationType::Subscription, EXPECTED_SUBSCRIPTION_FIELDS),
+        ] {
+            let root = schema.root_type(operation_type).unwrap();
+            for field in fields {
+                assert!(schema.has_field(root, field), "{}.{} missing from node_schema.graphql", root, field);
+            }
+        }
+    }
+
+    #[test]
+    fn test_schema_drift_roots_cover_the_bundled_documents() {
+        // The vendored schema cut down to the root fields `check_schema` expects
+        let mut schema = NodeSchema::bundled().unwrap();
+        for (operation_type, fields) in [
+            (OperationType::Query, EXPECTED_QUERY_FIELDS),
+            (OperationType::Mutation, EXPECTED_MUTATION_FIELDS),
+            (OperationType::Subscription, EXPECTED_SUBSCRIPTION_FIELDS),
+        ] {
+            let root = schema.root_type(operation_type).unwrap().to_string();
+            schema.types.get_mut(&root).unwrap().fields.retain(|field, _| fields.contains(&field.as_str()));
+        }
+
+        let mut unlisted = Vec::new();
+        for module in ["query", "mutation", "subscribe"] {
+            for (file, document) in bundled_documents(module) {
+                if let Err(error) = schema.validate(&document) {
+                    unlisted.push(format!("{}: {}", file, error));
+                }
+            }
+        }
+        assert!(unlisted.is_empty(), "root fields missing from the EXPECTED_*_FIELDS of client::schema:\n{}", unlisted.join("\n"));
+    }
+
+    #[test]
+    fn test_document_schema_errors() {
+        let schema = NodeSchema::bundled().unwrap();
+        assert_eq!(schema_error(&schema, "query { Token { slug, iconUrl } }"), "1:23: Cannot query field `iconUrl` on type `Token`");
+        assert_eq!(schema_error(&schema, "{ Tokens { slug } }"), "1:3: Cannot query field `Tokens` on type `Query`");
+        assert_eq!(
+            schema_error(&schema, "query {\n  Wallet(bundle: \"abc\") { address }\n}"),
+            "2:10: Unknown argument `bundle` on field `Query.Wallet`"
+        );
+        assert_eq!(schema_error(&schema, "query { Token { slug } Batch }"), "1:24: Field `Query.Batch` of type `Batch` must have a selection of subfields");
+        assert_eq!(schema_error(&schema, "query { Token { slug { id } } }"), "1:17: Field `Token.slug` of type `String` has no subfields");
+        assert_eq!(schema_error(&schema, "query ($t: Slug) { Token(slug: $t) { slug } }"), "1:12: Unknown type `Slug`");
+        assert_eq!(schema_error(&schema, "query { Token { ... on Token { address } } }"), "1:32: Cannot query field `address` on type `Token`");
+        assert_eq!(schema_error(&schema, "query { Token { ... on Coin { slug } } }"), "1:24: Unknown type `Coin`");
+        // Syntax errors come from the linter
+        assert_eq!(schema_error(&schema, "query { Token { slug }"), "1:7: Unclosed `{`");
+
+        assert!(schema.validate("query { first: Token { slug, __typename } __schema { types { name } } }").is_ok());
+    }
+
+    #[test]
+    fn test_parse_sdl() {
+        let schema = NodeSchema::parse(r#"
+            """Shapes"""
+            interface Shape { area: Float }
+            type Square implements Shape & Node @key(fields: "id") { id: ID!, area: Float, side(unit: Unit = METRE): Float }
+            type Circle implements Shape { area: Float }
+            union Figure = | Square | Circle
+            enum Unit { METRE "Imperial" FOOT }
+            input Filter { minArea: Float = 0, units: [Unit!] }
+            interface Node { id: ID! }
+            directive @key(fields: String!) repeatable on OBJECT | INTERFACE
+            type Query { figures(filter: Filter): [Figure!]! shapes: [Shape] }
+            extend type Query { square(id: ID!): Square }
+        "#).unwrap();
+
+        assert_eq!(schema.root_type(OperationType::Query), Some("Query"));
+        assert_eq!(schema.root_type(OperationType::Mutation), None);
+        assert!(schema.has_field("Query", "square"));
+        assert!(schema.validate("query ($f: Filter) { figures(filter: $f) { ... on Square { side(unit: FOOT) } __typename } shapes { area } }").is_ok());
+        assert_eq!(schema_error(&schema, "mutation { square(id: 1) { id } }"), "1:1: Schema has no mutation type");
+        assert_eq!(schema_error(&schema, "query { figures { area } }"), "1:19: Cannot query field `area` on type `Figure`");
+
+        let error = |sdl: &str| match NodeSchema::parse(sdl) {
+            Err(KnishIOError::InvalidQuery(message)) => message,
+            other => panic!("expected an SDL error for {:?}, got {:?}", sdl, other),
+        };
+        assert_eq!(error("type Query { a: Int } type Query { b: Int }"), "1:23: Type `Query` is defined twice");
+        assert_eq!(error("type Query { token: Tokn }"), "Field `Query.token` has undefined type `Tokn`");
+        assert_eq!(error("extend type Query { a: Int }"), "1:1: Cannot extend undefined type `Query`");
+        assert_eq!(error("schema { query: Root }"), "1:10: Root type `Root` is not defined");
+        assert_eq!(error("object Query { a: Int }"), "1:1: Unknown definition `object`");
+    }
+}
@@ -0,0 +1,105 @@
+//! SDK identification headers
+//!
+//! Every HTTP request names the calling SDK so nodes can tell clients apart: `User-Agent`
+//! in the usual product/version form and `X-KnishIO-SDK` as `key=value` pairs that are
+//! easy to parse server side. Both carry the crate version and the platform, and both can
+//! be replaced or dropped through `ClientConfig`.
+//!
+//! A `RequestInterceptor` sees the complete header set of each request, these included,
+//! and may change it before it is sent.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Header naming the SDK, its version and platform
+pub const SDK_HEADER: &str = "X-KnishIO-SDK";
+
+/// Callback receiving the node URI and headers of every HTTP request before it is sent
+pub type RequestInterceptor = Arc<dyn Fn(&str, &mut HashMap<String, String>) + Send + Sync>;
+
+/// Platform the SDK was built for, e.g. `linux-x86_64`
+pub fn platform() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// Default `User-Agent`, e.g. `KnishIO-Rust-SDK/0.9.2 (linux; x86_64)`
+pub fn default_user_agent() -> String {
+    format!(
+        "KnishIO-Rust-SDK/{} ({}; {})",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )
+}
+
+/// Default `X-KnishIO-SDK` value, e.g. `sdk=rust; version=0.9.2; platform=linux-x86_64`
+pub fn default_sdk_header() -> String {
+    format!("sdk=rust; version={}; platform={}", env!("CARGO_PKG_VERSION"), platform())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::{create_query_request, ClientConfig, GraphQLClient, RetryConfig};
+    use std::sync::Mutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve one request and return its header block, lower-cased
+    async fn capture_headers(config: ClientConfig, interceptor: Option<RequestInterceptor>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let uri = format!("http://{}/graphql", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 4096];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let read = stream.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+            }
+            let body = r#"{"data":{"ok":true}}"#;
+            let reply = format!("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+            stream.write_all(reply.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_lowercase()
+        });
+
+        let mut client = GraphQLClient::with_config(uri, config, RetryConfig::default());
+        client.set_request_interceptor(interceptor);
+        client.query(create_query_request("query { ok }", None)).await.unwrap();
+        server.await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_default_sdk_headers() {
+        let head = capture_headers(ClientConfig::default(), None).await;
+        assert!(head.contains(&format!("user-agent: {}", default_user_agent().to_lowercase())));
+        assert!(head.contains(&format!("x-knishio-sdk: {}", default_sdk_header().to_lowercase())));
+        assert!(head.contains(&platform().to_lowercase()));
+    }
+
+    #[tokio::test]
+    async fn test_overridden_headers_and_interceptor() {
+        let config = ClientConfig {
+            user_agent: "Wallet-App/2.1".to_string(),
+            sdk_header: None,
+            headers: HashMap::from([("X-Client-Version".to_string(), "2.1.0".to_string())]),
+            ..ClientConfig::default()
+        };
+        let seen = Arc::new(Mutex::new(HashMap::new()));
+        let record = seen.clone();
+        let interceptor: RequestInterceptor = Arc::new(move |_, headers| {
+            *record.lock().unwrap() = headers.clone();
+            headers.insert("X-Trace-Id".to_string(), "trace-1".to_string());
+        });
+
+        let head = capture_headers(config, Some(interceptor)).await;
+        assert!(head.contains("user-agent: wallet-app/2.1"));
+        assert!(!head.contains("x-knishio-sdk"));
+        assert!(head.contains("x-client-version: 2.1.0"));
+        assert!(head.contains("x-trace-id: trace-1"));
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.get("User-Agent").map(String::as_str), Some("Wallet-App/2.1"));
+        assert_eq!(seen.get("Content-Type").map(String::as_str), Some("application/json"));
+    }
+}
@@ -0,0 +1,357 @@
+//! Request recording and replay
+//!
+//! Enabled with the `vcr` feature. A `Cassette` attached to a `GraphQLClient` either
+//! records every exchange with the node into a fixture file, or serves the exchanges of an
+//! earlier recording back without touching the network. Application logic can then be
+//! tested offline and deterministically against what a real node once answered:
+//!
+//! ```no_run
+//! # #[cfg(feature = "vcr")]
+//! # {
+//! use knishio_client::graphql::{Cassette, GraphQLClient};
+//!
+//! // Once, against a live node
+//! let mut client = GraphQLClient::new("https://node.example/graphql");
+//! client.set_cassette(Some(Cassette::record("tests/fixtures/transfer.json")));
+//!
+//! // In every test run after that
+//! let mut client = GraphQLClient::new("https://node.example/graphql");
+//! client.set_cassette(Some(Cassette::replay("tests/fixtures/transfer.json").unwrap()));
+//! # }
+//! ```
+//!
+//! The fixture holds each request's query, variables and operation name with the decoded
+//! reply and its HTTP headers. Request headers, which carry the client's auth token, are
+//! not recorded. Replies do carry tokens, for instance from the `AccessToken` mutation or
+//! in the `payload` of an accepted authorization molecule, so every `token` in a reply is
+//! replaced with `[REDACTED]` before it is written, together with the `pubkey` issued
+//! beside it. Anything else that must stay out of a committed fixture can be scrubbed
+//! with `Cassette::redact`. Requests that fail before a reply is decoded (network errors,
+//! HTTP errors) are not recorded either.
+//!
+//! Molecules embed timestamps, positions and signatures, so a re-run rarely repeats the
+//! recorded variables exactly. By default (`ReplayMatch::Operation`) the n-th request for
+//! an operation gets the n-th recorded reply for it; `ReplayMatch::Exact` also requires
+//! the same variables. A request without a matching recording fails with
+//! `CassetteMismatch`.
+
+use crate::error::{KnishIOError, Result};
+use super::ResponseHeaders;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Version of the fixture file format
+const CASSETTE_VERSION: u32 = 1;
+
+/// Written in place of redacted values
+const REDACTED: &str = "[REDACTED]";
+
+/// Changes an exchange before it is written to the fixture
+pub type Redactor = Arc<dyn Fn(&mut Interaction) + Send + Sync>;
+
+/// How replayed requests are matched to recorded ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReplayMatch {
+    /// Same operation (operation name, or query text without one), in recorded order
+    #[default]
+    Operation,
+    /// Same operation and variables, in recorded order
+    Exact,
+}
+
+/// A recorded request and the node's reply
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Interaction {
+    /// Node the request went to
+    pub uri: String,
+    /// GraphQL document
+    pub query: Value,
+    /// Request variables
+    pub variables: Value,
+    /// Operation name, when the request named one
+    #[serde(default)]
+    pub operation_name: Option<String>,
+    /// Decoded reply body
+    pub response: Value,
+    /// HTTP headers of the reply
+    #[serde(default)]
+    pub headers: ResponseHeaders,
+}
+
+impl Interaction {
+    fn key(&self) -> Value {
+        self.operation_name.clone().map_or_else(|| self.query.clone(), Value::String)
+    }
+}
+
+/// Fixture file contents
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Tape {
+    version: u32,
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug)]
+enum Mode {
+    Record,
+    Replay { matching: ReplayMatch, played: Vec<bool> },
+}
+
+/// Records a client's exchanges to a fixture file, or replays them from one
+pub struct Cassette {
+    path: PathBuf,
+    mode: Mutex<Mode>,
+    interactions: Mutex<Vec<Interaction>>,
+    redactor: Option<Redactor>,
+}
+
+impl fmt::Debug for Cassette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cassette")
+            .field("path", &self.path)
+            .field("mode", &self.mode)
+            .field("interactions", &self.interactions)
+            .field("redactor", &self.redactor.is_some())
+            .finish()
+    }
+}
+
+impl Cassette {
+    /// Record into `path`, replacing any fixture there
+    ///
+    /// The file is rewritten after every exchange, so a test that fails halfway still
+    /// leaves the exchanges up to the failure.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Cassette {
+            path: path.into(),
+            mode: Mutex::new(Mode::Record),
+            interactions: Mutex::new(Vec::new()),
+            redactor: None,
+        }
+    }
+
+    /// Replay the fixture at `path`, matching requests by operation
+    ///
+    /// # Errors
+    ///
+    /// `Io` if the file cannot be read, `Serialization` if it is not a fixture of this
+    /// format version
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let text = std::fs::read_to_string(&path).map_err(|e| KnishIOError::Io(e.to_string()))?;
+        let tape: Tape = serde_json::from_str(&text).map_err(|e| KnishIOError::Serialization(e.to_string()))?;
+        if tape.version != CASSETTE_VERSION {
+            return Err(KnishIOError::Serialization(format!(
+                "cassette {} has format version {}, expected {}",
+                path.display(), tape.version, CASSETTE_VERSION
+            )));
+        }
+        Ok(Cassette {
+            path,
+            mode: Mutex::new(Mode::Replay { matching: ReplayMatch::default(), played: vec![false; tape.interactions.len()] }),
+            interactions: Mutex::new(tape.interactions),
+            redactor: None,
+        })
+    }
+
+    /// Run `redactor` on every exchange before it is written, after the built-in token
+    /// redaction (no effect while replaying)
+    pub fn redact(mut self, redactor: impl Fn(&mut Interaction) + Send + Sync + 'static) -> Self {
+        self.redactor = Some(Arc::new(redactor));
+        self
+    }
+
+    /// Match replayed requests by `matching` (no effect while recording)
+    pub fn matching(self, matching: ReplayMatch) -> Self {
+        if let Mode::Replay { matching: ref mut current, .. } = *self.mode.lock().unwrap_or_else(PoisonError::into_inner) {
+            *current = matching;
+        }
+        self
+    }
+
+    /// Fixture file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the cassette is replaying rather than recording
+    pub fn is_replaying(&self) -> bool {
+        matches!(*self.mode.lock().unwrap_or_else(PoisonError::into_inner), Mode::Replay { .. })
+    }
+
+    /// Exchanges recorded so far, or loaded for replay
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.interactions.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Recorded exchanges not replayed yet
+    pub fn unplayed(&self) -> usize {
+        match *self.mode.lock().unwrap_or_else(PoisonError::into_inner) {
+            Mode::Record => 0,
+            Mode::Replay { ref played, .. } => played.iter().filter(|played| !**played).count(),
+        }
+    }
+
+    /// Reply recorded for `payload`, while replaying
+    ///
+    /// Returns None while recording.
+    pub(crate) fn play(&self, payload: &Value) -> Option<Result<(Value, ResponseHeaders)>> {
+        let mut mode = self.mode.lock().unwrap_or_else(PoisonError::into_inner);
+        let Mode::Replay { matching, ref mut played } = *mode else {
+            return None;
+        };
+        let request = Interaction {
+            uri: String::new(),
+            query: payload["query"].clone(),
+            variables: payload["variables"].clone(),
+            operation_name: payload["operationName"].as_str().map(str::to_string),
+            response: Value::Null,
+            headers: ResponseHeaders::default(),
+        };
+
+        let interactions = self.interactions.lock().unwrap_or_else(PoisonError::into_inner);
+        let found = interactions.iter().enumerate().find(|(index, recorded)| {
+            !played[*index]
+                && recorded.key() == request.key()
+                && (matching == ReplayMatch::Operation || recorded.variables == request.variables)
+        });
+        Some(match found {
+            Some((index, recorded)) => {
+                played[index] = true;
+                Ok((recorded.response.clone(), recorded.headers.clone()))
+            }
+            None => Err(KnishIOError::CassetteMismatch(format!(
+                "no unplayed {} in {}",
+                request.operation_name.as_deref().unwrap_or("request"),
+                self.path.display()
+            ))),
+        })
+    }
+
+    /// Append an exchange and rewrite the fixture, while recording
+    pub(crate) fn store(&self, uri: &str, payload: &Value, response: &Value, headers: &ResponseHeaders) -> Result<()> {
+        if self.is_replaying() {
+            return Ok(());
+        }
+        let mut interaction = Interaction {
+            uri: uri.to_string(),
+            query: payload["query"].clone(),
+            variables: payload["variables"].clone(),
+            operation_name: payload["operationName"].as_str().map(str::to_string),
+            response: response.clone(),
+            headers: headers.clone(),
+        };
+        redact_tokens(&mut interaction.response);
+        if let Some(ref redactor) = self.redactor {
+            redactor(&mut interaction);
+        }
+
+        let mut interactions = self.interactions.lock().unwrap_or_else(PoisonError::into_inner);
+        interactions.push(interaction);
+
+        let tape = Tape { version: CASSETTE_VERSION, interactions: interactions.clone() };
+        let text = serde_json::to_string_pretty(&tape).map_err(|e| KnishIOError::Serialization(e.to_string()))?;
+        if let Some(parent) = self.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| KnishIOError::Io(e.to_string()))?;
+        }
+        std::fs::write(&self.path, text).map_err(|e| KnishIOError::Io(e.to_string()))
+    }
+}
+
+/// Replace every `token` in `value`, and the `pubkey` issued with it, with `[REDACTED]`
+///
+/// Strings holding a JSON object, like a molecule's `payload`, are redacted inside.
+fn redact_tokens(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            if fields.get("token").is_some_and(|token| !token.is_null()) {
+                fields.insert("token".to_string(), Value::String(REDACTED.to_string()));
+                if fields.get("pubkey").is_some_and(|pubkey| !pubkey.is_null()) {
+                    fields.insert("pubkey".to_string(), Value::String(REDACTED.to_string()));
+                }
+            }
+            fields.values_mut().for_each(redact_tokens);
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_tokens),
+        Value::String(text) if text.trim_start().starts_with('{') => {
+            if let Ok(mut embedded) = serde_json::from_str::<Value>(text) {
+                let before = embedded.clone();
+                redact_tokens(&mut embedded);
+                if embedded != before {
+                    *text = embedded.to_string();
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn payload(operation: &str, bundle: &str) -> Value {
+        json!({ "query": format!("query {} {{ x }}", operation), "variables": { "bundle": bundle }, "operationName": operation })
+    }
+
+    #[test]
+    fn test_record_then_replay() {
+        let path = std::env::temp_dir().join(format!("knishio-cassette-{}.json", uuid::Uuid::new_v4()));
+        let recorder = Cassette::record(&path);
+        let headers = ResponseHeaders::new([("X-Request-Id", "r1")]);
+        recorder.store("http://node", &payload("Balance", "b1"), &json!({ "data": { "n": 1 } }), &headers).unwrap();
+        recorder.store("http://node", &payload("Balance", "b2"), &json!({ "data": { "n": 2 } }), &headers).unwrap();
+        recorder.store("http://node", &payload("Wallet", "b1"), &json!({ "data": { "n": 3 } }), &headers).unwrap();
+        assert!(recorder.play(&payload("Balance", "b1")).is_none());
+
+        // By operation, in order, whatever the variables
+        let player = Cassette::replay(&path).unwrap();
+        let (reply, replayed_headers) = player.play(&payload("Balance", "other")).unwrap().unwrap();
+        assert_eq!((reply, replayed_headers.request_id()), (json!({ "data": { "n": 1 } }), Some("r1")));
+        assert_eq!(player.play(&payload("Wallet", "b1")).unwrap().unwrap().0["data"]["n"], 3);
+        assert_eq!(player.play(&payload("Balance", "b1")).unwrap().unwrap().0["data"]["n"], 2);
+        assert_eq!(player.unplayed(), 0);
+        let miss = player.play(&payload("Balance", "b1")).unwrap().unwrap_err();
+        assert!(matches!(miss, KnishIOError::CassetteMismatch(_)), "{}", miss);
+
+        // Exact matching also compares variables
+        let exact = Cassette::replay(&path).unwrap().matching(ReplayMatch::Exact);
+        assert_eq!(exact.play(&payload("Balance", "b2")).unwrap().unwrap().0["data"]["n"], 2);
+        assert!(exact.play(&payload("Balance", "b3")).unwrap().is_err());
+
+        std::fs::write(&path, r#"{ "version": 99, "interactions": [] }"#).unwrap();
+        assert!(Cassette::replay(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_tokens_are_redacted() {
+        let path = std::env::temp_dir().join(format!("knishio-cassette-{}.json", uuid::Uuid::new_v4()));
+        let recorder = Cassette::record(&path).redact(|interaction| {
+            interaction.variables["bundle"] = Value::String("bundle".to_string());
+        });
+        let headers = ResponseHeaders::default();
+        let access = json!({ "data": { "AccessToken": { "token": "bearer-1", "pubkey": "node-key", "expiresAt": 99 } } });
+        recorder.store("http://node", &payload("AccessToken", "b1"), &access, &headers).unwrap();
+        let payload_text = json!({ "token": "bearer-2", "pubkey": "node-key", "time": 1 }).to_string();
+        let proposed = json!({ "data": { "ProposeMolecule": { "status": "accepted", "payload": payload_text } } });
+        recorder.store("http://node", &payload("ProposeMolecule", "b1"), &proposed, &headers).unwrap();
+        let wallet = json!({ "data": { "Wallet": [{ "pubkey": "wallet-key" }] } });
+        recorder.store("http://node", &payload("Wallet", "b1"), &wallet, &headers).unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(!written.contains("bearer-") && !written.contains("node-key"), "{}", written);
+        assert!(!written.contains("b1"));
+        let tape = Cassette::replay(&path).unwrap().interactions();
+        assert_eq!(tape[0].response["data"]["AccessToken"]["expiresAt"], 99);
+        let embedded: Value = serde_json::from_str(tape[1].response["data"]["ProposeMolecule"]["payload"].as_str().unwrap()).unwrap();
+        assert_eq!((embedded["token"].as_str(), embedded["time"].as_i64()), (Some(REDACTED), Some(1)));
+        assert_eq!(tape[2].response, wallet);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
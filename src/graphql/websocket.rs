@@ -2,17 +2,27 @@
 //!
 //! This module provides advanced WebSocket functionality for GraphQL subscriptions,
 //! including connection pooling, auto-reconnection, and subscription lifecycle management.
+//!
+//! Nodes speak one of two sub-protocols: `graphql-transport-ws` (the `graphql-ws` library)
+//! or the legacy `graphql-ws` of `subscriptions-transport-ws`. `SubscriptionProtocol::Auto`
+//! offers both in `Sec-WebSocket-Protocol` and frames messages in the one the node picks;
+//! a node that picks none gets the legacy framing.
 
+use super::backpressure::{event_channel, ConsumerConfig, ConsumerStats, EventReceiver, EventSender, SendFailure};
 use crate::error::{KnishIOError, Result};
 use futures_util::{SinkExt, StreamExt};
 use serde_json::{json, Value};
 use std::collections::HashMap;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, RwLock};
-use tokio::time::{interval, sleep, timeout};
+use tokio::time::{interval_at, sleep, timeout};
 use tokio_tungstenite::{connect_async, tungstenite::Message, WebSocketStream, MaybeTlsStream};
 use tracing::{debug, error, info, warn};
+use tungstenite::client::IntoClientRequest;
+use tungstenite::error::{Error as WsError, ProtocolError, SubProtocolError};
+use tungstenite::http::HeaderValue;
 use tungstenite::Utf8Bytes;
 use uuid::Uuid;
 
@@ -26,19 +36,136 @@ pub enum ConnectionState {
     Failed,
 }
 
+/// GraphQL-over-WebSocket sub-protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SubscriptionProtocol {
+    /// Offer both and use the node's pick, the legacy protocol when it picks none
+    #[default]
+    Auto,
+    /// `graphql-transport-ws`, spoken by the `graphql-ws` library
+    GraphqlWs,
+    /// `graphql-ws`, the legacy protocol of `subscriptions-transport-ws`
+    SubscriptionsTransportWs,
+}
+
+impl SubscriptionProtocol {
+    /// `Sec-WebSocket-Protocol` name (None for `Auto`)
+    pub fn subprotocol(&self) -> Option<&'static str> {
+        match self {
+            SubscriptionProtocol::Auto => None,
+            SubscriptionProtocol::GraphqlWs => Some("graphql-transport-ws"),
+            SubscriptionProtocol::SubscriptionsTransportWs => Some("graphql-ws"),
+        }
+    }
+
+    /// Protocol of a `Sec-WebSocket-Protocol` name
+    pub fn from_subprotocol(name: &str) -> Option<Self> {
+        match name.trim() {
+            "graphql-transport-ws" => Some(SubscriptionProtocol::GraphqlWs),
+            "graphql-ws" => Some(SubscriptionProtocol::SubscriptionsTransportWs),
+            _ => None,
+        }
+    }
+
+    /// `Sec-WebSocket-Protocol` header value offered in the handshake
+    fn offer(&self) -> &'static str {
+        self.subprotocol().unwrap_or("graphql-transport-ws, graphql-ws")
+    }
+
+    /// Framing used when the node does not pick a sub-protocol
+    fn fallback(&self) -> Self {
+        match self {
+            SubscriptionProtocol::Auto => SubscriptionProtocol::SubscriptionsTransportWs,
+            protocol => *protocol,
+        }
+    }
+}
+
 /// WebSocket subscription manager for handling multiple GraphQL subscriptions
+///
+/// Clones share one connection: whichever clone starts it, every clone sends its
+/// commands over it.
 #[derive(Clone)]
 pub struct WebSocketManager {
     socket_uri: String,
-    auth_token: Option<String>,
+    /// Token sent in `connection_init`; read again on every (re)connect
+    auth_token: Arc<Mutex<Option<String>>>,
     app_key: String,
     state: Arc<RwLock<ConnectionState>>,
     subscriptions: Arc<RwLock<HashMap<String, SubscriptionInfo>>>,
-    connection_sender: Option<mpsc::UnboundedSender<WebSocketCommand>>,
+    connection_sender: Arc<Mutex<Option<mpsc::UnboundedSender<WebSocketCommand>>>>,
     reconnect_config: ReconnectConfig,
+    /// Sub-protocol offered in the handshake
+    protocol: SubscriptionProtocol,
+    counters: Arc<ConnectionCounters>,
+    /// Drops incoming data frames to exercise consumers' resync handling
+    #[cfg(feature = "fault-injection")]
+    fault_injector: Option<Arc<crate::graphql::FaultInjector>>,
     debug: bool,
 }
 
+/// Snapshot of one WebSocket connection, for health dashboards
+///
+/// Complements the HTTP `PoolStats`. Counters cover the manager's whole lifetime;
+/// `uptime` is that of the current connection.
+#[derive(Debug, Clone)]
+pub struct WebSocketStats {
+    /// Socket URI
+    pub uri: String,
+    /// Current connection state
+    pub state: ConnectionState,
+    /// Time since the current connection was acknowledged (None while not connected)
+    pub uptime: Option<Duration>,
+    /// Protocol messages received, keep-alives included
+    pub messages_in: u64,
+    /// Protocol messages sent, keep-alives included
+    pub messages_out: u64,
+    /// Connections established after the first one
+    pub reconnect_count: u64,
+    /// IDs of the subscriptions carried by the connection
+    pub subscription_ids: Vec<String>,
+    /// Most recent connection error
+    pub last_error: Option<String>,
+    /// Consumer statistics of the subscriptions on bounded channels, by ID
+    pub consumers: HashMap<String, ConsumerStats>,
+    /// Sub-protocol of the current or last connection (None before the first)
+    pub protocol: Option<SubscriptionProtocol>,
+}
+
+/// Counters shared between a manager and its connection task
+#[derive(Debug, Default)]
+struct ConnectionCounters {
+    messages_in: AtomicU64,
+    messages_out: AtomicU64,
+    connections: AtomicU64,
+    connected_at: Mutex<Option<Instant>>,
+    last_error: Mutex<Option<String>>,
+    protocol: Mutex<Option<SubscriptionProtocol>>,
+}
+
+impl ConnectionCounters {
+    fn set_connected(&self, connected: bool) {
+        if let Ok(mut connected_at) = self.connected_at.lock() {
+            *connected_at = connected.then(Instant::now);
+        }
+        if connected {
+            self.connections.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn set_protocol(&self, protocol: SubscriptionProtocol) {
+        if let Ok(mut current) = self.protocol.lock() {
+            *current = Some(protocol);
+        }
+    }
+
+    fn set_error(&self, error: &KnishIOError) {
+        if let Ok(mut last_error) = self.last_error.lock() {
+            *last_error = Some(error.to_string());
+        }
+    }
+}
+
 /// Configuration for WebSocket reconnection behavior
 #[derive(Debug, Clone)]
 pub struct ReconnectConfig {
@@ -57,7 +184,43 @@ struct SubscriptionInfo {
     query: String,
     variables: Option<Value>,
     operation_name: Option<String>,
-    callback_sender: mpsc::UnboundedSender<crate::GraphQLResponse>,
+    callback_sender: SubscriptionSink,
+    /// Set while a variables update is in flight so the server's `complete` for the
+    /// stopped operation does not drop the restarted one
+    restarting: bool,
+}
+
+/// Where a subscription's responses go
+#[derive(Debug, Clone)]
+enum SubscriptionSink {
+    Unbounded(mpsc::UnboundedSender<crate::GraphQLResponse>),
+    Bounded(EventSender),
+}
+
+impl SubscriptionSink {
+    /// Hand `response` to the consumer; under `OverflowPolicy::Block` this waits for room
+    async fn deliver(&self, response: crate::GraphQLResponse) -> std::result::Result<(), SendFailure> {
+        match self {
+            SubscriptionSink::Unbounded(sender) => sender.send(response).map_err(|_| SendFailure::Closed),
+            SubscriptionSink::Bounded(sender) => sender.send(response).await,
+        }
+    }
+
+    /// Hand over a final `response` without waiting
+    fn close_with(&self, response: crate::GraphQLResponse) {
+        match self {
+            SubscriptionSink::Unbounded(sender) => {
+                let _ = sender.send(response);
+            }
+            SubscriptionSink::Bounded(sender) => sender.close_with(response),
+        }
+    }
+}
+
+impl From<mpsc::UnboundedSender<crate::GraphQLResponse>> for SubscriptionSink {
+    fn from(sender: mpsc::UnboundedSender<crate::GraphQLResponse>) -> Self {
+        SubscriptionSink::Unbounded(sender)
+    }
 }
 
 /// Commands for controlling the WebSocket connection
@@ -68,13 +231,19 @@ enum WebSocketCommand {
         query: String,
         variables: Option<Value>,
         operation_name: Option<String>,
-        callback_sender: mpsc::UnboundedSender<crate::GraphQLResponse>,
+        callback_sender: SubscriptionSink,
     },
     Unsubscribe {
         id: String,
     },
+    UpdateVariables {
+        id: String,
+        variables: Option<Value>,
+    },
     Disconnect,
     Reconnect,
+    /// The auth token changed; open a new connection that inits with it
+    Reauthenticate,
 }
 
 /// WebSocket message types following GraphQL WebSocket protocol
@@ -89,6 +258,9 @@ enum GraphQLWsMessage {
     Stop { id: String },
     ConnectionTerminate,
     KeepAlive,
+    /// `graphql-transport-ws` ping, answered with a pong
+    Ping,
+    Pong,
 }
 
 impl Default for ReconnectConfig {
@@ -115,24 +287,68 @@ impl WebSocketManager {
     ) -> Self {
         WebSocketManager {
             socket_uri,
-            auth_token,
+            auth_token: Arc::new(Mutex::new(auth_token)),
             app_key,
             state: Arc::new(RwLock::new(ConnectionState::Disconnected)),
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
-            connection_sender: None,
+            connection_sender: Arc::new(Mutex::new(None)),
             reconnect_config,
+            protocol: SubscriptionProtocol::Auto,
+            counters: Arc::new(ConnectionCounters::default()),
+            #[cfg(feature = "fault-injection")]
+            fault_injector: None,
             debug,
         }
     }
+
+    /// Create a manager from a client's `SocketConfig`
+    pub fn from_config(config: &crate::graphql::SocketConfig, auth_token: Option<String>, debug: bool) -> Self {
+        let defaults = ReconnectConfig::default();
+        let reconnect_config = ReconnectConfig {
+            max_attempts: config.max_reconnect_attempts.unwrap_or(defaults.max_attempts),
+            initial_delay: config.reconnect_delay.unwrap_or(defaults.initial_delay),
+            connection_timeout: config.connect_timeout.unwrap_or(defaults.connection_timeout),
+            keep_alive_interval: config.keep_alive_interval.unwrap_or(defaults.keep_alive_interval),
+            ..defaults
+        };
+        WebSocketManager::new(config.socket_uri.clone(), auth_token, config.app_key.clone(), reconnect_config, debug)
+            .with_protocol(config.protocol)
+    }
+
+    /// Offer `protocol` in the handshake (defaults to `SubscriptionProtocol::Auto`)
+    ///
+    /// Takes effect on the next connection.
+    pub fn with_protocol(mut self, protocol: SubscriptionProtocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Sub-protocol offered in the handshake
+    pub fn protocol(&self) -> SubscriptionProtocol {
+        self.protocol
+    }
+
+    /// Drop incoming subscription data frames as `injector` decides
+    ///
+    /// Takes effect on the next connection. Dropped frames are counted in the injector's stats.
+    #[cfg(feature = "fault-injection")]
+    pub fn with_fault_injector(mut self, injector: Option<Arc<crate::graphql::FaultInjector>>) -> Self {
+        self.fault_injector = injector;
+        self
+    }
     
     /// Start the WebSocket connection manager
     pub async fn start(&mut self) -> Result<()> {
-        if self.connection_sender.is_some() {
-            return Ok(()); // Already started
-        }
-        
-        let (command_sender, command_receiver) = mpsc::unbounded_channel();
-        self.connection_sender = Some(command_sender);
+        let command_receiver = {
+            let mut connection_sender = self.connection_sender.lock()
+                .map_err(|_| KnishIOError::WebSocketError("Connection lock poisoned".into()))?;
+            if connection_sender.is_some() {
+                return Ok(()); // Already started
+            }
+            let (command_sender, command_receiver) = mpsc::unbounded_channel();
+            *connection_sender = Some(command_sender);
+            command_receiver
+        };
         
         let socket_uri = self.socket_uri.clone();
         let auth_token = self.auth_token.clone();
@@ -140,6 +356,10 @@ impl WebSocketManager {
         let state = self.state.clone();
         let subscriptions = self.subscriptions.clone();
         let reconnect_config = self.reconnect_config.clone();
+        let protocol = self.protocol;
+        let counters = self.counters.clone();
+        #[cfg(feature = "fault-injection")]
+        let fault_injector = self.fault_injector.clone();
         let debug = self.debug;
         
         tokio::spawn(async move {
@@ -151,6 +371,10 @@ impl WebSocketManager {
                 subscriptions,
                 command_receiver,
                 reconnect_config,
+                protocol,
+                counters,
+                #[cfg(feature = "fault-injection")]
+                fault_injector,
                 debug,
             ).await;
         });
@@ -165,14 +389,54 @@ impl WebSocketManager {
         variables: Option<Value>,
         operation_name: Option<String>,
     ) -> Result<mpsc::UnboundedReceiver<crate::GraphQLResponse>> {
+        let (_, receiver) = self.subscribe_with_id(query, variables, operation_name).await?;
+        Ok(receiver)
+    }
+
+    /// Subscribe and also return the subscription ID (for `unsubscribe` / `update_variables`)
+    pub async fn subscribe_with_id(
+        &mut self,
+        query: String,
+        variables: Option<Value>,
+        operation_name: Option<String>,
+    ) -> Result<(String, mpsc::UnboundedReceiver<crate::GraphQLResponse>)> {
+        let (callback_sender, callback_receiver) = mpsc::unbounded_channel();
+        let id = self.subscribe_sink(query, variables, operation_name, callback_sender.into()).await?;
+        Ok((id, callback_receiver))
+    }
+
+    /// Subscribe with a bounded channel, returning the subscription ID and its receiver
+    ///
+    /// At most `config.capacity` responses wait for the consumer; `config.overflow` decides
+    /// what a full channel does. The receiver's `stats` (and `consumer_stats` here) report
+    /// how far behind the consumer is.
+    pub async fn subscribe_bounded(
+        &mut self,
+        query: String,
+        variables: Option<Value>,
+        operation_name: Option<String>,
+        config: ConsumerConfig,
+    ) -> Result<(String, EventReceiver)> {
+        let (callback_sender, callback_receiver) = event_channel(config);
+        let id = self.subscribe_sink(query, variables, operation_name, SubscriptionSink::Bounded(callback_sender)).await?;
+        Ok((id, callback_receiver))
+    }
+
+    /// Start the connection and register a subscription delivering to `callback_sender`
+    async fn subscribe_sink(
+        &mut self,
+        query: String,
+        variables: Option<Value>,
+        operation_name: Option<String>,
+        callback_sender: SubscriptionSink,
+    ) -> Result<String> {
         self.start().await?;
         
         let id = Uuid::new_v4().to_string();
-        let (callback_sender, callback_receiver) = mpsc::unbounded_channel();
         
-        if let Some(ref sender) = self.connection_sender {
+        if let Some(sender) = self.command_sender() {
             sender.send(WebSocketCommand::Subscribe {
-                id,
+                id: id.clone(),
                 query,
                 variables,
                 operation_name,
@@ -180,12 +444,89 @@ impl WebSocketManager {
             }).map_err(|_| KnishIOError::WebSocketError("Failed to send subscribe command".into()))?;
         }
         
-        Ok(callback_receiver)
+        Ok(id)
+    }
+
+    /// Subscribe and return a `SubscriptionHandle` whose `update_variables` and
+    /// `unsubscribe` are wired to this connection
+    pub async fn subscribe_handle(
+        &mut self,
+        query: String,
+        variables: Option<Value>,
+        operation_name: Option<String>,
+    ) -> Result<(crate::subscribe::SubscriptionHandle, mpsc::UnboundedReceiver<crate::GraphQLResponse>)> {
+        let (id, receiver) = self.subscribe_with_id(query, variables, operation_name).await?;
+        let sender = self.command_sender()
+            .ok_or_else(|| KnishIOError::WebSocketError("WebSocket manager not started".into()))?;
+
+        let unsubscribe_fn = {
+            let sender = sender.clone();
+            let id = id.clone();
+            Box::new(move || {
+                let _ = sender.send(WebSocketCommand::Unsubscribe { id: id.clone() });
+            }) as Box<dyn Fn() + Send + Sync>
+        };
+
+        let update_fn = {
+            let id = id.clone();
+            Box::new(move |variables: Value| {
+                sender.send(WebSocketCommand::UpdateVariables { id: id.clone(), variables: Some(variables) })
+                    .map_err(|_| KnishIOError::WebSocketError("Failed to send update command".into()))
+            }) as crate::subscribe::simple_websocket::UpdateVariablesFn
+        };
+
+        let handle = crate::subscribe::SubscriptionHandle::new(id, unsubscribe_fn).with_update_fn(update_fn);
+        Ok((handle, receiver))
+    }
+
+    /// Replace a live subscription's variables, keeping its receiver
+    ///
+    /// Sends `stop` followed by `start` with the same ID on the open connection; if the
+    /// connection is down, the new variables are used when it resubscribes.
+    pub async fn update_variables(&self, subscription_id: &str, variables: Option<Value>) -> Result<()> {
+        if let Some(sender) = self.command_sender() {
+            sender.send(WebSocketCommand::UpdateVariables {
+                id: subscription_id.to_string(),
+                variables,
+            }).map_err(|_| KnishIOError::WebSocketError("Failed to send update command".into()))?;
+        }
+        Ok(())
+    }
+
+    /// Get the auth token used for `connection_init`
+    pub fn get_auth_token(&self) -> Option<String> {
+        self.auth_token.lock().ok().and_then(|token| token.clone())
+    }
+
+    /// Replace the auth token used for `connection_init`
+    ///
+    /// The node only reads the token when a connection is initialised, so a live
+    /// connection is closed and re-established with the new token. Subscriptions are
+    /// restarted under their existing IDs and keep delivering to the same receivers.
+    ///
+    /// # Returns
+    ///
+    /// Whether the token actually changed
+    pub fn set_auth_token(&self, token: Option<String>) -> Result<bool> {
+        {
+            let mut current = self.auth_token.lock()
+                .map_err(|_| KnishIOError::WebSocketError("Auth token lock poisoned".into()))?;
+            if *current == token {
+                return Ok(false);
+            }
+            *current = token;
+        }
+
+        if let Some(sender) = self.command_sender() {
+            sender.send(WebSocketCommand::Reauthenticate)
+                .map_err(|_| KnishIOError::WebSocketError("Failed to send reauthenticate command".into()))?;
+        }
+        Ok(true)
     }
     
     /// Unsubscribe from a specific subscription
     pub async fn unsubscribe(&self, subscription_id: &str) -> Result<()> {
-        if let Some(ref sender) = self.connection_sender {
+        if let Some(sender) = self.command_sender() {
             sender.send(WebSocketCommand::Unsubscribe {
                 id: subscription_id.to_string(),
             }).map_err(|_| KnishIOError::WebSocketError("Failed to send unsubscribe command".into()))?;
@@ -195,10 +536,10 @@ impl WebSocketManager {
     
     /// Disconnect and cleanup all subscriptions
     pub async fn disconnect(&mut self) {
-        if let Some(ref sender) = self.connection_sender {
+        let sender = self.connection_sender.lock().ok().and_then(|mut sender| sender.take());
+        if let Some(sender) = sender {
             let _ = sender.send(WebSocketCommand::Disconnect);
         }
-        self.connection_sender = None;
     }
     
     /// Get current connection state
@@ -210,10 +551,56 @@ impl WebSocketManager {
     pub async fn subscription_count(&self) -> usize {
         self.subscriptions.read().await.len()
     }
+
+    /// Command channel of the running connection, None before `start`
+    fn command_sender(&self) -> Option<mpsc::UnboundedSender<WebSocketCommand>> {
+        self.connection_sender.lock().ok().and_then(|sender| sender.clone())
+    }
+
+    /// True if `other` is a clone of this manager (same connection)
+    pub fn shares_connection(&self, other: &WebSocketManager) -> bool {
+        Arc::ptr_eq(&self.counters, &other.counters)
+    }
+
+    /// Consumer statistics of subscription `subscription_id`, if it uses a bounded channel
+    pub async fn consumer_stats(&self, subscription_id: &str) -> Option<ConsumerStats> {
+        match &self.subscriptions.read().await.get(subscription_id)?.callback_sender {
+            SubscriptionSink::Bounded(sender) => Some(sender.stats()),
+            SubscriptionSink::Unbounded(_) => None,
+        }
+    }
+
+    /// Traffic, uptime and subscriptions of this connection
+    pub async fn stats(&self) -> WebSocketStats {
+        let subscriptions = self.subscriptions.read().await;
+        let mut subscription_ids: Vec<String> = subscriptions.keys().cloned().collect();
+        subscription_ids.sort();
+        let consumers = subscriptions.iter()
+            .filter_map(|(id, sub_info)| match &sub_info.callback_sender {
+                SubscriptionSink::Bounded(sender) => Some((id.clone(), sender.stats())),
+                SubscriptionSink::Unbounded(_) => None,
+            })
+            .collect();
+        drop(subscriptions);
+        let connections = self.counters.connections.load(Ordering::Relaxed);
+
+        WebSocketStats {
+            uri: self.socket_uri.clone(),
+            state: self.get_state().await,
+            uptime: self.counters.connected_at.lock().ok().and_then(|at| at.map(|at| at.elapsed())),
+            messages_in: self.counters.messages_in.load(Ordering::Relaxed),
+            messages_out: self.counters.messages_out.load(Ordering::Relaxed),
+            reconnect_count: connections.saturating_sub(1),
+            subscription_ids,
+            last_error: self.counters.last_error.lock().ok().and_then(|error| error.clone()),
+            consumers,
+            protocol: self.counters.protocol.lock().ok().and_then(|protocol| *protocol),
+        }
+    }
     
     /// Force reconnection
     pub async fn reconnect(&self) -> Result<()> {
-        if let Some(ref sender) = self.connection_sender {
+        if let Some(sender) = self.command_sender() {
             sender.send(WebSocketCommand::Reconnect)
                 .map_err(|_| KnishIOError::WebSocketError("Failed to send reconnect command".into()))?;
         }
@@ -221,14 +608,19 @@ impl WebSocketManager {
     }
     
     /// Main connection loop that handles WebSocket lifecycle
+    #[allow(clippy::too_many_arguments)]
     async fn connection_loop(
         socket_uri: String,
-        auth_token: Option<String>,
+        auth_token: Arc<Mutex<Option<String>>>,
         app_key: String,
         state: Arc<RwLock<ConnectionState>>,
         subscriptions: Arc<RwLock<HashMap<String, SubscriptionInfo>>>,
         mut command_receiver: mpsc::UnboundedReceiver<WebSocketCommand>,
         reconnect_config: ReconnectConfig,
+        protocol: SubscriptionProtocol,
+        counters: Arc<ConnectionCounters>,
+        #[cfg(feature = "fault-injection")]
+        fault_injector: Option<Arc<crate::graphql::FaultInjector>>,
         debug: bool,
     ) {
         // Connection loop variables
@@ -237,7 +629,7 @@ impl WebSocketManager {
         loop {
             *state.write().await = ConnectionState::Connecting;
             
-            match Self::establish_connection(
+            let result = Self::establish_connection(
                 &socket_uri,
                 &auth_token,
                 &app_key,
@@ -245,8 +637,15 @@ impl WebSocketManager {
                 &subscriptions,
                 &mut command_receiver,
                 &reconnect_config,
+                protocol,
+                &counters,
+                #[cfg(feature = "fault-injection")]
+                fault_injector.as_deref(),
                 debug,
-            ).await {
+            ).await;
+            counters.set_connected(false);
+
+            match result {
                 Ok(_) => {
                     reconnect_attempts = 0;
                     if debug {
@@ -255,6 +654,7 @@ impl WebSocketManager {
                 }
                 Err(err) => {
                     reconnect_attempts += 1;
+                    counters.set_error(&err);
                     *state.write().await = ConnectionState::Failed;
                     
                     if debug {
@@ -311,35 +711,70 @@ impl WebSocketManager {
                     extensions: None,
                 }]),
                 extensions: None,
+                headers: Default::default(),
             };
-            let _ = sub_info.callback_sender.send(error_response);
+            sub_info.callback_sender.close_with(error_response);
         }
     }
     
+    /// Open the socket offering `protocol`; returns the stream and the framing to use
+    ///
+    /// A node that does not negotiate sub-protocols fails a handshake that offers one, so
+    /// that case is retried without the offer.
+    async fn open_socket(
+        socket_uri: &str,
+        protocol: SubscriptionProtocol,
+        connection_timeout: Duration,
+    ) -> Result<(WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>, SubscriptionProtocol)> {
+        let mut request = socket_uri.into_client_request()
+            .map_err(|e| KnishIOError::WebSocketError(format!("Connection failed: {}", e)))?;
+        request.headers_mut().insert("Sec-WebSocket-Protocol", HeaderValue::from_static(protocol.offer()));
+
+        let (ws_stream, response) = match timeout(connection_timeout, connect_async(request)).await {
+            Err(_) => return Err(KnishIOError::WebSocketError("Connection timeout".into())),
+            Ok(Err(WsError::Protocol(ProtocolError::SecWebSocketSubProtocolError(SubProtocolError::NoSubProtocol)))) => {
+                timeout(connection_timeout, connect_async(socket_uri))
+                    .await
+                    .map_err(|_| KnishIOError::WebSocketError("Connection timeout".into()))?
+                    .map_err(|e| KnishIOError::WebSocketError(format!("Connection failed: {}", e)))?
+            }
+            Ok(connected) => connected.map_err(|e| KnishIOError::WebSocketError(format!("Connection failed: {}", e)))?,
+        };
+
+        let picked = response.headers()
+            .get("Sec-WebSocket-Protocol")
+            .and_then(|value| value.to_str().ok())
+            .and_then(SubscriptionProtocol::from_subprotocol);
+        Ok((ws_stream, picked.unwrap_or(protocol.fallback())))
+    }
+
     /// Establish and manage a single WebSocket connection
+    #[allow(clippy::too_many_arguments)]
     async fn establish_connection(
         socket_uri: &str,
-        auth_token: &Option<String>,
+        auth_token: &Mutex<Option<String>>,
         app_key: &str,
         state: &Arc<RwLock<ConnectionState>>,
         subscriptions: &Arc<RwLock<HashMap<String, SubscriptionInfo>>>,
         command_receiver: &mut mpsc::UnboundedReceiver<WebSocketCommand>,
         reconnect_config: &ReconnectConfig,
+        protocol: SubscriptionProtocol,
+        counters: &ConnectionCounters,
+        #[cfg(feature = "fault-injection")]
+        fault_injector: Option<&crate::graphql::FaultInjector>,
         debug: bool,
     ) -> Result<()> {
         // Connect to WebSocket
-        let ws_stream = timeout(
-            reconnect_config.connection_timeout,
-            connect_async(socket_uri)
-        )
-        .await
-        .map_err(|_| KnishIOError::WebSocketError("Connection timeout".into()))?
-        .map_err(|e| KnishIOError::WebSocketError(format!("Connection failed: {}", e)))?
-        .0;
+        let (ws_stream, protocol) = Self::open_socket(socket_uri, protocol, reconnect_config.connection_timeout).await?;
+        counters.set_protocol(protocol);
+        if debug {
+            debug!("WebSocket speaking {:?}", protocol);
+        }
         
         let (mut ws_sender, mut ws_receiver) = ws_stream.split();
         
-        // Send connection init
+        // Send connection init with the token current at connect time
+        let auth_token = auth_token.lock().ok().and_then(|token| token.clone());
         let init_msg = GraphQLWsMessage::ConnectionInit {
             payload: Some(json!({
                 "authToken": auth_token,
@@ -347,7 +782,7 @@ impl WebSocketManager {
             }))
         };
         
-        Self::send_ws_message(&mut ws_sender, &init_msg).await?;
+        Self::send_ws_message(&mut ws_sender, protocol, counters, &init_msg).await?;
         
         // Wait for connection_ack
         let ack_timeout = Duration::from_secs(10);
@@ -355,7 +790,8 @@ impl WebSocketManager {
         
         match ack_result {
             Ok(Some(Ok(Message::Text(text)))) => {
-                if let Ok(msg) = Self::parse_ws_message(&text) {
+                counters.messages_in.fetch_add(1, Ordering::Relaxed);
+                if let Ok(msg) = Self::parse_ws_message(protocol, &text) {
                     if !matches!(msg, GraphQLWsMessage::ConnectionAck) {
                         return Err(KnishIOError::WebSocketError("Expected connection_ack".into()));
                     }
@@ -367,6 +803,7 @@ impl WebSocketManager {
         }
         
         *state.write().await = ConnectionState::Connected;
+        counters.set_connected(true);
         
         if debug {
             info!("WebSocket connected successfully");
@@ -387,11 +824,12 @@ impl WebSocketManager {
                     "operationName": sub.operation_name
                 })
             };
-            Self::send_ws_message(&mut ws_sender, &start_msg).await?;
+            Self::send_ws_message(&mut ws_sender, protocol, counters, &start_msg).await?;
         }
         
-        // Set up keep-alive
-        let mut keep_alive_interval = interval(reconnect_config.keep_alive_interval);
+        // Set up keep-alive; the first one goes out a full period after connecting
+        let period = reconnect_config.keep_alive_interval;
+        let mut keep_alive_interval = interval_at(tokio::time::Instant::now() + period, period);
         
         // Main message loop
         loop {
@@ -400,13 +838,18 @@ impl WebSocketManager {
                 ws_msg = ws_receiver.next() => {
                     match ws_msg {
                         Some(Ok(Message::Text(text))) => {
-                            if let Err(e) = Self::handle_ws_message(
-                                &text,
-                                subscriptions,
-                                debug
-                            ).await {
-                                if debug {
-                                    warn!("Error handling WebSocket message: {}", e);
+                            counters.messages_in.fetch_add(1, Ordering::Relaxed);
+                            #[cfg(feature = "fault-injection")]
+                            if Self::drops_frame(protocol, &text, fault_injector) {
+                                continue;
+                            }
+                            match Self::handle_ws_message(protocol, &text, subscriptions, debug).await {
+                                Ok(Some(reply)) => Self::send_ws_message(&mut ws_sender, protocol, counters, &reply).await?,
+                                Ok(None) => {}
+                                Err(e) => {
+                                    if debug {
+                                        warn!("Error handling WebSocket message: {}", e);
+                                    }
                                 }
                             }
                         }
@@ -436,6 +879,7 @@ impl WebSocketManager {
                                 variables: variables.clone(),
                                 operation_name: operation_name.clone(),
                                 callback_sender,
+                                restarting: false,
                             };
                             
                             subscriptions.write().await.insert(id.clone(), sub_info);
@@ -449,7 +893,7 @@ impl WebSocketManager {
                                 })
                             };
                             
-                            if let Err(e) = Self::send_ws_message(&mut ws_sender, &start_msg).await {
+                            if let Err(e) = Self::send_ws_message(&mut ws_sender, protocol, counters, &start_msg).await {
                                 if debug {
                                     error!("Failed to send subscription start: {}", e);
                                 }
@@ -461,20 +905,52 @@ impl WebSocketManager {
                             subscriptions.write().await.remove(&id);
                             
                             let stop_msg = GraphQLWsMessage::Stop { id };
-                            if let Err(e) = Self::send_ws_message(&mut ws_sender, &stop_msg).await {
+                            if let Err(e) = Self::send_ws_message(&mut ws_sender, protocol, counters, &stop_msg).await {
                                 if debug {
                                     error!("Failed to send subscription stop: {}", e);
                                 }
                             }
                         }
                         
+                        Some(WebSocketCommand::UpdateVariables { id, variables }) => {
+                            let restart = {
+                                let mut subs = subscriptions.write().await;
+                                subs.get_mut(&id).map(|sub| {
+                                    sub.variables = variables;
+                                    // Only the legacy protocol confirms a stop with `complete`
+                                    sub.restarting = protocol == SubscriptionProtocol::SubscriptionsTransportWs;
+                                    GraphQLWsMessage::Start {
+                                        id: id.clone(),
+                                        payload: json!({
+                                            "query": sub.query,
+                                            "variables": sub.variables,
+                                            "operationName": sub.operation_name
+                                        })
+                                    }
+                                })
+                            };
+
+                            if let Some(start_msg) = restart {
+                                let stop_msg = GraphQLWsMessage::Stop { id };
+                                Self::send_ws_message(&mut ws_sender, protocol, counters, &stop_msg).await?;
+                                if let Err(e) = Self::send_ws_message(&mut ws_sender, protocol, counters, &start_msg).await {
+                                    if debug {
+                                        error!("Failed to restart subscription: {}", e);
+                                    }
+                                    return Err(e);
+                                }
+                            } else if debug {
+                                warn!("Variables update for unknown subscription {}", id);
+                            }
+                        }
+
                         Some(WebSocketCommand::Disconnect) => {
                             if debug {
                                 info!("Disconnect requested");
                             }
                             
                             let terminate_msg = GraphQLWsMessage::ConnectionTerminate;
-                            let _ = Self::send_ws_message(&mut ws_sender, &terminate_msg).await;
+                            let _ = Self::send_ws_message(&mut ws_sender, protocol, counters, &terminate_msg).await;
                             return Ok(());
                         }
                         
@@ -484,6 +960,18 @@ impl WebSocketManager {
                             }
                             return Err(KnishIOError::WebSocketError("Reconnect requested".into()));
                         }
+
+                        Some(WebSocketCommand::Reauthenticate) => {
+                            if debug {
+                                info!("Auth token rotated, re-initialising connection");
+                            }
+
+                            // Ending cleanly reconnects straight away (no backoff) and the
+                            // new connection restarts every subscription under its old ID
+                            let terminate_msg = GraphQLWsMessage::ConnectionTerminate;
+                            let _ = Self::send_ws_message(&mut ws_sender, protocol, counters, &terminate_msg).await;
+                            return Ok(());
+                        }
                         
                         None => {
                             if debug {
@@ -497,7 +985,7 @@ impl WebSocketManager {
                 // Send keep-alive messages
                 _ = keep_alive_interval.tick() => {
                     let ka_msg = GraphQLWsMessage::KeepAlive;
-                    if let Err(e) = Self::send_ws_message(&mut ws_sender, &ka_msg).await {
+                    if let Err(e) = Self::send_ws_message(&mut ws_sender, protocol, counters, &ka_msg).await {
                         if debug {
                             warn!("Failed to send keep-alive: {}", e);
                         }
@@ -511,49 +999,72 @@ impl WebSocketManager {
     /// Send a GraphQL WebSocket message
     async fn send_ws_message(
         sender: &mut futures_util::stream::SplitSink<WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>, Message>,
+        protocol: SubscriptionProtocol,
+        counters: &ConnectionCounters,
         message: &GraphQLWsMessage,
     ) -> Result<()> {
-        let text = Self::serialize_ws_message(message)?;
-        sender.send(Message::Text(Utf8Bytes::from(text)))
+        // graphql-transport-ws has no terminate message: the socket is closed instead
+        let frame = match (protocol, message) {
+            (SubscriptionProtocol::GraphqlWs, GraphQLWsMessage::ConnectionTerminate) => Message::Close(None),
+            _ => Message::Text(Utf8Bytes::from(Self::serialize_ws_message(protocol, message)?)),
+        };
+        counters.messages_out.fetch_add(1, Ordering::Relaxed);
+        sender.send(frame)
             .await
             .map_err(|e| KnishIOError::WebSocketError(format!("Failed to send message: {}", e)))
     }
     
-    /// Handle incoming WebSocket message
+    /// Handle incoming WebSocket message; returns the reply it calls for, if any
     async fn handle_ws_message(
+        protocol: SubscriptionProtocol,
         text: &str,
         subscriptions: &Arc<RwLock<HashMap<String, SubscriptionInfo>>>,
         debug: bool,
-    ) -> Result<()> {
-        let message = Self::parse_ws_message(text)?;
+    ) -> Result<Option<GraphQLWsMessage>> {
+        let message = Self::parse_ws_message(protocol, text)?;
         
         match message {
             GraphQLWsMessage::Data { id, payload } => {
-                let subs = subscriptions.read().await;
-                if let Some(sub_info) = subs.get(&id) {
+                // Deliver without holding the lock: a blocking channel may wait for its consumer
+                let sink = subscriptions.read().await.get(&id).map(|sub_info| sub_info.callback_sender.clone());
+                if let Some(sink) = sink {
                     if let Ok(response) = serde_json::from_value::<crate::GraphQLResponse>(payload) {
-                        if sub_info.callback_sender.send(response).is_err()
-                            && debug {
-                                warn!("Failed to send data to subscription {}: receiver dropped", id);
+                        match sink.deliver(response).await {
+                            Ok(()) => {}
+                            Err(SendFailure::Closed) => {
+                                if debug {
+                                    warn!("Failed to send data to subscription {}: receiver dropped", id);
+                                }
                             }
+                            Err(SendFailure::Overflow) => {
+                                warn!("Subscription {} ended: consumer fell behind", id);
+                                subscriptions.write().await.remove(&id);
+                                // Tell the node too, or it keeps streaming the operation
+                                return Ok(Some(GraphQLWsMessage::Stop { id }));
+                            }
+                        }
                     }
                 }
             }
             
             GraphQLWsMessage::Error { id, payload } => {
-                let subs = subscriptions.read().await;
-                if let Some(sub_info) = subs.get(&id) {
-                    let error_response = crate::GraphQLResponse {
-                        data: None,
-                        errors: Some(vec![crate::GraphQLError {
+                let sink = subscriptions.read().await.get(&id).map(|sub_info| sub_info.callback_sender.clone());
+                if let Some(sink) = sink {
+                    // graphql-transport-ws sends a list of GraphQL errors
+                    let errors = serde_json::from_value::<Vec<crate::GraphQLError>>(payload.clone())
+                        .unwrap_or_else(|_| vec![crate::GraphQLError {
                             message: payload.as_str().unwrap_or("Subscription error").to_string(),
                             locations: None,
                             path: None,
                             extensions: None,
-                        }]),
+                        }]);
+                    let error_response = crate::GraphQLResponse {
+                        data: None,
+                        errors: Some(errors),
                         extensions: None,
+                        headers: Default::default(),
                     };
-                    let _ = sub_info.callback_sender.send(error_response);
+                    let _ = sink.deliver(error_response).await;
                 }
             }
             
@@ -561,13 +1072,22 @@ impl WebSocketManager {
                 if debug {
                     info!("Subscription {} completed by server", id);
                 }
-                // Remove the subscription but don't send error
-                subscriptions.write().await.remove(&id);
+                // Remove the subscription but don't send error, unless this completes
+                // the operation stopped by a variables update
+                let mut subs = subscriptions.write().await;
+                match subs.get_mut(&id) {
+                    Some(sub) if sub.restarting => sub.restarting = false,
+                    _ => {
+                        subs.remove(&id);
+                    }
+                }
             }
             
-            GraphQLWsMessage::KeepAlive => {
+            GraphQLWsMessage::KeepAlive | GraphQLWsMessage::Pong => {
                 // Keep-alive received, no action needed
             }
+
+            GraphQLWsMessage::Ping => return Ok(Some(GraphQLWsMessage::Pong)),
             
             _ => {
                 if debug {
@@ -576,11 +1096,20 @@ impl WebSocketManager {
             }
         }
         
-        Ok(())
+        Ok(None)
     }
     
+    /// True if `text` is a data frame the fault injector chose to drop
+    #[cfg(feature = "fault-injection")]
+    fn drops_frame(protocol: SubscriptionProtocol, text: &str, fault_injector: Option<&crate::graphql::FaultInjector>) -> bool {
+        fault_injector.is_some_and(|injector| {
+            matches!(Self::parse_ws_message(protocol, text), Ok(GraphQLWsMessage::Data { .. }))
+                && injector.roll_frame().is_some()
+        })
+    }
+
     /// Parse a WebSocket message from text
-    fn parse_ws_message(text: &str) -> Result<GraphQLWsMessage> {
+    fn parse_ws_message(protocol: SubscriptionProtocol, text: &str) -> Result<GraphQLWsMessage> {
         let value: Value = serde_json::from_str(text)
             .map_err(|e| KnishIOError::WebSocketError(format!("Failed to parse message: {}", e)))?;
         
@@ -588,9 +1117,10 @@ impl WebSocketManager {
             .and_then(|t| t.as_str())
             .ok_or_else(|| KnishIOError::WebSocketError("Missing message type".into()))?;
         
+        let graphql_ws = protocol == SubscriptionProtocol::GraphqlWs;
         match msg_type {
             "connection_ack" => Ok(GraphQLWsMessage::ConnectionAck),
-            "data" => {
+            "data" | "next" if (msg_type == "next") == graphql_ws => {
                 let id = value.get("id")
                     .and_then(|i| i.as_str())
                     .ok_or_else(|| KnishIOError::WebSocketError("Missing subscription ID".into()))?;
@@ -614,32 +1144,38 @@ impl WebSocketManager {
                     .ok_or_else(|| KnishIOError::WebSocketError("Missing subscription ID".into()))?;
                 Ok(GraphQLWsMessage::Complete { id: id.to_string() })
             }
-            "ka" => Ok(GraphQLWsMessage::KeepAlive),
+            "ka" if !graphql_ws => Ok(GraphQLWsMessage::KeepAlive),
+            "ping" if graphql_ws => Ok(GraphQLWsMessage::Ping),
+            "pong" if graphql_ws => Ok(GraphQLWsMessage::Pong),
             _ => Err(KnishIOError::WebSocketError(format!("Unknown message type: {}", msg_type)))
         }
     }
     
     /// Serialize a WebSocket message to text
-    fn serialize_ws_message(message: &GraphQLWsMessage) -> Result<String> {
+    fn serialize_ws_message(protocol: SubscriptionProtocol, message: &GraphQLWsMessage) -> Result<String> {
+        let graphql_ws = protocol == SubscriptionProtocol::GraphqlWs;
         let value = match message {
             GraphQLWsMessage::ConnectionInit { payload } => json!({
                 "type": "connection_init",
                 "payload": payload
             }),
             GraphQLWsMessage::Start { id, payload } => json!({
-                "type": "start",
+                "type": if graphql_ws { "subscribe" } else { "start" },
                 "id": id,
                 "payload": payload
             }),
             GraphQLWsMessage::Stop { id } => json!({
-                "type": "stop",
+                "type": if graphql_ws { "complete" } else { "stop" },
                 "id": id
             }),
-            GraphQLWsMessage::ConnectionTerminate => json!({
+            GraphQLWsMessage::ConnectionTerminate if !graphql_ws => json!({
                 "type": "connection_terminate"
             }),
             GraphQLWsMessage::KeepAlive => json!({
-                "type": "ka"
+                "type": if graphql_ws { "ping" } else { "ka" }
+            }),
+            GraphQLWsMessage::Pong if graphql_ws => json!({
+                "type": "pong"
             }),
             _ => return Err(KnishIOError::WebSocketError("Cannot serialize this message type".into())),
         };
@@ -667,7 +1203,7 @@ mod tests {
             payload: Some(json!({"authToken": "test"})),
         };
         
-        let serialized = WebSocketManager::serialize_ws_message(&init_msg).unwrap();
+        let serialized = WebSocketManager::serialize_ws_message(SubscriptionProtocol::SubscriptionsTransportWs, &init_msg).unwrap();
         assert!(serialized.contains("connection_init"));
         assert!(serialized.contains("authToken"));
     }
@@ -675,17 +1211,38 @@ mod tests {
     #[test]
     fn test_ws_message_parsing() {
         let text = r#"{"type":"connection_ack"}"#;
-        let parsed = WebSocketManager::parse_ws_message(text).unwrap();
+        let parsed = WebSocketManager::parse_ws_message(SubscriptionProtocol::SubscriptionsTransportWs, text).unwrap();
         assert!(matches!(parsed, GraphQLWsMessage::ConnectionAck));
         
         let data_text = r#"{"type":"data","id":"sub1","payload":{"data":{}}}"#;
-        let parsed_data = WebSocketManager::parse_ws_message(data_text).unwrap();
+        let parsed_data = WebSocketManager::parse_ws_message(SubscriptionProtocol::SubscriptionsTransportWs, data_text).unwrap();
         if let GraphQLWsMessage::Data { id, .. } = parsed_data {
             assert_eq!(id, "sub1");
         } else {
             panic!("Expected Data message");
         }
     }
+
+    #[test]
+    fn test_graphql_transport_ws_framing() {
+        let protocol = SubscriptionProtocol::GraphqlWs;
+        let start = GraphQLWsMessage::Start { id: "sub1".to_string(), payload: json!({"query": "subscription { test }"}) };
+        let serialized: Value = serde_json::from_str(&WebSocketManager::serialize_ws_message(protocol, &start).unwrap()).unwrap();
+        assert_eq!(serialized["type"], "subscribe");
+        let stop = WebSocketManager::serialize_ws_message(protocol, &GraphQLWsMessage::Stop { id: "sub1".to_string() }).unwrap();
+        assert!(stop.contains(r#""type":"complete""#));
+
+        let next = WebSocketManager::parse_ws_message(protocol, r#"{"type":"next","id":"sub1","payload":{"data":{}}}"#).unwrap();
+        assert!(matches!(next, GraphQLWsMessage::Data { ref id, .. } if id == "sub1"));
+        assert!(matches!(WebSocketManager::parse_ws_message(protocol, r#"{"type":"ping"}"#).unwrap(), GraphQLWsMessage::Ping));
+
+        // Each framing rejects the other's message types
+        assert!(WebSocketManager::parse_ws_message(protocol, r#"{"type":"data","id":"sub1"}"#).is_err());
+        assert!(WebSocketManager::parse_ws_message(SubscriptionProtocol::SubscriptionsTransportWs, r#"{"type":"next","id":"sub1"}"#).is_err());
+
+        assert_eq!(SubscriptionProtocol::from_subprotocol("graphql-ws"), Some(SubscriptionProtocol::SubscriptionsTransportWs));
+        assert_eq!(SubscriptionProtocol::Auto.offer(), "graphql-transport-ws, graphql-ws");
+    }
     
     #[tokio::test]
     async fn test_websocket_manager_creation() {
@@ -700,4 +1257,363 @@ mod tests {
         assert_eq!(manager.get_state().await, ConnectionState::Disconnected);
         assert_eq!(manager.subscription_count().await, 0);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_complete_after_variables_update_keeps_subscription() {
+        let subscriptions = Arc::new(RwLock::new(HashMap::new()));
+        let (callback_sender, _receiver) = mpsc::unbounded_channel();
+        subscriptions.write().await.insert("sub1".to_string(), SubscriptionInfo {
+            id: "sub1".to_string(),
+            query: "subscription { test }".to_string(),
+            variables: Some(json!({"bundle": "a"})),
+            operation_name: None,
+            callback_sender: callback_sender.into(),
+            restarting: true,
+        });
+
+        let complete = r#"{"type":"complete","id":"sub1"}"#;
+
+        // The complete for the stopped operation is absorbed...
+        WebSocketManager::handle_ws_message(SubscriptionProtocol::SubscriptionsTransportWs, complete, &subscriptions, false).await.unwrap();
+        assert!(!subscriptions.read().await["sub1"].restarting);
+
+        // ...a later one ends the subscription as usual
+        WebSocketManager::handle_ws_message(SubscriptionProtocol::SubscriptionsTransportWs, complete, &subscriptions, false).await.unwrap();
+        assert!(subscriptions.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bounded_subscription_overflow_ends_subscription() {
+        let subscriptions = Arc::new(RwLock::new(HashMap::new()));
+        let config = ConsumerConfig::default().capacity(1).overflow(crate::graphql::OverflowPolicy::Error);
+        let (callback_sender, mut receiver) = event_channel(config);
+        subscriptions.write().await.insert("sub1".to_string(), SubscriptionInfo {
+            id: "sub1".to_string(),
+            query: "subscription { test }".to_string(),
+            variables: None,
+            operation_name: None,
+            callback_sender: SubscriptionSink::Bounded(callback_sender),
+            restarting: false,
+        });
+
+        let data = r#"{"type":"data","id":"sub1","payload":{"data":{"test":1}}}"#;
+        let reply = WebSocketManager::handle_ws_message(SubscriptionProtocol::SubscriptionsTransportWs, data, &subscriptions, false).await.unwrap();
+        assert!(reply.is_none());
+        assert_eq!(receiver.stats().queued, 1);
+        assert!(subscriptions.read().await.contains_key("sub1"));
+
+        // The consumer has not kept up: the subscription ends with an error and is stopped on the node
+        let reply = WebSocketManager::handle_ws_message(SubscriptionProtocol::SubscriptionsTransportWs, data, &subscriptions, false).await.unwrap();
+        assert!(matches!(reply, Some(GraphQLWsMessage::Stop { ref id }) if id == "sub1"));
+        assert!(subscriptions.read().await.is_empty());
+        assert!(receiver.stats().overflowed);
+        assert!(receiver.recv().await.unwrap().data.is_some());
+        assert!(receiver.recv().await.unwrap().errors.is_some());
+        assert!(receiver.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_token_rotation_reinitialises_connection() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (init_sender, mut init_receiver) = mpsc::unbounded_channel();
+
+        // Acks every connection, reports its authToken, and answers each start with one event
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let init_sender = init_sender.clone();
+                tokio::spawn(async move {
+                    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    while let Some(Ok(Message::Text(text))) = ws.next().await {
+                        let msg: Value = serde_json::from_str(&text).unwrap();
+                        let reply = match msg["type"].as_str() {
+                            Some("connection_init") => {
+                                let _ = init_sender.send(msg["payload"]["authToken"].clone());
+                                json!({"type": "connection_ack"})
+                            }
+                            Some("start") => json!({
+                                "type": "data",
+                                "id": msg["id"],
+                                "payload": {"data": {"token": msg["payload"]["variables"]["n"]}}
+                            }),
+                            _ => continue,
+                        };
+                        let _ = ws.send(Message::Text(Utf8Bytes::from(reply.to_string()))).await;
+                    }
+                });
+            }
+        });
+
+        let mut manager = WebSocketManager::new(
+            format!("ws://{}", addr),
+            Some("old-token".to_string()),
+            "knishio".to_string(),
+            ReconnectConfig::default(),
+            false,
+        );
+        let (id, mut events) = manager
+            .subscribe_with_id("subscription { test }".to_string(), Some(json!({"n": 1})), None)
+            .await
+            .unwrap();
+
+        let wait = Duration::from_secs(5);
+        assert_eq!(timeout(wait, init_receiver.recv()).await.unwrap(), Some(json!("old-token")));
+        assert!(timeout(wait, events.recv()).await.unwrap().is_some());
+
+        let stats = manager.stats().await;
+        assert_eq!(stats.state, ConnectionState::Connected);
+        assert_eq!(stats.subscription_ids, vec![id.clone()]);
+        assert!(stats.messages_in >= 2 && stats.messages_out >= 2, "{:?}", stats);
+        assert!(stats.uptime.is_some());
+        assert_eq!(stats.reconnect_count, 0);
+        // The node does not negotiate, so the legacy framing is used
+        assert_eq!(stats.protocol, Some(SubscriptionProtocol::SubscriptionsTransportWs));
+
+        assert!(!manager.set_auth_token(Some("old-token".to_string())).unwrap());
+        assert!(manager.set_auth_token(Some("new-token".to_string())).unwrap());
+        assert_eq!(manager.get_auth_token().as_deref(), Some("new-token"));
+
+        // The new connection inits with the rotated token and the existing receiver keeps flowing
+        assert_eq!(timeout(wait, init_receiver.recv()).await.unwrap(), Some(json!("new-token")));
+        assert!(timeout(wait, events.recv()).await.unwrap().is_some());
+        assert_eq!(manager.subscription_count().await, 1);
+        assert!(manager.subscriptions.read().await.contains_key(&id));
+
+        // Rotation ends the old connection cleanly
+        let stats = manager.stats().await;
+        assert_eq!(stats.reconnect_count, 1);
+        assert!(stats.last_error.is_none());
+
+        manager.disconnect().await;
+    }
+
+    #[tokio::test]
+    async fn test_client_subscription_handles_update_variables() {
+        use crate::client::KnishIOClient;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Answers each start with one event carrying the token it was started with
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    while let Some(Ok(Message::Text(text))) = ws.next().await {
+                        let msg: Value = serde_json::from_str(&text).unwrap();
+                        let reply = match msg["type"].as_str() {
+                            Some("connection_init") => json!({"type": "connection_ack"}),
+                            Some("start") => json!({
+                                "type": "data",
+                                "id": msg["id"],
+                                "payload": {"data": {"WalletStatus": {"tokenSlug": msg["payload"]["variables"]["token"]}}}
+                            }),
+                            _ => continue,
+                        };
+                        let _ = ws.send(Message::Text(Utf8Bytes::from(reply.to_string()))).await;
+                    }
+                });
+            }
+        });
+
+        let mut client = KnishIOClient::new("http://127.0.0.1:9/graphql", None, None, None, None, None);
+        client.set_websocket_manager(WebSocketManager::new(
+            format!("ws://{}", addr),
+            None,
+            "knishio".to_string(),
+            ReconnectConfig::default(),
+            false,
+        ));
+
+        let (event_sender, mut events) = mpsc::unbounded_channel();
+        let handle = client.subscribe_wallet_status(Some("bundle".to_string()), "GOLD".to_string(), move |event| {
+            let _ = event_sender.send(event.data["WalletStatus"]["tokenSlug"].clone());
+        }).await.unwrap();
+        assert!(handle.supports_update());
+
+        let wait = Duration::from_secs(5);
+        assert_eq!(timeout(wait, events.recv()).await.unwrap(), Some(json!("GOLD")));
+
+        // The same handle and callback carry on with the new variables
+        handle.update_variables(json!({"bundle": "bundle", "token": "SILVER"})).unwrap();
+        assert_eq!(timeout(wait, events.recv()).await.unwrap(), Some(json!("SILVER")));
+        assert_eq!(client.list_active_subscriptions().await, vec![handle.operation_name.clone()]);
+
+        client.unsubscribe(&handle.operation_name).await;
+        assert_eq!(client.active_subscription_count().await, 0);
+    }
+
+    #[tokio::test]
+    #[allow(clippy::result_large_err)] // the handshake callback's error type is tungstenite's
+    async fn test_negotiates_graphql_transport_ws() {
+        use tungstenite::handshake::server::{Request, Response};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (seen_sender, mut seen) = mpsc::unbounded_channel();
+
+        // Picks graphql-transport-ws, pings after the ack, answers subscribe with next + error
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_hdr_async(stream, |request: &Request, mut response: Response| {
+                let offered = request.headers().get("Sec-WebSocket-Protocol").unwrap().to_str().unwrap();
+                assert!(offered.contains("graphql-transport-ws"));
+                response.headers_mut().insert("Sec-WebSocket-Protocol", HeaderValue::from_static("graphql-transport-ws"));
+                Ok(response)
+            }).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let msg: Value = serde_json::from_str(&text).unwrap();
+                let _ = seen_sender.send(msg["type"].as_str().unwrap_or_default().to_string());
+                let replies = match msg["type"].as_str() {
+                    Some("connection_init") => vec![json!({"type": "connection_ack"}), json!({"type": "ping"})],
+                    Some("subscribe") => vec![
+                        json!({"type": "next", "id": msg["id"], "payload": {"data": {"test": 1}}}),
+                        json!({"type": "error", "id": msg["id"], "payload": [{"message": "denied"}]}),
+                    ],
+                    _ => continue,
+                };
+                for reply in replies {
+                    let _ = ws.send(Message::Text(Utf8Bytes::from(reply.to_string()))).await;
+                }
+            }
+        });
+
+        let config = crate::graphql::SocketConfig { socket_uri: format!("ws://{}", addr), ..Default::default() };
+        let mut manager = WebSocketManager::from_config(&config, None, false);
+        assert_eq!(manager.protocol(), SubscriptionProtocol::Auto);
+        let mut events = manager.subscribe("subscription { test }".to_string(), None, None).await.unwrap();
+
+        let wait = Duration::from_secs(5);
+        assert!(timeout(wait, events.recv()).await.unwrap().unwrap().data.is_some());
+        let error = timeout(wait, events.recv()).await.unwrap().unwrap();
+        assert_eq!(error.errors.unwrap()[0].message, "denied");
+
+        let mut types = Vec::new();
+        while types.len() < 3 {
+            types.push(timeout(wait, seen.recv()).await.unwrap().unwrap());
+        }
+        types.sort();
+        assert_eq!(types, vec!["connection_init", "pong", "subscribe"]);
+        assert_eq!(manager.stats().await.protocol, Some(SubscriptionProtocol::GraphqlWs));
+
+        manager.disconnect().await;
+    }
+
+    #[tokio::test]
+    #[allow(clippy::result_large_err)] // the handshake callback's error type is tungstenite's
+    async fn test_client_subscribe_uses_the_configured_protocol() {
+        use tungstenite::handshake::server::{Request, Response};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (seen_sender, mut seen) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_hdr_async(stream, |request: &Request, mut response: Response| {
+                let offered = request.headers().get("Sec-WebSocket-Protocol").unwrap().to_str().unwrap();
+                assert_eq!(offered, "graphql-transport-ws");
+                response.headers_mut().insert("Sec-WebSocket-Protocol", HeaderValue::from_static("graphql-transport-ws"));
+                Ok(response)
+            }).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let msg: Value = serde_json::from_str(&text).unwrap();
+                let _ = seen_sender.send(msg["type"].as_str().unwrap_or_default().to_string());
+                let reply = match msg["type"].as_str() {
+                    Some("connection_init") => json!({"type": "connection_ack"}),
+                    Some("subscribe") => json!({"type": "next", "id": msg["id"], "payload": {"data": {"test": 1}}}),
+                    _ => continue,
+                };
+                let _ = ws.send(Message::Text(Utf8Bytes::from(reply.to_string()))).await;
+            }
+        });
+
+        let config = crate::graphql::SocketConfig {
+            socket_uri: format!("ws://{}", addr),
+            protocol: SubscriptionProtocol::GraphqlWs,
+            ..Default::default()
+        };
+        let mut client = crate::graphql::GraphQLClient::with_socket("http://localhost", config, false);
+        let (response_sender, mut responses) = mpsc::unbounded_channel();
+        let request = crate::graphql::create_subscription_request("subscription { test }", None, None);
+        client.subscribe(request, move |response| {
+            let _ = response_sender.send(response);
+        }).await.unwrap();
+
+        let wait = Duration::from_secs(5);
+        let response = timeout(wait, responses.recv()).await.unwrap().unwrap();
+        assert_eq!(response.data.unwrap()["test"], 1);
+        assert_eq!(timeout(wait, seen.recv()).await.unwrap().unwrap(), "connection_init");
+        assert_eq!(timeout(wait, seen.recv()).await.unwrap().unwrap(), "subscribe");
+    }
+
+    #[cfg(feature = "fault-injection")]
+    #[tokio::test]
+    #[allow(clippy::result_large_err)] // the handshake callback's error type is tungstenite's
+    async fn test_injected_frame_drops() {
+        use tungstenite::handshake::server::{Request, Response};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_hdr_async(stream, |_: &Request, mut response: Response| {
+                response.headers_mut().insert("Sec-WebSocket-Protocol", HeaderValue::from_static("graphql-transport-ws"));
+                Ok(response)
+            }).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let msg: Value = serde_json::from_str(&text).unwrap();
+                let replies = match msg["type"].as_str() {
+                    Some("connection_init") => vec![json!({"type": "connection_ack"})],
+                    Some("subscribe") => (1..=3).map(|n| json!({"type": "next", "id": msg["id"], "payload": {"data": {"n": n}}})).collect(),
+                    _ => continue,
+                };
+                for reply in replies {
+                    let _ = ws.send(Message::Text(Utf8Bytes::from(reply.to_string()))).await;
+                }
+            }
+        });
+
+        let injector = Arc::new(crate::graphql::FaultInjector::new().dropped_frames(1.0));
+        let mut manager = WebSocketManager::new(format!("ws://{}", addr), None, "knishio".to_string(), ReconnectConfig::default(), false)
+            .with_protocol(SubscriptionProtocol::GraphqlWs)
+            .with_fault_injector(Some(injector.clone()));
+        let mut responses = manager.subscribe("subscription { n }".to_string(), None, None).await.unwrap();
+
+        // Every data frame is dropped; the connection itself stays up
+        timeout(Duration::from_secs(5), async {
+            while injector.stats().dropped_frames < 3 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.unwrap();
+        assert!(responses.try_recv().is_err());
+        assert_eq!(manager.get_state().await, ConnectionState::Connected);
+        assert_eq!(injector.stats().timeouts, 0);
+    }
+
+    #[tokio::test]
+    async fn test_stats_record_connection_errors() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let config = ReconnectConfig { max_attempts: 1, ..ReconnectConfig::default() };
+        let mut manager = WebSocketManager::new(format!("ws://{}", addr), None, "knishio".to_string(), config, false);
+        manager.start().await.unwrap();
+
+        let stats = timeout(Duration::from_secs(5), async {
+            loop {
+                let stats = manager.stats().await;
+                if stats.state == ConnectionState::Disconnected && stats.last_error.is_some() {
+                    return stats;
+                }
+                sleep(Duration::from_millis(10)).await;
+            }
+        }).await.unwrap();
+
+        assert!(stats.last_error.unwrap().contains("Connection failed"));
+        assert_eq!((stats.messages_in, stats.messages_out, stats.reconnect_count), (0, 0, 0));
+        assert!(stats.uptime.is_none());
+    }
+}
@@ -0,0 +1,313 @@
+//! Identity bridge for external keypairs
+//!
+//! Some deployments link a KnishIO bundle to a keypair that already exists elsewhere
+//! (an Ed25519 account key, a secp256k1 wallet key, ...). The external key signs a
+//! challenge bound to the bundle hash, and the resulting `IdentityProof` is embedded as
+//! meta on an identifier (C-isotope) molecule. Anyone reading the identifier back can
+//! verify the proof with a matching `ExternalVerifier`.
+//!
+//! The SDK does not ship Ed25519 or secp256k1 implementations; callers plug in their
+//! own through the `ExternalSigner` / `ExternalVerifier` traits.
+
+use crate::error::{KnishIOError, Result};
+use crate::types::MetaItem;
+use serde_json::Value;
+
+/// Identifier meta key holding the hex-encoded external public key
+pub const META_CONTACT: &str = "contact";
+/// Identifier meta key holding the hex-encoded signature over the challenge
+pub const META_CODE: &str = "code";
+/// Identifier meta key holding the key scheme name
+pub const META_SCHEME: &str = "scheme";
+/// Identifier meta key holding the challenge nonce
+pub const META_NONCE: &str = "nonce";
+
+/// Supported external key schemes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyScheme {
+    /// Ed25519 (RFC 8032)
+    Ed25519,
+    /// ECDSA over secp256k1
+    Secp256k1,
+}
+
+impl KeyScheme {
+    /// Scheme name as stored in identifier meta (also used as the identifier type)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyScheme::Ed25519 => "ed25519",
+            KeyScheme::Secp256k1 => "secp256k1",
+        }
+    }
+
+    /// Parse a scheme name (case-insensitive)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "ed25519" => Some(KeyScheme::Ed25519),
+            "secp256k1" => Some(KeyScheme::Secp256k1),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for KeyScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An external private key able to sign identity challenges
+pub trait ExternalSigner: Send + Sync {
+    /// Scheme of the key
+    fn scheme(&self) -> KeyScheme;
+
+    /// Public key bytes in the scheme's canonical encoding
+    fn public_key(&self) -> Vec<u8>;
+
+    /// Sign `message` and return the signature bytes
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Signature verification for one external key scheme
+pub trait ExternalVerifier: Send + Sync {
+    /// Scheme this verifier understands
+    fn scheme(&self) -> KeyScheme;
+
+    /// Check `signature` over `message` against `public_key`
+    fn verify(&self, public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<bool>;
+}
+
+/// Proof that the holder of an external key controls a KnishIO bundle
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdentityProof {
+    /// External key scheme
+    pub scheme: KeyScheme,
+    /// Hex-encoded external public key
+    pub public_key: String,
+    /// Nonce mixed into the challenge to prevent replay across links
+    pub nonce: String,
+    /// Hex-encoded signature over `challenge(bundle, nonce)`
+    pub signature: String,
+}
+
+impl IdentityProof {
+    /// Challenge message signed by the external key
+    pub fn challenge(bundle: &str, nonce: &str) -> String {
+        format!("KnishIO identity link\nbundle:{}\nnonce:{}", bundle, nonce)
+    }
+
+    /// Sign the challenge for `bundle` with an external key
+    ///
+    /// # Errors
+    ///
+    /// Returns `WalletCredential` if `bundle` is not a valid bundle hash, or the signer's error
+    pub fn create(signer: &dyn ExternalSigner, bundle: &str, nonce: &str) -> Result<Self> {
+        if !crate::wallet::Wallet::is_bundle_hash(bundle) {
+            return Err(KnishIOError::WalletCredential);
+        }
+
+        let signature = signer.sign(Self::challenge(bundle, nonce).as_bytes())?;
+
+        Ok(IdentityProof {
+            scheme: signer.scheme(),
+            public_key: hex::encode(signer.public_key()),
+            nonce: nonce.to_string(),
+            signature: hex::encode(signature),
+        })
+    }
+
+    /// Verify this proof for `bundle`
+    ///
+    /// Returns `Ok(false)` for a wrong signature; errors only when the proof cannot be
+    /// checked at all (scheme mismatch or malformed hex).
+    pub fn verify(&self, verifier: &dyn ExternalVerifier, bundle: &str) -> Result<bool> {
+        if verifier.scheme() != self.scheme {
+            return Err(KnishIOError::custom(format!(
+                "Verifier for {} cannot check a {} proof",
+                verifier.scheme(),
+                self.scheme
+            )));
+        }
+
+        let public_key = hex::decode(&self.public_key)
+            .map_err(|e| KnishIOError::custom(format!("Invalid public key hex: {}", e)))?;
+        let signature = hex::decode(&self.signature)
+            .map_err(|e| KnishIOError::custom(format!("Invalid signature hex: {}", e)))?;
+
+        verifier.verify(&public_key, Self::challenge(bundle, &self.nonce).as_bytes(), &signature)
+    }
+
+    /// Identifier meta carrying this proof
+    pub fn to_meta(&self) -> Vec<MetaItem> {
+        vec![
+            MetaItem::new(META_CONTACT, self.public_key.clone()),
+            MetaItem::new(META_CODE, self.signature.clone()),
+            MetaItem::new(META_SCHEME, self.scheme.as_str()),
+            MetaItem::new(META_NONCE, self.nonce.clone()),
+        ]
+    }
+
+    /// Read a proof back from identifier meta (None if any part is missing)
+    pub fn from_meta(meta: &[MetaItem]) -> Option<Self> {
+        let get = |key: &str| meta.iter().find(|m| m.key == key).map(|m| m.value.clone());
+
+        Some(IdentityProof {
+            scheme: KeyScheme::parse(&get(META_SCHEME)?)?,
+            public_key: get(META_CONTACT)?,
+            nonce: get(META_NONCE)?,
+            signature: get(META_CODE)?,
+        })
+    }
+
+    /// Read a proof from an atom as returned by `query_atom`
+    ///
+    /// Accepts `metasJson` (string), `metas` or `meta`, each holding either a
+    /// `[{key, value}]` array or a plain object.
+    pub fn from_atom_json(atom: &Value) -> Option<Self> {
+        let metas = match atom.get("metasJson").and_then(|v| v.as_str()) {
+            Some(json) => serde_json::from_str(json).ok()?,
+            None => atom.get("metas").or_else(|| atom.get("meta"))?.clone(),
+        };
+
+        let meta: Vec<MetaItem> = match metas {
+            Value::Array(items) => items.iter()
+                .filter_map(|item| {
+                    let key = item.get("key")?.as_str()?;
+                    let value = item.get("value")?.as_str()?;
+                    Some(MetaItem::new(key, value))
+                })
+                .collect(),
+            Value::Object(map) => map.iter()
+                .filter_map(|(k, v)| Some(MetaItem::new(k.as_str(), v.as_str()?)))
+                .collect(),
+            _ => return None,
+        };
+
+        Self::from_meta(&meta)
+    }
+}
+
+/// An identity proof read from the ledger together with its verification result
+#[derive(Debug, Clone)]
+pub struct VerifiedIdentityProof {
+    /// The proof as stored on the identifier
+    pub proof: IdentityProof,
+    /// Whether the signature checks out for the queried bundle
+    pub valid: bool,
+}
+
+impl VerifiedIdentityProof {
+    /// Verify the proofs of `verifier`'s scheme found in identifier atoms
+    ///
+    /// Atoms without a complete proof are skipped. A proof that cannot be checked,
+    /// such as one with malformed hex, is returned as invalid rather than failing the rest.
+    pub fn from_atoms(atoms: &[Value], bundle: &str, verifier: &dyn ExternalVerifier) -> Vec<Self> {
+        let scheme = verifier.scheme();
+        atoms.iter()
+            .filter_map(IdentityProof::from_atom_json)
+            .filter(|proof| proof.scheme == scheme)
+            .map(|proof| {
+                let valid = proof.verify(verifier, bundle).unwrap_or(false);
+                VerifiedIdentityProof { proof, valid }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUNDLE: &str = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    /// Toy scheme: the "signature" is shake256(public_key || message)
+    struct MockKey(Vec<u8>);
+
+    fn mock_signature(public_key: &[u8], message: &[u8]) -> Vec<u8> {
+        let input = format!("{}{}", hex::encode(public_key), String::from_utf8_lossy(message));
+        crate::crypto::shake256(&input, 32).into_bytes()
+    }
+
+    impl ExternalSigner for MockKey {
+        fn scheme(&self) -> KeyScheme {
+            KeyScheme::Ed25519
+        }
+
+        fn public_key(&self) -> Vec<u8> {
+            self.0.clone()
+        }
+
+        fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+            Ok(mock_signature(&self.0, message))
+        }
+    }
+
+    struct MockVerifier(KeyScheme);
+
+    impl ExternalVerifier for MockVerifier {
+        fn scheme(&self) -> KeyScheme {
+            self.0
+        }
+
+        fn verify(&self, public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<bool> {
+            Ok(mock_signature(public_key, message) == signature)
+        }
+    }
+
+    #[test]
+    fn test_create_and_verify_proof() {
+        let proof = IdentityProof::create(&MockKey(vec![7; 32]), BUNDLE, "nonce-1").unwrap();
+        assert_eq!(proof.scheme, KeyScheme::Ed25519);
+        assert_eq!(proof.public_key, hex::encode([7u8; 32]));
+
+        let verifier = MockVerifier(KeyScheme::Ed25519);
+        assert!(proof.verify(&verifier, BUNDLE).unwrap());
+
+        // Bound to the bundle it was created for
+        let other = BUNDLE.replace('0', "f");
+        assert!(!proof.verify(&verifier, &other).unwrap());
+
+        // Wrong scheme cannot be checked
+        assert!(proof.verify(&MockVerifier(KeyScheme::Secp256k1), BUNDLE).is_err());
+
+        assert!(IdentityProof::create(&MockKey(vec![7; 32]), "not-a-bundle", "n").is_err());
+    }
+
+    #[test]
+    fn test_meta_round_trip() {
+        let proof = IdentityProof::create(&MockKey(vec![1, 2, 3]), BUNDLE, "nonce-2").unwrap();
+        let meta = proof.to_meta();
+        assert_eq!(IdentityProof::from_meta(&meta), Some(proof.clone()));
+        assert!(IdentityProof::from_meta(&meta[..2]).is_none());
+
+        let metas_json = serde_json::to_string(&meta).unwrap();
+        let atom = serde_json::json!({ "isotope": "C", "metasJson": metas_json });
+        assert_eq!(IdentityProof::from_atom_json(&atom), Some(proof));
+    }
+
+    #[test]
+    fn test_malformed_proof_does_not_block_others() {
+        let verifier = MockVerifier(KeyScheme::Ed25519);
+        let valid = IdentityProof::create(&MockKey(vec![5; 32]), BUNDLE, "nonce-3").unwrap();
+        let malformed = IdentityProof { public_key: "not hex".to_string(), ..valid.clone() };
+        let foreign = IdentityProof { scheme: KeyScheme::Secp256k1, ..valid.clone() };
+
+        let atoms: Vec<Value> = [&malformed, &valid, &foreign].iter()
+            .map(|proof| serde_json::json!({ "isotope": "C", "metasJson": serde_json::to_string(&proof.to_meta()).unwrap() }))
+            .chain(std::iter::once(serde_json::json!({ "isotope": "C", "metasJson": "[]" })))
+            .collect();
+
+        let results = VerifiedIdentityProof::from_atoms(&atoms, BUNDLE, &verifier);
+        assert_eq!(results.len(), 2);
+        assert_eq!((&results[0].proof, results[0].valid), (&malformed, false));
+        assert_eq!((&results[1].proof, results[1].valid), (&valid, true));
+    }
+
+    #[test]
+    fn test_key_scheme_parse() {
+        assert_eq!(KeyScheme::parse("Ed25519"), Some(KeyScheme::Ed25519));
+        assert_eq!(KeyScheme::parse("secp256k1"), Some(KeyScheme::Secp256k1));
+        assert_eq!(KeyScheme::parse("rsa"), None);
+        assert_eq!(KeyScheme::Secp256k1.to_string(), "secp256k1");
+    }
+}
@@ -17,7 +17,7 @@
 //! # Quick Start
 //!
 //! ```rust
-//! use knishio_client::{Wallet, Molecule};
+//! use knishio_client::{Wallet, Molecule, MoleculeParams};
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -31,13 +31,10 @@
 //!     )?;
 //!
 //!     // Create and sign a molecule
-//!     let mut molecule = Molecule::with_params(
-//!         Some("your-secret-here".to_string()),
-//!         None,
-//!         Some(wallet),
-//!         None,
-//!         None,
-//!         None,
+//!     let mut molecule = Molecule::from_params(
+//!         MoleculeParams::new()
+//!             .secret("your-secret-here")
+//!             .source_wallet(wallet),
 //!     );
 //!
 //!     // ... build your transaction ...
@@ -62,6 +59,7 @@ pub mod graphql;
 pub mod query;
 pub mod mutation;
 pub mod response;
+pub mod codec;
 
 // Client module
 pub mod client;
@@ -74,6 +72,19 @@ pub mod rules;
 pub mod versions;
 pub mod token_unit;
 pub mod policy_meta;
+pub mod identity_bridge;
+
+// In-process ledger simulator for integration tests
+#[cfg(any(test, feature = "test-ledger"))]
+pub mod test_ledger;
+
+// Deterministic wallet and molecule fixtures for tests
+#[cfg(any(test, feature = "testkit"))]
+pub mod testkit;
+
+// Node certification suite built on the testkit fixtures
+#[cfg(any(test, feature = "certification"))]
+pub mod certification;
 
 // Utility modules
 pub mod utils;
@@ -83,38 +94,41 @@ pub mod check_molecule;
 
 // Re-exports for convenience
 pub use atom::Atom;
-pub use error::{KnishIOError, Result};
-pub use molecule::{Molecule, TypeSafeMoleculeBuilder, ValueAtomParams, MetaAtomParams, IdentityAtomParams, TokenRequestAtomParams, BufferDepositAtomParams, BufferWithdrawAtomParams, FusionAtomParams, StackableTransferParams};
-pub use types::{Isotope, MetaItem};
-pub use wallet::Wallet;
-pub use client::{KnishIOClient, TransferRecipient, builder::ClientBuilder};
-pub use check_molecule::{CheckMolecule, IntegrityReport, MoleculeIntegrityResult};
-pub use token_unit::TokenUnit;
-pub use policy_meta::PolicyMeta;
+pub use error::{ErrorCatalog, KnishIOError, Result};
+pub use molecule::{Molecule, MoleculeParams, TypeSafeMoleculeBuilder, ValueAtomParams, MetaAtomParams, IdentityAtomParams, TokenRequestAtomParams, BufferDepositAtomParams, BufferWithdrawAtomParams, FusionAtomParams, StackableTransferParams};
+pub use types::{Isotope, MetaItem, SystemTokens, TradeRate, ValueString, DEFAULT_AUTH_TOKEN, DEFAULT_USER_TOKEN};
+pub use wallet::{Characters, OwnershipProof, ProofTree, Wallet, WalletHydration, WalletParams, WatchWallet};
+pub use client::{KnishIOClient, RemainderOptions, RemainderToken, TransferRecipient, SourceLeg, BulkSummary, BatchLineage, LedgerDiff, LedgerSnapshot, QuorumReport, QuorumStatus, SchemaReport, builder::ClientBuilder};
+pub use check_molecule::{CheckMolecule, IntegrityReport, IsotopeValidator, MoleculeIntegrityResult, ValidatorRegistry};
+pub use token_unit::{HeldTokenUnit, TokenUnit, TokenUnitFilter, UnitSelection};
+pub use policy_meta::{PolicyEntry, PolicyMeta};
 
 // Rules system re-exports
 pub use rules::{Rule, Callback, Condition};
 
 // Version utilities re-exports
-pub use versions::{HashAtom, Version4, AtomVersion, StructureUtils};
+pub use versions::{HashAtom, Version4, AtomVersion, StructureUtils, migrate_molecule, MigrationIssue, MoleculeMigration};
 
 // GraphQL re-exports - Production-Ready Client
 pub use graphql::{
-    GraphQLClient, GraphQLRequest, GraphQLResponse, GraphQLError, ErrorLocation,
-    SocketConfig, GraphQLConnectionStats, RetryPolicy, RetryStrategy, RetryCondition,
-    RetryExecutor, ClientConfig, ConnectionPoolConfig, PoolStats, WebSocketManager, ConnectionState,
-    WebSocketReconnectConfig, global_pool, execute_with_retry,
+    GraphQLClient, GraphQLRequest, GraphQLResponse, GraphQLError, ErrorLocation, HedgeConfig,
+    SocketConfig, GraphQLConnectionStats, QueryCostConfig, QueryCostListener, QueryCostReason, QueryCostStats,
+    QueryCostWarning, RequestOptions, RequestPriority, ResponseHeaders, SchedulerConfig, SchedulerStats, RetryPolicy, RetryStrategy, RetryCondition,
+    RetryExecutor, ClientConfig, ConnectionPoolConfig, PoolStats, WebSocketManager, WebSocketStats, ConnectionState,
+    WebSocketReconnectConfig, SubscriptionProtocol, ConsumerConfig, ConsumerMonitor, ConsumerStats, EventReceiver, OverflowPolicy,
+    RequestInterceptor, SDK_HEADER,
+    global_pool, execute_with_retry,
     create_query_request, create_mutation_request, create_subscription_request
 };
 pub use query::{Query, BaseQuery};
 pub use mutation::{Mutation, BaseMutation};
-pub use response::{Response, BaseResponse};
+pub use response::{Response, BaseResponse, ResponseEnvelope, AuthPayload, TokenPayload, WalletPayload};
 
 /// Cryptographic operations module
 ///
 /// Provides all cryptographic primitives used by the KnishIO SDK including
 /// SHAKE256 hashing, secret generation, and bundle hash computation.
-pub use crypto::{generate_bundle_hash, generate_secret, generate_batch_id, shake256};
+pub use crypto::{generate_bundle_hash, generate_secret, generate_batch_id, generate_namespaced_batch_id, validate_batch_id, shake256, derive_public_digest, derive_address};
 
 /// Molecule transaction builder utilities
 ///
@@ -177,13 +191,10 @@ mod integration_tests {
             None,
         ).unwrap();
 
-        let molecule = Molecule::with_params(
-            Some("test-secret-12345".to_string()),
-            None,
-            Some(wallet),
-            None,
-            None,
-            None,
+        let molecule = Molecule::from_params(
+            MoleculeParams::new()
+                .secret("test-secret-12345")
+                .source_wallet(wallet),
         );
 
         assert_eq!(molecule.atoms.len(), 0);
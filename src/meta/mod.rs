@@ -8,8 +8,11 @@ use std::collections::{HashMap, HashSet};
 use crate::types::MetaItem;
 use crate::error::Result;
 
+pub mod schema_registry;
+
 // Re-export PolicyMeta from the dedicated policy_meta module
 pub use crate::policy_meta::PolicyMeta;
+pub use schema_registry::{MetaSchema, Migration, SchemaRegistry, SCHEMA_VERSION_KEY};
 
 /// General metadata structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -281,12 +284,19 @@ impl AtomMeta {
     /// # Arguments
     ///
     /// * `policy` - Policy data to add
+    ///
+    /// # Errors
+    ///
+    /// `PolicyInvalid` if `policy` is not a policy object (see `PolicyMeta::validate`)
     pub fn add_policy(&mut self, policy: serde_json::Value) -> Result<&mut Self> {
         // Get current meta keys for policy validation
-        let meta_keys: Vec<String> = self.meta.iter().map(|item| item.key.clone()).collect();
+        let meta_keys: Vec<String> = self.meta.iter()
+            .filter(|item| item.key != "policy")
+            .map(|item| item.key.clone())
+            .collect();
         
         // Create PolicyMeta instance
-        let policy_meta = PolicyMeta::new(policy, meta_keys);
+        let policy_meta = PolicyMeta::parse(policy, meta_keys)?;
         
         let policy_json = policy_meta.to_json()?;
         let mut policy_map = HashMap::new();
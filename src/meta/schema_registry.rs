@@ -0,0 +1,273 @@
+//! Versioned meta schemas
+//!
+//! Metadata written to the ledger lives forever, but the structs apps read it into keep
+//! changing. A `SchemaRegistry` records, per meta type, the current schema version and a
+//! migration for every older version. Writes are stamped with `schemaVersion`; reads run
+//! the migrations from the stamped version up to the current one before deserializing, so
+//! code only ever sees the current shape. Metadata written before a schema was registered
+//! carries no stamp and is treated as version 1.
+
+use crate::error::{KnishIOError, Result};
+use crate::types::MetaItem;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Meta key holding the schema version a record was written with
+pub const SCHEMA_VERSION_KEY: &str = "schemaVersion";
+
+/// Migration from one schema version to the next, applied to the record's fields in place
+pub type Migration = Arc<dyn Fn(&mut Map<String, Value>) -> Result<()> + Send + Sync>;
+
+/// Schema of one meta type: its current version and the migrations leading up to it
+#[derive(Clone)]
+pub struct MetaSchema {
+    meta_type: String,
+    version: u32,
+    migrations: BTreeMap<u32, Migration>,
+}
+
+impl MetaSchema {
+    /// Schema for `meta_type` whose current version is `version` (versions start at 1)
+    pub fn new(meta_type: impl Into<String>, version: u32) -> Self {
+        MetaSchema {
+            meta_type: meta_type.into(),
+            version,
+            migrations: BTreeMap::new(),
+        }
+    }
+
+    /// Register the migration from version `from` to `from + 1`
+    pub fn migration<F>(mut self, from: u32, migrate: F) -> Self
+    where
+        F: Fn(&mut Map<String, Value>) -> Result<()> + Send + Sync + 'static,
+    {
+        self.migrations.insert(from, Arc::new(migrate));
+        self
+    }
+
+    /// Meta type this schema describes
+    pub fn meta_type(&self) -> &str {
+        &self.meta_type
+    }
+
+    /// Current schema version
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Bring `fields` from `from` up to the current version
+    fn upgrade(&self, fields: &mut Map<String, Value>, from: u32) -> Result<()> {
+        if from > self.version {
+            return Err(KnishIOError::custom(format!(
+                "{} meta has schema version {}, newer than the registered version {}",
+                self.meta_type, from, self.version
+            )));
+        }
+
+        for version in from..self.version {
+            let migrate = self.migrations.get(&version).ok_or_else(|| KnishIOError::custom(format!(
+                "No migration registered for {} meta from version {}",
+                self.meta_type, version
+            )))?;
+            migrate(fields)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for MetaSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MetaSchema")
+            .field("meta_type", &self.meta_type)
+            .field("version", &self.version)
+            .field("migrations", &self.migrations.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Registry of meta schemas keyed by meta type
+#[derive(Debug, Clone, Default)]
+pub struct SchemaRegistry {
+    schemas: HashMap<String, MetaSchema>,
+}
+
+impl SchemaRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a schema, replacing any earlier schema for the same meta type
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigurationError` if the version is 0 or a migration between version 1
+    /// and the current version is missing
+    pub fn register(&mut self, schema: MetaSchema) -> Result<&mut Self> {
+        if schema.version == 0 {
+            return Err(KnishIOError::ConfigurationError(format!(
+                "Schema versions for {} must start at 1", schema.meta_type
+            )));
+        }
+        if let Some(missing) = (1..schema.version).find(|v| !schema.migrations.contains_key(v)) {
+            return Err(KnishIOError::ConfigurationError(format!(
+                "Schema for {} is missing the migration from version {}", schema.meta_type, missing
+            )));
+        }
+
+        self.schemas.insert(schema.meta_type.clone(), schema);
+        Ok(self)
+    }
+
+    /// Schema registered for `meta_type`
+    pub fn get(&self, meta_type: &str) -> Option<&MetaSchema> {
+        self.schemas.get(meta_type)
+    }
+
+    /// Schema version a record was written with (1 if it carries no stamp)
+    pub fn version_of(fields: &Map<String, Value>) -> u32 {
+        match fields.get(SCHEMA_VERSION_KEY) {
+            Some(Value::Number(n)) => n.as_u64().map_or(1, |v| v as u32),
+            Some(Value::String(s)) => s.trim_matches('"').parse().unwrap_or(1),
+            _ => 1,
+        }
+    }
+
+    /// Add the current `schemaVersion` stamp for `meta_type` to `meta` (no-op if unregistered)
+    pub fn stamp(&self, meta_type: &str, meta: &mut HashMap<String, Value>) {
+        if let Some(schema) = self.get(meta_type) {
+            meta.insert(SCHEMA_VERSION_KEY.to_string(), Value::from(schema.version));
+        }
+    }
+
+    /// Migrate a record of `meta_type` to the current version and re-stamp it
+    ///
+    /// Records of unregistered meta types are returned unchanged.
+    pub fn migrate(&self, meta_type: &str, mut fields: Map<String, Value>) -> Result<Map<String, Value>> {
+        if let Some(schema) = self.get(meta_type) {
+            let from = Self::version_of(&fields);
+            schema.upgrade(&mut fields, from)?;
+            fields.insert(SCHEMA_VERSION_KEY.to_string(), Value::from(schema.version));
+        }
+
+        Ok(fields)
+    }
+
+    /// Serialize `value` into stamped meta ready for `create_meta`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `value` does not serialize to a JSON object
+    pub fn encode<T: Serialize>(&self, meta_type: &str, value: &T) -> Result<HashMap<String, Value>> {
+        let Value::Object(fields) = serde_json::to_value(value)? else {
+            return Err(KnishIOError::custom(format!("{} meta must serialize to an object", meta_type)));
+        };
+
+        let mut meta: HashMap<String, Value> = fields.into_iter().collect();
+        self.stamp(meta_type, &mut meta);
+        Ok(meta)
+    }
+
+    /// Read ledger meta items of `meta_type` into the current version of `T`
+    ///
+    /// Meta values are strings on the ledger; each is JSON-decoded where possible (the SDK
+    /// writes values JSON-encoded) and kept as a plain string otherwise. Later items win
+    /// over earlier ones with the same key.
+    pub fn decode<T: DeserializeOwned>(&self, meta_type: &str, items: &[MetaItem]) -> Result<T> {
+        let fields = items.iter()
+            .map(|item| {
+                let value = serde_json::from_str(&item.value).unwrap_or_else(|_| Value::String(item.value.clone()));
+                (item.key.clone(), value)
+            })
+            .collect();
+
+        let mut fields = self.migrate(meta_type, fields)?;
+        fields.remove(SCHEMA_VERSION_KEY);
+        Ok(serde_json::from_value(Value::Object(fields))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Profile {
+        display_name: String,
+        age: u32,
+        tags: Vec<String>,
+    }
+
+    fn registry() -> SchemaRegistry {
+        let schema = MetaSchema::new("profile", 3)
+            // v1 -> v2: `name` was renamed to `display_name`
+            .migration(1, |fields| {
+                if let Some(name) = fields.remove("name") {
+                    fields.insert("display_name".to_string(), name);
+                }
+                Ok(())
+            })
+            // v2 -> v3: `tags` became required
+            .migration(2, |fields| {
+                fields.entry("tags").or_insert_with(|| json!([]));
+                Ok(())
+            });
+
+        let mut registry = SchemaRegistry::new();
+        registry.register(schema).unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_old_records_are_migrated() {
+        let registry = registry();
+
+        // Unstamped record written before the schema existed, values as raw strings
+        let v1 = vec![MetaItem::new("name", "Alice"), MetaItem::new("age", "30")];
+        let profile: Profile = registry.decode("profile", &v1).unwrap();
+        assert_eq!(profile, Profile { display_name: "Alice".to_string(), age: 30, tags: vec![] });
+
+        // v2 record with JSON-encoded values, as create_meta writes them
+        let v2 = vec![
+            MetaItem::new("display_name", "\"Bob\""),
+            MetaItem::new("age", "41"),
+            MetaItem::new(SCHEMA_VERSION_KEY, "2"),
+        ];
+        let profile: Profile = registry.decode("profile", &v2).unwrap();
+        assert_eq!(profile.display_name, "Bob");
+        assert!(profile.tags.is_empty());
+    }
+
+    #[test]
+    fn test_encode_stamps_current_version() {
+        let registry = registry();
+        let profile = Profile { display_name: "Carol".to_string(), age: 25, tags: vec!["admin".to_string()] };
+
+        let meta = registry.encode("profile", &profile).unwrap();
+        assert_eq!(meta[SCHEMA_VERSION_KEY], json!(3));
+
+        // Round trip through the ledger's string representation
+        let items: Vec<MetaItem> = meta.iter().map(|(k, v)| MetaItem::new(k, v.to_string())).collect();
+        assert_eq!(registry.decode::<Profile>("profile", &items).unwrap(), profile);
+
+        // Unregistered meta types are not stamped
+        assert!(!registry.encode("other", &profile).unwrap().contains_key(SCHEMA_VERSION_KEY));
+    }
+
+    #[test]
+    fn test_invalid_schemas_and_future_versions() {
+        let mut registry = SchemaRegistry::new();
+        assert!(registry.register(MetaSchema::new("gap", 3).migration(1, |_| Ok(()))).is_err());
+        assert!(registry.register(MetaSchema::new("zero", 0)).is_err());
+
+        let registry = self::registry();
+        let future = vec![MetaItem::new("display_name", "Dan"), MetaItem::new(SCHEMA_VERSION_KEY, "9")];
+        assert!(registry.decode::<Profile>("profile", &future).is_err());
+    }
+}
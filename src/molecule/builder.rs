@@ -10,6 +10,8 @@
 //! - **Fluent API**: Intuitive builder pattern with method chaining
 //! - **Cross-SDK Compatibility**: Maintains exact compatibility with JavaScript reference
 //! - **Isotope-Specific Builders**: Type-safe atom creation for each isotope type
+//! - **Compound Mode**: Transfer, metadata and policy in one molecule, checked for
+//!   cross-atom consistency before signing
 //!
 //! # Examples
 //!
@@ -39,6 +41,7 @@
 //! # }
 //! ```
 
+use std::borrow::Cow;
 use std::marker::PhantomData;
 
 use crate::molecule::Molecule;
@@ -58,6 +61,9 @@ pub mod states {
     
     /// Has atoms added to the molecule
     pub struct WithAtoms;
+
+    /// Compound molecule - transfer, metadata and policy sections on one source wallet
+    pub struct Compound;
     
     /// Ready for signing - all required components present
     pub struct ReadyToSign;
@@ -74,8 +80,6 @@ pub mod states {
 pub struct TypeSafeMoleculeBuilder<State> {
     molecule: Molecule,
     secret: Option<String>,
-    source_wallet: Option<Wallet>,
-    remainder_wallet: Option<Wallet>,
     _phantom: PhantomData<State>,
 }
 
@@ -196,8 +200,6 @@ impl TypeSafeMoleculeBuilder<states::Empty> {
         Self {
             molecule: Molecule::new(),
             secret: Some(secret.into()),
-            source_wallet: None,
-            remainder_wallet: None,
             _phantom: PhantomData,
         }
     }
@@ -212,14 +214,12 @@ impl TypeSafeMoleculeBuilder<states::Empty> {
     ///
     /// Builder in WithSourceWallet state
     pub fn with_source_wallet(mut self, wallet: Wallet) -> TypeSafeMoleculeBuilder<states::WithSourceWallet> {
-        self.molecule.source_wallet = Some(wallet.clone());
         self.molecule.bundle = wallet.bundle.clone();
-        
+        self.molecule.source_wallet = Some(wallet);
+
         TypeSafeMoleculeBuilder {
             molecule: self.molecule,
             secret: self.secret,
-            source_wallet: Some(wallet),
-            remainder_wallet: self.remainder_wallet,
             _phantom: PhantomData,
         }
     }
@@ -236,8 +236,7 @@ impl TypeSafeMoleculeBuilder<states::WithSourceWallet> {
     ///
     /// Builder in same state with remainder wallet configured
     pub fn with_remainder_wallet(mut self, wallet: Wallet) -> Self {
-        self.molecule.remainder_wallet = Some(wallet.clone());
-        self.remainder_wallet = Some(wallet);
+        self.molecule.remainder_wallet = Some(wallet);
         self
     }
 
@@ -272,6 +271,46 @@ impl TypeSafeMoleculeBuilder<states::WithSourceWallet> {
         self
     }
 
+    /// Switch to compound mode
+    ///
+    /// A compound molecule combines a transfer with metadata and policy atoms, all signed
+    /// by the source wallet. `ready_to_sign` then checks the atoms against each other (see
+    /// `validate_compound`) instead of only counting them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use knishio_client::molecule::TypeSafeMoleculeBuilder;
+    /// use knishio_client::{MetaItem, Wallet};
+    /// use serde_json::json;
+    ///
+    /// let secret = "c".repeat(2048);
+    /// let mut source = Wallet::create(Some(&secret), None, "GOLD", None, None).unwrap();
+    /// source.set_balance_i128(100);
+    /// let remainder = source.create_remainder(&secret).unwrap();
+    /// let merchant = Wallet::create(Some("merchant"), None, "GOLD", None, None).unwrap();
+    ///
+    /// let signed = TypeSafeMoleculeBuilder::new(secret.as_str())
+    ///     .with_source_wallet(source.clone())
+    ///     .with_remainder_wallet(remainder)
+    ///     .compound()
+    ///     .add_transfer(&merchant, 30.0).unwrap()
+    ///     .add_meta("invoice", "INV-7", vec![MetaItem::new("total", "30")]).unwrap()
+    ///     .add_policy(json!({ "read": { "total": ["self"] } })).unwrap()
+    ///     .ready_to_sign().unwrap()
+    ///     .sign_sync().unwrap();
+    ///
+    /// assert_eq!(signed.atom_count(), 4);
+    /// assert!(signed.as_molecule().check(Some(&source)).is_ok());
+    /// ```
+    pub fn compound(self) -> TypeSafeMoleculeBuilder<states::Compound> {
+        TypeSafeMoleculeBuilder {
+            molecule: self.molecule,
+            secret: self.secret,
+            _phantom: PhantomData,
+        }
+    }
+
     /// Add a Value isotope atom to the molecule
     ///
     /// # Arguments
@@ -370,12 +409,12 @@ impl TypeSafeMoleculeBuilder<states::WithSourceWallet> {
     ///
     /// Builder in WithAtoms state with 2-3 V-isotope atoms added
     pub fn add_stackable_transfer(mut self, params: StackableTransferParams) -> Result<TypeSafeMoleculeBuilder<states::WithAtoms>> {
-        // Clone the wallets so we can split token_units without disturbing the builder's
-        // (which are returned for signing — signing doesn't depend on token_units).
-        let mut source_wallet = self.source_wallet.clone()
-            .ok_or_else(|| KnishIOError::custom("Source wallet is required"))?;
+        // Borrow the molecule's wallets; only a stackable transfer, which splits token_units,
+        // takes copies so the wallets returned for signing stay untouched.
+        let mut source_wallet = Cow::Borrowed(self.molecule.source_wallet.as_ref()
+            .ok_or_else(|| KnishIOError::custom("Source wallet is required"))?);
 
-        let source_balance = source_wallet.balance_as_i128();
+        let source_balance = source_wallet.whole_balance()?;
         let amount_i128 = params.amount as i128;
 
         if source_balance < amount_i128 {
@@ -389,14 +428,14 @@ impl TypeSafeMoleculeBuilder<states::WithSourceWallet> {
         // so the validator's Phase 2a moves per-unit ownership (it reads SENT from the source atom
         // and KEPT from the remainder atom). Fungible (units empty) emits no meta — unchanged.
         let mut remainder_wallet = if remainder > 0 {
-            Some(self.remainder_wallet.clone()
-                .ok_or_else(|| KnishIOError::custom("Remainder wallet required when transfer < source balance"))?)
+            Some(Cow::Borrowed(self.molecule.remainder_wallet.as_ref()
+                .ok_or_else(|| KnishIOError::custom("Remainder wallet required when transfer < source balance"))?))
         } else {
             None
         };
         if !params.units.is_empty() {
             if let Some(ref mut rem) = remainder_wallet {
-                source_wallet.split_units(&params.units, rem, None);
+                source_wallet.to_mut().split_units(&params.units, rem.to_mut(), None);
             }
             // Full transfer (no remainder): all units are SENT; source_wallet.token_units holds them.
         }
@@ -412,6 +451,7 @@ impl TypeSafeMoleculeBuilder<states::WithSourceWallet> {
             }
         };
         let sent_meta = units_meta(&source_wallet);
+        let mut atoms = Vec::with_capacity(3);
 
         // Atom 1: Source debit (negative full balance), carries SENT units.
         let mut source_atom = Atom::new(
@@ -427,7 +467,7 @@ impl TypeSafeMoleculeBuilder<states::WithSourceWallet> {
             sent_meta.clone(),
         );
         source_atom.value = Some((-source_balance).to_string());
-        self.molecule.add_atom(source_atom);
+        atoms.push(source_atom);
 
         // Atom 2: Recipient credit (positive transfer amount), carries SENT units.
         let mut recipient_atom = Atom::new(
@@ -443,7 +483,7 @@ impl TypeSafeMoleculeBuilder<states::WithSourceWallet> {
             sent_meta,
         );
         recipient_atom.value = Some(amount_i128.to_string());
-        self.molecule.add_atom(recipient_atom);
+        atoms.push(recipient_atom);
 
         // Atom 3: Remainder credit (change back to remainder wallet), carries KEPT units.
         if remainder > 0 {
@@ -462,15 +502,17 @@ impl TypeSafeMoleculeBuilder<states::WithSourceWallet> {
                     kept_meta,
                 );
                 remainder_atom.value = Some(remainder.to_string());
-                self.molecule.add_atom(remainder_atom);
+                atoms.push(remainder_atom);
             }
         }
 
+        for atom in atoms {
+            self.molecule.add_atom(atom);
+        }
+
         Ok(TypeSafeMoleculeBuilder {
             molecule: self.molecule,
             secret: self.secret,
-            source_wallet: self.source_wallet,
-            remainder_wallet: self.remainder_wallet,
             _phantom: PhantomData,
         })
     }
@@ -506,8 +548,6 @@ impl TypeSafeMoleculeBuilder<states::WithSourceWallet> {
         Ok(TypeSafeMoleculeBuilder {
             molecule: self.molecule,
             secret: self.secret,
-            source_wallet: self.source_wallet,
-            remainder_wallet: self.remainder_wallet,
             _phantom: PhantomData,
         })
     }
@@ -685,7 +725,7 @@ impl TypeSafeMoleculeBuilder<states::WithAtoms> {
     ///
     /// Result containing builder in same state with remainder atom added
     pub fn add_remainder_atom(mut self) -> Result<Self> {
-        let remainder_wallet = self.remainder_wallet.as_ref()
+        let remainder_wallet = self.molecule.remainder_wallet.as_ref()
             .ok_or_else(|| KnishIOError::custom("Remainder wallet not configured"))?;
 
         // Calculate the next position for the remainder atom
@@ -721,20 +761,175 @@ impl TypeSafeMoleculeBuilder<states::WithAtoms> {
         }
 
         // Validate that we have a source wallet
-        if self.source_wallet.is_none() {
+        if self.molecule.source_wallet.is_none() {
             return Err(KnishIOError::custom("Source wallet is required"));
         }
 
         Ok(TypeSafeMoleculeBuilder {
             molecule: self.molecule,
             secret: self.secret,
-            source_wallet: self.source_wallet,
-            remainder_wallet: self.remainder_wallet,
             _phantom: PhantomData,
         })
     }
 }
 
+impl TypeSafeMoleculeBuilder<states::Compound> {
+    /// Add the transfer section: debit of the source wallet, credit of `recipient` and the
+    /// change to the remainder wallet
+    ///
+    /// # Errors
+    ///
+    /// Fails without a remainder wallet, or with `BalanceInsufficient`
+    pub fn add_transfer(mut self, recipient: &Wallet, amount: f64) -> Result<Self> {
+        if self.molecule.remainder_wallet.is_none() {
+            return Err(KnishIOError::custom("Remainder wallet not configured"));
+        }
+        self.molecule.init_value(recipient, amount)?;
+        Ok(self)
+    }
+
+    /// Add a metadata atom
+    ///
+    /// Metadata lives on USER wallets: the source wallet itself when it is a USER wallet,
+    /// otherwise a fresh USER wallet of the same bundle.
+    pub fn add_meta<S: Into<String>>(mut self, meta_type: S, meta_id: S, meta: Vec<MetaItem>) -> Result<Self> {
+        let source_wallet = self.molecule.source_wallet.as_ref()
+            .ok_or_else(|| KnishIOError::custom("Source wallet is required"))?;
+        let user_token = self.molecule.system_tokens.user.as_str();
+        let meta_wallet = if source_wallet.token == user_token {
+            Cow::Borrowed(source_wallet)
+        } else {
+            let secret = self.secret.as_deref()
+                .ok_or_else(|| KnishIOError::custom("Secret is required for signing"))?;
+            Cow::Owned(Wallet::create(Some(secret), source_wallet.bundle.as_deref(), user_token, None, None)?)
+        };
+
+        let atom = Atom::new(
+            meta_wallet.position.as_deref().unwrap_or_default(),
+            meta_wallet.address.as_deref().unwrap_or_default(),
+            Isotope::M,
+            &meta_wallet.token,
+        ).with_optional_fields(
+            None,
+            None,
+            Some(&meta_type.into()),
+            Some(&meta_id.into()),
+            Some(meta),
+        );
+
+        self.molecule.add_atom(atom);
+        Ok(self)
+    }
+
+    /// Attach a policy to the most recently added metadata atom
+    ///
+    /// Keys the policy does not mention get the default rules for that atom's meta keys.
+    pub fn add_policy(mut self, policy: serde_json::Value) -> Result<Self> {
+        let atom = self.molecule.atoms.iter_mut().rev()
+            .find(|atom| atom.isotope == Isotope::M)
+            .ok_or_else(|| KnishIOError::custom("Compound molecule: a policy needs a metadata atom to attach to"))?;
+
+        let mut atom_meta = AtomMeta::new(Some(std::mem::take(&mut atom.meta)));
+        atom_meta.add_policy(policy)?;
+        atom.meta = atom_meta.meta;
+
+        self.molecule.molecular_hash = None;
+        Ok(self)
+    }
+
+    /// Check the compound atoms against each other and prepare for signing
+    pub fn ready_to_sign(self) -> Result<TypeSafeMoleculeBuilder<states::ReadyToSign>> {
+        validate_compound(&self.molecule, self.secret.as_deref())?;
+
+        Ok(TypeSafeMoleculeBuilder {
+            molecule: self.molecule,
+            secret: self.secret,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+/// Cross-atom checks for a compound molecule
+///
+/// - **Index layout**: atom indices run 0, 1, 2, ... and the value atoms come first, in
+///   one block, ahead of the metadata
+/// - **Signing atom**: atom 0 is on the source wallet (same address, position and token),
+///   since the molecule is signed with that wallet's key
+/// - **Same source wallet**: the source wallet is debited once, by atom 0, for its whole
+///   balance, and no other wallet is debited
+/// - **Metadata owner**: metadata and policy atoms are on USER wallets; with `secret`, those
+///   wallets must also be derived from it, i.e. belong to the signer
+/// - **Conservation**: value atoms are in the source token and sum to zero
+///
+/// # Errors
+///
+/// `AtomsMissing` for an empty molecule, `AtomIndex` for a broken index layout, otherwise an
+/// error naming the offending atom
+pub fn validate_compound(molecule: &Molecule, secret: Option<&str>) -> Result<()> {
+    let source = molecule.source_wallet.as_ref()
+        .ok_or_else(|| KnishIOError::custom("Source wallet is required"))?;
+    let source_address = source.address.as_deref().unwrap_or_default();
+    let source_position = source.position.as_deref().unwrap_or_default();
+    let first = molecule.atoms.first().ok_or(KnishIOError::AtomsMissing)?;
+
+    molecule.check_atom_indices()?;
+
+    let invalid = |index: usize, reason: &str| {
+        KnishIOError::custom(format!("Compound molecule: atom {} {}", index, reason))
+    };
+
+    if first.wallet_address != source_address || first.position != source_position || first.token != source.token {
+        return Err(invalid(0, "is not on the source wallet, which signs the molecule"));
+    }
+
+    let mut value_section_ended = false;
+    let mut total: i128 = 0;
+    for (index, atom) in molecule.atoms.iter().enumerate() {
+        let value = match atom.value.as_deref() {
+            Some(value) => value.parse::<i128>().map_err(|_| invalid(index, "has a non-integer value"))?,
+            None => 0,
+        };
+
+        if value < 0 && (index != 0 || atom.wallet_address != source_address) {
+            return Err(invalid(index, "debits a wallet after the source wallet's debit"));
+        }
+
+        match atom.isotope {
+            Isotope::V => {
+                if value_section_ended {
+                    return Err(invalid(index, "is a value atom after the metadata section"));
+                }
+                if atom.token != source.token {
+                    return Err(invalid(index, &format!("moves {} in a {} transfer", atom.token, source.token)));
+                }
+                if index == 0 && value != -source.whole_balance()? {
+                    return Err(invalid(index, "does not debit the whole source balance"));
+                }
+                total += value;
+            }
+            Isotope::M | Isotope::R => {
+                value_section_ended = true;
+                if atom.token != molecule.system_tokens.user {
+                    return Err(invalid(index, "carries metadata on a non-USER wallet"));
+                }
+                if let Some(secret) = secret {
+                    let owned = Wallet::create(Some(secret), None, &atom.token, Some(&atom.position), None)?;
+                    if owned.address.as_deref() != Some(atom.wallet_address.as_str()) {
+                        return Err(invalid(index, "is on a wallet the signer does not own"));
+                    }
+                }
+            }
+            _ => value_section_ended = true,
+        }
+    }
+
+    if total != 0 {
+        return Err(invalid(0, &format!("starts a transfer whose value atoms sum to {}", total)));
+    }
+
+    Ok(())
+}
+
 impl TypeSafeMoleculeBuilder<states::ReadyToSign> {
     /// Sign the molecule using WOTS+ one-time signatures
     ///
@@ -751,8 +946,6 @@ impl TypeSafeMoleculeBuilder<states::ReadyToSign> {
         Ok(TypeSafeMoleculeBuilder {
             molecule: self.molecule,
             secret: self.secret,
-            source_wallet: self.source_wallet,
-            remainder_wallet: self.remainder_wallet,
             _phantom: PhantomData,
         })
     }
@@ -787,8 +980,6 @@ impl TypeSafeMoleculeBuilder<states::ReadyToSign> {
         Ok(TypeSafeMoleculeBuilder {
             molecule: self.molecule,
             secret: self.secret,
-            source_wallet: self.source_wallet,
-            remainder_wallet: self.remainder_wallet,
             _phantom: PhantomData,
         })
     }
@@ -1700,4 +1891,61 @@ mod tests {
             .sum();
         assert_eq!(sum, 0, "Stackable transfer values must sum to 0, got {}", sum);
     }
+
+    #[test]
+    fn test_compound_validation() {
+        let secret = "compound-secret";
+        let mut source = Wallet::create(Some(secret), None, "GOLD", None, None).unwrap();
+        source.set_balance_i128(100);
+        let remainder = source.create_remainder(secret).unwrap();
+        let merchant = Wallet::create(Some("merchant"), None, "GOLD", None, None).unwrap();
+        let compound = || TypeSafeMoleculeBuilder::new(secret)
+            .with_source_wallet(source.clone())
+            .with_remainder_wallet(remainder.clone())
+            .compound();
+        let invoice = || vec![MetaItem::new("total", "30"), MetaItem::new("currency", "GOLD")];
+
+        let signed = compound()
+            .add_transfer(&merchant, 30.0).unwrap()
+            .add_meta("invoice", "INV-1", invoice()).unwrap()
+            .add_policy(serde_json::json!({ "read": { "total": ["self"] } })).unwrap()
+            .ready_to_sign().unwrap()
+            .sign_sync().unwrap();
+        let molecule = signed.as_molecule();
+        molecule.check(Some(&source)).unwrap();
+        assert_eq!(molecule.atoms[3].isotope, Isotope::M);
+        assert!(molecule.atoms[3].meta.iter().any(|m| m.key == "policy"));
+
+        // Metadata ahead of the transfer: the debit is no longer the signing atom
+        let error = compound()
+            .add_meta("invoice", "INV-2", invoice()).unwrap()
+            .add_transfer(&merchant, 30.0).unwrap()
+            .ready_to_sign().err().unwrap();
+        assert!(error.to_string().contains("atom 0 is not on the source wallet"), "{}", error);
+
+        // The source wallet can only be spent once
+        let error = compound()
+            .add_transfer(&merchant, 30.0).unwrap()
+            .add_transfer(&merchant, 10.0).unwrap()
+            .ready_to_sign().err().unwrap();
+        assert!(error.to_string().contains("atom 3 debits"), "{}", error);
+
+        assert!(compound().add_policy(serde_json::json!({})).is_err());
+        assert!(matches!(compound().add_transfer(&merchant, 500.0), Err(KnishIOError::BalanceInsufficient)));
+        assert!(matches!(compound().ready_to_sign(), Err(KnishIOError::AtomsMissing)));
+
+        // Layout problems introduced below the builder
+        let builder = compound().add_transfer(&merchant, 30.0).unwrap();
+        let mut molecule = builder.molecule().clone();
+        molecule.atoms.swap(1, 2);
+        assert!(matches!(validate_compound(&molecule, None), Err(KnishIOError::AtomIndex)));
+        molecule.reindex();
+        molecule.atoms[2].value = Some("20".to_string());
+        assert!(validate_compound(&molecule, None).unwrap_err().to_string().contains("sum to -10"));
+
+        // Metadata on someone else's USER wallet
+        let builder = compound().add_transfer(&merchant, 30.0).unwrap().add_meta("invoice", "INV-3", invoice()).unwrap();
+        assert!(validate_compound(builder.molecule(), Some(secret)).is_ok());
+        assert!(validate_compound(builder.molecule(), Some("another-secret")).unwrap_err().to_string().contains("does not own"));
+    }
 }
\ No newline at end of file
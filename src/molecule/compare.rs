@@ -0,0 +1,475 @@
+//! Structured comparison of molecule JSON documents
+//!
+//! When two SDKs produce different molecular hashes for what should be the same
+//! transaction, the cause is usually one of a handful of things: atoms in a different
+//! order, meta serialized differently (key order, numbers vs strings), a differing
+//! header field, or a different OTS fragment. `diff` walks two molecule JSON documents
+//! and reports each of those differences with its JSON path.
+//!
+//! Reordered atoms are paired up with their counterparts before they are compared, so a
+//! reordering is reported once and the differences inside each atom still show; atom
+//! paths use the left-hand index.
+
+use serde_json::Value;
+use std::fmt;
+
+/// Header fields compared between the two molecules
+const HEADER_FIELDS: [&str; 7] = ["bundle", "cellSlug", "cellSlugOrigin", "version", "createdAt", "status", "parentHashes"];
+
+/// Atom fields that identify an atom when pairing up reordered atoms
+const ATOM_IDENTITY_FIELDS: [&str; 8] = ["isotope", "walletAddress", "position", "token", "value", "batchId", "metaType", "metaId"];
+
+/// What kind of difference a `DiffEntry` describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiffCategory {
+    /// The molecular hashes differ
+    Hash,
+    /// A molecule-level field (bundle, createdAt, ...) differs
+    Header,
+    /// The molecules carry a different number of atoms
+    AtomCount,
+    /// The same atoms are present but in a different order
+    AtomOrder,
+    /// A scalar atom field differs at the same index
+    AtomField,
+    /// Atom meta differs (values, key order or value types)
+    Meta,
+    /// The OTS fragments differ
+    OtsFragment,
+}
+
+/// A single difference between two molecule documents
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffEntry {
+    /// Kind of difference
+    pub category: DiffCategory,
+    /// JSON path of the differing field (e.g. `atoms[2].meta[0].value`)
+    pub path: String,
+    /// Value in the left document (None when absent)
+    pub left: Option<Value>,
+    /// Value in the right document (None when absent)
+    pub right: Option<Value>,
+    /// Human-readable explanation
+    pub note: String,
+}
+
+/// Structured report of all differences between two molecule documents
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MoleculeDiff {
+    /// Differences in document order
+    pub entries: Vec<DiffEntry>,
+}
+
+impl MoleculeDiff {
+    /// True when no differences were found
+    pub fn is_identical(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// All differences of the given category
+    pub fn by_category(&self, category: DiffCategory) -> Vec<&DiffEntry> {
+        self.entries.iter().filter(|e| e.category == category).collect()
+    }
+
+    /// True when at least one difference of the given category was found
+    pub fn has(&self, category: DiffCategory) -> bool {
+        self.entries.iter().any(|e| e.category == category)
+    }
+
+    fn push(&mut self, category: DiffCategory, path: impl Into<String>, left: Option<&Value>, right: Option<&Value>, note: impl Into<String>) {
+        self.entries.push(DiffEntry {
+            category,
+            path: path.into(),
+            left: left.cloned(),
+            right: right.cloned(),
+            note: note.into(),
+        });
+    }
+}
+
+impl fmt::Display for MoleculeDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.entries.is_empty() {
+            return writeln!(f, "Molecules are identical");
+        }
+
+        writeln!(f, "{} difference(s):", self.entries.len())?;
+        for entry in &self.entries {
+            writeln!(f, "  [{:?}] {}: {}", entry.category, entry.path, entry.note)?;
+            writeln!(f, "      left:  {}", render(entry.left.as_ref()))?;
+            writeln!(f, "      right: {}", render(entry.right.as_ref()))?;
+        }
+        Ok(())
+    }
+}
+
+fn render(value: Option<&Value>) -> String {
+    match value {
+        None => "<absent>".to_string(),
+        Some(Value::String(s)) if s.chars().count() > 80 => {
+            format!("\"{}…\" ({} chars)", s.chars().take(80).collect::<String>(), s.chars().count())
+        }
+        Some(v) => v.to_string(),
+    }
+}
+
+/// Compare two molecule JSON documents
+///
+/// Accepts the output of `Molecule::to_json` (or any other SDK's molecule JSON) on
+/// both sides. A missing field and an explicit `null` are treated as equal.
+pub fn diff(a: &Value, b: &Value) -> MoleculeDiff {
+    let mut report = MoleculeDiff::default();
+
+    let hash_a = non_null(a.get("molecularHash"));
+    let hash_b = non_null(b.get("molecularHash"));
+    if hash_a != hash_b {
+        report.push(DiffCategory::Hash, "molecularHash", hash_a, hash_b, "molecular hashes differ");
+    }
+
+    for field in HEADER_FIELDS {
+        let left = non_null(a.get(field));
+        let right = non_null(b.get(field));
+        if left != right {
+            report.push(DiffCategory::Header, field, left, right, format!("{} differs", field));
+        }
+    }
+
+    let empty = Vec::new();
+    let atoms_a = a.get("atoms").and_then(|v| v.as_array()).unwrap_or(&empty);
+    let atoms_b = b.get("atoms").and_then(|v| v.as_array()).unwrap_or(&empty);
+
+    if atoms_a.len() != atoms_b.len() {
+        report.push(
+            DiffCategory::AtomCount,
+            "atoms",
+            Some(&Value::from(atoms_a.len())),
+            Some(&Value::from(atoms_b.len())),
+            "atom counts differ",
+        );
+    }
+
+    // Pair every left atom with its right-hand counterpart, then compare each pair
+    let pairs = align_atoms(atoms_a, atoms_b);
+    if pairs.iter().any(|(left, right)| left != right) {
+        let mut order = vec![Value::Null; atoms_a.len()];
+        for (left, right) in &pairs {
+            order[*left] = Value::from(*right);
+        }
+        report.push(
+            DiffCategory::AtomOrder,
+            "atoms",
+            None,
+            Some(&Value::Array(order)),
+            "atoms in a different order (right holds the right-hand index of each left atom)",
+        );
+    }
+
+    for (left, right) in pairs {
+        diff_atom(&mut report, left, &atoms_a[left], &atoms_b[right]);
+    }
+
+    report
+}
+
+/// Compare two molecule JSON strings
+pub fn diff_str(a: &str, b: &str) -> crate::error::Result<MoleculeDiff> {
+    let a: Value = serde_json::from_str(a)?;
+    let b: Value = serde_json::from_str(b)?;
+    Ok(diff(&a, &b))
+}
+
+fn non_null(value: Option<&Value>) -> Option<&Value> {
+    value.filter(|v| !v.is_null())
+}
+
+/// Pair up the atoms of both sides as `(left index, right index)`, in left-hand order
+///
+/// Atoms are matched one-to-one: first on their full content, then on their identity
+/// fields alone (so an atom whose meta or OTS fragment differs still finds its
+/// counterpart), and whatever is left over pairs up in order. Among equal candidates the
+/// atom at the same index wins, then the earliest unused one. Atoms without a
+/// counterpart (differing atom counts) are left out.
+fn align_atoms(atoms_a: &[Value], atoms_b: &[Value]) -> Vec<(usize, usize)> {
+    let mut partner: Vec<Option<usize>> = vec![None; atoms_a.len()];
+    let mut used = vec![false; atoms_b.len()];
+
+    for key in [atom_content as fn(&Value) -> String, atom_identity] {
+        let keys_b: Vec<String> = atoms_b.iter().map(key).collect();
+        for (left, atom) in atoms_a.iter().enumerate() {
+            if partner[left].is_some() {
+                continue;
+            }
+            let wanted = key(atom);
+            let candidate = Some(left)
+                .filter(|&right| right < keys_b.len() && !used[right] && keys_b[right] == wanted)
+                .or_else(|| (0..keys_b.len()).find(|&right| !used[right] && keys_b[right] == wanted));
+            if let Some(right) = candidate {
+                partner[left] = Some(right);
+                used[right] = true;
+            }
+        }
+    }
+
+    let mut leftovers = (0..atoms_b.len()).filter(|&right| !used[right]);
+    partner.iter()
+        .enumerate()
+        .filter_map(|(left, right)| match right {
+            Some(right) => Some((left, *right)),
+            None => leftovers.next().map(|right| (left, right)),
+        })
+        .collect()
+}
+
+fn atom_identity(atom: &Value) -> String {
+    ATOM_IDENTITY_FIELDS.iter()
+        .map(|field| non_null(atom.get(*field)).map(scalar_string).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+/// Identity fields plus meta (in key order) and OTS fragment
+fn atom_content(atom: &Value) -> String {
+    let mut meta = meta_pairs(non_null(atom.get("meta")));
+    meta.sort_by(|x, y| x.0.cmp(&y.0));
+    let meta: Vec<String> = meta.iter().map(|(key, value)| format!("{}={}", key, scalar_string(value))).collect();
+    let ots = non_null(atom.get("otsFragment")).map(scalar_string).unwrap_or_default();
+
+    format!("{}|{}|{}", atom_identity(atom), meta.join(","), ots)
+}
+
+/// Render a scalar without JSON quoting so `"1"` and `1` compare equal for identity purposes
+fn scalar_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn diff_atom(report: &mut MoleculeDiff, index: usize, a: &Value, b: &Value) {
+    let empty = serde_json::Map::new();
+    let obj_a = a.as_object().unwrap_or(&empty);
+    let obj_b = b.as_object().unwrap_or(&empty);
+
+    let mut fields: Vec<&String> = obj_a.keys().chain(obj_b.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    for field in fields {
+        let path = format!("atoms[{}].{}", index, field);
+        let left = non_null(obj_a.get(field));
+        let right = non_null(obj_b.get(field));
+
+        match field.as_str() {
+            "meta" => diff_meta(report, &path, left, right),
+            "otsFragment" => {
+                if left != right {
+                    let note = match (left.and_then(|v| v.as_str()), right.and_then(|v| v.as_str())) {
+                        (Some(l), Some(r)) => {
+                            let offset = l.chars().zip(r.chars()).take_while(|(x, y)| x == y).count();
+                            format!("OTS fragments diverge at character {}", offset)
+                        }
+                        _ => "OTS fragment present on one side only".to_string(),
+                    };
+                    report.push(DiffCategory::OtsFragment, path, left, right, note);
+                }
+            }
+            _ => {
+                if left != right {
+                    let note = match (left, right) {
+                        (Some(l), Some(r)) if scalar_string(l) == scalar_string(r) => {
+                            "same value, different JSON type".to_string()
+                        }
+                        _ => format!("{} differs", field),
+                    };
+                    report.push(DiffCategory::AtomField, path, left, right, note);
+                }
+            }
+        }
+    }
+}
+
+fn diff_meta(report: &mut MoleculeDiff, path: &str, left: Option<&Value>, right: Option<&Value>) {
+    if left == right {
+        return;
+    }
+
+    let pairs_a = meta_pairs(left);
+    let pairs_b = meta_pairs(right);
+
+    let mut sorted_a = pairs_a.clone();
+    let mut sorted_b = pairs_b.clone();
+    sorted_a.sort_by(|x, y| x.0.cmp(&y.0));
+    sorted_b.sort_by(|x, y| x.0.cmp(&y.0));
+
+    let keys_a: Vec<&String> = sorted_a.iter().map(|(k, _)| k).collect();
+    let keys_b: Vec<&String> = sorted_b.iter().map(|(k, _)| k).collect();
+
+    if sorted_a == sorted_b {
+        report.push(DiffCategory::Meta, path, left, right, "same meta entries in a different key order");
+        return;
+    }
+
+    if keys_a == keys_b {
+        for ((key, value_a), (_, value_b)) in sorted_a.iter().zip(sorted_b.iter()) {
+            if value_a != value_b {
+                let note = if scalar_string(value_a) == scalar_string(value_b) {
+                    format!("meta '{}' has the same value with a different JSON type", key)
+                } else {
+                    format!("meta '{}' differs", key)
+                };
+                report.push(DiffCategory::Meta, format!("{}.{}", path, key), Some(value_a), Some(value_b), note);
+            }
+        }
+        return;
+    }
+
+    report.push(DiffCategory::Meta, path, left, right, "meta keys differ");
+}
+
+/// Normalize either meta shape (`[{key, value}]` or `{key: value}`) into ordered pairs
+fn meta_pairs(meta: Option<&Value>) -> Vec<(String, Value)> {
+    match meta {
+        Some(Value::Array(items)) => items.iter()
+            .filter_map(|item| {
+                let key = item.get("key")?.as_str()?.to_string();
+                Some((key, item.get("value").cloned().unwrap_or(Value::Null)))
+            })
+            .collect(),
+        Some(Value::Object(map)) => map.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample() -> Value {
+        json!({
+            "molecularHash": "abc",
+            "bundle": "bundle",
+            "createdAt": "1700000000000",
+            "atoms": [
+                {
+                    "position": "p1", "walletAddress": "w1", "isotope": "V", "token": "TEST",
+                    "value": "-10", "meta": [], "otsFragment": "AAAA"
+                },
+                {
+                    "position": "p2", "walletAddress": "w2", "isotope": "V", "token": "TEST",
+                    "value": "10", "meta": [{"key": "a", "value": "1"}, {"key": "b", "value": "2"}]
+                }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_identical_molecules() {
+        let report = diff(&sample(), &sample());
+        assert!(report.is_identical());
+    }
+
+    #[test]
+    fn test_hash_and_header_differences() {
+        let mut other = sample();
+        other["molecularHash"] = json!("xyz");
+        other["createdAt"] = json!("1700000000001");
+
+        let report = diff(&sample(), &other);
+        assert!(report.has(DiffCategory::Hash));
+        assert_eq!(report.by_category(DiffCategory::Header)[0].path, "createdAt");
+    }
+
+    #[test]
+    fn test_atom_order_detected() {
+        let mut other = sample();
+        let atoms = other["atoms"].as_array_mut().unwrap();
+        atoms.swap(0, 1);
+
+        let report = diff(&sample(), &other);
+        assert!(report.has(DiffCategory::AtomOrder));
+        assert!(!report.has(DiffCategory::AtomField));
+        assert_eq!(report.by_category(DiffCategory::AtomOrder)[0].right, Some(json!([1, 0])));
+    }
+
+    #[test]
+    fn test_reordered_atoms_are_still_compared() {
+        let mut other = sample();
+        other["atoms"].as_array_mut().unwrap().swap(0, 1);
+        other["atoms"][0]["meta"] = json!([{"key": "a", "value": "1"}, {"key": "b", "value": "3"}]);
+        other["atoms"][1]["otsFragment"] = json!("AABB");
+        other["atoms"][1]["index"] = json!(1);
+
+        let report = diff(&sample(), &other);
+        assert_eq!(report.by_category(DiffCategory::AtomOrder)[0].right, Some(json!([1, 0])));
+        assert_eq!(report.by_category(DiffCategory::Meta)[0].path, "atoms[1].meta.b");
+        assert_eq!(report.by_category(DiffCategory::OtsFragment)[0].path, "atoms[0].otsFragment");
+        assert_eq!(report.by_category(DiffCategory::AtomField)[0].path, "atoms[0].index");
+    }
+
+    #[test]
+    fn test_duplicate_atoms_pair_one_to_one() {
+        // Same identity fields, told apart by meta only
+        let atom = |label: &str| json!({
+            "position": "p", "walletAddress": "w", "isotope": "M", "token": "USER",
+            "metaType": "note", "metaId": "n", "meta": [{"key": "label", "value": label}]
+        });
+        let left = json!({ "atoms": [atom("x"), atom("y"), atom("z")] });
+        let right = json!({ "atoms": [atom("z"), atom("x"), atom("y")] });
+
+        let report = diff(&left, &right);
+        assert_eq!(report.by_category(DiffCategory::AtomOrder)[0].right, Some(json!([1, 2, 0])));
+        assert_eq!(report.entries.len(), 1);
+
+        // Two atoms identical in every field match each other in place
+        let twins = json!({ "atoms": [atom("x"), atom("x")] });
+        assert!(diff(&twins, &twins).is_identical());
+
+        // One changed atom among duplicates: the others keep their pairing
+        let changed = json!({ "atoms": [atom("y"), atom("x"), atom("w")] });
+        let report = diff(&left, &changed);
+        assert_eq!(report.by_category(DiffCategory::AtomOrder)[0].right, Some(json!([1, 0, 2])));
+        assert_eq!(report.by_category(DiffCategory::Meta)[0].path, "atoms[2].meta.label");
+    }
+
+    #[test]
+    fn test_meta_differences() {
+        let mut reordered = sample();
+        reordered["atoms"][1]["meta"] = json!([{"key": "b", "value": "2"}, {"key": "a", "value": "1"}]);
+        let report = diff(&sample(), &reordered);
+        assert_eq!(report.by_category(DiffCategory::Meta)[0].note, "same meta entries in a different key order");
+
+        let mut retyped = sample();
+        retyped["atoms"][1]["meta"] = json!([{"key": "a", "value": 1}, {"key": "b", "value": "2"}]);
+        let report = diff(&sample(), &retyped);
+        let meta = report.by_category(DiffCategory::Meta);
+        assert_eq!(meta[0].path, "atoms[1].meta.a");
+        assert!(meta[0].note.contains("different JSON type"));
+    }
+
+    #[test]
+    fn test_ots_fragment_and_count() {
+        let mut other = sample();
+        other["atoms"][0]["otsFragment"] = json!("AABB");
+        let report = diff(&sample(), &other);
+        let ots = report.by_category(DiffCategory::OtsFragment);
+        assert_eq!(ots.len(), 1);
+        assert!(ots[0].note.contains("character 2"));
+
+        let mut shorter = sample();
+        shorter["atoms"].as_array_mut().unwrap().pop();
+        assert!(diff(&sample(), &shorter).has(DiffCategory::AtomCount));
+    }
+
+    #[test]
+    fn test_diff_str_and_display() {
+        let a = sample().to_string();
+        let report = diff_str(&a, &a).unwrap();
+        assert_eq!(report.to_string(), "Molecules are identical\n");
+        assert!(diff_str(&a, "not json").is_err());
+
+        // Long unicode values are cut on a character boundary
+        let long = "é".repeat(100);
+        assert_eq!(render(Some(&Value::String(long))), format!("\"{}…\" (100 chars)", "é".repeat(80)));
+    }
+}
@@ -0,0 +1,265 @@
+//! Human-readable molecule summaries
+//!
+//! `Molecule::describe` turns each atom into an intent such as "debit 50 FOO from
+//! W1a2b3c4…" or "attach receipt R-1 meta (amount, memo)", for confirmation dialogs
+//! before signing and for audit logs afterwards. Every isotope the builders emit has a
+//! phrasing; anything else falls back to a generic line naming the isotope.
+
+use crate::atom::Atom;
+use crate::molecule::Molecule;
+use crate::types::Isotope;
+use serde::Serialize;
+use std::fmt;
+
+/// What an atom does, as far as a reader is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IntentAction {
+    /// V-atom removing tokens from a wallet
+    Debit,
+    /// V-atom adding tokens to another bundle's wallet
+    Credit,
+    /// V-atom returning change to the signer's own bundle
+    Remainder,
+    /// C-atom creating a token
+    CreateToken,
+    /// C-atom creating a wallet, identifier or other object
+    Create,
+    /// M-atom attaching metadata
+    AttachMeta,
+    /// I-atom advancing the ContinuID chain
+    ContinuId,
+    /// T-atom requesting tokens
+    RequestTokens,
+    /// U-atom requesting an authorization token
+    Authorize,
+    /// R-atom setting a policy
+    SetPolicy,
+    /// B-atom moving tokens into a buffer
+    BufferDeposit,
+    /// B-atom moving tokens out of a buffer
+    BufferWithdraw,
+    /// F-atom fusing token units
+    Fuse,
+    /// P-atom peering with another node
+    Peer,
+    /// A-atom requesting an append
+    Append,
+}
+
+/// One atom's intent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtomIntent {
+    /// Index of the atom in the molecule
+    pub index: usize,
+    /// Atom isotope
+    pub isotope: Isotope,
+    /// What the atom does
+    pub action: IntentAction,
+    /// Token slug
+    pub token: String,
+    /// Unsigned amount, for atoms that carry a value
+    pub amount: Option<String>,
+    /// Wallet address the atom is signed for
+    pub wallet_address: String,
+    /// Meta type, when set
+    pub meta_type: Option<String>,
+    /// Meta ID, when set
+    pub meta_id: Option<String>,
+    /// Keys of the attached meta, in order
+    pub meta_keys: Vec<String>,
+    /// Human-readable description
+    pub text: String,
+}
+
+/// Intents of all atoms of a molecule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoleculeSummary {
+    /// Molecular hash, once signed
+    pub molecular_hash: Option<String>,
+    /// Signer's bundle hash
+    pub bundle: Option<String>,
+    /// One intent per atom, in atom order
+    pub intents: Vec<AtomIntent>,
+}
+
+impl MoleculeSummary {
+    /// Intents of the given action
+    pub fn by_action(&self, action: IntentAction) -> Vec<&AtomIntent> {
+        self.intents.iter().filter(|intent| intent.action == action).collect()
+    }
+}
+
+impl fmt::Display for MoleculeSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let texts: Vec<&str> = self.intents.iter().map(|intent| intent.text.as_str()).collect();
+        f.write_str(&texts.join(", "))
+    }
+}
+
+impl Molecule {
+    /// Summarize what this molecule does, one intent per atom
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use knishio_client::{Atom, Molecule};
+    /// use knishio_client::types::{Isotope, MetaItem};
+    ///
+    /// let mut molecule = Molecule::new();
+    /// molecule.bundle = Some("b".repeat(64));
+    /// let mut debit = Atom::new("p1", "a1b2c3d4e5f60718", Isotope::V, "FOO");
+    /// debit.value = Some("-50".to_string());
+    /// let mut credit = Atom::new("p2", "f0e1d2c3b4a59687", Isotope::V, "FOO");
+    /// credit.value = Some("50".to_string());
+    /// credit.meta_type = Some("walletBundle".to_string());
+    /// credit.meta_id = Some("c".repeat(64));
+    /// let mut receipt = Atom::new("p3", "a1b2c3d4e5f60718", Isotope::M, "USER");
+    /// receipt.meta_type = Some("receipt".to_string());
+    /// receipt.meta_id = Some("R-1".to_string());
+    /// receipt.meta = vec![MetaItem::new("memo", "rent")];
+    /// molecule.atoms = vec![debit, credit, receipt];
+    ///
+    /// assert_eq!(
+    ///     molecule.describe().to_string(),
+    ///     "debit 50 FOO from a1b2c3d4…, credit 50 FOO to bundle cccccccc…, attach receipt R-1 meta (memo)"
+    /// );
+    /// ```
+    pub fn describe(&self) -> MoleculeSummary {
+        MoleculeSummary {
+            molecular_hash: self.molecular_hash.clone(),
+            bundle: self.bundle.clone(),
+            intents: self.atoms.iter().enumerate()
+                .map(|(index, atom)| describe_atom(index, atom, self.bundle.as_deref()))
+                .collect(),
+        }
+    }
+}
+
+fn describe_atom(index: usize, atom: &Atom, bundle: Option<&str>) -> AtomIntent {
+    let value = atom.value.as_deref().filter(|value| !value.is_empty());
+    let negative = value.is_some_and(|value| value.starts_with('-'));
+    let amount = value.map(|value| value.trim_start_matches('-').to_string());
+    let shown = amount.as_deref().unwrap_or("0");
+    let token = atom.token.as_str();
+    let wallet = short(&atom.wallet_address);
+    let meta_type = atom.meta_type.as_deref().unwrap_or_default();
+    let meta_id = atom.meta_id.as_deref().unwrap_or_default();
+    let meta_keys: Vec<String> = atom.meta.iter().map(|meta| meta.key.clone()).collect();
+    let meta_value = |key: &str| atom.meta.iter().find(|meta| meta.key == key).map(|meta| meta.value.as_str());
+    let recipient = if meta_type == "walletBundle" && !meta_id.is_empty() {
+        format!("bundle {}", short(meta_id))
+    } else {
+        wallet.clone()
+    };
+
+    let (action, mut text) = match atom.isotope {
+        Isotope::V if negative => (IntentAction::Debit, format!("debit {} {} from {}", shown, token, wallet)),
+        Isotope::V if bundle.is_some_and(|own| own == meta_id) => {
+            (IntentAction::Remainder, format!("return {} {} to own wallet {}", shown, token, wallet))
+        }
+        Isotope::V => (IntentAction::Credit, format!("credit {} {} to {}", shown, token, recipient)),
+        Isotope::C if meta_type == "token" => {
+            (IntentAction::CreateToken, format!("create token {} with supply {}", meta_id, shown))
+        }
+        Isotope::C => (IntentAction::Create, format!("create {} {}", meta_type, short(meta_id))),
+        Isotope::M => (IntentAction::AttachMeta, format!("attach {} {} meta", meta_type, meta_id)),
+        Isotope::I => (IntentAction::ContinuId, format!("advance ContinuID to {}", wallet)),
+        Isotope::T => (
+            IntentAction::RequestTokens,
+            format!("request {} {} for {} {}", shown, meta_value("token").unwrap_or(token), meta_type, short(meta_id)),
+        ),
+        Isotope::U => (IntentAction::Authorize, format!("request authorization for {}", wallet)),
+        Isotope::R => (IntentAction::SetPolicy, format!("set policy on {} {}", meta_type, meta_id)),
+        Isotope::B if negative => (IntentAction::BufferWithdraw, format!("withdraw {} {} from buffer {}", shown, token, wallet)),
+        Isotope::B => (IntentAction::BufferDeposit, format!("deposit {} {} into buffer {}", shown, token, wallet)),
+        Isotope::F => (IntentAction::Fuse, format!("fuse {} units into {}", token, recipient)),
+        Isotope::P => (IntentAction::Peer, format!("peer with {}", meta_value("peerHost").unwrap_or(meta_id))),
+        Isotope::A => (IntentAction::Append, format!("request append to {} {}", meta_type, meta_id)),
+    };
+
+    // Value and fusion atoms carry wallet/unit bookkeeping; other atoms' meta is user data
+    if !meta_keys.is_empty() && !matches!(atom.isotope, Isotope::V | Isotope::F | Isotope::C | Isotope::I | Isotope::U) {
+        text.push_str(&format!(" ({})", meta_keys.join(", ")));
+    }
+
+    AtomIntent {
+        index,
+        isotope: atom.isotope,
+        action,
+        token: atom.token.clone(),
+        amount,
+        wallet_address: atom.wallet_address.clone(),
+        meta_type: atom.meta_type.clone(),
+        meta_id: atom.meta_id.clone(),
+        meta_keys,
+        text,
+    }
+}
+
+/// First 8 characters of a hash, for display
+fn short(hash: &str) -> String {
+    match hash.char_indices().nth(8) {
+        Some((end, _)) if hash.len() > 12 => format!("{}…", &hash[..end]),
+        _ => hash.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MetaItem;
+    use crate::wallet::Wallet;
+
+    #[test]
+    fn test_describe_transfer() {
+        let secret = "d".repeat(2048);
+        let mut source = Wallet::create(Some(&secret), None, "FOO", None, None).unwrap();
+        source.set_balance_i128(100);
+        let remainder = source.create_remainder(&secret).unwrap();
+        let recipient = Wallet::create(None, Some(&"e".repeat(64)), "FOO", None, None).unwrap();
+
+        let mut molecule = Molecule::new();
+        molecule.bundle = source.bundle.clone();
+        molecule.source_wallet = Some(source.clone());
+        molecule.remainder_wallet = Some(remainder);
+        molecule.init_value(&recipient, 30.0).unwrap();
+
+        let summary = molecule.describe();
+        let actions: Vec<IntentAction> = summary.intents.iter().map(|intent| intent.action).collect();
+        assert_eq!(actions, vec![IntentAction::Debit, IntentAction::Credit, IntentAction::Remainder]);
+        assert_eq!(summary.intents[0].amount.as_deref(), Some("100"));
+        assert_eq!(summary.intents[1].text, "credit 30 FOO to bundle eeeeeeee…");
+        assert_eq!(summary.intents[2].text, format!("return 70 FOO to own wallet {}", short(molecule.atoms[2].wallet_address.as_str())));
+        assert_eq!(summary.by_action(IntentAction::Credit).len(), 1);
+    }
+
+    #[test]
+    fn test_describe_other_isotopes() {
+        let mut molecule = Molecule::new();
+        let mut add = |isotope: Isotope, value: Option<&str>, meta_type: &str, meta_id: &str, meta: Vec<MetaItem>| {
+            let mut atom = Atom::new("pos", "address0123456789", isotope, "FOO");
+            atom.value = value.map(str::to_string);
+            atom.meta_type = Some(meta_type.to_string());
+            atom.meta_id = Some(meta_id.to_string());
+            atom.meta = meta;
+            molecule.atoms.push(atom);
+        };
+        add(Isotope::C, Some("1000"), "token", "FOO", vec![MetaItem::new("name", "Foo")]);
+        add(Isotope::M, None, "receipt", "R-1", vec![MetaItem::new("memo", "rent"), MetaItem::new("amount", "50")]);
+        add(Isotope::T, Some("5"), "walletBundle", "bundle", vec![MetaItem::new("token", "BAR")]);
+        add(Isotope::B, Some("-5"), "walletBundle", "bundle", Vec::new());
+        add(Isotope::R, None, "car", "VIN1", vec![MetaItem::new("policy", "{}")]);
+
+        let texts: Vec<String> = molecule.describe().intents.into_iter().map(|intent| intent.text).collect();
+        assert_eq!(texts, vec![
+            "create token FOO with supply 1000",
+            "attach receipt R-1 meta (memo, amount)",
+            "request 5 BAR for walletBundle bundle (token)",
+            "withdraw 5 FOO from buffer address0…",
+            "set policy on car VIN1 (policy)",
+        ]);
+    }
+}
@@ -5,19 +5,26 @@
 //! the JavaScript SDK, particularly the critical one-time signature algorithm.
 
 pub mod builder;
+pub mod compare;
+pub mod describe;
+pub mod signing_trace;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use serde::{Deserialize, Serialize};
 use crate::atom::{Atom, AtomCreateParams, WalletInfo};
-use crate::wallet::Wallet;
-use crate::crypto::{shake256, generate_bundle_hash};
-use crate::types::{Isotope, MetaItem};
+use crate::wallet::{Characters, Wallet, WalletParams};
+use crate::crypto::{generate_bundle_hash, hash_chains};
+use crate::types::{Isotope, MetaItem, SystemTokens};
 use crate::meta::AtomMeta;
 use crate::error::{KnishIOError, Result};
+use crate::check_molecule::ValidatorRegistry;
 use base64::{Engine as _, engine::general_purpose};
 
 // Re-export the type-safe builder for convenience
 pub use builder::{TypeSafeMoleculeBuilder, ValueAtomParams, MetaAtomParams, IdentityAtomParams, TokenRequestAtomParams, BufferDepositAtomParams, BufferWithdrawAtomParams, FusionAtomParams, StackableTransferParams};
+pub use compare::{diff, DiffCategory, DiffEntry, MoleculeDiff};
+pub use describe::{AtomIntent, IntentAction, MoleculeSummary};
+pub use signing_trace::{FragmentBoundary, SigningTrace, TraceDivergence};
 
 /// Helper function to chunk a string into pieces of specified size
 /// Equivalent to JavaScript's chunkSubstr function
@@ -36,6 +43,20 @@ fn chunk_string(s: &str, chunk_size: usize) -> Vec<String> {
     chunks
 }
 
+/// `tradeRates` meta for a buffer B atom: the rates as a JSON object sorted by token slug,
+/// so the molecular hash does not depend on map order. None when there are no rates.
+fn trade_rates_meta(trade_rates: &HashMap<String, f64>) -> Result<Option<Vec<MetaItem>>> {
+    if trade_rates.values().any(|rate| !rate.is_finite() || *rate < 0.0) {
+        return Err(KnishIOError::NegativeAmount);
+    }
+    if trade_rates.is_empty() {
+        return Ok(None);
+    }
+
+    let sorted: BTreeMap<&String, &f64> = trade_rates.iter().collect();
+    Ok(Some(vec![MetaItem::new("tradeRates", serde_json::to_string(&sorted)?)]))
+}
+
 /// Represents a molecular transaction containing multiple atomic operations
 ///
 /// Molecules are the fundamental units of transaction on the KnishIO distributed ledger,
@@ -50,7 +71,10 @@ pub struct Molecule {
     /// Bundle hash - 64-character hexadecimal user identifier
     pub bundle: Option<String>,
     
-    /// Creation timestamp
+    /// Creation timestamp, milliseconds since the Unix epoch
+    ///
+    /// Hashed as this string; read it with `created_at_millis` or `created_at_datetime`.
+    #[serde(deserialize_with = "crate::types::deserialize_created_at")]
     pub created_at: String,
     
     /// Status of the molecule
@@ -92,6 +116,110 @@ pub struct Molecule {
     /// USER ContinuID head position for the I-atom's previousPosition metadata.
     #[serde(skip)]
     pub continuid_position: Option<String>,
+
+    /// Local-only annotations (correlation IDs, labels); never serialized or hashed
+    #[serde(skip)]
+    pub annotations: BTreeMap<String, String>,
+
+    /// Token slugs of ContinuID and authorization wallets, `USER` and `AUTH` by default
+    #[serde(skip)]
+    pub system_tokens: SystemTokens,
+
+    /// Hash of the atoms as they were when `sign` last ran; atoms that no longer hash to
+    /// it were changed after signing
+    #[serde(skip)]
+    signed_hash: Option<String>,
+
+    /// Record the intermediates of `sign` (see `signing_trace`)
+    #[serde(skip)]
+    trace_signing: bool,
+
+    /// Trace of the last `sign` run with tracing on
+    #[serde(skip)]
+    signing_trace: Option<SigningTrace>,
+}
+
+/// Parameters for `Molecule::from_params`
+///
+/// ```
+/// use knishio_client::{Molecule, MoleculeParams, Wallet};
+///
+/// let secret = knishio_client::generate_secret("alice");
+/// let source = Wallet::create(Some(&secret), None, "USER", None, None).unwrap();
+/// let molecule = Molecule::from_params(MoleculeParams::new().secret(secret).source_wallet(source));
+/// assert!(molecule.remainder_wallet.is_some());
+/// ```
+#[derive(Clone, Default)]
+pub struct MoleculeParams {
+    /// 2048-character biometric hash
+    pub secret: Option<String>,
+    /// 64-character hexadecimal user identifier
+    pub bundle: Option<String>,
+    /// Source wallet for transactions
+    pub source_wallet: Option<Wallet>,
+    /// Remainder wallet for change (derived from the source wallet if None)
+    pub remainder_wallet: Option<Wallet>,
+    /// Cell slug for sharding
+    pub cell_slug: Option<String>,
+    /// Version identifier
+    pub version: Option<String>,
+}
+
+impl MoleculeParams {
+    /// Empty parameters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the secret
+    pub fn secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    /// Set the bundle hash
+    pub fn bundle(mut self, bundle: impl Into<String>) -> Self {
+        self.bundle = Some(bundle.into());
+        self
+    }
+
+    /// Set the source wallet
+    pub fn source_wallet(mut self, wallet: Wallet) -> Self {
+        self.source_wallet = Some(wallet);
+        self
+    }
+
+    /// Set the remainder wallet
+    pub fn remainder_wallet(mut self, wallet: Wallet) -> Self {
+        self.remainder_wallet = Some(wallet);
+        self
+    }
+
+    /// Set the cell slug
+    pub fn cell_slug(mut self, cell_slug: impl Into<String>) -> Self {
+        self.cell_slug = Some(cell_slug.into());
+        self
+    }
+
+    /// Set the version identifier
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+}
+
+/// Debug impl that redacts the secret
+impl std::fmt::Debug for MoleculeParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MoleculeParams")
+            .field("secret", &self.secret.as_ref().map(|_| "[REDACTED]"))
+            .field("bundle", &self.bundle)
+            .field("source_wallet", &self.source_wallet)
+            .field("remainder_wallet", &self.remainder_wallet)
+            .field("cell_slug", &self.cell_slug)
+            .field("version", &self.version)
+            .finish()
+    }
 }
 
 impl Molecule {
@@ -113,6 +241,11 @@ impl Molecule {
             remainder_wallet: None,
             parent_hashes: Vec::new(),
             continuid_position: None,
+            annotations: BTreeMap::new(),
+            system_tokens: SystemTokens::default(),
+            signed_hash: None,
+            trace_signing: false,
+            signing_trace: None,
         }
     }
     
@@ -127,22 +260,12 @@ impl Molecule {
         }
     }
     
-    /// Create a new Molecule instance with parameters
-    /// # Arguments
-    /// * `secret` - 2048-character biometric hash (optional)
-    /// * `bundle` - 64-character hexadecimal user identifier (optional)
-    /// * `source_wallet` - Source wallet for transactions (optional)
-    /// * `remainder_wallet` - Remainder wallet for change (optional)
-    /// * `cell_slug` - Cell slug for sharding (optional)
-    /// * `version` - Version identifier (optional)
-    pub fn with_params(
-        secret: Option<String>,
-        bundle: Option<String>,
-        source_wallet: Option<Wallet>,
-        remainder_wallet: Option<Wallet>,
-        cell_slug: Option<String>,
-        version: Option<String>,
-    ) -> Self {
+    /// Create a new Molecule instance from its parameters
+    ///
+    /// If a source wallet and a secret are given without a remainder wallet, a fresh
+    /// remainder wallet of the source's token is created.
+    pub fn from_params(params: MoleculeParams) -> Self {
+        let MoleculeParams { secret, bundle, source_wallet, remainder_wallet, cell_slug, version } = params;
         let timestamp = Self::generate_timestamp();
         
         // Create remainder wallet if source wallet provided but no remainder wallet
@@ -179,9 +302,34 @@ impl Molecule {
             remainder_wallet: final_remainder_wallet,
             parent_hashes: Vec::new(),
             continuid_position: None,
+            annotations: BTreeMap::new(),
+            system_tokens: SystemTokens::default(),
+            signed_hash: None,
+            trace_signing: false,
+            signing_trace: None,
         }
     }
     
+    /// Create a new Molecule instance from positional arguments
+    /// # Arguments
+    /// * `secret` - 2048-character biometric hash (optional)
+    /// * `bundle` - 64-character hexadecimal user identifier (optional)
+    /// * `source_wallet` - Source wallet for transactions (optional)
+    /// * `remainder_wallet` - Remainder wallet for change (optional)
+    /// * `cell_slug` - Cell slug for sharding (optional)
+    /// * `version` - Version identifier (optional)
+    #[deprecated(note = "use `Molecule::from_params(MoleculeParams)`")]
+    pub fn with_params(
+        secret: Option<String>,
+        bundle: Option<String>,
+        source_wallet: Option<Wallet>,
+        remainder_wallet: Option<Wallet>,
+        cell_slug: Option<String>,
+        version: Option<String>,
+    ) -> Self {
+        Self::from_params(MoleculeParams { secret, bundle, source_wallet, remainder_wallet, cell_slug, version })
+    }
+
     /// Convert JSON string to Molecule object (matches JS Molecule.jsonToObject)
     /// # Arguments
     /// * `json` - JSON string representation of a Molecule
@@ -279,6 +427,32 @@ impl Molecule {
     pub fn generate_next_atom_index(atoms: &[Atom]) -> u32 {
         atoms.len() as u32
     }
+
+    /// Renumber atoms 0, 1, 2, ... in their current order
+    ///
+    /// Needed after atoms are removed or rearranged. The old molecular hash and OTS
+    /// fragments no longer match, so both are cleared and the molecule must be signed again.
+    pub fn reindex(&mut self) {
+        for (index, atom) in self.atoms.iter_mut().enumerate() {
+            atom.index = Some(index as u32);
+            atom.ots_fragment = None;
+        }
+        self.molecular_hash = None;
+    }
+
+    /// Check that atom indices run 0, 1, 2, ... in atom order
+    ///
+    /// # Errors
+    ///
+    /// Returns `AtomIndex` if an index is missing, duplicated, out of order or skipped
+    pub fn check_atom_indices(&self) -> Result<()> {
+        for (position, atom) in self.atoms.iter().enumerate() {
+            if atom.index != Some(position as u32) {
+                return Err(KnishIOError::AtomIndex);
+            }
+        }
+        Ok(())
+    }
     
     /// Add an atom to this molecule
     /// # Arguments
@@ -305,13 +479,13 @@ impl Molecule {
     /// - `characters`: remainder wallet's character encoding (if available)
     pub fn add_continuid_atom(&mut self) -> Result<()> {
         // JS SDK pattern: If remainder wallet is not USER token, create a new USER remainder wallet.
-        // ContinuID I-atoms MUST always use "USER" token.
-        if self.remainder_wallet.as_ref().map_or(true, |w| w.token != "USER") {
+        // ContinuID I-atoms MUST always use the USER token (or the deployment's rename of it).
+        if self.remainder_wallet.as_ref().map_or(true, |w| w.token != self.system_tokens.user) {
             if let Some(ref secret) = self.secret {
                 let user_wallet = Wallet::create(
                     Some(secret),
                     self.bundle.as_deref(),
-                    "USER",
+                    &self.system_tokens.user,
                     None,
                     None,
                 ).ok();
@@ -389,6 +563,7 @@ impl Molecule {
         
         // Hash atoms to get molecular hash (use base17 as default like JS)
         self.molecular_hash = Some(Atom::hash_atoms(&self.atoms, "base17")?);
+        self.signed_hash = self.molecular_hash.clone();
         
         // Get signing atom (first atom)
         let signing_atom = &self.atoms[0];
@@ -411,23 +586,29 @@ impl Molecule {
             let normalized_hash = self.normalized_hash()?;
             
             // Build one-time signature
-            let mut signature_fragments = String::new();
-            
-            for (index, chunk) in key_chunks.iter().enumerate() {
-                if index >= normalized_hash.len() {
-                    break;
-                }
-                
-                let mut working_chunk = chunk.clone();
-                // Calculate iterations: 8 - value where value is -8 to 8
-                // This gives us 0 to 16 iterations
-                let iterations = (8 - normalized_hash[index] as i32) as usize;
-                
-                for _ in 0..iterations {
-                    working_chunk = shake256(&working_chunk, 512);
-                }
-                
-                signature_fragments.push_str(&working_chunk);
+            // Calculate iterations: 8 - value where value is -8 to 8
+            // This gives us 0 to 16 iterations per chunk
+            let iterations: Vec<usize> = normalized_hash
+                .iter()
+                .take(key_chunks.len())
+                .map(|&value| (8 - value as i32) as usize)
+                .collect();
+            let mut key_chunks = key_chunks;
+            key_chunks.truncate(iterations.len());
+            let mut trace = self.trace_signing.then(|| SigningTrace {
+                molecular_hash: self.molecular_hash.clone().unwrap_or_default(),
+                signing_token: signing_atom.token.clone(),
+                signing_position: signing_position.clone(),
+                key_chunks: key_chunks.clone(),
+                normalized_hash: normalized_hash.clone(),
+                iterations: iterations.clone(),
+                compressed,
+                ..Default::default()
+            });
+            let chunk_signatures = hash_chains(key_chunks, &iterations);
+            let mut signature_fragments = chunk_signatures.concat();
+            if let Some(ref mut trace) = trace {
+                trace.chunk_signatures = chunk_signatures;
             }
             
             // Compress signature if requested (hex to base64)
@@ -449,8 +630,22 @@ impl Molecule {
                 if chunk_count < self.atoms.len() {
                     self.atoms[chunk_count].ots_fragment = Some(chunk.clone());
                     last_position = Some(self.atoms[chunk_count].position.clone());
+                    if let Some(ref mut trace) = trace {
+                        let start = chunk_count * chunk_size;
+                        trace.fragments.push(FragmentBoundary {
+                            atom_index: chunk_count,
+                            position: self.atoms[chunk_count].position.clone(),
+                            start,
+                            end: start + chunk.len(),
+                        });
+                    }
                 }
             }
+
+            if let Some(mut trace) = trace {
+                trace.signature = signature_fragments;
+                self.signing_trace = Some(trace);
+            }
             
             Ok(last_position)
         } else {
@@ -458,6 +653,22 @@ impl Molecule {
         }
     }
     
+    /// Record the intermediates of every later `sign` in a `SigningTrace`
+    ///
+    /// For diagnosing signature mismatches with other SDKs; the trace holds the private key.
+    pub fn set_trace_signing(&mut self, enabled: bool) -> &mut Self {
+        self.trace_signing = enabled;
+        if !enabled {
+            self.signing_trace = None;
+        }
+        self
+    }
+
+    /// Trace of the last `sign` run with tracing enabled
+    pub fn signing_trace(&self) -> Option<&SigningTrace> {
+        self.signing_trace.as_ref()
+    }
+
     /// Sign the molecule with default parameters (non-anonymous, compressed).
     ///
     /// Convenience method equivalent to `molecule.sign(None, false, true)`,
@@ -485,11 +696,43 @@ impl Molecule {
     /// True if all validations pass, error otherwise
     pub fn check(&self, sender_wallet: Option<&Wallet>) -> Result<bool> {
         use crate::check_molecule::CheckMolecule;
-        
+
+        self.check_unmodified()?;
         let check_molecule = CheckMolecule::new(self)?;
         check_molecule.verify(sender_wallet)
     }
+
+    /// Validate like `check`, with custom isotope rules
+    ///
+    /// # Arguments
+    /// * `sender_wallet` - Optional sender wallet for balance validation
+    /// * `validators` - Isotope validators replacing the JavaScript-parity ruleset
+    pub fn check_with(&self, sender_wallet: Option<&Wallet>, validators: &ValidatorRegistry) -> Result<bool> {
+        use crate::check_molecule::CheckMolecule;
+
+        self.check_unmodified()?;
+        CheckMolecule::new(self)?.with_validators(validators).verify(sender_wallet)
+    }
     
+    /// Check that the atoms are still the ones this molecule was signed over
+    ///
+    /// Adding, removing or editing atoms after `sign` (including through the public
+    /// `atoms` field) leaves a hash and OTS fragments that no longer match. Molecules that
+    /// were not signed here, e.g. decoded from JSON, always pass.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MoleculeModifiedAfterSigning` if the atoms changed since the last `sign`
+    pub fn check_unmodified(&self) -> Result<()> {
+        let Some(ref signed_hash) = self.signed_hash else {
+            return Ok(());
+        };
+        if self.molecular_hash.as_ref() != Some(signed_hash) || Atom::hash_atoms(&self.atoms, "base17")? != *signed_hash {
+            return Err(KnishIOError::MoleculeModifiedAfterSigning);
+        }
+        Ok(())
+    }
+
     /// Generate next atomic index for this molecule
     pub fn generate_index(&self) -> u32 {
         Self::generate_next_atom_index(&self.atoms)
@@ -524,7 +767,7 @@ impl Molecule {
         };
         let (source_balance_i128, source_wallet_info, remainder_wallet_info, source_units_meta, remainder_units_meta) = {
             if let Some(ref source_wallet) = self.source_wallet {
-                let bal = source_wallet.balance_as_i128();
+                let bal = source_wallet.whole_balance()?;
                 if bal - amount_i128 < 0 {
                     return Err(KnishIOError::BalanceInsufficient);
                 }
@@ -631,7 +874,7 @@ impl Molecule {
 
         let (source_balance_i128, source_wallet_info, remainder_wallet_info, source_units_meta, remainder_units_meta) = {
             if let Some(ref source_wallet) = self.source_wallet {
-                let bal = source_wallet.balance_as_i128();
+                let bal = source_wallet.whole_balance()?;
                 if bal - total_i128 < 0 {
                     return Err(KnishIOError::BalanceInsufficient);
                 }
@@ -852,13 +1095,40 @@ impl Molecule {
         Ok(())
     }
     
+    /// Initialize an identifier molecule carrying an external-key identity proof
+    /// # Arguments
+    /// * `proof` - Proof signed by the external key for this molecule's bundle
+    pub fn init_identity_proof(&mut self, proof: &crate::identity_bridge::IdentityProof) -> Result<()> {
+        if let Some(ref source_wallet) = self.source_wallet {
+            let params = AtomCreateParams {
+                isotope: Isotope::C,
+                wallet_info: Some(WalletInfo {
+                    position: source_wallet.position.clone().unwrap_or_default(),
+                    address: source_wallet.address.clone().unwrap_or_default(),
+                    token: source_wallet.token.clone(),
+                    batch_id: source_wallet.batch_id.clone(),
+                }),
+                meta_type: Some("identifier".to_string()),
+                meta_id: Some(proof.scheme.as_str().to_string()),
+                meta: Some(proof.to_meta()),
+                ..Default::default()
+            };
+
+            self.add_atom(Atom::create(params));
+            self.add_continuid_atom()?;
+        }
+
+        Ok(())
+    }
+
     /// Initialize metadata molecule
     /// # Arguments
     /// * `meta` - Metadata key-value pairs
     /// * `meta_type` - Type of metadata
     /// * `meta_id` - Metadata identifier
     /// * `policy` - Access policy (optional)
-    pub fn init_meta(&mut self, meta: Vec<MetaItem>, meta_type: &str, meta_id: &str, _policy: Option<&str>) -> Result<()> {
+    pub fn init_meta(&mut self, meta: Vec<MetaItem>, meta_type: &str, meta_id: &str, policy: Option<&str>) -> Result<()> {
+        let meta = meta_with_policy(meta, policy)?;
         if let Some(ref source_wallet) = self.source_wallet {
             let params = AtomCreateParams {
                 isotope: Isotope::M,
@@ -971,7 +1241,7 @@ impl Molecule {
         };
         let (source_balance_i128, source_info, token, remainder_info, source_units_meta, remainder_units_meta) = {
             if let Some(ref source_wallet) = self.source_wallet {
-                let bal = source_wallet.balance_as_i128();
+                let bal = source_wallet.whole_balance()?;
                 if bal - amount_i128 < 0 {
                     return Err(KnishIOError::BalanceInsufficient);
                 }
@@ -1051,6 +1321,70 @@ impl Molecule {
         Ok(())
     }
     
+    /// Move a wallet's whole balance to a fresh wallet of the same bundle
+    ///
+    /// Two V atoms: the source is debited its full balance and `new_wallet` credited the
+    /// same amount, so no remainder wallet is involved and the old position is spent.
+    /// The credit atom publishes the new wallet's `pubkey` and `characters`, plus its
+    /// `tokenUnits` for a stackable token.
+    /// # Arguments
+    /// * `new_wallet` - Fresh wallet receiving the balance (and units)
+    pub fn init_wallet_rotation(&mut self, new_wallet: &Wallet) -> Result<()> {
+        if let Some(ref source_wallet) = self.source_wallet {
+            let balance = source_wallet.whole_balance()?;
+            if balance <= 0 {
+                return Err(KnishIOError::BalanceInsufficient);
+            }
+            if new_wallet.token != source_wallet.token {
+                return Err(KnishIOError::TransferMismatched);
+            }
+            if new_wallet.address == source_wallet.address {
+                return Err(KnishIOError::TransferToSelf);
+            }
+
+            let source_meta = if source_wallet.token_units.is_empty() {
+                None
+            } else {
+                let mut am = AtomMeta::new(None);
+                am.set_atom_wallet(source_wallet);
+                Some(am.meta)
+            };
+            let mut source_atom = Atom::create(AtomCreateParams {
+                isotope: Isotope::V,
+                wallet_info: Some(WalletInfo {
+                    position: source_wallet.position.clone().unwrap_or_default(),
+                    address: source_wallet.address.clone().unwrap_or_default(),
+                    token: source_wallet.token.clone(),
+                    batch_id: source_wallet.batch_id.clone(),
+                }),
+                meta: source_meta,
+                ..Default::default()
+            });
+            source_atom.value = Some((-balance).to_string());
+            self.add_atom(source_atom);
+
+            let mut target_meta = AtomMeta::new(None);
+            target_meta.set_atom_wallet(new_wallet);
+            let mut target_atom = Atom::create(AtomCreateParams {
+                isotope: Isotope::V,
+                wallet_info: Some(WalletInfo {
+                    position: new_wallet.position.clone().unwrap_or_default(),
+                    address: new_wallet.address.clone().unwrap_or_default(),
+                    token: new_wallet.token.clone(),
+                    batch_id: new_wallet.batch_id.clone(),
+                }),
+                meta_type: Some("walletBundle".to_string()),
+                meta_id: new_wallet.bundle.clone(),
+                meta: Some(target_meta.meta),
+                ..Default::default()
+            });
+            target_atom.value = Some(balance.to_string());
+            self.add_atom(target_atom);
+        }
+
+        Ok(())
+    }
+
     /// Replenishes non-finite token supplies (matches JS replenishToken)
     /// # Arguments
     /// * `amount` - Amount to replenish (must be positive)
@@ -1063,7 +1397,7 @@ impl Molecule {
         let amount_i128 = amount as i128;
 
         if let Some(ref mut source_wallet) = self.source_wallet.clone() {
-            let source_bal = source_wallet.balance_as_i128();
+            let source_bal = source_wallet.whole_balance()?;
 
             // Handle token units if provided
             if let Some(_unit_list) = units {
@@ -1130,22 +1464,20 @@ impl Molecule {
         meta_type: &str,
         meta_id: &str,
         meta: Vec<MetaItem>,
-        _policy: Option<&str>,
+        policy: Option<&str>,
     ) -> Result<()> {
+        let meta = meta_with_policy(meta, policy)?;
         if let Some(ref secret) = self.secret {
             if let Some(ref source_wallet) = self.source_wallet {
                 // Create policy wallet for USER token
                 let policy_wallet = Wallet::create(
                     Some(secret),
                     source_wallet.bundle.as_deref(),
-                    "USER",
+                    &self.system_tokens.user,
                     None,
                     None,
                 )?;
                 
-                let final_meta = meta;
-                // TODO: Add policy to meta if provided
-                
                 let params = AtomCreateParams {
                     isotope: Isotope::R,
                     wallet_info: Some(WalletInfo {
@@ -1156,7 +1488,7 @@ impl Molecule {
                     }),
                     meta_type: Some(meta_type.to_string()),
                     meta_id: Some(meta_id.to_string()),
-                    meta: Some(final_meta),
+                    meta: Some(meta),
                     ..Default::default()
                 };
                 
@@ -1170,13 +1502,13 @@ impl Molecule {
     /// Initialize deposit buffer molecule (matches JS initDepositBuffer)
     /// # Arguments
     /// * `amount` - Amount to deposit
-    /// * `trade_rates` - Trading rates (not implemented in this version)
-    pub fn init_deposit_buffer(&mut self, amount: f64, _trade_rates: HashMap<String, f64>) -> Result<()> {
+    /// * `trade_rates` - Trading rates of the buffer wallet, keyed by token slug
+    pub fn init_deposit_buffer(&mut self, amount: f64, trade_rates: HashMap<String, f64>) -> Result<()> {
         let amount_i128 = amount as i128;
 
         // Extract all needed data from source_wallet first
         let atoms_to_add = if let Some(ref source_wallet) = self.source_wallet {
-            let source_balance_i128 = source_wallet.balance_as_i128();
+            let source_balance_i128 = source_wallet.whole_balance()?;
             if source_balance_i128 - amount_i128 < 0 {
                 return Err(KnishIOError::BalanceInsufficient);
             }
@@ -1191,13 +1523,14 @@ impl Molecule {
 
             // Create buffer wallet
             if let Some(ref secret) = self.secret {
-                let buffer_wallet = Wallet::create(
+                let mut buffer_wallet = Wallet::create(
                     Some(secret),
                     self.bundle.as_deref(),
                     &source_token,
-                    source_batch_id.as_deref(),
+                    None,
                     None,
                 )?;
+                buffer_wallet.batch_id = source_batch_id.clone();
 
                 // Remove tokens from source (debit the FULL balance for UTXO
                 // conservation, matching the JS/PHP/TS reference; the change is
@@ -1229,6 +1562,7 @@ impl Molecule {
                     value: Some(amount),
                     meta_type: Some("walletBundle".to_string()),
                     meta_id: source_bundle.clone(),
+                    meta: trade_rates_meta(&trade_rates)?,
                     ..Default::default()
                 };
                 atoms.push(Atom::create(buffer_params));
@@ -1267,6 +1601,56 @@ impl Molecule {
         Ok(())
     }
     
+    /// Initialize a molecule replacing the trade rates of a buffer wallet
+    ///
+    /// The source wallet is the existing buffer wallet and the remainder wallet its
+    /// replacement: the whole balance moves from one to the other, and the replacement's
+    /// B atom carries the new rates. Empty `trade_rates` leave the buffer without rates.
+    ///
+    /// # Errors
+    ///
+    /// `WalletNotFound` without a source or remainder wallet, `NegativeAmount` for a
+    /// negative or non-finite rate
+    pub fn init_buffer_trade_rates(&mut self, trade_rates: HashMap<String, f64>) -> Result<()> {
+        let source_wallet = self.source_wallet.as_ref().ok_or(KnishIOError::WalletNotFound)?;
+        let remainder_wallet = self.remainder_wallet.as_ref().ok_or(KnishIOError::WalletNotFound)?;
+        let balance = source_wallet.whole_balance()?;
+
+        let mut source_atom = Atom::create(AtomCreateParams {
+            isotope: Isotope::B,
+            wallet_info: Some(WalletInfo {
+                position: source_wallet.position.clone().unwrap_or_default(),
+                address: source_wallet.address.clone().unwrap_or_default(),
+                token: source_wallet.token.clone(),
+                batch_id: source_wallet.batch_id.clone(),
+            }),
+            meta_type: Some("walletBundle".to_string()),
+            meta_id: source_wallet.bundle.clone(),
+            ..Default::default()
+        });
+        source_atom.value = Some((-balance).to_string());
+
+        let mut buffer_atom = Atom::create(AtomCreateParams {
+            isotope: Isotope::B,
+            wallet_info: Some(WalletInfo {
+                position: remainder_wallet.position.clone().unwrap_or_default(),
+                address: remainder_wallet.address.clone().unwrap_or_default(),
+                token: remainder_wallet.token.clone(),
+                batch_id: remainder_wallet.batch_id.clone(),
+            }),
+            meta_type: Some("walletBundle".to_string()),
+            meta_id: remainder_wallet.bundle.clone(),
+            meta: trade_rates_meta(&trade_rates)?,
+            ..Default::default()
+        });
+        buffer_atom.value = Some(balance.to_string());
+
+        self.add_atom(source_atom);
+        self.add_atom(buffer_atom);
+
+        Ok(())
+    }
+
     /// Initialize withdraw buffer molecule (matches JS initWithdrawBuffer)
     /// # Arguments
     /// * `recipients` - Map of recipient bundle hashes to amounts
@@ -1282,7 +1666,7 @@ impl Molecule {
 
         // Extract all needed data from source_wallet first
         let atoms_to_add = if let Some(ref source_wallet) = self.source_wallet {
-            let source_balance_i128 = source_wallet.balance_as_i128();
+            let source_balance_i128 = source_wallet.whole_balance()?;
             if source_balance_i128 - total_amount_i128 < 0 {
                 return Err(KnishIOError::BalanceInsufficient);
             }
@@ -1375,15 +1759,10 @@ impl Molecule {
         meta_type: &str,
         meta_id: &str,
         rule: &str,
-        _policy: Option<&str>,
+        policy: Option<&str>,
     ) -> Result<()> {
+        let rule_meta = meta_with_policy(vec![MetaItem::new("rule", rule)], policy)?;
         if let Some(ref source_wallet) = self.source_wallet {
-            // Create atom meta with rules
-            let mut rule_meta = Vec::new();
-            rule_meta.push(MetaItem::new("rule", rule));
-            
-            // TODO: Add policy to meta if provided
-            
             let params = AtomCreateParams {
                 isotope: Isotope::R,
                 wallet_info: Some(WalletInfo {
@@ -1415,7 +1794,7 @@ impl Molecule {
 
         // Extract all needed data from source_wallet first
         let atoms_to_add = if let Some(ref source_wallet) = self.source_wallet {
-            let source_balance_i128 = source_wallet.balance_as_i128();
+            let source_balance_i128 = source_wallet.whole_balance()?;
             if source_balance_i128 - amount_i128 < 0 {
                 return Err(KnishIOError::BalanceInsufficient);
             }
@@ -1503,7 +1882,32 @@ impl Molecule {
         let hash_result = Atom::hash_atoms(&self.atoms, "hex")?;
         Ok(hash_result)
     }
+
+    /// Get the canonical JSON the molecular hash is computed from
+    /// Exposes the exact bytes fed to SHAKE256 for versioned atoms, so hash mismatches
+    /// between SDKs can be narrowed down by diffing this output.
+    /// Result containing the canonical JSON string
+    pub fn to_canonical_json(&self) -> Result<String> {
+        if self.atoms.is_empty() {
+            return Err(KnishIOError::AtomsMissing);
+        }
+
+        Ok(Atom::canonical_json(&self.atoms))
+    }
     
+    /// Encode the molecule for exchange in `format`
+    ///
+    /// Binary formats carry the same document as `to_json`, so decoding with `from_wire`
+    /// reproduces the canonical JSON and molecular hash exactly.
+    pub fn to_wire(&self, format: crate::codec::WireFormat) -> Result<Vec<u8>> {
+        format.encode(&self.to_json(crate::types::MoleculeJsonOptions::default())?)
+    }
+
+    /// Decode a molecule produced by `to_wire`
+    pub fn from_wire(bytes: &[u8], format: crate::codec::WireFormat) -> Result<Self> {
+        Self::from_json(&format.decode(bytes)?, crate::types::MoleculeFromJsonOptions::default())
+    }
+
     /// Sign the molecule using the secret (simplified interface for type-safe builder)
     /// This is a convenience method that wraps the existing sign method
     /// with sensible defaults for the type-safe builder.
@@ -1514,18 +1918,14 @@ impl Molecule {
         // Store the secret for signing operations
         self.secret = Some(secret.to_string());
         
-        // Use the existing sign method with default parameters
-        let result = self.sign(
+        // Use the existing sign method with default parameters (it sets the molecular hash;
+        // its return value is the last signed position)
+        self.sign(
             self.bundle.clone(), // Use the existing bundle
             false,               // Not anonymous
             false,               // Not compressed
         )?;
-        
-        // Update molecular hash based on the signing result
-        if result.is_some() {
-            self.molecular_hash = result;
-        }
-        
+
         Ok(())
     }
     
@@ -1572,6 +1972,9 @@ impl Molecule {
             return Err(crate::error::KnishIOError::custom("Cannot serialize molecule with secret in secure mode"));
         }
 
+        // A signed molecule whose atoms changed since would go out with a stale hash
+        self.check_unmodified()?;
+
         // Core molecule properties (always included) - JavaScript SDK compatible format
         let mut serialized = serde_json::json!({
             "status": self.status,
@@ -1656,14 +2059,12 @@ impl Molecule {
             }
 
         // Create minimal molecule instance (never include secret from JSON)
-        let mut molecule = Molecule::with_params(
-            None, // secret: never from JSON
-            json.get("bundle").and_then(|b| b.as_str()).map(|s| s.to_string()),
-            None, // sourceWallet: reconstructed separately
-            None, // remainderWallet: reconstructed separately
-            json.get("cellSlug").and_then(|c| c.as_str()).map(|s| s.to_string()),
-            json.get("version").and_then(|v| v.as_str()).map(|s| s.to_string()),
-        );
+        let mut molecule = Molecule::from_params(MoleculeParams {
+            bundle: json.get("bundle").and_then(|b| b.as_str()).map(|s| s.to_string()),
+            cell_slug: json.get("cellSlug").and_then(|c| c.as_str()).map(|s| s.to_string()),
+            version: json.get("version").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            ..MoleculeParams::new()
+        });
 
         // Populate core properties with graceful handling of missing fields
         if let Some(status) = json.get("status").and_then(|s| s.as_str()) {
@@ -1672,8 +2073,8 @@ impl Molecule {
         if let Some(hash) = json.get("molecularHash").and_then(|h| h.as_str()) {
             molecule.molecular_hash = Some(hash.to_string());
         }
-        if let Some(created_at) = json.get("createdAt").and_then(|c| c.as_str()) {
-            molecule.created_at = created_at.to_string();
+        if let Some(created_at) = json.get("createdAt").filter(|c| !c.is_null()) {
+            molecule.created_at = crate::types::created_at_from_json(created_at);
         }
         // Handle cellSlugOrigin gracefully - may be missing in some SDKs (PHP/C)
         if let Some(cell_slug_origin) = json.get("cellSlugOrigin").and_then(|c| c.as_str()) {
@@ -1700,6 +2101,8 @@ impl Molecule {
                     .map_err(|e| crate::error::KnishIOError::custom(format!("Failed to reconstruct atom: {}", e)))?;
                 molecule.atoms.push(atom);
             }
+            // Atoms may arrive in any order; restore index order (ties keep input order)
+            molecule.atoms = Atom::sort_atoms(&molecule.atoms);
         }
 
         // Reconstruct validation context if available and requested
@@ -1718,10 +2121,23 @@ impl Molecule {
 
 impl Default for Molecule {
     fn default() -> Self {
-        Molecule::with_params(None, None, None, None, None, None)
+        Molecule::from_params(MoleculeParams::new())
     }
 }
 
+/// `meta` with `policy` (a JSON policy object) attached, as AtomMeta.addPolicy does in JS
+///
+/// Keys of `meta` the policy leaves out get the default rules.
+fn meta_with_policy(meta: Vec<MetaItem>, policy: Option<&str>) -> Result<Vec<MetaItem>> {
+    let Some(policy) = policy else {
+        return Ok(meta);
+    };
+    let policy = serde_json::from_str(policy).map_err(|_| KnishIOError::PolicyInvalid)?;
+    let mut atom_meta = AtomMeta::new(Some(meta));
+    atom_meta.add_policy(policy)?;
+    Ok(atom_meta.meta)
+}
+
 /// Helper function to reconstruct wallet from JSON data for validation context
 /// 
 /// Matches the JavaScript pattern of creating wallets with proper balances
@@ -1764,7 +2180,7 @@ fn reconstruct_wallet_from_json(wallet_data: &serde_json::Value) -> crate::error
     let characters = wallet_data.get("characters")
         .and_then(|c| c.as_str())
         .map(|s| s.to_string())
-        .or_else(|| Some("BASE64".to_string())); // Default value for cross-SDK compatibility
+        .or_else(|| Some(Characters::default().as_str().to_string())); // Default value for cross-SDK compatibility
     
     // Create wallet with minimal required information for validation
     // Handle cases where bundle is missing (PHP/C SDK compatibility)
@@ -1780,15 +2196,14 @@ fn reconstruct_wallet_from_json(wallet_data: &serde_json::Value) -> crate::error
     } else {
         // Special case: PHP/C SDKs may not include bundle in sourceWallet
         // Create wallet using new() method directly to bypass credential validation
-        crate::wallet::Wallet::new(
-            None, // secret
-            None, // bundle (missing in PHP)
-            Some(token),
-            address.as_deref(),
-            position.as_deref(),
-            batch_id.as_deref(),
-            characters.as_deref(),
-        )?
+        // No secret, and no bundle (missing in PHP)
+        Wallet::from_params(WalletParams {
+            address: address.clone(),
+            position: position.clone(),
+            batch_id: batch_id.clone(),
+            characters: characters.clone(),
+            ..WalletParams::new().token(token)
+        })?
     };
     
     // Set additional properties from JSON (balance is already String)
@@ -1806,7 +2221,7 @@ fn reconstruct_wallet_from_json(wallet_data: &serde_json::Value) -> crate::error
     // Handle optional fields that might be missing in other SDK JSON (especially PHP/C)
     // Set default values to ensure compatibility
     if wallet.characters.is_none() {
-        wallet.characters = Some("BASE64".to_string());
+        wallet.characters = Some(Characters::default().as_str().to_string());
     }
     
     // Initialize empty collections for missing fields to match JavaScript structure
@@ -1917,10 +2332,49 @@ impl Molecule {
         self.created_at = created_at;
     }
 
+    /// Creation time in milliseconds since the Unix epoch
+    ///
+    /// # Errors
+    ///
+    /// `InvalidTimestamp` when `created_at` is not a timestamp `parse_created_at` reads
+    pub fn created_at_millis(&self) -> Result<i64> {
+        crate::types::created_at_millis(&self.created_at)
+    }
+
+    /// Creation time as a date, see `created_at_millis`
+    pub fn created_at_datetime(&self) -> Result<chrono::DateTime<chrono::Utc>> {
+        crate::types::parse_created_at(&self.created_at)
+    }
+
     /// Set parent molecule hashes for DAG linkage
     pub fn set_parent_hashes(&mut self, hashes: Vec<String>) {
         self.parent_hashes = hashes;
     }
+
+    // ============================================================================
+    // Client annotations
+    // ============================================================================
+
+    /// Attach a local annotation, replacing any previous value for `key`
+    ///
+    /// Annotations let applications correlate ledger writes with their own requests.
+    /// They stay on the client: the molecule's JSON, wire encodings and molecular hash
+    /// are unaffected, but the response to the proposal carries them.
+    pub fn annotate(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.annotations.insert(key.into(), value.into());
+        self
+    }
+
+    /// Builder form of `annotate`
+    pub fn with_annotation(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.annotate(key, value);
+        self
+    }
+
+    /// Value of one annotation
+    pub fn annotation(&self, key: &str) -> Option<&str> {
+        self.annotations.get(key).map(String::as_str)
+    }
 }
 
 #[cfg(test)]
@@ -1929,14 +2383,7 @@ mod tests {
 
     #[test]
     fn test_molecule_creation() {
-        let molecule = Molecule::with_params(
-            Some("test-secret".to_string()),
-            Some("test-bundle".to_string()),
-            None,
-            None,
-            None,
-            None,
-        );
+        let molecule = Molecule::from_params(MoleculeParams::new().secret("test-secret").bundle("test-bundle"));
         
         assert_eq!(molecule.bundle, Some("test-bundle".to_string()));
         assert_eq!(molecule.secret, Some("test-secret".to_string()));
@@ -1944,6 +2391,24 @@ mod tests {
         assert!(molecule.molecular_hash.is_none());
     }
     
+    #[test]
+    fn test_annotations_stay_local() {
+        let secret = crate::crypto::generate_secret("annotations");
+        let source = Wallet::create(Some(&secret), None, "USER", None, None).unwrap();
+        let mut molecule = Molecule::from_params(MoleculeParams::new().secret(secret).source_wallet(source));
+        molecule.init_meta(vec![MetaItem::new("k", "v")], "note", "n-1", None).unwrap();
+        let plain = molecule.clone();
+
+        molecule.annotate("request_id", "req-1").annotate("label", "checkout");
+        assert_eq!(molecule.annotation("request_id"), Some("req-1"));
+        assert_eq!(molecule.annotations.len(), 2);
+
+        let json = molecule.to_json(crate::types::MoleculeJsonOptions::default()).unwrap();
+        assert_eq!(json, plain.to_json(crate::types::MoleculeJsonOptions::default()).unwrap());
+        assert!(!serde_json::to_string(&molecule).unwrap().contains("req-1"));
+        assert_eq!(molecule.get_molecular_hash().unwrap(), plain.get_molecular_hash().unwrap());
+    }
+
     #[test]
     fn test_enumerate() {
         let hash = "0123456789abcdef";
@@ -1977,6 +2442,89 @@ mod tests {
         let next_index = Molecule::generate_next_atom_index(&atoms);
         assert_eq!(next_index, 2);
     }
+
+    #[test]
+    fn test_atom_index_invariants() {
+        let mut molecule = Molecule::default();
+        for position in ["pos1", "pos2", "pos3"] {
+            molecule.add_atom(Atom::new(position, "addr", Isotope::V, "TEST"));
+        }
+        assert!(molecule.check_atom_indices().is_ok());
+
+        // Misordered, gapped and duplicated indices are all rejected
+        molecule.atoms.swap(0, 2);
+        assert!(matches!(molecule.check_atom_indices(), Err(KnishIOError::AtomIndex)));
+        molecule.atoms.swap(0, 2);
+        molecule.atoms[2].index = Some(3);
+        assert!(molecule.check_atom_indices().is_err());
+        molecule.atoms[2].index = Some(1);
+        assert!(molecule.check_atom_indices().is_err());
+
+        // reindex renumbers in the current order and drops the stale signature
+        molecule.molecular_hash = Some("stale".to_string());
+        molecule.atoms[0].ots_fragment = Some("stale".to_string());
+        molecule.atoms.remove(1);
+        molecule.reindex();
+        assert!(molecule.check_atom_indices().is_ok());
+        assert_eq!(molecule.atoms[1].position, "pos3");
+        assert!(molecule.molecular_hash.is_none());
+        assert!(molecule.atoms[0].ots_fragment.is_none());
+    }
+
+    #[test]
+    fn test_modified_after_signing() {
+        let mut source_wallet = Wallet::create(Some("signed-secret"), None, "TEST", None, None).unwrap();
+        source_wallet.set_balance_i128(100);
+        let recipient_wallet = Wallet::create(Some("signed-recipient"), None, "TEST", None, None).unwrap();
+        let mut molecule = Molecule::from_params(
+            MoleculeParams::new().secret("signed-secret").source_wallet(source_wallet.clone()),
+        );
+        molecule.init_value(&recipient_wallet, 40.0).unwrap();
+
+        // Unsigned molecules are not affected
+        assert!(molecule.check_unmodified().is_ok());
+        molecule.sign(None, false, true).unwrap();
+        assert!(molecule.check(Some(&source_wallet)).is_ok());
+
+        // Editing an atom through the public field
+        let mut edited = molecule.clone();
+        edited.atoms[1].value = Some("45".to_string());
+        assert!(matches!(edited.check(Some(&source_wallet)), Err(KnishIOError::MoleculeModifiedAfterSigning)));
+        assert!(matches!(edited.to_json(Default::default()), Err(KnishIOError::MoleculeModifiedAfterSigning)));
+
+        // Adding an atom, which drops the hash
+        let mut extended = molecule.clone();
+        extended.add_atom(Atom::new("pos", "addr", Isotope::M, "USER"));
+        assert!(matches!(extended.check(None), Err(KnishIOError::MoleculeModifiedAfterSigning)));
+
+        // Signing again accepts the new atoms; a decoded copy is checked on its own terms
+        edited.atoms[2].value = Some("55".to_string());
+        edited.sign(None, false, true).unwrap();
+        assert!(edited.check(Some(&source_wallet)).is_ok());
+        let decoded = Molecule::from_json(&molecule.to_json(Default::default()).unwrap(), Default::default()).unwrap();
+        assert!(decoded.check_unmodified().is_ok());
+    }
+
+    #[test]
+    fn test_from_json_restores_atom_order() {
+        let mut molecule = Molecule::default();
+        molecule.bundle = Some("test-bundle".to_string());
+        for position in ["pos1", "pos2", "pos3"] {
+            molecule.add_atom(Atom::new(position, "addr", Isotope::V, "TEST"));
+        }
+        let mut json = serde_json::to_value(&molecule).unwrap();
+        json["atoms"].as_array_mut().unwrap().reverse();
+
+        let options = crate::types::MoleculeFromJsonOptions {
+            include_validation_context: false,
+            validate_structure: false,
+            strict_mode: false,
+        };
+        let imported = Molecule::from_json(&json, options).unwrap();
+        assert!(imported.check_atom_indices().is_ok());
+        let positions: Vec<_> = imported.atoms.iter().map(|atom| atom.position.as_str()).collect();
+        assert_eq!(positions, ["pos1", "pos2", "pos3"]);
+    }
     
     #[test]
     fn test_isotope_filter() {
@@ -2003,6 +2551,133 @@ mod tests {
         assert!(molecule.molecular_hash.is_none()); // Should be reset
     }
     
+    #[test]
+    fn test_policy_meta_encoding() {
+        let secret = crate::crypto::generate_secret("policy-owner");
+        let source = Wallet::create(Some(&secret), None, "USER", None, None).unwrap();
+        let friend = "ab".repeat(32);
+        let policy = serde_json::json!({ "read": { "email": ["self", friend] } }).to_string();
+        let molecule = || Molecule::from_params(MoleculeParams::new().secret(secret.clone()).source_wallet(source.clone()));
+        let policy_of = |atom: &Atom| -> serde_json::Value {
+            let meta = atom.meta.iter().find(|item| item.key == "policy").unwrap();
+            serde_json::from_str(&meta.value).unwrap()
+        };
+
+        // Keys the policy leaves out get the defaults
+        let mut meta = molecule();
+        meta.init_meta(vec![MetaItem::new("email", "a@example.com"), MetaItem::new("name", "A")], "profile", "a", Some(&policy)).unwrap();
+        meta.sign(None, false, true).unwrap();
+        assert!(meta.check(None).unwrap());
+        let encoded = policy_of(&meta.atoms[0]);
+        assert_eq!(encoded["read"]["email"], serde_json::json!(["self", friend]));
+        assert_eq!(encoded["read"]["name"], serde_json::json!(["all"]));
+        assert_eq!(encoded["write"]["email"], serde_json::json!(["self"]));
+
+        // Policy atoms carry exactly the given policy
+        let mut rule = molecule();
+        rule.add_policy_atom("profile", "a", Vec::new(), Some(&policy)).unwrap();
+        assert_eq!(rule.atoms[0].isotope, Isotope::R);
+        assert_eq!(policy_of(&rule.atoms[0]), serde_json::json!({ "read": { "email": ["self", friend] }, "write": {} }));
+
+        let mut rule = molecule();
+        rule.create_rule("profile", "a", r#"[{"condition":[],"callback":[]}]"#, Some("{}")).unwrap();
+        assert_eq!(policy_of(&rule.atoms[0])["write"]["rule"], serde_json::json!(["self"]));
+
+        for invalid in [r#"{"read":{"email":["friends"]}}"#, r#"{"read":{"email":"self"}}"#, "not json"] {
+            assert!(matches!(molecule().init_meta(vec![MetaItem::new("k", "v")], "t", "i", Some(invalid)), Err(KnishIOError::PolicyInvalid)));
+            assert!(matches!(molecule().add_policy_atom("t", "i", Vec::new(), Some(invalid)), Err(KnishIOError::PolicyInvalid)));
+        }
+
+        // Malformed policies written by hand pass the JavaScript rules; PolicyValidator rejects them
+        let mut tampered = molecule();
+        tampered.init_meta(vec![MetaItem::new("k", "v"), MetaItem::new("policy", r#"{"read":{"k":["friends"]}}"#)], "t", "i", None).unwrap();
+        tampered.sign(None, false, true).unwrap();
+        assert!(tampered.check(None).unwrap());
+        let strict = crate::check_molecule::ValidatorRegistry::default().add(crate::check_molecule::PolicyValidator::new(Isotope::M));
+        let check = crate::check_molecule::CheckMolecule::new(&tampered).unwrap().with_validators(&strict);
+        assert!(matches!(check.verify(None), Err(KnishIOError::PolicyInvalid)));
+    }
+
+    #[test]
+    fn test_custom_system_tokens() {
+        let secret = crate::crypto::generate_secret("renamed-tokens");
+        let source = Wallet::create(Some(&secret), None, "MEMBER", None, None).unwrap();
+        let mut molecule = Molecule::from_params(MoleculeParams::new().secret(secret.clone()).source_wallet(source.clone()));
+        molecule.system_tokens = SystemTokens::new("MEMBER", "LOGIN");
+        molecule.init_meta(vec![MetaItem::new("k", "v")], "note", "n-1", None).unwrap();
+        molecule.sign(None, false, true).unwrap();
+
+        let continuid = molecule.atoms.iter().find(|atom| atom.isotope == Isotope::I).unwrap();
+        assert_eq!(continuid.token, "MEMBER");
+        assert!(molecule.check(None).unwrap());
+
+        // The same molecule is not valid under the default slugs
+        let mut defaults = molecule.clone();
+        defaults.system_tokens = SystemTokens::default();
+        assert!(defaults.check(None).is_err());
+    }
+
+    #[test]
+    fn test_anonymous_molecule_round_trip() {
+        let secret = crate::crypto::generate_secret("anonymous");
+        let source = Wallet::create(Some(&secret), None, "USER", None, None).unwrap();
+        let mut molecule = Molecule::from_params(MoleculeParams::new().secret(secret).source_wallet(source));
+        molecule.init_meta(vec![MetaItem::new("k", "v")], "note", "n-1", None).unwrap();
+        molecule.sign(None, true, true).unwrap();
+
+        assert!(molecule.bundle.is_none());
+        assert!(molecule.check(None).unwrap());
+
+        // The wire form carries an explicit null bundle, as JS Molecule.toJSON does
+        let json = molecule.to_json(crate::types::MoleculeJsonOptions::default()).unwrap();
+        assert!(json["bundle"].is_null());
+        let decoded = Molecule::from_json(&json, crate::types::MoleculeFromJsonOptions::default()).unwrap();
+        assert!(decoded.bundle.is_none());
+        assert!(decoded.check(None).unwrap());
+
+        // Node answers report the missing bundle as a null bundleHash
+        let atoms: Vec<serde_json::Value> = molecule.atoms.iter().map(|atom| serde_json::json!({
+            "position": atom.position,
+            "walletAddress": atom.wallet_address,
+            "isotope": atom.isotope.as_str(),
+            "tokenSlug": atom.token,
+            "value": atom.value,
+            "batchId": atom.batch_id,
+            "metaType": atom.meta_type,
+            "metaId": atom.meta_id,
+            "metasJson": serde_json::to_string(&atom.meta).unwrap(),
+            "index": atom.index,
+            "otsFragment": atom.ots_fragment,
+            "createdAt": atom.created_at,
+        })).collect();
+        let server_data = serde_json::json!({
+            "molecularHash": molecule.molecular_hash,
+            "bundleHash": null,
+            "status": "accepted",
+            "atoms": atoms,
+        });
+        let result = crate::check_molecule::CheckMolecule::verify_from_server_data(&server_data);
+        assert!(result.verified, "{:?}", result.error);
+    }
+
+    #[test]
+    fn test_wallet_rotation_atoms() {
+        let secret = crate::crypto::generate_secret("rotation");
+        let mut source = Wallet::create(Some(&secret), None, "GOLD", None, None).unwrap();
+        source.balance = "40".to_string();
+        let target = Wallet::create(Some(&secret), None, "GOLD", None, None).unwrap();
+
+        let mut molecule = Molecule::from_params(MoleculeParams::new().secret(secret).source_wallet(source.clone()));
+        assert!(matches!(molecule.init_wallet_rotation(&source), Err(KnishIOError::TransferToSelf)));
+        molecule.init_wallet_rotation(&target).unwrap();
+        molecule.sign(None, false, true).unwrap();
+
+        let values: Vec<_> = molecule.atoms.iter().map(|a| a.value.clone().unwrap()).collect();
+        assert_eq!(values, vec!["-40".to_string(), "40".to_string()]);
+        assert!(molecule.atoms[1].meta.iter().any(|m| m.key == "pubkey" && Some(&m.value) == target.pubkey.as_ref()));
+        assert!(molecule.check(Some(&source)).unwrap());
+    }
+
     #[test]
     fn test_json_serialization() {
         let mut molecule = Molecule::default();
@@ -2043,6 +2718,12 @@ mod tests {
         assert_eq!(molecule.bundle, Some("test-bundle".to_string()));
         assert_eq!(molecule.atoms.len(), 1);
         assert_eq!(molecule.atoms[0].isotope, Isotope::V);
+        assert_eq!(molecule.created_at_millis().unwrap(), 1_640_995_200_000);
+        assert_eq!(molecule.created_at_datetime().unwrap().timestamp(), 1_640_995_200);
+
+        let undated = Molecule::json_to_object(&json.replacen("1640995200000", "soon", 1)).unwrap();
+        let error = undated.created_at_millis().unwrap_err();
+        assert!(error.to_string().contains("Invalid timestamp"), "{}", error);
     }
     
     #[test]
@@ -2074,13 +2755,11 @@ mod tests {
             None
         ).unwrap();
         
-        let mut molecule = Molecule::with_params(
-            Some("test-secret".to_string()),
-            None,
-            Some(source_wallet),
-            Some(remainder_wallet),
-            None,
-            None,
+        let mut molecule = Molecule::from_params(
+            MoleculeParams::new()
+                .secret("test-secret")
+                .source_wallet(source_wallet)
+                .remainder_wallet(remainder_wallet),
         );
         
         molecule.init_value(&recipient_wallet, 50.0).unwrap();
@@ -2126,13 +2805,11 @@ mod tests {
         let r2_bundle = recipient_wallets[1].bundle.clone();
         let rem_bundle = remainder_wallet.bundle.clone();
 
-        let mut molecule = Molecule::with_params(
-            Some("mr-secret".to_string()),
-            None,
-            Some(source_wallet),
-            Some(remainder_wallet),
-            None,
-            None,
+        let mut molecule = Molecule::from_params(
+            MoleculeParams::new()
+                .secret("mr-secret")
+                .source_wallet(source_wallet)
+                .remainder_wallet(remainder_wallet),
         );
         molecule.init_values(&recipient_wallets, &[1.0, 1.0]).unwrap();
 
@@ -2186,13 +2863,10 @@ mod tests {
             None
         ).unwrap();
         
-        let mut molecule = Molecule::with_params(
-            Some("test-secret".to_string()),
-            None,
-            Some(source_wallet),
-            None,
-            None,
-            None,
+        let mut molecule = Molecule::from_params(
+            MoleculeParams::new()
+                .secret("test-secret")
+                .source_wallet(source_wallet),
         );
         
         let result = molecule.init_value(&recipient_wallet, 50.0);
@@ -2291,13 +2965,11 @@ mod tests {
             Some("test-secret"), None, "USER", None, None,
         ).unwrap();
 
-        let mut molecule = Molecule::with_params(
-            Some("test-secret".to_string()),
-            None,
-            Some(source_wallet),
-            Some(remainder_wallet),
-            None,
-            None,
+        let mut molecule = Molecule::from_params(
+            MoleculeParams::new()
+                .secret("test-secret")
+                .source_wallet(source_wallet)
+                .remainder_wallet(remainder_wallet),
         );
 
         molecule.add_continuid_atom().unwrap();
@@ -2321,13 +2993,10 @@ mod tests {
             Some("test-secret"), None, "USER", None, None,
         ).unwrap();
 
-        let mut molecule = Molecule::with_params(
-            Some("test-secret".to_string()),
-            None,
-            None, // no source wallet
-            Some(remainder_wallet),
-            None,
-            None,
+        let mut molecule = Molecule::from_params(
+            MoleculeParams::new()
+                .secret("test-secret")
+                .remainder_wallet(remainder_wallet),
         );
 
         molecule.add_continuid_atom().unwrap();
@@ -2349,13 +3018,10 @@ mod tests {
         ).unwrap();
         remainder_wallet.pubkey = Some("test-pubkey-hex".to_string());
 
-        let mut molecule = Molecule::with_params(
-            Some("test-secret".to_string()),
-            None,
-            None,
-            Some(remainder_wallet),
-            None,
-            None,
+        let mut molecule = Molecule::from_params(
+            MoleculeParams::new()
+                .secret("test-secret")
+                .remainder_wallet(remainder_wallet),
         );
 
         molecule.add_continuid_atom().unwrap();
@@ -2372,25 +3038,20 @@ mod tests {
 
     #[test]
     fn test_add_continuid_atom_skips_pubkey_when_absent() {
-        // Create remainder via Wallet::new with bundle (no secret) → no ML-KEM init → no pubkey
-        let remainder_wallet = Wallet::new(
-            None,                                    // no secret
-            Some("test-bundle"),                     // bundle
-            Some("USER"),                            // token
-            Some("test-address"),                    // address
-            Some(&"b".repeat(64)),                   // position (valid 64-hex)
-            None,                                    // batch_id
-            None,                                    // characters
+        // Create remainder via Wallet::from_params with bundle (no secret) → no ML-KEM init → no pubkey
+        let remainder_wallet = Wallet::from_params(
+            WalletParams::new()
+                .bundle("test-bundle")
+                .token("USER")
+                .address("test-address")
+                .position("b".repeat(64)),
         ).unwrap();
         assert!(remainder_wallet.pubkey.is_none(), "precondition: no pubkey on shadow wallet");
 
-        let mut molecule = Molecule::with_params(
-            Some("test-secret".to_string()),
-            None,
-            None,
-            Some(remainder_wallet),
-            None,
-            None,
+        let mut molecule = Molecule::from_params(
+            MoleculeParams::new()
+                .secret("test-secret")
+                .remainder_wallet(remainder_wallet),
         );
 
         molecule.add_continuid_atom().unwrap();
@@ -2413,20 +3074,16 @@ mod tests {
     #[test]
     fn test_init_authorization_registers_continuid_atom() {
         let secret = "test-secret";
-        // Mirror request_profile_auth_token's AUTH source wallet (Wallet::new generates a position,
+        // Mirror request_profile_auth_token's AUTH source wallet (Wallet::from_params generates a position,
         // derives the address, and initializes ML-KEM → pubkey).
-        let auth_source = Wallet::new(
-            Some(secret), None, Some("AUTH"), None, None, None, None,
-        ).unwrap();
+        let auth_source = Wallet::from_params(WalletParams::new().secret(secret).token("AUTH")).unwrap();
         let source_pos = auth_source.position.clone().expect("AUTH source must have a position");
 
-        let mut molecule = Molecule::with_params(
-            Some(secret.to_string()),
-            None,
-            Some(auth_source),
-            None, // NO explicit remainder — exactly like request_profile_auth_token (pre-fix)
-            None,
-            None,
+        let mut molecule = Molecule::from_params(
+            MoleculeParams::new()
+                .secret(secret)
+                .source_wallet(auth_source),
+            // NO explicit remainder — exactly like request_profile_auth_token (pre-fix)
         );
 
         molecule
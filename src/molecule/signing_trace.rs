@@ -0,0 +1,208 @@
+//! Signing traces
+//!
+//! When the Rust and JavaScript SDKs sign the same molecule differently, the molecular
+//! hash or the JSON usually shows where (see `molecule::compare`). When they agree and the
+//! signatures still differ, the cause is inside `sign`. With tracing enabled
+//! (`Molecule::set_trace_signing`) `sign` records each step in a `SigningTrace`:
+//!
+//! 1. the private key, as the 16 chunks that get hashed;
+//! 2. the normalized molecular hash;
+//! 3. the hash iterations of each key chunk;
+//! 4. the hashed chunks, the signature they form, and where it was cut among the atoms.
+//!
+//! `SigningTrace::first_divergence` compares two traces in that order and reports the first
+//! step where they disagree. The JSON form uses the camelCase field names of the struct.
+//! Traces from the other SDK may leave steps out; empty steps are skipped.
+//!
+//! The key chunks are the molecule's private signing key. Only trace with test secrets,
+//! and do not ship traces anywhere a secret would not go.
+
+use crate::error::{KnishIOError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// Where one atom's OTS fragment was cut from the signature
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FragmentBoundary {
+    /// Index of the atom carrying the fragment
+    pub atom_index: usize,
+    /// Position of that atom
+    pub position: String,
+    /// First character of the fragment in the signature
+    pub start: usize,
+    /// One past the fragment's last character
+    pub end: usize,
+}
+
+/// Intermediate values of one `Molecule::sign` run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SigningTrace {
+    /// Hash the signature was made over (base17)
+    pub molecular_hash: String,
+    /// Token of the signing atom
+    pub signing_token: String,
+    /// Position of the signing atom
+    pub signing_position: String,
+    /// Private key, in the chunks that are hashed
+    pub key_chunks: Vec<String>,
+    /// Normalized molecular hash, one value in -8..=8 per key chunk
+    pub normalized_hash: Vec<i8>,
+    /// Hash iterations of each key chunk (8 minus its normalized value)
+    pub iterations: Vec<usize>,
+    /// Each key chunk after its iterations
+    pub chunk_signatures: Vec<String>,
+    /// Whether the signature was compressed to base64
+    pub compressed: bool,
+    /// Signature before it was cut into fragments
+    pub signature: String,
+    /// Fragment of each atom
+    pub fragments: Vec<FragmentBoundary>,
+}
+
+/// First step at which two signing traces disagree
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceDivergence {
+    /// Trace field of the step (e.g. `iterations`)
+    pub step: &'static str,
+    /// Index within the step, for list steps
+    pub index: Option<usize>,
+    /// Value in this trace (None when absent)
+    pub ours: Option<Value>,
+    /// Value in the other trace (None when absent)
+    pub theirs: Option<Value>,
+}
+
+impl fmt::Display for TraceDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |value: &Option<Value>| value.as_ref().map_or("(absent)".to_string(), Value::to_string);
+        match self.index {
+            Some(index) => write!(f, "{}[{}]: {} vs {}", self.step, index, show(&self.ours), show(&self.theirs)),
+            None => write!(f, "{}: {} vs {}", self.step, show(&self.ours), show(&self.theirs)),
+        }
+    }
+}
+
+impl SigningTrace {
+    /// The trace as JSON
+    pub fn to_json(&self) -> Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    /// Read a trace exported by this or another SDK
+    ///
+    /// # Errors
+    ///
+    /// `Serialization` if a recorded step has the wrong type
+    pub fn from_json(json: &Value) -> Result<Self> {
+        serde_json::from_value(json.clone()).map_err(|e| KnishIOError::Serialization(e.to_string()))
+    }
+
+    /// First step where this trace and `other` disagree, in signing order
+    ///
+    /// Steps either trace left empty are skipped.
+    pub fn first_divergence(&self, other: &SigningTrace) -> Option<TraceDivergence> {
+        scalar("molecularHash", &self.molecular_hash, &other.molecular_hash)
+            .or_else(|| scalar("signingToken", &self.signing_token, &other.signing_token))
+            .or_else(|| scalar("signingPosition", &self.signing_position, &other.signing_position))
+            .or_else(|| list("keyChunks", &self.key_chunks, &other.key_chunks))
+            .or_else(|| list("normalizedHash", &self.normalized_hash, &other.normalized_hash))
+            .or_else(|| list("iterations", &self.iterations, &other.iterations))
+            .or_else(|| list("chunkSignatures", &self.chunk_signatures, &other.chunk_signatures))
+            .or_else(|| (!self.signature.is_empty() && !other.signature.is_empty() && self.compressed != other.compressed)
+                .then_some(TraceDivergence {
+                    step: "compressed",
+                    index: None,
+                    ours: Some(Value::Bool(self.compressed)),
+                    theirs: Some(Value::Bool(other.compressed)),
+                }))
+            .or_else(|| scalar("signature", &self.signature, &other.signature))
+            .or_else(|| list("fragments", &self.fragments, &other.fragments))
+    }
+}
+
+fn scalar(step: &'static str, ours: &str, theirs: &str) -> Option<TraceDivergence> {
+    (!ours.is_empty() && !theirs.is_empty() && ours != theirs).then(|| TraceDivergence {
+        step,
+        index: None,
+        ours: Some(Value::from(ours)),
+        theirs: Some(Value::from(theirs)),
+    })
+}
+
+fn list<T: Serialize + PartialEq>(step: &'static str, ours: &[T], theirs: &[T]) -> Option<TraceDivergence> {
+    if ours.is_empty() || theirs.is_empty() {
+        return None;
+    }
+    let index = (0..ours.len().max(theirs.len())).find(|&i| ours.get(i) != theirs.get(i))?;
+    let value = |item: Option<&T>| item.and_then(|item| serde_json::to_value(item).ok());
+    Some(TraceDivergence { step, index: Some(index), ours: value(ours.get(index)), theirs: value(theirs.get(index)) })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::molecule::{Molecule, MoleculeParams};
+    use crate::types::MetaItem;
+    use crate::wallet::Wallet;
+
+    fn signed(traced: bool) -> Molecule {
+        let secret = crate::crypto::generate_secret("trace-signer");
+        let source = Wallet::create(Some(&secret), None, "USER", Some("0".repeat(64).as_str()), None).unwrap();
+        let mut molecule = Molecule::from_params(MoleculeParams::new().secret(secret).source_wallet(source));
+        molecule.created_at = "1700000000000".to_string();
+        molecule.init_meta(vec![MetaItem::new("k", "v")], "note", "n-1", None).unwrap();
+        molecule.set_trace_signing(traced);
+        molecule.sign(None, false, true).unwrap();
+        molecule
+    }
+
+    #[test]
+    fn test_sign_records_trace() {
+        assert!(signed(false).signing_trace().is_none());
+
+        let molecule = signed(true);
+        let trace = molecule.signing_trace().unwrap();
+        assert_eq!(Some(&trace.molecular_hash), molecule.molecular_hash.as_ref());
+        assert_eq!(trace.key_chunks.len(), 16);
+        assert!(trace.key_chunks.iter().all(|chunk| chunk.len() == 128));
+        assert_eq!(trace.normalized_hash.len(), 64);
+        assert_eq!(trace.iterations.len(), 16);
+        assert!(trace.iterations.iter().zip(&trace.normalized_hash).all(|(n, v)| *n as i32 == 8 - *v as i32));
+        assert_eq!(trace.chunk_signatures.concat().len(), 2048);
+
+        // The fragments are the atoms' OTS fragments, cut from the signature in order
+        assert_eq!(trace.fragments.len(), molecule.atoms.len());
+        assert_eq!(trace.fragments.last().unwrap().end, trace.signature.len());
+        for boundary in &trace.fragments {
+            let atom = &molecule.atoms[boundary.atom_index];
+            assert_eq!(atom.ots_fragment.as_deref(), Some(&trace.signature[boundary.start..boundary.end]));
+            assert_eq!(atom.position, boundary.position);
+        }
+        assert!(molecule.check(None).unwrap());
+    }
+
+    #[test]
+    fn test_first_divergence() {
+        let trace = signed(true).signing_trace().unwrap().clone();
+        let exported = trace.to_json();
+        assert_eq!(super::SigningTrace::from_json(&exported).unwrap(), trace);
+        assert!(trace.first_divergence(&trace).is_none());
+
+        // A partial trace from another SDK whose iterations are off by one at chunk 3
+        let mut theirs = serde_json::json!({
+            "molecularHash": exported["molecularHash"],
+            "normalizedHash": exported["normalizedHash"],
+            "iterations": exported["iterations"],
+            "signature": "different",
+        });
+        theirs["iterations"][3] = serde_json::json!(trace.iterations[3] + 1);
+        let divergence = trace.first_divergence(&super::SigningTrace::from_json(&theirs).unwrap()).unwrap();
+        assert_eq!((divergence.step, divergence.index), ("iterations", Some(3)));
+        assert_eq!(divergence.theirs, Some(serde_json::json!(trace.iterations[3] + 1)));
+        assert!(divergence.to_string().starts_with("iterations[3]: "));
+
+        assert!(super::SigningTrace::from_json(&serde_json::json!({ "iterations": "x" })).is_err());
+    }
+}
@@ -27,6 +27,8 @@ pub struct CreateIdentifierParams {
 pub struct MutationCreateIdentifier {
     /// The underlying propose molecule mutation
     propose_molecule: MutationProposeMolecule,
+    /// Sign without attaching the signer's bundle
+    anonymous: bool,
 }
 
 impl MutationCreateIdentifier {
@@ -35,6 +37,7 @@ impl MutationCreateIdentifier {
     pub fn new(graph_ql_client: GraphQLClient, knish_io_client: KnishIOClient, molecule: Molecule) -> Self {
         MutationCreateIdentifier {
             propose_molecule: MutationProposeMolecule::new(graph_ql_client, knish_io_client, molecule),
+            anonymous: false,
         }
     }
     
@@ -42,8 +45,17 @@ impl MutationCreateIdentifier {
     pub fn from_molecule(molecule: Molecule) -> Self {
         MutationCreateIdentifier {
             propose_molecule: MutationProposeMolecule::from_molecule(molecule),
+            anonymous: false,
         }
     }
+
+    /// Sign the molecule anonymously, leaving its bundle unset
+    ///
+    /// The molecule must not already carry a bundle (see `KnishIOClient::set_anonymous`).
+    pub fn anonymous(mut self, anonymous: bool) -> Self {
+        self.anonymous = anonymous;
+        self
+    }
     
     /// Fill the molecule with identifier creation data (matches JS fillMolecule exactly)
     /// JS: fillMolecule({ type, contact, code })
@@ -59,7 +71,7 @@ impl MutationCreateIdentifier {
             // Sign with empty params (matches JS: this.$__molecule.sign({}))
             molecule.sign(
                 None, // empty bundle for sign({})
-                false, // anonymous = false
+                self.anonymous,
                 true   // compressed = true
             )?;
             
@@ -31,6 +31,8 @@ pub struct CreateMetaParams {
 pub struct MutationCreateMeta {
     /// The underlying propose molecule mutation
     propose_molecule: MutationProposeMolecule,
+    /// Sign without attaching the signer's bundle
+    anonymous: bool,
 }
 
 impl MutationCreateMeta {
@@ -39,6 +41,7 @@ impl MutationCreateMeta {
     pub fn new(graph_ql_client: GraphQLClient, knish_io_client: KnishIOClient, molecule: Molecule) -> Self {
         MutationCreateMeta {
             propose_molecule: MutationProposeMolecule::new(graph_ql_client, knish_io_client, molecule),
+            anonymous: false,
         }
     }
     
@@ -46,8 +49,17 @@ impl MutationCreateMeta {
     pub fn from_molecule(molecule: Molecule) -> Self {
         MutationCreateMeta {
             propose_molecule: MutationProposeMolecule::from_molecule(molecule),
+            anonymous: false,
         }
     }
+
+    /// Sign the molecule anonymously, leaving its bundle unset
+    ///
+    /// The molecule must not already carry a bundle (see `KnishIOClient::set_anonymous`).
+    pub fn anonymous(mut self, anonymous: bool) -> Self {
+        self.anonymous = anonymous;
+        self
+    }
     
     /// Fill the molecule with metadata (matches JS fillMolecule exactly)
     /// JS: fillMolecule({ metaType, metaId, meta, policy })
@@ -76,7 +88,7 @@ impl MutationCreateMeta {
             // Sign with empty params (matches JS: this.$__molecule.sign({}))
             molecule.sign(
                 None, // empty bundle for sign({})
-                false, // anonymous = false
+                self.anonymous,
                 true   // compressed = true
             )?;
             
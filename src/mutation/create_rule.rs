@@ -29,6 +29,8 @@ pub struct CreateRuleParams {
 pub struct MutationCreateRule {
     /// The underlying propose molecule mutation
     propose_molecule: MutationProposeMolecule,
+    /// Sign without attaching the signer's bundle
+    anonymous: bool,
 }
 
 impl MutationCreateRule {
@@ -36,6 +38,7 @@ impl MutationCreateRule {
     pub fn new(graph_ql_client: GraphQLClient, knish_io_client: KnishIOClient, molecule: Molecule) -> Self {
         MutationCreateRule {
             propose_molecule: MutationProposeMolecule::new(graph_ql_client, knish_io_client, molecule),
+            anonymous: false,
         }
     }
     
@@ -43,8 +46,17 @@ impl MutationCreateRule {
     pub fn from_molecule(molecule: Molecule) -> Self {
         MutationCreateRule {
             propose_molecule: MutationProposeMolecule::from_molecule(molecule),
+            anonymous: false,
         }
     }
+
+    /// Sign the molecule anonymously, leaving its bundle unset
+    ///
+    /// The molecule must not already carry a bundle (see `KnishIOClient::set_anonymous`).
+    pub fn anonymous(mut self, anonymous: bool) -> Self {
+        self.anonymous = anonymous;
+        self
+    }
     
     /// Fill the molecule with rule creation data (matches JS fillMolecule exactly)
     /// JS: fillMolecule({ metaType, metaId, rule, policy })
@@ -66,7 +78,7 @@ impl MutationCreateRule {
             // Sign with empty params (matches JS: this.$__molecule.sign({}))
             molecule.sign(
                 None, // empty bundle for sign({})
-                false, // anonymous = false
+                self.anonymous,
                 true   // compressed = true
             )?;
             
@@ -6,7 +6,7 @@
 use crate::mutation::{Mutation, propose_molecule::MutationProposeMolecule};
 use crate::query::Query;
 use crate::response::{Response, ResponseCreateToken};
-use crate::molecule::Molecule;
+use crate::molecule::{Molecule, MoleculeParams};
 use crate::wallet::Wallet;
 use crate::graphql::GraphQLClient;
 use crate::client::KnishIOClient;
@@ -89,14 +89,7 @@ impl MutationCreateToken {
     
     /// Create from token creation parameters
     pub fn from_params(params: CreateTokenParams, secret: &str) -> crate::error::Result<Self> {
-        let mut molecule = Molecule::with_params(
-            Some(secret.to_string()),
-            None,
-            None,
-            None,
-            None,
-            None,
-        );
+        let mut molecule = Molecule::from_params(MoleculeParams::new().secret(secret));
         
         // Convert meta HashMap to Vec<MetaItem>
         let meta_items = params.meta.clone().unwrap_or_default().into_iter()
@@ -6,7 +6,7 @@
 use crate::mutation::{Mutation, propose_molecule::MutationProposeMolecule};
 use crate::query::Query;
 use crate::response::{Response, ResponseCreateWallet};
-use crate::molecule::Molecule;
+use crate::molecule::{Molecule, MoleculeParams};
 use crate::wallet::Wallet;
 use crate::graphql::GraphQLClient;
 use crate::client::KnishIOClient;
@@ -58,14 +58,7 @@ impl MutationCreateWallet {
     
     /// Create from a wallet with proper initialization
     pub fn from_wallet(wallet: &Wallet, secret: &str) -> crate::error::Result<Self> {
-        let mut molecule = Molecule::with_params(
-            Some(secret.to_string()),
-            None,
-            None,
-            None,
-            None,
-            None,
-        );
+        let mut molecule = Molecule::from_params(MoleculeParams::new().secret(secret));
         
         // Initialize wallet creation in molecule
         molecule.init_wallet_creation(wallet, Vec::new())?;
@@ -25,6 +25,8 @@ pub struct DepositBufferTokenParams {
 pub struct MutationDepositBufferToken {
     /// The underlying propose molecule mutation
     propose_molecule: MutationProposeMolecule,
+    /// Sign without attaching the signer's bundle
+    anonymous: bool,
 }
 
 impl MutationDepositBufferToken {
@@ -32,6 +34,7 @@ impl MutationDepositBufferToken {
     pub fn new(graph_ql_client: GraphQLClient, knish_io_client: KnishIOClient, molecule: Molecule) -> Self {
         MutationDepositBufferToken {
             propose_molecule: MutationProposeMolecule::new(graph_ql_client, knish_io_client, molecule),
+            anonymous: false,
         }
     }
     
@@ -39,8 +42,17 @@ impl MutationDepositBufferToken {
     pub fn from_molecule(molecule: Molecule) -> Self {
         MutationDepositBufferToken {
             propose_molecule: MutationProposeMolecule::from_molecule(molecule),
+            anonymous: false,
         }
     }
+
+    /// Sign the molecule anonymously, leaving its bundle unset
+    ///
+    /// The molecule must not already carry a bundle (see `KnishIOClient::set_anonymous`).
+    pub fn anonymous(mut self, anonymous: bool) -> Self {
+        self.anonymous = anonymous;
+        self
+    }
     
     /// Fill the molecule with deposit buffer data (matches JS fillMolecule exactly)
     /// JS: fillMolecule({ amount, tradeRates })
@@ -53,7 +65,7 @@ impl MutationDepositBufferToken {
             )?;
             
             // Sign with empty params (matches JS: this.$__molecule.sign({}))
-            molecule.sign(None, false, true)?;
+            molecule.sign(None, self.anonymous, true)?;
             
             // Check with source wallet (matches JS: this.$__molecule.check(this.$__molecule.sourceWallet))
             if let Some(ref source_wallet) = molecule.source_wallet {
@@ -96,7 +108,7 @@ impl Query for MutationDepositBufferToken {
     
     /// Create a response from the JSON data (uses base ProposeMolecule response)
     fn create_response(&self, json: Value) -> Box<dyn Response> {
-        match ResponseProposeMolecule::new(json, None) {
+        match ResponseProposeMolecule::with_molecule(json, None, Some(self.molecule().clone())) {
             Ok(resp) => Box::new(resp),
             Err(e) => {
                 eprintln!("ResponseProposeMolecule construction failed: {}", e);
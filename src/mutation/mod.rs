@@ -44,7 +44,9 @@ pub trait Mutation: Query + Send + Sync {
         // correctly (JS keeps the full envelope + Dot.get("data.X"); the prior code unwrapped
         // response.data, leaving every "data.X" lookup to fail and fall back to the wrapper).
         let json_data = json!({ "data": response.data });
-        Ok(self.create_response(json_data))
+        let mut response_obj = self.create_response(json_data);
+        response_obj.set_headers(response.headers);
+        Ok(response_obj)
     }
     
     /// Create mutation context for authentication (can be overridden)
@@ -118,6 +120,23 @@ impl Mutation for BaseMutation {
     fn get_mutation(&self) -> &str {
         &self.mutation_string
     }
+
+    /// Execute the mutation after linting its caller-supplied document
+    async fn execute(
+        &self,
+        client: &GraphQLClient,
+        variables: Option<Value>,
+        _context: Option<HashMap<String, Value>>,
+    ) -> Result<Box<dyn Response>> {
+        crate::graphql::lint_document(&self.mutation_string)?;
+        let request = self.create_mutation_request(variables);
+        let response = client.mutate(request).await?;
+
+        let json_data = json!({ "data": response.data });
+        let mut response_obj = self.create_response(json_data);
+        response_obj.set_headers(response.headers);
+        Ok(response_obj)
+    }
 }
 
 // Specific mutation type implementations
@@ -246,7 +265,7 @@ impl Default for MutationBuilder {
 /// Convenience functions for creating common mutations
 pub mod helpers {
     use super::*;
-    use crate::molecule::Molecule;
+    use crate::molecule::{Molecule, MoleculeParams};
     use crate::wallet::Wallet;
     
     /// Create a value transfer mutation
@@ -262,13 +281,10 @@ pub mod helpers {
         recipient_wallet: &Wallet,
         amount: f64,
     ) -> Result<MutationTransferTokens> {
-        let mut molecule = Molecule::with_params(
-            Some(secret.to_string()),
-            None,
-            Some(source_wallet.clone()),
-            None,
-            None,
-            None,
+        let mut molecule = Molecule::from_params(
+            MoleculeParams::new()
+                .secret(secret)
+                .source_wallet(source_wallet.clone()),
         );
         
         // Initialize value transfer in molecule
@@ -288,14 +304,7 @@ pub mod helpers {
         secret: &str,
         wallet: &Wallet,
     ) -> Result<MutationCreateWallet> {
-        let mut molecule = Molecule::with_params(
-            Some(secret.to_string()),
-            None,
-            None,
-            None,
-            None,
-            None,
-        );
+        let mut molecule = Molecule::from_params(MoleculeParams::new().secret(secret));
         
         // Initialize wallet creation in molecule
         molecule.init_wallet_creation(wallet, Vec::new())?;
@@ -318,14 +327,7 @@ pub mod helpers {
         amount: f64,
         meta: Option<HashMap<String, serde_json::Value>>,
     ) -> Result<MutationCreateToken> {
-        let mut molecule = Molecule::with_params(
-            Some(secret.to_string()),
-            None,
-            None,
-            None,
-            None,
-            None,
-        );
+        let mut molecule = Molecule::from_params(MoleculeParams::new().secret(secret));
         
         // Convert meta HashMap to Vec<MetaItem>
         let meta_items = meta.unwrap_or_default().into_iter()
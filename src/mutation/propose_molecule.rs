@@ -108,7 +108,7 @@ impl Query for MutationProposeMolecule {
     /// Create a response from the JSON data
     fn create_response(&self, json: Value) -> Box<dyn Response> {
         // Using ResponseProposeMolecule for proper type safety
-        match ResponseProposeMolecule::new(json, None) {
+        match ResponseProposeMolecule::with_molecule(json, None, Some(self.molecule.clone())) {
             Ok(resp) => Box::new(resp),
             Err(e) => {
                 eprintln!("ResponseProposeMolecule construction failed: {}", e);
@@ -144,7 +144,9 @@ impl Mutation for MutationProposeMolecule {
         // Re-wrap under "data" so the response classes' `data.<Field>` data_keys navigate
         // correctly (matches JS's full-envelope + Dot.get convention).
         let json_data = json!({ "data": response.data });
-        Ok(self.create_response(json_data))
+        let mut response_obj = self.create_response(json_data);
+        response_obj.set_headers(response.headers);
+        Ok(response_obj)
     }
 }
 
@@ -35,6 +35,8 @@ pub struct RequestTokensParams {
 pub struct MutationRequestTokens {
     /// The underlying propose molecule mutation
     propose_molecule: MutationProposeMolecule,
+    /// Sign without attaching the signer's bundle
+    anonymous: bool,
 }
 
 impl MutationRequestTokens {
@@ -43,6 +45,7 @@ impl MutationRequestTokens {
     pub fn new(graph_ql_client: GraphQLClient, knish_io_client: KnishIOClient, molecule: Molecule) -> Self {
         MutationRequestTokens {
             propose_molecule: MutationProposeMolecule::new(graph_ql_client, knish_io_client, molecule),
+            anonymous: false,
         }
     }
     
@@ -50,8 +53,17 @@ impl MutationRequestTokens {
     pub fn from_molecule(molecule: Molecule) -> Self {
         MutationRequestTokens {
             propose_molecule: MutationProposeMolecule::from_molecule(molecule),
+            anonymous: false,
         }
     }
+
+    /// Sign the molecule anonymously, leaving its bundle unset
+    ///
+    /// The molecule must not already carry a bundle (see `KnishIOClient::set_anonymous`).
+    pub fn anonymous(mut self, anonymous: bool) -> Self {
+        self.anonymous = anonymous;
+        self
+    }
     
     /// Fill the molecule with token request data (matches JS fillMolecule exactly)
     /// JS: fillMolecule({ token, amount, metaType, metaId, meta = null, batchId = null })
@@ -79,7 +91,7 @@ impl MutationRequestTokens {
             // Sign with empty params (matches JS: this.$__molecule.sign({}))
             molecule.sign(
                 None, // empty bundle for sign({})
-                false, // anonymous = false
+                self.anonymous,
                 true   // compressed = true
             )?;
             
@@ -35,6 +35,8 @@ pub struct MultiTransferTokensParams {
 pub struct MutationTransferTokens {
     /// The underlying propose molecule mutation
     propose_molecule: MutationProposeMolecule,
+    /// Sign without attaching the signer's bundle
+    anonymous: bool,
 }
 
 impl MutationTransferTokens {
@@ -43,6 +45,7 @@ impl MutationTransferTokens {
     pub fn new(graph_ql_client: GraphQLClient, knish_io_client: KnishIOClient, molecule: Molecule) -> Self {
         MutationTransferTokens {
             propose_molecule: MutationProposeMolecule::new(graph_ql_client, knish_io_client, molecule),
+            anonymous: false,
         }
     }
     
@@ -50,8 +53,17 @@ impl MutationTransferTokens {
     pub fn from_molecule(molecule: Molecule) -> Self {
         MutationTransferTokens {
             propose_molecule: MutationProposeMolecule::from_molecule(molecule),
+            anonymous: false,
         }
     }
+
+    /// Sign the molecule anonymously, leaving its bundle unset
+    ///
+    /// The molecule must not already carry a bundle (see `KnishIOClient::set_anonymous`).
+    pub fn anonymous(mut self, anonymous: bool) -> Self {
+        self.anonymous = anonymous;
+        self
+    }
     
     /// Fill the molecule with transfer data (matches JS fillMolecule exactly)
     /// JS: fillMolecule({ recipientWallet, amount })
@@ -63,7 +75,7 @@ impl MutationTransferTokens {
             // Sign with empty params (matches JS: this.$__molecule.sign({}))
             molecule.sign(
                 None, // empty bundle for sign({})
-                false, // anonymous = false
+                self.anonymous,
                 true   // compressed = true
             )?;
             
@@ -85,7 +97,7 @@ impl MutationTransferTokens {
 
             molecule.sign(
                 None,  // empty bundle for sign({})
-                false, // anonymous = false
+                self.anonymous,
                 true   // compressed = true
             )?;
 
@@ -144,6 +156,7 @@ impl MutationTransferTokens {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::wallet::WalletParams;
     
     #[test]
     fn test_mutation_transfer_tokens_creation() {
@@ -167,7 +180,7 @@ mod tests {
     #[test]
     fn test_transfer_params() {
         let params = TransferTokensParams {
-            recipient_wallet: Wallet::new(Some("recipient-secret"), None, Some("TEST"), None, None, None, None).unwrap(),
+            recipient_wallet: Wallet::from_params(WalletParams::new().secret("recipient-secret").token("TEST")).unwrap(),
             amount: 50.0,
         };
         
@@ -27,6 +27,8 @@ pub struct WithdrawBufferTokenParams {
 pub struct MutationWithdrawBufferToken {
     /// The underlying propose molecule mutation
     propose_molecule: MutationProposeMolecule,
+    /// Sign without attaching the signer's bundle
+    anonymous: bool,
 }
 
 impl MutationWithdrawBufferToken {
@@ -34,6 +36,7 @@ impl MutationWithdrawBufferToken {
     pub fn new(graph_ql_client: GraphQLClient, knish_io_client: KnishIOClient, molecule: Molecule) -> Self {
         MutationWithdrawBufferToken {
             propose_molecule: MutationProposeMolecule::new(graph_ql_client, knish_io_client, molecule),
+            anonymous: false,
         }
     }
     
@@ -41,8 +44,17 @@ impl MutationWithdrawBufferToken {
     pub fn from_molecule(molecule: Molecule) -> Self {
         MutationWithdrawBufferToken {
             propose_molecule: MutationProposeMolecule::from_molecule(molecule),
+            anonymous: false,
         }
     }
+
+    /// Sign the molecule anonymously, leaving its bundle unset
+    ///
+    /// The molecule must not already carry a bundle (see `KnishIOClient::set_anonymous`).
+    pub fn anonymous(mut self, anonymous: bool) -> Self {
+        self.anonymous = anonymous;
+        self
+    }
     
     /// Fill the molecule with withdraw buffer data (matches JS fillMolecule exactly)
     /// JS: fillMolecule({ recipients, signingWallet })
@@ -55,7 +67,7 @@ impl MutationWithdrawBufferToken {
             )?;
             
             // Sign with empty params (matches JS: this.$__molecule.sign({}))
-            molecule.sign(None, false, true)?;
+            molecule.sign(None, self.anonymous, true)?;
             
             // Check with source wallet (matches JS: this.$__molecule.check(this.$__molecule.sourceWallet))
             if let Some(ref source_wallet) = molecule.source_wallet {
@@ -98,7 +110,7 @@ impl Query for MutationWithdrawBufferToken {
     
     /// Create a response from the JSON data (uses base ProposeMolecule response)
     fn create_response(&self, json: Value) -> Box<dyn Response> {
-        match ResponseProposeMolecule::new(json, None) {
+        match ResponseProposeMolecule::with_molecule(json, None, Some(self.molecule().clone())) {
             Ok(resp) => Box::new(resp),
             Err(e) => {
                 eprintln!("ResponseProposeMolecule construction failed: {}", e);
@@ -127,6 +139,7 @@ impl MutationWithdrawBufferToken {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::wallet::WalletParams;
     
     #[test]
     fn test_mutation_withdraw_buffer_token_creation() {
@@ -142,14 +155,13 @@ mod tests {
         let mut recipients = HashMap::new();
         recipients.insert("addr1".to_string(), 50.0);
         
-        let signing_wallet = Wallet::new(
-            Some("test_secret"),
-            Some("test_bundle"),
-            Some("TEST"),
-            Some("test_address"),
-            Some("test_position"),
-            None,
-            None
+        let signing_wallet = Wallet::from_params(
+            WalletParams::new()
+                .secret("test_secret")
+                .bundle("test_bundle")
+                .token("TEST")
+                .address("test_address")
+                .position("test_position"),
         ).expect("Failed to create wallet");
         
         let params = WithdrawBufferTokenParams {
@@ -0,0 +1,345 @@
+//! Effective policy resolution
+//!
+//! A meta instance can be governed by policies at three levels: the token it belongs to,
+//! its meta type, and the instance itself. `EffectivePolicy` merges them into one answer
+//! per `(action, key)`:
+//!
+//! 1. The most specific level that defines the `(action, key)` pair decides outright:
+//!    instance over meta type over token. Less specific clauses for the same pair are
+//!    shadowed, not unioned, so a level can narrow as well as widen access.
+//! 2. Pairs no level defines are inherited from the defaults of `PolicyMeta::fill_default`:
+//!    everyone may read, only the owner may write (anyone may write `characters`/`pubkey`).
+//!
+//! Within the deciding clause, `all` admits every bundle, `self` admits the instance
+//! owner (when known) and any other entry admits that bundle hash.
+
+use super::PolicyMeta;
+use serde_json::Value;
+use std::fmt;
+
+/// Level a policy clause was defined at, least specific first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PolicyLevel {
+    /// Built-in defaults applied when no level defines a key
+    Default,
+    /// Policy of the token the instance belongs to
+    Token,
+    /// Policy of the meta type
+    MetaType,
+    /// Policy of the meta instance itself
+    Instance,
+}
+
+impl fmt::Display for PolicyLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PolicyLevel::Default => "default",
+            PolicyLevel::Token => "token",
+            PolicyLevel::MetaType => "metaType",
+            PolicyLevel::Instance => "instance",
+        })
+    }
+}
+
+/// A policy attached at one level
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicySource {
+    /// Level the policy applies at
+    pub level: PolicyLevel,
+    /// Meta type the policy is stored under
+    pub meta_type: String,
+    /// Meta ID the policy is stored under (None for meta type policies)
+    pub meta_id: Option<String>,
+    /// The policy's read/write clauses
+    pub policy: PolicyMeta,
+}
+
+impl PolicySource {
+    /// Read a policy record returned by the node's `Policy` query
+    ///
+    /// The read/write clauses are taken from the record's `policy` field (an object or
+    /// a JSON string), or from the record itself. Records without clauses yield None.
+    pub fn from_record(level: PolicyLevel, record: &Value) -> Option<Self> {
+        let clauses = match record.get("policy") {
+            Some(Value::String(json)) => serde_json::from_str(json).ok()?,
+            Some(policy) => policy.clone(),
+            None => record.clone(),
+        };
+        let policy = PolicyMeta { policy: PolicyMeta::normalize_policy(clauses) };
+        if policy.is_empty() {
+            return None;
+        }
+
+        Some(PolicySource {
+            level,
+            meta_type: record.get("metaType").and_then(Value::as_str).unwrap_or_default().to_string(),
+            meta_id: record.get("metaId").and_then(Value::as_str).filter(|id| !id.is_empty()).map(str::to_string),
+            policy,
+        })
+    }
+}
+
+/// One `action`/`key` permission list and where it came from
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyClause {
+    /// Level the clause was defined at
+    pub level: PolicyLevel,
+    /// Meta type of the defining policy (empty for defaults)
+    pub meta_type: String,
+    /// Meta ID of the defining policy
+    pub meta_id: Option<String>,
+    /// Action the clause governs ("read" or "write")
+    pub action: String,
+    /// Metadata key the clause governs
+    pub key: String,
+    /// Permission entries (`all`, `self` or bundle hashes)
+    pub permissions: Vec<String>,
+}
+
+impl fmt::Display for PolicyClause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}.{} = [{}]", self.level, self.action, self.key, self.permissions.join(", "))?;
+        if !self.meta_type.is_empty() {
+            write!(f, " ({}:{})", self.meta_type, self.meta_id.as_deref().unwrap_or("*"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Answer to `EffectivePolicy::can`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyDecision {
+    /// Whether the bundle may perform the action
+    pub allowed: bool,
+    /// Clause that decided the answer
+    pub decided_by: PolicyClause,
+    /// Less specific clauses for the same action and key, overridden by `decided_by`
+    pub shadowed: Vec<PolicyClause>,
+}
+
+/// Token, meta type and instance policies merged by precedence
+///
+/// # Example
+///
+/// ```rust
+/// use knishio_client::policy_meta::{EffectivePolicy, PolicyLevel, PolicyMeta};
+/// use serde_json::json;
+///
+/// let policy = EffectivePolicy::new()
+///     .with_owner("alice")
+///     .with_policy(PolicyLevel::MetaType, "car", None, PolicyMeta::new(json!({ "write": { "color": ["all"] } }), vec![]))
+///     .with_policy(PolicyLevel::Instance, "car", Some("VIN1"), PolicyMeta::new(json!({ "write": { "color": ["self"] } }), vec![]));
+///
+/// assert!(policy.can("alice", "write", "color").allowed);
+///
+/// let decision = policy.can("bob", "write", "color");
+/// assert!(!decision.allowed);
+/// assert_eq!(decision.decided_by.level, PolicyLevel::Instance);
+/// assert_eq!(decision.shadowed[0].level, PolicyLevel::MetaType);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EffectivePolicy {
+    /// Applicable policies, in the order they were added
+    sources: Vec<PolicySource>,
+    /// Bundle that owns the instance, matched by `self`
+    owner: Option<String>,
+}
+
+impl EffectivePolicy {
+    /// Empty policy set: every answer comes from the defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the bundle that `self` permissions refer to
+    pub fn with_owner(mut self, owner: impl Into<String>) -> Self {
+        self.owner = Some(owner.into());
+        self
+    }
+
+    /// Add the policy defined at `level`
+    pub fn with_policy(mut self, level: PolicyLevel, meta_type: impl Into<String>, meta_id: Option<&str>, policy: PolicyMeta) -> Self {
+        self.add(PolicySource {
+            level,
+            meta_type: meta_type.into(),
+            meta_id: meta_id.map(str::to_string),
+            policy,
+        });
+        self
+    }
+
+    /// Add an applicable policy
+    pub fn add(&mut self, source: PolicySource) {
+        self.sources.push(source);
+    }
+
+    /// Applicable policies
+    pub fn sources(&self) -> &[PolicySource] {
+        &self.sources
+    }
+
+    /// Bundle that `self` permissions refer to
+    pub fn owner(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
+
+    /// Every clause for `action` and `key`, most specific first
+    ///
+    /// The list always ends with the default clause, so it is never empty.
+    pub fn clauses(&self, action: &str, key: &str) -> Vec<PolicyClause> {
+        let mut clauses: Vec<PolicyClause> = self.sources.iter()
+            .filter_map(|source| source.policy.get_permissions(action, key).map(|permissions| PolicyClause {
+                level: source.level,
+                meta_type: source.meta_type.clone(),
+                meta_id: source.meta_id.clone(),
+                action: action.to_string(),
+                key: key.to_string(),
+                permissions: permissions.clone(),
+            }))
+            .collect();
+        // Stable, so equal levels keep the order they were added in
+        clauses.sort_by_key(|clause| std::cmp::Reverse(clause.level));
+
+        clauses.push(PolicyClause {
+            level: PolicyLevel::Default,
+            meta_type: String::new(),
+            meta_id: None,
+            action: action.to_string(),
+            key: key.to_string(),
+            permissions: Self::default_permissions(action, key),
+        });
+        clauses
+    }
+
+    /// Whether `bundle` may perform `action` on `key`, and which clauses say so
+    pub fn can(&self, bundle: &str, action: &str, key: &str) -> PolicyDecision {
+        let mut shadowed = self.clauses(action, key);
+        let decided_by = shadowed.remove(0);
+
+        PolicyDecision {
+            allowed: self.admits(&decided_by.permissions, bundle),
+            decided_by,
+            shadowed,
+        }
+    }
+
+    /// The merged read/write permissions of every key any level defines
+    pub fn resolve(&self) -> PolicyMeta {
+        let mut merged = PolicyMeta::default();
+        for source in &self.sources {
+            for (action, keys) in source.policy.get() {
+                for key in keys.keys() {
+                    let decided_by = self.can("", action, key).decided_by;
+                    merged.set_permissions(action, key, decided_by.permissions);
+                }
+            }
+        }
+        merged
+    }
+
+    fn admits(&self, permissions: &[String], bundle: &str) -> bool {
+        permissions.iter().any(|permission| match permission.as_str() {
+            "all" => true,
+            "self" => self.owner.as_deref() == Some(bundle),
+            other => other == bundle,
+        })
+    }
+
+    /// Permissions `PolicyMeta::fill_default` gives keys without an explicit policy
+    fn default_permissions(action: &str, key: &str) -> Vec<String> {
+        let permission = match action {
+            "write" if key == "characters" || key == "pubkey" => "all",
+            "write" => "self",
+            _ => "all",
+        };
+        vec![permission.to_string()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn policy(value: serde_json::Value) -> PolicyMeta {
+        PolicyMeta { policy: PolicyMeta::normalize_policy(value) }
+    }
+
+    fn layered() -> EffectivePolicy {
+        EffectivePolicy::new()
+            .with_owner("owner")
+            .with_policy(PolicyLevel::Token, "token", Some("CAR"), policy(json!({
+                "read": { "vin": ["auditor"], "price": ["all"] },
+                "write": { "price": ["dealer"] },
+            })))
+            .with_policy(PolicyLevel::Instance, "car", Some("VIN1"), policy(json!({
+                "read": { "vin": ["self", "insurer"] },
+            })))
+            .with_policy(PolicyLevel::MetaType, "car", None, policy(json!({
+                "read": { "vin": ["all"] },
+                "write": { "price": ["self"] },
+            })))
+    }
+
+    #[test]
+    fn test_most_specific_level_decides() {
+        let policy = layered();
+
+        // Instance narrows the meta type's read access to vin
+        let decision = policy.can("stranger", "read", "vin");
+        assert!(!decision.allowed);
+        assert_eq!(decision.decided_by.level, PolicyLevel::Instance);
+        assert_eq!(
+            decision.shadowed.iter().map(|clause| clause.level).collect::<Vec<_>>(),
+            vec![PolicyLevel::MetaType, PolicyLevel::Token, PolicyLevel::Default]
+        );
+        assert!(policy.can("owner", "read", "vin").allowed);
+        assert!(policy.can("insurer", "read", "vin").allowed);
+        assert!(!policy.can("auditor", "read", "vin").allowed);
+
+        // The meta type overrides the token for write access to price
+        let decision = policy.can("dealer", "write", "price");
+        assert!(!decision.allowed);
+        assert_eq!(decision.decided_by.level, PolicyLevel::MetaType);
+        assert!(policy.can("owner", "write", "price").allowed);
+
+        // Only the token defines read access to price
+        assert_eq!(policy.can("anyone", "read", "price").decided_by.level, PolicyLevel::Token);
+    }
+
+    #[test]
+    fn test_sources_from_records() {
+        let record = json!({ "metaType": "car", "metaId": "VIN1", "policy": "{\"read\":{\"vin\":[\"self\"]}}" });
+        let source = PolicySource::from_record(PolicyLevel::Instance, &record).unwrap();
+        assert_eq!(source.meta_id.as_deref(), Some("VIN1"));
+        assert_eq!(source.policy.get_permissions("read", "vin").unwrap(), &vec!["self".to_string()]);
+
+        let inline = json!({ "metaType": "car", "metaId": "", "write": { "price": ["all"] } });
+        let source = PolicySource::from_record(PolicyLevel::MetaType, &inline).unwrap();
+        assert_eq!(source.meta_id, None);
+        assert!(source.policy.get_permissions("write", "price").is_some());
+
+        assert!(PolicySource::from_record(PolicyLevel::MetaType, &json!({ "metaType": "car", "rule": "[]" })).is_none());
+    }
+
+    #[test]
+    fn test_defaults_and_resolution() {
+        let policy = layered();
+
+        let decision = policy.can("stranger", "write", "color");
+        assert!(!decision.allowed);
+        assert_eq!(decision.decided_by.level, PolicyLevel::Default);
+        assert!(decision.shadowed.is_empty());
+        assert!(policy.can("owner", "write", "color").allowed);
+        assert!(policy.can("stranger", "write", "pubkey").allowed);
+        assert!(policy.can("stranger", "read", "color").allowed);
+
+        // Without a known owner nobody matches `self`
+        assert!(!EffectivePolicy::new().can("owner", "write", "color").allowed);
+
+        let resolved = policy.resolve();
+        assert_eq!(resolved.get_permissions("read", "vin").unwrap(), &vec!["self".to_string(), "insurer".to_string()]);
+        assert_eq!(resolved.get_permissions("write", "price").unwrap(), &vec!["self".to_string()]);
+        assert_eq!(resolved.get_permissions("read", "price").unwrap(), &vec!["all".to_string()]);
+    }
+}
@@ -7,6 +7,53 @@
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use crate::error::{KnishIOError, Result};
+use crate::wallet::Wallet;
+
+pub mod effective;
+
+pub use effective::{EffectivePolicy, PolicyClause, PolicyDecision, PolicyLevel, PolicySource};
+
+/// Policy actions, as the keys of a policy object
+pub const POLICY_ACTIONS: [&str; 2] = ["read", "write"];
+
+/// Who a policy clause admits
+///
+/// A clause lists bundle hashes and the two roles `all` (every bundle) and `self` (the
+/// owner of the meta instance).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PolicyEntry {
+    /// Every bundle
+    All,
+    /// The owner of the meta instance
+    Owner,
+    /// One bundle
+    Bundle(String),
+}
+
+impl PolicyEntry {
+    /// Parse one entry of a permission list
+    ///
+    /// # Errors
+    ///
+    /// `PolicyInvalid` for anything but `all`, `self` or a bundle hash
+    pub fn parse(entry: &str) -> Result<Self> {
+        match entry {
+            "all" => Ok(PolicyEntry::All),
+            "self" => Ok(PolicyEntry::Owner),
+            bundle if Wallet::is_bundle_hash(bundle) => Ok(PolicyEntry::Bundle(bundle.to_string())),
+            _ => Err(KnishIOError::PolicyInvalid),
+        }
+    }
+
+    /// The entry as written in a policy
+    pub fn as_str(&self) -> &str {
+        match self {
+            PolicyEntry::All => "all",
+            PolicyEntry::Owner => "self",
+            PolicyEntry::Bundle(bundle) => bundle,
+        }
+    }
+}
 
 /// Represents access control policies for metadata
 ///
@@ -125,6 +172,69 @@ impl PolicyMeta {
         normalized
     }
 
+    /// Check that `policy` has the shape of a policy object
+    ///
+    /// A policy is an object whose keys are `read` and/or `write`, each mapping metadata
+    /// keys to a list of `PolicyEntry` strings:
+    ///
+    /// ```rust
+    /// use knishio_client::policy_meta::PolicyMeta;
+    /// use serde_json::json;
+    ///
+    /// assert!(PolicyMeta::validate(&json!({ "read": { "email": ["self"] }, "write": { "email": ["self"] } })).is_ok());
+    /// assert!(PolicyMeta::validate(&json!({ "read": { "email": "self" } })).is_err());
+    /// assert!(PolicyMeta::validate(&json!({ "read": { "email": ["admins"] } })).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// `PolicyInvalid` on unknown actions, non-list permissions, empty keys or entries that
+    /// are not `all`, `self` or a bundle hash
+    pub fn validate(policy: &serde_json::Value) -> Result<()> {
+        let actions = policy.as_object().ok_or(KnishIOError::PolicyInvalid)?;
+        for (action, clauses) in actions {
+            if !POLICY_ACTIONS.contains(&action.as_str()) {
+                return Err(KnishIOError::PolicyInvalid);
+            }
+            for (key, entries) in clauses.as_object().ok_or(KnishIOError::PolicyInvalid)? {
+                if key.is_empty() {
+                    return Err(KnishIOError::PolicyInvalid);
+                }
+                for entry in entries.as_array().ok_or(KnishIOError::PolicyInvalid)? {
+                    PolicyEntry::parse(entry.as_str().ok_or(KnishIOError::PolicyInvalid)?)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate `policy` and build the policy for `meta_keys` from it
+    ///
+    /// Like `new`, but invalid policies are refused instead of silently trimmed.
+    ///
+    /// # Errors
+    ///
+    /// `PolicyInvalid` if `policy` fails `validate`
+    pub fn parse(policy: serde_json::Value, meta_keys: Vec<String>) -> Result<Self> {
+        Self::validate(&policy)?;
+        Ok(Self::new(policy, meta_keys))
+    }
+
+    /// Admit `entries` to `action` on `key`
+    ///
+    /// ```rust
+    /// use knishio_client::policy_meta::{PolicyEntry, PolicyMeta};
+    ///
+    /// let policy = PolicyMeta::default()
+    ///     .allow("read", "email", &[PolicyEntry::Owner, PolicyEntry::Bundle("ab".repeat(32))])
+    ///     .allow("write", "email", &[PolicyEntry::Owner]);
+    /// assert_eq!(policy.get_permissions("read", "email").unwrap().len(), 2);
+    /// ```
+    pub fn allow(mut self, action: &str, key: &str, entries: &[PolicyEntry]) -> Self {
+        self.set_permissions(action, key, entries.iter().map(|entry| entry.as_str().to_string()).collect());
+        self
+    }
+
     /// Fill default policy values for metadata keys
     ///
     /// Equivalent to fillDefault(metaKeys) in JavaScript SDK
@@ -658,6 +768,30 @@ mod tests {
         assert!(display_str.contains("pubkey"));
     }
 
+    #[test]
+    fn test_validate_policy_shape() {
+        let bundle = "0123456789abcdef".repeat(4);
+        assert!(PolicyMeta::validate(&json!({})).is_ok());
+        assert!(PolicyMeta::validate(&json!({ "read": { "email": ["all", "self", bundle] }, "write": {} })).is_ok());
+
+        for invalid in [
+            json!([]),
+            json!({ "delete": { "email": ["self"] } }),
+            json!({ "read": ["self"] }),
+            json!({ "read": { "email": "self" } }),
+            json!({ "read": { "email": [1] } }),
+            json!({ "read": { "email": ["everyone"] } }),
+            json!({ "read": { "": ["self"] } }),
+        ] {
+            assert!(matches!(PolicyMeta::validate(&invalid), Err(KnishIOError::PolicyInvalid)), "{}", invalid);
+        }
+
+        let policy = PolicyMeta::parse(json!({ "write": { "email": [bundle] } }), vec!["email".to_string()]).unwrap();
+        assert_eq!(policy.policy["write"]["email"], vec![bundle.clone()]);
+        assert_eq!(policy.policy["read"]["email"], vec!["all"]);
+        assert_eq!(PolicyEntry::parse(&bundle).unwrap(), PolicyEntry::Bundle(bundle));
+    }
+
     #[test]
     fn test_javascript_compatibility() {
         // Test exact JavaScript SDK behavior reproduction
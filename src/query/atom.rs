@@ -3,10 +3,15 @@
 //! Query for getting atoms with comprehensive filtering capabilities,
 //! equivalent to QueryAtom.js
 
+use crate::error::{KnishIOError, Result};
+use crate::graphql::GraphQLClient;
 use crate::query::Query;
 use crate::response::{Response, ResponseAtom};
 use serde_json::{json, Value};
 
+/// Atoms per page fetched by `QueryAtom::fetch_all`
+pub const ATOM_PAGE_SIZE: usize = 100;
+
 /// Query for getting atoms with comprehensive filtering capabilities
 #[derive(Debug, Default)]
 pub struct QueryAtom {
@@ -214,6 +219,28 @@ impl QueryAtom {
     pub fn latest() -> Self {
         Self::new().with_latest(true)
     }
+
+    /// Every matching atom, fetched `ATOM_PAGE_SIZE` at a time
+    ///
+    /// # Errors
+    ///
+    /// `InvalidResponse` when a page has no `instances` list, or the request's error
+    pub async fn fetch_all(&self, client: &GraphQLClient) -> Result<Vec<Value>> {
+        let mut atoms = Vec::new();
+        loop {
+            let mut variables = self.compiled_variables(None).unwrap_or_else(|| json!({}));
+            variables["queryArgs"] = json!({ "limit": ATOM_PAGE_SIZE.to_string(), "offset": atoms.len() });
+
+            let data = self.execute(client, Some(variables), None).await?.into_data();
+            let page = data["instances"].as_array().ok_or(KnishIOError::InvalidResponse)?;
+            let total = data["paginatorInfo"]["total"].as_u64().map_or(usize::MAX, |total| total as usize);
+            atoms.extend(page.iter().cloned());
+
+            if page.len() < ATOM_PAGE_SIZE || atoms.len() >= total {
+                return Ok(atoms);
+            }
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -5,6 +5,7 @@
 
 use crate::query::Query;
 use crate::response::{Response, ResponseContinuId};
+use crate::types::DEFAULT_USER_TOKEN;
 use serde_json::{json, Value};
 
 /// Queries the node for the next wallet to sign with for ContinuID
@@ -23,7 +24,7 @@ impl QueryContinuId {
     pub fn new(bundle: impl Into<String>) -> Self {
         QueryContinuId {
             bundle: bundle.into(),
-            token: "USER".to_string(),
+            token: DEFAULT_USER_TOKEN.to_string(),
         }
     }
 
@@ -60,6 +60,48 @@ pub enum ValueValue {
     Multiple(Vec<String>),
 }
 
+/// One condition of a `MetaFilter` list, matched against an instance's latest metas
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct MetaFilter {
+    /// Meta key
+    pub key: String,
+    /// Value compared against
+    pub value: String,
+    /// Comparison operator, `=` unless set
+    pub comparison: String,
+    /// How the condition joins the previous ones: `and` or `or`
+    pub criterion: String,
+}
+
+impl MetaFilter {
+    /// Instances whose `key` equals `value`
+    pub fn eq(key: impl Into<String>, value: impl Into<String>) -> Self {
+        MetaFilter {
+            key: key.into(),
+            value: value.into(),
+            comparison: "=".to_string(),
+            criterion: "and".to_string(),
+        }
+    }
+
+    /// Instances whose `key` differs from `value`
+    pub fn ne(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::eq(key, value).comparison("!=")
+    }
+
+    /// Use another comparison operator (e.g. `>`, `<=`, `like`)
+    pub fn comparison(mut self, comparison: impl Into<String>) -> Self {
+        self.comparison = comparison.into();
+        self
+    }
+
+    /// Join this condition to the previous ones with `or` instead of `and`
+    pub fn or(mut self) -> Self {
+        self.criterion = "or".to_string();
+        self
+    }
+}
+
 impl QueryMetaType {
     /// Create a new QueryMetaType instance
     pub fn new() -> Self {
@@ -126,6 +168,12 @@ impl QueryMetaType {
         self
     }
 
+    /// Set the filter from typed conditions
+    pub fn with_meta_filters(mut self, filters: &[MetaFilter]) -> Self {
+        self.filter = Some(json!(filters));
+        self
+    }
+
     /// Set the query arguments
     pub fn with_query_args(mut self, query_args: Value) -> Self {
         self.query_args = Some(query_args);
@@ -42,7 +42,9 @@ pub trait Query: Send + Sync {
         // (matches the mutation path + JS's full-envelope Dot.get convention; get_data() then
         // returns the inner object and the query methods use response.data() directly).
         let json_data = json!({ "data": response.data });
-        Ok(self.create_response(json_data))
+        let mut response_obj = self.create_response(json_data);
+        response_obj.set_headers(response.headers);
+        Ok(response_obj)
     }
 }
 
@@ -98,6 +100,7 @@ impl BaseQuery {
         if self.query_string.is_empty() {
             return Err(KnishIOError::Code("Query string was not initialized!".to_string()));
         }
+        crate::graphql::lint_document(&self.query_string)?;
         
         // Create the request
         let request = create_query_request(&self.query_string, self.compiled_vars.clone());
@@ -160,7 +163,8 @@ impl Query for BaseQuery {
             Ok(response) => {
                 // Re-wrap under "data" so `data.<Field>` data_keys navigate (mutation-path parity).
                 let json_data = json!({ "data": response.data });
-                let response_obj = query.create_response_raw(json_data);
+                let mut response_obj = query.create_response_raw(json_data);
+                response_obj.set_headers(response.headers);
                 
                 // Note: In Rust, we can't mutate self in an async trait method
                 // The response is returned directly instead of being stored
@@ -191,8 +195,10 @@ pub mod batch_history;
 pub mod continu_id;
 pub mod meta_type;
 pub mod meta_type_via_atom;
+pub mod molecule_status;
 pub mod policy;
 pub mod token;
+pub mod user_activity;
 pub mod wallet_bundle;
 pub mod wallet_list;
 
@@ -203,9 +209,11 @@ pub use balance::QueryBalance;
 pub use batch::QueryBatch;
 pub use batch_history::QueryBatchHistory;
 pub use continu_id::QueryContinuId;
-pub use meta_type::{QueryMetaType, MetaTypeValue};
+pub use meta_type::{MetaFilter, QueryMetaType, MetaTypeValue};
 pub use meta_type_via_atom::{QueryMetaTypeViaAtom, QueryMetaTypeViaAtomParams};
+pub use molecule_status::{MoleculeInfo, QueryMoleculeStatus};
 pub use policy::QueryPolicy;
 pub use token::QueryToken;
+pub use user_activity::{ActivityCountBy, ActivityInterval, QueryUserActivity, QueryUserActivityParams};
 pub use wallet_bundle::QueryWalletBundle;
 pub use wallet_list::QueryWalletList;
\ No newline at end of file
@@ -0,0 +1,203 @@
+//! QueryMoleculeStatus implementation
+//!
+//! Looks up a submitted molecule by its molecular hash through the `Atom` query, as
+//! QueryAtom.js does with `molecularHashes`
+//!
+//! Nodes only store the atoms of molecules they accepted, so a hit means the molecule
+//! was accepted. A rejected molecule leaves no atoms behind: its verdict and reason are
+//! only in the `ProposeMolecule` answer, which `MoleculeInfo::from_response_data` reads.
+
+use crate::query::Query;
+use crate::response::{Response, BaseResponse};
+use crate::types::MoleculeStatus;
+use serde_json::{json, Value};
+
+/// Queries the node for the atoms of a molecule
+pub struct QueryMoleculeStatus {
+    /// Molecular hash of the molecule to look up
+    molecular_hash: String,
+}
+
+impl QueryMoleculeStatus {
+    /// Create a new QueryMoleculeStatus instance for `molecular_hash`
+    pub fn new(molecular_hash: impl Into<String>) -> Self {
+        QueryMoleculeStatus {
+            molecular_hash: molecular_hash.into(),
+        }
+    }
+
+    /// Get the molecular hash
+    pub fn molecular_hash(&self) -> &str {
+        &self.molecular_hash
+    }
+}
+
+#[async_trait::async_trait]
+impl Query for QueryMoleculeStatus {
+    /// Get the GraphQL query string (equivalent to $__query in JS)
+    fn get_query(&self) -> &str {
+        r#"query( $molecularHashes: [String!] ) {
+          Atom( molecularHashes: $molecularHashes ) {
+            instances {
+              molecularHash,
+              createdAt
+            }
+          }
+        }"#
+    }
+
+    /// Compile variables for the query (equivalent to compiledVariables in JS)
+    fn compiled_variables(&self, variables: Option<Value>) -> Option<Value> {
+        Some(variables.unwrap_or_else(|| json!({ "molecularHashes": [self.molecular_hash] })))
+    }
+
+    /// Create a response from the JSON data (equivalent to createResponse in JS)
+    fn create_response(&self, json: Value) -> Box<dyn Response> {
+        match BaseResponse::new(json) {
+            Ok(resp) => Box::new(resp.with_data_key("data.Atom")),
+            Err(e) => {
+                eprintln!("BaseResponse construction failed: {}", e);
+                Box::new(BaseResponse::empty())
+            }
+        }
+    }
+}
+
+/// Status of a submitted molecule as recorded by the node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoleculeInfo {
+    /// Molecular hash
+    pub molecular_hash: String,
+    /// Pending until the node has processed the molecule
+    pub status: MoleculeStatus,
+    /// Why the molecule was rejected
+    pub reason: Option<String>,
+    /// Position in the node's ledger
+    pub height: Option<u64>,
+    /// Creation time, in milliseconds since the Unix epoch
+    pub created_at: Option<i64>,
+    /// Time the node received the molecule, in milliseconds since the Unix epoch
+    pub received_at: Option<i64>,
+    /// Time the node processed the molecule, in milliseconds since the Unix epoch
+    pub processed_at: Option<i64>,
+    /// Time the node broadcast the molecule to its peers, in milliseconds since the Unix epoch
+    pub broadcasted_at: Option<i64>,
+}
+
+impl MoleculeInfo {
+    /// Parse the `Molecule` object a `ProposeMolecule` mutation answers with
+    ///
+    /// Numbers are accepted as numbers or numeric strings; statuses other than
+    /// `accepted` and `rejected` count as pending.
+    pub fn from_response_data(data: &Value) -> Option<Self> {
+        let status = match data["status"].as_str().map(str::to_lowercase).as_deref() {
+            Some("accepted") => MoleculeStatus::Accepted,
+            Some("rejected") => MoleculeStatus::Rejected,
+            _ => MoleculeStatus::Pending,
+        };
+
+        Some(MoleculeInfo {
+            molecular_hash: data["molecularHash"].as_str()?.to_string(),
+            status,
+            reason: data["reason"].as_str().filter(|r| !r.is_empty()).map(str::to_string),
+            height: number(&data["height"]).and_then(|h| u64::try_from(h).ok()),
+            created_at: number(&data["createdAt"]),
+            received_at: number(&data["receivedAt"]),
+            processed_at: number(&data["processedAt"]),
+            broadcasted_at: number(&data["broadcastedAt"]),
+        })
+    }
+
+    /// Status of `molecular_hash` from the data of a `QueryMoleculeStatus` response
+    ///
+    /// # Returns
+    ///
+    /// An accepted status when the node holds atoms of the molecule, None otherwise
+    pub fn from_atoms(molecular_hash: &str, data: &Value) -> Option<Self> {
+        let atom = data["instances"].as_array()?
+            .iter()
+            .find(|atom| atom["molecularHash"].as_str() == Some(molecular_hash))?;
+
+        Some(MoleculeInfo {
+            molecular_hash: molecular_hash.to_string(),
+            status: MoleculeStatus::Accepted,
+            reason: None,
+            height: None,
+            created_at: number(&atom["createdAt"]),
+            received_at: None,
+            processed_at: None,
+            broadcasted_at: None,
+        })
+    }
+
+    /// True once the node has accepted or rejected the molecule
+    pub fn is_final(&self) -> bool {
+        self.status != MoleculeStatus::Pending
+    }
+
+    /// True if the node accepted the molecule
+    pub fn accepted(&self) -> bool {
+        self.status == MoleculeStatus::Accepted
+    }
+}
+
+/// A number sent as a number or a numeric string
+fn number(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compiled_variables() {
+        let query = QueryMoleculeStatus::new("hash");
+        assert_eq!(query.molecular_hash(), "hash");
+        assert_eq!(query.compiled_variables(None).unwrap()["molecularHashes"], json!(["hash"]));
+        assert!(query.get_query().contains("Atom( molecularHashes: $molecularHashes )"));
+    }
+
+    #[test]
+    fn test_molecule_info_parsing() {
+        let info = MoleculeInfo::from_response_data(&json!({
+            "molecularHash": "hash",
+            "status": "rejected",
+            "reason": "Insufficient balance",
+            "height": "12",
+            "createdAt": "1700000000000",
+            "processedAt": 1700000000500u64,
+            "broadcastedAt": null
+        })).unwrap();
+
+        assert_eq!(info.status, MoleculeStatus::Rejected);
+        assert!(info.is_final() && !info.accepted());
+        assert_eq!(info.reason.as_deref(), Some("Insufficient balance"));
+        assert_eq!(info.height, Some(12));
+        assert_eq!((info.created_at, info.processed_at, info.broadcasted_at), (Some(1_700_000_000_000), Some(1_700_000_000_500), None));
+
+        let pending = MoleculeInfo::from_response_data(&json!({ "molecularHash": "hash", "status": "broadcasted", "reason": "" })).unwrap();
+        assert!(!pending.is_final());
+        assert_eq!(pending.reason, None);
+        assert!(MoleculeInfo::from_response_data(&json!({ "status": "accepted" })).is_none());
+    }
+
+    #[test]
+    fn test_molecule_info_from_atoms() {
+        let data = json!({ "instances": [
+            { "molecularHash": "other", "createdAt": "1" },
+            { "molecularHash": "hash", "createdAt": "1700000000000" }
+        ] });
+
+        let info = MoleculeInfo::from_atoms("hash", &data).unwrap();
+        assert!(info.accepted());
+        assert_eq!(info.created_at, Some(1_700_000_000_000));
+        assert_eq!(MoleculeInfo::from_atoms("missing", &data), None);
+        assert_eq!(MoleculeInfo::from_atoms("hash", &json!({ "instances": [] })), None);
+        assert_eq!(MoleculeInfo::from_atoms("hash", &Value::Null), None);
+    }
+}
@@ -0,0 +1,255 @@
+//! QueryUserActivity implementation
+//!
+//! Query for the activity sessions recorded against a meta instance,
+//! equivalent to QueryUserActivity.js
+
+use crate::error::{KnishIOError, Result};
+use crate::query::Query;
+use crate::response::{BaseResponse, Response};
+use serde_json::{json, Value};
+use std::fmt;
+use std::str::FromStr;
+
+/// Bucket size of the node's activity counts (the `span` enum)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActivityInterval {
+    /// One bucket per hour
+    Hour,
+    /// One bucket per day
+    Day,
+    /// One bucket per week, starting Monday
+    Week,
+    /// One bucket per calendar month
+    Month,
+    /// One bucket per calendar year
+    Year,
+}
+
+impl ActivityInterval {
+    /// Name as the node's `span` enum spells it
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityInterval::Hour => "HOUR",
+            ActivityInterval::Day => "DAY",
+            ActivityInterval::Week => "WEEK",
+            ActivityInterval::Month => "MONTH",
+            ActivityInterval::Year => "YEAR",
+        }
+    }
+}
+
+impl fmt::Display for ActivityInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ActivityInterval {
+    type Err = KnishIOError;
+
+    /// Parse an interval name in any case
+    ///
+    /// # Errors
+    ///
+    /// `ConfigurationError` for a name the node would reject
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "HOUR" => Ok(ActivityInterval::Hour),
+            "DAY" => Ok(ActivityInterval::Day),
+            "WEEK" => Ok(ActivityInterval::Week),
+            "MONTH" => Ok(ActivityInterval::Month),
+            "YEAR" => Ok(ActivityInterval::Year),
+            _ => Err(KnishIOError::ConfigurationError(format!(
+                "unknown activity interval {:?}, expected HOUR, DAY, WEEK, MONTH or YEAR",
+                value
+            ))),
+        }
+    }
+}
+
+/// Device field the node can count activity by (the `CountByUserActivity` enum)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActivityCountBy {
+    /// Client IP address
+    IpAddress,
+    /// Browser name and version
+    Browser,
+    /// Operating system and CPU
+    OsCpu,
+    /// Screen resolution
+    Resolution,
+    /// Time zone
+    TimeZone,
+}
+
+impl ActivityCountBy {
+    /// Field name as the node and the activity JSON spell it
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityCountBy::IpAddress => "ipAddress",
+            ActivityCountBy::Browser => "browser",
+            ActivityCountBy::OsCpu => "osCpu",
+            ActivityCountBy::Resolution => "resolution",
+            ActivityCountBy::TimeZone => "timeZone",
+        }
+    }
+}
+
+impl FromStr for ActivityCountBy {
+    type Err = KnishIOError;
+
+    /// Parse a field name as the node spells it
+    ///
+    /// # Errors
+    ///
+    /// `ConfigurationError` for any other name
+    fn from_str(value: &str) -> Result<Self> {
+        [
+            ActivityCountBy::IpAddress,
+            ActivityCountBy::Browser,
+            ActivityCountBy::OsCpu,
+            ActivityCountBy::Resolution,
+            ActivityCountBy::TimeZone,
+        ]
+        .into_iter()
+        .find(|field| field.as_str() == value)
+        .ok_or_else(|| KnishIOError::ConfigurationError(format!("unknown activity count field {:?}", value)))
+    }
+}
+
+/// Filters of a `UserActivity` query; unset fields do not filter
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryUserActivityParams {
+    pub bundle_hash: Option<String>,
+    pub meta_type: Option<String>,
+    pub meta_id: Option<String>,
+    pub ip_address: Option<String>,
+    pub browser: Option<String>,
+    pub os_cpu: Option<String>,
+    pub resolution: Option<String>,
+    pub time_zone: Option<String>,
+    /// Device fields to count instances by
+    pub count_by: Vec<ActivityCountBy>,
+    /// Bucket size of the counts
+    pub interval: Option<ActivityInterval>,
+}
+
+/// Query for the activity recorded against a meta instance
+pub struct QueryUserActivity {
+    params: QueryUserActivityParams,
+}
+
+impl QueryUserActivity {
+    /// Create a new QueryUserActivity with `params`
+    pub fn new(params: QueryUserActivityParams) -> Self {
+        QueryUserActivity { params }
+    }
+
+    /// Get the filters
+    pub fn params(&self) -> &QueryUserActivityParams {
+        &self.params
+    }
+}
+
+#[async_trait::async_trait]
+impl Query for QueryUserActivity {
+    /// Get the GraphQL query string (equivalent to $__query in JS)
+    fn get_query(&self) -> &str {
+        r#"query UserActivity( $bundleHash: String, $metaType: String, $metaId: String, $ipAddress: String, $browser: String, $osCpu: String, $resolution: String, $timeZone: String, $countBy: [CountByUserActivity], $interval: span ) {
+          UserActivity( bundleHash: $bundleHash, metaType: $metaType, metaId: $metaId, ipAddress: $ipAddress, browser: $browser, osCpu: $osCpu, resolution: $resolution, timeZone: $timeZone, countBy: $countBy, interval: $interval ) {
+            createdAt,
+            bundleHash,
+            metaType,
+            metaId,
+            instances {
+              bundleHash,
+              metaType,
+              metaId,
+              jsonData,
+              createdAt,
+              updatedAt
+            },
+            instanceCount {
+              id,
+              count
+            }
+          }
+        }"#
+    }
+
+    /// Compile variables for the query (equivalent to compiledVariables in JS)
+    fn compiled_variables(&self, variables: Option<Value>) -> Option<Value> {
+        if let Some(provided_vars) = variables {
+            return Some(provided_vars);
+        }
+
+        let params = &self.params;
+        let mut vars = json!({});
+        for (name, value) in [
+            ("bundleHash", &params.bundle_hash),
+            ("metaType", &params.meta_type),
+            ("metaId", &params.meta_id),
+            ("ipAddress", &params.ip_address),
+            ("browser", &params.browser),
+            ("osCpu", &params.os_cpu),
+            ("resolution", &params.resolution),
+            ("timeZone", &params.time_zone),
+        ] {
+            if let Some(value) = value {
+                vars[name] = json!(value);
+            }
+        }
+        if !params.count_by.is_empty() {
+            vars["countBy"] = json!(params.count_by.iter().map(ActivityCountBy::as_str).collect::<Vec<_>>());
+        }
+        if let Some(interval) = params.interval {
+            vars["interval"] = json!(interval.as_str());
+        }
+
+        Some(vars)
+    }
+
+    /// Create a response from the JSON data (equivalent to createResponse in JS)
+    fn create_response(&self, json: Value) -> Box<dyn Response> {
+        match BaseResponse::new(json) {
+            Ok(resp) => Box::new(resp.with_data_key("data.UserActivity")),
+            Err(e) => {
+                eprintln!("BaseResponse construction failed: {}", e);
+                Box::new(BaseResponse::empty())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval_is_validated() {
+        assert_eq!("day".parse::<ActivityInterval>().unwrap(), ActivityInterval::Day);
+        assert_eq!(" Month ".parse::<ActivityInterval>().unwrap().as_str(), "MONTH");
+        assert!(matches!("fortnight".parse::<ActivityInterval>(), Err(KnishIOError::ConfigurationError(_))));
+        assert_eq!("osCpu".parse::<ActivityCountBy>().unwrap(), ActivityCountBy::OsCpu);
+        assert!("os_cpu".parse::<ActivityCountBy>().is_err());
+    }
+
+    #[test]
+    fn test_compiled_variables() {
+        let query = QueryUserActivity::new(QueryUserActivityParams {
+            meta_type: Some("app".to_string()),
+            meta_id: Some("dashboard".to_string()),
+            count_by: vec![ActivityCountBy::Browser, ActivityCountBy::TimeZone],
+            interval: Some(ActivityInterval::Day),
+            ..Default::default()
+        });
+        let variables = query.compiled_variables(None).unwrap();
+        assert_eq!(variables, json!({
+            "metaType": "app",
+            "metaId": "dashboard",
+            "countBy": ["browser", "timeZone"],
+            "interval": "DAY",
+        }));
+        assert!(query.get_query().contains("instanceCount"));
+    }
+}
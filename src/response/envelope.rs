@@ -0,0 +1,181 @@
+//! Owned response serialization
+//!
+//! Responses come back from the client as `Box<dyn Response>`, which serde cannot see
+//! through. `ResponseEnvelope` names the concrete type alongside its fields, so a response
+//! can be written to disk or handed to another process and read back as the same type:
+//!
+//! ```json
+//! { "type": "ResponseBalance", "response": { "base": { "data": { ... }, ... } } }
+//! ```
+//!
+//! Each concrete response also has `to_owned_json` / `from_owned_json` for its own fields
+//! without the envelope. Both forms keep the original reply, the data key, the query and
+//! the HTTP headers, so the restored response answers every `Response` method as before.
+//! The client molecule of a `ResponseProposeMolecule` is kept without its secret and
+//! local annotations, as `Molecule` always serializes.
+
+use super::*;
+
+macro_rules! response_envelope {
+    ($($variant:ident),+ $(,)?) => {
+        /// Any response, tagged with its concrete type
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        #[serde(tag = "type", content = "response")]
+        pub enum ResponseEnvelope {
+            $(
+                #[allow(missing_docs)]
+                $variant($variant),
+            )+
+        }
+
+        impl ResponseEnvelope {
+            /// The response as a trait object
+            pub fn as_response(&self) -> &dyn Response {
+                match self {
+                    $(ResponseEnvelope::$variant(response) => response,)+
+                }
+            }
+
+            /// Unwrap into a boxed response
+            pub fn into_response(self) -> Box<dyn Response> {
+                match self {
+                    $(ResponseEnvelope::$variant(response) => Box::new(response),)+
+                }
+            }
+
+            /// Name of the concrete response type (the `type` tag)
+            pub fn type_name(&self) -> &'static str {
+                match self {
+                    $(ResponseEnvelope::$variant(_) => stringify!($variant),)+
+                }
+            }
+        }
+
+        $(
+            impl From<$variant> for ResponseEnvelope {
+                fn from(response: $variant) -> Self {
+                    ResponseEnvelope::$variant(response)
+                }
+            }
+
+            impl $variant {
+                /// This response's fields as JSON, readable with `from_owned_json`
+                pub fn to_owned_json(&self) -> Value {
+                    serde_json::to_value(self).unwrap_or_default()
+                }
+
+                /// Restore a response written by `to_owned_json`
+                ///
+                /// # Errors
+                ///
+                /// `Serialization` if `json` is not a serialized response of this type
+                pub fn from_owned_json(json: &Value) -> Result<Self, KnishIOError> {
+                    Self::deserialize(json).map_err(|e| KnishIOError::Serialization(e.to_string()))
+                }
+            }
+        )+
+    };
+}
+
+response_envelope!(
+    BaseResponse,
+    ResponseActiveSession,
+    ResponseAtom,
+    ResponseAuthorizationGuest,
+    ResponseBalance,
+    ResponseClaimShadowWallet,
+    ResponseContinuId,
+    ResponseCreateIdentifier,
+    ResponseCreateMeta,
+    ResponseCreateRule,
+    ResponseCreateToken,
+    ResponseCreateWallet,
+    ResponseLinkIdentifier,
+    ResponseMetaBatch,
+    ResponseMetaType,
+    ResponseMetaTypeViaAtom,
+    ResponsePolicy,
+    ResponseProposeMolecule,
+    ResponseQueryActiveSession,
+    ResponseRequestAuthorization,
+    ResponseRequestAuthorizationGuest,
+    ResponseRequestTokens,
+    ResponseTransferTokens,
+    ResponseWalletBundle,
+    ResponseWalletList,
+);
+
+impl ResponseEnvelope {
+    /// The envelope as JSON, readable with `from_owned_json`
+    pub fn to_owned_json(&self) -> Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    /// Restore an envelope written by `to_owned_json`
+    ///
+    /// # Errors
+    ///
+    /// `Serialization` for an unknown `type` or fields that do not fit it
+    pub fn from_owned_json(json: &Value) -> Result<Self, KnishIOError> {
+        Self::deserialize(json).map_err(|e| KnishIOError::Serialization(e.to_string()))
+    }
+}
+
+impl dyn Response {
+    /// This response in a serializable envelope
+    ///
+    /// # Errors
+    ///
+    /// `Serialization` for a response type defined outside the SDK
+    pub fn to_envelope(&self) -> Result<ResponseEnvelope, KnishIOError> {
+        self.envelope().ok_or_else(|| KnishIOError::Serialization("response type has no envelope".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::ResponseHeaders;
+    use serde_json::json;
+
+    #[test]
+    fn test_envelope_round_trip() {
+        let reply = json!({ "data": { "Balance": { "amount": "5", "tokenSlug": "GOLD" } } });
+        let mut response: Box<dyn Response> = Box::new(ResponseBalance::new(reply.clone(), Some(json!({ "q": 1 }))).unwrap());
+        response.set_headers(ResponseHeaders::new([("X-Request-Id", "req-1")]));
+
+        let stored = response.to_envelope().unwrap().to_owned_json();
+        assert_eq!(stored["type"], "ResponseBalance");
+        let envelope = ResponseEnvelope::from_owned_json(&serde_json::from_str(&stored.to_string()).unwrap()).unwrap();
+        assert_eq!(envelope.type_name(), "ResponseBalance");
+        assert!(matches!(envelope, ResponseEnvelope::ResponseBalance(_)));
+
+        let restored = envelope.into_response();
+        assert_eq!(restored.data(), response.data());
+        assert_eq!(restored.data_key(), Some("data.Balance"));
+        assert_eq!(restored.to_json(), reply);
+        assert_eq!(restored.query(), Some(&json!({ "q": 1 })));
+        assert_eq!(restored.headers().and_then(|h| h.request_id()), Some("req-1"));
+
+        assert!(ResponseEnvelope::from_owned_json(&json!({ "type": "ResponseNope", "response": {} })).is_err());
+    }
+
+    #[test]
+    fn test_owned_json_keeps_propose_molecule_state() {
+        let reply = json!({ "data": { "ProposeMolecule": {
+            "molecularHash": "h1",
+            "status": "accepted",
+            "payload": "{\"token\":\"t\"}",
+        } } });
+        let mut molecule = Molecule::new();
+        molecule.molecular_hash = Some("h1".to_string());
+        let response = ResponseProposeMolecule::with_molecule(reply, None, Some(molecule)).unwrap();
+
+        let restored = ResponseProposeMolecule::from_owned_json(&response.to_owned_json()).unwrap();
+        assert_eq!(restored.molecular_hash().as_deref(), Some("h1"));
+        assert!(Response::success(&restored));
+        assert_eq!(Response::payload(&restored), Some(&json!({ "token": "t" })));
+        assert_eq!(restored.client_molecule().as_ref().and_then(|m| m.molecular_hash.as_deref()), Some("h1"));
+        assert!(ResponseProposeMolecule::from_owned_json(&json!({ "base": 1 })).is_err());
+    }
+}
@@ -0,0 +1,231 @@
+//! Bytes-backed meta values
+//!
+//! Meta values are strings on the wire, and binary payloads (images, documents, sealed
+//! records) travel base64 encoded inside them. Going through `Value` accessors, a large
+//! value is copied into a `String`, again into the caller's own type and once more when
+//! decoded. `MetaBlob` takes the string out of the response instead: it owns the text as
+//! `Bytes`, so clones and slices share one buffer, and decodes base64 only when asked,
+//! caching the result.
+
+use crate::error::{KnishIOError, Result};
+use base64::Engine as _;
+use bytes::Bytes;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+/// Meta values longer than this many bytes count as large
+pub const LARGE_META_THRESHOLD: usize = 4096;
+
+/// A meta value held as shared bytes, with lazily decoded base64 content
+///
+/// ```rust
+/// use knishio_client::response::MetaBlob;
+///
+/// let blob = MetaBlob::from(String::from("aGVsbG8="));
+/// assert_eq!(blob.as_str(), "aGVsbG8=");
+/// assert_eq!(&blob.decoded().unwrap()[..], b"hello");
+/// ```
+#[derive(Clone, Default)]
+pub struct MetaBlob {
+    /// Value text, always valid UTF-8
+    raw: Bytes,
+    /// Base64 content of `raw`, decoded on first use; None if `raw` is not base64
+    decoded: Arc<OnceLock<Option<Bytes>>>,
+}
+
+impl MetaBlob {
+    /// Value text as stored on the ledger
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.raw).unwrap_or_default()
+    }
+
+    /// Value text as shared bytes; cloning it does not copy the buffer
+    pub fn raw(&self) -> &Bytes {
+        &self.raw
+    }
+
+    /// Length of the value text in bytes
+    pub fn len(&self) -> usize {
+        self.raw.len()
+    }
+
+    /// True for an empty value
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    /// True when the value is longer than `LARGE_META_THRESHOLD`
+    pub fn is_large(&self) -> bool {
+        self.raw.len() > LARGE_META_THRESHOLD
+    }
+
+    /// Base64-decoded content, decoded on the first call and shared by every clone
+    ///
+    /// # Errors
+    ///
+    /// Returns `Serialization` if the value is not standard base64
+    pub fn decoded(&self) -> Result<Bytes> {
+        self.decoded
+            .get_or_init(|| base64::engine::general_purpose::STANDARD.decode(&self.raw).ok().map(Bytes::from))
+            .clone()
+            .ok_or_else(|| KnishIOError::Serialization(format!(
+                "Meta value of {} bytes is not valid base64",
+                self.raw.len()
+            )))
+    }
+}
+
+impl From<String> for MetaBlob {
+    /// Take over the string's buffer without copying it
+    fn from(value: String) -> Self {
+        MetaBlob {
+            raw: Bytes::from(value.into_bytes()),
+            decoded: Arc::default(),
+        }
+    }
+}
+
+impl From<&str> for MetaBlob {
+    fn from(value: &str) -> Self {
+        MetaBlob::from(value.to_string())
+    }
+}
+
+impl PartialEq for MetaBlob {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl Eq for MetaBlob {}
+
+impl fmt::Debug for MetaBlob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_large() {
+            write!(f, "MetaBlob({} bytes)", self.raw.len())
+        } else {
+            f.debug_tuple("MetaBlob").field(&self.as_str()).finish()
+        }
+    }
+}
+
+impl Serialize for MetaBlob {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for MetaBlob {
+    /// Owned strings, e.g. from `serde_json::from_value`, are moved rather than copied
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct BlobVisitor;
+
+        impl Visitor<'_> for BlobVisitor {
+            type Value = MetaBlob;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a meta value string")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> std::result::Result<MetaBlob, E> {
+                Ok(MetaBlob::from(value))
+            }
+
+            fn visit_string<E: de::Error>(self, value: String) -> std::result::Result<MetaBlob, E> {
+                Ok(MetaBlob::from(value))
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, value: Vec<u8>) -> std::result::Result<MetaBlob, E> {
+                String::from_utf8(value)
+                    .map(MetaBlob::from)
+                    .map_err(|e| E::custom(e.utf8_error()))
+            }
+
+            fn visit_bytes<E: de::Error>(self, value: &[u8]) -> std::result::Result<MetaBlob, E> {
+                self.visit_byte_buf(value.to_vec())
+            }
+        }
+
+        deserializer.deserialize_string(BlobVisitor)
+    }
+}
+
+/// Latest meta values of `meta_id` in `MetaType` query data, keyed by meta key
+///
+/// Takes the data by value so each value's string buffer moves into its `MetaBlob`.
+pub fn take_meta_blobs(data: Value, meta_id: &str) -> HashMap<String, MetaBlob> {
+    let meta_types = match data {
+        Value::Array(items) => items,
+        other => vec![other],
+    };
+
+    let mut blobs = HashMap::new();
+    for mut meta_type in meta_types {
+        let Value::Array(instances) = meta_type["instances"].take() else {
+            continue;
+        };
+        for mut instance in instances.into_iter().filter(|i| i["metaId"].as_str() == Some(meta_id)) {
+            let Value::Array(metas) = instance["metas"].take() else {
+                continue;
+            };
+            for mut meta in metas {
+                if let (Value::String(key), Value::String(value)) = (meta["key"].take(), meta["value"].take()) {
+                    blobs.insert(key, MetaBlob::from(value));
+                }
+            }
+        }
+    }
+
+    blobs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_meta_blob_shares_and_decodes_lazily() {
+        let payload = vec![7u8; LARGE_META_THRESHOLD];
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&payload);
+        let pointer = encoded.as_ptr();
+
+        let blob = MetaBlob::from(encoded);
+        assert_eq!(blob.raw().as_ptr(), pointer);
+        assert!(blob.is_large());
+        assert_eq!(format!("{:?}", blob), format!("MetaBlob({} bytes)", blob.len()));
+
+        let copy = blob.clone();
+        assert_eq!(copy.raw().as_ptr(), pointer);
+        assert_eq!(&blob.decoded().unwrap()[..], &payload[..]);
+        assert_eq!(copy.decoded().unwrap().as_ptr(), blob.decoded().unwrap().as_ptr());
+
+        let text = MetaBlob::from("not base64!");
+        assert!(!text.is_large());
+        assert!(matches!(text.decoded(), Err(KnishIOError::Serialization(_))));
+        assert_eq!(serde_json::to_value(&text).unwrap(), json!("not base64!"));
+        assert_eq!(serde_json::from_value::<MetaBlob>(json!("not base64!")).unwrap(), text);
+    }
+
+    #[test]
+    fn test_take_meta_blobs() {
+        let data = json!([{
+            "metaType": "document",
+            "instances": [
+                { "metaId": "doc-1", "metas": [{ "key": "file", "value": "djE=" }, { "key": "name", "value": "a.pdf" }] },
+                { "metaId": "doc-2", "metas": [{ "key": "file", "value": "b3RoZXI=" }] },
+                { "metaId": "doc-1", "metas": [{ "key": "file", "value": "djI=" }] }
+            ]
+        }]);
+
+        let blobs = take_meta_blobs(data, "doc-1");
+        assert_eq!(blobs.len(), 2);
+        assert_eq!(&blobs["file"].decoded().unwrap()[..], b"v2");
+        assert_eq!(blobs["name"].as_str(), "a.pdf");
+        assert!(take_meta_blobs(json!(null), "doc-1").is_empty());
+    }
+}
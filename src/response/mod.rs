@@ -9,6 +9,9 @@
 //! - **BaseResponse**: Core response handling with error detection and data extraction
 //! - **Response Trait**: Standard interface for all response types
 //! - **Specific Responses**: 22 response types matching JavaScript SDK implementations
+//! - **Typed Access**: `deserialize_into` reads data into caller structs, reporting the JSON path of mismatches
+//! - **Meta Blobs**: `MetaBlob` holds large meta values as shared bytes, decoding base64 on demand
+//! - **Envelopes**: `ResponseEnvelope` serializes any response with its concrete type, for storage or IPC
 //!
 //! # Error Handling
 //!
@@ -29,9 +32,18 @@ use crate::molecule::Molecule;
 use crate::wallet::Wallet;
 use crate::token_unit::TokenUnit;
 use crate::error::KnishIOError;
+use crate::graphql::ResponseHeaders;
 use serde::{Serialize, Deserialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+mod envelope;
+mod meta_blob;
+mod typed;
+
+pub use envelope::ResponseEnvelope;
+pub use meta_blob::{take_meta_blobs, MetaBlob, LARGE_META_THRESHOLD};
+pub use typed::from_value_at;
 
 // =====================================================
 // Response Factory and Utility Functions
@@ -95,28 +107,11 @@ pub struct ResponseUtils;
 
 impl ResponseUtils {
     /// Extract operation name from GraphQL query
+    ///
+    /// The operation's own name, or its first root field when it is anonymous; None when
+    /// the document does not pass `lint_document`.
     pub fn extract_operation_name(query: &str) -> Option<String> {
-        // Simple regex-like extraction for operation names
-        if let Some(start) = query.find("query ").or_else(|| query.find("mutation ")) {
-            let rest = &query[start + 6..];
-            if let Some(end) = rest.find(' ').or_else(|| rest.find('(').or_else(|| rest.find('{'))) {
-                return Some(rest[..end].trim().to_string());
-            }
-        }
-        
-        // Try to extract from operation body
-        if let Some(start) = query.find('{'). and_then(|i| {
-            let rest = &query[i + 1..];
-            rest.find(char::is_alphabetic)
-        }) {
-            let operation_start = query.find('{').unwrap() + 1 + start;
-            let rest = &query[operation_start..];
-            if let Some(end) = rest.find(char::is_whitespace).or_else(|| rest.find('(')).or_else(|| rest.find('{')) {
-                return Some(rest[..end].trim().to_string());
-            }
-        }
-        
-        None
+        crate::graphql::lint_document(query).ok()?.operation_name().map(str::to_string)
     }
     
     /// Check if response indicates molecular acceptance
@@ -166,6 +161,37 @@ pub trait Response: Send + Sync {
     
     /// Get the original query that generated this response
     fn query(&self) -> Option<&Value>;
+
+    /// Client annotations of the proposed molecule, for responses that track one
+    fn annotations(&self) -> Option<&BTreeMap<String, String>> {
+        None
+    }
+
+    /// Dot path `data()` is read from, when the response type has one
+    fn data_key(&self) -> Option<&str> {
+        None
+    }
+
+    /// HTTP headers of the reply, for responses built from a node's reply
+    fn headers(&self) -> Option<&ResponseHeaders> {
+        None
+    }
+
+    /// Attach the HTTP headers of the reply this response was built from
+    fn set_headers(&mut self, _headers: ResponseHeaders) {}
+
+    /// This response in a serializable envelope, for the SDK's own response types
+    fn envelope(&self) -> Option<ResponseEnvelope> {
+        None
+    }
+
+    /// Consume the response and return its data (equivalent to data())
+    ///
+    /// Responses that own their data move it out rather than cloning it, which matters
+    /// for large meta values; see `take_meta_blobs`.
+    fn into_data(self: Box<Self>) -> Value {
+        self.data().clone()
+    }
 }
 
 /// Base Response implementation (equivalent to Response.js)
@@ -186,6 +212,9 @@ pub struct BaseResponse {
     payload: Option<Value>,
     /// Original query for reference
     query: Option<Value>,
+    /// HTTP headers of the reply
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    headers: Option<ResponseHeaders>,
 }
 
 impl BaseResponse {
@@ -201,11 +230,12 @@ impl BaseResponse {
         
         let mut response = BaseResponse {
             data: json.clone(),
-            origin_response: json.clone(),
+            origin_response: json,
             error_key: "exception".to_string(),
             data_key: None,
             payload: None,
             query: None,
+            headers: None,
         };
         
         // Check for server errors (equivalent to JS error checking)
@@ -223,6 +253,7 @@ impl BaseResponse {
             data_key: None,
             payload: None,
             query: None,
+            headers: None,
         }
     }
 
@@ -297,12 +328,41 @@ impl BaseResponse {
             &self.data
         }
     }
+
+    /// Move the data selected by data_key out of the response
+    pub fn into_data(mut self) -> Value {
+        let pointer = self.data_key.as_ref().map(|key| format!("/{}", key.replace('.', "/")));
+        match pointer.and_then(|pointer| self.data.pointer_mut(&pointer)) {
+            Some(value) => value.take(),
+            None => self.data,
+        }
+    }
 }
 
 impl Response for BaseResponse {
     fn data(&self) -> &Value {
         self.get_data()
     }
+
+    fn data_key(&self) -> Option<&str> {
+        self.data_key.as_deref()
+    }
+
+    fn headers(&self) -> Option<&ResponseHeaders> {
+        self.headers.as_ref()
+    }
+
+    fn set_headers(&mut self, headers: ResponseHeaders) {
+        self.headers = Some(headers);
+    }
+
+    fn envelope(&self) -> Option<ResponseEnvelope> {
+        Some(self.clone().into())
+    }
+
+    fn into_data(self: Box<Self>) -> Value {
+        BaseResponse::into_data(*self)
+    }
     
     fn success(&self) -> bool {
         !self.has_errors() && self.get_data().is_object()
@@ -358,7 +418,7 @@ impl Response for BaseResponse {
 // =====================================================
 
 /// Response for ActiveSession query (equivalent to ResponseActiveSession.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseActiveSession {
     base: BaseResponse,
 }
@@ -395,6 +455,7 @@ impl ResponseActiveSession {
 
 impl Response for ResponseActiveSession {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { self.base.success() }
     fn error(&self) -> Option<String> { self.base.error() }
     fn get(&self, key: &str) -> Option<&Value> { self.base.get(key) }
@@ -403,10 +464,13 @@ impl Response for ResponseActiveSession {
     fn status(&self) -> Option<String> { self.base.status() }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for Atom query (equivalent to ResponseAtom.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseAtom {
     base: BaseResponse,
 }
@@ -441,6 +505,7 @@ impl ResponseAtom {
 
 impl Response for ResponseAtom {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { self.base.success() }
     fn error(&self) -> Option<String> { self.base.error() }
     fn get(&self, key: &str) -> Option<&Value> { self.base.get(key) }
@@ -452,10 +517,13 @@ impl Response for ResponseAtom {
     fn status(&self) -> Option<String> { self.base.status() }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for AuthorizationGuest (equivalent to ResponseAuthorizationGuest.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseAuthorizationGuest {
     base: BaseResponse,
 }
@@ -478,6 +546,7 @@ impl ResponseAuthorizationGuest {
 
 impl Response for ResponseAuthorizationGuest {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { self.wallet().is_some() }
     fn error(&self) -> Option<String> { self.base.error() }
     fn get(&self, key: &str) -> Option<&Value> { self.base.get(key) }
@@ -486,10 +555,13 @@ impl Response for ResponseAuthorizationGuest {
     fn status(&self) -> Option<String> { self.base.status() }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for Balance query (equivalent to ResponseBalance.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseBalance {
     base: BaseResponse,
 }
@@ -521,6 +593,7 @@ impl ResponseBalance {
 
 impl Response for ResponseBalance {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { self.base.success() }
     fn error(&self) -> Option<String> { self.base.error() }
     fn get(&self, key: &str) -> Option<&Value> { self.base.get(key) }
@@ -532,10 +605,13 @@ impl Response for ResponseBalance {
     fn status(&self) -> Option<String> { self.base.status() }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for ClaimShadowWallet (equivalent to ResponseClaimShadowWallet.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseClaimShadowWallet {
     base: BaseResponse,
 }
@@ -565,6 +641,7 @@ impl ResponseClaimShadowWallet {
 
 impl Response for ResponseClaimShadowWallet {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { 
         self.base.success() && 
         self.status().is_some_and(|s| s == "accepted")
@@ -581,10 +658,13 @@ impl Response for ResponseClaimShadowWallet {
     }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for ContinuId query (equivalent to ResponseContinuId.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseContinuId {
     base: BaseResponse,
 }
@@ -614,6 +694,7 @@ impl ResponseContinuId {
 
 impl Response for ResponseContinuId {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { self.base.success() }
     fn error(&self) -> Option<String> { self.base.error() }
     fn get(&self, key: &str) -> Option<&Value> { self.base.get(key) }
@@ -622,10 +703,13 @@ impl Response for ResponseContinuId {
     fn status(&self) -> Option<String> { self.base.status() }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for CreateIdentifier (equivalent to ResponseCreateIdentifier.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseCreateIdentifier {
     base: BaseResponse,
 }
@@ -655,6 +739,7 @@ impl ResponseCreateIdentifier {
 
 impl Response for ResponseCreateIdentifier {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { 
         self.base.success() && 
         self.status().is_some_and(|s| s == "accepted")
@@ -671,10 +756,13 @@ impl Response for ResponseCreateIdentifier {
     }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for CreateMeta (equivalent to ResponseCreateMeta.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseCreateMeta {
     base: BaseResponse,
 }
@@ -706,6 +794,7 @@ impl ResponseCreateMeta {
 
 impl Response for ResponseCreateMeta {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { 
         self.base.success() && 
         self.status().is_some_and(|s| s == "accepted")
@@ -722,10 +811,13 @@ impl Response for ResponseCreateMeta {
     }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for CreateRule (equivalent to ResponseCreateRule.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseCreateRule {
     base: BaseResponse,
 }
@@ -757,6 +849,7 @@ impl ResponseCreateRule {
 
 impl Response for ResponseCreateRule {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { 
         self.base.success() && 
         self.status().is_some_and(|s| s == "accepted")
@@ -773,10 +866,13 @@ impl Response for ResponseCreateRule {
     }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for CreateToken (equivalent to ResponseCreateToken.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseCreateToken {
     base: BaseResponse,
 }
@@ -808,6 +904,7 @@ impl ResponseCreateToken {
 
 impl Response for ResponseCreateToken {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { 
         self.base.success() && 
         self.status().is_some_and(|s| s == "accepted")
@@ -824,10 +921,13 @@ impl Response for ResponseCreateToken {
     }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for CreateWallet (equivalent to ResponseCreateWallet.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseCreateWallet {
     base: BaseResponse,
 }
@@ -859,6 +959,7 @@ impl ResponseCreateWallet {
 
 impl Response for ResponseCreateWallet {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { 
         self.base.success() && 
         self.status().is_some_and(|s| s == "accepted")
@@ -875,10 +976,13 @@ impl Response for ResponseCreateWallet {
     }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for LinkIdentifier (equivalent to ResponseLinkIdentifier.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseLinkIdentifier {
     base: BaseResponse,
 }
@@ -934,6 +1038,7 @@ impl ResponseLinkIdentifier {
 
 impl Response for ResponseLinkIdentifier {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { 
         // Match JS success() logic: return Dot.get(this.data(), 'set')
         self.base.get_data()
@@ -948,10 +1053,13 @@ impl Response for ResponseLinkIdentifier {
     fn status(&self) -> Option<String> { self.base.status() }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for MetaBatch (equivalent to ResponseMetaBatch.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseMetaBatch {
     base: BaseResponse,
 }
@@ -971,6 +1079,7 @@ impl ResponseMetaBatch {
 
 impl Response for ResponseMetaBatch {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { self.base.success() }
     fn error(&self) -> Option<String> { self.base.error() }
     fn get(&self, key: &str) -> Option<&Value> { self.base.get(key) }
@@ -979,10 +1088,13 @@ impl Response for ResponseMetaBatch {
     fn status(&self) -> Option<String> { self.base.status() }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for MetaType (equivalent to ResponseMetaType.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseMetaType {
     base: BaseResponse,
 }
@@ -1002,11 +1114,33 @@ impl ResponseMetaType {
             .unwrap_or_default()
     }
     
+    /// `countBy` group sizes, keyed by the counted value
+    ///
+    /// Nodes send `instanceCount` as a list of `{ key, value }` pairs on each MetaType
+    /// entry; a plain map is accepted too.
     pub fn instance_count(&self) -> HashMap<String, i64> {
-        self.base.get_data()
-            .get("instanceCount")
-            .and_then(|v| serde_json::from_value(v.clone()).ok())
-            .unwrap_or_default()
+        let data = self.base.get_data();
+        let entries: Vec<&Value> = match data.as_array() {
+            Some(entries) => entries.iter().collect(),
+            None => vec![data],
+        };
+
+        let mut counts = HashMap::new();
+        for instance_count in entries.into_iter().filter_map(|entry| entry.get("instanceCount")) {
+            match instance_count {
+                Value::Array(groups) => {
+                    for group in groups {
+                        let count = group["value"].as_i64()
+                            .or_else(|| group["value"].as_str().and_then(|v| v.parse().ok()));
+                        if let (Some(key), Some(count)) = (group["key"].as_str(), count) {
+                            counts.insert(key.to_string(), count);
+                        }
+                    }
+                }
+                other => counts.extend(serde_json::from_value::<HashMap<String, i64>>(other.clone()).unwrap_or_default()),
+            }
+        }
+        counts
     }
     
     pub fn paginator_info(&self) -> Option<&Value> {
@@ -1017,6 +1151,7 @@ impl ResponseMetaType {
 
 impl Response for ResponseMetaType {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { self.base.success() }
     fn error(&self) -> Option<String> { self.base.error() }
     fn get(&self, key: &str) -> Option<&Value> { self.base.get(key) }
@@ -1025,10 +1160,14 @@ impl Response for ResponseMetaType {
     fn status(&self) -> Option<String> { self.base.status() }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
+    fn into_data(self: Box<Self>) -> Value { self.base.into_data() }
 }
 
 /// Response for MetaTypeViaAtom (equivalent to ResponseMetaTypeViaAtom.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseMetaTypeViaAtom {
     base: BaseResponse,
 }
@@ -1102,6 +1241,7 @@ impl ResponseMetaTypeViaAtom {
 
 impl Response for ResponseMetaTypeViaAtom {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { self.base.success() }
     fn error(&self) -> Option<String> { self.base.error() }
     fn get(&self, key: &str) -> Option<&Value> { self.base.get(key) }
@@ -1110,10 +1250,13 @@ impl Response for ResponseMetaTypeViaAtom {
     fn status(&self) -> Option<String> { self.base.status() }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for Policy (equivalent to ResponsePolicy.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponsePolicy {
     base: BaseResponse,
 }
@@ -1135,6 +1278,7 @@ impl ResponsePolicy {
 
 impl Response for ResponsePolicy {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { self.base.success() }
     fn error(&self) -> Option<String> { self.base.error() }
     fn get(&self, key: &str) -> Option<&Value> { self.base.get(key) }
@@ -1143,10 +1287,13 @@ impl Response for ResponsePolicy {
     fn status(&self) -> Option<String> { self.base.status() }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for ProposeMolecule (equivalent to ResponseProposeMolecule.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseProposeMolecule {
     base: BaseResponse,
     client_molecule: Option<Molecule>,
@@ -1191,6 +1338,7 @@ impl ResponseProposeMolecule {
         &self.client_molecule
     }
     
+    /// Molecule of the response, its `createdAt` kept as the node sent it
     pub fn molecule(&self) -> Option<Molecule> {
         let data = self.base.get_data();
         
@@ -1198,8 +1346,8 @@ impl ResponseProposeMolecule {
         molecule.molecular_hash = data.get("molecularHash").and_then(|v| v.as_str()).map(|s| s.to_string());
         molecule.status = data.get("status").and_then(|v| v.as_str()).map(|s| s.to_string());
         
-        if let Some(created_at) = data.get("createdAt").and_then(|v| v.as_str()) {
-            molecule.created_at = created_at.to_string();
+        if let Some(created_at) = data.get("createdAt").filter(|v| !v.is_null()) {
+            molecule.created_at = crate::types::created_at_from_json(created_at);
         }
         
         Some(molecule)
@@ -1227,10 +1375,26 @@ impl ResponseProposeMolecule {
             .unwrap_or("Invalid response from server")
             .to_string()
     }
+
+    /// Deserialize the parsed payload into `T` (e.g. `AuthPayload`, `TokenPayload`, `WalletPayload`)
+    ///
+    /// # Returns
+    /// None if the molecule produced no payload
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidResponse` if the payload does not match `T`
+    pub fn payload_as<T: serde::de::DeserializeOwned>(&self) -> Result<Option<T>, KnishIOError> {
+        match &self.parsed_payload {
+            None | Some(Value::Null) => Ok(None),
+            Some(payload) => decode_payload(payload).map(Some),
+        }
+    }
 }
 
 impl Response for ResponseProposeMolecule {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { self.status() == "accepted" }
     fn error(&self) -> Option<String> { self.base.error() }
     fn get(&self, key: &str) -> Option<&Value> { self.base.get(key) }
@@ -1241,10 +1405,132 @@ impl Response for ResponseProposeMolecule {
     fn status(&self) -> Option<String> { Some(self.status()) }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
+    fn annotations(&self) -> Option<&BTreeMap<String, String>> {
+        self.client_molecule.as_ref().map(|molecule| &molecule.annotations)
+    }
+}
+
+// =====================================================
+// Typed ProposeMolecule Payloads
+// =====================================================
+
+/// Auth token payload returned for an accepted U-isotope (authorization) molecule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthPayload {
+    /// Session token to send as `X-Auth-Token`
+    pub token: String,
+    /// Expiry as a Unix timestamp in seconds, as `AuthToken::new` takes it (nodes send
+    /// either a string or a number)
+    #[serde(default, deserialize_with = "deserialize_optional_timestamp")]
+    pub expires_at: Option<i64>,
+    /// Server public key used for encrypted sessions
+    #[serde(default)]
+    pub pubkey: Option<String>,
+    /// Whether the session is encrypted
+    #[serde(default)]
+    pub encrypt: Option<bool>,
+}
+
+/// Token creation payload returned for an accepted token issuance molecule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenPayload {
+    /// Slug of the issued token
+    #[serde(alias = "slug", alias = "tokenSlug")]
+    pub token: String,
+    /// Issued amount (nodes send either a string or a number)
+    #[serde(default, deserialize_with = "deserialize_optional_amount")]
+    pub amount: Option<String>,
+    /// Batch ID of stackable tokens
+    #[serde(default)]
+    pub batch_id: Option<String>,
+    /// Any other fields the node included
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+/// Wallet payload returned for an accepted wallet creation molecule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletPayload {
+    /// Wallet address
+    pub address: String,
+    /// Owning bundle hash
+    #[serde(default, alias = "bundle")]
+    pub bundle_hash: Option<String>,
+    /// Token slug the wallet holds
+    #[serde(default, alias = "token")]
+    pub token_slug: Option<String>,
+    /// Wallet position
+    #[serde(default)]
+    pub position: Option<String>,
+    /// Batch ID of stackable tokens
+    #[serde(default)]
+    pub batch_id: Option<String>,
+    /// Any other fields the node included
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+/// Accept an amount sent as either a JSON string or number
+pub(crate) fn deserialize_optional_amount<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(s) => Some(s),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+/// Accept a Unix timestamp sent as either a JSON string or number
+pub(crate) fn deserialize_optional_timestamp<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(s) => s.trim().parse().ok(),
+        Value::Number(n) => n.as_i64().or_else(|| n.as_f64().map(|f| f as i64)),
+        _ => None,
+    })
+}
+
+/// Deserialize a molecule payload into `T`
+///
+/// Nodes send the payload either as a JSON object or as a stringified JSON document;
+/// both are accepted.
+///
+/// # Errors
+///
+/// Returns `InvalidResponse` if the payload does not match `T`
+pub fn decode_payload<T: serde::de::DeserializeOwned>(payload: &Value) -> Result<T, KnishIOError> {
+    let decoded = match payload {
+        Value::String(text) => serde_json::from_str(text),
+        other => T::deserialize(other),
+    };
+    decoded.map_err(|_| KnishIOError::InvalidResponse)
+}
+
+impl dyn Response {
+    /// Deserialize this response's payload into `T`
+    ///
+    /// # Returns
+    /// None if the response carries no payload
+    pub fn payload_as<T: serde::de::DeserializeOwned>(&self) -> Result<Option<T>, KnishIOError> {
+        match self.payload() {
+            None | Some(Value::Null) => Ok(None),
+            Some(payload) => decode_payload(payload).map(Some),
+        }
+    }
 }
 
 /// Response for QueryActiveSession (equivalent to ResponseQueryActiveSession.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseQueryActiveSession {
     base: BaseResponse,
 }
@@ -1276,6 +1562,7 @@ impl ResponseQueryActiveSession {
 
 impl Response for ResponseQueryActiveSession {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { self.base.success() }
     fn error(&self) -> Option<String> { self.base.error() }
     fn get(&self, key: &str) -> Option<&Value> { self.base.get(key) }
@@ -1284,10 +1571,13 @@ impl Response for ResponseQueryActiveSession {
     fn status(&self) -> Option<String> { self.base.status() }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for RequestAuthorization (equivalent to ResponseRequestAuthorization.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseRequestAuthorization {
     base: BaseResponse,
 }
@@ -1319,6 +1609,7 @@ impl ResponseRequestAuthorization {
 
 impl Response for ResponseRequestAuthorization {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { 
         self.base.success() && 
         self.status().is_some_and(|s| s == "accepted")
@@ -1335,10 +1626,13 @@ impl Response for ResponseRequestAuthorization {
     }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for RequestAuthorizationGuest (equivalent to ResponseRequestAuthorizationGuest.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseRequestAuthorizationGuest {
     base: BaseResponse,
 }
@@ -1409,6 +1703,7 @@ impl ResponseRequestAuthorizationGuest {
 
 impl Response for ResponseRequestAuthorizationGuest {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { 
         // Match JS success() logic: payload !== null
         self.base.get_data().as_object().is_some_and(|obj| !obj.is_empty())
@@ -1423,10 +1718,13 @@ impl Response for ResponseRequestAuthorizationGuest {
     fn status(&self) -> Option<String> { self.base.status() }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for RequestTokens (equivalent to ResponseRequestTokens.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseRequestTokens {
     base: BaseResponse,
 }
@@ -1458,6 +1756,7 @@ impl ResponseRequestTokens {
 
 impl Response for ResponseRequestTokens {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { 
         self.base.success() && 
         self.status().is_some_and(|s| s == "accepted")
@@ -1474,10 +1773,13 @@ impl Response for ResponseRequestTokens {
     }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for TransferTokens (equivalent to ResponseTransferTokens.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseTransferTokens {
     base: BaseResponse,
 }
@@ -1509,6 +1811,7 @@ impl ResponseTransferTokens {
 
 impl Response for ResponseTransferTokens {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { 
         self.base.success() && 
         self.status().is_some_and(|s| s == "accepted")
@@ -1525,10 +1828,13 @@ impl Response for ResponseTransferTokens {
     }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for WalletBundle (equivalent to ResponseWalletBundle.js)  
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseWalletBundle {
     base: BaseResponse,
 }
@@ -1556,6 +1862,7 @@ impl ResponseWalletBundle {
 
 impl Response for ResponseWalletBundle {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { self.base.success() }
     fn error(&self) -> Option<String> { self.base.error() }
     fn get(&self, key: &str) -> Option<&Value> { self.base.get(key) }
@@ -1564,10 +1871,13 @@ impl Response for ResponseWalletBundle {
     fn status(&self) -> Option<String> { self.base.status() }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 /// Response for WalletList (equivalent to ResponseWalletList.js)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseWalletList {
     base: BaseResponse,
 }
@@ -1692,6 +2002,7 @@ impl ResponseWalletList {
 
 impl Response for ResponseWalletList {
     fn data(&self) -> &Value { self.base.data() }
+    fn data_key(&self) -> Option<&str> { self.base.data_key() }
     fn success(&self) -> bool { self.base.success() }
     fn error(&self) -> Option<String> { self.base.error() }
     fn get(&self, key: &str) -> Option<&Value> { self.base.get(key) }
@@ -1700,6 +2011,9 @@ impl Response for ResponseWalletList {
     fn status(&self) -> Option<String> { self.base.status() }
     fn to_json(&self) -> Value { self.base.to_json() }
     fn query(&self) -> Option<&Value> { self.base.query() }
+    fn headers(&self) -> Option<&ResponseHeaders> { self.base.headers() }
+    fn set_headers(&mut self, headers: ResponseHeaders) { self.base.set_headers(headers) }
+    fn envelope(&self) -> Option<ResponseEnvelope> { Some(self.clone().into()) }
 }
 
 // =====================================================
@@ -1910,6 +2224,21 @@ mod tests {
         assert!(response.error().is_none());
     }
 
+    #[test]
+    fn test_meta_type_instance_count() {
+        let response = ResponseMetaType::new(json!({
+            "data": { "MetaType": [{
+                "metaType": "ticket",
+                "instanceCount": [{ "key": "open", "value": 3 }, { "key": "closed", "value": "1" }],
+                "instances": []
+            }] }
+        }), None).unwrap();
+
+        let counts = response.instance_count();
+        assert_eq!(counts.get("open"), Some(&3));
+        assert_eq!(counts.get("closed"), Some(&1));
+    }
+
     #[test]
     fn test_response_with_error() {
         let json = json!({
@@ -1937,5 +2266,58 @@ mod tests {
         assert!(response.success());
         assert_eq!(response.status(), "accepted");
         assert_eq!(response.molecular_hash(), Some("abc123".to_string()));
+        assert_eq!(response.molecule().unwrap().created_at_millis().unwrap(), 1_704_067_200_000);
+
+        // A null or unreadable createdAt still yields the molecule
+        for created_at in [json!(null), json!("pending")] {
+            let json = json!({"data": {"ProposeMolecule": {"molecularHash": "abc123", "status": "accepted", "createdAt": created_at}}});
+            let molecule = ResponseProposeMolecule::new(json, None).unwrap().molecule().unwrap();
+            assert_eq!(molecule.molecular_hash.as_deref(), Some("abc123"));
+        }
+    }
+
+    #[test]
+    fn test_propose_molecule_typed_payloads() {
+        let auth = json!({
+            "data": {
+                "ProposeMolecule": {
+                    "molecularHash": "abc123",
+                    "status": "accepted",
+                    "payload": "{\"token\":\"jwt\",\"expiresAt\":1700000000,\"pubkey\":null,\"encrypt\":false}"
+                }
+            }
+        });
+        let response = ResponseProposeMolecule::new(auth, None).unwrap();
+        let payload: AuthPayload = response.payload_as().unwrap().unwrap();
+        assert_eq!(payload.token, "jwt");
+        assert_eq!(payload.expires_at, Some(1700000000));
+        assert!(response.payload_as::<WalletPayload>().is_err());
+
+        // Nodes also send the expiry as a string
+        for expires_at in [json!("1700000000"), json!(1700000000.0)] {
+            let payload: AuthPayload = decode_payload(&json!({ "token": "jwt", "expiresAt": expires_at })).unwrap();
+            assert_eq!(payload.expires_at, Some(1700000000));
+        }
+        let payload: AuthPayload = decode_payload(&json!({ "token": "jwt", "expiresAt": null })).unwrap();
+        assert_eq!(payload.expires_at, None);
+
+        // Object payloads and numeric amounts work through the trait object as well
+        let token = json!({
+            "data": {
+                "ProposeMolecule": {
+                    "status": "accepted",
+                    "payload": { "slug": "DEMO", "amount": 1000, "fungibility": "fungible" }
+                }
+            }
+        });
+        let response: Box<dyn Response> = Box::new(ResponseProposeMolecule::new(token, None).unwrap());
+        let payload: TokenPayload = response.payload_as().unwrap().unwrap();
+        assert_eq!(payload.token, "DEMO");
+        assert_eq!(payload.amount.as_deref(), Some("1000"));
+        assert_eq!(payload.extra["fungibility"], "fungible");
+
+        let empty = json!({ "data": { "ProposeMolecule": { "status": "accepted" } } });
+        let response = ResponseProposeMolecule::new(empty, None).unwrap();
+        assert_eq!(response.payload_as::<AuthPayload>().unwrap(), None);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,262 @@
+//! Typed response deserialization
+//!
+//! `deserialize_into` reads a response's data (after its data key) into a caller's own
+//! serde type. A mismatch is reported with the JSON path of the failing field, e.g.
+//! `$.data.Balance.tokenUnits[2].id: invalid type: integer `7`, expected a string`,
+//! so schema drift on the node side is easy to pin down.
+//!
+//! Paths are tracked by a thin deserializer over `serde_json::Value` that records where
+//! the innermost error happened.
+
+use super::Response;
+use crate::error::KnishIOError;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::forward_to_deserialize_any;
+use serde_json::Value;
+use std::cell::RefCell;
+
+impl dyn Response {
+    /// Deserialize this response's data into `T`
+    ///
+    /// # Errors
+    ///
+    /// `ResponseShape` naming the JSON path of the first field that does not fit `T`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use knishio_client::response::{BaseResponse, Response};
+    /// use serde::Deserialize;
+    /// use serde_json::json;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Balance { amount: String }
+    ///
+    /// let response = BaseResponse::new(json!({ "data": { "Balance": { "amount": 5 } } }))
+    ///     .unwrap()
+    ///     .with_data_key("data.Balance");
+    /// let response: &dyn Response = &response;
+    ///
+    /// let error = response.deserialize_into::<Balance>().err().unwrap();
+    /// assert!(error.to_string().contains("$.data.Balance.amount"));
+    /// ```
+    pub fn deserialize_into<T: DeserializeOwned>(&self) -> Result<T, KnishIOError> {
+        let root = match self.data_key() {
+            Some(key) => format!("$.{}", key),
+            None => "$".to_string(),
+        };
+        from_value_at(self.data(), root)
+    }
+
+    /// Deserialize the value at the dot-separated `path` below this response's data into `T`
+    ///
+    /// For query-specific shapes, e.g. `deserialize_at::<Vec<Instance>>("instances")` on a
+    /// meta type response. A missing path is an error rather than `null`.
+    pub fn deserialize_at<T: DeserializeOwned>(&self, path: &str) -> Result<T, KnishIOError> {
+        let mut value = self.data();
+        let mut location = match self.data_key() {
+            Some(key) => format!("$.{}", key),
+            None => "$".to_string(),
+        };
+        for part in path.split('.').filter(|part| !part.is_empty()) {
+            location = format!("{}.{}", location, part);
+            value = value.get(part).ok_or_else(|| KnishIOError::ResponseShape {
+                path: location.clone(),
+                message: "missing".to_string(),
+            })?;
+        }
+        from_value_at(value, location)
+    }
+}
+
+/// Deserialize `value` into `T`, reporting errors relative to `root`
+pub fn from_value_at<T: DeserializeOwned>(value: &Value, root: String) -> Result<T, KnishIOError> {
+    let failed = RefCell::new(None);
+    T::deserialize(PathDeserializer { value, path: root.clone(), failed: &failed }).map_err(|error| {
+        KnishIOError::ResponseShape {
+            path: failed.into_inner().unwrap_or(root),
+            message: error.to_string(),
+        }
+    })
+}
+
+/// Deserializer over a `Value` that knows its own JSON path
+struct PathDeserializer<'a, 'p> {
+    value: &'a Value,
+    path: String,
+    /// Path of the innermost failure, set once
+    failed: &'p RefCell<Option<String>>,
+}
+
+impl<'a, 'p> PathDeserializer<'a, 'p> {
+    fn child(&self, value: &'a Value, path: String) -> Self {
+        PathDeserializer { value, path, failed: self.failed }
+    }
+
+    /// Run `seed` on a child value, recording its path if it is the first to fail
+    fn deserialize_child<'de, S: DeserializeSeed<'de>>(&self, seed: S, value: &'a Value, path: String) -> Result<S::Value, serde_json::Error> {
+        seed.deserialize(self.child(value, path.clone())).inspect_err(|_| {
+            self.failed.borrow_mut().get_or_insert(path);
+        })
+    }
+}
+
+impl<'de, 'a, 'p> de::Deserializer<'de> for PathDeserializer<'a, 'p> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(value) => visitor.visit_bool(*value),
+            Value::Number(number) => {
+                if let Some(value) = number.as_u64() {
+                    visitor.visit_u64(value)
+                } else if let Some(value) = number.as_i64() {
+                    visitor.visit_i64(value)
+                } else {
+                    visitor.visit_f64(number.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            Value::String(value) => visitor.visit_str(value),
+            Value::Array(items) => visitor.visit_seq(PathSeq { parent: &self, items: items.iter().enumerate() }),
+            Value::Object(map) => visitor.visit_map(PathMap { parent: &self, entries: map.iter(), pending: None }),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        // Enum contents are rare in responses; they report the enum's own path
+        de::Deserializer::deserialize_enum(self.value.clone().into_deserializer(), name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct PathSeq<'a, 'p, 's> {
+    parent: &'s PathDeserializer<'a, 'p>,
+    items: std::iter::Enumerate<std::slice::Iter<'a, Value>>,
+}
+
+impl<'de, 'a, 'p, 's> SeqAccess<'de> for PathSeq<'a, 'p, 's> {
+    type Error = serde_json::Error;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<Option<S::Value>, Self::Error> {
+        match self.items.next() {
+            Some((index, value)) => {
+                let path = format!("{}[{}]", self.parent.path, index);
+                self.parent.deserialize_child(seed, value, path).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct PathMap<'a, 'p, 's> {
+    parent: &'s PathDeserializer<'a, 'p>,
+    entries: serde_json::map::Iter<'a>,
+    pending: Option<(&'a String, &'a Value)>,
+}
+
+impl<'de, 'a, 'p, 's> MapAccess<'de> for PathMap<'a, 'p, 's> {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.pending = Some((key, value));
+                seed.deserialize(key.as_str().into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<S::Value, Self::Error> {
+        let (key, value) = self.pending.take()
+            .ok_or_else(|| de::Error::custom("value requested before its key"))?;
+        let path = format!("{}.{}", self.parent.path, key);
+        self.parent.deserialize_child(seed, value, path)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::BaseResponse;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    struct Unit {
+        id: String,
+        name: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    struct Balance {
+        amount: String,
+        token_units: Vec<Unit>,
+    }
+
+    fn response(units: Value) -> BaseResponse {
+        BaseResponse::new(json!({ "data": { "Balance": { "amount": "10", "tokenUnits": units } } }))
+            .unwrap()
+            .with_data_key("data.Balance")
+    }
+
+    #[test]
+    fn test_deserialize_into() {
+        let ok = response(json!([{ "id": "u1", "name": null }, { "id": "u2", "name": "Two" }]));
+        let ok: &dyn Response = &ok;
+        let balance: Balance = ok.deserialize_into().unwrap();
+        assert_eq!(balance.token_units[1], Unit { id: "u2".to_string(), name: Some("Two".to_string()) });
+        assert_eq!(ok.deserialize_at::<String>("amount").unwrap(), "10");
+
+        let wrong_type = response(json!([{ "id": "u1" }, { "id": 7 }]));
+        let wrong_type: &dyn Response = &wrong_type;
+        match wrong_type.deserialize_into::<Balance>() {
+            Err(KnishIOError::ResponseShape { path, message }) => {
+                assert_eq!(path, "$.data.Balance.tokenUnits[1].id");
+                assert!(message.contains("expected a string"), "{}", message);
+            }
+            other => panic!("unexpected {:?}", other.map(|_| ())),
+        }
+
+        let missing = response(json!([{ "name": "nameless" }]));
+        let missing: &dyn Response = &missing;
+        let error = missing.deserialize_into::<Balance>().err().unwrap();
+        assert_eq!(error.to_string(), "Response does not match the expected type at $.data.Balance.tokenUnits[0]: missing field `id`");
+
+        assert!(matches!(
+            ok.deserialize_at::<String>("owner.name"),
+            Err(KnishIOError::ResponseShape { path, .. }) if path == "$.data.Balance.owner"
+        ));
+    }
+}
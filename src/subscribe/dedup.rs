@@ -0,0 +1,183 @@
+//! Subscription event deduplication
+//!
+//! After a reconnect the node replays recent events, and a retried broadcast can be
+//! announced twice, so a subscriber may receive the same CreateMolecule event more than
+//! once. `EventDeduplicator` remembers the events it has let through, keyed by event type
+//! and molecular hash, within a window bounded by both size and age. Events that carry
+//! no molecular hash (wallet status, session pings) cannot be told apart and always pass.
+
+use serde_json::Value;
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Size and age limits of the deduplication window
+#[derive(Debug, Clone, PartialEq)]
+pub struct DedupConfig {
+    /// Most events remembered at once; the oldest is forgotten first
+    pub capacity: usize,
+    /// How long an event is remembered
+    pub ttl: Duration,
+}
+
+impl Default for DedupConfig {
+    fn default() -> Self {
+        DedupConfig {
+            capacity: 1024,
+            ttl: Duration::from_secs(600),
+        }
+    }
+}
+
+impl DedupConfig {
+    /// Set the most events remembered at once (at least one)
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Set how long an event is remembered
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+}
+
+/// Events seen recently, oldest first
+#[derive(Debug, Default)]
+struct DedupWindow {
+    order: VecDeque<(String, Instant)>,
+    keys: HashSet<String>,
+}
+
+impl DedupWindow {
+    fn forget_oldest(&mut self) {
+        if let Some((key, _)) = self.order.pop_front() {
+            self.keys.remove(&key);
+        }
+    }
+}
+
+/// Lets each subscription event through once per window
+///
+/// Clones share the same window.
+#[derive(Debug, Clone)]
+pub struct EventDeduplicator {
+    config: DedupConfig,
+    window: Arc<Mutex<DedupWindow>>,
+}
+
+impl EventDeduplicator {
+    /// Create an empty window
+    pub fn new(config: DedupConfig) -> Self {
+        EventDeduplicator {
+            config,
+            window: Arc::new(Mutex::new(DedupWindow::default())),
+        }
+    }
+
+    /// Window limits
+    pub fn config(&self) -> &DedupConfig {
+        &self.config
+    }
+
+    /// True unless an `event_type` event with the same molecular hash was let through
+    /// within the window
+    pub fn first_seen(&self, event_type: &str, data: &Value) -> bool {
+        let Some(hash) = event_molecular_hash(data) else {
+            return true;
+        };
+        let key = format!("{}:{}", event_type, hash);
+        let now = Instant::now();
+
+        let mut window = self.window.lock().unwrap_or_else(PoisonError::into_inner);
+        while window.order.front().is_some_and(|(_, seen_at)| now.duration_since(*seen_at) >= self.config.ttl) {
+            window.forget_oldest();
+        }
+
+        if window.keys.contains(&key) {
+            return false;
+        }
+        while window.order.len() >= self.config.capacity {
+            window.forget_oldest();
+        }
+        window.keys.insert(key.clone());
+        window.order.push_back((key, now));
+        true
+    }
+
+    /// Number of events currently remembered
+    pub fn len(&self) -> usize {
+        self.window.lock().unwrap_or_else(PoisonError::into_inner).order.len()
+    }
+
+    /// True when no event is remembered
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forget every event
+    pub fn clear(&self) {
+        let mut window = self.window.lock().unwrap_or_else(PoisonError::into_inner);
+        window.order.clear();
+        window.keys.clear();
+    }
+}
+
+/// Molecular hash of a subscription event
+///
+/// Accepts the bare payload (`{ molecularHash, ... }`) as well as the payload wrapped in
+/// its `data` and root field (`{ data: { CreateMolecule: { molecularHash, ... } } }`).
+pub fn event_molecular_hash(data: &Value) -> Option<&str> {
+    fn find(value: &Value, depth: usize) -> Option<&str> {
+        let object = value.as_object()?;
+        if let Some(hash) = object.get("molecularHash").and_then(Value::as_str) {
+            return Some(hash);
+        }
+        if depth == 0 || object.len() != 1 {
+            return None;
+        }
+        object.values().next().and_then(|inner| find(inner, depth - 1))
+    }
+
+    find(data, 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_duplicates_are_dropped_within_the_window() {
+        let dedup = EventDeduplicator::new(DedupConfig::default().capacity(2));
+        let first = json!({ "data": { "CreateMolecule": { "molecularHash": "h1", "status": "accepted" } } });
+
+        assert!(dedup.first_seen("CreateMolecule", &first));
+        assert!(!dedup.first_seen("CreateMolecule", &json!({ "molecularHash": "h1" })));
+        assert!(dedup.first_seen("WalletStatus", &json!({ "molecularHash": "h1" })));
+
+        // Events without a molecular hash always pass
+        assert!(dedup.first_seen("ActiveWallet", &json!({ "address": "a" })));
+        assert!(dedup.first_seen("ActiveWallet", &json!({ "address": "a" })));
+
+        // Capacity evicts the oldest
+        assert!(dedup.first_seen("CreateMolecule", &json!({ "molecularHash": "h2" })));
+        assert_eq!(dedup.len(), 2);
+        assert!(dedup.first_seen("CreateMolecule", &json!({ "molecularHash": "h1" })));
+
+        dedup.clear();
+        assert!(dedup.is_empty());
+    }
+
+    #[test]
+    fn test_events_expire_after_ttl() {
+        let dedup = EventDeduplicator::new(DedupConfig::default().ttl(Duration::from_millis(20)));
+        let event = json!({ "molecularHash": "h1" });
+
+        assert!(dedup.first_seen("CreateMolecule", &event));
+        assert!(!dedup.first_seen("CreateMolecule", &event));
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(dedup.first_seen("CreateMolecule", &event));
+    }
+}
@@ -0,0 +1,146 @@
+//! Subscription limits
+//!
+//! A client that opens subscriptions and never closes them keeps growing its share of the
+//! node's socket until the node disconnects it. `SubscriptionLimits` caps a
+//! `SubscriptionManager`: at most `max_active` subscriptions at once, and optionally none
+//! that has gone `idle_timeout` without an event.
+//!
+//! When a new subscription would exceed the cap, `LimitPolicy::EvictLeastRecent` closes the
+//! subscription whose last event (or opening) is oldest, and `LimitPolicy::Reject` closes
+//! the new one and fails with `SubscriptionLimit`. Idle subscriptions are reaped whenever a
+//! subscription is opened, by `SubscriptionManager::reap_idle`, and periodically by
+//! `SubscriptionManager::spawn_reaper`. Every eviction and reap is reported to the
+//! listener set with `SubscriptionManager::on_limit_event`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// What a manager does with a subscription that would exceed `max_active`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LimitPolicy {
+    /// Close the least recently active subscription to make room
+    #[default]
+    EvictLeastRecent,
+    /// Refuse the new subscription with `SubscriptionLimit`
+    Reject,
+}
+
+/// Caps on a manager's subscriptions; the default has none
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SubscriptionLimits {
+    /// Most subscriptions open at once (None for no cap)
+    pub max_active: Option<usize>,
+    /// What happens to a subscription past the cap
+    pub on_limit: LimitPolicy,
+    /// How long a subscription may go without events before it is reaped (None keeps it)
+    pub idle_timeout: Option<Duration>,
+}
+
+impl SubscriptionLimits {
+    /// Set the most subscriptions open at once (at least one)
+    pub fn max_active(mut self, max_active: usize) -> Self {
+        self.max_active = Some(max_active.max(1));
+        self
+    }
+
+    /// Set what happens to a subscription past the cap
+    pub fn on_limit(mut self, policy: LimitPolicy) -> Self {
+        self.on_limit = policy;
+        self
+    }
+
+    /// Set how long a subscription may go without events before it is reaped
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+}
+
+/// A subscription closed by the manager rather than by its owner
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubscriptionLimitEvent {
+    /// Closed to make room under `max_active`
+    Evicted {
+        /// Operation name of the closed subscription
+        operation_name: String,
+        /// Time since its last event
+        idle: Duration,
+    },
+    /// Closed after `idle_timeout` without events
+    Reaped {
+        /// Operation name of the closed subscription
+        operation_name: String,
+        /// Time since its last event
+        idle: Duration,
+    },
+}
+
+impl fmt::Display for SubscriptionLimitEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubscriptionLimitEvent::Evicted { operation_name, idle } => {
+                write!(f, "evicted {} (idle {:?})", operation_name, idle)
+            }
+            SubscriptionLimitEvent::Reaped { operation_name, idle } => {
+                write!(f, "reaped {} (idle {:?})", operation_name, idle)
+            }
+        }
+    }
+}
+
+/// Listener for `SubscriptionLimitEvent`s
+pub type LimitListener = Arc<dyn Fn(SubscriptionLimitEvent) + Send + Sync>;
+
+/// Last activity of each tracked subscription; clones share the same record
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ActivityLog {
+    last_seen: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl ActivityLog {
+    /// Mark `operation_name` active now (a no-op for untracked subscriptions)
+    pub(crate) fn touch(&self, operation_name: &str) {
+        let mut last_seen = self.last_seen.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(seen) = last_seen.get_mut(operation_name) {
+            *seen = Instant::now();
+        }
+    }
+
+    pub(crate) fn start(&self, operation_name: &str) {
+        self.last_seen.lock().unwrap_or_else(PoisonError::into_inner).insert(operation_name.to_string(), Instant::now());
+    }
+
+    pub(crate) fn forget(&self, operation_name: &str) {
+        self.last_seen.lock().unwrap_or_else(PoisonError::into_inner).remove(operation_name);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.last_seen.lock().unwrap_or_else(PoisonError::into_inner).clear();
+    }
+
+    /// Time since `operation_name`'s last activity
+    pub(crate) fn idle(&self, operation_name: &str) -> Option<Duration> {
+        self.last_seen.lock().unwrap_or_else(PoisonError::into_inner).get(operation_name).map(Instant::elapsed)
+    }
+
+    /// The tracked subscription with the oldest activity
+    pub(crate) fn least_recent(&self) -> Option<(String, Duration)> {
+        self.last_seen.lock().unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .min_by_key(|(_, seen)| **seen)
+            .map(|(name, seen)| (name.clone(), seen.elapsed()))
+    }
+
+    /// Tracked subscriptions without activity for at least `timeout`
+    pub(crate) fn idle_for(&self, timeout: Duration) -> Vec<(String, Duration)> {
+        let mut idle: Vec<(String, Duration)> = self.last_seen.lock().unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(name, seen)| (name.clone(), seen.elapsed()))
+            .filter(|(_, idle)| *idle >= timeout)
+            .collect();
+        idle.sort_by_key(|(_, idle)| std::cmp::Reverse(*idle));
+        idle
+    }
+}
@@ -14,13 +14,22 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use serde_json::Value;
 use async_trait::async_trait;
-use crate::error::Result;
-use crate::graphql::GraphQLClient;
+use crate::error::{KnishIOError, Result};
+use crate::graphql::{GraphQLClient, GraphQLResponse, WebSocketManager, WebSocketStats};
 
 // Simple WebSocket implementation
 pub mod simple_websocket;
 pub use simple_websocket::{SimpleSubscriptionManager, SimpleWebSocketClient, SubscriptionHandle};
 
+// Event deduplication window
+pub mod dedup;
+pub use dedup::{DedupConfig, EventDeduplicator};
+
+// Active and idle subscription limits
+pub mod limits;
+pub use limits::{LimitListener, LimitPolicy, SubscriptionLimitEvent, SubscriptionLimits};
+use limits::ActivityLog;
+
 // Specific subscription implementations (matching JavaScript)
 pub mod active_wallet_subscribe;
 pub mod active_session_subscribe;
@@ -92,6 +101,16 @@ pub enum SubscriptionStatus {
 pub struct SubscriptionManager {
     subscriptions: Arc<RwLock<HashMap<String, SubscriptionHandle>>>,
     graphql_client: Arc<GraphQLClient>,
+    /// WebSocket connections reported by `stats`
+    sockets: Arc<std::sync::RwLock<Vec<WebSocketManager>>>,
+    /// Drops repeated events; None delivers everything
+    dedup: Arc<std::sync::RwLock<Option<EventDeduplicator>>>,
+    /// Caps on open and idle subscriptions
+    limits: Arc<std::sync::RwLock<SubscriptionLimits>>,
+    /// Last event of each subscription, for eviction and reaping
+    activity: ActivityLog,
+    /// Told about subscriptions closed by the limits
+    limit_listener: Arc<std::sync::RwLock<Option<LimitListener>>>,
 }
 
 impl SubscriptionManager {
@@ -99,7 +118,161 @@ impl SubscriptionManager {
         Self {
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
             graphql_client,
+            sockets: Arc::new(std::sync::RwLock::new(Vec::new())),
+            dedup: Arc::new(std::sync::RwLock::new(Some(EventDeduplicator::new(DedupConfig::default())))),
+            limits: Arc::new(std::sync::RwLock::new(SubscriptionLimits::default())),
+            activity: ActivityLog::default(),
+            limit_listener: Arc::new(std::sync::RwLock::new(None)),
+        }
+    }
+
+    /// Replace the subscription limits
+    ///
+    /// There are none by default. Subscriptions already open over a new cap stay open
+    /// until the next one is opened.
+    pub fn set_limits(&self, limits: SubscriptionLimits) {
+        *self.limits.write().unwrap_or_else(std::sync::PoisonError::into_inner) = limits;
+    }
+
+    /// Current subscription limits
+    pub fn limits(&self) -> SubscriptionLimits {
+        self.limits.read().unwrap_or_else(std::sync::PoisonError::into_inner).clone()
+    }
+
+    /// Call `listener` for every subscription evicted or reaped by the limits
+    pub fn on_limit_event<F>(&self, listener: F)
+    where
+        F: Fn(SubscriptionLimitEvent) + Send + Sync + 'static,
+    {
+        *self.limit_listener.write().unwrap_or_else(std::sync::PoisonError::into_inner) = Some(Arc::new(listener));
+    }
+
+    fn notify_limit_event(&self, event: SubscriptionLimitEvent) {
+        let listener = self.limit_listener.read().unwrap_or_else(std::sync::PoisonError::into_inner).clone();
+        if let Some(listener) = listener {
+            listener(event);
+        }
+    }
+
+    /// Mark `operation_name` active, postponing its eviction and reaping
+    pub fn touch(&self, operation_name: &str) {
+        self.activity.touch(operation_name);
+    }
+
+    /// Time since `operation_name`'s last event, or since it was opened
+    pub fn idle_time(&self, operation_name: &str) -> Option<std::time::Duration> {
+        self.activity.idle(operation_name)
+    }
+
+    /// Register an opened subscription, enforcing the limits
+    ///
+    /// Idle subscriptions are reaped first. If the manager is still at `max_active`, the
+    /// least recently active subscription is evicted, or under `LimitPolicy::Reject`
+    /// `handle` is unsubscribed and `SubscriptionLimit` returned. The returned handle
+    /// also removes the subscription from the manager when unsubscribed.
+    pub async fn track(&self, handle: SubscriptionHandle) -> Result<SubscriptionHandle> {
+        self.reap_idle().await;
+
+        let limits = self.limits();
+        if let Some(max_active) = limits.max_active {
+            loop {
+                let active = self.active_count().await;
+                if active < max_active {
+                    break;
+                }
+                if limits.on_limit == LimitPolicy::Reject {
+                    handle.unsubscribe();
+                    return Err(KnishIOError::SubscriptionLimit(format!(
+                        "{} of {} subscriptions open, {} refused",
+                        active, max_active, handle.operation_name
+                    )));
+                }
+                let Some((operation_name, idle)) = self.activity.least_recent() else {
+                    break;
+                };
+                self.unsubscribe(&operation_name).await;
+                self.notify_limit_event(SubscriptionLimitEvent::Evicted { operation_name, idle });
+            }
+        }
+
+        let operation_name = handle.operation_name.clone();
+        self.subscriptions.write().await.insert(operation_name.clone(), handle.clone());
+        self.activity.start(&operation_name);
+
+        let manager = self.clone();
+        Ok(handle.chain_unsubscribe(Box::new(move || {
+            let manager = manager.clone();
+            let operation_name = operation_name.clone();
+            manager.activity.forget(&operation_name);
+            tokio::spawn(async move {
+                manager.subscriptions.write().await.remove(&operation_name);
+            });
+        })))
+    }
+
+    /// Unsubscribe every subscription idle for `idle_timeout` or longer, returning their names
+    pub async fn reap_idle(&self) -> Vec<String> {
+        let Some(timeout) = self.limits().idle_timeout else {
+            return Vec::new();
+        };
+        let mut reaped = Vec::new();
+        for (operation_name, idle) in self.activity.idle_for(timeout) {
+            self.unsubscribe(&operation_name).await;
+            reaped.push(operation_name.clone());
+            self.notify_limit_event(SubscriptionLimitEvent::Reaped { operation_name, idle });
         }
+        reaped
+    }
+
+    /// Reap idle subscriptions every `every` until the returned handle is aborted
+    pub fn spawn_reaper(&self, every: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(every);
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                manager.reap_idle().await;
+            }
+        })
+    }
+
+    /// Replace the deduplication window, or turn deduplication off with None
+    ///
+    /// Deduplication is on by default with `DedupConfig::default()`. Events already
+    /// remembered are forgotten.
+    pub fn set_dedup(&self, config: Option<DedupConfig>) {
+        *self.dedup.write().unwrap_or_else(std::sync::PoisonError::into_inner) = config.map(EventDeduplicator::new);
+    }
+
+    /// Current deduplication window
+    pub fn dedup(&self) -> Option<EventDeduplicator> {
+        self.dedup.read().unwrap_or_else(std::sync::PoisonError::into_inner).clone()
+    }
+
+    /// True if an `event_type` event should reach the consumer, false for a duplicate
+    pub fn accept_event(&self, event_type: &str, data: &Value) -> bool {
+        self.dedup().is_none_or(|dedup| dedup.first_seen(event_type, data))
+    }
+
+    /// Forward the responses of a WebSocket subscription, dropping duplicate `event_type` events
+    pub fn dedup_stream(
+        &self,
+        event_type: &str,
+        mut receiver: tokio::sync::mpsc::UnboundedReceiver<GraphQLResponse>,
+    ) -> tokio::sync::mpsc::UnboundedReceiver<GraphQLResponse> {
+        let (sender, deduplicated) = tokio::sync::mpsc::unbounded_channel();
+        let manager = self.clone();
+        let event_type = event_type.to_string();
+        tokio::spawn(async move {
+            while let Some(response) = receiver.recv().await {
+                let duplicate = response.data.as_ref().is_some_and(|data| !manager.accept_event(&event_type, data));
+                if !duplicate && sender.send(response).is_err() {
+                    break;
+                }
+            }
+        });
+        deduplicated
     }
     
     /// Create subscription request matching JavaScript createSubscribe() pattern
@@ -112,58 +285,52 @@ impl SubscriptionManager {
     }
     
     /// Subscribe to GraphQL subscription (JavaScript client.subscribe() pattern)
+    ///
+    /// Opens the subscription with `open` and registers it against the limits.
     pub async fn subscribe<F>(
         &self,
-        _request: SubscribeRequest,
-        _closure: F,
+        request: SubscribeRequest,
+        closure: F,
     ) -> Result<SubscriptionHandle>
     where
         F: Fn(Value) + Send + Sync + 'static,
     {
-        let operation_name = format!("subscription_{}", uuid::Uuid::new_v4());
-        
-        // Create unsubscribe function (JavaScript pattern)
-        let subscriptions = self.subscriptions.clone();
-        let op_name = operation_name.clone();
-        let unsubscribe_fn = Box::new(move || {
-            let subscriptions = subscriptions.clone();
-            let op_name = op_name.clone();
-            tokio::spawn(async move {
-                let mut subs = subscriptions.write().await;
-                subs.remove(&op_name);
-            });
-        }) as Box<dyn Fn() + Send + Sync>;
-        
-        let handle = SubscriptionHandle::new(operation_name.clone(), unsubscribe_fn);
-        
-        // Clone operation_name before the move
-        let op_name_for_final = operation_name.clone();
-        
-        // Store subscription (JavaScript Map pattern)
-        {
-            let mut subs = self.subscriptions.write().await;
-            subs.insert(operation_name, handle);
-        }
-        
-        // Return handle with unsubscribe (JavaScript return { unsubscribe: ... } pattern)
-        let final_unsubscribe_fn = {
-            let manager = self.clone();
-            let op_name = op_name_for_final.clone();
-            Box::new(move || {
-                let manager = manager.clone();
-                let op_name = op_name.clone();
-                tokio::spawn(async move {
-                    manager.unsubscribe(&op_name).await;
-                });
-            }) as Box<dyn Fn() + Send + Sync>
+        let handle = self.open(request, closure).await?;
+        self.track(handle).await
+    }
+
+    /// Open `request` on the first attached WebSocket connection, without tracking it
+    ///
+    /// The `data` of every event is handed to `closure`, and the returned handle can
+    /// `update_variables` in place.
+    ///
+    /// # Errors
+    ///
+    /// `WebSocketError` when no WebSocket connection is attached
+    pub async fn open<F>(&self, request: SubscribeRequest, closure: F) -> Result<SubscriptionHandle>
+    where
+        F: Fn(Value) + Send + Sync + 'static,
+    {
+        let socket = self.sockets.read().unwrap_or_else(std::sync::PoisonError::into_inner).first().cloned();
+        let Some(mut socket) = socket else {
+            return Err(KnishIOError::WebSocketError("No WebSocket connection attached to open the subscription on".to_string()));
         };
-        
-        Ok(SubscriptionHandle::new(op_name_for_final, final_unsubscribe_fn))
+
+        let (handle, mut receiver) = socket.subscribe_handle(request.query, Some(request.variables), None).await?;
+        tokio::spawn(async move {
+            while let Some(response) = receiver.recv().await {
+                if let Some(data) = response.data {
+                    closure(data);
+                }
+            }
+        });
+        Ok(handle)
     }
     
     /// Unsubscribe from specific subscription (JavaScript pattern)
     pub async fn unsubscribe(&self, operation_name: &str) {
         let mut subs = self.subscriptions.write().await;
+        self.activity.forget(operation_name);
         if let Some(subscription) = subs.remove(operation_name) {
             subscription.unsubscribe();
         }
@@ -172,6 +339,7 @@ impl SubscriptionManager {
     /// Unsubscribe from all subscriptions (JavaScript pattern)
     pub async fn unsubscribe_all(&self) {
         let mut subs = self.subscriptions.write().await;
+        self.activity.clear();
         for (_, subscription) in subs.drain() {
             subscription.unsubscribe();
         }
@@ -212,6 +380,32 @@ impl SubscriptionManager {
         self.subscriptions.read().await.keys().cloned().collect()
     }
     
+    /// Report `socket` in `stats` (a connection attached twice is listed once)
+    ///
+    /// The first attached connection also carries the subscriptions `subscribe` opens.
+    /// `KnishIOClient::set_websocket_manager` attaches the client's connection itself.
+    pub fn attach_socket(&self, socket: WebSocketManager) {
+        let mut sockets = self.sockets.write().unwrap_or_else(std::sync::PoisonError::into_inner);
+        if !sockets.iter().any(|s| s.shares_connection(&socket)) {
+            sockets.push(socket);
+        }
+    }
+
+    /// Stop reporting `socket` (or any clone of it) in `stats`
+    pub fn detach_socket(&self, socket: &WebSocketManager) {
+        self.sockets.write().unwrap_or_else(std::sync::PoisonError::into_inner).retain(|s| !s.shares_connection(socket));
+    }
+
+    /// Per-connection statistics of the attached WebSocket connections, in attach order
+    pub async fn stats(&self) -> Vec<WebSocketStats> {
+        let sockets = self.sockets.read().unwrap_or_else(std::sync::PoisonError::into_inner).clone();
+        let mut stats = Vec::with_capacity(sockets.len());
+        for socket in &sockets {
+            stats.push(socket.stats().await);
+        }
+        stats
+    }
+
     /// Check if subscription exists by ID (JavaScript Map.has() pattern)
     pub async fn get_subscription(&self, id: &str) -> Option<String> {
         let subs = self.subscriptions.read().await;
@@ -228,6 +422,11 @@ impl Clone for SubscriptionManager {
         Self {
             subscriptions: self.subscriptions.clone(),
             graphql_client: self.graphql_client.clone(),
+            sockets: self.sockets.clone(),
+            dedup: self.dedup.clone(),
+            limits: self.limits.clone(),
+            activity: self.activity.clone(),
+            limit_listener: self.limit_listener.clone(),
         }
     }
 }
@@ -252,7 +451,120 @@ mod tests {
         
         assert!(manager.subscriptions.read().await.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_stats_list_attached_sockets() {
+        use crate::graphql::{ConnectionState, WebSocketReconnectConfig};
+
+        let manager = SubscriptionManager::new(Arc::new(GraphQLClient::new("ws://localhost:8080")));
+        assert!(manager.stats().await.is_empty());
+
+        let socket = WebSocketManager::new(
+            "ws://localhost:8080/graphql".to_string(),
+            None,
+            "knishio".to_string(),
+            WebSocketReconnectConfig::default(),
+            false,
+        );
+        manager.attach_socket(socket.clone());
+        manager.attach_socket(socket.clone());
+
+        let stats = manager.stats().await;
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].uri, "ws://localhost:8080/graphql");
+        assert_eq!(stats[0].state, ConnectionState::Disconnected);
+        assert!(stats[0].subscription_ids.is_empty() && stats[0].uptime.is_none());
+
+        manager.detach_socket(&socket);
+        assert!(manager.stats().await.is_empty());
+    }
     
+    #[tokio::test]
+    async fn test_duplicate_events_are_dropped() {
+        let manager = SubscriptionManager::new(Arc::new(GraphQLClient::new("ws://localhost:8080")));
+        let event = json!({ "molecularHash": "h1", "status": "accepted" });
+        assert!(manager.accept_event("CreateMolecule", &event));
+        assert!(!manager.clone().accept_event("CreateMolecule", &event));
+
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut stream = manager.dedup_stream("CreateMolecule", receiver);
+        for hash in ["h2", "h1", "h2", "h3"] {
+            let data = json!({ "CreateMolecule": { "molecularHash": hash } });
+            sender.send(GraphQLResponse { data: Some(data), errors: None, extensions: None, headers: Default::default() }).unwrap();
+        }
+        drop(sender);
+        let mut delivered = Vec::new();
+        while let Some(response) = stream.recv().await {
+            delivered.push(dedup::event_molecular_hash(response.data.as_ref().unwrap()).unwrap().to_string());
+        }
+        assert_eq!(delivered, vec!["h2", "h3"]);
+
+        manager.set_dedup(None);
+        assert!(manager.dedup().is_none());
+        assert!(manager.accept_event("CreateMolecule", &event));
+    }
+
+    #[tokio::test]
+    async fn test_open_without_socket_fails() {
+        let manager = SubscriptionManager::new(Arc::new(GraphQLClient::new("ws://localhost:8080")));
+        let request = manager.create_subscribe_request("subscription { test }", json!({}));
+
+        let error = manager.subscribe(request, |_| {}).await.err().unwrap();
+        assert!(matches!(error, KnishIOError::WebSocketError(_)), "{:?}", error);
+        assert_eq!(manager.active_count().await, 0);
+    }
+
+    /// Track a handle with nothing behind it, as `subscribe` does for an opened one
+    async fn track_stub(manager: &SubscriptionManager) -> Result<SubscriptionHandle> {
+        let operation_name = format!("subscription_{}", uuid::Uuid::new_v4());
+        manager.track(SubscriptionHandle::new(operation_name, Box::new(|| {}))).await
+    }
+
+    #[tokio::test]
+    async fn test_limits_evict_reject_and_reap() {
+        use std::time::Duration;
+
+        let manager = SubscriptionManager::new(Arc::new(GraphQLClient::new("ws://localhost:8080")));
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = events.clone();
+        manager.on_limit_event(move |event| recorder.lock().unwrap().push(event));
+        manager.set_limits(SubscriptionLimits::default().max_active(2));
+
+        let first = track_stub(&manager).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let second = track_stub(&manager).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        // The least recently active one makes room
+        manager.touch(&first.operation_name);
+        let third = track_stub(&manager).await.unwrap();
+        let mut open = manager.list_subscriptions().await;
+        open.sort();
+        let mut expected = vec![first.operation_name.clone(), third.operation_name.clone()];
+        expected.sort();
+        assert_eq!(open, expected);
+        assert!(matches!(
+            events.lock().unwrap().as_slice(),
+            [SubscriptionLimitEvent::Evicted { operation_name, .. }] if *operation_name == second.operation_name
+        ));
+
+        manager.set_limits(SubscriptionLimits::default().max_active(2).on_limit(LimitPolicy::Reject));
+        let refused = track_stub(&manager).await.err().unwrap();
+        assert!(matches!(refused, KnishIOError::SubscriptionLimit(_)), "{:?}", refused);
+        assert_eq!(manager.active_count().await, 2);
+
+        // Unsubscribing through the handle frees the slot
+        third.unsubscribe();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(manager.active_count().await, 1);
+
+        manager.set_limits(SubscriptionLimits::default().idle_timeout(Duration::from_millis(20)));
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(manager.reap_idle().await, vec![first.operation_name.clone()]);
+        assert_eq!(manager.active_count().await, 0);
+        assert!(matches!(events.lock().unwrap().last(), Some(SubscriptionLimitEvent::Reaped { .. })));
+    }
+
     #[tokio::test]
     async fn test_create_subscribe_request() {
         let client = Arc::new(GraphQLClient::new("ws://localhost:8080"));
@@ -8,12 +8,19 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
 use serde_json::Value;
-use crate::error::Result;
+use crate::error::{KnishIOError, Result};
+
+/// Callback that re-targets a live subscription at new variables
+pub type UpdateVariablesFn = Box<dyn Fn(Value) -> Result<()> + Send + Sync>;
 
 /// Simple subscription handle matching JavaScript pattern
+///
+/// Clones control the same subscription.
+#[derive(Clone)]
 pub struct SubscriptionHandle {
     pub operation_name: String,
-    unsubscribe_fn: Box<dyn Fn() + Send + Sync>,
+    unsubscribe_fn: Arc<dyn Fn() + Send + Sync>,
+    update_fn: Option<Arc<dyn Fn(Value) -> Result<()> + Send + Sync>>,
 }
 
 // Manual Debug implementation since function pointers don't implement Debug
@@ -22,6 +29,7 @@ impl std::fmt::Debug for SubscriptionHandle {
         f.debug_struct("SubscriptionHandle")
             .field("operation_name", &self.operation_name)
             .field("unsubscribe_fn", &"<function>")
+            .field("updatable", &self.update_fn.is_some())
             .finish()
     }
 }
@@ -30,14 +38,54 @@ impl SubscriptionHandle {
     pub fn new(operation_name: String, unsubscribe_fn: Box<dyn Fn() + Send + Sync>) -> Self {
         Self {
             operation_name,
-            unsubscribe_fn,
+            unsubscribe_fn: Arc::from(unsubscribe_fn),
+            update_fn: None,
         }
     }
+
+    /// Attach a variables updater (set by transports that can restart a subscription in place)
+    pub fn with_update_fn(mut self, update_fn: UpdateVariablesFn) -> Self {
+        self.update_fn = Some(Arc::from(update_fn));
+        self
+    }
+
+    /// This handle with `on_unsubscribe` run after its own unsubscribe
+    pub(crate) fn chain_unsubscribe(mut self, on_unsubscribe: Box<dyn Fn() + Send + Sync>) -> Self {
+        let inner = self.unsubscribe_fn;
+        self.unsubscribe_fn = Arc::new(move || {
+            inner();
+            on_unsubscribe();
+        });
+        self
+    }
     
     /// Unsubscribe from this subscription (JavaScript pattern)
     pub fn unsubscribe(&self) {
         (self.unsubscribe_fn)();
     }
+
+    /// Whether `update_variables` is supported by the transport behind this handle
+    pub fn supports_update(&self) -> bool {
+        self.update_fn.is_some()
+    }
+
+    /// Point this subscription at new variables without tearing down the consumer stream
+    ///
+    /// The transport stops the server-side operation and starts it again under the same
+    /// id, so events keep arriving on the existing receiver/closure.
+    ///
+    /// # Errors
+    ///
+    /// Returns `WebSocketError` if the transport cannot update subscriptions in place
+    pub fn update_variables(&self, new_vars: Value) -> Result<()> {
+        match self.update_fn {
+            Some(ref update) => update(new_vars),
+            None => Err(KnishIOError::WebSocketError(format!(
+                "Subscription {} does not support variable updates",
+                self.operation_name
+            ))),
+        }
+    }
 }
 
 /// Simple subscription manager matching JavaScript UrqlClientWrapper
@@ -237,4 +285,23 @@ mod tests {
         let subs = manager.subscriptions.read().await;
         assert!(subs.is_empty());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_update_variables() {
+        let plain = SubscriptionHandle::new("plain".to_string(), Box::new(|| {}));
+        assert!(!plain.supports_update());
+        assert!(plain.update_variables(json!({"bundle": "b"})).is_err());
+
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let updatable = SubscriptionHandle::new("live".to_string(), Box::new(|| {}))
+            .with_update_fn(Box::new(move |vars| {
+                recorder.lock().unwrap().push(vars);
+                Ok(())
+            }));
+
+        assert!(updatable.supports_update());
+        updatable.update_variables(json!({"bundle": "b"})).unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec![json!({"bundle": "b"})]);
+    }
+}
@@ -0,0 +1,727 @@
+//! Embedded test ledger
+//!
+//! An in-process stand-in for a validator node, enabled with the `test-ledger` feature.
+//! `TestLedger::start()` binds a GraphQL endpoint on a loopback port; clients pointed at
+//! `ledger.uri()` talk to it over real HTTP, so integration tests exercise the same
+//! request/response path as against a live node without needing one.
+//!
+//! The ledger accepts `ProposeMolecule`, runs `CheckMolecule` on every proposal and keeps
+//! wallet balances, tokens, metadata and ContinuID heads in memory. It answers the
+//! `ContinuId`, `Balance`, `Wallet`, `Token`, `MetaType` and `Atom` queries; any other root
+//! field is answered with a GraphQL error, and non-JSON request bodies with 415. Every reply carries an `X-Request-Id` header. It is a simulator, not a
+//! validator: there is no consensus, no policy enforcement and no stackable-unit routing.
+//!
+//! ```no_run
+//! # async fn demo() -> knishio_client::Result<()> {
+//! use knishio_client::test_ledger::TestLedger;
+//!
+//! let ledger = TestLedger::start().await?;
+//! let secret = knishio_client::generate_secret("alice");
+//! let mut client = ledger.client(&secret);
+//!
+//! client.create_token("DEMO", Some(100.0), None, None, Vec::new()).await?;
+//! assert_eq!(ledger.balance(client.get_bundle().unwrap_or_default(), "DEMO"), 100.0);
+//! # Ok(())
+//! # }
+//! ```
+
+mod state;
+
+pub use state::{LedgerMolecule, LedgerWallet};
+
+use crate::client::KnishIOClient;
+use crate::error::Result;
+use crate::types::SystemTokens;
+use crate::wallet::Wallet;
+use serde_json::Value;
+use state::LedgerState;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// An in-process ledger serving GraphQL on a loopback port
+///
+/// The server stops when the `TestLedger` is dropped.
+pub struct TestLedger {
+    uri: String,
+    state: Arc<Mutex<LedgerState>>,
+    server: JoinHandle<()>,
+}
+
+impl TestLedger {
+    /// Start a ledger on a free loopback port
+    pub async fn start() -> Result<Self> {
+        Self::start_with_tokens(SystemTokens::default()).await
+    }
+
+    /// Start a ledger of a deployment that renamed the USER and AUTH tokens
+    pub async fn start_with_tokens(system_tokens: SystemTokens) -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let uri = format!("http://{}/graphql", listener.local_addr()?);
+        let state = Arc::new(Mutex::new(LedgerState::with_system_tokens(system_tokens)));
+        let server = tokio::spawn(serve(listener, state.clone()));
+
+        Ok(TestLedger { uri, state, server })
+    }
+
+    /// GraphQL endpoint of this ledger
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// A client pointed at this ledger with `secret` already set
+    pub fn client(&self, secret: &str) -> KnishIOClient {
+        let mut client = KnishIOClient::new(self.uri.as_str(), None, None, None, None, Some(false));
+        client.set_secret(secret);
+        client
+    }
+
+    /// Create a funded `token` wallet for `secret` without going through a molecule
+    ///
+    /// Useful for tests that need a balance of a token they did not issue themselves.
+    pub fn fund(&self, secret: &str, token: &str, amount: f64) -> Result<Wallet> {
+        let wallet = Wallet::create(Some(secret), None, token, None, None)?;
+        self.fund_wallet(&wallet, amount);
+        Ok(wallet)
+    }
+
+    /// Credit `amount` to an existing `wallet`, e.g. a fixture at a known position
+    pub fn fund_wallet(&self, wallet: &Wallet, amount: f64) {
+        let record = LedgerWallet {
+            address: wallet.address.clone(),
+            bundle: wallet.bundle.clone().unwrap_or_default(),
+            token: wallet.token.clone(),
+            position: wallet.position.clone(),
+            batch_id: wallet.batch_id.clone(),
+            characters: wallet.characters.clone(),
+            pubkey: wallet.pubkey.clone(),
+            amount: 0.0,
+            created_at: chrono::Utc::now().timestamp_millis().to_string(),
+            buffer: false,
+            trade_rates: Vec::new(),
+        };
+        self.state().credit(record, amount);
+    }
+
+    /// Credit `amount` to a shadow wallet of `bundle`, as a transfer to an unknown recipient would
+    pub fn fund_shadow(&self, bundle: &str, token: &str, batch_id: Option<&str>, amount: f64) {
+        let mut record = LedgerWallet::new("", "", bundle, token);
+        record.batch_id = batch_id.map(str::to_string);
+        self.state().credit(record, amount);
+    }
+
+    /// Total balance of `token` held by `bundle`
+    pub fn balance(&self, bundle: &str, token: &str) -> f64 {
+        self.state().balance(bundle, token)
+    }
+
+    /// Current ContinuID wallet of `bundle`
+    pub fn continu_id(&self, bundle: &str) -> Option<LedgerWallet> {
+        self.state().continu_id(bundle).cloned()
+    }
+
+    /// Every molecule proposed so far, in order, with its outcome
+    pub fn molecules(&self) -> Vec<LedgerMolecule> {
+        self.state().molecules().to_vec()
+    }
+
+    /// Rotate the node's encryption pubkey, returning the new one
+    ///
+    /// Auth exchanges from then on hand out the new key.
+    pub fn rotate_node_key(&self) -> String {
+        self.state().rotate_node_key()
+    }
+
+    fn state(&self) -> MutexGuard<'_, LedgerState> {
+        lock(&self.state)
+    }
+}
+
+impl Drop for TestLedger {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+/// Lock the state, recovering from a poisoned lock (a panicking test must not wedge the others)
+fn lock(state: &Mutex<LedgerState>) -> MutexGuard<'_, LedgerState> {
+    state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+async fn serve(listener: TcpListener, state: Arc<Mutex<LedgerState>>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        tokio::spawn(handle_connection(stream, state.clone()));
+    }
+}
+
+/// Source of the `X-Request-Id` of replies
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Serve HTTP/1.1 POST requests on one keep-alive connection
+async fn handle_connection(stream: TcpStream, state: Arc<Mutex<LedgerState>>) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).await? == 0 {
+            return Ok(());
+        }
+
+        let mut content_length = 0;
+        let mut json_body = true;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).await?;
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap_or(0);
+                }
+                if name.eq_ignore_ascii_case("content-type") {
+                    json_body = value.trim().starts_with("application/json");
+                }
+            }
+        }
+
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).await?;
+
+        // Like a node without binary support, refuse anything but JSON
+        let (status, payload) = if !json_body {
+            ("415 Unsupported Media Type", String::new())
+        } else {
+            let response = match serde_json::from_slice::<Value>(&body) {
+                Ok(request) => lock(&state).handle(&request),
+                Err(e) => serde_json::json!({ "data": null, "errors": [{ "message": format!("Invalid request body: {}", e) }] }),
+            };
+            ("200 OK", response.to_string())
+        };
+        let head = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nX-Request-Id: ledger-{}\r\n\r\n",
+            status,
+            payload.len(),
+            NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed)
+        );
+
+        let stream = reader.get_mut();
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(payload.as_bytes()).await?;
+        stream.flush().await?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{generate_bundle_hash, generate_secret};
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_token_round_trip() {
+        let ledger = TestLedger::start().await.unwrap();
+        let alice_secret = generate_secret("test-ledger-alice");
+        let bob_bundle = generate_bundle_hash(&generate_secret("test-ledger-bob"));
+
+        let mut alice = ledger.client(&alice_secret);
+        let alice_bundle = alice.get_bundle().unwrap().to_string();
+        alice.authenticate(HashMap::new()).await.unwrap();
+        assert!(ledger.continu_id(&alice_bundle).is_some());
+
+        let response = alice.create_token("TLDG", Some(1000.0), None, None, Vec::new()).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        assert_eq!(ledger.balance(&alice_bundle, "TLDG"), 1000.0);
+
+        let response = alice.transfer_token(&bob_bundle, "TLDG", Some(250.0), Vec::new(), None, None).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        assert_eq!(ledger.balance(&alice_bundle, "TLDG"), 750.0);
+        assert_eq!(ledger.balance(&bob_bundle, "TLDG"), 250.0);
+
+        let wallet = alice.query_balance("TLDG", None).await.unwrap();
+        assert_eq!(wallet.balance, "750");
+
+        // Issuing the same slug twice is rejected
+        let response = alice.create_token("TLDG", Some(1.0), None, None, Vec::new()).await.unwrap();
+        assert!(!response.success());
+        assert!(ledger.molecules().iter().all(|m| m.accepted() || m.reason.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_funded_wallet_and_overspend() {
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("test-ledger-funded");
+        let bundle = generate_bundle_hash(&secret);
+        ledger.fund(&secret, "FUND", 10.0).unwrap();
+
+        let mut client = ledger.client(&secret);
+        let other = generate_bundle_hash(&generate_secret("test-ledger-other"));
+
+        assert!(client.transfer_token(&other, "FUND", Some(50.0), Vec::new(), None, None).await.is_err());
+
+        let response = client.transfer_token(&other, "FUND", Some(4.0), Vec::new(), None, None).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        assert_eq!(ledger.balance(&bundle, "FUND"), 6.0);
+        assert_eq!(ledger.balance(&other, "FUND"), 4.0);
+    }
+
+    #[tokio::test]
+    async fn test_versioned_meta_round_trip() {
+        use crate::meta::{MetaSchema, SchemaRegistry};
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Listing {
+            title: String,
+            price: f64,
+        }
+
+        let ledger = TestLedger::start().await.unwrap();
+        let mut client = ledger.client(&generate_secret("test-ledger-meta"));
+
+        // Written before any schema existed: `name` instead of `title`
+        let mut legacy = HashMap::new();
+        legacy.insert("name".to_string(), serde_json::json!("Lamp"));
+        legacy.insert("price".to_string(), serde_json::json!(12.5));
+        let response = client.create_meta("listing", "L1", legacy, None).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+
+        let mut registry = SchemaRegistry::new();
+        registry.register(MetaSchema::new("listing", 2).migration(1, |fields| {
+            if let Some(name) = fields.remove("name") {
+                fields.insert("title".to_string(), name);
+            }
+            Ok(())
+        })).unwrap();
+        client.set_schema_registry(registry);
+
+        let listing: Listing = client.query_meta_as("listing", "L1").await.unwrap().unwrap();
+        assert_eq!(listing, Listing { title: "Lamp".to_string(), price: 12.5 });
+
+        let chair = Listing { title: "Chair".to_string(), price: 40.0 };
+        let response = client.create_meta_as("listing", "L2", &chair, None).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        assert_eq!(client.query_meta_as::<Listing>("listing", "L2").await.unwrap(), Some(chair));
+        assert_eq!(client.query_meta_as::<Listing>("listing", "L3").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_anonymous_meta() {
+        let ledger = TestLedger::start().await.unwrap();
+        let mut client = ledger.client(&generate_secret("test-ledger-anonymous"));
+        client.set_anonymous(true);
+        assert!(client.is_anonymous());
+
+        let mut meta = HashMap::new();
+        meta.insert("label".to_string(), serde_json::json!("unsigned"));
+        let response = client.create_meta("note", "anon-1", meta, None).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+
+        let atoms = client.query_atom(None, None, None, None, None, None, None, Some("note"), Some("anon-1")).await.unwrap();
+        assert!(!atoms.is_empty());
+        assert!(atoms.iter().all(|atom| atom["bundleHashes"].as_array().is_some_and(|b| b.is_empty())));
+
+        // Back to named molecules: the bundle is set again
+        client.set_anonymous(false);
+        let mut meta = HashMap::new();
+        meta.insert("label".to_string(), serde_json::json!("signed"));
+        let response = client.create_meta("note", "named-1", meta, None).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        let atoms = client.query_atom(None, None, None, None, None, None, None, Some("note"), Some("named-1")).await.unwrap();
+        let bundle = client.get_bundle().unwrap().to_string();
+        assert!(atoms.iter().all(|atom| atom["bundleHashes"][0].as_str() == Some(bundle.as_str())));
+    }
+
+    #[tokio::test]
+    async fn test_renamed_system_tokens() {
+        use crate::types::SystemTokens;
+
+        let tokens = SystemTokens::new("MEMBER", "LOGIN");
+        let ledger = TestLedger::start_with_tokens(tokens.clone()).await.unwrap();
+        let mut client = ledger.client(&generate_secret("test-ledger-renamed-tokens"));
+        client.set_system_tokens(tokens);
+
+        client.authenticate(HashMap::new()).await.unwrap();
+        let mut meta = HashMap::new();
+        meta.insert("label".to_string(), serde_json::json!("renamed"));
+        let response = client.create_meta("note", "renamed-1", meta, None).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+
+        let continu_id = client.query_continu_id(None).await.unwrap().unwrap();
+        assert_eq!(continu_id.token, "MEMBER");
+
+        let saved = client.save_auth_token("renamed").unwrap();
+        let restored = client.load_auth_token("renamed", &saved).unwrap();
+        assert_eq!(restored.get_wallet().unwrap().token, "LOGIN");
+    }
+
+    #[tokio::test]
+    async fn test_annotations_reach_the_response() {
+        use crate::types::MetaItem;
+
+        let ledger = TestLedger::start().await.unwrap();
+        let mut client = ledger.client(&generate_secret("test-ledger-annotations"));
+        client.authenticate(HashMap::new()).await.unwrap();
+
+        let mut molecule = client.create_molecule(None, None, None, None).await.unwrap()
+            .with_annotation("request_id", "req-42");
+        molecule.init_meta(vec![MetaItem::new("label", "tagged")], "note", "n-1", None).unwrap();
+        molecule.sign(None, false, true).unwrap();
+
+        let response = client.propose_molecule(molecule).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        let annotations = response.annotations().unwrap();
+        assert_eq!(annotations.get("request_id").map(String::as_str), Some("req-42"));
+    }
+
+    #[tokio::test]
+    async fn test_response_headers_are_kept() {
+        use crate::query::BaseQuery;
+
+        let ledger = TestLedger::start().await.unwrap();
+        let mut client = ledger.client(&generate_secret("test-ledger-headers"));
+        client.authenticate(HashMap::new()).await.unwrap();
+
+        let mut molecule = client.create_molecule(None, None, None, None).await.unwrap();
+        molecule.init_meta(vec![crate::types::MetaItem::new("label", "x")], "note", "n-1", None).unwrap();
+        molecule.sign(None, false, true).unwrap();
+        let proposed = client.propose_molecule(molecule).await.unwrap();
+        let first = proposed.headers().and_then(|headers| headers.request_id()).unwrap().to_string();
+        assert!(first.starts_with("ledger-"));
+
+        let query = BaseQuery::new("query { Token { slug } }");
+        let response = client.execute_query(&query, None).await.unwrap();
+        let headers = response.headers().unwrap();
+        assert_ne!(headers.request_id(), Some(first.as_str()));
+        assert_eq!(headers.get("content-type"), Some("application/json"));
+    }
+
+    #[tokio::test]
+    async fn test_create_molecule_remainder_options() {
+        use crate::client::RemainderOptions;
+        use crate::wallet::Wallet;
+
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("test-ledger-remainder-options");
+        let mut client = ledger.client(&secret);
+        client.authenticate(HashMap::new()).await.unwrap();
+
+        let molecule = client.create_molecule(None, None, None, None).await.unwrap();
+        let user_remainder = molecule.remainder_wallet.unwrap();
+        assert_eq!(user_remainder.token, "USER");
+
+        let mut source = Wallet::create(Some(&secret), None, "FOO", None, None).unwrap();
+        source.batch_id = Some("batch-1".to_string());
+
+        let molecule = client.create_molecule_with(None, None, Some(source.clone()), None, RemainderOptions::source_token()).await.unwrap();
+        let remainder = molecule.remainder_wallet.unwrap();
+        assert_eq!((remainder.token.as_str(), remainder.batch_id.as_deref()), ("FOO", Some("batch-1")));
+
+        let options = RemainderOptions::token("BAR").inherit_batch_id(false);
+        let molecule = client.create_molecule_with(None, None, Some(source), None, options).await.unwrap();
+        let remainder = molecule.remainder_wallet.unwrap();
+        assert_eq!((remainder.token.as_str(), remainder.batch_id.as_deref()), ("BAR", None));
+
+        // Non-USER remainders leave the ContinuID remainder alone
+        assert_eq!(client.get_remainder_wallet().and_then(|w| w.address.clone()), user_remainder.address);
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_is_resigned_and_resubmitted() {
+        use crate::client::DeadLetterCause;
+        use crate::types::MetaItem;
+
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("test-ledger-dead-letter");
+        let bundle = generate_bundle_hash(&secret);
+        let mut client = ledger.client(&secret);
+        client.authenticate(HashMap::new()).await.unwrap();
+
+        // Two molecules signed by the same ContinuID head; only the first can land
+        let mut molecules = Vec::new();
+        for label in ["first", "second"] {
+            let mut molecule = client.create_molecule(None, None, None, None).await.unwrap();
+            molecule.init_meta(vec![MetaItem::new("label", label)], "note", label, None).unwrap();
+            molecule.sign(None, false, true).unwrap();
+            molecules.push(molecule);
+        }
+        let stale = molecules.pop().unwrap();
+        assert!(client.submit_molecule(molecules.pop().unwrap()).await.unwrap().success());
+
+        let response = client.submit_molecule(stale).await.unwrap();
+        assert!(!response.success());
+        let letter = client.dead_letters().list()[0].clone();
+        assert!(matches!(&letter.cause, DeadLetterCause::Rejected { reason } if reason.contains("ContinuID")), "{}", letter);
+        assert_eq!(letter.attempts, 1);
+
+        let response = client.resubmit_dead_letter(&letter.id).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        assert!(client.dead_letters().is_empty());
+
+        let landed = ledger.molecules().last().unwrap().molecular_hash.clone();
+        assert_ne!(Some(landed.as_str()), letter.molecular_hash());
+        let head = ledger.continu_id(&bundle).unwrap();
+        assert_eq!(client.query_continu_id(Some(&bundle)).await.unwrap().and_then(|w| w.address), head.address);
+    }
+
+    #[cfg(feature = "cbor")]
+    #[tokio::test]
+    async fn test_binary_format_falls_back_to_json() {
+        use crate::codec::WireFormat;
+
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("wire-fallback");
+        let mut client = ledger.client(&secret);
+        client.set_wire_format(WireFormat::Cbor);
+        assert_eq!(client.wire_format(), WireFormat::Cbor);
+
+        let response = client.create_token("WIRE", Some(5.0), None, None, Vec::new()).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+        assert_eq!(client.wire_format(), WireFormat::Json);
+        assert_eq!(ledger.balance(&generate_bundle_hash(&secret), "WIRE"), 5.0);
+    }
+
+    #[cfg(feature = "fault-injection")]
+    #[tokio::test]
+    async fn test_injected_faults() {
+        use crate::error::KnishIOError;
+        use crate::graphql::{FaultInjector, RetryPolicy};
+
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("test-ledger-faults");
+        let bundle = generate_bundle_hash(&secret);
+        ledger.fund(&secret, "FAULT", 10.0).unwrap();
+        let mut client = ledger.client(&secret);
+
+        client.set_fault_injector(Some(FaultInjector::new().server_errors(1.0)));
+        let err = client.query_balance("FAULT", Some(&bundle)).await.unwrap_err();
+        assert!(RetryPolicy::default().should_retry(&err), "{}", err);
+
+        client.set_fault_injector(Some(FaultInjector::new().malformed_responses(1.0)));
+        let err = client.query_balance("FAULT", Some(&bundle)).await.unwrap_err();
+        assert!(matches!(err, KnishIOError::Serialization(_)), "{}", err);
+
+        let delay = std::time::Duration::from_millis(50);
+        client.set_fault_injector(Some(FaultInjector::new().timeouts(1.0).timeout_delay(delay)));
+        let started = std::time::Instant::now();
+        let err = client.query_balance("FAULT", Some(&bundle)).await.unwrap_err();
+        assert!(started.elapsed() >= delay);
+        assert!(matches!(err, KnishIOError::Network(_)) && RetryPolicy::default().should_retry(&err), "{}", err);
+
+        client.set_fault_injector(None);
+        assert!(client.query_balance("FAULT", Some(&bundle)).await.is_ok());
+    }
+
+    #[cfg(feature = "vcr")]
+    #[tokio::test]
+    async fn test_cassette_replays_offline() {
+        use crate::error::KnishIOError;
+        use crate::graphql::Cassette;
+
+        let path = std::env::temp_dir().join(format!("knishio-ledger-cassette-{}.json", uuid::Uuid::new_v4()));
+        let secret = generate_secret("test-ledger-cassette");
+        let bundle = generate_bundle_hash(&secret);
+
+        let ledger = TestLedger::start().await.unwrap();
+        let mut client = ledger.client(&secret);
+        client.set_cassette(Some(Cassette::record(&path)));
+        let created = client.create_token("TAPE", Some(7.0), None, None, Vec::new()).await.unwrap();
+        assert!(created.success(), "{:?}", created.reason());
+        let recorded = client.query_balance("TAPE", Some(&bundle)).await.unwrap();
+
+        // A fresh ledger knows nothing of the token; the replay never reaches it
+        let empty = TestLedger::start().await.unwrap();
+        let mut replaying = empty.client(&secret);
+        replaying.set_cassette(Some(Cassette::replay(&path).unwrap()));
+        let created = replaying.create_token("TAPE", Some(7.0), None, None, Vec::new()).await.unwrap();
+        assert!(created.success(), "{:?}", created.reason());
+        let replayed = replaying.query_balance("TAPE", Some(&bundle)).await.unwrap();
+        assert_eq!((replayed.balance.as_str(), replayed.address.as_deref()), (recorded.balance.as_str(), recorded.address.as_deref()));
+        assert!(empty.molecules().is_empty());
+
+        let miss = replaying.query_balance("TAPE", Some(&bundle)).await.unwrap_err();
+        assert!(matches!(miss, KnishIOError::CassetteMismatch(_)), "{}", miss);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_guest_scope_is_refused_locally() {
+        use crate::auth::{AuthScope, AuthToken};
+        use crate::error::KnishIOError;
+
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("test-ledger-scope");
+        let mut client = ledger.client(&secret);
+        let expires = chrono::Utc::now().timestamp() + 3600;
+        client.set_auth_token(AuthToken::new("guest-token".to_string(), Some(expires), None, None).with_scope(AuthScope::Guest));
+
+        let Err(err) = client.create_token("SCOPE", Some(1.0), None, None, Vec::new()).await else {
+            panic!("guest session was allowed to create a token");
+        };
+        assert!(matches!(
+            err,
+            KnishIOError::InsufficientAuthScope { ref operation, required: AuthScope::Profile, actual: AuthScope::Guest }
+                if operation == "create_token"
+        ), "{}", err);
+        assert!(ledger.molecules().is_empty());
+
+        // Logging in replaces the cached guest token with a profile token
+        assert_eq!(client.login(&secret).await.unwrap().scope(), AuthScope::Profile);
+        let response = client.create_token("SCOPE", Some(1.0), None, None, Vec::new()).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+    }
+
+    #[tokio::test]
+    async fn test_manual_token_keeps_writes_open() {
+        use crate::auth::AuthToken;
+
+        let ledger = TestLedger::start().await.unwrap();
+        let secret = generate_secret("test-ledger-manual-token");
+        let mut client = ledger.client(&secret);
+        let expires = chrono::Utc::now().timestamp() + 3600;
+        client.set_auth_token(AuthToken::new("issued-elsewhere".to_string(), Some(expires), None, None));
+
+        let response = client.create_token("MANUAL", Some(1.0), None, None, Vec::new()).await.unwrap();
+        assert!(response.success(), "{:?}", response.reason());
+    }
+
+    #[tokio::test]
+    async fn test_login_keeps_tokens_per_user() {
+        let ledger = TestLedger::start().await.unwrap();
+        let alice_secret = generate_secret("test-ledger-login-alice");
+        let bob_secret = generate_secret("test-ledger-login-bob");
+        let alice_bundle = generate_bundle_hash(&alice_secret);
+
+        let mut client = ledger.client(&alice_secret);
+        let alice_token = client.login(&alice_secret).await.unwrap();
+        let bob_token = client.login(&bob_secret).await.unwrap();
+        assert_ne!(alice_token.token(), bob_token.token());
+        assert_eq!(client.auth_token_store().len(), 2);
+
+        // Switching back reuses alice's stored token instead of re-authenticating
+        let proposals = ledger.molecules().len();
+        assert_eq!(client.login(&alice_secret).await.unwrap().token(), alice_token.token());
+        assert_eq!(ledger.molecules().len(), proposals);
+        assert_eq!(client.get_auth_token_for_uri(ledger.uri()).unwrap().token(), alice_token.token());
+
+        client.logout();
+        assert!(client.get_bundle().is_none());
+        assert!(client.auth_token_store().peek(ledger.uri(), Some(&alice_bundle)).is_none());
+        assert_eq!(client.auth_token_store().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_websocket_manager_follows_token_rotation() {
+        use crate::graphql::{WebSocketManager, WebSocketReconnectConfig};
+
+        let ledger = TestLedger::start().await.unwrap();
+        let alice_secret = generate_secret("test-ledger-socket-alice");
+        let bob_secret = generate_secret("test-ledger-socket-bob");
+
+        let mut client = ledger.client(&alice_secret);
+        let alice_token = client.login(&alice_secret).await.unwrap();
+
+        // Attaching hands over the current token
+        let manager = WebSocketManager::new(
+            "ws://127.0.0.1:9/graphql".to_string(),
+            None,
+            "knishio".to_string(),
+            WebSocketReconnectConfig::default(),
+            false,
+        );
+        client.set_websocket_manager(manager);
+        let socket = client.get_websocket_manager().unwrap().clone();
+        assert_eq!(socket.get_auth_token().as_deref(), Some(alice_token.token()));
+
+        // Every later rotation is pushed to the connection
+        let bob_token = client.login(&bob_secret).await.unwrap();
+        assert_eq!(socket.get_auth_token().as_deref(), Some(bob_token.token()));
+        client.login(&alice_secret).await.unwrap();
+        assert_eq!(socket.get_auth_token().as_deref(), Some(alice_token.token()));
+
+        client.logout();
+        assert!(socket.get_auth_token().is_none());
+
+        // The subscription manager reports the client's connection, and only the current one
+        let stats = client.get_subscription_manager().unwrap().stats().await;
+        assert_eq!(stats.iter().map(|s| s.uri.as_str()).collect::<Vec<_>>(), ["ws://127.0.0.1:9/graphql"]);
+        client.set_websocket_manager(WebSocketManager::new(
+            "ws://127.0.0.1:10/graphql".to_string(),
+            None,
+            "knishio".to_string(),
+            WebSocketReconnectConfig::default(),
+            false,
+        ));
+        let stats = client.get_subscription_manager().unwrap().stats().await;
+        assert_eq!(stats.iter().map(|s| s.uri.as_str()).collect::<Vec<_>>(), ["ws://127.0.0.1:10/graphql"]);
+    }
+
+    #[tokio::test]
+    async fn test_hedged_query_returns_fastest_node() {
+        use crate::graphql::{create_query_request, GraphQLClient, HedgeConfig};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        let ledger = TestLedger::start().await.unwrap();
+        let wallet = ledger.fund(&generate_secret("test-ledger-hedge"), "HEDGE", 5.0).unwrap();
+
+        // A node that accepts connections and never answers
+        let stalled = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stalled_uri = format!("http://{}/graphql", stalled.local_addr().unwrap());
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let counter = accepted.clone();
+        let _server = tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((stream, _)) = stalled.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                held.push(stream);
+            }
+        });
+
+        let balance = || create_query_request(
+            "query( $bundleHash: String, $token: String ) { Balance( bundleHash: $bundleHash, token: $token ) { amount } }",
+            Some(serde_json::json!({ "bundleHash": wallet.bundle, "token": "HEDGE" })),
+        );
+
+        // Slow primary: the hedge node answers and the stalled request is abandoned
+        let mut client = GraphQLClient::new(stalled_uri.clone());
+        client.set_hedge(Some(HedgeConfig { uri: ledger.uri().to_string(), delay: Duration::from_millis(20), auth_token: None }));
+        let response = tokio::time::timeout(Duration::from_secs(5), client.query(balance()))
+            .await
+            .expect("hedged query waited on the stalled node")
+            .unwrap();
+        assert_eq!(response.data.unwrap()["Balance"]["amount"], "5");
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+
+        // Fast primary: the duplicate is never sent
+        let mut client = GraphQLClient::new(ledger.uri().to_string());
+        client.set_hedge(Some(HedgeConfig { uri: stalled_uri.clone(), delay: Duration::from_secs(2), auth_token: None }));
+        client.query(balance()).await.unwrap();
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+
+        // An authenticated client hedges with the hedge node's own token, or not at all
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let mut client = GraphQLClient::new(stalled_uri);
+        client.set_auth_data("primary-token".to_string(), None, None);
+        let record = sent.clone();
+        client.set_request_interceptor(Some(Arc::new(move |uri: &str, headers: &mut std::collections::HashMap<String, String>| {
+            record.lock().unwrap().push((uri.to_string(), headers.get("X-Auth-Token").cloned()));
+        })));
+        client.set_hedge(Some(HedgeConfig { uri: ledger.uri().to_string(), delay: Duration::from_millis(20), auth_token: None }));
+        assert!(tokio::time::timeout(Duration::from_millis(200), client.query(balance())).await.is_err());
+        assert_eq!(sent.lock().unwrap().len(), 1);
+
+        client.set_hedge(Some(HedgeConfig {
+            uri: ledger.uri().to_string(),
+            delay: Duration::from_millis(20),
+            auth_token: Some("hedge-token".to_string()),
+        }));
+        client.query(balance()).await.unwrap();
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent[2], (ledger.uri().to_string(), Some("hedge-token".to_string())));
+        assert_eq!(sent[1].1.as_deref(), Some("primary-token"));
+    }
+}
@@ -0,0 +1,719 @@
+//! In-memory ledger state and GraphQL dispatch for the test ledger
+
+use crate::atom::Atom;
+use crate::molecule::Molecule;
+use crate::types::{AtomFromJsonOptions, Isotope, MetaItem, MoleculeFromJsonOptions, SystemTokens};
+use crate::wallet::Wallet;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// Lifetime of auth tokens issued by the test ledger, in seconds
+const AUTH_TOKEN_LIFETIME: i64 = 3600;
+
+fn now_millis() -> String {
+    chrono::Utc::now().timestamp_millis().to_string()
+}
+
+/// Render an amount the way the node does: integers without a fractional part
+fn format_amount(amount: f64) -> String {
+    if amount.fract() == 0.0 {
+        format!("{}", amount as i128)
+    } else {
+        amount.to_string()
+    }
+}
+
+fn meta_value<'a>(meta: &'a [MetaItem], key: &str) -> Option<&'a str> {
+    meta.iter().find(|m| m.key == key).map(|m| m.value.as_str())
+}
+
+/// A wallet as tracked by the test ledger
+#[derive(Debug, Clone, PartialEq)]
+pub struct LedgerWallet {
+    /// Wallet address (None for shadow wallets)
+    pub address: Option<String>,
+    /// Owning bundle
+    pub bundle: String,
+    /// Token slug
+    pub token: String,
+    /// Wallet position (None for shadow wallets)
+    pub position: Option<String>,
+    /// Batch ID
+    pub batch_id: Option<String>,
+    /// Public key characters
+    pub characters: Option<String>,
+    /// ML-KEM public key
+    pub pubkey: Option<String>,
+    /// Current balance
+    pub amount: f64,
+    /// Creation timestamp (milliseconds)
+    pub created_at: String,
+    /// True for buffer wallets (credited by B atoms)
+    pub buffer: bool,
+    /// Trade rates of a buffer wallet, as token slug and amount
+    pub trade_rates: Vec<(String, f64)>,
+}
+
+impl LedgerWallet {
+    pub(super) fn new(address: &str, position: &str, bundle: &str, token: &str) -> Self {
+        LedgerWallet {
+            address: Some(address.to_string()).filter(|a| !a.is_empty()),
+            bundle: bundle.to_string(),
+            token: token.to_string(),
+            position: Some(position.to_string()).filter(|p| !p.is_empty()),
+            batch_id: None,
+            characters: None,
+            pubkey: None,
+            amount: 0.0,
+            created_at: now_millis(),
+            buffer: false,
+            trade_rates: Vec::new(),
+        }
+    }
+
+    /// Key under which the wallet is stored: its address, or bundle + token for shadow wallets
+    fn key(&self) -> String {
+        match &self.address {
+            Some(address) => address.clone(),
+            None => format!("{}:{}", self.bundle, self.token),
+        }
+    }
+
+    /// Wallet type as reported by the node
+    fn wallet_type(&self) -> &'static str {
+        match (self.buffer, &self.address) {
+            (true, _) => "buffer",
+            (false, Some(_)) => "regular",
+            (false, None) => "shadow",
+        }
+    }
+
+    /// Wallet object in the shape of the `Balance` / `Wallet` / `ContinuId` queries
+    pub fn to_json(&self) -> Value {
+        let trade_rates: Vec<Value> = self.trade_rates.iter()
+            .map(|(slug, amount)| json!({ "tokenSlug": slug, "amount": amount.to_string() }))
+            .collect();
+        json!({
+            "address": self.address,
+            "bundleHash": self.bundle,
+            "type": self.wallet_type(),
+            "tokenSlug": self.token,
+            "batchId": self.batch_id,
+            "position": self.position,
+            "amount": format_amount(self.amount),
+            "characters": self.characters,
+            "pubkey": self.pubkey,
+            "createdAt": self.created_at,
+            "tokenUnits": [],
+            "tradeRates": trade_rates,
+        })
+    }
+}
+
+/// A molecule proposed to the test ledger and its outcome
+#[derive(Debug, Clone, PartialEq)]
+pub struct LedgerMolecule {
+    /// Molecular hash as submitted
+    pub molecular_hash: String,
+    /// "accepted" or "rejected"
+    pub status: String,
+    /// Rejection reason
+    pub reason: Option<String>,
+    /// Cell the molecule was proposed in
+    pub cell_slug: Option<String>,
+}
+
+impl LedgerMolecule {
+    /// True if the ledger accepted the molecule
+    pub fn accepted(&self) -> bool {
+        self.status == "accepted"
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LedgerToken {
+    slug: String,
+    amount: f64,
+    meta: Vec<MetaItem>,
+}
+
+#[derive(Debug, Clone)]
+struct LedgerAtom {
+    atom: Atom,
+    molecular_hash: String,
+    bundle: Option<String>,
+    cell_slug: Option<String>,
+}
+
+/// One `MetaType` instance being assembled for a query response
+struct MetaInstance {
+    meta_id: String,
+    metas: Vec<Value>,
+    created_at: String,
+}
+
+impl MetaInstance {
+    /// Latest value written under `key`
+    fn latest(&self, key: &str) -> Option<&str> {
+        self.metas.iter().rev().find(|m| m["key"] == key).and_then(|m| m["value"].as_str())
+    }
+}
+
+/// Evaluate a `MetaFilter` list (`=` / `!=`, joined by `and` / `or`) on an instance
+fn matches_meta_filters(instance: &MetaInstance, filters: &[Value]) -> bool {
+    filters.iter().enumerate().fold(true, |matched, (index, filter)| {
+        let value = instance.latest(filter["key"].as_str().unwrap_or_default());
+        let expected = filter["value"].as_str();
+        let hit = match filter["comparison"].as_str().unwrap_or("=") {
+            "!=" => value != expected,
+            _ => value == expected,
+        };
+        match filter["criterion"].as_str() {
+            _ if index == 0 => hit,
+            Some("or") => matched || hit,
+            _ => matched && hit,
+        }
+    })
+}
+
+/// Everything the test ledger knows
+#[derive(Debug, Default)]
+pub struct LedgerState {
+    wallets: HashMap<String, LedgerWallet>,
+    /// Insertion order of `wallets`, so the newest wallet wins in `Balance`
+    wallet_order: Vec<String>,
+    continu_ids: HashMap<String, LedgerWallet>,
+    tokens: HashMap<String, LedgerToken>,
+    atoms: Vec<LedgerAtom>,
+    molecules: Vec<LedgerMolecule>,
+    /// Encryption pubkey handed out with auth tokens
+    node_key: Option<String>,
+    /// ContinuID and auth token slugs of this deployment
+    system_tokens: SystemTokens,
+}
+
+impl LedgerState {
+    /// An empty ledger of a deployment using `system_tokens`
+    pub fn with_system_tokens(system_tokens: SystemTokens) -> Self {
+        LedgerState { system_tokens, ..LedgerState::default() }
+    }
+
+    /// Answer one GraphQL request (`{ query, variables }`)
+    pub fn handle(&mut self, request: &Value) -> Value {
+        let query = request.get("query").and_then(|q| q.as_str()).unwrap_or_default();
+        let empty = json!({});
+        let variables = request.get("variables").filter(|v| v.is_object()).unwrap_or(&empty);
+
+        let Some(field) = root_field(query) else {
+            return graphql_error("Could not determine the root field of the request");
+        };
+
+        let data = match field {
+            "ProposeMolecule" => self.propose(&variables["molecule"]),
+            "ContinuId" => self.query_continu_id(variables),
+            "Balance" => self.query_balance(variables),
+            "Wallet" => self.query_wallets(variables),
+            "Token" => self.query_tokens(variables),
+            "MetaType" => self.query_meta_types(variables),
+            "Atom" => self.query_atoms(variables),
+            other => return graphql_error(&format!("Test ledger does not implement {}", other)),
+        };
+
+        json!({ "data": { field: data } })
+    }
+
+    /// Current balance of a bundle's token wallets, buffers excluded
+    pub fn balance(&self, bundle: &str, token: &str) -> f64 {
+        self.wallets.values()
+            .filter(|w| w.bundle == bundle && w.token == token && !w.buffer)
+            .map(|w| w.amount)
+            .sum()
+    }
+
+    /// Current ContinuID head of a bundle
+    pub fn continu_id(&self, bundle: &str) -> Option<&LedgerWallet> {
+        self.continu_ids.get(bundle)
+    }
+
+    /// Every molecule proposed so far, in order
+    pub fn molecules(&self) -> &[LedgerMolecule] {
+        &self.molecules
+    }
+
+    /// Replace the encryption pubkey handed out with auth tokens
+    pub fn rotate_node_key(&mut self) -> String {
+        let key = format!("test-ledger-key-{}", uuid::Uuid::new_v4().simple());
+        self.node_key = Some(key.clone());
+        key
+    }
+
+    /// Credit `amount` to a wallet, creating it if needed
+    pub fn credit(&mut self, mut wallet: LedgerWallet, amount: f64) {
+        let key = wallet.key();
+        match self.wallets.get_mut(&key) {
+            Some(existing) => existing.amount += amount,
+            None => {
+                wallet.amount = amount;
+                self.wallet_order.push(key.clone());
+                self.wallets.insert(key, wallet);
+            }
+        }
+    }
+
+    fn propose(&mut self, molecule: &Value) -> Value {
+        let received_at = now_millis();
+        let molecular_hash = molecule.get("molecularHash")
+            .and_then(|h| h.as_str())
+            .unwrap_or_default()
+            .to_string();
+
+        let (status, reason, payload) = match self.accept(molecule, &molecular_hash) {
+            Ok(payload) => ("accepted", None, payload),
+            Err(reason) => ("rejected", Some(reason), None),
+        };
+
+        let processed_at = now_millis();
+        self.molecules.push(LedgerMolecule {
+            molecular_hash: molecular_hash.clone(),
+            status: status.to_string(),
+            reason: reason.clone(),
+            cell_slug: molecule.get("cellSlug").and_then(|c| c.as_str()).map(str::to_string),
+        });
+
+        json!({
+            "molecularHash": molecular_hash,
+            "height": self.molecules.len(),
+            "depth": 0,
+            "status": status,
+            "reason": reason,
+            "payload": payload.map(|p| p.to_string()),
+            "createdAt": processed_at,
+            "receivedAt": received_at,
+            "processedAt": processed_at,
+            "broadcastedAt": null,
+        })
+    }
+
+    /// Validate a molecule against the ledger and apply it; returns the payload or a rejection reason
+    fn accept(&mut self, json: &Value, molecular_hash: &str) -> std::result::Result<Option<Value>, String> {
+        let mut molecule = parse_molecule(json)?;
+        molecule.system_tokens = self.system_tokens.clone();
+        let first = molecule.atoms.first().ok_or("Molecule has no atoms")?;
+
+        if self.molecules.iter().any(|m| m.accepted() && m.molecular_hash == molecular_hash) {
+            return Err("Molecule has already been processed".to_string());
+        }
+
+        // UTXO: a V or B debit must drain a wallet the ledger knows about
+        let sender = if matches!(first.isotope, Isotope::V | Isotope::B) {
+            let wallet = self.wallets.get(&first.wallet_address)
+                .ok_or("Source wallet is not known to the ledger")?;
+            Some(Wallet::from_response_data(wallet.to_json()).map_err(|e| e.to_string())?)
+        } else {
+            None
+        };
+
+        // ContinuID relay: USER molecules must be signed by the bundle's current head
+        if first.token == self.system_tokens.user {
+            let head = molecule.bundle.as_deref().and_then(|bundle| self.continu_ids.get(bundle));
+            if let Some(head) = head {
+                if head.address.as_deref() != Some(first.wallet_address.as_str()) {
+                    return Err("ContinuID mismatch: molecule is not signed by the current ContinuID wallet".to_string());
+                }
+            }
+        }
+
+        molecule.check(sender.as_ref()).map_err(|e| e.to_string())?;
+
+        for atom in molecule.get_isotopes(&[Isotope::C]) {
+            if atom.meta_type.as_deref() == Some("token") {
+                let slug = atom.meta_id.clone().unwrap_or_default();
+                if self.tokens.contains_key(&slug) {
+                    return Err(format!("Token {} already exists", slug));
+                }
+            }
+        }
+
+        let mut payload = None;
+        for atom in &molecule.atoms {
+            let value: f64 = atom.value.as_deref().and_then(|v| v.parse().ok()).unwrap_or(0.0);
+
+            match atom.isotope {
+                Isotope::V | Isotope::B if value < 0.0 => {
+                    if let Some(wallet) = self.wallets.get_mut(&atom.wallet_address) {
+                        wallet.amount += value;
+                    }
+                }
+                Isotope::V => {
+                    let bundle = atom.meta_id.clone().unwrap_or_default();
+                    let mut wallet = LedgerWallet::new(&atom.wallet_address, &atom.position, &bundle, &atom.token);
+                    wallet.batch_id = atom.batch_id.clone();
+                    wallet.pubkey = meta_value(&atom.meta, "pubkey").map(str::to_string);
+                    wallet.characters = meta_value(&atom.meta, "characters").map(str::to_string);
+                    self.credit(wallet, value);
+                }
+                Isotope::B => {
+                    let bundle = atom.meta_id.clone().unwrap_or_default();
+                    let mut wallet = LedgerWallet::new(&atom.wallet_address, &atom.position, &bundle, &atom.token);
+                    wallet.batch_id = atom.batch_id.clone();
+                    wallet.buffer = true;
+                    wallet.trade_rates = meta_value(&atom.meta, "tradeRates")
+                        .and_then(|rates| serde_json::from_str::<BTreeMap<String, f64>>(rates).ok())
+                        .map(|rates| rates.into_iter().collect())
+                        .unwrap_or_default();
+                    self.credit(wallet, value);
+                }
+                Isotope::C if matches!(atom.meta_type.as_deref(), Some("token") | Some("wallet")) => {
+                    let token = meta_value(&atom.meta, "walletTokenSlug").unwrap_or(&atom.token);
+                    let mut wallet = LedgerWallet::new(
+                        meta_value(&atom.meta, "walletAddress").unwrap_or_default(),
+                        meta_value(&atom.meta, "walletPosition").unwrap_or_default(),
+                        meta_value(&atom.meta, "walletBundleHash").or(molecule.bundle.as_deref()).unwrap_or_default(),
+                        token,
+                    );
+                    wallet.batch_id = meta_value(&atom.meta, "walletBatchId").map(str::to_string);
+                    wallet.pubkey = meta_value(&atom.meta, "walletPubkey").map(str::to_string);
+                    wallet.characters = meta_value(&atom.meta, "walletCharacters").map(str::to_string);
+
+                    // A claim promotes the bundle's shadow wallet of that batch into the new wallet
+                    let mut value = value;
+                    if meta_value(&atom.meta, "shadowWalletClaim").is_some() {
+                        let shadow_key = format!("{}:{}", wallet.bundle, wallet.token);
+                        if self.wallets.get(&shadow_key).is_some_and(|shadow| shadow.batch_id == wallet.batch_id) {
+                            if let Some(shadow) = self.wallets.remove(&shadow_key) {
+                                self.wallet_order.retain(|key| key != &shadow_key);
+                                value += shadow.amount;
+                            }
+                        }
+                    }
+
+                    if atom.meta_type.as_deref() == Some("token") {
+                        let slug = atom.meta_id.clone().unwrap_or_default();
+                        self.tokens.insert(slug.clone(), LedgerToken { slug, amount: value, meta: atom.meta.clone() });
+                    }
+                    self.credit(wallet, value);
+                }
+                Isotope::I => {
+                    let bundle = atom.meta_id.clone().or_else(|| molecule.bundle.clone()).unwrap_or_default();
+                    let mut wallet = LedgerWallet::new(&atom.wallet_address, &atom.position, &bundle, &atom.token);
+                    wallet.pubkey = meta_value(&atom.meta, "pubkey").map(str::to_string);
+                    wallet.characters = meta_value(&atom.meta, "characters").map(str::to_string);
+                    self.continu_ids.insert(bundle, wallet);
+                }
+                Isotope::U => {
+                    payload = Some(json!({
+                        "token": format!("test-ledger-{}", uuid::Uuid::new_v4().simple()),
+                        "expiresAt": chrono::Utc::now().timestamp() + AUTH_TOKEN_LIFETIME,
+                        "pubkey": self.node_key,
+                        "encrypt": false,
+                    }));
+                }
+                _ => {}
+            }
+        }
+
+        self.atoms.extend(molecule.atoms.iter().map(|atom| LedgerAtom {
+            atom: atom.clone(),
+            molecular_hash: molecular_hash.to_string(),
+            bundle: molecule.bundle.clone(),
+            cell_slug: molecule.cell_slug.clone(),
+        }));
+
+        Ok(payload)
+    }
+
+    fn query_continu_id(&self, variables: &Value) -> Value {
+        let token = variables.get("token").and_then(|t| t.as_str());
+        variables.get("bundle")
+            .and_then(|b| b.as_str())
+            .and_then(|bundle| self.continu_ids.get(bundle))
+            .filter(|wallet| token.is_none_or(|t| wallet.token == t))
+            .map_or(Value::Null, LedgerWallet::to_json)
+    }
+
+    /// Wallets matching the `bundleHash` / `token(Slug)` / `address` / `position` / `type`
+    /// variables, oldest first; buffer wallets only match an explicit `type: "buffer"`
+    fn matching_wallets(&self, variables: &Value) -> Vec<&LedgerWallet> {
+        let var = |name: &str| variables.get(name).and_then(|v| v.as_str());
+        let wallet_type = var("type");
+        let bundle = var("bundleHash");
+        let token = var("token").or_else(|| var("tokenSlug"));
+        let address = var("address");
+        let position = var("position");
+
+        self.wallet_order.iter()
+            .filter_map(|key| self.wallets.get(key))
+            .filter(|w| bundle.is_none_or(|b| w.bundle == b))
+            .filter(|w| token.is_none_or(|t| w.token == t))
+            .filter(|w| address.is_none_or(|a| w.address.as_deref() == Some(a)))
+            .filter(|w| position.is_none_or(|p| w.position.as_deref() == Some(p)))
+            .filter(|w| wallet_type.map_or(!w.buffer, |t| w.wallet_type() == t))
+            .collect()
+    }
+
+    fn query_balance(&self, variables: &Value) -> Value {
+        // The newest funded wallet is the spendable one; spent UTXO wallets stay at zero
+        self.matching_wallets(variables)
+            .into_iter()
+            .rev()
+            .find(|w| w.amount > 0.0)
+            .map_or(Value::Null, LedgerWallet::to_json)
+    }
+
+    fn query_wallets(&self, variables: &Value) -> Value {
+        let wallets = self.matching_wallets(variables)
+            .into_iter()
+            .filter(|w| w.amount > 0.0)
+            .map(|w| {
+                let mut json = w.to_json();
+                json["token"] = self.tokens.get(&w.token).map_or(Value::Null, |t| self.token_json(t));
+                json
+            })
+            .collect();
+
+        Value::Array(wallets)
+    }
+
+    fn token_json(&self, token: &LedgerToken) -> Value {
+        let meta = |key: &str| meta_value(&token.meta, key);
+        json!({
+            "slug": token.slug,
+            "name": meta("name"),
+            "fungibility": meta("fungibility"),
+            "supply": meta("supply"),
+            "decimals": meta("decimals"),
+            "amount": format_amount(token.amount),
+            "icon": meta("icon"),
+        })
+    }
+
+    fn query_tokens(&self, variables: &Value) -> Value {
+        let slugs = string_list(variables, "slugs", "slug");
+        let mut tokens: Vec<&LedgerToken> = self.tokens.values()
+            .filter(|t| slugs.is_empty() || slugs.contains(&t.slug.as_str()))
+            .collect();
+        tokens.sort_by(|a, b| a.slug.cmp(&b.slug));
+
+        Value::Array(tokens.into_iter().map(|t| self.token_json(t)).collect())
+    }
+
+    fn query_meta_types(&self, variables: &Value) -> Value {
+        let meta_types = string_list(variables, "metaTypes", "metaType");
+        let meta_ids = string_list(variables, "metaIds", "metaId");
+        let latest = variables.get("latest").and_then(|l| l.as_bool()).unwrap_or(false);
+
+        // metaType -> instances, in first-seen order
+        let mut grouped: Vec<(String, Vec<MetaInstance>)> = Vec::new();
+        for entry in self.atoms.iter().filter(|a| a.atom.isotope == Isotope::M) {
+            let meta_type = entry.atom.meta_type.clone().unwrap_or_default();
+            let meta_id = entry.atom.meta_id.clone().unwrap_or_default();
+            if (!meta_types.is_empty() && !meta_types.contains(&meta_type.as_str()))
+                || (!meta_ids.is_empty() && !meta_ids.contains(&meta_id.as_str())) {
+                continue;
+            }
+
+            let index = match grouped.iter().position(|(t, _)| *t == meta_type) {
+                Some(index) => index,
+                None => {
+                    grouped.push((meta_type, Vec::new()));
+                    grouped.len() - 1
+                }
+            };
+            let instances = &mut grouped[index].1;
+            let index = match instances.iter().position(|i| i.meta_id == meta_id) {
+                Some(index) => index,
+                None => {
+                    instances.push(MetaInstance { meta_id, metas: Vec::new(), created_at: entry.atom.created_at.clone() });
+                    instances.len() - 1
+                }
+            };
+
+            let metas = &mut instances[index].metas;
+            for item in &entry.atom.meta {
+                if latest {
+                    metas.retain(|m| m["key"] != item.key.as_str());
+                }
+                metas.push(json!({
+                    "molecularHash": entry.molecular_hash,
+                    "position": entry.atom.position,
+                    "key": item.key,
+                    "value": item.value,
+                    "createdAt": entry.atom.created_at,
+                }));
+            }
+        }
+
+        let filters = variables.get("filter").and_then(|f| f.as_array()).cloned().unwrap_or_default();
+        let count_by = variables.get("countBy").and_then(|c| c.as_str());
+
+        Value::Array(grouped.into_iter().map(|(meta_type, mut instances)| {
+            instances.retain(|instance| matches_meta_filters(instance, &filters));
+            let total = instances.len();
+
+            // countBy: instance counts per latest value of the key, in first-seen order
+            let mut counts: Vec<(String, usize)> = Vec::new();
+            for value in instances.iter().filter_map(|i| count_by.and_then(|key| i.latest(key))) {
+                match counts.iter_mut().find(|(v, _)| v == value) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((value.to_string(), 1)),
+                }
+            }
+
+            json!({
+                "metaType": meta_type,
+                "instanceCount": counts.into_iter()
+                    .map(|(key, value)| json!({ "key": key, "value": value }))
+                    .collect::<Vec<_>>(),
+                "instances": instances.into_iter().map(|instance| json!({
+                    "metaType": meta_type,
+                    "metaId": instance.meta_id,
+                    "createdAt": instance.created_at,
+                    "metas": instance.metas,
+                })).collect::<Vec<_>>(),
+                "paginatorInfo": { "currentPage": 1, "total": total },
+            })
+        }).collect())
+    }
+
+    fn query_atoms(&self, variables: &Value) -> Value {
+        let filter = |name: &str| string_list(variables, name, "");
+        let molecular_hashes = filter("molecularHashes");
+        let bundle_hashes = filter("bundleHashes");
+        let positions = filter("positions");
+        let wallet_addresses = filter("walletAddresses");
+        let isotopes = filter("isotopes");
+        let token_slugs = filter("tokenSlugs");
+        let cell_slugs = filter("cellSlugs");
+        let batch_ids = filter("batchIds");
+        let meta_types = filter("metaTypes");
+        let meta_ids = filter("metaIds");
+
+        let matches = |list: &[&str], value: Option<&str>| {
+            list.is_empty() || value.is_some_and(|v| list.contains(&v))
+        };
+
+        let instances: Vec<Value> = self.atoms.iter()
+            .filter(|e| matches(&molecular_hashes, Some(&e.molecular_hash)))
+            .filter(|e| matches(&bundle_hashes, e.bundle.as_deref()))
+            .filter(|e| matches(&positions, Some(&e.atom.position)))
+            .filter(|e| matches(&wallet_addresses, Some(&e.atom.wallet_address)))
+            .filter(|e| matches(&isotopes, Some(e.atom.isotope.as_str())))
+            .filter(|e| matches(&token_slugs, Some(&e.atom.token)))
+            .filter(|e| matches(&cell_slugs, e.cell_slug.as_deref()))
+            .filter(|e| matches(&batch_ids, e.atom.batch_id.as_deref()))
+            .filter(|e| matches(&meta_types, e.atom.meta_type.as_deref()))
+            .filter(|e| matches(&meta_ids, e.atom.meta_id.as_deref()))
+            .map(|e| json!({
+                "position": e.atom.position,
+                "walletAddress": e.atom.wallet_address,
+                "tokenSlug": e.atom.token,
+                "isotope": e.atom.isotope.as_str(),
+                "index": e.atom.index,
+                "molecularHash": e.molecular_hash,
+                "metaId": e.atom.meta_id,
+                "metaType": e.atom.meta_type,
+                "metasJson": serde_json::to_string(&e.atom.meta).unwrap_or_default(),
+                "batchId": e.atom.batch_id,
+                "value": e.atom.value,
+                "bundleHashes": e.bundle.iter().collect::<Vec<_>>(),
+                "cellSlugs": e.cell_slug.iter().collect::<Vec<_>>(),
+                "createdAt": e.atom.created_at,
+                "otsFragment": e.atom.ots_fragment,
+            }))
+            .collect();
+
+        let total = instances.len();
+        let offset = variables["queryArgs"]["offset"].as_u64().map_or(0, |offset| offset as usize);
+        let limit = variables["queryArgs"]["limit"].as_str()
+            .and_then(|limit| limit.parse::<usize>().ok())
+            .unwrap_or(total);
+        let instances: Vec<Value> = instances.into_iter().skip(offset).take(limit).collect();
+        let page = offset.checked_div(limit).map_or(1, |page| page + 1);
+        json!({ "instances": instances, "paginatorInfo": { "currentPage": page, "total": total } })
+    }
+}
+
+/// Rebuild a proposed molecule
+///
+/// `Molecule::from_json` insists on a position and address for every atom, but transfers
+/// to a shadow wallet legitimately carry neither, so atoms are read without that check.
+fn parse_molecule(json: &Value) -> std::result::Result<Molecule, String> {
+    let mut header = json.clone();
+    let atoms = header.get_mut("atoms").map(Value::take).unwrap_or_default();
+    header["atoms"] = json!([{ "position": "-", "walletAddress": "-", "isotope": "V", "token": "-" }]);
+
+    let mut molecule = Molecule::from_json(&header, MoleculeFromJsonOptions::default())
+        .map_err(|e| e.to_string())?;
+    molecule.atoms = atoms.as_array()
+        .ok_or("Molecule has no atoms")?
+        .iter()
+        .map(|atom| Atom::from_json(atom, AtomFromJsonOptions { validate_structure: false, strict_mode: false }))
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(molecule)
+}
+
+/// Name of the first field selected by a GraphQL document
+fn root_field(query: &str) -> Option<&str> {
+    let body = query[query.find('{')? + 1..].trim_start();
+    let end = body.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(body.len());
+    Some(&body[..end]).filter(|field| !field.is_empty())
+}
+
+/// Values of a list variable, falling back to a single-value variable
+fn string_list<'a>(variables: &'a Value, list: &str, single: &str) -> Vec<&'a str> {
+    match variables.get(list).and_then(|v| v.as_array()) {
+        Some(items) => items.iter().filter_map(|v| v.as_str()).collect(),
+        None => variables.get(single).and_then(|v| v.as_str()).into_iter().collect(),
+    }
+}
+
+fn graphql_error(message: &str) -> Value {
+    json!({ "data": null, "errors": [{ "message": message }] })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_field() {
+        assert_eq!(root_field("query ($bundle: String!) {\n  ContinuId(bundle: $bundle) { address }\n}"), Some("ContinuId"));
+        assert_eq!(root_field("mutation( $molecule: MoleculeInput! ) { ProposeMolecule( molecule: $molecule ) { status } }"), Some("ProposeMolecule"));
+        assert_eq!(root_field("query { __schema { queryType { name } } }"), Some("__schema"));
+        assert_eq!(root_field("no selection"), None);
+    }
+
+    #[test]
+    fn test_balance_and_unsupported_fields() {
+        let mut state = LedgerState::default();
+        let mut wallet = LedgerWallet::new("addr-1", "pos-1", "bundle-1", "TEST");
+        wallet.batch_id = Some("batch".to_string());
+        state.credit(wallet, 100.0);
+
+        let response = state.handle(&json!({
+            "query": "query( $bundleHash: String, $token: String ) { Balance( bundleHash: $bundleHash, token: $token ) { amount } }",
+            "variables": { "bundleHash": "bundle-1", "token": "TEST" },
+        }));
+        assert_eq!(response["data"]["Balance"]["amount"], "100");
+        assert_eq!(response["data"]["Balance"]["address"], "addr-1");
+        assert_eq!(state.balance("bundle-1", "TEST"), 100.0);
+
+        let response = state.handle(&json!({ "query": "mutation { AccessToken { token } }" }));
+        assert!(response["errors"][0]["message"].as_str().unwrap().contains("AccessToken"));
+    }
+
+    #[test]
+    fn test_rejects_malformed_molecules() {
+        let mut state = LedgerState::default();
+        let response = state.handle(&json!({
+            "query": "mutation( $molecule: MoleculeInput! ) { ProposeMolecule( molecule: $molecule ) { status } }",
+            "variables": { "molecule": { "molecularHash": "abc", "atoms": [] } },
+        }));
+
+        assert_eq!(response["data"]["ProposeMolecule"]["status"], "rejected");
+        assert_eq!(state.molecules().len(), 1);
+        assert!(!state.molecules()[0].accepted());
+    }
+}
@@ -0,0 +1,184 @@
+//! Fixtures for tests of code built on the SDK
+//!
+//! Enabled with the `testkit` feature. Wallets and molecules are derived from a seed
+//! string: the same seed always gives the same secret, bundle and wallet positions, so
+//! tests can assert on addresses and bundles without spelling out key derivation.
+//!
+//! ```rust
+//! use knishio_client::testkit::{FixtureMolecule, FixtureWallet};
+//!
+//! # fn main() -> knishio_client::Result<()> {
+//! let alice = FixtureWallet::funded("GOLD", 100.0)?;
+//! let bob = FixtureWallet::for_seed("bob", "GOLD")?;
+//!
+//! let molecule = FixtureMolecule::transfer(&alice, &bob.wallet, 40.0)?;
+//! assert!(molecule.check(Some(&alice.wallet))?);
+//! assert_eq!(FixtureWallet::funded("GOLD", 100.0)?.wallet.address, alice.wallet.address);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::crypto::{generate_bundle_hash, generate_secret, shake256};
+use crate::error::Result;
+use crate::molecule::{Molecule, MoleculeParams};
+use crate::types::MetaItem;
+use crate::wallet::Wallet;
+
+/// Seed of the wallets built without one
+pub const DEFAULT_SEED: &str = "knishio-testkit";
+
+/// Secret derived from `seed`
+pub fn secret(seed: &str) -> String {
+    generate_secret(seed)
+}
+
+/// Bundle hash of the secret derived from `seed`
+pub fn bundle(seed: &str) -> String {
+    generate_bundle_hash(&secret(seed))
+}
+
+/// Wallet position `index` of `token` for `seed`, 64 hex characters
+pub fn position(seed: &str, token: &str, index: u32) -> String {
+    shake256(&format!("knishio-testkit:{}:{}:{}", seed, token, index), 64)
+}
+
+/// A wallet with the secret it was derived from
+#[derive(Debug, Clone)]
+pub struct FixtureWallet {
+    /// Seed the secret was derived from
+    pub seed: String,
+    /// Secret owning the wallet
+    pub secret: String,
+    /// The wallet, at position `index`
+    pub wallet: Wallet,
+    /// Position index; the remainder of a transfer takes the next one
+    pub index: u32,
+}
+
+impl FixtureWallet {
+    /// Wallet of the default seed holding `balance` of `token`
+    pub fn funded(token: &str, balance: f64) -> Result<Self> {
+        Self::funded_for(DEFAULT_SEED, token, balance)
+    }
+
+    /// Wallet of `seed` holding `balance` of `token`
+    pub fn funded_for(seed: &str, token: &str, balance: f64) -> Result<Self> {
+        let mut fixture = Self::at(seed, token, 0)?;
+        fixture.wallet.set_balance_f64(balance);
+        Ok(fixture)
+    }
+
+    /// Empty wallet of `seed`, e.g. a transfer recipient
+    pub fn for_seed(seed: &str, token: &str) -> Result<Self> {
+        Self::at(seed, token, 0)
+    }
+
+    /// Empty wallet of `seed` at position `index`
+    pub fn at(seed: &str, token: &str, index: u32) -> Result<Self> {
+        let secret = secret(seed);
+        let wallet = Wallet::create(Some(&secret), None, token, Some(&position(seed, token, index)), None)?;
+        Ok(FixtureWallet { seed: seed.to_string(), secret, wallet, index })
+    }
+
+    /// Bundle hash of the owner
+    pub fn bundle(&self) -> String {
+        generate_bundle_hash(&self.secret)
+    }
+
+    /// The wallet that takes this one's change: same owner and token, next position
+    pub fn remainder(&self) -> Result<Wallet> {
+        let mut remainder = Self::at(&self.seed, &self.wallet.token, self.index + 1)?.wallet;
+        remainder.init_batch_id(Some(&self.wallet), true);
+        Ok(remainder)
+    }
+
+    /// The owner's USER wallet at this wallet's position index
+    pub fn user(&self) -> Result<Wallet> {
+        Ok(Self::at(&self.seed, "USER", self.index)?.wallet)
+    }
+
+    /// Unsigned molecule of this wallet's owner spending `source`
+    pub(crate) fn molecule(&self, source: Wallet, remainder: Wallet) -> Molecule {
+        Molecule::from_params(
+            MoleculeParams::new()
+                .secret(&self.secret)
+                .bundle(self.bundle())
+                .source_wallet(source)
+                .remainder_wallet(remainder),
+        )
+    }
+}
+
+/// Signed molecules built from fixture wallets
+pub struct FixtureMolecule;
+
+impl FixtureMolecule {
+    /// `from` sends `amount` to `to`; the change goes to `from.remainder()`
+    ///
+    /// # Errors
+    ///
+    /// Whatever `init_value` or signing reports, e.g. `BalanceInsufficient`
+    pub fn transfer(from: &FixtureWallet, to: &Wallet, amount: f64) -> Result<Molecule> {
+        let mut molecule = from.molecule(from.wallet.clone(), from.remainder()?);
+        molecule.init_value(to, amount)?;
+        molecule.sign(None, false, true)?;
+        Ok(molecule)
+    }
+
+    /// `owner` writes `meta` to the `meta_type` instance `meta_id`
+    ///
+    /// The M atom is on the owner's USER wallet and the ContinuID atom moves to the next
+    /// USER position.
+    pub fn meta(owner: &FixtureWallet, meta_type: &str, meta_id: &str, meta: Vec<MetaItem>) -> Result<Molecule> {
+        let user = owner.user()?;
+        let next = FixtureWallet::at(&owner.seed, "USER", owner.index + 1)?.wallet;
+        let mut molecule = owner.molecule(user, next);
+        molecule.init_meta(meta, meta_type, meta_id, None)?;
+        molecule.sign(None, false, true)?;
+        Ok(molecule)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::KnishIOError;
+    use crate::types::Isotope;
+
+    #[test]
+    fn test_fixtures_are_deterministic() {
+        let alice = FixtureWallet::funded("GOLD", 100.0).unwrap();
+        let again = FixtureWallet::funded("GOLD", 100.0).unwrap();
+        assert_eq!(alice.wallet.address, again.wallet.address);
+        assert_eq!(alice.wallet.position.as_deref(), Some(position(DEFAULT_SEED, "GOLD", 0).as_str()));
+        assert_eq!(alice.bundle(), bundle(DEFAULT_SEED));
+        assert_eq!(alice.wallet.whole_balance().unwrap(), 100);
+        assert_ne!(alice.remainder().unwrap().address, alice.wallet.address);
+
+        let bob = FixtureWallet::for_seed("bob", "GOLD").unwrap();
+        assert_ne!(bob.bundle(), alice.bundle());
+        assert_eq!(bob.wallet.whole_balance().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_fixture_molecules() {
+        let alice = FixtureWallet::funded("GOLD", 100.0).unwrap();
+        let bob = FixtureWallet::for_seed("bob", "GOLD").unwrap();
+
+        let molecule = FixtureMolecule::transfer(&alice, &bob.wallet, 40.0).unwrap();
+        assert!(molecule.check(Some(&alice.wallet)).unwrap());
+        let values: Vec<_> = molecule.atoms.iter().map(|atom| atom.value.clone().unwrap_or_default()).collect();
+        assert_eq!(values, vec!["-100", "40", "60"]);
+        assert_eq!(molecule.atoms[2].wallet_address, alice.remainder().unwrap().address.unwrap());
+
+        assert!(matches!(
+            FixtureMolecule::transfer(&alice, &bob.wallet, 500.0),
+            Err(KnishIOError::BalanceInsufficient)
+        ));
+
+        let molecule = FixtureMolecule::meta(&alice, "profile", "alice", vec![MetaItem::new("name", "Alice")]).unwrap();
+        assert!(molecule.check(None).unwrap());
+        let isotopes: Vec<_> = molecule.atoms.iter().map(|atom| atom.isotope).collect();
+        assert_eq!(isotopes, vec![Isotope::M, Isotope::I]);
+    }
+}
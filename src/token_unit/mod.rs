@@ -7,6 +7,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use crate::error::{KnishIOError, Result};
+use crate::wallet::Wallet;
 
 /// Represents a token unit with its metadata
 ///
@@ -410,6 +411,162 @@ impl TokenUnit {
 }
 
 
+/// Strategy for picking token units when a stackable transfer specifies only an amount
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnitSelection {
+    /// Take units in the order the ledger returned them
+    #[default]
+    First,
+    /// Take units from the end of the ledger order
+    Last,
+    /// Take a random sample of units
+    Random,
+}
+
+impl UnitSelection {
+    /// Select `count` unit IDs from `units` according to this strategy
+    ///
+    /// # Errors
+    ///
+    /// Returns `StackableUnitAmount` if fewer than `count` units are available
+    pub fn select(&self, units: &[TokenUnit], count: usize) -> Result<Vec<String>> {
+        if count > units.len() {
+            return Err(KnishIOError::StackableUnitAmount);
+        }
+
+        let selected = match self {
+            UnitSelection::First => units.iter().take(count).map(|u| u.id.clone()).collect(),
+            UnitSelection::Last => units[units.len() - count..].iter().map(|u| u.id.clone()).collect(),
+            UnitSelection::Random => {
+                let mut indices = rand::seq::index::sample(&mut rand::rng(), units.len(), count).into_vec();
+                indices.sort_unstable();
+                indices.into_iter().map(|i| units[i].id.clone()).collect()
+            }
+        };
+
+        Ok(selected)
+    }
+}
+
+/// A token unit as held by a particular wallet
+///
+/// Returned by `KnishIOClient::query_token_units`, which flattens the units of every
+/// wallet a bundle holds for a token, and by `KnishIOClient::find_token_units`, which
+/// searches every holder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeldTokenUnit {
+    /// The unit itself (id, name and metadata)
+    pub unit: TokenUnit,
+    /// Bundle owning the wallet
+    pub bundle_hash: Option<String>,
+    /// Address of the wallet holding the unit
+    pub wallet_address: Option<String>,
+    /// Position of the wallet holding the unit
+    pub wallet_position: Option<String>,
+    /// Batch ID of the wallet holding the unit
+    pub batch_id: Option<String>,
+}
+
+impl HeldTokenUnit {
+    /// Collect the units of `wallets` that pass `filter`, in wallet order
+    pub fn collect(wallets: &[Wallet], filter: &TokenUnitFilter) -> Vec<Self> {
+        wallets.iter()
+            .flat_map(|wallet| wallet.token_units.iter()
+                .filter(|unit| filter.matches(unit))
+                .map(move |unit| HeldTokenUnit {
+                    unit: unit.clone(),
+                    bundle_hash: wallet.bundle.clone(),
+                    wallet_address: wallet.address.clone(),
+                    wallet_position: wallet.position.clone(),
+                    batch_id: wallet.batch_id.clone(),
+                }))
+            .collect()
+    }
+}
+
+/// Filter on token unit metadata
+///
+/// Every condition must hold for a unit to match; an empty filter matches every unit.
+///
+/// # Example
+///
+/// ```rust
+/// use knishio_client::token_unit::{TokenUnit, TokenUnitFilter};
+/// use serde_json::json;
+///
+/// let mut unit = TokenUnit::new("unit1".to_string(), "Sword".to_string(), None);
+/// unit.set_meta("rarity", json!("epic"));
+///
+/// assert!(TokenUnitFilter::new().has_meta("rarity").matches(&unit));
+/// assert!(!TokenUnitFilter::new().meta_equals("rarity", json!("common")).matches(&unit));
+/// assert!(TokenUnitFilter::new().meta_one_of("rarity", vec![json!("rare"), json!("epic")]).matches(&unit));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TokenUnitFilter {
+    conditions: Vec<UnitCondition>,
+}
+
+/// One metadata condition of a `TokenUnitFilter`
+#[derive(Debug, Clone, PartialEq)]
+enum UnitCondition {
+    Exists(String),
+    Equals(String, serde_json::Value),
+    NotEquals(String, serde_json::Value),
+    OneOf(String, Vec<serde_json::Value>),
+}
+
+impl UnitCondition {
+    fn matches(&self, unit: &TokenUnit) -> bool {
+        match self {
+            UnitCondition::Exists(key) => unit.get_meta(key).is_some(),
+            UnitCondition::Equals(key, expected) => unit.get_meta(key) == Some(expected),
+            UnitCondition::NotEquals(key, unexpected) => unit.get_meta(key) != Some(unexpected),
+            UnitCondition::OneOf(key, allowed) => unit.get_meta(key).is_some_and(|actual| allowed.contains(actual)),
+        }
+    }
+}
+
+impl TokenUnitFilter {
+    /// Filter matching every unit
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require the metadata key `key` to be present
+    pub fn has_meta(mut self, key: impl Into<String>) -> Self {
+        self.conditions.push(UnitCondition::Exists(key.into()));
+        self
+    }
+
+    /// Require the metadata key `key` to hold `value`
+    pub fn meta_equals(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.conditions.push(UnitCondition::Equals(key.into(), value));
+        self
+    }
+
+    /// Require the metadata key `key` to be missing or hold something other than `value`
+    pub fn meta_not_equals(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.conditions.push(UnitCondition::NotEquals(key.into(), value));
+        self
+    }
+
+    /// Require the metadata key `key` to hold one of `values`
+    pub fn meta_one_of(mut self, key: impl Into<String>, values: Vec<serde_json::Value>) -> Self {
+        self.conditions.push(UnitCondition::OneOf(key.into(), values));
+        self
+    }
+
+    /// True for a filter without conditions
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    /// Whether `unit` satisfies every condition
+    pub fn matches(&self, unit: &TokenUnit) -> bool {
+        self.conditions.iter().all(|condition| condition.matches(unit))
+    }
+}
+
 impl std::fmt::Display for TokenUnit {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "TokenUnit(id: {}, name: {})", self.id, self.name)
@@ -753,4 +910,55 @@ mod tests {
         // Test null handling for getFusedTokenUnits (JavaScript line 110)
         assert_eq!(token_unit_no_zone.get_fused_token_units(), None);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_unit_selection() {
+        let units: Vec<TokenUnit> = ["a", "b", "c", "d"].iter()
+            .map(|id| TokenUnit::new(id.to_string(), id.to_uppercase(), None))
+            .collect();
+
+        assert_eq!(UnitSelection::First.select(&units, 2).unwrap(), vec!["a", "b"]);
+        assert_eq!(UnitSelection::Last.select(&units, 2).unwrap(), vec!["c", "d"]);
+
+        let random = UnitSelection::Random.select(&units, 3).unwrap();
+        assert_eq!(random.len(), 3);
+        assert!(random.iter().all(|id| units.iter().any(|u| &u.id == id)));
+
+        assert!(UnitSelection::First.select(&units, 5).is_err());
+        assert!(UnitSelection::default().select(&units, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_held_token_units() {
+        let unit = |id: &str, rarity: &str| {
+            let mut unit = TokenUnit::new(id.to_string(), id.to_uppercase(), None);
+            unit.set_meta("rarity", json!(rarity));
+            unit
+        };
+        let mut first = Wallet::create(Some("held-units-secret"), None, "SWORD", None, None).unwrap();
+        first.batch_id = Some("batch-1".to_string());
+        first.token_units = vec![unit("a", "epic"), unit("b", "common")];
+        let mut second = Wallet::create(Some("held-units-secret"), None, "SWORD", None, None).unwrap();
+        second.batch_id = Some("batch-2".to_string());
+        second.token_units = vec![unit("c", "epic"), TokenUnit::new("d".to_string(), "D".to_string(), None)];
+        let wallets = vec![first.clone(), second.clone()];
+
+        let all = HeldTokenUnit::collect(&wallets, &TokenUnitFilter::new());
+        assert_eq!(all.iter().map(|held| held.unit.id.as_str()).collect::<Vec<_>>(), vec!["a", "b", "c", "d"]);
+        assert_eq!(all[0].wallet_address, first.address);
+        assert_eq!(all[2].batch_id.as_deref(), Some("batch-2"));
+        assert_eq!(all[3].wallet_position, second.position);
+
+        let epic = HeldTokenUnit::collect(&wallets, &TokenUnitFilter::new().meta_equals("rarity", json!("epic")));
+        assert_eq!(epic.iter().map(|held| held.unit.id.as_str()).collect::<Vec<_>>(), vec!["a", "c"]);
+
+        let tagged = HeldTokenUnit::collect(&wallets, &TokenUnitFilter::new().has_meta("rarity"));
+        assert_eq!(tagged.len(), 3);
+        assert!(HeldTokenUnit::collect(&wallets, &TokenUnitFilter::new().has_meta("rarity").has_meta("edition")).is_empty());
+        assert_eq!(all[0].bundle_hash, first.bundle);
+
+        let ids = |filter: TokenUnitFilter| HeldTokenUnit::collect(&wallets, &filter).into_iter().map(|held| held.unit.id).collect::<Vec<_>>();
+        assert_eq!(ids(TokenUnitFilter::new().meta_not_equals("rarity", json!("epic"))), vec!["b", "d"]);
+        assert_eq!(ids(TokenUnitFilter::new().meta_one_of("rarity", vec![json!("common"), json!("rare")])), vec!["b"]);
+    }
+}
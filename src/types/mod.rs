@@ -6,6 +6,14 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+mod timestamp;
+mod value_string;
+
+pub use timestamp::{created_at_millis, parse_created_at};
+pub use value_string::ValueString;
+pub(crate) use timestamp::{created_at_from_json, deserialize_created_at};
+pub(crate) use value_string::{atom_value, deserialize_atom_value, MAX_EXPONENT};
+
 /// Isotope types for atomic operations
 ///
 /// Each isotope represents a different type of operation that can be
@@ -116,6 +124,45 @@ pub struct ContinuId {
     pub batch_id: Option<String>,
 }
 
+/// Token slug of ContinuID wallets unless a deployment renames it
+pub const DEFAULT_USER_TOKEN: &str = "USER";
+
+/// Token slug of authorization wallets unless a deployment renames it
+pub const DEFAULT_AUTH_TOKEN: &str = "AUTH";
+
+/// Token slugs of the wallets a deployment signs identity and authorization with
+///
+/// Every public network uses `USER` and `AUTH`; private deployments may rename them.
+/// Molecules carry the slugs they were built with, and `check` validates against them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemTokens {
+    /// ContinuID, metadata and creation wallets
+    pub user: String,
+    /// Authorization wallets
+    pub auth: String,
+}
+
+impl Default for SystemTokens {
+    fn default() -> Self {
+        SystemTokens {
+            user: DEFAULT_USER_TOKEN.to_string(),
+            auth: DEFAULT_AUTH_TOKEN.to_string(),
+        }
+    }
+}
+
+impl SystemTokens {
+    /// Slugs `user` and `auth`
+    pub fn new(user: impl Into<String>, auth: impl Into<String>) -> Self {
+        SystemTokens { user: user.into(), auth: auth.into() }
+    }
+
+    /// True for the public network's `USER` and `AUTH`
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 /// Molecule status enum
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -200,11 +247,14 @@ pub struct BatchInfo {
 }
 
 /// Trade rate for buffer operations
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TradeRate {
+    /// Token held by the buffer wallet
     pub source_token: String,
+    /// Token the buffer trades against
     pub target_token: String,
+    /// Amount of `target_token` per unit of `source_token`
     pub rate: f64,
 }
 
@@ -0,0 +1,106 @@
+//! `createdAt` timestamps
+//!
+//! Atoms and molecules carry their creation time as milliseconds since the Unix epoch in
+//! a decimal string, JavaScript's `String(+new Date())`; node responses may carry an
+//! RFC 3339 or SQL (`2024-01-01 00:00:00`, UTC) date instead. The string is what goes into
+//! the molecular hash, so `Atom::created_at` and `Molecule::created_at` keep it verbatim and
+//! reading JSON never fails on it; only the typed accessors (`created_at_millis`,
+//! `created_at_datetime`) check it, with `parse_created_at`.
+
+use crate::error::{KnishIOError, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+/// Date of a `createdAt` string
+///
+/// ```rust
+/// use knishio_client::types::parse_created_at;
+///
+/// assert_eq!(parse_created_at("1700000000000").unwrap().to_rfc3339(), "2023-11-14T22:13:20+00:00");
+/// assert_eq!(parse_created_at("2023-11-14T22:13:20Z").unwrap().timestamp_millis(), 1_700_000_000_000);
+/// assert_eq!(parse_created_at("2023-11-14 22:13:20").unwrap().timestamp_millis(), 1_700_000_000_000);
+/// assert!(parse_created_at("2023-11-14").is_err());
+/// ```
+///
+/// # Errors
+///
+/// `InvalidTimestamp` unless `created_at` is ASCII digits naming a representable date in
+/// milliseconds, an RFC 3339 date, or a `YYYY-MM-DD HH:MM:SS` date taken as UTC
+pub fn parse_created_at(created_at: &str) -> Result<DateTime<Utc>> {
+    let invalid = || KnishIOError::InvalidTimestamp(created_at.to_string());
+    if created_at.is_empty() {
+        return Err(invalid());
+    }
+    if !created_at.bytes().all(|b| b.is_ascii_digit()) {
+        return DateTime::parse_from_rfc3339(created_at)
+            .map(|date| date.with_timezone(&Utc))
+            .or_else(|_| NaiveDateTime::parse_from_str(created_at, "%Y-%m-%d %H:%M:%S").map(|date| date.and_utc()))
+            .map_err(|_| invalid());
+    }
+    let millis: i64 = created_at.parse().map_err(|_| invalid())?;
+    DateTime::from_timestamp_millis(millis).ok_or_else(invalid)
+}
+
+/// Milliseconds of a `createdAt` string, see `parse_created_at`
+pub fn created_at_millis(created_at: &str) -> Result<i64> {
+    parse_created_at(created_at).map(|date| date.timestamp_millis())
+}
+
+/// `createdAt` string of a JSON value, kept as received
+///
+/// Strings are taken verbatim and numbers are written out in decimal; `null` is an empty
+/// string (a timestamp not set yet). Nothing is checked here, see `parse_created_at`.
+pub(crate) fn created_at_from_json(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Serde `deserialize_with` for `createdAt` fields, see `created_at_from_json`
+pub(crate) fn deserialize_created_at<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<String, D::Error> {
+    Ok(created_at_from_json(&Value::deserialize(deserializer)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_created_at() {
+        assert_eq!(created_at_millis("1700000000000").unwrap(), 1_700_000_000_000);
+        assert_eq!(created_at_millis("0").unwrap(), 0);
+        assert_eq!(created_at_millis("2024-01-01T00:00:00Z").unwrap(), 1_704_067_200_000);
+        assert_eq!(created_at_millis("2024-01-01T01:00:00+01:00").unwrap(), 1_704_067_200_000);
+        assert_eq!(created_at_millis("2024-01-01 00:00:00").unwrap(), 1_704_067_200_000);
+        for invalid in ["", "-1", "+1", "1.5", " 1", "2026-01-01", "2024-01-01 00:00", "99999999999999999999"] {
+            assert!(matches!(parse_created_at(invalid), Err(KnishIOError::InvalidTimestamp(_))), "{:?}", invalid);
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct Stamped {
+        #[serde(deserialize_with = "deserialize_created_at")]
+        created_at: String,
+    }
+
+    #[test]
+    fn test_deserialize_keeps_the_string() {
+        let stamped: Stamped = serde_json::from_str(r#"{"created_at":"01700000000000"}"#).unwrap();
+        assert_eq!(stamped.created_at, "01700000000000");
+        let stamped: Stamped = serde_json::from_str(r#"{"created_at":1700000000000}"#).unwrap();
+        assert_eq!(stamped.created_at, "1700000000000");
+        let stamped: Stamped = serde_json::from_str(r#"{"created_at":""}"#).unwrap();
+        assert_eq!(stamped.created_at, "");
+        let stamped: Stamped = serde_json::from_str(r#"{"created_at":"2024-01-01T00:00:00Z"}"#).unwrap();
+        assert_eq!(stamped.created_at, "2024-01-01T00:00:00Z");
+
+        // Whatever else the node sends is kept for the typed accessors to reject
+        for (raw, kept) in [(r#""soon""#, "soon"), ("-5", "-5"), ("1.5", "1.5"), ("null", ""), (r#""2024-01-01 00:00:00""#, "2024-01-01 00:00:00")] {
+            let json = format!(r#"{{"created_at":{}}}"#, raw);
+            assert_eq!(serde_json::from_str::<Stamped>(&json).unwrap().created_at, kept, "{}", raw);
+        }
+    }
+}
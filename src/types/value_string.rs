@@ -0,0 +1,257 @@
+//! Canonical atom value strings
+//!
+//! Atom values travel and are hashed as strings, and SDKs disagree on how a number
+//! becomes one: `50`, `50.0` and `5e1` are the same amount but three different
+//! molecular hashes. `ValueString` holds the one canonical spelling every value this
+//! SDK writes uses: plain decimal notation, no sign on zero, no leading zeros, no
+//! trailing fractional zeros and no decimal point on integers.
+//!
+//! Values received from elsewhere are hashed as they were signed, so `Atom` keeps a
+//! received string verbatim; only values that arrive as JSON numbers are canonicalized.
+
+use crate::error::{KnishIOError, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+/// Largest exponent accepted in `1e21`-style input
+pub(crate) const MAX_EXPONENT: i64 = 1024;
+
+/// Integers up to this magnitude are exact in an f64
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+/// An amount in canonical decimal notation, e.g. `-50`, `0.25`, `1000000000000000000000`
+///
+/// ```rust
+/// use knishio_client::types::ValueString;
+///
+/// assert_eq!(ValueString::from_f64(50.0).unwrap().as_str(), "50");
+/// assert_eq!(ValueString::parse("+050.500").unwrap().as_str(), "50.5");
+/// assert_eq!(ValueString::parse("1e+21").unwrap().as_str(), "1000000000000000000000");
+/// assert_eq!(ValueString::parse("-0.0").unwrap(), ValueString::from(0i64));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ValueString(String);
+
+impl ValueString {
+    /// Canonical form of a decimal string, with optional sign and exponent
+    ///
+    /// # Errors
+    ///
+    /// `InvalidAmount` if `text` is not a decimal number
+    pub fn parse(text: &str) -> Result<Self> {
+        let invalid = || KnishIOError::InvalidAmount(text.to_string());
+        let trimmed = text.trim();
+
+        let (number, exponent) = match trimmed.split_once(['e', 'E']) {
+            Some((number, exponent)) => (number, exponent.parse::<i64>().map_err(|_| invalid())?),
+            None => (trimmed, 0),
+        };
+        if exponent.abs() > MAX_EXPONENT {
+            return Err(invalid());
+        }
+        let (negative, number) = match number.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, number.strip_prefix('+').unwrap_or(number)),
+        };
+        let (int, frac) = number.split_once('.').unwrap_or((number, ""));
+        if (int.is_empty() && frac.is_empty()) || !int.chars().chain(frac.chars()).all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        // Move the decimal point by the exponent over the digits
+        let digits = format!("{}{}", int, frac);
+        let point = int.len() as i64 + exponent;
+        let (int, frac) = if point <= 0 {
+            ("0".to_string(), format!("{}{}", "0".repeat(point.unsigned_abs() as usize), digits))
+        } else if point as usize >= digits.len() {
+            (format!("{}{}", digits, "0".repeat(point as usize - digits.len())), String::new())
+        } else {
+            let (int, frac) = digits.split_at(point as usize);
+            (int.to_string(), frac.to_string())
+        };
+
+        let int = match int.trim_start_matches('0') {
+            "" => "0",
+            int => int,
+        };
+        let frac = frac.trim_end_matches('0');
+        let magnitude = if frac.is_empty() { int.to_string() } else { format!("{}.{}", int, frac) };
+
+        Ok(ValueString(if negative && magnitude != "0" { format!("-{}", magnitude) } else { magnitude }))
+    }
+
+    /// Canonical form of `value`: integers without a fractional part, anything else in
+    /// its shortest round-trip decimal form
+    ///
+    /// # Errors
+    ///
+    /// `InvalidAmount` for NaN and infinities
+    pub fn from_f64(value: f64) -> Result<Self> {
+        if !value.is_finite() {
+            return Err(KnishIOError::InvalidAmount(value.to_string()));
+        }
+        if value.fract() == 0.0 && value.abs() <= MAX_SAFE_INTEGER {
+            return Ok(ValueString::from(value as i64));
+        }
+        Self::parse(&value.to_string())
+    }
+
+    /// Canonical text, for atoms taking a value that may not be finite
+    ///
+    /// Non-finite values keep their Rust spelling (`NaN`, `inf`), which `CheckMolecule`
+    /// rejects.
+    pub(crate) fn canonical_f64(value: f64) -> String {
+        Self::from_f64(value).map(String::from).unwrap_or_else(|_| value.to_string())
+    }
+
+    /// The canonical text
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The value as an f64, possibly rounded
+    pub fn to_f64(&self) -> f64 {
+        self.0.parse().unwrap_or_default()
+    }
+
+    /// Whether the value is below zero
+    pub fn is_negative(&self) -> bool {
+        self.0.starts_with('-')
+    }
+}
+
+impl From<i64> for ValueString {
+    fn from(value: i64) -> Self {
+        ValueString(value.to_string())
+    }
+}
+
+impl From<i128> for ValueString {
+    fn from(value: i128) -> Self {
+        ValueString(value.to_string())
+    }
+}
+
+impl From<ValueString> for String {
+    fn from(value: ValueString) -> Self {
+        value.0
+    }
+}
+
+impl FromStr for ValueString {
+    type Err = KnishIOError;
+
+    fn from_str(text: &str) -> Result<Self> {
+        Self::parse(text)
+    }
+}
+
+impl fmt::Display for ValueString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for ValueString {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Serialize for ValueString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+/// Accepts a string or a JSON number, canonicalizing either
+impl<'de> Deserialize<'de> for ValueString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::String(text) => Self::parse(&text),
+            Value::Number(number) => from_number(&number),
+            other => Err(KnishIOError::InvalidAmount(other.to_string())),
+        }
+        .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Canonical text of a JSON number, exact for integers of any size serde_json holds
+fn from_number(number: &serde_json::Number) -> Result<ValueString> {
+    if let Some(integer) = number.as_i64() {
+        Ok(ValueString::from(integer))
+    } else if let Some(integer) = number.as_u64() {
+        Ok(ValueString(integer.to_string()))
+    } else {
+        ValueString::from_f64(number.as_f64().unwrap_or(f64::NAN))
+    }
+}
+
+/// Atom `value` field: strings verbatim, as they were signed; numbers canonicalized
+pub(crate) fn deserialize_atom_value<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<String>, D::Error> {
+    atom_value(&Value::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+}
+
+/// Atom value of a JSON field, see `deserialize_atom_value`
+pub(crate) fn atom_value(value: &Value) -> Result<Option<String>> {
+    match value {
+        Value::Null => Ok(None),
+        Value::String(text) => Ok(Some(text.clone())),
+        Value::Number(number) => from_number(number).map(|value| Some(value.into())),
+        other => Err(KnishIOError::InvalidAmount(other.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_forms() {
+        let cases = [
+            ("50", "50"),
+            ("50.0", "50"),
+            ("-50.000", "-50"),
+            ("+007", "7"),
+            ("0.10", "0.1"),
+            (".5", "0.5"),
+            ("5.", "5"),
+            ("-0", "0"),
+            ("0.000", "0"),
+            ("5e1", "50"),
+            ("1e+21", "1000000000000000000000"),
+            ("1.5E-3", "0.0015"),
+            ("-12.5e-1", "-1.25"),
+            ("123456789012345678901234567890", "123456789012345678901234567890"),
+        ];
+        for (input, canonical) in cases {
+            assert_eq!(ValueString::parse(input).unwrap().as_str(), canonical, "{}", input);
+        }
+
+        for invalid in ["", "-", ".", "1,5", "abc", "1e", "1e99999", "NaN", "--1"] {
+            assert!(matches!(ValueString::parse(invalid), Err(KnishIOError::InvalidAmount(_))), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_from_numbers() {
+        assert_eq!(ValueString::from_f64(50.0).unwrap().as_str(), "50");
+        assert_eq!(ValueString::from_f64(-0.0).unwrap().as_str(), "0");
+        assert_eq!(ValueString::from_f64(0.1 + 0.2).unwrap().as_str(), "0.30000000000000004");
+        assert_eq!(ValueString::from_f64(1e21).unwrap().as_str(), "1000000000000000000000");
+        assert_eq!(ValueString::from_f64(-2.5e-7).unwrap().as_str(), "-0.00000025");
+        assert!(ValueString::from_f64(f64::NAN).is_err());
+        assert!(ValueString::from_f64(f64::INFINITY).is_err());
+        assert_eq!(ValueString::from(-9_007_199_254_740_993i128).as_str(), "-9007199254740993");
+        assert!(ValueString::parse("-3").unwrap().is_negative());
+        assert_eq!(ValueString::parse("12.5").unwrap().to_f64(), 12.5);
+
+        let parsed: Vec<ValueString> = serde_json::from_str(r#"["50.0", 50, 50.0, -1.50, 18446744073709551615]"#).unwrap();
+        let texts: Vec<&str> = parsed.iter().map(ValueString::as_str).collect();
+        assert_eq!(texts, vec!["50", "50", "50", "-1.5", "18446744073709551615"]);
+        assert_eq!(serde_json::to_string(&parsed[0]).unwrap(), r#""50""#);
+        assert!(serde_json::from_str::<ValueString>("true").is_err());
+    }
+}
@@ -0,0 +1,162 @@
+//! Canonical JSON serialization
+//!
+//! Versioned molecule hashing feeds a JSON document into SHAKE256, so every SDK must
+//! produce the same bytes for the same atoms. This module writes JSON exactly as the
+//! JavaScript SDK's `JSON.stringify` does for hashing input: no whitespace, object keys
+//! sorted by UTF-16 code units, JavaScript number formatting and `JSON.stringify` string
+//! escaping. The output does not depend on serde_json's map ordering features.
+
+use serde_json::{Map, Number, Value};
+use std::fmt::Write;
+
+/// Serialize `value` to canonical JSON
+pub fn canonical_json(value: &Value) -> String {
+    let mut output = String::new();
+    write_value(&mut output, value);
+    output
+}
+
+fn write_value(output: &mut String, value: &Value) {
+    match value {
+        Value::Null => output.push_str("null"),
+        Value::Bool(b) => output.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => write_number(output, n),
+        Value::String(s) => write_string(output, s),
+        Value::Array(items) => {
+            output.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_value(output, item);
+            }
+            output.push(']');
+        }
+        Value::Object(map) => write_object(output, map),
+    }
+}
+
+fn write_object(output: &mut String, map: &Map<String, Value>) {
+    let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+    // JavaScript compares strings by UTF-16 code units, not by code points
+    entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+    output.push('{');
+    for (i, (key, value)) in entries.into_iter().enumerate() {
+        if i > 0 {
+            output.push(',');
+        }
+        write_string(output, key);
+        output.push(':');
+        write_value(output, value);
+    }
+    output.push('}');
+}
+
+/// Escape a string the way `JSON.stringify` does
+fn write_string(output: &mut String, s: &str) {
+    output.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\u{08}' => output.push_str("\\b"),
+            '\u{0c}' => output.push_str("\\f"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(output, "\\u{:04x}", c as u32);
+            }
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+/// Format a number the way JavaScript's `Number.prototype.toString` does
+fn write_number(output: &mut String, n: &Number) {
+    if n.is_i64() || n.is_u64() {
+        output.push_str(&n.to_string());
+        return;
+    }
+
+    let f = n.as_f64().unwrap_or(0.0);
+    if !f.is_finite() {
+        output.push_str("null");
+        return;
+    }
+    if f == 0.0 {
+        output.push('0');
+        return;
+    }
+
+    // Shortest round-trip digits and decimal exponent, e.g. 1.25e-7 -> ("125", -7)
+    let scientific = format!("{:e}", f.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    // Position of the decimal point relative to the digits (ECMAScript's `n`)
+    let point = exponent + 1;
+    let k = digits.len() as i32;
+
+    if f < 0.0 {
+        output.push('-');
+    }
+
+    if k <= point && point <= 21 {
+        output.push_str(&digits);
+        output.push_str(&"0".repeat((point - k) as usize));
+    } else if 0 < point && point <= 21 {
+        output.push_str(&digits[..point as usize]);
+        output.push('.');
+        output.push_str(&digits[point as usize..]);
+    } else if -6 < point && point <= 0 {
+        output.push_str("0.");
+        output.push_str(&"0".repeat((-point) as usize));
+        output.push_str(&digits);
+    } else {
+        output.push_str(&digits[..1]);
+        if k > 1 {
+            output.push('.');
+            output.push_str(&digits[1..]);
+        }
+        let _ = write!(output, "e{}{}", if point > 0 { "+" } else { "-" }, (point - 1).abs());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_keys_sorted_and_compact() {
+        let value = json!({"b": [1, {"z": null, "a": true}], "a": "x", "B": -3});
+        assert_eq!(canonical_json(&value), r#"{"B":-3,"a":"x","b":[1,{"a":true,"z":null}]}"#);
+    }
+
+    #[test]
+    fn test_string_escaping_matches_json_stringify() {
+        let value = json!("quote\" slash\\ / tab\t nl\n bell\u{07} é 😀");
+        assert_eq!(canonical_json(&value), "\"quote\\\" slash\\\\ / tab\\t nl\\n bell\\u0007 é 😀\"");
+    }
+
+    #[test]
+    fn test_numbers_match_javascript() {
+        let cases = [
+            (json!(1.0), "1"),
+            (json!(0.1), "0.1"),
+            (json!(-2.5), "-2.5"),
+            (json!(123456.789), "123456.789"),
+            (json!(1e21), "1e+21"),
+            (json!(1.5e300), "1.5e+300"),
+            (json!(0.000001), "0.000001"),
+            (json!(1.25e-7), "1.25e-7"),
+            (json!(u64::MAX), "18446744073709551615"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(canonical_json(&value), expected, "{}", value);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Time source for expiry checks
+//!
+//! Auth token expiry is judged against a `Clock` rather than the wall clock directly, so
+//! tests can expire a token by moving a `ManualClock` forward instead of sleeping.
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Source of the current time
+pub trait Clock: Debug + Send + Sync {
+    /// Milliseconds since the Unix epoch
+    fn now_millis(&self) -> i64;
+}
+
+/// The system's wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> i64 {
+        chrono::Utc::now().timestamp_millis()
+    }
+}
+
+/// A clock that only moves when told to
+///
+/// Clones share the same time, so a test can keep one and advance the clock it handed
+/// to a client.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    millis: Arc<AtomicI64>,
+}
+
+impl ManualClock {
+    /// A clock stopped at `millis` since the Unix epoch
+    pub fn new(millis: i64) -> Self {
+        ManualClock { millis: Arc::new(AtomicI64::new(millis)) }
+    }
+
+    /// A clock stopped at the current wall-clock time
+    pub fn starting_now() -> Self {
+        Self::new(SystemClock.now_millis())
+    }
+
+    /// Move the clock forward by `duration`
+    pub fn advance(&self, duration: Duration) {
+        let millis = i64::try_from(duration.as_millis()).unwrap_or(i64::MAX);
+        self.millis.fetch_add(millis, Ordering::SeqCst);
+    }
+
+    /// Stop the clock at `millis` since the Unix epoch
+    pub fn set_millis(&self, millis: i64) {
+        self.millis.store(millis, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_millis(&self) -> i64 {
+        self.millis.load(Ordering::SeqCst)
+    }
+}
+
+/// The wall clock, shared
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
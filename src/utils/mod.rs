@@ -7,6 +7,8 @@ pub mod decimal;
 pub mod dot;
 pub mod hex;
 pub mod array;
+pub mod canonical;
+pub mod clock;
 
 // Re-export commonly used utilities
 pub use strings::{
@@ -24,6 +26,8 @@ pub use strings::{
     trim_string,
 };
 
+pub use canonical::canonical_json;
+pub use clock::{Clock, ManualClock, SystemClock};
 pub use decimal::Decimal;
 pub use dot::Dot;
 pub use hex::{Hex, HexOptions};
@@ -0,0 +1,334 @@
+//! V3 to V4 molecule migration
+//!
+//! Molecules proposed before structured hashing carry unversioned (V3) atoms, hashed
+//! from their concatenated property values. `migrate_molecule` re-emits such a molecule
+//! with Version4 atoms, so indexers replaying old ledger data can store and validate it
+//! in the current structure. Both SDK JSON (`token`, `meta`) and node query results
+//! (`tokenSlug`, `metasJson`) are accepted.
+//!
+//! A V4 molecule hashes differently, so the original OTS signature cannot be carried
+//! over: the migrated molecule is unsigned and keeps the original hash alongside the new
+//! one. Everything else that could not be migrated verbatim is listed as a
+//! `MigrationIssue`.
+
+use super::{StructureUtils, Version4};
+use crate::atom::Atom;
+use crate::error::{KnishIOError, Result};
+use crate::types::{AtomFromJsonOptions, AtomJsonOptions};
+use serde_json::{json, Map, Value};
+use std::fmt;
+
+/// Version stamped on migrated atoms
+pub const MIGRATED_VERSION: &str = "4";
+
+/// Atom fields the migration understands
+const KNOWN_ATOM_FIELDS: &[&str] = &[
+    "position", "walletAddress", "isotope", "token", "tokenSlug", "value", "batchId",
+    "metaType", "metaId", "meta", "metasJson", "index", "otsFragment", "createdAt", "version",
+];
+
+/// Something the migration could not carry over as-is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationIssue {
+    /// Position of the atom in the input, or None for molecule-level issues
+    pub atom: Option<usize>,
+    /// Field concerned
+    pub field: String,
+    /// What happened to it
+    pub reason: String,
+}
+
+impl MigrationIssue {
+    fn new(atom: Option<usize>, field: &str, reason: impl Into<String>) -> Self {
+        MigrationIssue { atom, field: field.to_string(), reason: reason.into() }
+    }
+}
+
+impl fmt::Display for MigrationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.atom {
+            Some(atom) => write!(f, "atom {}: {}: {}", atom, self.field, self.reason),
+            None => write!(f, "{}: {}", self.field, self.reason),
+        }
+    }
+}
+
+/// Result of migrating one molecule
+#[derive(Debug, Clone)]
+pub struct MoleculeMigration {
+    /// The V4 molecule as JSON, unsigned
+    pub molecule: Value,
+    /// The V4 atoms, in index order
+    pub atoms: Vec<Atom>,
+    /// `StructureUtils` view of each atom's Version4 representation
+    pub structures: Vec<Value>,
+    /// Molecular hash of the V4 atoms
+    pub molecular_hash: String,
+    /// Molecular hash the input carried
+    pub legacy_hash: Option<String>,
+    /// Whether the input's hash matches its V3 atoms (None without a hash)
+    pub legacy_hash_verified: Option<bool>,
+    /// Fields that could not be migrated verbatim
+    pub issues: Vec<MigrationIssue>,
+}
+
+impl MoleculeMigration {
+    /// True when nothing beyond the signature and hash had to change
+    pub fn is_clean(&self) -> bool {
+        self.issues.iter().all(|issue| issue.atom.is_none())
+    }
+}
+
+/// Re-emit a V3 molecule (SDK JSON or node query result) with Version4 atoms
+///
+/// # Errors
+///
+/// `AtomsMissing` without atoms; a custom error for atoms lacking a position, wallet
+/// address, isotope or token, which cannot be migrated at all.
+///
+/// # Example
+///
+/// ```rust
+/// use knishio_client::versions::migrate_molecule;
+/// use serde_json::json;
+///
+/// let migration = migrate_molecule(&json!({
+///     "molecularHash": "0123",
+///     "atoms": [{
+///         "position": "a1", "walletAddress": "w1", "isotope": "M", "tokenSlug": "USER",
+///         "metaType": "car", "metaId": "VIN1", "metasJson": "{\"color\":\"red\"}", "index": 0,
+///         "createdAt": "1700000000000", "otsFragment": "sig"
+///     }]
+/// })).unwrap();
+///
+/// assert_eq!(migration.atoms[0].version.as_deref(), Some("4"));
+/// assert_eq!(migration.atoms[0].meta[0].key, "color");
+/// assert_eq!(migration.legacy_hash_verified, Some(false));
+/// assert!(migration.is_clean());
+/// ```
+pub fn migrate_molecule(molecule: &Value) -> Result<MoleculeMigration> {
+    let inputs = molecule.get("atoms").and_then(Value::as_array)
+        .filter(|atoms| !atoms.is_empty())
+        .ok_or(KnishIOError::AtomsMissing)?;
+
+    let mut issues = Vec::new();
+    let mut legacy_atoms = Vec::with_capacity(inputs.len());
+    for (position, input) in inputs.iter().enumerate() {
+        legacy_atoms.push(legacy_atom(input, position, &mut issues)?);
+    }
+    let legacy_atoms = Atom::sort_atoms(&legacy_atoms);
+
+    let legacy_hash = molecule.get("molecularHash").and_then(Value::as_str).map(str::to_string);
+    let legacy_hash_verified = match legacy_hash {
+        Some(ref hash) => Some(Atom::hash_atoms(&legacy_atoms, "base17")? == *hash),
+        None => None,
+    };
+
+    let atoms: Vec<Atom> = legacy_atoms.into_iter()
+        .map(|mut atom| {
+            atom.version = Some(MIGRATED_VERSION.to_string());
+            atom.ots_fragment = None;
+            atom
+        })
+        .collect();
+    let molecular_hash = Atom::hash_atoms(&atoms, "base17")?;
+    let structures = atoms.iter()
+        .map(|atom| StructureUtils::create_sorted_object(&Version4::from_atom(atom)))
+        .collect::<Result<Vec<_>>>()?;
+
+    if inputs.iter().any(|atom| atom.get("otsFragment").is_some_and(|sig| !sig.is_null())) {
+        issues.push(MigrationIssue::new(None, "otsFragment", "signature covers the V3 hash and was dropped; re-sign to propose"));
+    }
+    if legacy_hash.is_some() {
+        issues.push(MigrationIssue::new(None, "molecularHash", "recomputed for V4 atoms; original kept as legacyMolecularHash"));
+    }
+
+    let mut output = Map::new();
+    for field in ["bundle", "bundleHash", "cellSlug", "status", "createdAt"] {
+        if let Some(value) = molecule.get(field).filter(|value| !value.is_null()) {
+            output.insert(field.to_string(), value.clone());
+        }
+    }
+    output.insert("molecularHash".to_string(), json!(molecular_hash));
+    output.insert("legacyMolecularHash".to_string(), json!(legacy_hash));
+    let atom_options = AtomJsonOptions { include_ots_fragments: false, validate_fields: false };
+    output.insert("atoms".to_string(), Value::Array(
+        atoms.iter().map(|atom| atom.to_json(atom_options.clone())).collect::<Result<Vec<_>>>()?
+    ));
+
+    Ok(MoleculeMigration {
+        molecule: Value::Object(output),
+        atoms,
+        structures,
+        molecular_hash,
+        legacy_hash,
+        legacy_hash_verified,
+        issues,
+    })
+}
+
+/// Parse one input atom into an unversioned atom, recording lossy conversions
+fn legacy_atom(input: &Value, position: usize, issues: &mut Vec<MigrationIssue>) -> Result<Atom> {
+    let object = input.as_object()
+        .ok_or_else(|| KnishIOError::custom(format!("atom {} is not an object", position)))?;
+    let at = Some(position);
+
+    let mut normalized = object.clone();
+    if let Some(slug) = object.get("tokenSlug").filter(|_| !object.contains_key("token")) {
+        normalized.insert("token".to_string(), slug.clone());
+    }
+    if let Some(value) = object.get("value").filter(|value| value.is_number()) {
+        normalized.insert("value".to_string(), json!(value.to_string()));
+    }
+    if let Some(created_at) = object.get("createdAt").filter(|value| value.is_number()) {
+        normalized.insert("createdAt".to_string(), json!(created_at.to_string()));
+    }
+    if !object.contains_key("meta") {
+        if let Some(metas) = object.get("metasJson") {
+            normalized.insert("meta".to_string(), Value::Array(metas_json(metas, position, issues)));
+        }
+    }
+
+    let mut atom = Atom::from_json(&Value::Object(normalized), AtomFromJsonOptions::default())
+        .map_err(|e| KnishIOError::custom(format!("atom {} cannot be migrated: {}", position, e)))?;
+
+    for field in object.keys().filter(|field| !KNOWN_ATOM_FIELDS.contains(&field.as_str())) {
+        issues.push(MigrationIssue::new(at, field, "not part of the V4 atom structure; dropped"));
+    }
+    if atom.index.is_none() {
+        atom.index = Some(position as u32);
+        issues.push(MigrationIssue::new(at, "index", format!("missing; assigned {} from input order", position)));
+    }
+    if !object.get("createdAt").is_some_and(|created_at| !created_at.is_null()) {
+        // Atom::from_json falls back to the current time, which would change the hash
+        atom.created_at = String::new();
+        issues.push(MigrationIssue::new(at, "createdAt", "missing; hashed as null"));
+    }
+    match object.get("version").and_then(Value::as_str) {
+        None | Some(MIGRATED_VERSION) => {}
+        Some(other) => issues.push(MigrationIssue::new(at, "version", format!("unknown version {}; re-emitted as {}", other, MIGRATED_VERSION))),
+    }
+    // Legacy hashing ignores the version only when no atom has one
+    atom.version = None;
+    Ok(atom)
+}
+
+/// Meta items of a node's `metasJson` (an array of {key, value} or a key/value object)
+fn metas_json(metas: &Value, position: usize, issues: &mut Vec<MigrationIssue>) -> Vec<Value> {
+    let parsed = match metas {
+        Value::String(text) => match serde_json::from_str::<Value>(text) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                issues.push(MigrationIssue::new(Some(position), "metasJson", format!("unparseable ({}); meta dropped", e)));
+                return Vec::new();
+            }
+        },
+        other => other.clone(),
+    };
+
+    let pairs: Vec<(String, Value)> = match parsed {
+        Value::Array(items) => items.into_iter()
+            .map(|item| (item.get("key").and_then(Value::as_str).unwrap_or_default().to_string(), item.get("value").cloned().unwrap_or(Value::Null)))
+            .collect(),
+        Value::Object(object) => object.into_iter().collect(),
+        Value::Null => Vec::new(),
+        other => {
+            issues.push(MigrationIssue::new(Some(position), "metasJson", format!("unexpected {}; meta dropped", other)));
+            return Vec::new();
+        }
+    };
+
+    pairs.into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(text) => text,
+                Value::Null => String::new(),
+                other => {
+                    issues.push(MigrationIssue::new(Some(position), &format!("meta.{}", key), "non-string value stored as JSON text"));
+                    other.to_string()
+                }
+            };
+            json!({ "key": key, "value": value })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Isotope;
+
+    fn v3_molecule() -> Value {
+        let mut first = Atom::new("pos0", "addr0", Isotope::V, "TEST");
+        first.index = Some(0);
+        first.value = Some("-5".to_string());
+        first.created_at = "1700000000000".to_string();
+        let mut second = Atom::new("pos1", "addr1", Isotope::V, "TEST");
+        second.index = Some(1);
+        second.value = Some("5".to_string());
+        second.created_at = "1700000000000".to_string();
+        let legacy_hash = Atom::hash_atoms(&[first, second], "base17").unwrap();
+
+        json!({
+            "molecularHash": legacy_hash,
+            "bundleHash": "bundle",
+            "atoms": [
+                // Node query results: tokenSlug, numeric values, out of order
+                { "position": "pos1", "walletAddress": "addr1", "isotope": "V", "tokenSlug": "TEST", "value": 5,
+                  "index": 1, "createdAt": "1700000000000", "otsFragment": "sig1" },
+                { "position": "pos0", "walletAddress": "addr0", "isotope": "V", "tokenSlug": "TEST", "value": -5,
+                  "index": 0, "createdAt": "1700000000000", "otsFragment": "sig0" },
+            ],
+        })
+    }
+
+    #[test]
+    fn test_migrates_v3_molecule() {
+        let migration = migrate_molecule(&v3_molecule()).unwrap();
+
+        assert_eq!(migration.legacy_hash_verified, Some(true));
+        assert!(migration.is_clean(), "{:?}", migration.issues);
+        assert_eq!(migration.atoms.iter().map(|atom| atom.position.as_str()).collect::<Vec<_>>(), vec!["pos0", "pos1"]);
+        assert!(migration.atoms.iter().all(|atom| atom.version.as_deref() == Some("4") && atom.ots_fragment.is_none()));
+        assert_eq!(migration.molecular_hash, Atom::hash_atoms(&migration.atoms, "base17").unwrap());
+        assert_ne!(Some(&migration.molecular_hash), migration.legacy_hash.as_ref());
+
+        assert_eq!(migration.molecule["bundleHash"], "bundle");
+        assert_eq!(migration.molecule["legacyMolecularHash"], json!(migration.legacy_hash));
+        assert_eq!(migration.molecule["atoms"][0]["value"], "-5");
+        assert!(migration.molecule["atoms"][0].get("otsFragment").is_none());
+        assert_eq!(migration.structures.len(), 2);
+        assert!(migration.structures[0].is_array());
+
+        let fields: Vec<&str> = migration.issues.iter().map(|issue| issue.field.as_str()).collect();
+        assert_eq!(fields, vec!["otsFragment", "molecularHash"]);
+    }
+
+    #[test]
+    fn test_reports_lossy_fields() {
+        let migration = migrate_molecule(&json!({
+            "atoms": [{
+                "position": "pos0", "walletAddress": "addr0", "isotope": "M", "token": "USER",
+                "metaType": "car", "metaId": "VIN1", "metasJson": "{\"seats\":4,\"color\":\"red\"}",
+                "version": "3", "cellSlug": "legacy",
+            }],
+        })).unwrap();
+
+        assert!(!migration.is_clean());
+        assert_eq!(migration.legacy_hash_verified, None);
+        let issue = |field: &str| migration.issues.iter().find(|issue| issue.field == field).map(ToString::to_string);
+        assert_eq!(issue("cellSlug").unwrap(), "atom 0: cellSlug: not part of the V4 atom structure; dropped");
+        assert!(issue("index").is_some());
+        assert!(issue("createdAt").is_some());
+        assert!(issue("version").is_some());
+        assert!(issue("meta.seats").is_some());
+        assert!(issue("meta.color").is_none());
+
+        let atom = &migration.atoms[0];
+        assert_eq!(atom.index, Some(0));
+        assert!(atom.meta.iter().any(|meta| meta.key == "seats" && meta.value == "4"));
+
+        assert!(migrate_molecule(&json!({ "atoms": [] })).is_err());
+        assert!(migrate_molecule(&json!({ "atoms": [{ "position": "p", "isotope": "V" }] })).is_err());
+    }
+}
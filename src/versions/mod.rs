@@ -10,9 +10,11 @@ use crate::atom::Atom;
 use crate::error::Result;
 
 pub mod hash_atom;
+pub mod migrate;
 pub mod version4;
 
 pub use hash_atom::HashAtom;
+pub use migrate::{migrate_molecule, MigrationIssue, MoleculeMigration};
 pub use version4::Version4;
 
 /// Trait for version-specific atom implementations
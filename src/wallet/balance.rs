@@ -0,0 +1,145 @@
+//! Typed wallet balances
+//!
+//! `Wallet::balance` is the ledger's decimal string and `Wallet::token_units` the stackable
+//! units the wallet holds; nothing ties the two together. `Wallet::balance_info` reads both
+//! into one `Balance` so amounts are compared as integers, never as floats.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Balance of a wallet with the count of its token units
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Balance {
+    /// Balance with the decimal point removed: "12.50" is 1250
+    pub amount: i128,
+    /// Stackable token units the wallet holds
+    pub unit_count: usize,
+    /// Decimal places `amount` is scaled by: "12.50" has 2
+    pub decimals_applied: u32,
+}
+
+impl Balance {
+    /// Parse a decimal balance string ("42", "-3", "12.50"); None when it is not one
+    pub fn parse(balance: &str, unit_count: usize) -> Option<Self> {
+        let (amount, decimals_applied) = parse_decimal(balance)?;
+        Some(Balance { amount, unit_count, decimals_applied })
+    }
+
+    /// Whole part of the balance, rounding towards zero
+    pub fn whole(&self) -> i128 {
+        self.amount / 10i128.pow(self.decimals_applied)
+    }
+
+    /// Whole part of the balance when it has no fraction ("12.00" but not "12.50")
+    pub fn whole_exact(&self) -> Option<i128> {
+        let scale = 10i128.pow(self.decimals_applied);
+        (self.amount % scale == 0).then(|| self.amount / scale)
+    }
+
+    /// Balance as f64, for display only
+    pub fn to_f64(&self) -> f64 {
+        self.amount as f64 / 10f64.powi(self.decimals_applied as i32)
+    }
+
+    /// Whether the balance is at least `amount`, compared exactly in decimal
+    ///
+    /// A NaN or infinite `amount` is never covered.
+    pub fn covers(&self, amount: f64) -> bool {
+        match parse_decimal(&amount.to_string()) {
+            Some(amount) => compare_decimal((self.amount, self.decimals_applied), amount) != Ordering::Less,
+            None => false,
+        }
+    }
+
+    /// Order two balances by amount, exactly in decimal ("1.50" equals "1.5")
+    pub fn cmp_amount(&self, other: &Balance) -> Ordering {
+        compare_decimal((self.amount, self.decimals_applied), (other.amount, other.decimals_applied))
+    }
+
+    /// Sum of two balances and of their unit counts; None when it overflows
+    pub fn checked_add(&self, other: &Balance) -> Option<Balance> {
+        let (a, b, decimals_applied) = align((self.amount, self.decimals_applied), (other.amount, other.decimals_applied))?;
+        Some(Balance {
+            amount: a.checked_add(b)?,
+            unit_count: self.unit_count.checked_add(other.unit_count)?,
+            decimals_applied,
+        })
+    }
+
+    /// Difference of two balances; the unit count drops by `other`'s, floored at zero.
+    /// None when it overflows
+    pub fn checked_sub(&self, other: &Balance) -> Option<Balance> {
+        let (a, b, decimals_applied) = align((self.amount, self.decimals_applied), (other.amount, other.decimals_applied))?;
+        Some(Balance {
+            amount: a.checked_sub(b)?,
+            unit_count: self.unit_count.saturating_sub(other.unit_count),
+            decimals_applied,
+        })
+    }
+
+    /// Whether the amount and the unit count agree
+    ///
+    /// Wallets without units always agree; a wallet with units holds exactly one token per
+    /// unit and no fraction.
+    pub fn is_reconciled(&self) -> bool {
+        self.unit_count == 0 || (self.decimals_applied == 0 && self.amount == self.unit_count as i128)
+    }
+}
+
+impl fmt::Display for Balance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.decimals_applied == 0 {
+            return write!(f, "{}", self.amount);
+        }
+        let scale = 10i128.pow(self.decimals_applied);
+        let sign = if self.amount < 0 { "-" } else { "" };
+        write!(
+            f,
+            "{}{}.{:0width$}",
+            sign,
+            (self.amount / scale).abs(),
+            (self.amount % scale).abs(),
+            width = self.decimals_applied as usize
+        )
+    }
+}
+
+/// Split a decimal string into its digits as an integer and its number of decimal places
+fn parse_decimal(value: &str) -> Option<(i128, u32)> {
+    let value = value.trim();
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    // 10^38 is the largest power of ten an i128 holds
+    if (whole.is_empty() && fraction.is_empty()) || fraction.len() > 38 {
+        return None;
+    }
+    if !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut amount: i128 = 0;
+    for digit in whole.chars().chain(fraction.chars()) {
+        amount = amount.checked_mul(10)?.checked_add(i128::from(digit.to_digit(10)?))?;
+    }
+    Some((if negative { -amount } else { amount }, fraction.len() as u32))
+}
+
+/// Two scaled decimals brought to the same number of decimal places
+fn align((a, a_decimals): (i128, u32), (b, b_decimals): (i128, u32)) -> Option<(i128, i128, u32)> {
+    let scale = |value: i128, by: u32| 10i128.checked_pow(by).and_then(|factor| value.checked_mul(factor));
+    let decimals = a_decimals.max(b_decimals);
+    Some((scale(a, decimals - a_decimals)?, scale(b, decimals - b_decimals)?, decimals))
+}
+
+/// Order two scaled decimals
+fn compare_decimal((a, a_decimals): (i128, u32), (b, b_decimals): (i128, u32)) -> Ordering {
+    match align((a, a_decimals), (b, b_decimals)) {
+        Some((a, b, _)) => a.cmp(&b),
+        // Out of i128 range: fall back to the whole parts
+        _ => (a / 10i128.pow(a_decimals)).cmp(&(b / 10i128.pow(b_decimals))),
+    }
+}
@@ -0,0 +1,148 @@
+//! Wallet character sets
+//!
+//! `Wallet.characters` names the alphabet used for the wallet's public key material
+//! (the ML-KEM public key published as `pubkey` meta). The JS SDK and this crate default
+//! to `BASE64`, but wallets created by other SDKs may carry `BASE58` or `HEX` keys.
+//! `Characters` turns those magic strings into a type with encoding, validation and
+//! conversion between alphabets.
+
+use crate::crypto::{base58_decode, base58_encode_bytes};
+use crate::error::{KnishIOError, Result};
+use base64::Engine as _;
+use std::fmt;
+use std::str::FromStr;
+
+/// Alphabet used to encode a wallet's public key material
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Characters {
+    /// Standard padded Base64 (JS SDK default)
+    #[default]
+    Base64,
+    /// Bitcoin-alphabet Base58
+    Base58,
+    /// Lowercase hexadecimal
+    Hex,
+}
+
+impl Characters {
+    /// All supported alphabets, in detection order
+    pub const ALL: [Characters; 3] = [Characters::Base64, Characters::Base58, Characters::Hex];
+
+    /// Canonical name as stored in `Wallet.characters` and `characters` meta
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Characters::Base64 => "BASE64",
+            Characters::Base58 => "BASE58",
+            Characters::Hex => "HEX",
+        }
+    }
+
+    /// Encode bytes in this alphabet
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            Characters::Base64 => base64::engine::general_purpose::STANDARD.encode(bytes),
+            Characters::Base58 => base58_encode_bytes(bytes),
+            Characters::Hex => hex::encode(bytes),
+        }
+    }
+
+    /// Decode a string in this alphabet
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecryptionKey` if `value` is not valid in this alphabet
+    pub fn decode(&self, value: &str) -> Result<Vec<u8>> {
+        match self {
+            Characters::Base64 => base64::engine::general_purpose::STANDARD
+                .decode(value)
+                .map_err(|_| KnishIOError::DecryptionKey),
+            Characters::Base58 => base58_decode(value).map_err(|_| KnishIOError::DecryptionKey),
+            Characters::Hex => hex::decode(value).map_err(|_| KnishIOError::DecryptionKey),
+        }
+    }
+
+    /// Whether `value` is well-formed in this alphabet
+    pub fn is_valid(&self, value: &str) -> bool {
+        !value.is_empty() && self.decode(value).is_ok()
+    }
+
+    /// Re-encode `value` from this alphabet into `target`
+    pub fn convert(&self, value: &str, target: Characters) -> Result<String> {
+        Ok(target.encode(&self.decode(value)?))
+    }
+
+    /// Find the alphabet in which `value` decodes to exactly `expected_len` bytes
+    ///
+    /// Several alphabets overlap (a hex string is also valid Base64), so a known key length
+    /// is what makes detection unambiguous.
+    pub fn detect(value: &str, expected_len: usize) -> Option<(Characters, Vec<u8>)> {
+        Self::ALL.iter().find_map(|charset| {
+            charset.decode(value).ok()
+                .filter(|bytes| bytes.len() == expected_len)
+                .map(|bytes| (*charset, bytes))
+        })
+    }
+}
+
+impl FromStr for Characters {
+    type Err = KnishIOError;
+
+    /// Parse a character set name (case-insensitive)
+    fn from_str(name: &str) -> Result<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "BASE64" => Ok(Characters::Base64),
+            "BASE58" => Ok(Characters::Base58),
+            "HEX" | "BASE16" => Ok(Characters::Hex),
+            other => Err(KnishIOError::custom(format!("Unsupported wallet characters: {}", other))),
+        }
+    }
+}
+
+impl fmt::Display for Characters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!("BASE64".parse::<Characters>().unwrap(), Characters::Base64);
+        assert_eq!("base58".parse::<Characters>().unwrap(), Characters::Base58);
+        assert_eq!("Hex".parse::<Characters>().unwrap(), Characters::Hex);
+        assert!("EBCDIC".parse::<Characters>().is_err());
+        assert_eq!(Characters::default().to_string(), "BASE64");
+    }
+
+    #[test]
+    fn test_round_trip_and_convert() {
+        let bytes = [0u8, 1, 2, 250, 255, 17, 42];
+
+        for charset in Characters::ALL {
+            let encoded = charset.encode(&bytes);
+            assert!(charset.is_valid(&encoded));
+            assert_eq!(charset.decode(&encoded).unwrap(), bytes);
+        }
+
+        let base64 = Characters::Base64.encode(&bytes);
+        let hex = Characters::Base64.convert(&base64, Characters::Hex).unwrap();
+        assert_eq!(hex, hex::encode(bytes));
+        assert!(!Characters::Hex.is_valid("zz"));
+    }
+
+    #[test]
+    fn test_detect_by_length() {
+        let bytes = vec![7u8; 32];
+
+        for charset in Characters::ALL {
+            let (detected, decoded) = Characters::detect(&charset.encode(&bytes), 32).unwrap();
+            assert_eq!(detected, charset);
+            assert_eq!(decoded, bytes);
+        }
+
+        assert!(Characters::detect("not a key", 32).is_none());
+    }
+}
@@ -3,6 +3,16 @@
 //! This module provides the Wallet struct and associated methods for wallet
 //! management, ensuring exact compatibility with the JavaScript implementation.
 
+pub mod balance;
+pub mod characters;
+pub mod proof;
+pub mod watch;
+
+pub use balance::Balance;
+pub use characters::Characters;
+pub use proof::{OwnershipProof, ProofTree};
+pub use watch::WatchWallet;
+
 use crate::crypto::{generate_address, generate_bundle_hash, generate_key};
 use crate::error::{KnishIOError, Result};
 use crate::types::TokenUnit;
@@ -12,6 +22,18 @@ use rand::{RngCore};
 use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
 
+/// How much of a GraphQL wallet response `Wallet::from_response_data_with` parses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WalletHydration {
+    /// Balance, token, bundle and address only - for tight balance-polling loops
+    Minimal,
+    /// All response fields including token units (the `from_response_data` behavior)
+    #[default]
+    Standard,
+    /// Standard, plus signing and ML-KEM keys regenerated from the client secret
+    Full,
+}
+
 /// Wallet structure representing cryptographic keys and token management
 ///
 /// The Wallet struct maintains exact compatibility with the JavaScript implementation,
@@ -86,37 +108,123 @@ impl std::fmt::Debug for Wallet {
     }
 }
 
+/// Parameters for `Wallet::from_params`
+///
+/// Every field is optional. With a secret, the bundle, position, key, address and
+/// ML-KEM keys are derived from it where not given; without one the wallet is a shadow
+/// wallet identified by its bundle.
+///
+/// ```
+/// use knishio_client::wallet::{Wallet, WalletParams};
+///
+/// let secret = knishio_client::generate_secret("alice");
+/// let wallet = Wallet::from_params(WalletParams::new().secret(&secret).token("DEMO")).unwrap();
+/// assert_eq!(wallet.token, "DEMO");
+/// ```
+#[derive(Clone, Default)]
+pub struct WalletParams {
+    /// 2048-character biometric hash (None for shadow wallets)
+    pub secret: Option<String>,
+    /// 64-character hexadecimal user identifier (derived from the secret if None)
+    pub bundle: Option<String>,
+    /// Token slug (defaults to "USER")
+    pub token: Option<String>,
+    /// Hexadecimal public key (derived from the key if None)
+    pub address: Option<String>,
+    /// Position string (generated if None)
+    pub position: Option<String>,
+    /// Batch ID for stackable tokens
+    pub batch_id: Option<String>,
+    /// Character encoding (defaults to BASE64)
+    pub characters: Option<String>,
+}
+
+impl WalletParams {
+    /// Empty parameters: a USER wallet once a secret is set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the secret
+    pub fn secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    /// Set the bundle hash
+    pub fn bundle(mut self, bundle: impl Into<String>) -> Self {
+        self.bundle = Some(bundle.into());
+        self
+    }
+
+    /// Set the token slug
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Set the address
+    pub fn address(mut self, address: impl Into<String>) -> Self {
+        self.address = Some(address.into());
+        self
+    }
+
+    /// Set the position
+    pub fn position(mut self, position: impl Into<String>) -> Self {
+        self.position = Some(position.into());
+        self
+    }
+
+    /// Set the batch ID
+    pub fn batch_id(mut self, batch_id: impl Into<String>) -> Self {
+        self.batch_id = Some(batch_id.into());
+        self
+    }
+
+    /// Set the character encoding
+    pub fn characters(mut self, characters: impl Into<String>) -> Self {
+        self.characters = Some(characters.into());
+        self
+    }
+}
+
+/// Debug impl that redacts the secret
+impl std::fmt::Debug for WalletParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WalletParams")
+            .field("secret", &self.secret.as_ref().map(|_| "[REDACTED]"))
+            .field("bundle", &self.bundle)
+            .field("token", &self.token)
+            .field("address", &self.address)
+            .field("position", &self.position)
+            .field("batch_id", &self.batch_id)
+            .field("characters", &self.characters)
+            .finish()
+    }
+}
+
 impl Wallet {
     /// Create a new Wallet instance
     ///
     /// # Arguments
-    /// 
-    /// * `secret` - 2048-character biometric hash (optional for shadow wallets)
-    /// * `bundle` - 64-character hexadecimal user identifier (optional)
-    /// * `token` - Token slug (defaults to "USER")
-    /// * `address` - Hexadecimal public key (optional)
-    /// * `position` - Position string (optional)
-    /// * `batch_id` - Batch ID for transactions (optional)
-    /// * `characters` - Character encoding (optional)
-    pub fn new(
-        secret: Option<&str>,
-        bundle: Option<&str>,
-        token: Option<&str>,
-        address: Option<&str>,
-        position: Option<&str>,
-        batch_id: Option<&str>,
-        characters: Option<&str>,
-    ) -> Result<Self> {
-        let token = token.unwrap_or("USER").to_string();
+    ///
+    /// * `params` - Secret, bundle, token and the other optional wallet fields
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if address derivation or ML-KEM key generation fails
+    pub fn from_params(params: WalletParams) -> Result<Self> {
+        let WalletParams { secret, bundle, token, address, position, batch_id, characters } = params;
+        let token = token.unwrap_or_else(|| "USER".to_string());
         
         let mut wallet = Wallet {
             token: token.clone(),
             balance: "0".to_string(),
-            address: address.map(|s| s.to_string()),
-            position: position.map(|s| s.to_string()),
-            bundle: bundle.map(|s| s.to_string()),
-            batch_id: batch_id.map(|s| s.to_string()),
-            characters: characters.map(|s| s.to_string()),
+            address,
+            position,
+            bundle,
+            batch_id,
+            characters,
             key: None,
             pubkey: None,
             privkey: None,
@@ -125,7 +233,7 @@ impl Wallet {
             molecules: HashMap::new(),
         };
 
-        if let Some(secret) = secret {
+        if let Some(secret) = secret.as_deref() {
             // Set bundle from the secret if not provided
             if wallet.bundle.is_none() {
                 wallet.bundle = Some(generate_bundle_hash(secret));
@@ -149,7 +257,7 @@ impl Wallet {
 
             // Set default characters
             if wallet.characters.is_none() {
-                wallet.characters = Some("BASE64".to_string());
+                wallet.characters = Some(Characters::default().as_str().to_string());
             }
 
             // Initialize ML-KEM keys
@@ -159,6 +267,38 @@ impl Wallet {
         Ok(wallet)
     }
 
+    /// Create a new Wallet instance from positional arguments
+    ///
+    /// # Arguments
+    /// 
+    /// * `secret` - 2048-character biometric hash (optional for shadow wallets)
+    /// * `bundle` - 64-character hexadecimal user identifier (optional)
+    /// * `token` - Token slug (defaults to "USER")
+    /// * `address` - Hexadecimal public key (optional)
+    /// * `position` - Position string (optional)
+    /// * `batch_id` - Batch ID for transactions (optional)
+    /// * `characters` - Character encoding (optional)
+    #[deprecated(note = "use `Wallet::from_params(WalletParams)`")]
+    pub fn new(
+        secret: Option<&str>,
+        bundle: Option<&str>,
+        token: Option<&str>,
+        address: Option<&str>,
+        position: Option<&str>,
+        batch_id: Option<&str>,
+        characters: Option<&str>,
+    ) -> Result<Self> {
+        Self::from_params(WalletParams {
+            secret: secret.map(str::to_string),
+            bundle: bundle.map(str::to_string),
+            token: token.map(str::to_string),
+            address: address.map(str::to_string),
+            position: position.map(str::to_string),
+            batch_id: batch_id.map(str::to_string),
+            characters: characters.map(str::to_string),
+        })
+    }
+
     /// Create a new Wallet instance using the builder pattern
     ///
     /// # Arguments
@@ -194,15 +334,14 @@ impl Wallet {
             }
         }
 
-        Self::new(
-            secret,
-            final_bundle.as_deref(),
-            Some(token),
-            None,
-            final_position.as_deref(),
-            None,  // batch_id
-            characters,
-        )
+        Self::from_params(WalletParams {
+            secret: secret.map(str::to_string),
+            bundle: final_bundle,
+            token: Some(token.to_string()),
+            position: final_position,
+            characters: characters.map(str::to_string),
+            ..WalletParams::new()
+        })
     }
 
     /// Create wallet from GraphQL response data (matches JS implementation)
@@ -215,6 +354,27 @@ impl Wallet {
     ///
     /// Result containing the wallet instance
     pub fn from_response_data(data: serde_json::Value) -> Result<Self> {
+        Self::from_response_data_with(data, WalletHydration::Standard, None)
+    }
+
+    /// Create wallet from GraphQL response data with a chosen hydration level
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Response data from GraphQL query
+    /// * `hydration` - Which fields to parse (see `WalletHydration`)
+    /// * `secret` - Secret used by `WalletHydration::Full` to regenerate keys; ignored otherwise
+    ///
+    /// # Returns
+    ///
+    /// Result containing the wallet instance
+    pub fn from_response_data_with(
+        data: serde_json::Value,
+        hydration: WalletHydration,
+        secret: Option<&str>,
+    ) -> Result<Self> {
+        let minimal = hydration == WalletHydration::Minimal;
+
         // The Balance query selects `amount` (the validator's balance field); fall back to
         // `balance` for other shapes. Reading only `balance` (absent from the Balance selection
         // set) silently yielded 0 for every live balance query.
@@ -237,23 +397,29 @@ impl Wallet {
             data["tokenSlug"].as_str().unwrap_or("USER"),
             data["address"].as_str(),
             data["bundleHash"].as_str(), 
-            data["position"].as_str(),
-            data["characters"].as_str(),
-            data["batchId"].as_str(),
+            data["position"].as_str().filter(|_| !minimal),
+            data["characters"].as_str().filter(|_| !minimal),
+            data["batchId"].as_str().filter(|_| !minimal),
         );
 
-        let mut wallet = Self::new(
-            None, // No secret when creating from response data
-            bundle,
-            Some(token),
-            address,
-            position,
-            batch_id,
-            characters,
-        )?;
+        // No secret when creating from response data
+        let mut wallet = Self::from_params(WalletParams {
+            bundle: bundle.map(str::to_string),
+            token: Some(token.to_string()),
+            address: address.map(str::to_string),
+            position: position.map(str::to_string),
+            batch_id: batch_id.map(str::to_string),
+            characters: characters.map(str::to_string),
+            ..WalletParams::new()
+        })?;
 
         wallet.balance = balance;
 
+        // Balance-only polling does not need token units
+        if minimal {
+            return Ok(wallet);
+        }
+
         // Parse token units. The GraphQL Balance response returns each unit as an OBJECT
         // { id, name, metas } (metas is a String scalar / null on the wire), so parse the object
         // form; tolerate the array-of-arrays wire form [id, name, metas] too (the atom-meta shape).
@@ -294,6 +460,27 @@ impl Wallet {
             })
             .unwrap_or_default();
 
+        // Buffer wallets list their trade rates as { tokenSlug, amount } (amount is a String on the wire)
+        if let Some(rates) = data["tradeRates"].as_array() {
+            for rate in rates {
+                let slug = rate.get("tokenSlug").and_then(|v| v.as_str());
+                let amount = rate.get("amount").and_then(|v| v.as_str().and_then(|s| s.parse::<f64>().ok()).or_else(|| v.as_f64()));
+                if let (Some(slug), Some(amount)) = (slug, amount) {
+                    wallet.trade_rates.insert(slug.to_string(), amount);
+                }
+            }
+        }
+
+        // Regenerate signing and ML-KEM keys, but only for wallets the secret actually owns
+        if hydration == WalletHydration::Full {
+            if let (Some(secret), Some(position)) = (secret, wallet.position.clone()) {
+                if wallet.bundle.as_deref() == Some(generate_bundle_hash(secret).as_str()) {
+                    let token = wallet.token.clone();
+                    wallet.set_key_from_secret(secret, &token, &position)?;
+                }
+            }
+        }
+
         Ok(wallet)
     }
 
@@ -326,7 +513,7 @@ impl Wallet {
         
         // Set default characters
         if self.characters.is_none() {
-            self.characters = Some("BASE64".to_string());
+            self.characters = Some(Characters::default().as_str().to_string());
         }
         
         // Initialize ML-KEM keys
@@ -359,6 +546,45 @@ impl Wallet {
         maybe_bundle_hash.len() == 64 && maybe_bundle_hash.chars().all(|c| c.is_ascii_hexdigit())
     }
 
+    /// Alphabet of this wallet's public key material
+    ///
+    /// # Returns
+    ///
+    /// The parsed `characters` value, or the default (`BASE64`) when unset
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `characters` names an unsupported alphabet
+    pub fn charset(&self) -> Result<Characters> {
+        self.characters.as_deref()
+            .map(str::parse)
+            .unwrap_or(Ok(Characters::default()))
+    }
+
+    /// Re-encode this wallet's public key into another alphabet
+    ///
+    /// Used for wallets created by SDKs with a non-default alphabet. The current encoding
+    /// of `pubkey` is detected from its content, so wallets whose `characters` field is
+    /// missing or wrong are migrated too.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - Alphabet to migrate to
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecryptionKey` if the existing public key cannot be decoded as an ML-KEM768 key
+    pub fn migrate_characters(&mut self, target: Characters) -> Result<()> {
+        if let Some(ref pubkey) = self.pubkey {
+            let (_, bytes) = Characters::detect(pubkey, 1184)
+                .ok_or(KnishIOError::DecryptionKey)?;
+            self.pubkey = Some(target.encode(&bytes));
+        }
+
+        self.characters = Some(target.as_str().to_string());
+        Ok(())
+    }
+
     /// Generate a cryptographic key for wallet operations
     ///
     /// Delegates to the crypto module's implementation.
@@ -391,6 +617,39 @@ impl Wallet {
         crate::crypto::generate_address(key)
     }
 
+    /// Confirm that this wallet's address matches its position and secret
+    ///
+    /// Re-derives key → digest → address without signing anything, so audit tools can
+    /// check wallets reported by a node. Falls back to the wallet's own key when no
+    /// secret is given.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - Secret the wallet is expected to belong to (optional if the wallet holds its key)
+    ///
+    /// # Returns
+    ///
+    /// True if the derived address equals `self.address`
+    ///
+    /// # Errors
+    ///
+    /// Returns `WalletCredential` if the wallet has no address or position, and
+    /// `MissingSecret` if neither a secret nor a key is available
+    pub fn verify_address(&self, secret: Option<&str>) -> Result<bool> {
+        let address = self.address.as_deref().ok_or(KnishIOError::WalletCredential)?;
+
+        let key = match secret {
+            Some(secret) => {
+                let position = self.position.as_deref().ok_or(KnishIOError::WalletCredential)?;
+                generate_key(secret, &self.token, position)
+            }
+            None => self.key.clone().ok_or(KnishIOError::MissingSecret)?,
+        };
+
+        let derived = crate::crypto::derive_address(&crate::crypto::derive_public_digest(&key)?);
+        Ok(crate::crypto::constant_time_eq(&derived, &address.to_lowercase()))
+    }
+
     /// Generate a random position string
     ///
     /// Creates a random hexadecimal position.
@@ -630,8 +889,8 @@ impl Wallet {
             use libcrux_ml_kem::mlkem768;
             let keypair = mlkem768::generate_key_pair(seed);
             
-            // Serialize keys to match JavaScript base64 format
-            self.pubkey = Some(base64::engine::general_purpose::STANDARD.encode(keypair.pk().as_slice()));
+            // Serialize the public key in the wallet's alphabet (BASE64 unless migrated)
+            self.pubkey = Some(self.charset().unwrap_or_default().encode(keypair.pk().as_slice()));
             self.privkey = Some(keypair.sk().as_slice().to_vec());
         }
         
@@ -657,10 +916,10 @@ impl Wallet {
         let message_string = serde_json::to_string(message)?;
         let message_bytes = message_string.as_bytes();
         
-        // Deserialize recipient public key from base64
-        let recipient_pubkey_bytes = base64::engine::general_purpose::STANDARD
-            .decode(recipient_pubkey)
-            .map_err(|_| KnishIOError::DecryptionKey)?;
+        // Deserialize recipient public key (BASE64 from the JS SDK, BASE58/HEX from others)
+        let recipient_pubkey_bytes = Characters::detect(recipient_pubkey, 1184)
+            .map(|(_, bytes)| bytes)
+            .ok_or(KnishIOError::DecryptionKey)?;
             
         // Perform ML-KEM768 encapsulation to get shared secret
         use libcrux_ml_kem::mlkem768;
@@ -842,6 +1101,7 @@ pub struct EncryptedMessage {
 // Balance helper methods for precision-safe arithmetic
 impl Wallet {
     /// Parse balance as i128 for arithmetic (0 if unparseable)
+    #[deprecated(note = "truncates fractions and reads garbage as zero; use `whole_balance` or `balance_info`")]
     pub fn balance_as_i128(&self) -> i128 {
         self.balance.parse::<i128>().unwrap_or_else(|_| {
             // Fallback: try parsing as f64 and converting (for "100.0" style strings)
@@ -849,6 +1109,36 @@ impl Wallet {
         })
     }
 
+    /// Balance and token unit count read together
+    ///
+    /// Compare amounts through this rather than through a float of `balance`. An
+    /// unparseable balance reads as zero; `try_balance_info` refuses it.
+    pub fn balance_info(&self) -> Balance {
+        let unit_count = self.token_units.len();
+        self.try_balance_info().unwrap_or(Balance { unit_count, ..Balance::default() })
+    }
+
+    /// `balance_info`, failing on a balance that is not a decimal
+    ///
+    /// # Errors
+    ///
+    /// `InvalidAmount` when `balance` is not a decimal
+    pub fn try_balance_info(&self) -> Result<Balance> {
+        Balance::parse(&self.balance, self.token_units.len())
+            .ok_or_else(|| KnishIOError::InvalidAmount(format!("wallet balance {:?} is not a decimal", self.balance)))
+    }
+
+    /// Balance as a whole number of tokens, the amount atoms move
+    ///
+    /// # Errors
+    ///
+    /// `InvalidAmount` when `balance` is not a decimal or has a fraction
+    pub fn whole_balance(&self) -> Result<i128> {
+        self.try_balance_info()?
+            .whole_exact()
+            .ok_or_else(|| KnishIOError::InvalidAmount(format!("wallet balance {} is not a whole number", self.balance)))
+    }
+
     /// Set balance from i128
     pub fn set_balance_i128(&mut self, val: i128) {
         self.balance = val.to_string();
@@ -903,15 +1193,7 @@ mod tests {
 
     #[test]
     fn test_shadow_wallet() {
-        let wallet = Wallet::new(
-            None,
-            Some("test-bundle"),
-            Some("TEST"),
-            None,
-            None,
-            None,
-            None,
-        ).unwrap();
+        let wallet = Wallet::from_params(WalletParams::new().bundle("test-bundle").token("TEST")).unwrap();
         
         assert!(wallet.is_shadow());
         assert_eq!(wallet.token, "TEST");
@@ -972,6 +1254,89 @@ mod tests {
         assert_eq!(wallet.token_units[0].id, "unit1");
     }
 
+    #[test]
+    fn test_from_response_data_hydration_levels() {
+        let secret = "hydration-secret-12345";
+        let bundle = generate_bundle_hash(secret);
+        let data = serde_json::json!({
+            "amount": "42",
+            "tokenSlug": "TEST",
+            "bundleHash": bundle,
+            "position": "a".repeat(64),
+            "batchId": "test-batch",
+            "tokenUnits": [{ "id": "unit1", "name": "Unit 1" }]
+        });
+
+        let minimal = Wallet::from_response_data_with(data.clone(), WalletHydration::Minimal, None).unwrap();
+        assert_eq!(minimal.balance, "42");
+        assert!(minimal.position.is_none());
+        assert!(minimal.batch_id.is_none());
+        assert!(minimal.token_units.is_empty());
+
+        let standard = Wallet::from_response_data_with(data.clone(), WalletHydration::Standard, Some(secret)).unwrap();
+        assert_eq!(standard.token_units.len(), 1);
+        assert!(standard.key.is_none());
+
+        let full = Wallet::from_response_data_with(data.clone(), WalletHydration::Full, Some(secret)).unwrap();
+        assert!(full.key.is_some());
+        assert!(full.pubkey.is_some());
+
+        // Keys are never derived for a bundle the secret does not own
+        let foreign = Wallet::from_response_data_with(data, WalletHydration::Full, Some("other-secret")).unwrap();
+        assert!(foreign.key.is_none());
+    }
+
+    #[test]
+    fn test_verify_address() {
+        let secret = "verify-address-secret";
+        let wallet = Wallet::create(Some(secret), None, "TEST", None, None).unwrap();
+
+        assert!(wallet.verify_address(Some(secret)).unwrap());
+        assert!(wallet.verify_address(None).unwrap());
+        assert!(!wallet.verify_address(Some("another-secret")).unwrap());
+
+        let mut tampered = wallet.clone();
+        tampered.position = Some("b".repeat(64));
+        assert!(!tampered.verify_address(Some(secret)).unwrap());
+
+        let mut keyless = wallet.clone();
+        keyless.key = None;
+        assert!(keyless.verify_address(None).is_err());
+
+        keyless.address = None;
+        assert!(keyless.verify_address(Some(secret)).is_err());
+    }
+
+    #[test]
+    fn test_characters_respected_and_migrated() {
+        let secret = "characters-secret-12345";
+        let base64 = Wallet::create(Some(secret), None, "TEST", None, None).unwrap();
+        assert_eq!(base64.charset().unwrap(), Characters::Base64);
+
+        let position = base64.position.clone().unwrap();
+        let hex = Wallet::from_params(
+            WalletParams::new()
+                .secret(secret)
+                .token("TEST")
+                .position(&position)
+                .characters("HEX"),
+        ).unwrap();
+        assert_eq!(hex.charset().unwrap(), Characters::Hex);
+        assert_eq!(
+            Characters::Hex.decode(hex.pubkey.as_ref().unwrap()).unwrap(),
+            Characters::Base64.decode(base64.pubkey.as_ref().unwrap()).unwrap()
+        );
+
+        let mut migrated = hex.clone();
+        migrated.migrate_characters(Characters::Base64).unwrap();
+        assert_eq!(migrated.pubkey, base64.pubkey);
+        assert_eq!(migrated.characters.as_deref(), Some("BASE64"));
+
+        let mut unknown = base64.clone();
+        unknown.characters = Some("EBCDIC".to_string());
+        assert!(unknown.charset().is_err());
+    }
+
     #[test]
     fn test_set_key_from_secret() {
         let mut wallet = Wallet::default();
@@ -1098,10 +1463,7 @@ mod tests {
     #[test]
     fn test_mlkem_shadow_wallet_no_keys() {
         // Shadow wallets (no secret) should NOT have ML-KEM keys
-        let wallet = Wallet::new(
-            None, Some("test-bundle"), Some("TEST"),
-            None, None, None, None,
-        ).unwrap();
+        let wallet = Wallet::from_params(WalletParams::new().bundle("test-bundle").token("TEST")).unwrap();
 
         assert!(wallet.pubkey.is_none(), "Shadow wallet should not have ML-KEM pubkey");
         assert!(wallet.privkey.is_none(), "Shadow wallet should not have ML-KEM privkey");
@@ -1142,7 +1504,7 @@ mod tests {
         // 9007199254740993 > 2^53 — f64 would round this to 9007199254740992
         let mut wallet = Wallet::default();
         wallet.balance = "9007199254740993".to_string();
-        assert_eq!(wallet.balance_as_i128(), 9007199254740993_i128);
+        assert_eq!(wallet.whole_balance().unwrap(), 9007199254740993_i128);
         assert_eq!(wallet.balance, "9007199254740993");
     }
 
@@ -1150,23 +1512,78 @@ mod tests {
     fn test_balance_precision_very_large_values() {
         let mut wallet = Wallet::default();
         wallet.balance = "999999999999999999999".to_string(); // ~10^21, far beyond f64 integer range
-        assert_eq!(wallet.balance_as_i128(), 999999999999999999999_i128);
+        assert_eq!(wallet.whole_balance().unwrap(), 999999999999999999999_i128);
     }
 
     #[test]
     fn test_balance_helpers() {
         let mut wallet = Wallet::default();
         assert_eq!(wallet.balance, "0");
-        assert_eq!(wallet.balance_as_i128(), 0);
+        assert_eq!(wallet.whole_balance().unwrap(), 0);
 
         wallet.set_balance_i128(42);
         assert_eq!(wallet.balance, "42");
-        assert_eq!(wallet.balance_as_i128(), 42);
+        assert_eq!(wallet.whole_balance().unwrap(), 42);
+
+        wallet.balance = "100.0".to_string();
+        assert_eq!(wallet.whole_balance().unwrap(), 100);
+        for invalid in ["100.5", "garbage", ""] {
+            wallet.balance = invalid.to_string();
+            assert!(matches!(wallet.whole_balance(), Err(KnishIOError::InvalidAmount(_))), "{:?}", invalid);
+        }
 
         wallet.set_balance_f64(1000.0);
         assert_eq!(wallet.balance, "1000");
     }
 
+    #[test]
+    fn test_balance_info() {
+        let mut wallet = Wallet::default();
+        wallet.balance = "12.50".to_string();
+        let balance = wallet.balance_info();
+        assert_eq!((balance.amount, balance.decimals_applied, balance.whole()), (1250, 2, 12));
+        assert_eq!(balance.to_string(), "12.50");
+        assert!(balance.covers(12.5) && !balance.covers(12.51) && !balance.covers(f64::NAN));
+
+        // Beyond f64's integer precision the comparison stays exact
+        wallet.balance = "9007199254740993".to_string();
+        assert!(!wallet.balance_info().covers(9007199254740994.0));
+        assert_eq!(wallet.balance_info().whole(), 9007199254740993);
+
+        wallet.balance = "2".to_string();
+        wallet.token_units = vec![TokenUnit::new("u1".to_string(), "u1".to_string(), None), TokenUnit::new("u2".to_string(), "u2".to_string(), None)];
+        assert!(wallet.balance_info().is_reconciled());
+        wallet.token_units.pop();
+        assert!(!wallet.balance_info().is_reconciled());
+
+        wallet.balance = "-0.5".to_string();
+        assert_eq!(wallet.balance_info().to_string(), "-0.5");
+        wallet.balance = "garbage".to_string();
+        assert_eq!(wallet.balance_info(), Balance { amount: 0, unit_count: 1, decimals_applied: 0 });
+
+        let (a, b) = (Balance::parse("1.25", 1).unwrap(), Balance::parse("2.5", 2).unwrap());
+        assert_eq!(a.checked_add(&b).unwrap(), Balance { amount: 375, unit_count: 3, decimals_applied: 2 });
+        assert_eq!(a.checked_sub(&b).unwrap().to_string(), "-1.25");
+        assert_eq!(b.cmp_amount(&Balance::parse("2.50", 0).unwrap()), std::cmp::Ordering::Equal);
+        assert_eq!(Balance { amount: i128::MAX, ..a }.checked_add(&b), None);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_params_match_positional_constructor() {
+        let secret = crate::crypto::generate_secret("wallet-params");
+        let position = "c".repeat(64);
+        let params = WalletParams::new().secret(&secret).token("TEST").position(&position).batch_id("b-1");
+        assert!(format!("{:?}", params).contains("[REDACTED]"));
+
+        let wallet = Wallet::from_params(params).unwrap();
+        let positional = Wallet::new(Some(&secret), None, Some("TEST"), None, Some(&position), Some("b-1"), None).unwrap();
+        assert_eq!(wallet.address, positional.address);
+        assert_eq!(wallet.bundle, positional.bundle);
+        assert_eq!(wallet.batch_id.as_deref(), Some("b-1"));
+        assert_eq!(Wallet::from_params(WalletParams::new().secret(&secret)).unwrap().token, "USER");
+    }
+
     #[test]
     fn test_balance_serde_string_format() {
         // Deserialize from string format (server sends this)
@@ -0,0 +1,285 @@
+//! Ownership proofs
+//!
+//! `Wallet::prove_ownership` answers a login or third-party challenge with a WOTS+
+//! signature, off-ledger and without using up a transactional position.
+//!
+//! WOTS+ keys are one-time, so a bundle cannot answer every challenge with the same key.
+//! Instead a `ProofTree` derives `2^height` leaf keys from the secret and commits to their
+//! addresses with a Merkle root. Each challenge is signed with an unused leaf key, and the
+//! proof carries the leaf's authentication path up to the root.
+//!
+//! A signature alone shows control of some key, not of the bundle: anyone can derive keys
+//! from their own secret and label them with any bundle hash. A proof is only accepted
+//! under a root the verifier already trusts for the bundle. Proof trees stay local: nothing
+//! is written to the ledger, so the application hands the root to the verifier once over a
+//! channel that already authenticates the bundle (for instance at enrollment) and the
+//! verifier keeps it. The caller keeps track of used leaves; signing two challenges with
+//! one leaf leaks its key.
+
+use crate::crypto::{
+    generate_address, generate_bundle_hash, generate_key, generate_ots_signature, hex_to_base17,
+    shake256, verify_ots_signature,
+};
+use crate::error::{KnishIOError, Result};
+use crate::wallet::Wallet;
+use serde::{Deserialize, Serialize};
+
+/// Token slug under which proof keys are derived (a derivation label, not a ledger token)
+pub const PROOF_TOKEN: &str = "PROOF";
+
+/// Default proof tree height (256 challenges per tree)
+pub const PROOF_TREE_HEIGHT: u32 = 8;
+
+/// Largest supported proof tree height
+pub const MAX_PROOF_TREE_HEIGHT: u32 = 16;
+
+/// Leaf keys of a bundle and the Merkle tree over their addresses
+#[derive(Debug, Clone)]
+pub struct ProofTree {
+    bundle: String,
+    /// `levels[0]` holds the leaf addresses, the last level the root
+    levels: Vec<Vec<String>>,
+}
+
+impl ProofTree {
+    /// Derive the `2^height` leaf keys of `secret`'s bundle and hash them up to the root
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigurationError` if `height` exceeds `MAX_PROOF_TREE_HEIGHT`
+    pub fn new(secret: &str, height: u32) -> Result<Self> {
+        if height > MAX_PROOF_TREE_HEIGHT {
+            return Err(KnishIOError::ConfigurationError(format!(
+                "Proof tree height {} exceeds {}", height, MAX_PROOF_TREE_HEIGHT
+            )));
+        }
+        let bundle = generate_bundle_hash(secret);
+        let leaves = (0..1u32 << height)
+            .map(|leaf| generate_address(&Self::leaf_key(secret, &bundle, leaf)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut levels = vec![leaves];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let parents = level.chunks(2).map(|pair| node_hash(&pair[0], &pair[1])).collect();
+            levels.push(parents);
+        }
+        Ok(Self { bundle, levels })
+    }
+
+    /// Bundle the tree belongs to
+    pub fn bundle(&self) -> &str {
+        &self.bundle
+    }
+
+    /// Tree height
+    pub fn height(&self) -> u32 {
+        (self.levels.len() - 1) as u32
+    }
+
+    /// Number of challenges the tree can answer
+    pub fn capacity(&self) -> u32 {
+        self.levels[0].len() as u32
+    }
+
+    /// Merkle root, the value a verifier trusts for the bundle
+    pub fn root(&self) -> &str {
+        &self.levels[self.levels.len() - 1][0]
+    }
+
+    /// Sign `challenge` with the key of `leaf`
+    ///
+    /// # Errors
+    ///
+    /// `WalletCredential` if `secret` does not belong to the tree's bundle, `ConfigurationError`
+    /// if `leaf` is outside the tree
+    pub fn prove(&self, secret: &str, challenge: &str, leaf: u32) -> Result<OwnershipProof> {
+        if generate_bundle_hash(secret) != self.bundle {
+            return Err(KnishIOError::WalletCredential);
+        }
+        if leaf >= self.capacity() {
+            return Err(KnishIOError::ConfigurationError(format!(
+                "Proof leaf {} is outside a tree of {} leaves", leaf, self.capacity()
+            )));
+        }
+
+        let auth_path = self.levels[..self.levels.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(depth, level)| level[((leaf >> depth) ^ 1) as usize].clone())
+            .collect();
+        let key = Self::leaf_key(secret, &self.bundle, leaf);
+        let hash = OwnershipProof::message_hash(&self.bundle, self.root(), leaf, challenge)?;
+        Ok(OwnershipProof {
+            bundle: self.bundle.clone(),
+            leaf,
+            address: self.levels[0][leaf as usize].clone(),
+            auth_path,
+            signature: generate_ots_signature(&key, &hash)?,
+        })
+    }
+
+    fn leaf_key(secret: &str, bundle: &str, leaf: u32) -> String {
+        let position = shake256(&format!("KnishIO ownership proof\nbundle:{}\nleaf:{}", bundle, leaf), 256);
+        generate_key(secret, PROOF_TOKEN, &position)
+    }
+}
+
+/// Parent of two tree nodes
+fn node_hash(left: &str, right: &str) -> String {
+    shake256(&format!("KnishIO ownership proof\nnode:{}{}", left, right), 256)
+}
+
+/// Signed answer to an ownership challenge
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnershipProof {
+    /// Bundle hash the proof is made for
+    pub bundle: String,
+    /// Index of the leaf key that signed
+    pub leaf: u32,
+    /// Address of the leaf key
+    pub address: String,
+    /// Sibling nodes from the leaf up to the root
+    pub auth_path: Vec<String>,
+    /// 16 WOTS+ signature fragments over `message_hash`
+    pub signature: Vec<String>,
+}
+
+impl OwnershipProof {
+    /// Merkle root the leaf address and authentication path lead to
+    pub fn root(&self) -> Option<String> {
+        if self.auth_path.len() > MAX_PROOF_TREE_HEIGHT as usize || u64::from(self.leaf) >> self.auth_path.len() != 0 {
+            return None;
+        }
+        let root = self.auth_path.iter().enumerate().fold(self.address.clone(), |node, (depth, sibling)| {
+            if (self.leaf >> depth) & 1 == 0 {
+                node_hash(&node, sibling)
+            } else {
+                node_hash(sibling, &node)
+            }
+        });
+        Some(root)
+    }
+
+    /// Whether the signature answers `challenge` for `bundle` under `root`
+    ///
+    /// Says nothing about who holds the tree; see `Wallet::verify_ownership_proof`.
+    pub(crate) fn signature_matches(&self, bundle: &str, root: &str, challenge: &str) -> bool {
+        if self.bundle != bundle {
+            return false;
+        }
+        match Self::message_hash(bundle, root, self.leaf, challenge) {
+            Ok(hash) => verify_ots_signature(&self.signature, &hash, &self.address),
+            Err(_) => false,
+        }
+    }
+
+    /// Base17 hash signed by the leaf key
+    pub fn message_hash(bundle: &str, root: &str, leaf: u32, challenge: &str) -> Result<String> {
+        hex_to_base17(&shake256(
+            &format!("KnishIO ownership proof\nbundle:{}\nroot:{}\nleaf:{}\nchallenge:{}", bundle, root, leaf, challenge),
+            256,
+        ))
+    }
+}
+
+impl Wallet {
+    /// Sign `challenge` with leaf `leaf` of this wallet's bundle's default-height proof tree
+    ///
+    /// Builds the tree on every call; keep a `ProofTree` around when answering several
+    /// challenges. The proof only verifies against the tree's root, which the verifier must
+    /// already trust for the bundle. Never sign twice with one leaf.
+    ///
+    /// # Errors
+    ///
+    /// Returns `WalletCredential` if `secret` does not belong to this wallet's bundle
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use knishio_client::{ProofTree, Wallet};
+    /// use knishio_client::wallet::proof::PROOF_TREE_HEIGHT;
+    ///
+    /// let secret = "a".repeat(2048);
+    /// let wallet = Wallet::create(Some(&secret), None, "USER", None, None).unwrap();
+    /// let bundle = wallet.bundle.clone().unwrap();
+    ///
+    /// let proof = wallet.prove_ownership(&secret, "login-nonce-42", 0).unwrap();
+    /// // The root the verifier was given for the bundle at enrollment
+    /// let trusted = vec![ProofTree::new(&secret, PROOF_TREE_HEIGHT).unwrap().root().to_string()];
+    /// assert!(Wallet::verify_ownership_proof(&proof, &bundle, "login-nonce-42", &trusted));
+    /// assert!(!Wallet::verify_ownership_proof(&proof, &bundle, "another-nonce", &trusted));
+    /// assert!(!Wallet::verify_ownership_proof(&proof, &bundle, "login-nonce-42", &[]));
+    /// ```
+    pub fn prove_ownership(&self, secret: &str, challenge: &str, leaf: u32) -> Result<OwnershipProof> {
+        if self.bundle.as_ref().is_some_and(|own| *own != generate_bundle_hash(secret)) {
+            return Err(KnishIOError::WalletCredential);
+        }
+        ProofTree::new(secret, PROOF_TREE_HEIGHT)?.prove(secret, challenge, leaf)
+    }
+
+    /// Check that `proof` answers `challenge` for `bundle` with one of the bundle's trees
+    ///
+    /// `trusted_roots` are the tree roots the verifier holds for `bundle`; a proof leading
+    /// to any other root is rejected.
+    pub fn verify_ownership_proof(proof: &OwnershipProof, bundle: &str, challenge: &str, trusted_roots: &[String]) -> bool {
+        proof.root().is_some_and(|root| trusted_roots.contains(&root) && proof.signature_matches(bundle, &root, challenge))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ownership_proof() {
+        let secret = "b".repeat(2048);
+        let tree = ProofTree::new(&secret, 2).unwrap();
+        let bundle = tree.bundle().to_string();
+        let trusted = vec![tree.root().to_string()];
+        assert_eq!((tree.height(), tree.capacity()), (2, 4));
+
+        for leaf in 0..tree.capacity() {
+            let proof = tree.prove(&secret, &format!("challenge-{}", leaf), leaf).unwrap();
+            assert_eq!(proof.root().as_deref(), Some(tree.root()));
+            assert!(Wallet::verify_ownership_proof(&proof, &bundle, &format!("challenge-{}", leaf), &trusted));
+        }
+
+        // Wrong bundle, challenge, leaf index or a tampered signature
+        let proof = tree.prove(&secret, "challenge-1", 1).unwrap();
+        assert!(!Wallet::verify_ownership_proof(&proof, &generate_bundle_hash("other"), "challenge-1", &trusted));
+        assert!(!Wallet::verify_ownership_proof(&proof, &bundle, "challenge-2", &trusted));
+        let mut moved = proof.clone();
+        moved.leaf = 0;
+        assert!(!Wallet::verify_ownership_proof(&moved, &bundle, "challenge-1", &trusted));
+        moved.leaf = 5;
+        assert_eq!(moved.root(), None);
+        let mut tampered = proof.clone();
+        tampered.signature[0] = "0".repeat(128);
+        assert!(!Wallet::verify_ownership_proof(&tampered, &bundle, "challenge-1", &trusted));
+
+        assert!(matches!(tree.prove("other-secret", "challenge-1", 1), Err(KnishIOError::WalletCredential)));
+        assert!(matches!(tree.prove(&secret, "challenge-1", 4), Err(KnishIOError::ConfigurationError(_))));
+    }
+
+    #[test]
+    fn test_foreign_key_cannot_prove_ownership() {
+        let victim_secret = "v".repeat(2048);
+        let victim = generate_bundle_hash(&victim_secret);
+        let trusted = vec![ProofTree::new(&victim_secret, 1).unwrap().root().to_string()];
+
+        // A tree built from another secret, relabelled for the victim's bundle
+        let forger = ProofTree::new(&"m".repeat(2048), 1).unwrap();
+        let mut forged = forger.prove(&"m".repeat(2048), "challenge-1", 0).unwrap();
+        forged.bundle = victim.clone();
+        forged.signature = generate_ots_signature(
+            &ProofTree::leaf_key(&"m".repeat(2048), forger.bundle(), 0),
+            &OwnershipProof::message_hash(&victim, forger.root(), 0, "challenge-1").unwrap(),
+        ).unwrap();
+
+        // The forgery carries a valid signature, just not under one of the bundle's roots
+        assert!(forged.signature_matches(&victim, forger.root(), "challenge-1"));
+        assert!(!Wallet::verify_ownership_proof(&forged, &victim, "challenge-1", &trusted));
+        assert!(!Wallet::verify_ownership_proof(&forged, &victim, "challenge-1", &[]));
+    }
+}
@@ -0,0 +1,158 @@
+//! Watch-only wallets
+//!
+//! A `WatchWallet` identifies a wallet by its bundle hash (and optionally its address)
+//! without holding a secret, signing key or ML-KEM private key. Monitoring services can
+//! use it with the client's query and subscription methods, but because it never carries
+//! key material and cannot be converted into a `Wallet`, it can never be used as the
+//! source of a signed molecule.
+
+use crate::error::{KnishIOError, Result};
+use crate::types::TokenUnit;
+use crate::wallet::{Balance, Wallet};
+use serde::{Deserialize, Serialize};
+
+/// Read-only view of a wallet, constructed from public identifiers only
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchWallet {
+    /// Token slug this wallet is watched for
+    pub token: String,
+
+    /// Bundle hash - 64-character hexadecimal user identifier
+    pub bundle: String,
+
+    /// Wallet address, when watching a single wallet rather than the whole bundle
+    pub address: Option<String>,
+
+    /// Position string reported by the ledger (informational only)
+    pub position: Option<String>,
+
+    /// Batch ID reported by the ledger
+    pub batch_id: Option<String>,
+
+    /// Last known balance (stored as String for arbitrary-precision integers)
+    pub balance: String,
+
+    /// Last known token units
+    pub token_units: Vec<TokenUnit>,
+}
+
+impl WatchWallet {
+    /// Create a watch-only wallet for a bundle
+    ///
+    /// # Arguments
+    ///
+    /// * `bundle` - 64-character hexadecimal bundle hash
+    /// * `token` - Token slug to watch
+    ///
+    /// # Errors
+    ///
+    /// Returns `WalletCredential` if `bundle` is not a valid bundle hash
+    pub fn new(bundle: &str, token: &str) -> Result<Self> {
+        if !Wallet::is_bundle_hash(bundle) {
+            return Err(KnishIOError::WalletCredential);
+        }
+
+        Ok(WatchWallet {
+            token: token.to_string(),
+            bundle: bundle.to_string(),
+            address: None,
+            position: None,
+            batch_id: None,
+            balance: "0".to_string(),
+            token_units: Vec::new(),
+        })
+    }
+
+    /// Narrow the watch to a single wallet address
+    pub fn with_address(mut self, address: impl Into<String>) -> Self {
+        self.address = Some(address.into());
+        self
+    }
+
+    /// Build a watch-only wallet from GraphQL response data
+    ///
+    /// Accepts the same shapes as `Wallet::from_response_data`.
+    pub fn from_response_data(data: serde_json::Value) -> Result<Self> {
+        let wallet = Wallet::from_response_data(data)?;
+        Self::try_from(&wallet)
+    }
+
+    /// Balance and token unit count read together, see `Wallet::balance_info`
+    pub fn balance_info(&self) -> Balance {
+        let unit_count = self.token_units.len();
+        Balance::parse(&self.balance, unit_count).unwrap_or(Balance { unit_count, ..Balance::default() })
+    }
+}
+
+/// Strip a wallet down to its public identifiers
+///
+/// The key, ML-KEM private key and any secret-derived state are dropped. Fails with
+/// `MissingBundle` for wallets that have no bundle hash.
+impl TryFrom<&Wallet> for WatchWallet {
+    type Error = KnishIOError;
+
+    fn try_from(wallet: &Wallet) -> Result<Self> {
+        let bundle = wallet.bundle.clone().ok_or(KnishIOError::MissingBundle)?;
+
+        Ok(WatchWallet {
+            token: wallet.token.clone(),
+            bundle,
+            address: wallet.address.clone(),
+            position: wallet.position.clone(),
+            batch_id: wallet.batch_id.clone(),
+            balance: wallet.balance.clone(),
+            token_units: wallet.token_units.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUNDLE: &str = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn test_new_requires_bundle_hash() {
+        assert!(WatchWallet::new("not-a-bundle", "TEST").is_err());
+
+        let watch = WatchWallet::new(BUNDLE, "TEST").unwrap();
+        assert_eq!(watch.bundle, BUNDLE);
+        assert_eq!(watch.token, "TEST");
+        assert_eq!(watch.balance, "0");
+        assert!(watch.address.is_none());
+    }
+
+    #[test]
+    fn test_from_wallet_drops_key_material() {
+        let wallet = Wallet::create(Some("watch-secret-12345"), None, "TEST", None, None).unwrap();
+        let watch = WatchWallet::try_from(&wallet).unwrap();
+
+        assert_eq!(Some(watch.bundle.clone()), wallet.bundle);
+        assert_eq!(watch.address, wallet.address);
+
+        // Nothing secret-derived survives serialization
+        let json = serde_json::to_value(&watch).unwrap();
+        assert!(json.get("key").is_none());
+        assert!(json.get("privkey").is_none());
+    }
+
+    #[test]
+    fn test_from_response_data() {
+        let data = serde_json::json!({
+            "amount": "250",
+            "tokenSlug": "TEST",
+            "address": "test-address",
+            "bundleHash": BUNDLE,
+            "position": "test-position",
+        });
+
+        let watch = WatchWallet::from_response_data(data).unwrap();
+        assert_eq!(watch.balance_info().whole_exact(), Some(250));
+        assert_eq!(watch.address.as_deref(), Some("test-address"));
+
+        let missing_bundle = serde_json::json!({ "amount": "1", "tokenSlug": "TEST" });
+        assert!(WatchWallet::from_response_data(missing_bundle).is_err());
+    }
+}
@@ -186,6 +186,7 @@ fn test_generate_secret_vectors() {
 /// JS/PHP/TS reference. (Pre-fix Rust debited only -amount → source = -amount and
 /// Σ = balance-amount ≠ 0, which the validator's b_isotope check rejects.)
 #[test]
+#[allow(deprecated)] // pins the positional constructor the vectors were written against
 fn test_buffer_deposit_conservation_vectors() {
     use knishio_client::{Molecule, Wallet};
     use std::collections::HashMap;
@@ -252,6 +253,7 @@ fn test_buffer_deposit_conservation_vectors() {
 /// test_buffer_deposit_conservation_vectors. Atom order: source B (-balance), recipient
 /// V (+amount), remainder B (+(balance-amount)).
 #[test]
+#[allow(deprecated)] // pins the positional constructor the vectors were written against
 fn test_buffer_withdraw_conservation_vectors() {
     use knishio_client::{Molecule, Wallet};
     use std::collections::HashMap;
@@ -315,6 +317,64 @@ fn test_buffer_withdraw_conservation_vectors() {
     }
 }
 
+/// The buffer vectors built through `MoleculeParams` give the same atoms as through the
+/// deprecated positional `Molecule::with_params` above.
+#[test]
+#[allow(deprecated)]
+fn test_buffer_conservation_vectors_with_molecule_params() {
+    use knishio_client::{Molecule, MoleculeParams, Wallet};
+    use std::collections::HashMap;
+
+    let vectors = load_patent_vectors();
+    let secret = "abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890";
+    let bundle = generate_bundle_hash(secret);
+    let position = "1a2b3c4d5e6f1a2b3c4d5e6f1a2b3c4d5e6f1a2b3c4d5e6f1a2b3c4d5e6f1a2b";
+
+    let source = |balance: &str| {
+        let mut source = Wallet::create(Some(secret), None, "BUFTOK", Some(position), None)
+            .expect("create buffer source wallet");
+        source.balance = balance.to_string();
+        source
+    };
+    let positional = |source: Wallet| Molecule::with_params(
+        Some(secret.to_string()),
+        Some(bundle.clone()),
+        Some(source),
+        None,
+        Some("buftest".to_string()),
+        None,
+    );
+    let from_params = |source: Wallet| Molecule::from_params(
+        MoleculeParams::new()
+            .secret(secret)
+            .bundle(bundle.clone())
+            .source_wallet(source)
+            .cell_slug("buftest"),
+    );
+    // Isotope and value of each atom; remainder positions are random
+    let wire = |mol: &Molecule| mol.atoms.iter()
+        .map(|atom| (format!("{:?}", atom.isotope), atom.value.clone()))
+        .collect::<Vec<_>>();
+
+    for test in &vectors.vectors.buffer_deposit_conservation.tests {
+        let mut expected = positional(source(&test.source_balance.to_string()));
+        let mut mol = from_params(source(&test.source_balance.to_string()));
+        expected.init_deposit_buffer(test.amount, HashMap::new()).expect("init_deposit_buffer");
+        mol.init_deposit_buffer(test.amount, HashMap::new()).expect("init_deposit_buffer");
+        assert_eq!(wire(&mol), wire(&expected), "[{}] deposit atoms", test.name);
+        assert_eq!(mol.cell_slug, expected.cell_slug);
+    }
+
+    for test in &vectors.vectors.buffer_withdraw_conservation.tests {
+        let recipients: HashMap<String, f64> = HashMap::from([(bundle.clone(), test.amount)]);
+        let mut expected = positional(source(&test.source_balance.to_string()));
+        let mut mol = from_params(source(&test.source_balance.to_string()));
+        expected.init_withdraw_buffer(recipients.clone(), None).expect("init_withdraw_buffer");
+        mol.init_withdraw_buffer(recipients, None).expect("init_withdraw_buffer");
+        assert_eq!(wire(&mol), wire(&expected), "[{}] withdraw atoms", test.name);
+    }
+}
+
 /// Patent Claims 5, 12-14: ContinuID identity relay chain
 #[test]
 fn test_continuid_chain_vectors() {
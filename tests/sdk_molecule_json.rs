@@ -0,0 +1,50 @@
+//! Signed molecule JSON written by SDKs, read through `Molecule::from_json`
+//!
+//! Every file in `tests/fixtures/molecule_json` is checked; see the README there.
+
+use knishio_client::types::{MoleculeFromJsonOptions, MoleculeJsonOptions};
+use knishio_client::{Atom, Molecule};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+fn fixtures() -> Vec<(std::path::PathBuf, Value)> {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/molecule_json");
+    let mut fixtures = Vec::new();
+    for entry in fs::read_dir(&directory).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|extension| extension.to_str()) == Some("json") {
+            let fixture = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+            fixtures.push((path, fixture));
+        }
+    }
+    fixtures
+}
+
+#[test]
+fn test_sdk_molecule_fixtures() {
+    let mut checked = 0;
+
+    for (path, fixture) in fixtures() {
+        let options = MoleculeFromJsonOptions { include_validation_context: false, ..Default::default() };
+        let molecule = Molecule::from_json(&fixture["molecule"], options)
+            .unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+
+        let expected = &fixture["expected"];
+        assert_eq!(molecule.bundle.as_deref(), expected["bundle"].as_str(), "{}", path.display());
+        assert_eq!(
+            Atom::hash_atoms(&molecule.atoms, "base17").unwrap(),
+            expected["molecularHash"].as_str().unwrap(),
+            "{}",
+            path.display()
+        );
+        assert!(molecule.check(None).unwrap(), "{}", path.display());
+
+        // Written back, the bundle is what the SDK wrote
+        let written = molecule.to_json(MoleculeJsonOptions { include_validation_context: false, ..Default::default() }).unwrap();
+        assert_eq!(written["bundle"], fixture["molecule"]["bundle"], "{}", path.display());
+        checked += 1;
+    }
+
+    assert!(checked > 0, "no fixtures in tests/fixtures/molecule_json");
+}